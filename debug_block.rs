@@ -14,6 +14,8 @@ fn create_test_field_entry() -> FieldDirectoryEntry {
         segment_uncompressed_len: 1000,
         segment_compressed_len: 500,
         segment_offset: 0,
+        stats: None,
+        quantiles: None,
     }
 }
 
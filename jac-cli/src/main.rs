@@ -5,23 +5,45 @@
 //! - unpack: decompress .jac → JSON/NDJSON/CSV
 //! - ls: list blocks, fields, and optional statistics
 //! - cat: stream values for a field across blocks
+//! - agg: group-by aggregation over columns
+//! - stat: most frequent values of a field
+//! - index: add an index footer to a file written without one
+//! - query: SQL-subset queries (feature `query`)
+//! - tui: interactive archive explorer (feature `tui`)
+//! - serve: read-only HTTP endpoints over a directory (feature `serve`)
 
 use clap::{Parser, Subcommand, ValueEnum};
 use indicatif::{ProgressBar, ProgressStyle};
 use jac_io::{
-    execute_compress, execute_decompress, parallel::ParallelConfig, BlockHandle, Codec,
-    CompressOptions, CompressRequest, CompressSummary, ContainerFormat, DecompressFormat,
-    DecompressOptions, DecompressOpts, DecompressRequest, DecompressSummary, InputSource, JacInput,
-    JacReader, Limits, OutputSink,
+    check_vectors, detect_input_format, execute_aggregate, execute_build_index, execute_compress,
+    execute_decompress, execute_retention, generate_vectors, suggest_wrappers,
+    vectors::MANIFEST_FILE, Aggregate, AggregateRequest, AutoLevelPolicy, BlockHandle, BlockRange,
+    Codec, CompressOptions, CompressRequest, CompressSummary, CompressionProfile, ContainerFormat,
+    DecompressFormat, DecompressOptions, DecompressOpts, DecompressRequest, DecompressSummary,
+    DedupeConfig, DictionaryMode, DuplicateKeyPolicy, ErrorCategory, FieldCatalogEntry,
+    FieldCatalogOptions, FieldEncodingHint, FieldQuery, FilterSummary, IndexStatus, InputDetection,
+    InputFormat, InputSource, JacInput, JacReader, KeyNormalization, Limits, LineEnding,
+    MultiJacReader, OutputSink, OversizedRecordPolicy, Predicate, RetentionPolicy,
+    RetentionRequest, SchemaMapping, TextEncoding, TimestampUnit, TypeTag, Utf8Policy, ValueRef,
+    WrapperConfig, WrapperSuggestion,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
+use std::ffi::OsString;
 use std::fs::File;
 use std::io::{BufWriter, Read, Seek, Write};
 use std::path::{Path, PathBuf};
-use std::time::{Duration, Instant};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "serve")]
+mod serve;
+#[cfg(feature = "tui")]
+mod tui;
 
 /// Error context extracted from a segment limit error message.
 #[derive(Debug)]
@@ -137,6 +159,31 @@ fn load_config() -> Result<JacConfig, Box<dyn Error>> {
     Ok(config)
 }
 
+/// Load `CompressOptions` from a YAML (`.yaml`/`.yml`) or JSON file; omitted
+/// fields keep their library defaults.
+fn load_options_file(path: &Path) -> Result<CompressOptions, Box<dyn Error>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        std::io::Error::new(
+            e.kind(),
+            format!("failed to read options file {}: {}", path.display(), e),
+        )
+    })?;
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let parsed = match extension.as_str() {
+        // Enum variants are written as `{zstd: 6}`, as in JSON, not as YAML tags
+        "yaml" | "yml" => serde_yaml::with::singleton_map_recursive::deserialize(
+            serde_yaml::Deserializer::from_str(&content),
+        )
+        .map_err(|e| e.to_string()),
+        _ => serde_json::from_str(&content).map_err(|e| e.to_string()),
+    };
+    parsed.map_err(|e| usage_error(format!("invalid options file {}: {}", path.display(), e)))
+}
+
 // dirs crate placeholder - add to Cargo.toml or implement inline
 mod dirs {
     use std::path::PathBuf;
@@ -164,154 +211,30 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Compress JSON/NDJSON to .jac format
-    Pack {
-        /// Input file (JSON array or NDJSON)
-        input: PathBuf,
-        /// Output file (.jac)
-        #[arg(short, long)]
-        output: PathBuf,
-        /// Target records per block
-        #[arg(long, default_value = "100000")]
-        block_records: usize,
-        /// Zstd compression level
-        #[arg(long, default_value = "6")]
-        zstd_level: u8,
-        /// Canonicalize keys (lexicographic order)
-        #[arg(long)]
-        canonicalize_keys: bool,
-        /// Canonicalize numbers (scientific notation, trim trailing zeros)
-        #[arg(long)]
-        canonicalize_numbers: bool,
-        /// Maximum dictionary entries per field
-        #[arg(long, default_value = "4096")]
-        max_dict_entries: usize,
-        /// Emit index footer and pointer (enabled by default)
-        #[arg(long)]
-        no_index: bool,
-        /// Explicitly treat input as NDJSON (overrides extension detection)
-        #[arg(long)]
-        ndjson: bool,
-        /// Explicitly treat input as JSON array (overrides extension detection)
-        #[arg(long)]
-        json_array: bool,
-        /// Show progress spinner while compressing
-        #[arg(long)]
-        progress: bool,
-        /// Cap the number of parallel worker threads (1 forces sequential mode; defaults to auto-detect)
-        #[arg(long)]
-        threads: Option<usize>,
-        /// Fraction of available memory reserved for parallel compression (0 < factor ≤ 1, override with JAC_PARALLEL_MEMORY_FACTOR)
-        #[arg(long = "parallel-memory-factor", value_name = "FACTOR")]
-        parallel_memory_factor: Option<f64>,
-        /// Override segment size limit in bytes (requires --allow-large-segments when above default)
-        #[arg(long = "max-segment-bytes", value_name = "BYTES")]
-        max_segment_bytes: Option<u64>,
-        /// Confirm raising segment limits above the default 64 MiB ceiling
-        #[arg(long = "allow-large-segments")]
-        allow_large_segments: bool,
-        /// Display per-field metrics in summary (flush/rejection counts, max segment sizes)
-        #[arg(long = "verbose-metrics")]
-        verbose_metrics: bool,
-        /// JSON Pointer path to array/object to extract (RFC 6901 format, e.g., /data or /api/v1/results)
-        #[arg(long = "wrapper-pointer", value_name = "POINTER")]
-        wrapper_pointer: Option<String>,
-        /// Maximum depth for wrapper traversal (default: 3, max: 10)
-        /// Applies to all wrapper modes (pointer, sections, map, array-headers)
-        #[arg(
-            long = "wrapper-depth",
-            value_name = "DEPTH"
-        )]
-        wrapper_depth: Option<usize>,
-        /// Buffer size limit for wrapper preprocessing (default: 16M, max: 128M)
-        /// Applies to all wrapper modes (pointer, sections, map, array-headers)
-        #[arg(
-            long = "wrapper-buffer",
-            value_name = "SIZE"
-        )]
-        wrapper_buffer: Option<String>,
-        /// Section names for multi-section wrapper (e.g., users admins guests)
-        #[arg(
-            long = "wrapper-sections",
-            value_name = "NAME",
-            num_args = 1..,
-            conflicts_with = "wrapper_pointer"
-        )]
-        wrapper_sections: Option<Vec<String>>,
-        /// Custom pointer for a section (format: name=/pointer/path)
-        #[arg(
-            long = "wrapper-section-pointer",
-            value_name = "NAME=POINTER",
-            requires = "wrapper_sections"
-        )]
-        wrapper_section_pointer: Option<Vec<String>>,
-        /// Field name for injected section label (default: _section)
-        #[arg(
-            long = "wrapper-section-label-field",
-            value_name = "FIELD",
-            requires = "wrapper_sections"
-        )]
-        wrapper_section_label_field: Option<String>,
-        /// Disable section label injection
-        #[arg(long = "wrapper-section-no-label", requires = "wrapper_sections")]
-        wrapper_section_no_label: bool,
-        /// Error when a section is not found (default: skip missing sections)
-        #[arg(long = "wrapper-sections-missing-error", requires = "wrapper_sections")]
-        wrapper_sections_missing_error: bool,
-        /// Enable keyed map wrapper (flatten object-of-objects to records)
-        #[arg(
-            long = "wrapper-map",
-            conflicts_with_all = ["wrapper_pointer", "wrapper_sections"]
-        )]
-        wrapper_map: bool,
-        /// JSON Pointer to map object (default: root, empty string)
-        #[arg(
-            long = "wrapper-map-pointer",
-            value_name = "POINTER",
-            requires = "wrapper_map"
-        )]
-        wrapper_map_pointer: Option<String>,
-        /// Field name for injected map key (default: _key)
-        #[arg(
-            long = "wrapper-map-key-field",
-            value_name = "FIELD",
-            requires = "wrapper_map"
-        )]
-        wrapper_map_key_field: Option<String>,
-        /// Overwrite existing field if key field already exists (default: error on collision)
-        #[arg(long = "wrapper-map-overwrite-key", requires = "wrapper_map")]
-        wrapper_map_overwrite_key: bool,
-        /// Enable array-with-headers wrapper (CSV-like format: first row = headers)
-        #[arg(
-            long = "wrapper-array-headers",
-            conflicts_with_all = ["wrapper_pointer", "wrapper_sections", "wrapper_map"]
-        )]
-        wrapper_array_headers: bool,
-    },
+    Pack(PackArgs),
     /// Decompress .jac to JSON/NDJSON
-    Unpack {
-        /// Input file (.jac)
-        input: PathBuf,
-        /// Output file
-        #[arg(short, long)]
-        output: PathBuf,
-        /// Output as NDJSON (default). Use --json-array for array output.
-        #[arg(long)]
-        ndjson: bool,
-        /// Output as JSON array
-        #[arg(long = "json-array")]
-        json_array: bool,
-        /// Show progress spinner while decompressing
-        #[arg(long)]
-        progress: bool,
-    },
+    Unpack(UnpackArgs),
     /// List blocks, fields, and record counts
     ///
     /// Examples:
     ///   jac ls data.jac
     ///   jac ls data.jac --verbose --format json
     ///   jac ls data.jac --fields-only
+    ///   jac ls 'archives/**/*.jac'
+    ///   jac ls archives/ --has-field user --field-type decimal
+    ///
+    /// A directory (searched recursively for .jac files) or a glob pattern
+    /// lists each matching file under a `==> path <==` heading; JSON output
+    /// becomes an array of per-file objects with a `file` key.
+    ///
+    /// `--has-field` and `--field-type` list only the files, and the fields in
+    /// them, that match: one `field<TAB>N blocks` line each, prefixed by the
+    /// path for several inputs and followed by the field's types with
+    /// `--field-type`. No match at all is an error.
+    ///
+    /// Files still being written are listed up to their last complete block.
     Ls {
-        /// Input file (.jac)
+        /// Input file (.jac), directory, or glob pattern
         input: PathBuf,
         /// Output format (table, json)
         #[arg(long, value_enum, default_value_t = LsFormat::Table)]
@@ -331,6 +254,12 @@ enum Commands {
         /// Maximum values to sample per field when `--stats` is enabled (default: 50000)
         #[arg(long, requires = "stats", value_name = "N")]
         stats_sample: Option<usize>,
+        /// Only list files containing this field (repeatable; all must be present)
+        #[arg(long = "has-field", value_name = "FIELD")]
+        has_field: Vec<String>,
+        /// Only list fields storing values of this type
+        #[arg(long = "field-type", value_enum, value_name = "TYPE")]
+        field_type: Option<FieldTypeArg>,
     },
     /// Stream values for a specific field
     ///
@@ -338,8 +267,16 @@ enum Commands {
     ///   jac cat data.jac --field user
     ///   jac cat data.jac --field user --format csv
     ///   jac cat data.jac --field level --blocks 2-5 --progress
+    ///   jac cat archives/ --field msg
+    ///
+    /// A directory (searched recursively for .jac files) or a glob pattern is
+    /// read file by file in sorted order. Each value is then tagged with its
+    /// file: `{"file": ..., "value": ...}` objects, or a leading CSV column.
+    /// Files without the field are skipped; `--blocks` applies within each file.
+    ///
+    /// Files still being written are read up to their last complete block.
     Cat {
-        /// Input file (.jac)
+        /// Input file (.jac), directory, or glob pattern
         input: PathBuf,
         /// Field name to extract
         #[arg(long)]
@@ -350,10 +287,559 @@ enum Commands {
         /// Block range filter (e.g. "1-5" or "3")
         #[arg(long)]
         blocks: Option<String>,
+        /// Only emit values of records matching this filter (e.g. "code >= 500")
+        #[arg(long = "where", value_name = "FILTER")]
+        filter: Option<String>,
         /// Display a progress spinner during streaming
         #[arg(long)]
         progress: bool,
     },
+    /// Aggregate fields per group, emitting one NDJSON row per group
+    ///
+    /// Examples:
+    ///   jac agg data.jac --group-by level --count
+    ///   jac agg data.jac --group-by user --sum bytes --max latency
+    ///   jac agg data.jac --distinct user
+    Agg {
+        /// Input file (.jac)
+        input: PathBuf,
+        /// Field to group by (repeatable; omit for a single global group)
+        #[arg(long = "group-by", value_name = "FIELD")]
+        group_by: Vec<String>,
+        /// Count records per group
+        #[arg(long)]
+        count: bool,
+        /// Sum numeric values of a field (repeatable)
+        #[arg(long, value_name = "FIELD")]
+        sum: Vec<String>,
+        /// Minimum value of a field (repeatable)
+        #[arg(long, value_name = "FIELD")]
+        min: Vec<String>,
+        /// Maximum value of a field (repeatable)
+        #[arg(long, value_name = "FIELD")]
+        max: Vec<String>,
+        /// Approximate distinct value count of a field (repeatable)
+        #[arg(long, value_name = "FIELD")]
+        distinct: Vec<String>,
+    },
+    /// Count records, or records where a field is present, without decoding
+    ///
+    /// Counts come from the index footer and block headers; no segment is
+    /// decompressed. Files still being written are counted up to their last
+    /// complete block.
+    ///
+    /// Examples:
+    ///   jac count data.jac
+    ///   jac count data.jac --field user
+    ///   jac count 'archives/*.jac'
+    ///
+    /// Several files print one `count<TAB>path` line each, then a total.
+    Count {
+        /// Input file (.jac), directory, or glob pattern
+        input: PathBuf,
+        /// Count records where this field is present (nulls included)
+        #[arg(long)]
+        field: Option<String>,
+    },
+    /// Show the most frequent values of a field
+    ///
+    /// Dictionary-encoded blocks are counted from their index histograms
+    /// without decoding values. With --estimate, the value, null and
+    /// approximate distinct counts come from the field statistics of files
+    /// packed with --field-stats, and percentiles of numeric values from
+    /// files packed with --quantile-sketches, reading block headers only.
+    ///
+    /// Examples:
+    ///   jac stat data.jac --field user
+    ///   jac stat data.jac --field level --top 5
+    ///   jac stat data.jac --field user --estimate
+    Stat {
+        /// Input file (.jac)
+        input: PathBuf,
+        /// Field to count values for
+        #[arg(long)]
+        field: String,
+        /// Number of values to show (default: 20)
+        #[arg(long, default_value_t = 20, value_name = "N")]
+        top: usize,
+        /// Report counts from stored field statistics instead of reading values
+        #[arg(long, conflicts_with = "top")]
+        estimate: bool,
+    },
+    /// Export block and field directory metadata as a dataset
+    ///
+    /// Emits one record per field of every block: the file, block number,
+    /// offset and size, segment sizes and encodings, and any field statistics
+    /// or quantile sketches stored at pack time. Only block headers are read.
+    /// An output path ending in .jac is packed as a JAC file; any other path,
+    /// or stdout, receives NDJSON.
+    ///
+    /// Examples:
+    ///   jac meta data.jac -o meta.jac
+    ///   jac meta archives/ -o fleet.jac
+    ///   jac agg fleet.jac --group-by field --sum compressed_bytes
+    Meta {
+        /// Input file (.jac), directory, or glob pattern
+        input: PathBuf,
+        /// Output file (.jac for JAC, otherwise NDJSON; default: NDJSON on stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Drop records older than a retention period
+    ///
+    /// Records whose --timestamp-field is before now minus --drop-older-than
+    /// are removed; records without a readable timestamp are kept. Numeric
+    /// timestamps count --timestamp-unit since the Unix epoch, and strings are
+    /// read as RFC 3339. Blocks whose quantile sketches (pack
+    /// --quantile-sketches) put them wholly before or after the cutoff are
+    /// dropped or copied without decoding; other blocks have their timestamp
+    /// column scanned, and only blocks mixing both are re-encoded.
+    ///
+    /// Examples:
+    ///   jac rewrite data.jac --drop-older-than 90d --timestamp-field ts
+    ///   jac rewrite data.jac -o recent.jac --drop-older-than 12h --timestamp-field ts --timestamp-unit s
+    Rewrite {
+        /// Input file (.jac)
+        input: PathBuf,
+        /// Output file (default: replace the input once the rewrite succeeds)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Retention period: a whole number followed by s, m, h, d or w (e.g. 90d)
+        #[arg(long = "drop-older-than", value_name = "AGE", value_parser = parse_age)]
+        drop_older_than: Duration,
+        /// Field holding each record's timestamp
+        #[arg(long = "timestamp-field", value_name = "FIELD")]
+        timestamp_field: String,
+        /// Unit of numeric timestamps
+        #[arg(long = "timestamp-unit", value_enum, default_value_t = TimestampUnitArg::Ms)]
+        timestamp_unit: TimestampUnitArg,
+    },
+    /// Add an index footer to a file written without one
+    ///
+    /// Scans the blocks once and appends the footer and pointer in place, so
+    /// later projections and seeks no longer scan the whole file. A stale or
+    /// corrupt footer is replaced; a valid one is left untouched.
+    ///
+    /// Examples:
+    ///   jac index data.jac
+    Index {
+        /// Input file (.jac), modified in place
+        input: PathBuf,
+    },
+    /// Write the SPEC §12 conformance vectors
+    ///
+    /// Each vector is an NDJSON input, the .jac file encoded from it, and an
+    /// entry in manifest.json with the expected record count, projections,
+    /// and the encodings it exercises (dictionary overflow, delta integers,
+    /// decimals, unicode, mixed types).
+    ///
+    /// Examples:
+    ///   jac gen-vectors vectors/
+    GenVectors {
+        /// Output directory (created if missing)
+        out_dir: PathBuf,
+    },
+    /// Check a directory of conformance vectors against their inputs
+    ///
+    /// Every .jac file listed in manifest.json must decode to its NDJSON input
+    /// and reproduce the listed projections. Differing block layout or
+    /// encodings are reported as notes, not failures.
+    ///
+    /// Examples:
+    ///   jac check-vectors vectors/
+    CheckVectors {
+        /// Directory containing manifest.json
+        dir: PathBuf,
+    },
+    /// Run a SQL-subset query, emitting matching rows as NDJSON
+    ///
+    /// Examples:
+    ///   jac query data.jac "select ts, msg where level='ERROR' limit 100"
+    ///   jac query data.jac "select * where code >= 500 and retry is not null"
+    #[cfg(feature = "query")]
+    Query {
+        /// Input file (.jac)
+        input: PathBuf,
+        /// Query text: [SELECT] fields|* [WHERE predicate] [LIMIT n]
+        query: String,
+        /// Print block pruning statistics to stderr
+        #[arg(long, short = 'v')]
+        verbose: bool,
+    },
+    /// Browse blocks, fields, field statistics and records interactively
+    ///
+    /// Tab switches between the block and field lists, arrow keys move the
+    /// selection, and q quits. With a field selected, the preview shows only
+    /// that field's values in the selected block.
+    ///
+    /// Example:
+    ///   jac tui data.jac
+    #[cfg(feature = "tui")]
+    Tui {
+        /// Input file (.jac)
+        input: PathBuf,
+    },
+    /// Serve the .jac files of a directory over HTTP (read-only)
+    ///
+    /// Endpoints: /files, /files/<path>, /files/<path>/fields,
+    /// /files/<path>/records?offset=N&limit=M and
+    /// /files/<path>/project?fields=a,b&where=<predicate>&limit=M.
+    ///
+    /// Example:
+    ///   jac serve archives/ --port 8080
+    #[cfg(feature = "serve")]
+    Serve {
+        /// Directory to serve (searched recursively for .jac files)
+        dir: PathBuf,
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Address to bind; use 0.0.0.0 to accept remote connections
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+        /// Requests handled at once
+        #[arg(long, default_value_t = 4)]
+        workers: usize,
+        /// Most records returned by one records or project request
+        #[arg(long, default_value_t = 10_000)]
+        max_records: u64,
+    },
+}
+
+/// Flags of `jac pack`
+#[derive(Parser)]
+struct PackArgs {
+    /// Input file (JSON array or NDJSON)
+    input: PathBuf,
+    /// Output file (.jac)
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Target records per block
+    #[arg(long, default_value = "100000")]
+    block_records: usize,
+    /// Zstd compression level, or `auto` to pick one by sampling the input
+    #[arg(long, default_value = "6", value_parser = parse_zstd_level)]
+    zstd_level: ZstdLevelArg,
+    /// Canonicalize keys (lexicographic order)
+    #[arg(long)]
+    canonicalize_keys: bool,
+    /// Canonicalize numbers (scientific notation, trim trailing zeros)
+    #[arg(long)]
+    canonicalize_numbers: bool,
+    /// Maximum dictionary entries per field
+    #[arg(long, default_value = "4096")]
+    max_dict_entries: usize,
+    /// Largest ratio of distinct strings to string values in a block for dictionary encoding (0 to 1, default 0.125)
+    #[arg(long = "dict-max-ratio", value_name = "RATIO")]
+    dict_max_ratio: Option<f64>,
+    /// Never dictionary encode this field's strings, e.g. UUID columns (repeatable)
+    #[arg(long = "no-dict-field", value_name = "FIELD")]
+    no_dict_field: Option<Vec<String>>,
+    /// Encoding hints for a field, comma separated: delta (delta encode increasing integers), no-dict or dict (repeatable)
+    #[arg(long = "field-hint", value_name = "FIELD=HINTS")]
+    field_hint: Option<Vec<String>>,
+    /// Emit index footer and pointer (enabled by default)
+    #[arg(long)]
+    no_index: bool,
+    /// Explicitly treat input as NDJSON (overrides extension detection)
+    #[arg(long)]
+    ndjson: bool,
+    /// Explicitly treat input as JSON array (overrides extension detection)
+    #[arg(long)]
+    json_array: bool,
+    /// Show progress spinner while compressing
+    #[arg(long)]
+    progress: bool,
+    /// Cap the number of parallel worker threads (1 forces sequential mode; defaults to auto-detect)
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Fraction of available memory reserved for parallel compression (0 < factor ≤ 1, override with JAC_PARALLEL_MEMORY_FACTOR)
+    #[arg(long = "parallel-memory-factor", value_name = "FACTOR")]
+    parallel_memory_factor: Option<f64>,
+    /// Override segment size limit in bytes (requires --allow-large-segments when above default)
+    #[arg(long = "max-segment-bytes", value_name = "BYTES")]
+    max_segment_bytes: Option<u64>,
+    /// Confirm raising segment limits above the default 64 MiB ceiling
+    #[arg(long = "allow-large-segments")]
+    allow_large_segments: bool,
+    /// Display per-field metrics in summary (flush/rejection counts, max segment sizes)
+    #[arg(long = "verbose-metrics")]
+    verbose_metrics: bool,
+    /// JSON Pointer path to array/object to extract (RFC 6901 format, e.g., /data or /api/v1/results)
+    #[arg(long = "wrapper-pointer", value_name = "POINTER")]
+    wrapper_pointer: Option<String>,
+    /// Maximum depth for wrapper traversal (default: 3, max: 10)
+    /// Applies to all wrapper modes (pointer, sections, map, array-headers)
+    #[arg(long = "wrapper-depth", value_name = "DEPTH")]
+    wrapper_depth: Option<usize>,
+    /// Buffer size limit for wrapper preprocessing (default: 16M, max: 128M)
+    /// Applies to all wrapper modes (pointer, sections, map, array-headers)
+    #[arg(long = "wrapper-buffer", value_name = "SIZE")]
+    wrapper_buffer: Option<String>,
+    /// Section names for multi-section wrapper (e.g., users admins guests)
+    #[arg(
+        long = "wrapper-sections",
+        value_name = "NAME",
+        num_args = 1..,
+        conflicts_with = "wrapper_pointer"
+    )]
+    wrapper_sections: Option<Vec<String>>,
+    /// Custom pointer for a section (format: name=/pointer/path)
+    #[arg(
+        long = "wrapper-section-pointer",
+        value_name = "NAME=POINTER",
+        requires = "wrapper_sections"
+    )]
+    wrapper_section_pointer: Option<Vec<String>>,
+    /// Prefix for the field names of a section's records (format: name=prefix)
+    #[arg(
+        long = "wrapper-section-prefix",
+        value_name = "NAME=PREFIX",
+        requires = "wrapper_sections"
+    )]
+    wrapper_section_prefix: Option<Vec<String>>,
+    /// Maximum records taken from a section (format: name=count)
+    #[arg(
+        long = "wrapper-section-limit",
+        value_name = "NAME=COUNT",
+        requires = "wrapper_sections"
+    )]
+    wrapper_section_limit: Option<Vec<String>>,
+    /// Field name for injected section label (default: _section)
+    #[arg(
+        long = "wrapper-section-label-field",
+        value_name = "FIELD",
+        requires = "wrapper_sections"
+    )]
+    wrapper_section_label_field: Option<String>,
+    /// Disable section label injection
+    #[arg(long = "wrapper-section-no-label", requires = "wrapper_sections")]
+    wrapper_section_no_label: bool,
+    /// Error when a section is not found (default: skip missing sections)
+    #[arg(long = "wrapper-sections-missing-error", requires = "wrapper_sections")]
+    wrapper_sections_missing_error: bool,
+    /// Enable keyed map wrapper (flatten object-of-objects to records)
+    #[arg(
+        long = "wrapper-map",
+        conflicts_with_all = ["wrapper_pointer", "wrapper_sections"]
+    )]
+    wrapper_map: bool,
+    /// JSON Pointer to map object (default: root, empty string)
+    #[arg(
+        long = "wrapper-map-pointer",
+        value_name = "POINTER",
+        requires = "wrapper_map"
+    )]
+    wrapper_map_pointer: Option<String>,
+    /// Field name for injected map key (default: _key)
+    #[arg(
+        long = "wrapper-map-key-field",
+        value_name = "FIELD",
+        requires = "wrapper_map"
+    )]
+    wrapper_map_key_field: Option<String>,
+    /// Treat the map as two-level (object of objects of objects) and inject each inner key into FIELD
+    #[arg(
+        long = "wrapper-map-inner-key-field",
+        value_name = "FIELD",
+        requires = "wrapper_map"
+    )]
+    wrapper_map_inner_key_field: Option<String>,
+    /// Treat key fields as dotted paths into nested objects (e.g. meta.id)
+    #[arg(long = "wrapper-map-nested-keys", requires = "wrapper_map")]
+    wrapper_map_nested_keys: bool,
+    /// Overwrite existing field if key field already exists (default: error on collision)
+    #[arg(long = "wrapper-map-overwrite-key", requires = "wrapper_map")]
+    wrapper_map_overwrite_key: bool,
+    /// Enable array-with-headers wrapper (CSV-like format: first row = headers)
+    #[arg(
+        long = "wrapper-array-headers",
+        conflicts_with_all = ["wrapper_pointer", "wrapper_sections", "wrapper_map"]
+    )]
+    wrapper_array_headers: bool,
+    /// Read the second row as column types (int, decimal, bool, string) and convert cells to them
+    #[arg(
+        long = "wrapper-array-headers-type-row",
+        requires = "wrapper_array_headers"
+    )]
+    wrapper_array_headers_type_row: bool,
+    /// String cell read as null (repeatable, e.g. "", NULL, N/A)
+    #[arg(
+        long = "wrapper-array-headers-null-marker",
+        value_name = "MARKER",
+        requires = "wrapper_array_headers"
+    )]
+    wrapper_array_headers_null_marker: Option<Vec<String>>,
+    /// Record each record's original key order so unpack reproduces it (ignored with --canonicalize-keys)
+    #[arg(long = "preserve-key-order")]
+    preserve_key_order: bool,
+    /// Handling of invalid UTF-8 in string values (strict, lossy, base64)
+    #[arg(long = "utf8", value_enum, default_value_t = Utf8Mode::Strict)]
+    utf8_mode: Utf8Mode,
+    /// Reject records whose raw NDJSON line or JSON array element exceeds this many bytes
+    #[arg(long = "max-record-bytes")]
+    max_record_bytes: Option<usize>,
+    /// Skip (and count) oversized records instead of failing
+    #[arg(long = "skip-oversized-records", requires = "max_record_bytes")]
+    skip_oversized_records: bool,
+    /// Load compression options from a YAML or JSON file; flags set on the command line take precedence
+    #[arg(long = "options-file", value_name = "PATH")]
+    options_file: Option<PathBuf>,
+    /// Preset for codec level, block size, dictionary size and key canonicalization; flags set on the command line take precedence
+    #[arg(
+        long,
+        value_enum,
+        conflicts_with_all = ["options_file", "fast", "balanced", "max_compression"]
+    )]
+    profile: Option<ProfileMode>,
+    /// Shorthand for --profile fast
+    #[arg(long, conflicts_with_all = ["options_file", "balanced", "max_compression"])]
+    fast: bool,
+    /// Shorthand for --profile balanced
+    #[arg(long, conflicts_with_all = ["options_file", "max_compression"])]
+    balanced: bool,
+    /// Shorthand for --profile max
+    #[arg(long = "max-compression", conflicts_with = "options_file")]
+    max_compression: bool,
+    /// Fail when the input has no records instead of writing an empty file
+    #[arg(long = "fail-on-empty")]
+    fail_on_empty: bool,
+    /// Normalize top-level keys before encoding (comma-separated: case, nfc)
+    #[arg(long = "normalize-keys", value_enum, value_delimiter = ',')]
+    normalize_keys: Vec<KeyNormalizeMode>,
+    /// Resolution when keys of one record normalize to the same name
+    #[arg(
+        long = "key-collision",
+        value_enum,
+        default_value_t = KeyCollisionArg::Error,
+        requires = "normalize_keys"
+    )]
+    key_collision: KeyCollisionArg,
+    /// Name fields in block directories by IDs into a file-level table (smaller metadata for wide schemas)
+    #[arg(long = "intern-field-names")]
+    intern_field_names: bool,
+    /// Protect each block header with its own CRC32C so damaged directories are rejected early
+    #[arg(long = "header-crc")]
+    header_crc: bool,
+    /// Store per-field null counts and distinct-value sketches in block directories (read by `jac stat --estimate` and filter pruning)
+    #[arg(long = "field-stats")]
+    field_stats: bool,
+    /// Store quantile sketches of numeric fields in block directories (percentiles in `jac stat --estimate`, range pruning for filters)
+    #[arg(long = "quantile-sketches")]
+    quantile_sketches: bool,
+    /// Drop records identical to an earlier one
+    #[arg(long)]
+    dedupe: bool,
+    /// Compare only these top-level fields when deduplicating (repeatable; implies --dedupe)
+    #[arg(long = "dedupe-key", value_name = "FIELD")]
+    dedupe_key: Vec<String>,
+    /// Compare against the last N distinct records instead of the whole input (implies --dedupe)
+    #[arg(long = "dedupe-window", value_name = "N")]
+    dedupe_window: Option<usize>,
+    /// Finish the output at a block boundary before it exceeds N bytes and continue into OUTPUT.part2.jac, OUTPUT.part3.jac, ...
+    #[arg(long = "max-output-bytes", value_name = "N")]
+    max_output_bytes: Option<u64>,
+    /// On Ctrl-C, finish the output with the records read so far instead of removing it
+    /// (Ctrl-C is noticed when the next record is read; a second Ctrl-C removes the output and exits)
+    #[arg(long = "finalize-on-interrupt")]
+    finalize_on_interrupt: bool,
+    /// Pack the input as text even when it starts with the JAC file magic
+    #[arg(long = "force-treat-as-text")]
+    force_treat_as_text: bool,
+}
+
+impl PackArgs {
+    /// Arguments of `jac pack INPUT -o OUTPUT`, every flag at its default
+    fn new(input: PathBuf, output: PathBuf) -> Self {
+        let mut output_flag = OsString::from("--output=");
+        output_flag.push(output);
+        Self::parse_from([
+            OsString::from("pack"),
+            output_flag,
+            "--".into(),
+            input.into(),
+        ])
+    }
+}
+
+/// Flags of `jac unpack`
+#[derive(Parser)]
+struct UnpackArgs {
+    /// Input file (.jac)
+    input: PathBuf,
+    /// Output file
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Output as NDJSON (default). Use --json-array for array output.
+    #[arg(long)]
+    ndjson: bool,
+    /// Output as JSON array
+    #[arg(long = "json-array")]
+    json_array: bool,
+    /// Output as concatenated MessagePack maps (requires the `msgpack` feature)
+    #[arg(long, conflicts_with_all = ["ndjson", "json_array", "cbor"])]
+    msgpack: bool,
+    /// Output as a CBOR sequence (requires the `cbor` feature)
+    #[arg(long, conflicts_with_all = ["ndjson", "json_array"])]
+    cbor: bool,
+    /// Rename a top-level field in the output (repeatable)
+    #[arg(long, value_name = "OLD=NEW")]
+    rename: Vec<String>,
+    /// Schema mapping file (JSON with `rename`, `cast` and `defaults`)
+    /// applied to every record before `--rename`
+    #[arg(long = "schema-map", value_name = "FILE")]
+    schema_map: Option<PathBuf>,
+    /// Block range filter (e.g. "1-5" or "3"); other blocks are not decoded
+    #[arg(long)]
+    blocks: Option<String>,
+    /// Only write records where this JSON pointer resolves (e.g. /error/code)
+    #[arg(long = "require-pointer", value_name = "POINTER")]
+    require_pointer: Option<String>,
+    /// Only write records matching this filter (e.g. "level in ('WARN', 'ERROR')");
+    /// blocks that cannot match are not decoded
+    #[arg(long = "where", value_name = "FILTER")]
+    filter: Option<String>,
+    /// Continue past structural anomalies and unreadable blocks, reporting warnings
+    #[arg(long)]
+    permissive: bool,
+    /// Copy stored nested objects and arrays to JSON output without
+    /// checking that they are well-formed (faster; trusts the archive)
+    #[arg(long = "skip-nested-validation")]
+    skip_nested_validation: bool,
+    /// Write canonical JSON (keys sorted at every depth, integral floats
+    /// as integers) so archives of the same data unpack identically
+    #[arg(long, conflicts_with_all = ["msgpack", "cbor"])]
+    canonical: bool,
+    /// End NDJSON records with \r\n instead of \n
+    #[arg(long, conflicts_with_all = ["json_array", "msgpack", "cbor"])]
+    crlf: bool,
+    /// Character encoding of JSON output
+    #[arg(long, value_enum, default_value_t = EncodingArg::Utf8)]
+    encoding: EncodingArg,
+    /// Fail if JSON output is not valid UTF-8 (always checked with --encoding ascii)
+    #[arg(long = "validate-utf8", conflicts_with_all = ["msgpack", "cbor"])]
+    validate_utf8: bool,
+    /// Show progress spinner while decompressing
+    #[arg(long)]
+    progress: bool,
+    /// Display read-side metrics in summary (bytes read, segments decompressed, decode time)
+    #[arg(long = "verbose-metrics")]
+    verbose_metrics: bool,
+}
+
+impl UnpackArgs {
+    /// Arguments of `jac unpack INPUT -o OUTPUT`, every flag at its default
+    #[cfg(test)]
+    fn new(input: PathBuf, output: PathBuf) -> Self {
+        let mut output_flag = OsString::from("--output=");
+        output_flag.push(output);
+        Self::parse_from([
+            OsString::from("unpack"),
+            output_flag,
+            "--".into(),
+            input.into(),
+        ])
+    }
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
@@ -362,6 +848,95 @@ enum LsFormat {
     Json,
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum FieldTypeArg {
+    Null,
+    Bool,
+    Int,
+    Decimal,
+    String,
+    Object,
+    Array,
+}
+
+impl From<FieldTypeArg> for TypeTag {
+    fn from(arg: FieldTypeArg) -> Self {
+        match arg {
+            FieldTypeArg::Null => TypeTag::Null,
+            FieldTypeArg::Bool => TypeTag::Bool,
+            FieldTypeArg::Int => TypeTag::Int,
+            FieldTypeArg::Decimal => TypeTag::Decimal,
+            FieldTypeArg::String => TypeTag::String,
+            FieldTypeArg::Object => TypeTag::Object,
+            FieldTypeArg::Array => TypeTag::Array,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum EncodingArg {
+    Utf8,
+    /// UTF-8 with a byte order mark
+    #[value(name = "utf8-bom")]
+    Utf8Bom,
+    /// Non-ASCII characters escaped as \uXXXX
+    Ascii,
+}
+
+impl From<EncodingArg> for TextEncoding {
+    fn from(arg: EncodingArg) -> Self {
+        match arg {
+            EncodingArg::Utf8 => TextEncoding::Utf8,
+            EncodingArg::Utf8Bom => TextEncoding::Utf8Bom,
+            EncodingArg::Ascii => TextEncoding::Ascii,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum TimestampUnitArg {
+    S,
+    Ms,
+    Us,
+    Ns,
+}
+
+impl From<TimestampUnitArg> for TimestampUnit {
+    fn from(arg: TimestampUnitArg) -> Self {
+        match arg {
+            TimestampUnitArg::S => TimestampUnit::Seconds,
+            TimestampUnitArg::Ms => TimestampUnit::Millis,
+            TimestampUnitArg::Us => TimestampUnit::Micros,
+            TimestampUnitArg::Ns => TimestampUnit::Nanos,
+        }
+    }
+}
+
+/// `--drop-older-than` value: a whole number of seconds, minutes, hours,
+/// days or weeks.
+fn parse_age(value: &str) -> Result<Duration, String> {
+    let error = || {
+        format!(
+            "expected a number followed by s, m, h, d or w, got '{}'",
+            value
+        )
+    };
+    let unit = value.chars().last().ok_or_else(error)?;
+    let seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3_600,
+        'd' => 86_400,
+        'w' => 604_800,
+        _ => return Err(error()),
+    };
+    let number: u64 = value[..value.len() - 1].parse().map_err(|_| error())?;
+    number
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(error)
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
 enum CatFormat {
     Ndjson,
@@ -370,127 +945,271 @@ enum CatFormat {
     Csv,
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let cli = Cli::parse();
+/// `--zstd-level` value
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum ZstdLevelArg {
+    Auto,
+    Fixed(u8),
+}
+
+fn parse_zstd_level(value: &str) -> Result<ZstdLevelArg, String> {
+    if value.eq_ignore_ascii_case("auto") {
+        return Ok(ZstdLevelArg::Auto);
+    }
+    value
+        .parse::<u8>()
+        .map(ZstdLevelArg::Fixed)
+        .map_err(|_| format!("expected a level from 0 to 255 or 'auto', got '{}'", value))
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum ProfileMode {
+    Fast,
+    Balanced,
+    #[value(alias = "max-compression")]
+    Max,
+}
+
+impl From<ProfileMode> for CompressionProfile {
+    fn from(mode: ProfileMode) -> Self {
+        match mode {
+            ProfileMode::Fast => CompressionProfile::Fast,
+            ProfileMode::Balanced => CompressionProfile::Balanced,
+            ProfileMode::Max => CompressionProfile::MaxCompression,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum Utf8Mode {
+    Strict,
+    Lossy,
+    Base64,
+}
+
+impl From<Utf8Mode> for Utf8Policy {
+    fn from(mode: Utf8Mode) -> Self {
+        match mode {
+            Utf8Mode::Strict => Utf8Policy::Strict,
+            Utf8Mode::Lossy => Utf8Policy::Lossy,
+            Utf8Mode::Base64 => Utf8Policy::Base64,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum KeyNormalizeMode {
+    /// Lowercase keys
+    Case,
+    /// Unicode NFC
+    Nfc,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum KeyCollisionArg {
+    Error,
+    FirstWins,
+    LastWins,
+}
+
+impl From<KeyCollisionArg> for DuplicateKeyPolicy {
+    fn from(arg: KeyCollisionArg) -> Self {
+        match arg {
+            KeyCollisionArg::Error => DuplicateKeyPolicy::Error,
+            KeyCollisionArg::FirstWins => DuplicateKeyPolicy::FirstWins,
+            KeyCollisionArg::LastWins => DuplicateKeyPolicy::LastWins,
+        }
+    }
+}
 
+/// `--normalize-keys`/`--key-collision` as a policy, or `None` when unset.
+fn key_normalization_from_args(
+    modes: &[KeyNormalizeMode],
+    collision: KeyCollisionArg,
+) -> Option<KeyNormalization> {
+    if modes.is_empty() {
+        return None;
+    }
+    Some(KeyNormalization {
+        case_fold: modes.contains(&KeyNormalizeMode::Case),
+        unicode_nfc: modes.contains(&KeyNormalizeMode::Nfc),
+        on_collision: collision.into(),
+    })
+}
+
+/// `--dedupe`/`--dedupe-key`/`--dedupe-window` as a config, or `None` when unset.
+fn dedupe_from_args(
+    dedupe: bool,
+    key_fields: Vec<String>,
+    window: Option<usize>,
+) -> Option<DedupeConfig> {
+    if !dedupe && key_fields.is_empty() && window.is_none() {
+        return None;
+    }
+    Some(DedupeConfig {
+        key_fields,
+        window,
+        ..DedupeConfig::default()
+    })
+}
+
+/// Flag raised by Ctrl-C, installing the handler on first use.
+///
+/// Commands poll the flag between records and wind down on their own, so a
+/// command blocked reading its input (a quiet stdin or FIFO) only notices
+/// when the next record arrives. A second Ctrl-C exits at once, after
+/// removing the output registered with [`PartialOutput`].
+fn interrupt_flag() -> Arc<AtomicBool> {
+    static FLAG: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+    Arc::clone(FLAG.get_or_init(|| {
+        let flag = Arc::new(AtomicBool::new(false));
+        let raised = Arc::clone(&flag);
+        // If another handler is already installed Ctrl-C keeps its behavior.
+        let _ = ctrlc::set_handler(move || {
+            if raised.swap(true, Ordering::Relaxed) {
+                PartialOutput::remove();
+                std::process::exit(130);
+            }
+        });
+        flag
+    }))
+}
+
+/// Output file being written, with the time writing started.
+static PARTIAL_OUTPUT: Mutex<Option<(PathBuf, SystemTime)>> = Mutex::new(None);
+
+/// Registers the output of a running command, so that a second Ctrl-C
+/// removes it (and its continuation parts) before exiting; unregistered on
+/// drop.
+struct PartialOutput;
+
+impl PartialOutput {
+    fn register(path: &Path) -> Self {
+        // Allow for file systems with coarse modification times
+        let started = SystemTime::now() - Duration::from_secs(1);
+        *PARTIAL_OUTPUT
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some((path.to_path_buf(), started));
+        PartialOutput
+    }
+
+    /// Remove the registered output and its parts, skipping any file not
+    /// modified since writing started.
+    fn remove() {
+        let registered = PARTIAL_OUTPUT
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        let Some((first, started)) = registered else {
+            return;
+        };
+        let written = |path: &Path| {
+            path.metadata()
+                .and_then(|meta| meta.modified())
+                .is_ok_and(|modified| modified >= started)
+        };
+        if written(&first) {
+            let _ = std::fs::remove_file(&first);
+        }
+        for part in 2.. {
+            let path = jac_io::default_part_path(&first, part);
+            if !written(&path) {
+                break;
+            }
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+}
+
+impl Drop for PartialOutput {
+    fn drop(&mut self) {
+        *PARTIAL_OUTPUT
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+    }
+}
+
+/// Process exit status for a failure of the given category. Argument errors
+/// reported by clap itself also exit with 2; interruptions follow the shell's
+/// 128 + SIGINT convention.
+fn exit_status(category: ErrorCategory) -> u8 {
+    match category {
+        ErrorCategory::Internal => 1,
+        ErrorCategory::Usage => 2,
+        ErrorCategory::Io => 3,
+        ErrorCategory::Format => 4,
+        ErrorCategory::Limits => 5,
+        ErrorCategory::Interrupted => 130,
+    }
+}
+
+/// Error for arguments the CLI rejects while validating them, reported as
+/// `invalid_argument` in the `usage` category.
+fn usage_error(message: impl Into<String>) -> Box<dyn Error> {
+    Box::new(jac_io::JacError::InvalidArgument(message.into()))
+}
+
+/// Stable code and category of a command failure. Library errors, including
+/// the CLI's own [`usage_error`]s, carry their own; bare I/O and JSON errors
+/// map to `io` and `json`, and anything else is `internal`.
+fn classify_error(err: &(dyn Error + 'static)) -> (&'static str, ErrorCategory) {
+    if let Some(err) = err.downcast_ref::<jac_io::JacError>() {
+        (err.code(), err.category())
+    } else if err.is::<std::io::Error>() {
+        ("io", ErrorCategory::Io)
+    } else if err.is::<serde_json::Error>() {
+        ("json", ErrorCategory::Format)
+    } else {
+        ("internal", ErrorCategory::Internal)
+    }
+}
+
+/// Print `err` followed by a one-line JSON trailer scripts can parse, and
+/// return the exit status for its category.
+fn report_error(err: &(dyn Error + 'static)) -> ExitCode {
+    let (code, category) = classify_error(err);
+    let status = exit_status(category);
+    eprintln!("Error: {:?}", err);
+    eprintln!(
+        "{}",
+        serde_json::json!({
+            "error": {
+                "code": code,
+                "category": category.as_str(),
+                "exit_code": status,
+                "message": err.to_string(),
+            }
+        })
+    );
+    ExitCode::from(status)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_error(err.as_ref()),
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
     // Handle shortcut mode: jac <file> -> jac pack <file> -o <file>.jac
     if let Some(input_file) = cli.input_file {
         if cli.command.is_some() {
-            return Err("Cannot specify both input file and subcommand".into());
+            return Err(usage_error("Cannot specify both input file and subcommand"));
         }
 
         // Auto-generate output filename by appending .jac
         let output_file = input_file.with_extension("jac");
 
         // Use default compression settings for shortcut mode
-        handle_pack(
-            input_file,
-            output_file,
-            100000, // default block_records
-            6,      // default zstd_level
-            false,  // canonicalize_keys
-            false,  // canonicalize_numbers
-            4096,   // max_dict_entries
-            true,   // emit_index
-            false,  // force_ndjson
-            false,  // force_json_array
-            false,  // show_progress
-            None,   // threads
-            None,   // parallel_memory_factor
-            None,   // max_segment_bytes
-            false,  // allow_large_segments
-            false,  // verbose_metrics
-            None,   // wrapper_pointer
-            None,   // wrapper_depth
-            None,   // wrapper_buffer
-            None,   // wrapper_sections
-            None,   // wrapper_section_pointer
-            None,   // wrapper_section_label_field
-            false,  // wrapper_section_no_label
-            false,  // wrapper_sections_missing_error
-            false,  // wrapper_map
-            None,   // wrapper_map_pointer
-            None,   // wrapper_map_key_field
-            false,  // wrapper_map_overwrite_key
-            false,  // wrapper_array_headers
-        )?;
+        handle_pack(PackArgs::new(input_file, output_file))?;
         return Ok(());
     }
 
     // Handle subcommand mode
     match cli.command {
-        Some(Commands::Pack {
-            input,
-            output,
-            block_records,
-            zstd_level,
-            canonicalize_keys,
-            canonicalize_numbers,
-            max_dict_entries,
-            no_index,
-            ndjson,
-            json_array,
-            progress,
-            threads,
-            parallel_memory_factor,
-            max_segment_bytes,
-            allow_large_segments,
-            verbose_metrics,
-            wrapper_pointer,
-            wrapper_depth,
-            wrapper_buffer,
-            wrapper_sections,
-            wrapper_section_pointer,
-            wrapper_section_label_field,
-            wrapper_section_no_label,
-            wrapper_sections_missing_error,
-            wrapper_map,
-            wrapper_map_pointer,
-            wrapper_map_key_field,
-            wrapper_map_overwrite_key,
-            wrapper_array_headers,
-        }) => {
-            handle_pack(
-                input,
-                output,
-                block_records,
-                zstd_level,
-                canonicalize_keys,
-                canonicalize_numbers,
-                max_dict_entries,
-                !no_index,
-                ndjson,
-                json_array,
-                progress,
-                threads,
-                parallel_memory_factor,
-                max_segment_bytes,
-                allow_large_segments,
-                verbose_metrics,
-                wrapper_pointer,
-                wrapper_depth,
-                wrapper_buffer,
-                wrapper_sections,
-                wrapper_section_pointer,
-                wrapper_section_label_field,
-                wrapper_section_no_label,
-                wrapper_sections_missing_error,
-                wrapper_map,
-                wrapper_map_pointer,
-                wrapper_map_key_field,
-                wrapper_map_overwrite_key,
-                wrapper_array_headers,
-            )?;
-        }
-        Some(Commands::Unpack {
-            input,
-            output,
-            ndjson,
-            json_array,
-            progress,
-        }) => {
-            handle_unpack(input, output, ndjson, json_array, progress)?;
-        }
+        Some(Commands::Pack(args)) => handle_pack(args)?,
+        Some(Commands::Unpack(args)) => handle_unpack(args)?,
         Some(Commands::Ls {
             input,
             format,
@@ -499,25 +1218,120 @@ fn main() -> Result<(), Box<dyn Error>> {
             blocks_only,
             stats,
             stats_sample,
+            has_field,
+            field_type,
         }) => {
-            handle_ls(
-                input,
-                format,
-                verbose,
-                fields_only,
-                blocks_only,
-                stats,
-                stats_sample,
-            )?;
+            let query = FieldQuery {
+                has_fields: has_field,
+                field_type: field_type.map(TypeTag::from),
+            };
+            if query.is_empty() {
+                handle_ls(
+                    input,
+                    format,
+                    verbose,
+                    fields_only,
+                    blocks_only,
+                    stats,
+                    stats_sample,
+                )?;
+            } else {
+                handle_ls_matches(input, format, &query)?;
+            }
         }
         Some(Commands::Cat {
             input,
             field,
             format,
             blocks,
+            filter,
             progress,
         }) => {
-            handle_cat(input, field, format, blocks, progress)?;
+            handle_cat(input, field, format, blocks, filter, progress)?;
+        }
+        Some(Commands::Agg {
+            input,
+            group_by,
+            count,
+            sum,
+            min,
+            max,
+            distinct,
+        }) => {
+            let mut aggregates = Vec::new();
+            if count {
+                aggregates.push(Aggregate::Count);
+            }
+            aggregates.extend(sum.into_iter().map(Aggregate::Sum));
+            aggregates.extend(min.into_iter().map(Aggregate::Min));
+            aggregates.extend(max.into_iter().map(Aggregate::Max));
+            aggregates.extend(distinct.into_iter().map(Aggregate::DistinctApprox));
+            handle_agg(input, group_by, aggregates)?;
+        }
+        Some(Commands::Count { input, field }) => {
+            handle_count(input, field)?;
+        }
+        Some(Commands::Stat {
+            input,
+            field,
+            top,
+            estimate,
+        }) => {
+            handle_stat(input, field, top, estimate)?;
+        }
+        Some(Commands::Meta { input, output }) => {
+            handle_meta(input, output)?;
+        }
+        Some(Commands::Rewrite {
+            input,
+            output,
+            drop_older_than,
+            timestamp_field,
+            timestamp_unit,
+        }) => {
+            handle_rewrite(
+                input,
+                output,
+                drop_older_than,
+                timestamp_field,
+                timestamp_unit.into(),
+            )?;
+        }
+        Some(Commands::Index { input }) => {
+            handle_index(input)?;
+        }
+        Some(Commands::GenVectors { out_dir }) => {
+            handle_gen_vectors(out_dir)?;
+        }
+        Some(Commands::CheckVectors { dir }) => {
+            handle_check_vectors(dir)?;
+        }
+        #[cfg(feature = "query")]
+        Some(Commands::Query {
+            input,
+            query,
+            verbose,
+        }) => {
+            handle_query(input, query, verbose)?;
+        }
+        #[cfg(feature = "tui")]
+        Some(Commands::Tui { input }) => {
+            tui::run(JacReader::open_snapshot(input, snapshot_decompress_opts())?)?;
+        }
+        #[cfg(feature = "serve")]
+        Some(Commands::Serve {
+            dir,
+            port,
+            bind,
+            workers,
+            max_records,
+        }) => {
+            serve::run(serve::ServeConfig {
+                root: dir,
+                bind: format!("{}:{}", bind, port),
+                workers,
+                max_records,
+            })?;
         }
         None => {
             return Err(
@@ -530,40 +1344,105 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
-fn handle_pack(
-    input: PathBuf,
-    output: PathBuf,
-    mut block_records: usize,
-    mut zstd_level: u8,
-    canonicalize_keys: bool,
-    canonicalize_numbers: bool,
-    max_dict_entries: usize,
-    emit_index: bool,
-    force_ndjson: bool,
-    force_json_array: bool,
-    show_progress: bool,
-    threads: Option<usize>,
-    parallel_memory_factor: Option<f64>,
-    max_segment_bytes: Option<u64>,
-    allow_large_segments: bool,
-    verbose_metrics: bool,
-    wrapper_pointer: Option<String>,
-    wrapper_depth: Option<usize>,
-    wrapper_buffer: Option<String>,
-    wrapper_sections: Option<Vec<String>>,
-    wrapper_section_pointer: Option<Vec<String>>,
-    wrapper_section_label_field: Option<String>,
-    wrapper_section_no_label: bool,
-    wrapper_sections_missing_error: bool,
-    wrapper_map: bool,
-    wrapper_map_pointer: Option<String>,
-    wrapper_map_key_field: Option<String>,
-    wrapper_map_overwrite_key: bool,
-    wrapper_array_headers: bool,
-) -> Result<(), Box<dyn Error>> {
+fn handle_pack(args: PackArgs) -> Result<(), Box<dyn Error>> {
+    let PackArgs {
+        input,
+        output,
+        mut block_records,
+        zstd_level,
+        canonicalize_keys,
+        canonicalize_numbers,
+        max_dict_entries,
+        dict_max_ratio,
+        no_dict_field,
+        field_hint,
+        no_index,
+        ndjson: force_ndjson,
+        json_array: force_json_array,
+        progress: show_progress,
+        threads,
+        parallel_memory_factor,
+        max_segment_bytes,
+        allow_large_segments,
+        verbose_metrics,
+        wrapper_pointer,
+        wrapper_depth,
+        wrapper_buffer,
+        wrapper_sections,
+        wrapper_section_pointer,
+        wrapper_section_prefix,
+        wrapper_section_limit,
+        wrapper_section_label_field,
+        wrapper_section_no_label,
+        wrapper_sections_missing_error,
+        wrapper_map,
+        wrapper_map_pointer,
+        wrapper_map_key_field,
+        wrapper_map_inner_key_field,
+        wrapper_map_nested_keys,
+        wrapper_map_overwrite_key,
+        wrapper_array_headers,
+        wrapper_array_headers_type_row,
+        wrapper_array_headers_null_marker,
+        preserve_key_order,
+        utf8_mode,
+        max_record_bytes,
+        skip_oversized_records,
+        options_file,
+        profile,
+        fast,
+        balanced,
+        max_compression,
+        fail_on_empty,
+        normalize_keys,
+        key_collision,
+        intern_field_names,
+        header_crc,
+        field_stats,
+        quantile_sketches,
+        dedupe,
+        dedupe_key,
+        dedupe_window,
+        max_output_bytes,
+        finalize_on_interrupt,
+        force_treat_as_text,
+    } = args;
+    let emit_index = !no_index;
+    let (mut zstd_level, auto_zstd_level) = match zstd_level {
+        ZstdLevelArg::Auto => (6, true),
+        ZstdLevelArg::Fixed(level) => (level, false),
+    };
+    let profile = if fast {
+        Some(ProfileMode::Fast)
+    } else if balanced {
+        Some(ProfileMode::Balanced)
+    } else if max_compression {
+        Some(ProfileMode::Max)
+    } else {
+        profile
+    }
+    .map(CompressionProfile::from);
+    let key_normalization = key_normalization_from_args(&normalize_keys, key_collision);
+    let dedupe = dedupe_from_args(dedupe, dedupe_key, dedupe_window);
     let start = Instant::now();
 
+    if !force_treat_as_text && starts_with_jac_magic(&input)? {
+        return Err(usage_error(format!(
+            "{} is already a JAC file; pack expects JSON input. To re-encode it, unpack it first \
+             (jac unpack {} -o records.ndjson, then jac pack records.ndjson -o ...), or pass \
+             --force-treat-as-text to pack its bytes as text anyway",
+            input.display(),
+            input.display()
+        )));
+    }
+
+    // An options file or profile replaces the built-in defaults; explicit flags still win
+    let file_options = match options_file {
+        Some(ref path) => Some(load_options_file(path)?),
+        None => profile.map(CompressOptions::from_profile),
+    };
+    let from_file = file_options.is_some();
+
     // Load configuration file early so we can apply defaults
     let config = match load_config() {
         Ok(c) => c,
@@ -577,33 +1456,39 @@ fn handle_pack(
     // Apply compression config defaults if values weren't explicitly set
     // Note: CLI uses clap defaults, so we need to check if user actually provided them
     // For now, we'll apply config values if they differ from current defaults
+    // (an options file or profile takes the place of these defaults)
     if let Some(config_block_records) = config.compression.default_block_records {
         // Only apply if we're using the standard default (100000)
-        if block_records == 100_000 {
+        if block_records == 100_000 && !from_file {
             block_records = config_block_records;
         }
     }
 
     if let Some(config_zstd_level) = config.compression.default_zstd_level {
         // Only apply if we're using the standard default (6)
-        if zstd_level == 6 {
+        if zstd_level == 6 && !from_file {
             zstd_level = config_zstd_level;
         }
     }
 
-    let mut limits = Limits::default();
+    let mut limits = file_options
+        .as_ref()
+        .map(|opts| opts.limits.clone())
+        .unwrap_or_default();
     if let Some(bytes) = max_segment_bytes {
         if bytes == 0 {
-            return Err("--max-segment-bytes must be greater than zero".into());
+            return Err(usage_error("--max-segment-bytes must be greater than zero"));
         }
         let default_limit = Limits::default().max_segment_uncompressed_len as u64;
         if bytes > default_limit && !allow_large_segments {
-            return Err(
-                "--max-segment-bytes above 67108864 requires --allow-large-segments".into(),
-            );
+            return Err(usage_error(
+                "--max-segment-bytes above 67108864 requires --allow-large-segments",
+            ));
         }
         if bytes > usize::MAX as u64 {
-            return Err("--max-segment-bytes exceeds platform usize range".into());
+            return Err(usage_error(
+                "--max-segment-bytes exceeds platform usize range",
+            ));
         }
         if bytes > default_limit {
             eprintln!(
@@ -616,7 +1501,10 @@ fn handle_pack(
 
     // Track whether users explicitly tweaked parallel heuristics.
     let explicit_memory_factor = parallel_memory_factor.is_some();
-    let mut parallel_config = ParallelConfig::default();
+    let mut parallel_config = file_options
+        .as_ref()
+        .map(|opts| opts.parallel_config)
+        .unwrap_or_default();
     let mut memory_factor = parallel_config.memory_reservation_factor;
     let mut env_memory_override = false;
 
@@ -627,9 +1515,9 @@ fn handle_pack(
                 format!("JAC_PARALLEL_MEMORY_FACTOR must be a positive number (received '{value}')")
             })?;
             if !parsed.is_finite() || parsed <= 0.0 {
-                return Err(
-                    "JAC_PARALLEL_MEMORY_FACTOR must be greater than zero and finite".into(),
-                );
+                return Err(usage_error(
+                    "JAC_PARALLEL_MEMORY_FACTOR must be greater than zero and finite",
+                ));
             }
             memory_factor = parsed.min(1.0);
             env_memory_override = true;
@@ -638,7 +1526,9 @@ fn handle_pack(
 
     if let Some(flag_factor) = parallel_memory_factor {
         if !flag_factor.is_finite() || flag_factor <= 0.0 {
-            return Err("--parallel-memory-factor must be greater than zero and finite".into());
+            return Err(usage_error(
+                "--parallel-memory-factor must be greater than zero and finite",
+            ));
         }
         memory_factor = flag_factor.min(1.0);
         env_memory_override = false;
@@ -649,7 +1539,7 @@ fn handle_pack(
     let explicit_threads = threads.is_some();
     if let Some(thread_cap) = threads {
         if thread_cap == 0 {
-            return Err("--threads must be greater than zero".into());
+            return Err(usage_error("--threads must be greater than zero"));
         }
         parallel_config.max_threads = Some(thread_cap);
     }
@@ -657,21 +1547,82 @@ fn handle_pack(
     // Store segment limit before moving limits into options
     let segment_limit = limits.max_segment_uncompressed_len;
 
-    let (input_source, container_hint) =
+    let (input_source, container_hint, detection) =
         resolve_input_source(&input, force_ndjson, force_json_array)?;
-    let options = CompressOptions {
+    let mut options = file_options.unwrap_or_else(|| CompressOptions {
         block_target_records: block_records,
         default_codec: Codec::Zstd(zstd_level),
-        canonicalize_keys,
-        canonicalize_numbers,
-        nested_opaque: true,
         max_dict_entries,
-        limits,
-        parallel_config,
-    };
+        ..CompressOptions::default()
+    });
+    // Flags left at their defaults keep the options file or profile values
+    if block_records != 100_000 {
+        options.block_target_records = block_records;
+    }
+    if zstd_level != 6 {
+        options.default_codec = Codec::Zstd(zstd_level);
+    }
+    if max_dict_entries != 4096 {
+        options.max_dict_entries = max_dict_entries;
+    }
+    if let Some(ratio) = dict_max_ratio {
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(usage_error("--dict-max-ratio must be between 0 and 1"));
+        }
+        options.dictionary_policy.max_cardinality_ratio = ratio;
+    }
+    for (field, hint) in parse_field_hints(field_hint)? {
+        options.field_hints.insert(field, hint);
+    }
+    for field in no_dict_field.unwrap_or_default() {
+        options.field_hints.entry(field).or_default().dictionary = Some(DictionaryMode::Never);
+    }
+    if utf8_mode != Utf8Mode::Strict {
+        options.utf8_policy = utf8_mode.into();
+    }
+    if max_record_bytes.is_some() {
+        options.max_record_bytes = max_record_bytes;
+    }
+    if skip_oversized_records {
+        options.oversized_record_policy = OversizedRecordPolicy::Skip;
+    }
+    options.canonicalize_keys |= canonicalize_keys;
+    options.preserve_key_order |= preserve_key_order;
+    options.canonicalize_numbers |= canonicalize_numbers;
+    options.limits = limits;
+    options.parallel_config = parallel_config;
+    if auto_zstd_level {
+        options
+            .zstd_auto_level
+            .get_or_insert_with(AutoLevelPolicy::default);
+    }
+    if fail_on_empty {
+        options.allow_empty = false;
+    }
+    if let Some(key_normalization) = key_normalization {
+        options.key_normalization = key_normalization;
+    }
+    if intern_field_names {
+        options.intern_field_names = true;
+    }
+    if header_crc {
+        options.block_header_crc = true;
+    }
+    if field_stats {
+        options.field_stats = true;
+    }
+    if quantile_sketches {
+        options.quantile_sketches = true;
+    }
+    if dedupe.is_some() {
+        options.dedupe = dedupe;
+    }
+    if max_output_bytes.is_some() {
+        options.max_output_bytes = max_output_bytes;
+    }
 
     // Parse wrapper configuration if provided
-    use jac_io::{MissingSectionBehavior, SectionSpec, WrapperConfig, WrapperLimits};
+    use jac_io::{MissingSectionBehavior, SectionSpec, WrapperLimits};
 
     // Config file already loaded earlier; check for environment variable overrides and config file settings
     let debug_wrapper = std::env::var("JAC_DEBUG_WRAPPER")
@@ -732,14 +1683,13 @@ fn handle_pack(
     if let Some(depth) = wrapper_depth {
         let hard_max_depth = WrapperLimits::hard_maximums().max_depth;
         if depth == 0 {
-            return Err("--wrapper-depth must be at least 1".into());
+            return Err(usage_error("--wrapper-depth must be at least 1"));
         }
         if depth > hard_max_depth {
-            return Err(format!(
+            return Err(usage_error(format!(
                 "--wrapper-depth {} exceeds hard maximum {} (security limit)",
                 depth, hard_max_depth
-            )
-            .into());
+            )));
         }
         wrapper_limits.max_depth = depth;
     }
@@ -749,15 +1699,14 @@ fn handle_pack(
         let buffer_bytes = parse_size(&buffer_str)?;
         let hard_max_buffer = WrapperLimits::hard_maximums().max_buffer_bytes;
         if buffer_bytes == 0 {
-            return Err("--wrapper-buffer must be greater than zero".into());
+            return Err(usage_error("--wrapper-buffer must be greater than zero"));
         }
         if buffer_bytes > hard_max_buffer {
-            return Err(format!(
+            return Err(usage_error(format!(
                 "--wrapper-buffer {} exceeds hard maximum {} (security limit)",
                 format_size(buffer_bytes),
                 format_size(hard_max_buffer)
-            )
-            .into());
+            )));
         }
         wrapper_limits.max_buffer_bytes = buffer_bytes;
     }
@@ -781,20 +1730,25 @@ fn handle_pack(
     } else if let Some(section_names) = wrapper_sections {
         // Parse sections configuration
 
-        // Parse custom pointers for sections
-        let mut custom_pointers: HashMap<String, String> = HashMap::new();
-        if let Some(pointer_specs) = wrapper_section_pointer {
-            for spec in pointer_specs {
-                let parts: Vec<&str> = spec.splitn(2, '=').collect();
-                if parts.len() != 2 {
-                    return Err(format!(
-                        "Invalid section pointer format '{}'. Expected: name=/pointer/path",
-                        spec
-                    )
-                    .into());
-                }
-                custom_pointers.insert(parts[0].to_string(), parts[1].to_string());
-            }
+        // Parse custom pointers, field prefixes and record limits for sections
+        let custom_pointers = parse_section_settings(
+            wrapper_section_pointer,
+            "section pointer",
+            "name=/pointer/path",
+        )?;
+        let field_prefixes =
+            parse_section_settings(wrapper_section_prefix, "section prefix", "name=prefix")?;
+        let mut record_limits: HashMap<String, usize> = HashMap::new();
+        for (name, count) in
+            parse_section_settings(wrapper_section_limit, "section limit", "name=count")?
+        {
+            let count = count.parse().map_err(|_| {
+                usage_error(format!(
+                    "Invalid record count '{}' for section '{}'",
+                    count, name
+                ))
+            })?;
+            record_limits.insert(name, count);
         }
 
         // Build section specs
@@ -809,11 +1763,15 @@ fn handle_pack(
                 name: name.clone(),
                 pointer,
                 label: None, // Use section name as label by default
+                field_prefix: field_prefixes.get(&name).cloned(),
+                max_records: record_limits.get(&name).copied(),
             });
         }
 
         if sections.is_empty() {
-            return Err("--wrapper-sections requires at least one section name".into());
+            return Err(usage_error(
+                "--wrapper-sections requires at least one section name",
+            ));
         }
 
         let missing_behavior = if wrapper_sections_missing_error {
@@ -856,12 +1814,18 @@ fn handle_pack(
             eprintln!("🔍 Wrapper config: KeyedMap mode");
             eprintln!("  - Pointer: {}", if pointer.is_empty() { "/" } else { &pointer });
             eprintln!("  - Key field: {}", key_field);
+            if let Some(inner_key_field) = &wrapper_map_inner_key_field {
+                eprintln!("  - Inner key field: {}", inner_key_field);
+            }
+            eprintln!("  - Nested key fields: {}", wrapper_map_nested_keys);
             eprintln!("  - Collision mode: {:?}", collision_mode);
         }
 
         WrapperConfig::KeyedMap {
             pointer,
             key_field,
+            inner_key_field: wrapper_map_inner_key_field,
+            nested_key_fields: wrapper_map_nested_keys,
             limits: wrapper_limits,
             collision_mode,
         }
@@ -871,28 +1835,53 @@ fn handle_pack(
         if debug_wrapper {
             eprintln!("🔍 Wrapper config: ArrayWithHeaders mode");
             eprintln!("  - Buffer limit: {} bytes", wrapper_limits.max_buffer_bytes);
+            eprintln!("  - Type row: {}", wrapper_array_headers_type_row);
+            eprintln!("  - Null markers: {:?}", wrapper_array_headers_null_marker);
         }
 
         WrapperConfig::ArrayWithHeaders {
             limits: wrapper_limits,
+            type_row: wrapper_array_headers_type_row,
+            null_markers: wrapper_array_headers_null_marker.unwrap_or_default(),
         }
     } else {
         if debug_wrapper {
             eprintln!("🔍 Wrapper config: None (standard mode)");
         }
+        if let Some(detection) = &detection {
+            // Refuse only an envelope seen whole, and not in a file named as
+            // NDJSON; anything less certain gets the hint below instead
+            if detection.format == InputFormat::SingleObject
+                && detection.suggested_wrapper.is_some()
+                && !detection.truncated
+                && detection.confidence >= MIN_ENVELOPE_CONFIDENCE
+                && extension_format(&input) != Some(ContainerFormat::Ndjson)
+            {
+                let suggestions = suggest_wrappers(File::open(&input)?)?;
+                return Err(usage_error(single_object_error(&suggestions)));
+            }
+            if let Some(flags) = detection.suggested_wrapper.as_ref().and_then(wrapper_flags) {
+                eprintln!(
+                    "Hint: the input looks like an envelope around its records; {} would extract them",
+                    flags
+                );
+            }
+        }
         WrapperConfig::None
     };
 
-    let request = CompressRequest {
-        input: input_source,
-        output: OutputSink::Path(output.clone()),
-        options,
-        container_hint,
-        emit_index,
-        wrapper_config,
-    };
+    let request = CompressRequest::builder()
+        .input(input_source)
+        .output(OutputSink::Path(output.clone()))
+        .options(options)
+        .container_hint(container_hint)
+        .emit_index(emit_index)
+        .wrapper_config(wrapper_config)
+        .interrupt(interrupt_flag())
+        .build()?;
 
     let mut progress_bar = show_progress.then(|| create_spinner("Compressing records"));
+    let _partial_output = PartialOutput::register(&output);
 
     // Execute compression with enhanced error handling for segment limits
     let summary = match execute_compress(request) {
@@ -936,11 +1925,40 @@ fn handle_pack(
                 );
             }
 
-            return Err(format!("Segment limit exceeded: {}", msg).into());
+            return Err(jac_io::JacError::LimitExceeded(msg.clone()).into());
         }
         Err(e) => return Err(e.into()),
     };
 
+    if summary.interrupted {
+        if let Some(pb) = progress_bar.take() {
+            pb.finish_and_clear();
+        }
+        if finalize_on_interrupt {
+            report_compress_summary(
+                &summary,
+                &output,
+                verbose_metrics,
+                block_records,
+                segment_limit,
+            )?;
+            eprintln!(
+                "Interrupted: finalized {} with the {} records read so far",
+                output.display(),
+                summary.metrics.records_written
+            );
+        } else {
+            for path in &summary.output_files {
+                std::fs::remove_file(path)?;
+            }
+            eprintln!(
+                "Interrupted: removed partial output {} (use --finalize-on-interrupt to keep it)",
+                output.display()
+            );
+        }
+        return Err(jac_io::JacError::Interrupted.into());
+    }
+
     let decision_to_report = summary.parallel_decision.clone();
     let should_report_decision =
         verbose_metrics || explicit_threads || explicit_memory_factor || env_memory_override;
@@ -976,31 +1994,91 @@ fn handle_pack(
     Ok(())
 }
 
-fn handle_unpack(
-    input: PathBuf,
-    output: PathBuf,
+/// Resolve `jac unpack` output flags into a format.
+fn unpack_format(
     force_ndjson: bool,
     force_json_array: bool,
-    show_progress: bool,
-) -> Result<(), Box<dyn Error>> {
-    let start = Instant::now();
+    msgpack: bool,
+    cbor: bool,
+) -> Result<DecompressFormat, Box<dyn Error>> {
     if force_ndjson && force_json_array {
-        return Err("--ndjson and --json-array are mutually exclusive".into());
+        return Err(usage_error(
+            "--ndjson and --json-array are mutually exclusive",
+        ));
     }
-    let format = if force_ndjson {
+    Ok(if force_ndjson {
         DecompressFormat::Ndjson
     } else if force_json_array {
         DecompressFormat::JsonArray
+    } else if msgpack {
+        DecompressFormat::MessagePack
+    } else if cbor {
+        DecompressFormat::Cbor
     } else {
         DecompressFormat::Auto
-    };
+    })
+}
 
-    let request = DecompressRequest {
-        input: JacInput::Path(input.clone()),
-        output: OutputSink::Path(output.clone()),
-        format,
-        options: DecompressOptions::default(),
+fn handle_unpack(args: UnpackArgs) -> Result<(), Box<dyn Error>> {
+    let UnpackArgs {
+        input,
+        output,
+        ndjson,
+        json_array,
+        msgpack,
+        cbor,
+        rename,
+        schema_map,
+        blocks,
+        require_pointer,
+        filter,
+        permissive,
+        skip_nested_validation,
+        canonical,
+        crlf,
+        encoding,
+        validate_utf8,
+        progress: show_progress,
+        verbose_metrics,
+    } = args;
+    let format = unpack_format(ndjson, json_array, msgpack, cbor)?;
+    let line_ending = if crlf {
+        LineEnding::CrLf
+    } else {
+        LineEnding::Lf
     };
+    let encoding = TextEncoding::from(encoding);
+    let start = Instant::now();
+    let rename = parse_rename_map(&rename)?;
+    let schema_mapping = schema_map
+        .map(|path| SchemaMapping::load(&path))
+        .transpose()?;
+
+    let mut request = DecompressRequest::builder()
+        .input(JacInput::Path(input.clone()))
+        .output(OutputSink::Path(output.clone()))
+        .format(format)
+        .options(DecompressOptions {
+            strict: !permissive,
+            schema_mapping,
+            validate_nested_json: !skip_nested_validation,
+            canonical_json: canonical,
+            ..DecompressOptions::default()
+        })
+        .rename(rename)
+        .line_ending(line_ending)
+        .encoding(encoding)
+        .validate_utf8(validate_utf8);
+    if let Some(range) = parse_block_range(blocks)? {
+        request = request.blocks(range);
+    }
+    if let Some(pointer) = require_pointer {
+        request = request.require_pointer(pointer);
+    }
+    if let Some(filter) = filter {
+        request = request.filter(Predicate::parse(&filter)?);
+    }
+    let request = request.build()?;
 
     let mut progress_bar = show_progress.then(|| create_spinner("Decompressing records"));
     let summary = execute_decompress(request)?;
@@ -1021,133 +2099,308 @@ fn handle_unpack(
                 .unwrap_or_default()
         ));
     }
-    report_decompress_summary(&summary, &output, elapsed, rec_rate, mb_rate)?;
+    report_decompress_summary(
+        &summary,
+        &output,
+        elapsed,
+        rec_rate,
+        mb_rate,
+        verbose_metrics,
+    )?;
     Ok(())
 }
 
+/// Whether `path` is a regular file starting with the JAC file magic. Other
+/// inputs (FIFOs, devices) are not read ahead.
+fn starts_with_jac_magic(path: &Path) -> std::io::Result<bool> {
+    if !path.metadata().map(|meta| meta.is_file()).unwrap_or(false) {
+        return Ok(false);
+    }
+    let mut magic = [0u8; 4];
+    match File::open(path)?.read_exact(&mut magic) {
+        Ok(()) => Ok(magic == jac_format::constants::FILE_MAGIC),
+        Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// How records are read from an input, its container format, and what
+/// sniffing it found (unless the format was forced).
+type ResolvedInput = (InputSource, Option<ContainerFormat>, Option<InputDetection>);
+
 fn resolve_input_source(
     path: &Path,
     force_ndjson: bool,
     force_json_array: bool,
-) -> Result<(InputSource, Option<ContainerFormat>), Box<dyn Error>> {
+) -> Result<ResolvedInput, Box<dyn Error>> {
+    /// Below this, the extension decides instead of the sniffed format
+    const MIN_DETECTION_CONFIDENCE: f64 = 0.3;
+
     if force_ndjson && force_json_array {
-        return Err("--ndjson and --json-array are mutually exclusive".into());
+        return Err(usage_error(
+            "--ndjson and --json-array are mutually exclusive",
+        ));
     }
 
     if force_ndjson {
         return Ok((
             InputSource::NdjsonPath(path.to_path_buf()),
             Some(ContainerFormat::Ndjson),
+            None,
         ));
     }
     if force_json_array {
         return Ok((
             InputSource::JsonArrayPath(path.to_path_buf()),
             Some(ContainerFormat::JsonArray),
+            None,
         ));
     }
 
-    if let Some(format) = detect_container_format(path)? {
-        let source = match format {
-            ContainerFormat::Ndjson => InputSource::NdjsonPath(path.to_path_buf()),
-            ContainerFormat::JsonArray => InputSource::JsonArrayPath(path.to_path_buf()),
-            ContainerFormat::Unknown => {
-                unreachable!("detect_container_format never returns Unknown")
-            }
-        };
-        return Ok((source, Some(format)));
-    }
-
-    let format = match path.extension().and_then(|ext| ext.to_str()) {
-        Some(ext) if ext.eq_ignore_ascii_case("json") => ContainerFormat::JsonArray,
-        Some(ext) if ext.eq_ignore_ascii_case("ndjson") || ext.eq_ignore_ascii_case("jsonl") => {
-            ContainerFormat::Ndjson
-        }
-        _ => ContainerFormat::Ndjson,
-    };
+    let detection = detect_input_format(File::open(path)?)?;
+    let by_extension = extension_format(path);
+    // A first value cut off by the sniffed prefix says little about the rest
+    let detected = detection.container_format().filter(|_| {
+        detection.confidence >= MIN_DETECTION_CONFIDENCE
+            && !(detection.truncated && by_extension.is_some())
+    });
+    let format = detected.or(by_extension).unwrap_or(ContainerFormat::Ndjson);
 
     let source = match format {
-        ContainerFormat::Ndjson => InputSource::NdjsonPath(path.to_path_buf()),
         ContainerFormat::JsonArray => InputSource::JsonArrayPath(path.to_path_buf()),
-        ContainerFormat::Unknown => unreachable!(),
+        _ => InputSource::NdjsonPath(path.to_path_buf()),
     };
 
-    Ok((source, Some(format)))
+    Ok((source, Some(format), Some(detection)))
 }
 
-fn detect_container_format(path: &Path) -> Result<Option<ContainerFormat>, Box<dyn Error>> {
-    const DETECTION_LIMIT: usize = 4096;
-    let mut file = File::open(path)?;
-    let mut buffer = Vec::with_capacity(512);
-    let mut chunk = [0u8; 512];
+/// Lowest detection confidence at which pack refuses a single envelope
+/// object instead of hinting at a wrapper
+const MIN_ENVELOPE_CONFIDENCE: f64 = 0.8;
+
+/// Container format named by the file extension of `path`, if any.
+fn extension_format(path: &Path) -> Option<ContainerFormat> {
+    let ext = path.extension()?.to_str()?;
+    if ext.eq_ignore_ascii_case("json") {
+        Some(ContainerFormat::JsonArray)
+    } else if ext.eq_ignore_ascii_case("ndjson") || ext.eq_ignore_ascii_case("jsonl") {
+        Some(ContainerFormat::Ndjson)
+    } else {
+        None
+    }
+}
 
-    loop {
-        let read = file.read(&mut chunk)?;
-        if read == 0 {
-            return Ok(None);
+/// Error for a single top-level object that looks like an envelope, naming
+/// the wrappers that would extract its records.
+fn single_object_error(suggestions: &[WrapperSuggestion]) -> String {
+    let candidates: Vec<String> = suggestions
+        .iter()
+        .filter_map(|suggestion| {
+            let records = suggestion.records_seen;
+            wrapper_flags(&suggestion.wrapper).map(|flags| {
+                format!(
+                    "{} ({} record{} seen)",
+                    flags,
+                    records,
+                    if records == 1 { "" } else { "s" }
+                )
+            })
+        })
+        .collect();
+    format!(
+        "input is a single JSON object, not a sequence of records, and looks like an envelope. \
+         Candidates in the first 64 KiB: {}. Pass one of these, or --json-array to pack the \
+         object as a single record",
+        candidates.join(", ")
+    )
+}
+
+/// Parse repeated `--field-hint FIELD=HINTS` flags.
+fn parse_field_hints(
+    specs: Option<Vec<String>>,
+) -> Result<HashMap<String, FieldEncodingHint>, Box<dyn Error>> {
+    let mut hints = HashMap::new();
+    for (field, names) in parse_section_settings(specs, "--field-hint", "FIELD=HINTS")? {
+        let mut hint = FieldEncodingHint::default();
+        for name in names.split(',').map(str::trim) {
+            match name {
+                "delta" => hint.prefer_delta = true,
+                "no-dict" => hint.dictionary = Some(DictionaryMode::Never),
+                "dict" => hint.dictionary = Some(DictionaryMode::Always),
+                _ => {
+                    return Err(usage_error(format!(
+                        "Unknown field hint '{}' for '{}'. Expected: delta, no-dict or dict",
+                        name, field
+                    )))
+                }
+            }
         }
-        buffer.extend_from_slice(&chunk[..read]);
-        if let Some(format) = interpret_leading_bytes(&buffer) {
-            return Ok(Some(format));
+        hints.insert(field, hint);
+    }
+    Ok(hints)
+}
+
+/// Parse repeated `name=value` settings of `--wrapper-section-*` and
+/// `--field-hint` flags.
+fn parse_section_settings(
+    specs: Option<Vec<String>>,
+    what: &str,
+    expected: &str,
+) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let mut settings = HashMap::new();
+    for spec in specs.unwrap_or_default() {
+        let Some((name, value)) = spec.split_once('=') else {
+            return Err(usage_error(format!(
+                "Invalid {} format '{}'. Expected: {}",
+                what, spec, expected
+            )));
+        };
+        settings.insert(name.to_string(), value.to_string());
+    }
+    Ok(settings)
+}
+
+/// `jac pack` flags selecting `wrapper`, for hints.
+fn wrapper_flags(wrapper: &WrapperConfig) -> Option<String> {
+    match wrapper {
+        WrapperConfig::Pointer { path, .. } => Some(format!("--wrapper-pointer '{}'", path)),
+        WrapperConfig::KeyedMap { pointer, .. } if pointer.is_empty() => {
+            Some("--wrapper-map".to_string())
         }
-        if buffer.len() >= DETECTION_LIMIT {
-            break;
+        WrapperConfig::KeyedMap { pointer, .. } => {
+            Some(format!("--wrapper-map --wrapper-map-pointer '{}'", pointer))
         }
+        WrapperConfig::ArrayWithHeaders { .. } => Some("--wrapper-array-headers".to_string()),
+        _ => None,
     }
-
-    Ok(None)
 }
 
-fn interpret_leading_bytes(buf: &[u8]) -> Option<ContainerFormat> {
-    const BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+fn report_compress_summary(
+    summary: &CompressSummary,
+    output: &Path,
+    verbose_metrics: bool,
+    _block_records: usize,
+    segment_limit: usize,
+) -> Result<(), Box<dyn Error>> {
+    let mut stderr = std::io::stderr().lock();
+    let elapsed = summary.runtime_stats.wall_time;
+    let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+    let rec_rate = summary.metrics.records_written as f64 / secs;
+    let mb_rate = summary.metrics.bytes_written as f64 / (1024.0 * 1024.0) / secs;
+    writeln!(
+        &mut stderr,
+        "Compressed to {} (records: {}, blocks: {}, bytes written: {}, elapsed: {:.2?}, {:.1} rec/s, {:.2} MiB/s, segment flushes: {}, record rejects: {})",
+        output.display(),
+        summary.metrics.records_written,
+        summary.metrics.blocks_written,
+        summary.metrics.bytes_written,
+        elapsed,
+        rec_rate,
+        mb_rate,
+        summary.metrics.segment_limit_flushes,
+        summary.metrics.segment_limit_record_rejections
+    )?;
 
-    if buf.len() < BOM.len() && buf == &BOM[..buf.len()] {
-        return None;
+    if summary.output_files.len() > 1 {
+        writeln!(
+            &mut stderr,
+            "Split at --max-output-bytes into {} files:",
+            summary.output_files.len()
+        )?;
+        for path in &summary.output_files {
+            writeln!(&mut stderr, "  {}", path.display())?;
+        }
+    }
+
+    let utf8 = &summary.utf8_metrics;
+    if utf8.invalid_strings > 0 {
+        writeln!(
+            &mut stderr,
+            "Repaired invalid UTF-8: {} strings ({} sequences replaced, {} base64-wrapped)",
+            utf8.invalid_strings, utf8.replaced_sequences, utf8.base64_wrapped
+        )?;
+    }
+
+    if summary.oversized_records_skipped > 0 {
+        writeln!(
+            &mut stderr,
+            "Skipped {} records exceeding --max-record-bytes",
+            summary.oversized_records_skipped
+        )?;
+    }
+
+    if summary.dedupe_metrics.duplicates_dropped > 0 {
+        writeln!(
+            &mut stderr,
+            "Dropped {} duplicate records",
+            summary.dedupe_metrics.duplicates_dropped
+        )?;
+    }
+
+    let keys = &summary.key_normalization_metrics;
+    if keys.keys_renamed > 0 || keys.keys_dropped > 0 {
+        writeln!(
+            &mut stderr,
+            "Normalized {} keys into {} names ({} records with collisions, {} keys dropped)",
+            keys.keys_renamed,
+            keys.renamed.len(),
+            keys.collisions,
+            keys.keys_dropped
+        )?;
+        if verbose_metrics {
+            for (name, spellings) in &keys.renamed {
+                let spellings: Vec<&str> = spellings.iter().map(String::as_str).collect();
+                writeln!(&mut stderr, "  {} <- {}", name, spellings.join(", "))?;
+            }
+        }
+    }
+
+    if let Some(tuning) = &summary.level_tuning {
+        writeln!(
+            &mut stderr,
+            "Auto zstd level: {} (sampled {} records, {} bytes)",
+            tuning.chosen_level, tuning.sample_records, tuning.sample_bytes
+        )?;
+        if verbose_metrics {
+            for trial in &tuning.trials {
+                writeln!(
+                    &mut stderr,
+                    "  Level {}: {} bytes in {:.2?}",
+                    trial.level, trial.compressed_bytes, trial.elapsed
+                )?;
+            }
+        }
     }
 
-    let mut slice = buf;
-    if slice.starts_with(&BOM) {
-        slice = &slice[BOM.len()..];
+    let metrics = &summary.metrics;
+    if verbose_metrics && metrics.dictionary_hits + metrics.dictionary_misses > 0 {
+        writeln!(
+            &mut stderr,
+            "String dictionary lookups: {} hits, {} misses",
+            metrics.dictionary_hits, metrics.dictionary_misses
+        )?;
     }
-
-    for &byte in slice {
-        match byte {
-            b' ' | b'\t' | b'\r' | b'\n' => continue,
-            b'[' => return Some(ContainerFormat::JsonArray),
-            b'"' | b'-' | b'0'..=b'9' => return Some(ContainerFormat::Ndjson),
-            b'{' => return None,
-            _ => return Some(ContainerFormat::Ndjson),
-        }
+    if verbose_metrics && metrics.dictionary_spills > 0 {
+        writeln!(
+            &mut stderr,
+            "Dictionary spills: {} field segments stored raw after exceeding the dictionary limit",
+            metrics.dictionary_spills
+        )?;
     }
 
-    None
-}
-
-fn report_compress_summary(
-    summary: &CompressSummary,
-    output: &Path,
-    verbose_metrics: bool,
-    _block_records: usize,
-    segment_limit: usize,
-) -> Result<(), Box<dyn Error>> {
-    let mut stderr = std::io::stderr().lock();
-    let elapsed = summary.runtime_stats.wall_time;
-    let secs = elapsed.as_secs_f64().max(f64::EPSILON);
-    let rec_rate = summary.metrics.records_written as f64 / secs;
-    let mb_rate = summary.metrics.bytes_written as f64 / (1024.0 * 1024.0) / secs;
-    writeln!(
-        &mut stderr,
-        "Compressed to {} (records: {}, blocks: {}, bytes written: {}, elapsed: {:.2?}, {:.1} rec/s, {:.2} MiB/s, segment flushes: {}, record rejects: {})",
-        output.display(),
-        summary.metrics.records_written,
-        summary.metrics.blocks_written,
-        summary.metrics.bytes_written,
-        elapsed,
-        rec_rate,
-        mb_rate,
-        summary.metrics.segment_limit_flushes,
-        summary.metrics.segment_limit_record_rejections
-    )?;
+    let drifting: Vec<&str> = summary
+        .metrics
+        .drifting_fields()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if !drifting.is_empty() {
+        writeln!(
+            &mut stderr,
+            "Mixed value types in {} fields: {}",
+            drifting.len(),
+            drifting.join(", ")
+        )?;
+    }
 
     if let Some(peak) = summary.runtime_stats.peak_rss_bytes {
         let peak_mib = bytes_to_mib(peak);
@@ -1198,11 +2451,19 @@ fn report_compress_summary(
 
         for (field_name, metrics) in field_metrics {
             let max_mb = metrics.max_segment_size_seen as f64 / (1024.0 * 1024.0);
-            writeln!(
+            write!(
                 &mut stderr,
                 "  Field '{}': {} flushes, {} rejections, max segment {:.2} MiB",
                 field_name, metrics.flush_count, metrics.rejection_count, max_mb
             )?;
+            if metrics.dictionary_spills > 0 {
+                write!(
+                    &mut stderr,
+                    ", {} dictionary spills",
+                    metrics.dictionary_spills
+                )?;
+            }
+            writeln!(&mut stderr)?;
         }
     }
 
@@ -1210,6 +2471,14 @@ fn report_compress_summary(
     if let Some(metrics) = &summary.wrapper_metrics {
         writeln!(&mut stderr)?;
 
+        for (section, skipped) in &metrics.sections_truncated {
+            writeln!(
+                &mut stderr,
+                "Warning: section '{}' has {} more records than its limit; they were skipped",
+                section, skipped
+            )?;
+        }
+
         // Always show condensed wrapper metrics (mode and buffer usage)
         let buffer_mib = metrics.buffer_peak_bytes as f64 / (1024.0 * 1024.0);
         writeln!(
@@ -1298,12 +2567,24 @@ fn bytes_to_mib(bytes: u64) -> f64 {
     bytes as f64 / (1024.0 * 1024.0)
 }
 
+fn describe_filter_summary(summary: &FilterSummary) -> String {
+    format!(
+        "Filter (blocks scanned: {}, blocks pruned: {}, records matched: {}, dictionary entries matched: {} of {})",
+        summary.blocks_scanned,
+        summary.blocks_pruned,
+        summary.records_matched,
+        summary.dictionary_entries_matched,
+        summary.dictionary_entries_tested
+    )
+}
+
 fn report_decompress_summary(
     summary: &DecompressSummary,
     output: &Path,
     elapsed: Duration,
     rec_rate: f64,
     mb_rate: Option<f64>,
+    verbose_metrics: bool,
 ) -> Result<(), Box<dyn Error>> {
     let mut stderr = std::io::stderr().lock();
     let mut message = format!(
@@ -1319,6 +2600,37 @@ fn report_decompress_summary(
     }
     message.push(')');
     writeln!(&mut stderr, "{}", message)?;
+    if let Some(filter) = &summary.filter {
+        writeln!(&mut stderr, "{}", describe_filter_summary(filter))?;
+    }
+    if verbose_metrics {
+        let metrics = &summary.metrics;
+        writeln!(
+            &mut stderr,
+            "Read metrics (bytes read: {}, blocks decoded: {}, segments decompressed: {}, decode time: {:.2?}, crc32c: {})",
+            metrics.bytes_read,
+            metrics.blocks_decoded,
+            metrics.segments_decompressed,
+            metrics.decode_time,
+            jac_format::checksum::crc32c_backend()
+        )?;
+        #[cfg(feature = "latency-histogram")]
+        if let Some(p50) = metrics.block_latency.quantile(0.5) {
+            writeln!(
+                &mut stderr,
+                "  Block decode latency: p50 < {:.2?}, p99 < {:.2?}, max {:.2?}",
+                p50,
+                metrics.block_latency.quantile(0.99).unwrap_or(p50),
+                metrics.block_latency.max()
+            )?;
+            for (bound, count) in metrics.block_latency.buckets() {
+                writeln!(&mut stderr, "    < {:.2?}: {}", bound, count)?;
+            }
+        }
+    }
+    for warning in &summary.warnings {
+        writeln!(&mut stderr, "Warning: {}", warning)?;
+    }
     Ok(())
 }
 
@@ -1352,16 +2664,41 @@ struct DetailedFieldStats {
     null_count: usize,
     absent_values: usize,
     type_distribution: BTreeMap<String, usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    string_length: Option<StringLengthStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    int_range: Option<IntRange>,
+    /// Sampled decimals by scale (digits after the point as stored)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    decimal_scales: BTreeMap<u32, usize>,
     sample_size: usize,
     sampled: bool,
 }
 
+/// Lengths of the sampled strings, in bytes
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+struct StringLengthStats {
+    avg: f64,
+    max: usize,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+struct IntRange {
+    min: i64,
+    max: i64,
+}
+
 struct FieldStatsAccumulator {
     total_records: usize,
     present_values: usize,
     absent_values: usize,
     null_count: usize,
     type_distribution: BTreeMap<String, usize>,
+    strings: usize,
+    string_bytes: usize,
+    max_string_len: usize,
+    int_range: Option<IntRange>,
+    decimal_scales: BTreeMap<u32, usize>,
     sample_values: usize,
     sampled: bool,
     sample_limit: usize,
@@ -1382,57 +2719,52 @@ impl FieldStatsAccumulator {
             absent_values: 0,
             null_count: 0,
             type_distribution: BTreeMap::new(),
+            strings: 0,
+            string_bytes: 0,
+            max_string_len: 0,
+            int_range: None,
+            decimal_scales: BTreeMap::new(),
             sample_values: 0,
             sampled: false,
             sample_limit,
         }
     }
 
-    fn record_value(&mut self, value: &Value) {
+    /// Sample one present value, read from its typed column, up to the limit.
+    fn record_value(&mut self, value: ValueRef<'_>) {
         if self.sample_values >= self.sample_limit {
             self.sampled = true;
             return;
         }
-
         self.sample_values += 1;
 
         match value {
-            Value::Null => {
+            ValueRef::Null => {
                 self.null_count += 1;
+                return;
             }
-            Value::Bool(_) => {
-                *self
-                    .type_distribution
-                    .entry("bool".to_string())
-                    .or_default() += 1;
-            }
-            Value::Number(num) => {
-                let kind = if num.is_i64() || num.is_u64() {
-                    "int"
-                } else {
-                    "decimal"
-                };
-                *self.type_distribution.entry(kind.to_string()).or_default() += 1;
-            }
-            Value::String(_) => {
-                *self
-                    .type_distribution
-                    .entry("string".to_string())
-                    .or_default() += 1;
+            ValueRef::Int(int) => {
+                let range = self
+                    .int_range
+                    .get_or_insert(IntRange { min: int, max: int });
+                range.min = range.min.min(int);
+                range.max = range.max.max(int);
             }
-            Value::Array(_) => {
-                *self
-                    .type_distribution
-                    .entry("array".to_string())
-                    .or_default() += 1;
+            ValueRef::Decimal(decimal) => {
+                let scale = u32::try_from(-i64::from(decimal.exponent)).unwrap_or(0);
+                *self.decimal_scales.entry(scale).or_default() += 1;
             }
-            Value::Object(_) => {
-                *self
-                    .type_distribution
-                    .entry("object".to_string())
-                    .or_default() += 1;
+            ValueRef::Str(text) => {
+                self.strings += 1;
+                self.string_bytes += text.len();
+                self.max_string_len = self.max_string_len.max(text.len());
             }
+            ValueRef::Bool(_) | ValueRef::RawJson(_) => {}
         }
+        *self
+            .type_distribution
+            .entry(value.type_tag().name().to_string())
+            .or_default() += 1;
     }
 
     fn into_detailed(self, field_name: String) -> DetailedFieldStats {
@@ -1443,92 +2775,185 @@ impl FieldStatsAccumulator {
             null_count: self.null_count,
             absent_values: self.absent_values,
             type_distribution: self.type_distribution,
+            string_length: (self.strings > 0).then(|| StringLengthStats {
+                avg: self.string_bytes as f64 / self.strings as f64,
+                max: self.max_string_len,
+            }),
+            int_range: self.int_range,
+            decimal_scales: self.decimal_scales,
             sample_size: self.sample_values,
             sampled: self.sampled,
         }
     }
 }
 
-#[derive(Debug, Clone)]
-enum BlockRange {
-    Single(usize),
-    Range { start: usize, end: Option<usize> },
-}
-
-impl BlockRange {
-    fn into_bounds(self, block_count: usize) -> Result<(usize, usize), Box<dyn Error>> {
-        if block_count == 0 {
-            return Err("Block range requested, but file contains no blocks".into());
-        }
+fn handle_ls(
+    input: PathBuf,
+    format: LsFormat,
+    verbose: bool,
+    fields_only: bool,
+    blocks_only: bool,
+    stats: bool,
+    stats_sample: Option<usize>,
+) -> Result<(), Box<dyn Error>> {
+    let sample_limit = stats_sample.unwrap_or(STATS_SAMPLE_LIMIT_PER_FIELD);
+    if sample_limit == 0 {
+        return Err(usage_error("--stats-sample must be greater than 0"));
+    }
+    let inputs = expand_inputs(&input)?;
+    let mut stdout = std::io::stdout().lock();
+    let ls = |path: &Path, out: &mut dyn Write| {
+        ls_file(
+            path,
+            format,
+            verbose,
+            fields_only,
+            blocks_only,
+            stats,
+            sample_limit,
+            out,
+        )
+    };
 
-        match self {
-            BlockRange::Single(idx) => {
-                if idx >= block_count {
-                    return Err(format!(
-                        "Block {} exceeds file block count ({} blocks)",
-                        idx + 1,
-                        block_count
-                    )
-                    .into());
+    if inputs.len() == 1 {
+        return ls(&inputs[0], &mut stdout);
+    }
+    match format {
+        LsFormat::Table => {
+            for (idx, path) in inputs.iter().enumerate() {
+                if idx > 0 {
+                    writeln!(stdout)?;
                 }
-                Ok((idx, idx))
+                writeln!(stdout, "==> {} <==", path.display())?;
+                ls(path, &mut stdout)?;
             }
-            BlockRange::Range { start, end } => {
-                if start >= block_count {
-                    return Err(format!(
-                        "Block range start {} exceeds file block count ({} blocks)",
-                        start + 1,
-                        block_count
-                    )
-                    .into());
-                }
-                let end_idx = match end {
-                    Some(e) => e,
-                    None => block_count - 1,
-                };
-                if end_idx >= block_count {
-                    return Err(format!(
-                        "Block range end {} exceeds file block count ({} blocks)",
-                        end_idx + 1,
-                        block_count
-                    )
-                    .into());
-                }
-                if start > end_idx {
-                    return Err(format!(
-                        "Invalid block range: start ({}) > end ({})",
-                        start + 1,
-                        end_idx + 1
-                    )
-                    .into());
+        }
+        LsFormat::Json => {
+            let mut listings = Vec::with_capacity(inputs.len());
+            for path in &inputs {
+                let mut buf = Vec::new();
+                ls(path, &mut buf)?;
+                let mut listing = serde_json::Map::new();
+                listing.insert("file".to_string(), Value::from(path.display().to_string()));
+                if let Value::Object(fields) = serde_json::from_slice(&buf)? {
+                    listing.extend(fields);
                 }
-                Ok((start, end_idx))
+                listings.push(Value::Object(listing));
             }
+            serde_json::to_writer_pretty(&mut stdout, &listings)?;
+            writeln!(stdout)?;
         }
     }
+    Ok(())
 }
 
-fn handle_ls(
+fn handle_ls_matches(
     input: PathBuf,
     format: LsFormat,
+    query: &FieldQuery,
+) -> Result<(), Box<dyn Error>> {
+    let inputs = expand_inputs(&input)?;
+    let mut matches = Vec::new();
+    for path in &inputs {
+        let mut reader = JacReader::open_snapshot(path, snapshot_decompress_opts())?;
+        warn_index_status(path, &reader);
+        let fields = reader.find_fields(query)?;
+        if !fields.is_empty() {
+            matches.push((path, fields));
+        }
+    }
+    if matches.is_empty() {
+        return Err(usage_error("No files match the field filters"));
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    match format {
+        LsFormat::Table => {
+            for (path, fields) in &matches {
+                for entry in fields {
+                    if inputs.len() > 1 {
+                        write!(stdout, "{}\t", path.display())?;
+                    }
+                    write!(stdout, "{}\t{} blocks", entry.name, entry.blocks.len())?;
+                    if query.field_type.is_some() {
+                        let types: Vec<&str> = entry.types.iter().map(|tag| tag.name()).collect();
+                        write!(stdout, "\t{}", types.join(","))?;
+                    }
+                    writeln!(stdout)?;
+                }
+            }
+        }
+        LsFormat::Json => {
+            let listing = |fields: &[FieldCatalogEntry]| {
+                let fields: Vec<Value> = fields
+                    .iter()
+                    .map(|entry| {
+                        let blocks: Vec<usize> = entry.blocks.iter().map(|idx| idx + 1).collect();
+                        let mut field = serde_json::json!({
+                            "name": entry.name,
+                            "blocks": blocks,
+                            "values": entry.value_count,
+                        });
+                        if query.field_type.is_some() {
+                            let types: Vec<&str> =
+                                entry.types.iter().map(|tag| tag.name()).collect();
+                            field["types"] = Value::from(types);
+                        }
+                        field
+                    })
+                    .collect();
+                serde_json::json!({ "fields": fields })
+            };
+            let value = if inputs.len() == 1 {
+                listing(&matches[0].1)
+            } else {
+                Value::Array(
+                    matches
+                        .iter()
+                        .map(|(path, fields)| {
+                            let mut object = serde_json::Map::new();
+                            object.insert(
+                                "file".to_string(),
+                                Value::from(path.display().to_string()),
+                            );
+                            if let Value::Object(rest) = listing(fields) {
+                                object.extend(rest);
+                            }
+                            Value::Object(object)
+                        })
+                        .collect(),
+                )
+            };
+            serde_json::to_writer_pretty(&mut stdout, &value)?;
+            writeln!(stdout)?;
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn ls_file(
+    input: &Path,
+    format: LsFormat,
     verbose: bool,
     fields_only: bool,
     blocks_only: bool,
     stats: bool,
-    stats_sample: Option<usize>,
+    sample_limit: usize,
+    out: &mut dyn Write,
 ) -> Result<(), Box<dyn Error>> {
     let start = Instant::now();
-    let sample_limit = stats_sample.unwrap_or(STATS_SAMPLE_LIMIT_PER_FIELD);
-    if sample_limit == 0 {
-        return Err("--stats-sample must be greater than 0".into());
-    }
-    let file = File::open(&input)?;
+    let file = File::open(input)?;
     let options = DecompressOptions::default();
     let codec_opts = DecompressOpts {
         limits: options.limits.clone(),
         verify_checksums: options.verify_checksums,
+        strict: options.strict,
+        declared_limits: options.declared_limits,
+        ..DecompressOpts::default()
     };
-    let mut reader = JacReader::new(file, codec_opts)?;
+    let mut reader = JacReader::snapshot(file, codec_opts)?;
+    warn_index_status(input, &reader);
 
     let mut scan_spinner = if verbose || stats {
         Some(create_spinner("Scanning blocks"))
@@ -1549,6 +2974,9 @@ fn handle_ls(
 
         let mut field_summaries = Vec::with_capacity(block.header.fields.len());
         for field in &block.header.fields {
+            if field.is_key_order_sidecar() {
+                continue;
+            }
             all_fields.insert(field.field_name.clone());
             let ratio = if field.segment_uncompressed_len > 0 {
                 Some(field.segment_compressed_len as f64 / field.segment_uncompressed_len as f64)
@@ -1574,7 +3002,7 @@ fn handle_ls(
         summaries.push(BlockSummary {
             block_index: idx + 1,
             record_count: block.record_count,
-            field_count: block.header.fields.len(),
+            field_count: field_summaries.len(),
             compressed_size: block.size,
             fields: field_summaries,
         });
@@ -1619,6 +3047,12 @@ fn handle_ls(
             total_records,
             bytes_mib
         )?;
+        if !reader.is_finalized() {
+            writeln!(
+                &mut stderr,
+                "No index footer: listing fully written blocks only (file may still be in progress)"
+            )?;
+        }
         if let Some(stats_vec) = detailed_stats.as_ref() {
             if let Some(top) = stats_vec.iter().max_by_key(|s| s.present_values) {
                 writeln!(
@@ -1640,9 +3074,8 @@ fn handle_ls(
 
     match format {
         LsFormat::Table => {
-            let mut stdout = std::io::stdout().lock();
             print_ls_table(
-                &mut stdout,
+                out,
                 &summaries,
                 &sorted_fields,
                 verbose,
@@ -1653,9 +3086,8 @@ fn handle_ls(
             )?;
         }
         LsFormat::Json => {
-            let mut stdout = std::io::stdout().lock();
             print_ls_json(
-                &mut stdout,
+                out,
                 &summaries,
                 &sorted_fields,
                 verbose,
@@ -1811,104 +3243,504 @@ fn print_ls_json(
         root.insert("blocks".to_string(), serde_json::to_value(summaries)?);
     }
 
-    root.insert("verbose".to_string(), serde_json::Value::Bool(verbose));
-
-    if let Some(stats_entries) = stats {
-        root.insert("stats".to_string(), serde_json::to_value(stats_entries)?);
-        root.insert(
-            "stats_sample_limit".to_string(),
-            serde_json::Value::from(sample_limit),
-        );
+    root.insert("verbose".to_string(), serde_json::Value::Bool(verbose));
+
+    if let Some(stats_entries) = stats {
+        root.insert("stats".to_string(), serde_json::to_value(stats_entries)?);
+        root.insert(
+            "stats_sample_limit".to_string(),
+            serde_json::Value::from(sample_limit),
+        );
+    }
+
+    serde_json::to_writer_pretty(&mut *writer, &serde_json::Value::Object(root))?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+fn compute_detailed_stats(
+    reader: &mut JacReader<File>,
+    blocks: &[BlockHandle],
+    summaries: &[BlockSummary],
+    all_fields: &[String],
+    progress: Option<&ProgressBar>,
+    sample_limit: usize,
+) -> Result<Vec<DetailedFieldStats>, Box<dyn Error>> {
+    let mut stats_map: BTreeMap<String, FieldStatsAccumulator> = all_fields
+        .iter()
+        .cloned()
+        .map(|name| (name, FieldStatsAccumulator::new(sample_limit)))
+        .collect();
+
+    for (idx, block) in blocks.iter().enumerate() {
+        if let Some(pb) = progress {
+            pb.set_position((idx + 1) as u64);
+        }
+
+        let summary = &summaries[idx];
+        let mut present_fields: HashSet<&str> = HashSet::with_capacity(summary.fields.len());
+
+        for field_summary in &summary.fields {
+            present_fields.insert(field_summary.name.as_str());
+            let accumulator = stats_map
+                .get_mut(&field_summary.name)
+                .expect("field should exist in stats map");
+
+            accumulator.total_records += block.record_count;
+            accumulator.present_values += field_summary.present_count;
+            accumulator.absent_values += block.record_count - field_summary.present_count;
+
+            if accumulator.sampled {
+                continue;
+            }
+            // A segment that fails to decode contributes no samples.
+            let _ = reader.visit_field(block, &field_summary.name, |_, value| {
+                accumulator.record_value(value);
+                Ok(())
+            });
+        }
+
+        for field_name in all_fields {
+            if present_fields.contains(field_name.as_str()) {
+                continue;
+            }
+            if let Some(accumulator) = stats_map.get_mut(field_name) {
+                accumulator.total_records += block.record_count;
+                accumulator.absent_values += block.record_count;
+            }
+        }
+    }
+
+    Ok(stats_map
+        .into_iter()
+        .map(|(name, accumulator)| accumulator.into_detailed(name))
+        .collect())
+}
+
+fn handle_agg(
+    input: PathBuf,
+    group_by: Vec<String>,
+    aggregates: Vec<Aggregate>,
+) -> Result<(), Box<dyn Error>> {
+    if aggregates.is_empty() {
+        return Err(usage_error(
+            "No aggregates requested. Use --count, --sum, --min, --max, or --distinct.",
+        ));
+    }
+
+    let result = execute_aggregate(AggregateRequest {
+        input: JacInput::Path(input),
+        group_by,
+        aggregates,
+        options: DecompressOptions::default(),
+    })?;
+
+    let stdout = std::io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    for row in result.rows() {
+        serde_json::to_writer(&mut out, &Value::Object(row))?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn handle_count(input: PathBuf, field: Option<String>) -> Result<(), Box<dyn Error>> {
+    let inputs = expand_inputs(&input)?;
+    let readers = inputs
+        .iter()
+        .map(|path| JacReader::open_snapshot(path, snapshot_decompress_opts()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut archives = MultiJacReader::from_readers(readers)?;
+
+    let mut total = 0u64;
+    for (path, reader) in inputs.iter().zip(archives.readers_mut()) {
+        warn_index_status(path, reader);
+        let count = match &field {
+            Some(field) => reader.count_present(field)?,
+            None => reader.count_records()?,
+        };
+        total += count;
+        if inputs.len() > 1 {
+            println!("{}\t{}", count, path.display());
+        }
+    }
+    if inputs.len() > 1 {
+        println!("{}\ttotal", total);
+    } else {
+        println!("{}", total);
+    }
+    Ok(())
+}
+
+/// Options for reading files a writer may still be appending to.
+fn snapshot_decompress_options() -> DecompressOptions {
+    DecompressOptions {
+        snapshot: true,
+        ..DecompressOptions::default()
+    }
+}
+
+fn snapshot_decompress_opts() -> DecompressOpts {
+    let options = snapshot_decompress_options();
+    DecompressOpts {
+        limits: options.limits,
+        verify_checksums: options.verify_checksums,
+        strict: options.strict,
+        declared_limits: options.declared_limits,
+        ..DecompressOpts::default()
+    }
+}
+
+/// Report a misplaced or unusable index footer found while opening `path`.
+fn warn_index_status<R: Read + Seek>(path: &Path, reader: &JacReader<R>) {
+    if let Some(warning) = reader.index_status().warning() {
+        eprintln!("Warning: {}: {}", path.display(), warning);
+    }
+}
+
+/// Expand a read command's input into archive paths.
+///
+/// A directory is searched recursively for `.jac` files and a path that does
+/// not exist but contains `*`, `?` or `[` is matched as a glob pattern; both
+/// yield sorted paths. Any other path is returned unchanged.
+fn expand_inputs(input: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut paths = Vec::new();
+    if input.is_dir() {
+        collect_jac_files(input, &mut paths)?;
+    } else if !input.exists() && input.to_string_lossy().contains(['*', '?', '[']) {
+        for entry in glob::glob(&input.to_string_lossy())? {
+            let path = entry?;
+            if path.is_file() {
+                paths.push(path);
+            }
+        }
+    } else {
+        return Ok(vec![input.to_path_buf()]);
+    }
+
+    if paths.is_empty() {
+        return Err(usage_error(format!(
+            "No .jac files match '{}'",
+            input.display()
+        )));
     }
+    paths.sort();
+    Ok(paths)
+}
 
-    serde_json::to_writer_pretty(&mut *writer, &serde_json::Value::Object(root))?;
-    writeln!(writer)?;
+fn collect_jac_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_jac_files(&path, paths)?;
+        } else if path.extension().is_some_and(|ext| ext == "jac") {
+            paths.push(path);
+        }
+    }
     Ok(())
 }
 
-fn compute_detailed_stats(
-    reader: &mut JacReader<File>,
-    blocks: &[BlockHandle],
-    summaries: &[BlockSummary],
-    all_fields: &[String],
-    progress: Option<&ProgressBar>,
-    sample_limit: usize,
-) -> Result<Vec<DetailedFieldStats>, Box<dyn Error>> {
-    #[derive(Default)]
-    struct CachedBlock {
-        values: HashMap<String, Vec<Option<Value>>>,
+fn handle_stat(
+    input: PathBuf,
+    field: String,
+    top: usize,
+    estimate: bool,
+) -> Result<(), Box<dyn Error>> {
+    let file = File::open(&input)?;
+    let options = DecompressOptions::default();
+    let codec_opts = DecompressOpts {
+        limits: options.limits.clone(),
+        verify_checksums: options.verify_checksums,
+        strict: options.strict,
+        declared_limits: options.declared_limits,
+        ..DecompressOpts::default()
+    };
+    let mut reader = JacReader::new(file, codec_opts)?;
+    if estimate {
+        let stats = reader.field_stats(&field)?;
+        if stats.present == 0 {
+            return Err(usage_error(format!(
+                "Field '{}' has no values in JAC file",
+                field
+            )));
+        }
+        if !stats.complete() && stats.quantiles.is_empty() {
+            return Err(usage_error(format!(
+                "{} of {} blocks holding '{}' have no field statistics (pack with --field-stats or --quantile-sketches, or drop --estimate)",
+                stats.blocks - stats.blocks_with_stats,
+                stats.blocks,
+                field
+            )));
+        }
+        println!("Field: {}", field);
+        println!("Values: {}", stats.present);
+        if stats.complete() {
+            println!("Nulls: {}", stats.nulls);
+            println!("Distinct: ~{}", stats.approx_distinct().unwrap_or(0));
+            println!("Blocks: {} from field statistics", stats.blocks);
+        }
+        if !stats.quantiles.is_empty() {
+            println!();
+            println!(
+                "Numeric values: {} ({} of {} blocks with quantile sketches)",
+                stats.numeric_values(),
+                stats.quantiles.len(),
+                stats.blocks
+            );
+            for (label, q) in [
+                ("min", 0.0),
+                ("p25", 0.25),
+                ("p50", 0.5),
+                ("p75", 0.75),
+                ("p90", 0.9),
+                ("p99", 0.99),
+                ("max", 1.0),
+            ] {
+                if let Some(value) = stats.quantile(q) {
+                    println!("{:>5}  {}", label, (value * 1000.0).round() / 1000.0);
+                }
+            }
+        }
+        return Ok(());
+    }
+    let counts = reader.value_counts(&field)?;
+    if counts.total == 0 {
+        return Err(usage_error(format!(
+            "Field '{}' has no values in JAC file",
+            field
+        )));
     }
 
-    let mut stats_map: BTreeMap<String, FieldStatsAccumulator> = all_fields
-        .iter()
-        .cloned()
-        .map(|name| (name, FieldStatsAccumulator::new(sample_limit)))
-        .collect();
+    println!("Field: {}", field);
+    println!("Values: {}", counts.total);
+    println!("Distinct: {}", counts.distinct());
+    println!(
+        "Blocks: {} from dictionary, {} decoded",
+        counts.dictionary_blocks, counts.decoded_blocks
+    );
+    println!();
+    println!("{:>12}  {:>7}  Value", "Count", "Share");
+    for (value, count) in counts.top(top) {
+        let share = *count as f64 * 100.0 / counts.total as f64;
+        let display = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        println!("{:>12}  {:>6.2}%  {}", count, share, display);
+    }
+    Ok(())
+}
 
-    let mut cache: Vec<CachedBlock> = (0..blocks.len())
-        .map(|_| CachedBlock {
-            values: HashMap::new(),
-        })
-        .collect();
+fn handle_meta(input: PathBuf, output: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let mut paths = expand_inputs(&input)?;
+    // A metadata file written into the scanned directory must not be read back
+    // (and truncated) while it is being written.
+    if let Some(output) = output.as_deref().and_then(|path| path.canonicalize().ok()) {
+        paths.retain(|path| path.canonicalize().map_or(true, |path| path != output));
+    }
+    let error = Arc::new(Mutex::new(None));
+    let records = MetaRecords {
+        paths: paths.into_iter(),
+        current: Vec::new().into_iter(),
+        error: Arc::clone(&error),
+    };
 
-    for (idx, block) in blocks.iter().enumerate() {
-        if let Some(pb) = progress {
-            pb.set_position((idx + 1) as u64);
+    let (count, written_to) = match output {
+        Some(output) if output.extension().is_some_and(|ext| ext == "jac") => {
+            let request = CompressRequest::builder()
+                .input(InputSource::Iterator(Box::new(records)))
+                .output(OutputSink::Path(output.clone()))
+                .build()?;
+            let summary = execute_compress(request)?;
+            (summary.metrics.records_written, Some(output))
         }
+        output => {
+            let mut out: Box<dyn Write> = match &output {
+                Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                None => Box::new(BufWriter::new(std::io::stdout().lock())),
+            };
+            let mut count = 0u64;
+            for record in records {
+                serde_json::to_writer(&mut out, &Value::Object(record))?;
+                out.write_all(b"\n")?;
+                count += 1;
+            }
+            out.flush()?;
+            (count, output)
+        }
+    };
+    take_meta_error(&error)?;
+    if let Some(path) = written_to {
+        eprintln!("Wrote {} metadata records to {}", count, path.display());
+    }
+    Ok(())
+}
 
-        let summary = &summaries[idx];
-        let mut present_fields: HashSet<&str> = HashSet::with_capacity(summary.fields.len());
+/// Directory records of `jac meta`, read one archive at a time. The first error
+/// ends the stream and is left in `error` for the caller.
+struct MetaRecords {
+    paths: std::vec::IntoIter<PathBuf>,
+    current: std::vec::IntoIter<serde_json::Map<String, Value>>,
+    error: Arc<Mutex<Option<jac_io::JacError>>>,
+}
 
-        for field_summary in &summary.fields {
-            present_fields.insert(field_summary.name.as_str());
-            let accumulator = stats_map
-                .get_mut(&field_summary.name)
-                .expect("field should exist in stats map");
+impl MetaRecords {
+    fn read(path: &Path) -> Result<Vec<serde_json::Map<String, Value>>, jac_io::JacError> {
+        let mut reader = JacReader::open_snapshot(path, snapshot_decompress_opts())?;
+        warn_index_status(path, &reader);
+        let file = Value::String(path.display().to_string());
+        Ok(reader
+            .directory_records()?
+            .into_iter()
+            .map(|entry| {
+                let mut record = serde_json::Map::new();
+                record.insert("file".to_string(), file.clone());
+                record.extend(entry);
+                record
+            })
+            .collect())
+    }
+}
 
-            accumulator.total_records += block.record_count;
-            accumulator.present_values += field_summary.present_count;
-            accumulator.absent_values += block.record_count - field_summary.present_count;
+impl Iterator for MetaRecords {
+    type Item = serde_json::Map<String, Value>;
 
-            let block_cache = &mut cache[idx];
-            let values = block_cache
-                .values
-                .entry(field_summary.name.clone())
-                .or_insert_with(|| {
-                    decode_field(reader, block, &field_summary.name).unwrap_or_default()
-                });
-
-            for value_opt in values.iter().flatten() {
-                accumulator.record_value(value_opt);
-                if accumulator.sampled {
-                    break;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.current.next() {
+                return Some(record);
+            }
+            let path = self.paths.next()?;
+            match Self::read(&path) {
+                Ok(records) => self.current = records.into_iter(),
+                Err(err) => {
+                    *self.error.lock().unwrap() = Some(err);
+                    self.paths = Vec::new().into_iter();
+                    return None;
                 }
             }
         }
+    }
+}
 
-        for field_name in all_fields {
-            if present_fields.contains(field_name.as_str()) {
-                continue;
-            }
-            if let Some(accumulator) = stats_map.get_mut(field_name) {
-                accumulator.total_records += block.record_count;
-                accumulator.absent_values += block.record_count;
-            }
-        }
+fn take_meta_error(error: &Mutex<Option<jac_io::JacError>>) -> Result<(), Box<dyn Error>> {
+    match error.lock().unwrap().take() {
+        Some(err) => Err(err.into()),
+        None => Ok(()),
     }
+}
 
-    Ok(stats_map
-        .into_iter()
-        .map(|(name, accumulator)| accumulator.into_detailed(name))
-        .collect())
+fn handle_rewrite(
+    input: PathBuf,
+    output: Option<PathBuf>,
+    drop_older_than: Duration,
+    timestamp_field: String,
+    unit: TimestampUnit,
+) -> Result<(), Box<dyn Error>> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let cutoff = now.saturating_sub(drop_older_than);
+    let summary = execute_retention(RetentionRequest {
+        input: input.clone(),
+        output: output.clone(),
+        policy: RetentionPolicy {
+            timestamp_field,
+            cutoff_millis: i64::try_from(cutoff.as_millis())?,
+            unit,
+        },
+    })?;
+    println!(
+        "Kept {} records, dropped {} ({} blocks copied, {} dropped, {} re-encoded; {} decided from block metadata)",
+        summary.records_kept,
+        summary.records_dropped,
+        summary.blocks_kept,
+        summary.blocks_dropped,
+        summary.blocks_filtered,
+        summary.blocks_decided_from_metadata
+    );
+    println!("Wrote {}", output.unwrap_or(input).display());
+    Ok(())
 }
 
-fn decode_field(
-    reader: &mut JacReader<File>,
-    block: &BlockHandle,
-    field: &str,
-) -> Result<Vec<Option<Value>>, Box<dyn Error>> {
-    let field_iter = reader.project_field(block, field)?;
-    Ok(field_iter.collect::<Result<Vec<Option<Value>>, _>>()?)
+fn handle_index(input: PathBuf) -> Result<(), Box<dyn Error>> {
+    let summary = execute_build_index(&input)?;
+    if !summary.written {
+        println!("{} already has a valid index", input.display());
+        return Ok(());
+    }
+    if let IndexStatus::Recovered { pointer, .. } | IndexStatus::Invalid { pointer } =
+        summary.previous_status
+    {
+        eprintln!("Replaced the index footer at offset {}", pointer);
+    }
+    println!(
+        "Indexed {} blocks ({} records) at offset {}",
+        summary.blocks, summary.records, summary.index_offset
+    );
+    Ok(())
+}
+
+fn handle_gen_vectors(out_dir: PathBuf) -> Result<(), Box<dyn Error>> {
+    let manifest = generate_vectors(&out_dir)?;
+    for entry in &manifest.vectors {
+        println!(
+            "{}\t{} records, {} blocks\t{}",
+            entry.name, entry.records, entry.blocks, entry.description
+        );
+    }
+    eprintln!(
+        "Wrote {} vectors and {} to {}",
+        manifest.vectors.len(),
+        MANIFEST_FILE,
+        out_dir.display()
+    );
+    Ok(())
+}
+
+fn handle_check_vectors(dir: PathBuf) -> Result<(), Box<dyn Error>> {
+    let checks = check_vectors(&dir)?;
+    let mut failed = 0;
+    for check in &checks {
+        println!(
+            "{} {}",
+            if check.passed() { "PASS" } else { "FAIL" },
+            check.name
+        );
+        for failure in &check.failures {
+            println!("  error: {}", failure);
+        }
+        for warning in &check.warnings {
+            println!("  note: {}", warning);
+        }
+        failed += usize::from(!check.passed());
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} vectors failed", failed, checks.len()).into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "query")]
+fn handle_query(input: PathBuf, query: String, verbose: bool) -> Result<(), Box<dyn Error>> {
+    let query = jac_io::Query::parse(&query)?;
+    let filtered = query.filter.is_some();
+    let summary = jac_io::execute_query(jac_io::QueryRequest {
+        input: JacInput::Path(input),
+        output: OutputSink::Writer(Box::new(std::io::stdout())),
+        query,
+        options: DecompressOptions::default(),
+    })?;
+
+    if verbose {
+        eprintln!(
+            "Rows: {} (blocks scanned: {}, pruned: {})",
+            summary.rows_written, summary.blocks_scanned, summary.blocks_pruned
+        );
+        if filtered {
+            eprintln!("{}", describe_filter_summary(&summary.filter));
+        }
+    }
+    Ok(())
 }
 
 fn handle_cat(
@@ -1916,61 +3748,95 @@ fn handle_cat(
     field: String,
     format: CatFormat,
     blocks: Option<String>,
+    filter: Option<String>,
     progress: bool,
 ) -> Result<(), Box<dyn Error>> {
-    let file = File::open(&input)?;
-    let options = DecompressOptions::default();
-    let codec_opts = DecompressOpts {
-        limits: options.limits.clone(),
-        verify_checksums: options.verify_checksums,
-    };
-    let mut reader = JacReader::new(file, codec_opts)?;
+    let inputs = expand_inputs(&input)?;
     let range = parse_block_range(blocks)?;
+    let filter = filter.map(|text| Predicate::parse(&text)).transpose()?;
 
-    let (available_fields, field_present) = collect_available_fields(&mut reader, &field)?;
-    if !field_present {
-        let mut sorted: Vec<_> = available_fields.into_iter().collect();
-        sorted.sort();
-        if sorted.is_empty() {
-            return Err(format!(
-                "Field '{}' not found in JAC file (no fields detected)",
-                field
-            )
-            .into());
+    let readers = inputs
+        .iter()
+        .map(|path| JacReader::open_snapshot(path, snapshot_decompress_opts()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut archives = MultiJacReader::from_readers(readers)?;
+    for (path, reader) in inputs.iter().zip(archives.readers_mut()) {
+        warn_index_status(path, reader);
+    }
+
+    let catalog = archives.field_catalog_with(&FieldCatalogOptions {
+        include_types: false,
+    })?;
+    if !catalog.iter().any(|entry| entry.name == field) {
+        let target = if inputs.len() > 1 {
+            format!("any of {} JAC files", inputs.len())
+        } else {
+            "JAC file".to_string()
+        };
+        if catalog.is_empty() {
+            return Err(usage_error(format!(
+                "Field '{}' not found in {} (no fields detected)",
+                field, target
+            )));
         }
-        return Err(format!(
-            "Field '{}' not found in JAC file. Available fields: {}",
+        let names: Vec<_> = catalog.iter().map(|entry| entry.name.as_str()).collect();
+        return Err(usage_error(format!(
+            "Field '{}' not found in {}. Available fields: {}",
             field,
-            sorted.join(", ")
-        )
-        .into());
+            target,
+            names.join(", ")
+        )));
     }
 
-    reader.rewind()?;
     let mut writer = CatWriter::new(format)?;
+    let source = |archive: usize| (inputs.len() > 1).then(|| inputs[archive].display().to_string());
     let mut progress_bar = progress.then(|| create_spinner("Streaming field values"));
     let mut values_emitted: u64 = 0;
     let start = Instant::now();
     let mut reader_metrics = ReaderMetrics::default();
+    let mut filter_summary = FilterSummary::default();
+
+    if range.is_some() || filter.is_some() {
+        for (archive, reader) in archives.readers_mut().iter_mut().enumerate() {
+            writer.set_source(source(archive));
+            let block_handles: Vec<_> = reader.blocks().collect::<Result<Vec<_>, _>>()?;
+            let block_count = block_handles.len();
+            let (start_idx, end_idx) = match range {
+                Some(range) => range.bounds(block_count)?,
+                None => (0, block_count.saturating_sub(1)),
+            };
 
-    if let Some(range) = range {
-        let block_handles: Vec<_> = reader.blocks().collect::<Result<Vec<_>, _>>()?;
-        let block_count = block_handles.len();
-        let (start_idx, end_idx) = range.into_bounds(block_count)?;
-
-        for (block_idx, block) in block_handles.into_iter().enumerate() {
-            if block_idx < start_idx || block_idx > end_idx {
-                continue;
-            }
+            for (block_idx, block) in block_handles.into_iter().enumerate() {
+                if block_idx < start_idx
+                    || block_idx > end_idx
+                    || block.field_entry(&field).is_none()
+                {
+                    continue;
+                }
+                let values: Vec<Option<Value>> = match &filter {
+                    Some(filter) => match reader.filter_columns(
+                        &block,
+                        std::slice::from_ref(&field),
+                        filter,
+                        &mut filter_summary,
+                    )? {
+                        Some((mut columns, selected)) => Arc::unwrap_or_clone(columns.remove(0))
+                            .into_iter()
+                            .zip(selected)
+                            .filter_map(|(value, keep)| keep.then_some(value))
+                            .collect(),
+                        None => continue,
+                    },
+                    None => reader
+                        .project_field(&block, &field)?
+                        .collect::<Result<_, _>>()?,
+                };
 
-            reader_metrics.blocks_read += 1;
-            reader_metrics.records_observed += block.record_count as u64;
-            reader_metrics.bytes_observed += block.size as u64;
+                reader_metrics.blocks_read += 1;
+                reader_metrics.records_observed += block.record_count as u64;
+                reader_metrics.bytes_observed += block.size as u64;
 
-            let field_iter = reader.project_field(&block, &field)?;
-            for value_result in field_iter {
-                let maybe_value = value_result?;
-                if let Some(value) = maybe_value {
+                for value in values.into_iter().flatten() {
                     writer.write_value(value)?;
                     values_emitted += 1;
                     if let Some(pb) = &progress_bar {
@@ -1980,18 +3846,24 @@ fn handle_cat(
             }
         }
     } else {
-        let mut projection_stream = reader.projection_stream(field)?;
-        for value_result in projection_stream.by_ref() {
-            let maybe_value = value_result?;
-            if let Some(value) = maybe_value {
-                writer.write_value(value)?;
-                values_emitted += 1;
-                if let Some(pb) = &progress_bar {
-                    pb.set_position(values_emitted);
-                }
+        let mut projection_stream =
+            archives.multi_projection_stream(std::slice::from_ref(&field))?;
+        let mut archive = None;
+        while let Some(row) = projection_stream.next() {
+            let Some(value) = row?.pop().flatten() else {
+                continue;
+            };
+            if archive != projection_stream.archive_index() {
+                archive = projection_stream.archive_index();
+                writer.set_source(archive.and_then(source));
+            }
+            writer.write_value(value)?;
+            values_emitted += 1;
+            if let Some(pb) = &progress_bar {
+                pb.set_position(values_emitted);
             }
         }
-        reader_metrics.records_observed = values_emitted;
+        reader_metrics.records_observed += values_emitted;
     }
 
     if reader_metrics.blocks_read == 0 && reader_metrics.records_observed > 0 {
@@ -2025,93 +3897,38 @@ fn handle_cat(
         reader_metrics.records_observed,
         reader_metrics.bytes_observed as f64 / (1024.0 * 1024.0)
     )?;
+    if filter.is_some() {
+        writeln!(&mut stderr, "{}", describe_filter_summary(&filter_summary))?;
+    }
     Ok(())
 }
 
-fn parse_block_range(spec: Option<String>) -> Result<Option<BlockRange>, Box<dyn Error>> {
-    let Some(spec) = spec else {
-        return Ok(None);
-    };
-    let trimmed = spec.trim();
-    if trimmed.is_empty() {
-        return Err("Block range cannot be empty".into());
-    }
-
-    if let Ok(n) = trimmed.parse::<usize>() {
-        if n == 0 {
-            return Err("Block numbers start from 1".into());
-        }
-        return Ok(Some(BlockRange::Single(n - 1)));
-    }
-
-    if let Some((start_part, end_part)) = trimmed.split_once('-') {
-        let start_idx = if start_part.is_empty() {
-            0
-        } else {
-            let start = start_part.parse::<usize>()?;
-            if start == 0 {
-                return Err("Block numbers start from 1".into());
-            }
-            start - 1
-        };
-
-        let end_idx = if end_part.is_empty() {
-            None
-        } else {
-            let end = end_part.parse::<usize>()?;
-            if end == 0 {
-                return Err("Block numbers start from 1".into());
-            }
-            Some(end - 1)
-        };
-
-        if let Some(end) = end_idx {
-            if start_idx > end {
-                return Err(format!(
-                    "Invalid block range: start ({}) > end ({})",
-                    start_idx + 1,
-                    end + 1
-                )
-                .into());
-            }
+fn parse_rename_map(specs: &[String]) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let mut rename = HashMap::with_capacity(specs.len());
+    for spec in specs {
+        let (old, new) = spec
+            .split_once('=')
+            .filter(|(old, new)| !old.is_empty() && !new.is_empty())
+            .ok_or_else(|| usage_error(format!("Invalid --rename '{}'. Expected OLD=NEW", spec)))?;
+        if rename.insert(old.to_string(), new.to_string()).is_some() {
+            return Err(usage_error(format!(
+                "Field '{}' is renamed more than once",
+                old
+            )));
         }
-
-        return Ok(Some(BlockRange::Range {
-            start: start_idx,
-            end: end_idx,
-        }));
     }
+    Ok(rename)
+}
 
-    Err(format!(
-        "Invalid block range syntax: '{}'. Expected 'N' or 'N-M'",
-        trimmed
-    )
-    .into())
-}
-
-fn collect_available_fields<R: Read + Seek>(
-    reader: &mut JacReader<R>,
-    target_field: &str,
-) -> Result<(HashSet<String>, bool), Box<dyn Error>> {
-    let mut available = HashSet::new();
-    let mut found = false;
-    let mut blocks = reader.blocks();
-    while let Some(block_res) = blocks.next() {
-        let block = block_res?;
-        for entry in &block.header.fields {
-            if entry.field_name == target_field {
-                found = true;
-            }
-            available.insert(entry.field_name.clone());
-        }
-    }
-    Ok((available, found))
+fn parse_block_range(spec: Option<String>) -> Result<Option<BlockRange>, Box<dyn Error>> {
+    Ok(spec.map(|spec| spec.parse::<BlockRange>()).transpose()?)
 }
 
 struct CatWriter {
     format: CatFormat,
     writer: Box<dyn Write>,
     first: bool,
+    source: Option<String>,
 }
 
 impl CatWriter {
@@ -2130,10 +3947,27 @@ impl CatWriter {
             format,
             writer,
             first: true,
+            source: None,
         })
     }
 
+    /// Tag subsequent values with the file they came from.
+    fn set_source(&mut self, source: Option<String>) {
+        self.source = source;
+    }
+
     fn write_value(&mut self, value: Value) -> Result<(), Box<dyn Error>> {
+        let value = match (&self.source, self.format) {
+            (Some(source), CatFormat::Ndjson | CatFormat::JsonArray) => {
+                serde_json::json!({ "file": source, "value": value })
+            }
+            (Some(source), CatFormat::Csv) => {
+                let text = csv_serialize(&value)?;
+                write!(self.writer, "{},", source)?;
+                Value::String(text)
+            }
+            (None, _) => value,
+        };
         match self.format {
             CatFormat::Ndjson => {
                 serde_json::to_writer(&mut self.writer, &value)?;
@@ -2195,7 +4029,7 @@ fn create_spinner(message: &str) -> ProgressBar {
 fn parse_size(size_str: &str) -> Result<usize, Box<dyn Error>> {
     let size_str = size_str.trim();
     if size_str.is_empty() {
-        return Err("Size string cannot be empty".into());
+        return Err(usage_error("Size string cannot be empty"));
     }
 
     let (number_part, suffix) = if let Some(last_char) = size_str.chars().last() {
@@ -2213,10 +4047,10 @@ fn parse_size(size_str: &str) -> Result<usize, Box<dyn Error>> {
 
     let number: f64 = number_part
         .parse()
-        .map_err(|_| format!("Invalid number in size string: '{}'", size_str))?;
+        .map_err(|_| usage_error(format!("Invalid number in size string: '{}'", size_str)))?;
 
     if number < 0.0 {
-        return Err("Size cannot be negative".into());
+        return Err(usage_error("Size cannot be negative"));
     }
 
     let multiplier = match suffix {
@@ -2224,7 +4058,12 @@ fn parse_size(size_str: &str) -> Result<usize, Box<dyn Error>> {
         Some('K') => 1024,
         Some('M') => 1024 * 1024,
         Some('G') => 1024 * 1024 * 1024,
-        Some(c) => return Err(format!("Unknown size suffix '{}'. Use K, M, or G", c).into()),
+        Some(c) => {
+            return Err(usage_error(format!(
+                "Unknown size suffix '{}'. Use K, M, or G",
+                c
+            )))
+        }
     };
 
     let bytes = (number * multiplier as f64) as usize;
@@ -2264,46 +4103,16 @@ mod tests {
 
         fs::write(&paths.input_ndjson, data).unwrap();
 
-        handle_pack(
-            paths.input_ndjson.clone(),
-            paths.output_jac.clone(),
-            1000,
-            3,
-            false,
-            false,
-            4_096,
-            true,
-            true,
-            false,
-            false,
-            None,
-            None,
-            None,
-            false,
-            false, // verbose_metrics
-            None,  // wrapper_pointer
-            None,  // wrapper_depth
-            None,  // wrapper_buffer
-            None,  // wrapper_sections
-            None,  // wrapper_section_pointer
-            None,  // wrapper_section_label_field
-            false, // wrapper_section_no_label
-            false, // wrapper_sections_missing_error
-            false, // wrapper_map
-            None,  // wrapper_map_pointer
-            None,  // wrapper_map_key_field
-            false, // wrapper_map_overwrite_key
-            false, // wrapper_array_headers
-        )
-        .unwrap();
+        let mut args = PackArgs::new(paths.input_ndjson.clone(), paths.output_jac.clone());
+        args.block_records = 1000;
+        args.zstd_level = ZstdLevelArg::Fixed(3);
+        args.ndjson = true;
+        handle_pack(args).unwrap();
 
-        handle_unpack(
+        handle_unpack(UnpackArgs::new(
             paths.output_jac.clone(),
             paths.output_json.clone(),
-            true,
-            false,
-            false,
-        )
+        ))
         .unwrap();
 
         let result = fs::read_to_string(&paths.output_json).unwrap();
@@ -2317,47 +4126,14 @@ mod tests {
 
         fs::write(&paths.input_json, data).unwrap();
 
-        handle_pack(
-            paths.input_json.clone(),
-            paths.output_jac.clone(),
-            10,
-            3,
-            false,
-            false,
-            4_096,
-            true,
-            false,
-            false,
-            false,
-            None,
-            None,
-            None,
-            false,
-            false, // verbose_metrics
-            None,  // wrapper_pointer
-            None,  // wrapper_depth
-            None,  // wrapper_buffer
-            None,  // wrapper_sections
-            None,  // wrapper_section_pointer
-            None,  // wrapper_section_label_field
-            false, // wrapper_section_no_label
-            false, // wrapper_sections_missing_error
-            false, // wrapper_map
-            None,  // wrapper_map_pointer
-            None,  // wrapper_map_key_field
-            false, // wrapper_map_overwrite_key
-            false, // wrapper_array_headers
-        )
-        .unwrap();
+        let mut args = PackArgs::new(paths.input_json.clone(), paths.output_jac.clone());
+        args.block_records = 10;
+        args.zstd_level = ZstdLevelArg::Fixed(3);
+        handle_pack(args).unwrap();
 
-        handle_unpack(
-            paths.output_jac.clone(),
-            paths.output_json.clone(),
-            false,
-            true,
-            false,
-        )
-        .unwrap();
+        let mut args = UnpackArgs::new(paths.output_jac.clone(), paths.output_json.clone());
+        args.json_array = true;
+        handle_unpack(args).unwrap();
 
         let result = fs::read_to_string(&paths.output_json).unwrap();
         let expected: Value = serde_json::from_str(data).unwrap();
@@ -2365,6 +4141,36 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn parse_rename_map_accepts_pairs_and_rejects_malformed_specs() {
+        let rename = parse_rename_map(&["uid=user_id".to_string(), "ts=time".to_string()])
+            .expect("valid renames");
+        assert_eq!(rename.get("uid").map(String::as_str), Some("user_id"));
+        assert_eq!(rename.get("ts").map(String::as_str), Some("time"));
+
+        assert!(parse_rename_map(&["uid".to_string()]).is_err());
+        assert!(parse_rename_map(&["=user_id".to_string()]).is_err());
+        assert!(parse_rename_map(&["a=b".to_string(), "a=c".to_string()]).is_err());
+    }
+
+    #[test]
+    fn parse_field_hints_combines_hints_per_field() {
+        let hints = parse_field_hints(Some(vec![
+            "ts=delta".to_string(),
+            "id=no-dict".to_string(),
+            "code=delta, dict".to_string(),
+        ]))
+        .expect("valid hints");
+        assert!(hints["ts"].prefer_delta);
+        assert_eq!(hints["ts"].dictionary, None);
+        assert_eq!(hints["id"].dictionary, Some(DictionaryMode::Never));
+        assert!(hints["code"].prefer_delta);
+        assert_eq!(hints["code"].dictionary, Some(DictionaryMode::Always));
+
+        assert!(parse_field_hints(Some(vec!["ts".to_string()])).is_err());
+        assert!(parse_field_hints(Some(vec!["ts=fast".to_string()])).is_err());
+    }
+
     #[test]
     fn parse_block_range_single_block() {
         let range = parse_block_range(Some("3".to_string()))
@@ -2390,13 +4196,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn partial_output_removes_the_registered_file_and_its_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("out.jac");
+        let guard = PartialOutput::register(&output);
+        for name in ["out.jac", "out.part2.jac", "out.part3.jac", "other.jac"] {
+            std::fs::write(dir.path().join(name), b"partial").unwrap();
+        }
+        PartialOutput::remove();
+        let left: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(left, ["other.jac"]);
+
+        drop(guard);
+        std::fs::write(&output, b"finished").unwrap();
+        PartialOutput::remove();
+        assert!(output.exists());
+    }
+
+    #[test]
+    fn parse_age_accepts_unit_suffixes() {
+        assert_eq!(parse_age("90d").unwrap(), Duration::from_secs(90 * 86_400));
+        assert_eq!(parse_age("12h").unwrap(), Duration::from_secs(12 * 3_600));
+        assert_eq!(parse_age("2w").unwrap(), Duration::from_secs(2 * 604_800));
+        for bad in ["", "d", "90", "1.5d", "-1d", "90y", "9é"] {
+            assert!(parse_age(bad).is_err(), "{} should be rejected", bad);
+        }
+    }
+
     #[test]
     fn block_range_into_bounds_normalizes_end() {
         let range = BlockRange::Range {
             start: 1,
             end: None,
         };
-        let (start, end) = range.into_bounds(4).unwrap();
+        let (start, end) = range.bounds(4).unwrap();
         assert_eq!(start, 1);
         assert_eq!(end, 3);
     }
@@ -2498,44 +4335,13 @@ mod tests {
         let input_file = paths.input_ndjson.clone();
         let output_file = input_file.with_extension("jac");
 
-        handle_pack(
-            input_file,
-            output_file.clone(),
-            100000, // default block_records
-            6,      // default zstd_level
-            false,  // canonicalize_keys
-            false,  // canonicalize_numbers
-            4096,   // max_dict_entries
-            true,   // emit_index
-            false,  // force_ndjson
-            false,  // force_json_array
-            false,  // show_progress
-            None,   // threads
-            None,   // parallel_memory_factor
-            None,   // max_segment_bytes
-            false,  // allow_large_segments
-            false,  // verbose_metrics
-            None,   // wrapper_pointer
-            None,   // wrapper_depth
-            None,   // wrapper_buffer
-            None,   // wrapper_sections
-            None,   // wrapper_section_pointer
-            None,   // wrapper_section_label_field
-            false,  // wrapper_section_no_label
-            false,  // wrapper_sections_missing_error
-            false,  // wrapper_map
-            None,   // wrapper_map_pointer
-            None,   // wrapper_map_key_field
-            false,  // wrapper_map_overwrite_key
-            false,  // wrapper_array_headers
-        )
-        .unwrap();
+        handle_pack(PackArgs::new(input_file, output_file.clone())).unwrap();
 
         // Verify the .jac file was created
         assert!(output_file.exists());
 
         // Verify we can decompress it back
-        handle_unpack(output_file, paths.output_json.clone(), true, false, false).unwrap();
+        handle_unpack(UnpackArgs::new(output_file, paths.output_json.clone())).unwrap();
 
         let result = fs::read_to_string(&paths.output_json).unwrap();
         assert_eq!(normalize(&result), normalize(data));
@@ -2552,43 +4358,14 @@ mod tests {
         let input_file = paths.input_json.clone();
         let output_file = input_file.with_extension("jac");
 
-        handle_pack(
-            input_file,
-            output_file.clone(),
-            100000,
-            6,
-            false,
-            false,
-            4096,
-            true,
-            false,
-            false,
-            false,
-            None,
-            None,
-            None,
-            false,
-            false, // verbose_metrics
-            None,  // wrapper_pointer
-            None,  // wrapper_depth
-            None,  // wrapper_buffer
-            None,  // wrapper_sections
-            None,  // wrapper_section_pointer
-            None,  // wrapper_section_label_field
-            false, // wrapper_section_no_label
-            false, // wrapper_sections_missing_error
-            false, // wrapper_map
-            None,  // wrapper_map_pointer
-            None,  // wrapper_map_key_field
-            false, // wrapper_map_overwrite_key
-            false, // wrapper_array_headers
-        )
-        .unwrap();
+        handle_pack(PackArgs::new(input_file, output_file.clone())).unwrap();
 
         assert!(output_file.exists());
 
         // Verify we can decompress it back as JSON array
-        handle_unpack(output_file, paths.output_json.clone(), false, true, false).unwrap();
+        let mut args = UnpackArgs::new(output_file, paths.output_json.clone());
+        args.json_array = true;
+        handle_unpack(args).unwrap();
 
         let result = fs::read_to_string(&paths.output_json).unwrap();
         let expected: Value = serde_json::from_str(data).unwrap();
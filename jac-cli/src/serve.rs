@@ -0,0 +1,580 @@
+//! `jac serve`: read-only HTTP access to a directory of archives (feature
+//! `serve`).
+//!
+//! Every endpoint answers `GET` with JSON, or NDJSON for record bodies:
+//!
+//! - `/files`: the `.jac` files under the served directory, with their sizes
+//! - `/files/<path>`: block and record counts of one file
+//! - `/files/<path>/fields`: its field catalog
+//! - `/files/<path>/records?offset=N&limit=M`: a range of records
+//! - `/files/<path>/project?fields=a,b&where=<predicate>&limit=M`: the listed
+//!   fields of the records matching a filter expression
+//! - `/metrics`: request, record and read counters for Prometheus
+//!
+//! Files are read as snapshots, so an archive that is still being written is
+//! served up to its last complete block.
+//!
+//! A fixed pool of worker threads accepts requests, so at most `workers`
+//! files are read at once and further connections wait their turn. Record
+//! bodies are capped at `max_records` rows whatever limit a client asks for.
+
+use crate::{
+    classify_error, collect_jac_files, snapshot_decompress_options, snapshot_decompress_opts,
+    usage_error,
+};
+use jac_io::query::Selection;
+use jac_io::{
+    execute_decompress, execute_query, DecompressFormat, DecompressRequest, ErrorCategory,
+    JacInput, JacReader, OutputSink, Predicate, Query, QueryRequest, ServiceMetrics,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tiny_http::{Header, Method, Response, Server};
+
+/// Settings for [`run`].
+pub struct ServeConfig {
+    /// Directory whose `.jac` files are served (searched recursively)
+    pub root: PathBuf,
+    /// Address to listen on, such as `127.0.0.1:8080`
+    pub bind: String,
+    /// Requests handled concurrently
+    pub workers: usize,
+    /// Most records returned by one `records` or `project` request
+    pub max_records: u64,
+}
+
+/// Serve `config.root` until the process is stopped.
+pub fn run(config: ServeConfig) -> Result<(), Box<dyn Error>> {
+    if config.workers == 0 {
+        return Err(usage_error("--workers must be greater than zero"));
+    }
+    if !config.root.is_dir() {
+        return Err(usage_error(format!(
+            "{} is not a directory",
+            config.root.display()
+        )));
+    }
+    let service = Arc::new(Service {
+        root: config.root.canonicalize()?,
+        max_records: config.max_records,
+        metrics: ServiceMetrics::default(),
+    });
+    let server =
+        Arc::new(Server::http(&config.bind).map_err(|e| {
+            std::io::Error::other(format!("cannot listen on {}: {}", config.bind, e))
+        })?);
+    eprintln!(
+        "Serving {} on http://{} ({} workers)",
+        service.root.display(),
+        server.server_addr(),
+        config.workers
+    );
+
+    let workers: Vec<_> = (0..config.workers)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let service = Arc::clone(&service);
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    service.respond(request);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+/// Status, content type and body of a response.
+#[derive(Debug)]
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    fn json(value: Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    fn text(body: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/plain; version=0.0.4",
+            body: body.into_bytes(),
+        }
+    }
+
+    fn ndjson(body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/x-ndjson",
+            body,
+        }
+    }
+
+    fn error(status: u16, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            ..Self::json(json!({"error": {"code": code, "message": message.into()}}))
+        }
+    }
+
+    /// Error reply for a failure while reading a file: the stable error code
+    /// of the CLI, with a status chosen by its category.
+    fn from_error(err: &(dyn Error + 'static)) -> Self {
+        let (code, category) = classify_error(err);
+        let status = match category {
+            ErrorCategory::Usage => 400,
+            ErrorCategory::Limits => 413,
+            ErrorCategory::Format => 422,
+            ErrorCategory::Io | ErrorCategory::Interrupted | ErrorCategory::Internal => 500,
+        };
+        Self::error(status, code, err.to_string())
+    }
+}
+
+impl<E: Error + 'static> From<E> for Reply {
+    fn from(err: E) -> Self {
+        Self::from_error(&err)
+    }
+}
+
+/// Prefix of the metric names under `/metrics`.
+const METRICS_NAMESPACE: &str = "jac_serve";
+
+struct Service {
+    root: PathBuf,
+    max_records: u64,
+    metrics: ServiceMetrics,
+}
+
+impl Service {
+    fn respond(&self, request: tiny_http::Request) {
+        ServiceMetrics::add(&self.metrics.requests, 1);
+        ServiceMetrics::add(&self.metrics.queue_depth, 1);
+        let reply = if *request.method() == Method::Get {
+            self.handle(request.url()).unwrap_or_else(|reply| reply)
+        } else {
+            Reply::error(405, "method_not_allowed", "Only GET is supported")
+        };
+        if reply.status >= 400 {
+            ServiceMetrics::add(&self.metrics.errors, 1);
+        }
+        let content_type =
+            Header::from_bytes("Content-Type", reply.content_type).expect("static header is valid");
+        let response = Response::from_data(reply.body)
+            .with_status_code(reply.status)
+            .with_header(content_type);
+        let _ = request.respond(response);
+        ServiceMetrics::sub(&self.metrics.queue_depth, 1);
+    }
+
+    fn handle(&self, url: &str) -> Result<Reply, Reply> {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let path = percent_decode(path, false)?;
+        let params = parse_params(query)?;
+
+        if path == "/metrics" {
+            return Ok(Reply::text(self.metrics.render(METRICS_NAMESPACE)));
+        }
+        if path == "/files" || path == "/files/" {
+            return self.list_files();
+        }
+        let Some(rest) = path.strip_prefix("/files/") else {
+            return Err(Reply::error(
+                404,
+                "not_found",
+                format!("No endpoint at {}", path),
+            ));
+        };
+        match rest.rsplit_once('/') {
+            Some((file, "fields")) => self.fields(&self.resolve(file)?),
+            Some((file, "records")) => self.records(&self.resolve(file)?, &params),
+            Some((file, "project")) => self.project(&self.resolve(file)?, &params),
+            _ => self.file_info(&self.resolve(rest)?),
+        }
+    }
+
+    /// Map a client path to a `.jac` file inside the served directory.
+    fn resolve(&self, relative: &str) -> Result<PathBuf, Reply> {
+        let not_found = || {
+            Reply::error(
+                404,
+                "file_not_found",
+                format!("No .jac file at '{}'", relative),
+            )
+        };
+        let relative = Path::new(relative);
+        if relative.extension().map_or(true, |ext| ext != "jac")
+            || !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(not_found());
+        }
+        // Canonicalizing resolves symlinks, which must not lead outside the root.
+        let path = self
+            .root
+            .join(relative)
+            .canonicalize()
+            .map_err(|_| not_found())?;
+        if !path.starts_with(&self.root) || !path.is_file() {
+            return Err(not_found());
+        }
+        Ok(path)
+    }
+
+    fn relative<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.root).unwrap_or(path)
+    }
+
+    fn list_files(&self) -> Result<Reply, Reply> {
+        let mut paths = Vec::new();
+        collect_jac_files(&self.root, &mut paths).map_err(|e| Reply::from_error(e.as_ref()))?;
+        paths.sort();
+        let files = paths
+            .iter()
+            .map(|path| {
+                let bytes = std::fs::metadata(path)?.len();
+                Ok(json!({
+                    "path": self.relative(path).to_string_lossy(),
+                    "bytes": bytes,
+                }))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Reply::json(json!({ "files": files })))
+    }
+
+    fn file_info(&self, path: &Path) -> Result<Reply, Reply> {
+        let mut reader = JacReader::open_snapshot(path, snapshot_decompress_opts())?;
+        Ok(Reply::json(json!({
+            "path": self.relative(path).to_string_lossy(),
+            "bytes": std::fs::metadata(path)?.len(),
+            "blocks": reader.block_count()?,
+            "records": reader.count_records()?,
+            "indexed": reader.is_finalized(),
+        })))
+    }
+
+    fn fields(&self, path: &Path) -> Result<Reply, Reply> {
+        let mut reader = JacReader::open_snapshot(path, snapshot_decompress_opts())?;
+        let fields: Vec<Value> = reader
+            .field_catalog()?
+            .into_iter()
+            .map(|entry| {
+                json!({
+                    "name": entry.name,
+                    "types": entry.types.iter().map(|tag| tag.name()).collect::<Vec<_>>(),
+                    "blocks": entry.blocks.len(),
+                    "values": entry.value_count,
+                    "compressed_bytes": entry.compressed_bytes,
+                    "uncompressed_bytes": entry.uncompressed_bytes,
+                })
+            })
+            .collect();
+        Ok(Reply::json(json!({ "fields": fields })))
+    }
+
+    fn records(&self, path: &Path, params: &HashMap<String, String>) -> Result<Reply, Reply> {
+        let buffer = SharedBuffer::default();
+        let request = DecompressRequest::builder()
+            .input(JacInput::Path(path.to_path_buf()))
+            .output(OutputSink::Writer(Box::new(buffer.clone())))
+            .format(DecompressFormat::Ndjson)
+            .offset(number_param(params, "offset")?.unwrap_or(0))
+            .limit(self.limit(params)?)
+            .options(snapshot_decompress_options())
+            .build()?;
+        let summary = execute_decompress(request)?;
+        ServiceMetrics::add(&self.metrics.records, summary.records_written);
+        ServiceMetrics::add(&self.metrics.blocks_read, summary.metrics.blocks_decoded);
+        ServiceMetrics::add(&self.metrics.bytes_read, summary.metrics.bytes_read);
+        Ok(Reply::ndjson(buffer.take()))
+    }
+
+    fn project(&self, path: &Path, params: &HashMap<String, String>) -> Result<Reply, Reply> {
+        let select = match params.get("fields").map(String::as_str) {
+            None | Some("") | Some("*") => Selection::All,
+            Some(list) => Selection::Fields(
+                list.split(',')
+                    .map(|field| field.trim().to_string())
+                    .filter(|field| !field.is_empty())
+                    .collect(),
+            ),
+        };
+        let filter = params
+            .get("where")
+            .map(|predicate| Predicate::parse(predicate))
+            .transpose()
+            .map_err(|e| Reply::error(400, "invalid_query", e.to_string()))?;
+        let limit = usize::try_from(self.limit(params)?).unwrap_or(usize::MAX);
+
+        let buffer = SharedBuffer::default();
+        let summary = execute_query(QueryRequest {
+            input: JacInput::Path(path.to_path_buf()),
+            output: OutputSink::Writer(Box::new(buffer.clone())),
+            query: Query {
+                select,
+                filter,
+                limit: Some(limit),
+            },
+            options: snapshot_decompress_options(),
+        })?;
+        ServiceMetrics::add(&self.metrics.records, summary.rows_written);
+        ServiceMetrics::add(&self.metrics.blocks_read, summary.blocks_scanned as u64);
+        Ok(Reply::ndjson(buffer.take()))
+    }
+
+    /// Requested `limit`, capped at `max_records`.
+    fn limit(&self, params: &HashMap<String, String>) -> Result<u64, Reply> {
+        Ok(number_param(params, "limit")?
+            .map_or(self.max_records, |limit| limit.min(self.max_records)))
+    }
+}
+
+fn number_param(params: &HashMap<String, String>, name: &str) -> Result<Option<u64>, Reply> {
+    params
+        .get(name)
+        .map(|value| {
+            value.parse::<u64>().map_err(|_| {
+                Reply::error(
+                    400,
+                    "invalid_parameter",
+                    format!("'{}' must be a non-negative integer, got '{}'", name, value),
+                )
+            })
+        })
+        .transpose()
+}
+
+fn parse_params(query: &str) -> Result<HashMap<String, String>, Reply> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((percent_decode(key, true)?, percent_decode(value, true)?))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes, and `+` as a space in query strings.
+fn percent_decode(input: &str, plus_as_space: bool) -> Result<String, Reply> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'%' => {
+                let byte = input
+                    .get(idx + 1..idx + 3)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| Reply::error(400, "invalid_url", "Malformed percent escape"))?;
+                out.push(byte);
+                idx += 3;
+            }
+            b'+' if plus_as_space => {
+                out.push(b' ');
+                idx += 1;
+            }
+            byte => {
+                out.push(byte);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8(out).map_err(|_| Reply::error(400, "invalid_url", "URL is not valid UTF-8"))
+}
+
+/// Response body the decompress and query requests write into.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(
+            &mut *self
+                .0
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jac_io::{compress_records, CompressOptions};
+
+    fn service() -> (tempfile::TempDir, Service) {
+        let dir = tempfile::tempdir().unwrap();
+        let records: Vec<_> = (0..25)
+            .map(|i| {
+                let level = if i % 5 == 0 { "error" } else { "info" };
+                json!({"id": i, "level": level})
+                    .as_object()
+                    .unwrap()
+                    .clone()
+            })
+            .collect();
+        let opts = CompressOptions {
+            block_target_records: 10,
+            ..CompressOptions::default()
+        };
+        std::fs::create_dir(dir.path().join("logs")).unwrap();
+        std::fs::write(
+            dir.path().join("logs/app.jac"),
+            compress_records(records, opts).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not an archive").unwrap();
+        let service = Service {
+            root: dir.path().canonicalize().unwrap(),
+            max_records: 8,
+            metrics: ServiceMetrics::default(),
+        };
+        (dir, service)
+    }
+
+    fn body(reply: Result<Reply, Reply>) -> (u16, String) {
+        let reply = reply.unwrap_or_else(|reply| reply);
+        (reply.status, String::from_utf8(reply.body).unwrap())
+    }
+
+    fn json_body(reply: Result<Reply, Reply>) -> (u16, Value) {
+        let (status, body) = body(reply);
+        (status, serde_json::from_str(&body).unwrap())
+    }
+
+    #[test]
+    fn endpoints_read_files_under_the_root() {
+        let (_dir, service) = service();
+        let (status, files) = json_body(service.handle("/files"));
+        assert_eq!(status, 200);
+        assert_eq!(files["files"][0]["path"], "logs/app.jac");
+        assert_eq!(files["files"].as_array().unwrap().len(), 1);
+
+        let (_, info) = json_body(service.handle("/files/logs/app.jac"));
+        assert_eq!(
+            (info["blocks"].clone(), info["records"].clone()),
+            (json!(3), json!(25))
+        );
+
+        let (_, fields) = json_body(service.handle("/files/logs%2Fapp.jac/fields"));
+        assert_eq!(fields["fields"][1]["name"], "level");
+        assert_eq!(fields["fields"][1]["types"], json!(["string"]));
+
+        let (status, records) =
+            body(service.handle("/files/logs/app.jac/records?offset=12&limit=2"));
+        assert_eq!(status, 200);
+        assert_eq!(
+            records,
+            "{\"id\":12,\"level\":\"info\"}\n{\"id\":13,\"level\":\"info\"}\n"
+        );
+        let (_, capped) = body(service.handle("/files/logs/app.jac/records?limit=100"));
+        assert_eq!(capped.lines().count(), 8);
+
+        let (_, projected) =
+            body(service.handle(
+                "/files/logs/app.jac/project?fields=id&where=level+%3D+%27error%27&limit=3",
+            ));
+        assert_eq!(projected, "{\"id\":0}\n{\"id\":5}\n{\"id\":10}\n");
+
+        let (status, metrics) = body(service.handle("/metrics"));
+        assert_eq!(status, 200);
+        assert!(metrics.contains("jac_serve_records_total 13\n"));
+        assert!(metrics.contains("# TYPE jac_serve_queue_depth gauge\n"));
+    }
+
+    #[test]
+    fn records_and_project_read_files_still_being_written() {
+        let (dir, service) = service();
+        let full = dir.path().join("logs/app.jac");
+        let mut reader = JacReader::open_snapshot(&full, snapshot_decompress_opts()).unwrap();
+        let last = reader.blocks().last().unwrap().unwrap();
+        let bytes = std::fs::read(&full).unwrap();
+        let cut = last.offset as usize + last.size / 2;
+        std::fs::write(dir.path().join("logs/live.jac"), &bytes[..cut]).unwrap();
+
+        let (_, info) = json_body(service.handle("/files/logs/live.jac"));
+        assert_eq!(
+            (info["records"].clone(), info["indexed"].clone()),
+            (json!(20), json!(false))
+        );
+
+        let (status, records) =
+            body(service.handle("/files/logs/live.jac/records?offset=15&limit=10"));
+        assert_eq!(status, 200);
+        let ids: Vec<Value> = records
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["id"].clone())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![json!(15), json!(16), json!(17), json!(18), json!(19)]
+        );
+
+        let (status, projected) = body(
+            service.handle("/files/logs/live.jac/project?fields=id&where=level+%3D+%27error%27"),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(
+            projected,
+            "{\"id\":0}\n{\"id\":5}\n{\"id\":10}\n{\"id\":15}\n"
+        );
+    }
+
+    #[test]
+    fn bad_requests_are_rejected() {
+        let (_dir, service) = service();
+        for url in [
+            "/files/../outside.jac",
+            "/files/notes.txt",
+            "/files/missing.jac/fields",
+            "/files//etc/passwd.jac",
+        ] {
+            let (status, error) = json_body(service.handle(url));
+            assert_eq!(status, 404, "{url}");
+            assert_eq!(error["error"]["code"], "file_not_found");
+        }
+        let (status, error) = json_body(service.handle("/files/logs/app.jac/records?limit=x"));
+        assert_eq!(
+            (status, error["error"]["code"].clone()),
+            (400, json!("invalid_parameter"))
+        );
+        let (status, error) =
+            json_body(service.handle("/files/logs/app.jac/project?where=level+%3D"));
+        assert_eq!(
+            (status, error["error"]["code"].clone()),
+            (400, json!("invalid_query"))
+        );
+        assert_eq!(json_body(service.handle("/blocks")).0, 404);
+    }
+}
@@ -0,0 +1,405 @@
+//! `jac tui`: an interactive explorer for one archive (feature `tui`).
+//!
+//! Four panes show the blocks of the file, its field catalog, statistics for
+//! the selected field, and a preview of the selected block. Only block headers
+//! and type tags are read up front; the preview projects the columns of one
+//! block whenever the selection changes, so large archives open quickly.
+
+use jac_format::constants::{ENCODING_FLAG_DELTA, ENCODING_FLAG_DICTIONARY};
+use jac_io::{BlockHandle, FieldCatalogEntry, JacReader};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use serde_json::{Map, Value};
+use std::error::Error;
+use std::io::{Read, Seek};
+
+/// Records of the selected block shown in the preview pane.
+const PREVIEW_RECORDS: usize = 50;
+
+/// Rows moved by Page Up / Page Down.
+const PAGE_ROWS: isize = 10;
+
+/// Open the explorer on `reader` and run it until the user quits.
+pub fn run<R: Read + Seek>(reader: JacReader<R>) -> Result<(), Box<dyn Error>> {
+    let mut explorer = Explorer::new(reader)?;
+    let mut terminal = ratatui::try_init()?;
+    let result = explorer.event_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Blocks,
+    Fields,
+}
+
+struct Explorer<R: Read + Seek> {
+    reader: JacReader<R>,
+    blocks: Vec<BlockHandle>,
+    catalog: Vec<FieldCatalogEntry>,
+    records: u64,
+    focus: Pane,
+    block_state: ListState,
+    field_state: ListState,
+    preview: Vec<String>,
+}
+
+impl<R: Read + Seek> Explorer<R> {
+    fn new(mut reader: JacReader<R>) -> Result<Self, Box<dyn Error>> {
+        let blocks = reader.blocks().collect::<jac_io::Result<Vec<_>>>()?;
+        let catalog = reader.field_catalog()?;
+        let records = blocks.iter().map(|block| block.record_count as u64).sum();
+        let mut explorer = Self {
+            reader,
+            records,
+            focus: Pane::Blocks,
+            block_state: ListState::default().with_selected((!blocks.is_empty()).then_some(0)),
+            field_state: ListState::default().with_selected((!catalog.is_empty()).then_some(0)),
+            blocks,
+            catalog,
+            preview: Vec::new(),
+        };
+        explorer.refresh_preview();
+        Ok(explorer)
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Box<dyn Error>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Apply a key press; returns `false` when the explorer should exit.
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Left | KeyCode::Right => {
+                self.focus = match self.focus {
+                    Pane::Blocks => Pane::Fields,
+                    Pane::Fields => Pane::Blocks,
+                };
+                self.refresh_preview();
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.step(1),
+            KeyCode::Up | KeyCode::Char('k') => self.step(-1),
+            KeyCode::PageDown => self.step(PAGE_ROWS),
+            KeyCode::PageUp => self.step(-PAGE_ROWS),
+            KeyCode::Home => self.step(isize::MIN),
+            KeyCode::End => self.step(isize::MAX),
+            _ => {}
+        }
+        true
+    }
+
+    /// Move the selection of the focused list by `delta` rows, clamped.
+    fn step(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            Pane::Blocks => (&mut self.block_state, self.blocks.len()),
+            Pane::Fields => (&mut self.field_state, self.catalog.len()),
+        };
+        if len == 0 {
+            return;
+        }
+        let current = state.selected().unwrap_or(0) as isize;
+        let next = current.saturating_add(delta).clamp(0, len as isize - 1);
+        state.select(Some(next as usize));
+        self.refresh_preview();
+    }
+
+    fn selected_block(&self) -> Option<(usize, &BlockHandle)> {
+        let idx = self.block_state.selected()?;
+        Some((idx, self.blocks.get(idx)?))
+    }
+
+    fn selected_field(&self) -> Option<&FieldCatalogEntry> {
+        self.catalog.get(self.field_state.selected()?)
+    }
+
+    /// Project the preview for the current selection: whole records while the
+    /// block list has focus, the selected field's values while the field list
+    /// has. Decoding errors are shown in the pane instead of ending the session.
+    fn refresh_preview(&mut self) {
+        self.preview.clear();
+        let Some((_, block)) = self.selected_block() else {
+            return;
+        };
+        let block = block.clone();
+        let single = match (self.focus, self.selected_field()) {
+            (Pane::Fields, Some(entry)) => Some(entry.name.clone()),
+            _ => None,
+        };
+        let fields: Vec<String> = match &single {
+            Some(name) => vec![name.clone()],
+            None => block
+                .header
+                .fields
+                .iter()
+                .filter(|entry| !entry.is_key_order_sidecar())
+                .map(|entry| entry.field_name.clone())
+                .collect(),
+        };
+
+        let columns = match self.reader.project_columns(&block, &fields) {
+            Ok(columns) => columns,
+            Err(err) => {
+                self.preview
+                    .push(format!("Failed to decode block: {}", err));
+                return;
+            }
+        };
+        for row in 0..block.record_count.min(PREVIEW_RECORDS) {
+            let line = if single.is_some() {
+                match &columns[0][row] {
+                    Some(value) => format!("{:>6}  {}", row + 1, value),
+                    None => format!("{:>6}  -", row + 1),
+                }
+            } else {
+                let record: Map<String, Value> = fields
+                    .iter()
+                    .zip(&columns)
+                    .filter_map(|(field, column)| Some((field.clone(), column[row].clone()?)))
+                    .collect();
+                format!("{:>6}  {}", row + 1, Value::Object(record))
+            };
+            self.preview.push(line);
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [top, bottom, help] = Layout::vertical([
+            Constraint::Percentage(45),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [blocks_area, fields_area, stats_area] = Layout::horizontal([
+            Constraint::Percentage(30),
+            Constraint::Percentage(35),
+            Constraint::Percentage(35),
+        ])
+        .areas(top);
+
+        let highlight = Style::default().add_modifier(Modifier::REVERSED);
+
+        let block_items: Vec<ListItem> = self
+            .blocks
+            .iter()
+            .enumerate()
+            .map(|(idx, block)| {
+                ListItem::new(format!(
+                    "#{:<4} {:>8} rec {:>10} B",
+                    idx + 1,
+                    block.record_count,
+                    block.size
+                ))
+            })
+            .collect();
+        let blocks_title = format!(
+            "Blocks ({} blocks, {} records)",
+            self.blocks.len(),
+            self.records
+        );
+        let blocks = List::new(block_items)
+            .block(self.pane(blocks_title, self.focus == Pane::Blocks))
+            .highlight_style(highlight);
+        frame.render_stateful_widget(blocks, blocks_area, &mut self.block_state);
+
+        let field_items: Vec<ListItem> = self
+            .catalog
+            .iter()
+            .map(|entry| ListItem::new(format!("{}  [{}]", entry.name, type_names(entry))))
+            .collect();
+        let fields_title = format!("Fields ({})", self.catalog.len());
+        let fields = List::new(field_items)
+            .block(self.pane(fields_title, self.focus == Pane::Fields))
+            .highlight_style(highlight);
+        frame.render_stateful_widget(fields, fields_area, &mut self.field_state);
+
+        let stats = Paragraph::new(self.stats_lines())
+            .block(self.pane("Stats".to_string(), false))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(stats, stats_area);
+
+        let preview_title = match (self.selected_block(), self.focus, self.selected_field()) {
+            (None, _, _) => "Preview".to_string(),
+            (Some((idx, _)), Pane::Fields, Some(entry)) => {
+                format!("Values of '{}' in block #{}", entry.name, idx + 1)
+            }
+            (Some((idx, block)), _, _) => format!(
+                "Records 1-{} of {} in block #{}",
+                block.record_count.min(PREVIEW_RECORDS),
+                block.record_count,
+                idx + 1
+            ),
+        };
+        let preview = Paragraph::new(
+            self.preview
+                .iter()
+                .map(|line| Line::from(line.as_str()))
+                .collect::<Vec<_>>(),
+        )
+        .block(self.pane(preview_title, false));
+        frame.render_widget(preview, bottom);
+
+        frame.render_widget(
+            Paragraph::new("Tab switch pane  ↑/↓ select  PgUp/PgDn page  q quit"),
+            help,
+        );
+    }
+
+    fn pane(&self, title: String, focused: bool) -> Block<'static> {
+        let block = Block::default().borders(Borders::ALL).title(title);
+        if focused {
+            block.border_style(Style::default().add_modifier(Modifier::BOLD))
+        } else {
+            block
+        }
+    }
+
+    /// File-wide totals for the selected field, then its directory entry in
+    /// the selected block.
+    fn stats_lines(&self) -> Vec<Line<'static>> {
+        let Some(entry) = self.selected_field() else {
+            return vec![Line::from("No fields")];
+        };
+        let ratio = if entry.compressed_bytes == 0 {
+            0.0
+        } else {
+            entry.uncompressed_bytes as f64 / entry.compressed_bytes as f64
+        };
+        let mut lines = vec![
+            Line::from(format!("Field: {}", entry.name)),
+            Line::from(format!("Types: {}", type_names(entry))),
+            Line::from(format!(
+                "Blocks: {} of {}",
+                entry.blocks.len(),
+                self.blocks.len()
+            )),
+            Line::from(format!(
+                "Values: {} of {} records",
+                entry.value_count, self.records
+            )),
+            Line::from(format!(
+                "Bytes: {} compressed, {} raw ({:.2}x)",
+                entry.compressed_bytes, entry.uncompressed_bytes, ratio
+            )),
+            Line::from(""),
+        ];
+
+        let Some((idx, block)) = self.selected_block() else {
+            return lines;
+        };
+        match block.field_entry(&entry.name) {
+            Some(dir) => {
+                let encoding = if dir.encoding_flags & ENCODING_FLAG_DICTIONARY != 0 {
+                    format!("dictionary ({} entries)", dir.dict_entry_count)
+                } else if dir.encoding_flags & ENCODING_FLAG_DELTA != 0 {
+                    "delta".to_string()
+                } else {
+                    "plain".to_string()
+                };
+                lines.push(Line::from(format!("In block #{}:", idx + 1)));
+                lines.push(Line::from(format!(
+                    "  present in {} of {} records",
+                    dir.value_count_present, block.record_count
+                )));
+                lines.push(Line::from(format!("  encoding: {}", encoding)));
+                lines.push(Line::from(format!(
+                    "  segment: {} compressed, {} raw",
+                    dir.segment_compressed_len, dir.segment_uncompressed_len
+                )));
+            }
+            None => lines.push(Line::from(format!("Not present in block #{}", idx + 1))),
+        }
+        lines
+    }
+}
+
+fn type_names(entry: &FieldCatalogEntry) -> String {
+    entry
+        .types
+        .iter()
+        .map(|tag| tag.name())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jac_codec::DecompressOpts;
+    use jac_io::{compress_records, CompressOptions};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    fn explorer() -> Explorer<Cursor<Vec<u8>>> {
+        let records = (0..30).map(|i| {
+            let mut record = json!({"id": i, "level": if i % 2 == 0 { "info" } else { "warn" }});
+            if i >= 20 {
+                record["user"] = json!(format!("u{}", i));
+            }
+            record.as_object().unwrap().clone()
+        });
+        let opts = CompressOptions {
+            block_target_records: 10,
+            ..CompressOptions::default()
+        };
+        let bytes = compress_records(records.collect::<Vec<_>>(), opts).unwrap();
+        let reader = JacReader::new(Cursor::new(bytes), DecompressOpts::default()).unwrap();
+        Explorer::new(reader).unwrap()
+    }
+
+    fn render(explorer: &mut Explorer<Cursor<Vec<u8>>>) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|frame| explorer.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let mut screen = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                screen.push_str(buffer[(x, y)].symbol());
+            }
+            screen.push('\n');
+        }
+        screen
+    }
+
+    #[test]
+    fn panes_follow_the_selection() {
+        let mut explorer = explorer();
+        assert_eq!(explorer.preview.len(), 10);
+        assert_eq!(explorer.preview[0], r#"     1  {"id":0,"level":"info"}"#);
+        let screen = render(&mut explorer);
+        assert!(screen.contains("Blocks (3 blocks, 30 records)"));
+        assert!(screen.contains("level  [string]"));
+        assert!(screen.contains("Records 1-10 of 10 in block #1"));
+
+        explorer.handle_key(KeyCode::End);
+        assert!(explorer.preview[0].ends_with(r#"{"id":20,"level":"info","user":"u20"}"#));
+
+        explorer.handle_key(KeyCode::Tab);
+        explorer.handle_key(KeyCode::End);
+        assert_eq!(explorer.selected_field().unwrap().name, "user");
+        assert_eq!(explorer.preview[1], r#"     2  "u21""#);
+        let screen = render(&mut explorer);
+        assert!(screen.contains("Values of 'user' in block #3"));
+        assert!(screen.contains("Blocks: 1 of 3"));
+        assert!(screen.contains("present in 10 of 10 records"));
+
+        explorer.handle_key(KeyCode::Tab);
+        explorer.handle_key(KeyCode::Home);
+        assert!(render(&mut explorer).contains("Not present in block #1"));
+        assert!(!explorer.handle_key(KeyCode::Char('q')));
+    }
+}
@@ -1,11 +1,12 @@
 use jac_format::constants::{ENCODING_FLAG_DELTA, ENCODING_FLAG_DICTIONARY};
 use predicates::prelude::*;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::error::Error;
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tempfile::{tempdir, TempDir};
 
 struct SampleFile {
@@ -210,6 +211,549 @@ fn cat_unknown_field_fails() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn stat_lists_top_values() -> Result<(), Box<dyn Error>> {
+    let sample = build_sample_file()?;
+    let output = assert_cmd::Command::cargo_bin("jac")?
+        .args([
+            "stat",
+            sample.jac_path.to_str().unwrap(),
+            "--field",
+            "user",
+            "--top",
+            "1",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output)?;
+    assert!(stdout.contains("Values: 2"), "{}", stdout);
+    assert!(stdout.contains("Distinct: 2"), "{}", stdout);
+    assert!(stdout.contains("50.00%  alice"), "{}", stdout);
+    assert!(!stdout.contains("bob"), "{}", stdout);
+    Ok(())
+}
+
+#[test]
+fn stat_estimate_reads_field_stats_written_at_pack_time() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let with_stats = dir.path().join("stats.jac");
+    let without_stats = dir.path().join("plain.jac");
+    let records: Vec<String> = (0..40)
+        .map(|i| match i % 4 {
+            0 => "{\"user\":null}".to_string(),
+            _ => format!("{{\"user\":\"u{}\"}}", i % 10),
+        })
+        .collect();
+    fs::write(&input_path, records.join("\n"))?;
+    for (output, extra) in [(&with_stats, Some("--field-stats")), (&without_stats, None)] {
+        assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+            .arg("pack")
+            .arg(&input_path)
+            .arg("-o")
+            .arg(output)
+            .args(["--block-records", "16"])
+            .args(extra)
+            .assert()
+            .success();
+    }
+
+    let stat = |path: &Path| {
+        assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+            .arg("stat")
+            .arg(path)
+            .args(["--field", "user", "--estimate"])
+            .assert()
+    };
+    let output = stat(&with_stats).success().get_output().stdout.clone();
+    let stdout = String::from_utf8(output)?;
+    assert!(stdout.contains("Values: 40"), "{}", stdout);
+    assert!(stdout.contains("Nulls: 10"), "{}", stdout);
+    assert!(stdout.contains("Distinct: ~10"), "{}", stdout);
+    assert!(
+        stdout.contains("Blocks: 3 from field statistics"),
+        "{}",
+        stdout
+    );
+
+    stat(&without_stats)
+        .failure()
+        .stderr(predicate::str::contains(
+            "3 of 3 blocks holding 'user' have no field statistics",
+        ));
+    Ok(())
+}
+
+#[test]
+fn meta_exports_directory_metadata_of_several_files() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let archives = dir.path().join("archives");
+    fs::create_dir(&archives)?;
+    let input_path = dir.path().join("input.ndjson");
+    fs::write(&input_path, "{\"id\":1,\"msg\":\"a\"}\n{\"id\":2}\n")?;
+    for name in ["a.jac", "b.jac"] {
+        assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+            .arg("pack")
+            .arg(&input_path)
+            .arg("-o")
+            .arg(archives.join(name))
+            .assert()
+            .success();
+    }
+
+    let meta_path = dir.path().join("meta.jac");
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("meta")
+        .arg(&archives)
+        .arg("-o")
+        .arg(&meta_path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Wrote 4 metadata records"));
+
+    // The metadata is itself a JAC file
+    let output = assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("cat")
+        .arg(&meta_path)
+        .args(["--field", "present"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert_eq!(String::from_utf8(output)?, "2\n1\n2\n1\n");
+
+    let output = assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("meta")
+        .arg(archives.join("b.jac"))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let records: Vec<Value> = String::from_utf8(output)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(records.len(), 2);
+    assert!(records[0]["file"].as_str().unwrap().ends_with("b.jac"));
+    assert_eq!(records[1]["field"], json!("msg"));
+    assert_eq!(records[1]["block"], json!(1));
+
+    // An output inside the scanned directory is left out of the scan, even
+    // when an earlier run already created it
+    for _ in 0..2 {
+        assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+            .arg("meta")
+            .arg(&archives)
+            .arg("-o")
+            .arg(archives.join("meta.jac"))
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Wrote 4 metadata records"));
+    }
+    Ok(())
+}
+
+#[test]
+fn quantile_sketches_give_percentiles_and_prune_range_filters() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("latency.jac");
+    let output_path = dir.path().join("slow.ndjson");
+    let records: Vec<String> = (1..=300)
+        .map(|i| format!("{{\"latency\":{}}}", i))
+        .collect();
+    fs::write(&input_path, records.join("\n"))?;
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("pack")
+        .arg(&input_path)
+        .arg("-o")
+        .arg(&jac_path)
+        .args(["--block-records", "100", "--quantile-sketches"])
+        .assert()
+        .success();
+
+    let output = assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("stat")
+        .arg(&jac_path)
+        .args(["--field", "latency", "--estimate"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output)?;
+    assert!(
+        stdout.contains("Numeric values: 300 (3 of 3 blocks with quantile sketches)"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("  min  1\n"), "{}", stdout);
+    assert!(stdout.contains("  max  300\n"), "{}", stdout);
+    assert!(stdout.contains("  p50  "), "{}", stdout);
+    // Without field statistics the null and distinct counts are left out
+    assert!(!stdout.contains("Distinct"), "{}", stdout);
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("unpack")
+        .arg(&jac_path)
+        .arg("-o")
+        .arg(&output_path)
+        .args(["--where", "latency > 290"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Filter (blocks scanned: 1, blocks pruned: 2, records matched: 10",
+        ));
+    assert_eq!(fs::read_to_string(&output_path)?.lines().count(), 10);
+    Ok(())
+}
+
+#[test]
+fn count_reports_records_and_present_values() -> Result<(), Box<dyn Error>> {
+    let sample = build_sample_file()?;
+    let count = |extra: &[&str]| -> Result<String, Box<dyn Error>> {
+        let output = assert_cmd::Command::cargo_bin("jac")?
+            .arg("count")
+            .arg(&sample.jac_path)
+            .args(extra)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        Ok(String::from_utf8(output)?)
+    };
+    assert_eq!(count(&[])?, "2\n");
+    assert_eq!(count(&["--field", "user"])?, "2\n");
+    assert_eq!(count(&["--field", "missing"])?, "0\n");
+    Ok(())
+}
+
+#[test]
+fn index_adds_footer_to_unindexed_file() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("output.jac");
+    fs::write(&input_path, "{\"n\":1}\n{\"n\":2}\n{\"n\":3}\n")?;
+    assert_cmd::Command::cargo_bin("jac")?
+        .arg("pack")
+        .arg(&input_path)
+        .arg("-o")
+        .arg(&jac_path)
+        .args(["--block-records", "2", "--no-index"])
+        .assert()
+        .success();
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .arg("index")
+        .arg(&jac_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Indexed 2 blocks (3 records)"));
+    let reader = jac_io::JacReader::open(&jac_path, jac_io::DecompressOpts::default())?;
+    assert!(reader.is_finalized());
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .arg("index")
+        .arg(&jac_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already has a valid index"));
+    Ok(())
+}
+
+#[test]
+fn unpack_blocks_decodes_only_the_selected_range() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("output.jac");
+    let output_path = dir.path().join("output.ndjson");
+    let ndjson: String = (0..7)
+        .map(|n| json!({ "n": n }).to_string() + "\n")
+        .collect();
+    fs::write(&input_path, ndjson)?;
+    assert_cmd::Command::cargo_bin("jac")?
+        .arg("pack")
+        .arg(&input_path)
+        .arg("-o")
+        .arg(&jac_path)
+        .args(["--block-records", "2"])
+        .assert()
+        .success();
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .arg("unpack")
+        .arg(&jac_path)
+        .arg("-o")
+        .arg(&output_path)
+        .args(["--blocks", "2-3"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(&output_path)?,
+        "{\"n\":2}\n{\"n\":3}\n{\"n\":4}\n{\"n\":5}\n"
+    );
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .arg("unpack")
+        .arg(&jac_path)
+        .arg("-o")
+        .arg(&output_path)
+        .args(["--blocks", "5"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("exceeds file block count"));
+    Ok(())
+}
+
+#[test]
+fn gen_and_check_vectors_round_trip() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    assert_cmd::Command::cargo_bin("jac")?
+        .arg("gen-vectors")
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("spec_12_1\t4 records, 1 blocks"));
+    assert!(dir.path().join("manifest.json").exists());
+    assert!(dir.path().join("dict_overflow.jac").exists());
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .arg("check-vectors")
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("PASS unicode"))
+        .stdout(predicate::str::contains("FAIL").not());
+
+    fs::write(dir.path().join("spec_12_1.ndjson"), "{\"ts\":1}\n")?;
+    assert_cmd::Command::cargo_bin("jac")?
+        .arg("check-vectors")
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("FAIL spec_12_1"))
+        .stderr(predicate::str::contains("1 of 6 vectors failed"));
+    Ok(())
+}
+
+#[test]
+fn read_commands_expand_directories_and_globs() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let nested = dir.path().join("2024").join("01");
+    fs::create_dir_all(&nested)?;
+    let pack = |name: &str, body: &str, target: &Path| -> Result<PathBuf, Box<dyn Error>> {
+        let input = dir.path().join(format!("{}.ndjson", name));
+        fs::write(&input, body)?;
+        let output = target.join(format!("{}.jac", name));
+        assert_cmd::Command::cargo_bin("jac")?
+            .arg("pack")
+            .arg(&input)
+            .arg("-o")
+            .arg(&output)
+            .assert()
+            .success();
+        Ok(output)
+    };
+    let first = pack("a", "{\"msg\":\"one\"}\n{\"msg\":\"two\"}\n", dir.path())?;
+    let second = pack("b", "{\"msg\":\"three\"}\n", &nested)?;
+    pack("c", "{\"other\":1}\n", &nested)?;
+    let run = |args: &[&std::ffi::OsStr]| -> Result<String, Box<dyn Error>> {
+        let output = assert_cmd::Command::cargo_bin("jac")?
+            .args(args)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        Ok(String::from_utf8(output)?)
+    };
+
+    let cat = run(&[
+        "cat".as_ref(),
+        dir.path().as_os_str(),
+        "--field".as_ref(),
+        "msg".as_ref(),
+    ])?;
+    let rows: Vec<Value> = cat
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(
+        rows,
+        vec![
+            json!({"file": second.display().to_string(), "value": "three"}),
+            json!({"file": first.display().to_string(), "value": "one"}),
+            json!({"file": first.display().to_string(), "value": "two"}),
+        ]
+    );
+
+    let pattern = dir.path().join("**").join("*.jac");
+    let count = run(&["count".as_ref(), pattern.as_os_str()])?;
+    assert!(count.ends_with("4\ttotal\n"), "{}", count);
+    assert!(
+        count.contains(&format!("1\t{}\n", second.display())),
+        "{}",
+        count
+    );
+
+    let ls = run(&[
+        "ls".as_ref(),
+        pattern.as_os_str(),
+        "--format".as_ref(),
+        "json".as_ref(),
+        "--fields-only".as_ref(),
+    ])?;
+    let listings: Vec<Value> = serde_json::from_str(&ls)?;
+    assert_eq!(listings.len(), 3);
+    assert_eq!(listings[0]["file"], json!(second.display().to_string()));
+    assert_eq!(listings[2]["fields"], json!(["msg"]));
+
+    let third = nested.join("c.jac");
+    let expected = format!("{}\tother\t1 blocks", third.display());
+    let has_field = run(&[
+        "ls".as_ref(),
+        dir.path().as_os_str(),
+        "--has-field".as_ref(),
+        "other".as_ref(),
+    ])?;
+    assert_eq!(has_field, expected.clone() + "\n");
+    let typed = run(&[
+        "ls".as_ref(),
+        dir.path().as_os_str(),
+        "--field-type".as_ref(),
+        "int".as_ref(),
+    ])?;
+    assert_eq!(typed, expected + "\tint\n");
+    assert_cmd::Command::cargo_bin("jac")?
+        .arg("ls")
+        .arg(dir.path())
+        .args(["--has-field", "msg", "--field-type", "int"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No files match the field filters"));
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .arg("ls")
+        .arg(dir.path().join("*.missing"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No .jac files match"));
+    Ok(())
+}
+
+#[test]
+fn ls_and_cat_read_files_still_being_written() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("growing.jac");
+    fs::write(
+        &input_path,
+        "{\"user\":\"alice\"}\n{\"user\":\"bob\"}\n{\"user\":\"carol\"}\n",
+    )?;
+    assert_cmd::Command::cargo_bin("jac")?
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            jac_path.to_str().unwrap(),
+            "--block-records",
+            "1",
+        ])
+        .assert()
+        .success();
+
+    // Cut the file inside its last block, as a reader racing the writer would see it.
+    let mut reader = jac_io::JacReader::open(&jac_path, jac_io::DecompressOpts::default())?;
+    let blocks = reader.blocks().collect::<jac_format::Result<Vec<_>>>()?;
+    assert_eq!(blocks.len(), 3);
+    let mut bytes = fs::read(&jac_path)?;
+    bytes.truncate(blocks[2].offset as usize + blocks[2].size / 2);
+    fs::write(&jac_path, bytes)?;
+
+    let output = assert_cmd::Command::cargo_bin("jac")?
+        .args(["ls", jac_path.to_str().unwrap(), "--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let ls_json: Value = serde_json::from_slice(&output)?;
+    assert_eq!(ls_json["blocks"].as_array().unwrap().len(), 2);
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .args(["cat", jac_path.to_str().unwrap(), "--field", "user"])
+        .assert()
+        .success()
+        .stdout("\"alice\"\n\"bob\"\n");
+    Ok(())
+}
+
+#[test]
+fn query_filters_and_projects_rows() -> Result<(), Box<dyn Error>> {
+    let sample = build_sample_file()?;
+    assert_cmd::Command::cargo_bin("jac")?
+        .args([
+            "query",
+            sample.jac_path.to_str().unwrap(),
+            "select user where level = 'warn' limit 5",
+            "--verbose",
+        ])
+        .assert()
+        .success()
+        .stdout("{\"user\":\"bob\"}\n")
+        .stderr(predicate::str::contains("Rows: 1"));
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .args([
+            "query",
+            sample.jac_path.to_str().unwrap(),
+            "select user where",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid query"));
+    Ok(())
+}
+
+#[test]
+fn agg_counts_records_per_group() -> Result<(), Box<dyn Error>> {
+    let sample = build_sample_file()?;
+    let output = assert_cmd::Command::cargo_bin("jac")?
+        .args([
+            "agg",
+            sample.jac_path.to_str().unwrap(),
+            "--group-by",
+            "level",
+            "--count",
+            "--distinct",
+            "user",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output)?;
+    let rows: Vec<Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            serde_json::json!({"level": "info", "count": 1, "distinct(user)": 1}),
+            serde_json::json!({"level": "warn", "count": 1, "distinct(user)": 1}),
+        ]
+    );
+    Ok(())
+}
+
 #[test]
 fn unpack_auto_defaults_to_ndjson_output() -> Result<(), Box<dyn Error>> {
     let sample = build_sample_file()?;
@@ -276,139 +820,899 @@ fn unpack_auto_preserves_json_array_wrapper() -> Result<(), Box<dyn Error>> {
 }
 
 #[test]
-fn pack_accepts_bom_prefixed_ndjson() -> Result<(), Box<dyn Error>> {
-    let dir = tempfile::tempdir()?;
-    let input_path = dir.path().join("bom.ndjson");
-    let jac_path = dir.path().join("bom.jac");
-
-    let mut file = fs::File::create(&input_path)?;
-    file.write_all("\u{feff}{\"value\":1}\r\n".as_bytes())?;
-    file.write_all("{\"value\":2}\n".as_bytes())?;
-    file.write_all("{\"value\":3}".as_bytes())?;
+fn pack_accepts_bom_prefixed_ndjson() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("bom.ndjson");
+    let jac_path = dir.path().join("bom.jac");
+
+    let mut file = fs::File::create(&input_path)?;
+    file.write_all("\u{feff}{\"value\":1}\r\n".as_bytes())?;
+    file.write_all("{\"value\":2}\n".as_bytes())?;
+    file.write_all("{\"value\":3}".as_bytes())?;
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            jac_path.to_str().unwrap(),
+            "--ndjson",
+        ])
+        .assert()
+        .success();
+
+    let output = assert_cmd::Command::cargo_bin("jac")?
+        .args(["cat", jac_path.to_str().unwrap(), "--field", "value"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let values: Vec<i64> = String::from_utf8(output)?
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .map(|value| value.as_i64().unwrap())
+        .collect();
+    assert_eq!(values, vec![1, 2, 3]);
+
+    Ok(())
+}
+
+#[test]
+fn pack_requires_allow_flag_for_large_segments() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let output_path = dir.path().join("output.jac");
+    fs::write(&input_path, "{\"value\":1}\n")?;
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--max-segment-bytes",
+            "134217728",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--allow-large-segments"));
+    Ok(())
+}
+
+#[test]
+fn pack_allows_large_segments_with_confirmation() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let output_path = dir.path().join("output.jac");
+    fs::write(&input_path, "{\"value\":1}\n{\"value\":2}\n")?;
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--max-segment-bytes",
+            "134217728",
+            "--allow-large-segments",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: increasing --max-segment-bytes",
+        ));
+    Ok(())
+}
+
+#[test]
+fn pack_verbose_reports_parallel_decision() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let output_path = dir.path().join("output.jac");
+    fs::write(&input_path, "{\"value\":1}\n{\"value\":2}\n{\"value\":3}\n")?;
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .env_remove("JAC_PARALLEL_MEMORY_FACTOR")
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--verbose-metrics",
+        ])
+        .assert()
+        .success()
+        .stderr(
+            predicate::str::contains("Small input file")
+                .and(predicate::str::contains("reservation factor 0.75")),
+        );
+    Ok(())
+}
+
+#[test]
+fn pack_threads_one_forces_sequential_message() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let output_path = dir.path().join("output.jac");
+    fs::write(&input_path, "{\"value\":1}\n{\"value\":2}\n")?;
+
+    assert_cmd::Command::cargo_bin("jac")?
+        .env_remove("JAC_PARALLEL_MEMORY_FACTOR")
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--threads",
+            "1",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Sequential mode forced by thread cap",
+        ));
+    Ok(())
+}
+
+#[test]
+fn pack_options_file_sets_defaults_and_flags_override() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let output_path = dir.path().join("output.jac");
+    let options_path = dir.path().join("pack.yaml");
+    fs::write(&input_path, "{\"value\":1}\n{\"value\":2}\n{\"value\":3}\n")?;
+    fs::write(
+        &options_path,
+        "block_target_records: 1\ndefault_codec: {zstd: 1}\nutf8_policy: lossy\n",
+    )?;
+
+    let pack = |extra: &[&str]| -> Result<assert_cmd::assert::Assert, Box<dyn Error>> {
+        Ok(assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+            .env_remove("JAC_PARALLEL_MEMORY_FACTOR")
+            .args([
+                "pack",
+                input_path.to_str().unwrap(),
+                "-o",
+                output_path.to_str().unwrap(),
+                "--options-file",
+                options_path.to_str().unwrap(),
+                "--verbose-metrics",
+            ])
+            .args(extra)
+            .assert())
+    };
+
+    pack(&[])?
+        .success()
+        .stderr(predicate::str::contains("blocks: 3"));
+    pack(&["--block-records", "2"])?
+        .success()
+        .stderr(predicate::str::contains("blocks: 2"));
+
+    fs::write(&options_path, "block_target_records: [1]\n")?;
+    pack(&[])?
+        .failure()
+        .stderr(predicate::str::contains("invalid options file"));
+    Ok(())
+}
+
+#[test]
+fn pack_profile_sets_codec_level_and_flags_override() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let output_path = dir.path().join("output.jac");
+    fs::write(&input_path, "{\"b\":1,\"a\":2}\n{\"b\":3,\"a\":4}\n")?;
+
+    let packed_level = |extra: &[&str]| -> Result<u8, Box<dyn Error>> {
+        assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+            .args([
+                "pack",
+                input_path.to_str().unwrap(),
+                "-o",
+                output_path.to_str().unwrap(),
+            ])
+            .args(extra)
+            .assert()
+            .success();
+        let reader = jac_io::JacReader::open(&output_path, jac_io::DecompressOpts::default())?;
+        Ok(reader.file_header().default_compression_level)
+    };
+
+    assert_eq!(packed_level(&["--profile", "max"])?, 19);
+    assert_eq!(packed_level(&["--fast"])?, 1);
+    assert_eq!(
+        packed_level(&["--profile", "fast", "--zstd-level", "9"])?,
+        9
+    );
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--fast",
+            "--max-compression",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+#[test]
+fn pack_zstd_level_auto_reports_chosen_level() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let output_path = dir.path().join("output.jac");
+    let input: String = (0..200)
+        .map(|i| json!({"id": i, "level": "info"}).to_string() + "\n")
+        .collect();
+    fs::write(&input_path, input)?;
+
+    let pack = |level: &str| {
+        assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+            .args([
+                "pack",
+                input_path.to_str().unwrap(),
+                "-o",
+                output_path.to_str().unwrap(),
+                "--zstd-level",
+                level,
+                "--verbose-metrics",
+            ])
+            .assert()
+    };
+
+    pack("auto").success().stderr(
+        predicate::str::contains("Auto zstd level:")
+            .and(predicate::str::contains("sampled 200 records"))
+            .and(predicate::str::contains("  Level 19:")),
+    );
+    pack("fastest")
+        .failure()
+        .stderr(predicate::str::contains("or 'auto'"));
+    Ok(())
+}
+
+#[test]
+fn pack_empty_input_round_trips_or_fails_on_request() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("empty.ndjson");
+    let jac_path = dir.path().join("empty.jac");
+    let unpacked_path = dir.path().join("empty.json");
+    fs::write(&input_path, "")?;
+
+    let jac = || assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"));
+    jac()
+        .args(["pack", input_path.to_str().unwrap(), "-o"])
+        .arg(&jac_path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("records: 0, blocks: 0"));
+    jac()
+        .args(["ls", "--format", "json"])
+        .arg(&jac_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"blocks\": []"));
+    jac()
+        .args(["unpack", "--json-array", "-o"])
+        .arg(&unpacked_path)
+        .arg(&jac_path)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&unpacked_path)?, "[]");
+
+    let refused_path = dir.path().join("refused.jac");
+    jac()
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "--fail-on-empty",
+            "-o",
+        ])
+        .arg(&refused_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("input contains no records"));
+    assert!(!refused_path.exists());
+    Ok(())
+}
+
+#[test]
+fn pack_normalize_keys_merges_spellings_and_reports_collisions() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("output.jac");
+    let unpacked_path = dir.path().join("output.ndjson");
+    fs::write(
+        &input_path,
+        "{\"userId\":1}\n{\"userid\":2}\n{\"UserID\":3,\"userid\":4}\n",
+    )?;
+
+    let pack = |collision: &str| {
+        assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+            .args(["pack", input_path.to_str().unwrap(), "-o"])
+            .arg(&jac_path)
+            .args(["--normalize-keys", "case,nfc", "--key-collision", collision])
+            .arg("--verbose-metrics")
+            .assert()
+    };
+    pack("error")
+        .failure()
+        .stderr(predicate::str::contains("'UserID' and 'userid'"));
+    pack("last-wins").success().stderr(
+        predicate::str::contains(
+            "Normalized 1 keys into 1 names (1 records with collisions, 1 keys dropped)",
+        )
+        .and(predicate::str::contains("userid <- UserID, userId")),
+    );
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["unpack", "-o"])
+        .arg(&unpacked_path)
+        .arg(&jac_path)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(&unpacked_path)?,
+        "{\"userid\":1}\n{\"userid\":2}\n{\"userid\":4}\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn pack_intern_field_names_lists_and_unpacks() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("output.jac");
+    let unpacked_path = dir.path().join("output.ndjson");
+    let input: String = (0..30)
+        .map(|i| json!({"user": format!("u{}", i % 3), "seq": i}).to_string() + "\n")
+        .collect();
+    fs::write(&input_path, &input)?;
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["pack", input_path.to_str().unwrap(), "-o"])
+        .arg(&jac_path)
+        .args(["--block-records", "10", "--intern-field-names"])
+        .assert()
+        .success();
+
+    let output = assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["ls", jac_path.to_str().unwrap(), "--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let value: Value = serde_json::from_slice(&output)?;
+    assert_eq!(value["fields"], json!(["seq", "user"]));
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["unpack", "-o"])
+        .arg(&unpacked_path)
+        .arg(&jac_path)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&unpacked_path)?, input);
+    Ok(())
+}
+
+#[test]
+fn pack_header_crc_round_trips_and_rejects_damaged_header() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("output.jac");
+    let unpacked_path = dir.path().join("output.ndjson");
+    fs::write(&input_path, "{\"id\":1}\n{\"id\":2}\n")?;
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["pack", input_path.to_str().unwrap(), "-o"])
+        .arg(&jac_path)
+        .arg("--header-crc")
+        .assert()
+        .success();
+    let unpack = || {
+        assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+            .args(["unpack", "-o"])
+            .arg(&unpacked_path)
+            .arg(&jac_path)
+            .assert()
+    };
+    unpack().success();
+    assert_eq!(
+        fs::read_to_string(&unpacked_path)?,
+        "{\"id\":1}\n{\"id\":2}\n"
+    );
+
+    // Flip the block's record count, just past its magic and header length.
+    let mut bytes = fs::read(&jac_path)?;
+    let block = bytes
+        .windows(4)
+        .position(|window| window == b"BLK1")
+        .unwrap();
+    bytes[block + 5] ^= 0x01;
+    fs::write(&jac_path, bytes)?;
+    unpack()
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("ChecksumMismatch"))
+        .stderr(predicate::str::contains(r#""code":"checksum_mismatch""#));
+    Ok(())
+}
+
+#[test]
+fn failures_end_with_a_structured_error_trailer() -> Result<(), Box<dyn Error>> {
+    let sample = build_sample_file()?;
+    let trailer = |output: &std::process::Output| -> Value {
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        serde_json::from_str(stderr.lines().last().unwrap()).unwrap()
+    };
+
+    let missing = assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["ls", "does-not-exist.jac"])
+        .assert()
+        .failure()
+        .code(3);
+    let error = &trailer(missing.get_output())["error"];
+    assert_eq!(error["category"], "io");
+    assert_eq!(error["exit_code"], 3);
+
+    let usage = assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["ls", "--stats", "--stats-sample", "0"])
+        .arg(&sample.jac_path)
+        .assert()
+        .failure()
+        .code(2);
+    let error = &trailer(usage.get_output())["error"];
+    assert_eq!(error["code"], "invalid_argument");
+    assert_eq!(error["category"], "usage");
+    assert_eq!(error["message"], "--stats-sample must be greater than 0");
+    Ok(())
+}
+
+#[test]
+fn unpack_skip_nested_validation_keeps_nested_values() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("output.jac");
+    let output_path = dir.path().join("decoded.ndjson");
+    let input = "{\"id\":1,\"doc\":{\"tags\":[\"a\",\"b\"],\"n\":null}}\n{\"id\":2,\"doc\":[1,{\"x\":true}]}\n";
+    fs::write(&input_path, input)?;
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            jac_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args([
+            "unpack",
+            jac_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--skip-nested-validation",
+        ])
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(&output_path)?, input);
+    Ok(())
+}
+
+#[test]
+fn unpack_canonical_sorts_keys_and_normalizes_numbers() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("output.jac");
+    let output_path = dir.path().join("decoded.ndjson");
+    fs::write(&input_path, "{\"b\":2.0,\"a\":{\"d\":[1.5],\"c\":true}}\n")?;
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            jac_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args([
+            "unpack",
+            jac_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--ndjson",
+            "--canonical",
+        ])
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&output_path)?,
+        "{\"a\":{\"c\":true,\"d\":[1.5]},\"b\":2}\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn unpack_crlf_and_encoding_shape_text_output() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("output.jac");
+    let output_path = dir.path().join("decoded.ndjson");
+    fs::write(&input_path, "{\"city\":\"Köln\"}\n{\"city\":\"Oslo\"}\n")?;
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            jac_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    let unpack = |extra: &[&str]| -> Result<Vec<u8>, Box<dyn Error>> {
+        assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+            .args([
+                "unpack",
+                jac_path.to_str().unwrap(),
+                "-o",
+                output_path.to_str().unwrap(),
+            ])
+            .args(extra)
+            .assert()
+            .success();
+        Ok(fs::read(&output_path)?)
+    };
+
+    assert_eq!(
+        unpack(&["--crlf", "--encoding", "utf8-bom"])?,
+        "\u{feff}{\"city\":\"Köln\"}\r\n{\"city\":\"Oslo\"}\r\n".as_bytes()
+    );
+    assert_eq!(
+        unpack(&["--encoding", "ascii"])?,
+        b"{\"city\":\"K\\u00f6ln\"}\n{\"city\":\"Oslo\"}\n"
+    );
+    assert_eq!(
+        unpack(&["--validate-utf8"])?,
+        "{\"city\":\"Köln\"}\n{\"city\":\"Oslo\"}\n".as_bytes()
+    );
+    Ok(())
+}
+
+#[test]
+fn unpack_require_pointer_filters_records() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("output.jac");
+    let output_path = dir.path().join("decoded.ndjson");
+    fs::write(
+        &input_path,
+        "{\"id\":1,\"error\":{\"code\":7}}\n{\"id\":2}\n{\"id\":3,\"error\":{}}\n",
+    )?;
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            jac_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args([
+            "unpack",
+            jac_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--require-pointer",
+            "/error/code",
+        ])
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&output_path)?,
+        "{\"id\":1,\"error\":{\"code\":7}}\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn unpack_and_cat_where_filter_records() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("output.jac");
+    let output_path = dir.path().join("decoded.ndjson");
+    fs::write(
+        &input_path,
+        "{\"id\":1,\"level\":\"INFO\",\"path\":\"/health\"}\n\
+         {\"id\":2,\"level\":\"ERROR\",\"path\":\"/api/users\"}\n\
+         {\"id\":3,\"level\":\"WARN\",\"path\":\"/api/orders\"}\n",
+    )?;
 
-    assert_cmd::Command::cargo_bin("jac")?
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
         .args([
             "pack",
             input_path.to_str().unwrap(),
             "-o",
             jac_path.to_str().unwrap(),
-            "--ndjson",
         ])
         .assert()
         .success();
-
-    let output = assert_cmd::Command::cargo_bin("jac")?
-        .args(["cat", jac_path.to_str().unwrap(), "--field", "value"])
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args([
+            "unpack",
+            jac_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--where",
+            "level in ('WARN', 'ERROR') and path matches '^/api/(users|orders)$'",
+        ])
         .assert()
         .success()
-        .get_output()
-        .stdout
-        .clone();
-
-    let values: Vec<i64> = String::from_utf8(output)?
+        .stderr(predicate::str::contains(
+            "Filter (blocks scanned: 1, blocks pruned: 0, records matched: 2",
+        ));
+    let ids: Vec<Value> = fs::read_to_string(&output_path)?
         .lines()
-        .map(|line| serde_json::from_str::<Value>(line).unwrap())
-        .map(|value| value.as_i64().unwrap())
+        .map(|line| serde_json::from_str::<Value>(line).unwrap()["id"].clone())
         .collect();
-    assert_eq!(values, vec![1, 2, 3]);
-
-    Ok(())
-}
+    assert_eq!(ids, [json!(2), json!(3)]);
 
-#[test]
-fn pack_requires_allow_flag_for_large_segments() -> Result<(), Box<dyn Error>> {
-    let dir = tempfile::tempdir()?;
-    let input_path = dir.path().join("input.ndjson");
-    let output_path = dir.path().join("output.jac");
-    fs::write(&input_path, "{\"value\":1}\n")?;
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args([
+            "cat",
+            jac_path.to_str().unwrap(),
+            "--field",
+            "id",
+            "--where",
+            "not path starts with '/api'",
+        ])
+        .assert()
+        .success()
+        .stdout("1\n")
+        .stderr(predicate::str::contains("records matched: 1"));
 
-    assert_cmd::Command::cargo_bin("jac")?
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
         .args([
-            "pack",
-            input_path.to_str().unwrap(),
-            "-o",
-            output_path.to_str().unwrap(),
-            "--max-segment-bytes",
-            "134217728",
+            "cat",
+            jac_path.to_str().unwrap(),
+            "--field",
+            "id",
+            "--where",
+            "level in (",
         ])
         .assert()
         .failure()
-        .stderr(predicate::str::contains("--allow-large-segments"));
+        .stderr(predicate::str::contains("Invalid filter"));
     Ok(())
 }
 
 #[test]
-fn pack_allows_large_segments_with_confirmation() -> Result<(), Box<dyn Error>> {
+fn unpack_verbose_metrics_reports_decode_work() -> Result<(), Box<dyn Error>> {
     let dir = tempfile::tempdir()?;
     let input_path = dir.path().join("input.ndjson");
-    let output_path = dir.path().join("output.jac");
-    fs::write(&input_path, "{\"value\":1}\n{\"value\":2}\n")?;
+    let jac_path = dir.path().join("output.jac");
+    let unpacked_path = dir.path().join("output.ndjson");
+    fs::write(
+        &input_path,
+        "{\"id\":1,\"tag\":\"a\"}\n{\"id\":2,\"tag\":\"b\"}\n",
+    )?;
 
-    assert_cmd::Command::cargo_bin("jac")?
-        .args([
-            "pack",
-            input_path.to_str().unwrap(),
-            "-o",
-            output_path.to_str().unwrap(),
-            "--max-segment-bytes",
-            "134217728",
-            "--allow-large-segments",
-        ])
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["pack", input_path.to_str().unwrap(), "-o"])
+        .arg(&jac_path)
+        .assert()
+        .success();
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["unpack", "--verbose-metrics", "-o"])
+        .arg(&unpacked_path)
+        .arg(&jac_path)
         .assert()
         .success()
         .stderr(predicate::str::contains(
-            "Warning: increasing --max-segment-bytes",
+            "blocks decoded: 1, segments decompressed: 2, decode time:",
         ));
     Ok(())
 }
 
 #[test]
-fn pack_verbose_reports_parallel_decision() -> Result<(), Box<dyn Error>> {
+fn unpack_msgpack_writes_binary_records() -> Result<(), Box<dyn Error>> {
     let dir = tempfile::tempdir()?;
     let input_path = dir.path().join("input.ndjson");
-    let output_path = dir.path().join("output.jac");
-    fs::write(&input_path, "{\"value\":1}\n{\"value\":2}\n{\"value\":3}\n")?;
+    let jac_path = dir.path().join("output.jac");
+    let unpacked_path = dir.path().join("output.msgpack");
+    fs::write(&input_path, "{\"id\":1}\n{\"id\":2}\n")?;
 
-    assert_cmd::Command::cargo_bin("jac")?
-        .env_remove("JAC_PARALLEL_MEMORY_FACTOR")
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["pack", input_path.to_str().unwrap(), "-o"])
+        .arg(&jac_path)
+        .assert()
+        .success();
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["unpack", "--msgpack", "--ndjson", "-o"])
+        .arg(&unpacked_path)
+        .arg(&jac_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    let unpack = assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["unpack", "--msgpack", "-o"])
+        .arg(&unpacked_path)
+        .arg(&jac_path)
+        .assert();
+    if cfg!(feature = "msgpack") {
+        unpack.success();
+        // Two one-entry fixmaps: {"id": 1} and {"id": 2}.
+        assert_eq!(
+            fs::read(&unpacked_path)?,
+            [
+                &[0x81, 0xa2, b'i', b'd', 0x01][..],
+                &[0x81, 0xa2, b'i', b'd', 0x02]
+            ]
+            .concat()
+        );
+    } else {
+        unpack
+            .failure()
+            .stderr(predicate::str::contains("requires the `msgpack` feature"));
+    }
+    Ok(())
+}
+
+#[test]
+fn pack_dedupe_key_drops_resent_records() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("output.jac");
+    let unpacked_path = dir.path().join("output.ndjson");
+    fs::write(
+        &input_path,
+        "{\"id\":1,\"try\":1}\n{\"id\":2,\"try\":1}\n{\"id\":1,\"try\":2}\n",
+    )?;
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
         .args([
             "pack",
             input_path.to_str().unwrap(),
+            "--dedupe-key",
+            "id",
             "-o",
-            output_path.to_str().unwrap(),
-            "--verbose-metrics",
         ])
+        .arg(&jac_path)
         .assert()
         .success()
-        .stderr(
-            predicate::str::contains("Small input file")
-                .and(predicate::str::contains("reservation factor 0.75")),
-        );
+        .stderr(predicate::str::contains("Dropped 1 duplicate records"));
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["unpack", "-o"])
+        .arg(&unpacked_path)
+        .arg(&jac_path)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(&unpacked_path)?,
+        "{\"id\":1,\"try\":1}\n{\"id\":2,\"try\":1}\n"
+    );
     Ok(())
 }
 
 #[test]
-fn pack_threads_one_forces_sequential_message() -> Result<(), Box<dyn Error>> {
+fn pack_max_output_bytes_continues_into_part_files() -> Result<(), Box<dyn Error>> {
     let dir = tempfile::tempdir()?;
     let input_path = dir.path().join("input.ndjson");
-    let output_path = dir.path().join("output.jac");
-    fs::write(&input_path, "{\"value\":1}\n{\"value\":2}\n")?;
+    let jac_path = dir.path().join("output.jac");
+    let unpacked_path = dir.path().join("part2.ndjson");
+    let input = (0..60)
+        .map(|i| format!("{{\"id\":{}}}\n", i))
+        .collect::<Vec<_>>()
+        .concat();
+    fs::write(&input_path, input)?;
 
-    assert_cmd::Command::cargo_bin("jac")?
-        .env_remove("JAC_PARALLEL_MEMORY_FACTOR")
+    // A 1-byte cap leaves every block in a file of its own.
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
         .args([
             "pack",
             input_path.to_str().unwrap(),
-            "-o",
-            output_path.to_str().unwrap(),
-            "--threads",
+            "--block-records",
+            "20",
+            "--max-output-bytes",
             "1",
+            "-o",
         ])
+        .arg(&jac_path)
         .assert()
         .success()
         .stderr(predicate::str::contains(
-            "Sequential mode forced by thread cap",
+            "Split at --max-output-bytes into 3 files",
         ));
+    assert!(dir.path().join("output.part3.jac").exists());
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["unpack", "-o"])
+        .arg(&unpacked_path)
+        .arg(dir.path().join("output.part2.jac"))
+        .assert()
+        .success();
+    let expected = (20..40)
+        .map(|i| format!("{{\"id\":{}}}\n", i))
+        .collect::<Vec<_>>()
+        .concat();
+    assert_eq!(fs::read_to_string(&unpacked_path)?, expected);
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn pack_interrupt_removes_or_finalizes_the_output() -> Result<(), Box<dyn Error>> {
+    use std::process::{Command, Stdio};
+    use std::time::Duration;
+
+    let dir = tempfile::tempdir()?;
+    // Pack from a FIFO, interrupting once 50 records have been fed in.
+    let interrupted_pack = |name: &str, extra: &[&str]| -> Result<_, Box<dyn Error>> {
+        let fifo = dir.path().join(format!("{name}.ndjson"));
+        let jac_path = dir.path().join(format!("{name}.jac"));
+        assert!(Command::new("mkfifo").arg(&fifo).status()?.success());
+        let child = Command::new(env!("CARGO_BIN_EXE_jac"))
+            .args(["pack", "--ndjson", "--threads", "1"])
+            .args(extra)
+            .arg(&fifo)
+            .arg("-o")
+            .arg(&jac_path)
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut feed = fs::OpenOptions::new().write(true).open(&fifo)?;
+        for i in 0..50 {
+            writeln!(feed, "{{\"id\":{}}}", i)?;
+        }
+        feed.flush()?;
+        std::thread::sleep(Duration::from_millis(300));
+        let kill = Command::new("kill")
+            .args(["-INT", &child.id().to_string()])
+            .status()?;
+        assert!(kill.success());
+        std::thread::sleep(Duration::from_millis(100));
+        // One more line wakes the reader, which then sees the interrupt.
+        writeln!(feed, "{{\"id\":50}}")?;
+        drop(feed);
+        let output = child.wait_with_output()?;
+        Ok((jac_path, output))
+    };
+
+    let (jac_path, output) = interrupted_pack("removed", &[])?;
+    assert_eq!(output.status.code(), Some(130));
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("removed partial output"), "{stderr}");
+    assert!(stderr.contains("\"code\":\"interrupted\""), "{stderr}");
+    assert!(!jac_path.exists());
+
+    let (jac_path, output) = interrupted_pack("kept", &["--finalize-on-interrupt"])?;
+    assert_eq!(output.status.code(), Some(130));
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(
+        stderr.contains("with the 50 records read so far"),
+        "{stderr}"
+    );
+    let unpacked_path = dir.path().join("kept.out.ndjson");
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .args(["unpack", "-o"])
+        .arg(&unpacked_path)
+        .arg(&jac_path)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&unpacked_path)?.lines().count(), 50);
     Ok(())
 }
 
@@ -617,3 +1921,420 @@ fn segment_limit_error_message_is_helpful() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn unpack_applies_schema_mapping() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("mixed.jac");
+    let mapping = dir.path().join("mapping.json");
+    let output = dir.path().join("output.ndjson");
+    fs::write(
+        &input,
+        "{\"user\":42,\"ts\":1}\n{\"user_id\":\"7\",\"ts\":2,\"region\":\"eu\"}\n",
+    )?;
+    fs::write(
+        &mapping,
+        r#"{"rename": {"user": "user_id"}, "cast": {"user_id": "to_string"}, "defaults": {"region": "unknown"}}"#,
+    )?;
+    let jac = || assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"));
+    jac()
+        .arg("pack")
+        .arg(&input)
+        .arg("-o")
+        .arg(&jac_path)
+        .assert()
+        .success();
+
+    jac()
+        .arg("unpack")
+        .arg(&jac_path)
+        .arg("-o")
+        .arg(&output)
+        .arg("--schema-map")
+        .arg(&mapping)
+        .args(["--rename", "ts=timestamp"])
+        .assert()
+        .success();
+    let records: Vec<Value> = fs::read_to_string(&output)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(
+        records,
+        vec![
+            json!({"user_id": "42", "timestamp": 1, "region": "unknown"}),
+            json!({"user_id": "7", "timestamp": 2, "region": "eu"}),
+        ]
+    );
+
+    fs::write(&mapping, r#"{"renames": {"user": "user_id"}}"#)?;
+    jac()
+        .arg("unpack")
+        .arg(&jac_path)
+        .arg("-o")
+        .arg(&output)
+        .arg("--schema-map")
+        .arg(&mapping)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid schema mapping"));
+    Ok(())
+}
+
+#[test]
+fn ls_stats_reports_value_level_stats() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("values.jac");
+    fs::write(
+        &input,
+        "{\"name\":\"ab\",\"n\":-3,\"price\":1.25}\n\
+         {\"name\":\"abcdef\",\"n\":40,\"price\":2.5}\n\
+         {\"name\":\"x\",\"n\":7,\"price\":0.75}\n",
+    )?;
+    let jac = || assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"));
+    jac()
+        .arg("pack")
+        .arg(&input)
+        .arg("-o")
+        .arg(&jac_path)
+        .assert()
+        .success();
+
+    let output = jac()
+        .arg("ls")
+        .arg(&jac_path)
+        .args(["--format", "json", "--stats"])
+        .output()?;
+    assert!(output.status.success());
+    let value: Value = serde_json::from_slice(&output.stdout)?;
+    let stats = value["stats"].as_array().unwrap();
+    let field = |name: &str| stats.iter().find(|entry| entry["field_name"] == name);
+    let name = field("name").unwrap();
+    assert_eq!(name["string_length"]["avg"], json!(3.0));
+    assert_eq!(name["string_length"]["max"], json!(6));
+    assert!(name.get("int_range").is_none());
+    assert_eq!(
+        field("n").unwrap()["int_range"],
+        json!({"min": -3, "max": 40})
+    );
+    assert_eq!(
+        field("price").unwrap()["decimal_scales"],
+        json!({"1": 1, "2": 2})
+    );
+    Ok(())
+}
+
+#[test]
+fn pack_rejects_jac_input_unless_forced() -> Result<(), Box<dyn Error>> {
+    let sample = build_sample_file()?;
+    let dir = tempdir()?;
+    let output = dir.path().join("again.jac");
+    let jac = || assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"));
+    jac()
+        .arg("pack")
+        .arg(&sample.jac_path)
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is already a JAC file"))
+        .stderr(predicate::str::contains("jac unpack"))
+        .stderr(predicate::str::contains("--force-treat-as-text"));
+    assert!(!output.exists());
+
+    // Forced, the binary is read as NDJSON and fails as it did before.
+    jac()
+        .arg("pack")
+        .arg(&sample.jac_path)
+        .arg("-o")
+        .arg(&output)
+        .arg("--force-treat-as-text")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is already a JAC file").not());
+    Ok(())
+}
+
+#[test]
+fn pack_sniffs_input_format_and_suggests_wrappers() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let jac = || assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"));
+    let unpacked = |jac_path: &Path| -> Result<Vec<Value>, Box<dyn Error>> {
+        let output = dir.path().join("unpacked.ndjson");
+        jac()
+            .arg("unpack")
+            .arg(jac_path)
+            .arg("-o")
+            .arg(&output)
+            .arg("--ndjson")
+            .assert()
+            .success();
+        Ok(fs::read_to_string(&output)?
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?)
+    };
+
+    // NDJSON is recognised despite a .json extension.
+    let ndjson = dir.path().join("lines.json");
+    fs::write(&ndjson, "{\"id\":1}\n{\"id\":2}\n")?;
+    let jac_path = dir.path().join("lines.jac");
+    jac()
+        .arg("pack")
+        .arg(&ndjson)
+        .arg("-o")
+        .arg(&jac_path)
+        .assert()
+        .success();
+    assert_eq!(
+        unpacked(&jac_path)?,
+        vec![json!({"id": 1}), json!({"id": 2})]
+    );
+
+    let envelope = dir.path().join("envelope.json");
+    fs::write(&envelope, r#"{"page":1,"data":[{"id":1},{"id":2}]}"#)?;
+    let jac_path = dir.path().join("envelope.jac");
+    jac()
+        .arg("pack")
+        .arg(&envelope)
+        .arg("-o")
+        .arg(&jac_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Candidates in the first 64 KiB: --wrapper-pointer '/data' (2 records seen)",
+        ));
+    jac()
+        .arg("pack")
+        .arg(&envelope)
+        .arg("-o")
+        .arg(&jac_path)
+        .args(["--wrapper-pointer", "/data"])
+        .assert()
+        .success();
+    assert_eq!(
+        unpacked(&jac_path)?,
+        vec![json!({"id": 1}), json!({"id": 2})]
+    );
+
+    // --json-array keeps the object as one record.
+    jac()
+        .arg("pack")
+        .arg(&envelope)
+        .arg("-o")
+        .arg(&jac_path)
+        .arg("--json-array")
+        .assert()
+        .success();
+    assert_eq!(unpacked(&jac_path)?.len(), 1);
+
+    // Arrays of header rows only get a hint.
+    let rows = dir.path().join("rows.json");
+    fs::write(&rows, r#"[["id","name"],[1,"a"]]"#)?;
+    jac()
+        .arg("pack")
+        .arg(&rows)
+        .arg("-o")
+        .arg(&jac_path)
+        .assert()
+        .stderr(predicate::str::contains(
+            "Hint: the input looks like an envelope around its records; --wrapper-array-headers",
+        ));
+
+    // A first NDJSON line longer than the sniffed prefix is not an envelope.
+    let long_line = dir.path().join("long.ndjson");
+    let pad = "x".repeat(70_000);
+    fs::write(
+        &long_line,
+        format!(
+            "{{\"events\":[{{\"k\":0}},{{\"k\":1}}],\"pad\":\"{}\"}}\n{{\"events\":[]}}\n",
+            pad
+        ),
+    )?;
+    jac()
+        .arg("pack")
+        .arg(&long_line)
+        .arg("-o")
+        .arg(&jac_path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Hint: the input looks like an envelope",
+        ));
+    assert_eq!(unpacked(&jac_path)?.len(), 2);
+
+    // Nor is an envelope in a file named as NDJSON refused.
+    let named = dir.path().join("envelope.ndjson");
+    fs::write(&named, r#"{"page":1,"data":[{"id":1},{"id":2}]}"#)?;
+    jac()
+        .arg("pack")
+        .arg(&named)
+        .arg("-o")
+        .arg(&jac_path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "--wrapper-pointer '/data' would extract them",
+        ));
+    assert_eq!(unpacked(&jac_path)?.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn pack_dictionary_policy_flags() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input = dir.path().join("events.ndjson");
+    let mut ndjson = String::new();
+    for i in 0..16 {
+        let level = ["info", "warn"][i % 2];
+        let record = json!({ "level": level, "host": format!("h{}", i % 4) });
+        ndjson.push_str(&format!("{}\n", record));
+    }
+    fs::write(&input, ndjson)?;
+    let jac_path = dir.path().join("events.jac");
+    let jac = || assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"));
+    let dictionary_fields = |extra: &[&str]| -> Result<Vec<String>, Box<dyn Error>> {
+        jac()
+            .arg("pack")
+            .arg(&input)
+            .arg("-o")
+            .arg(&jac_path)
+            .args(extra)
+            .assert()
+            .success();
+        let output = jac()
+            .arg("ls")
+            .arg(&jac_path)
+            .args(["--format", "json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let ls: Value = serde_json::from_slice(&output)?;
+        let mut names: Vec<String> = ls["blocks"][0]["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|field| {
+                field["encoding_flags"].as_u64().unwrap() & ENCODING_FLAG_DICTIONARY != 0
+            })
+            .map(|field| field["name"].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        Ok(names)
+    };
+
+    // Four hosts among 16 values exceed the default one-in-eight ratio.
+    assert_eq!(dictionary_fields(&[])?, ["level"]);
+    assert_eq!(
+        dictionary_fields(&["--dict-max-ratio", "0.25"])?,
+        ["host", "level"]
+    );
+    assert_eq!(
+        dictionary_fields(&["--dict-max-ratio", "0.25", "--no-dict-field", "level"])?,
+        ["host"]
+    );
+
+    jac()
+        .arg("pack")
+        .arg(&input)
+        .arg("-o")
+        .arg(&jac_path)
+        .args(["--dict-max-ratio", "1.5"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--dict-max-ratio must be between 0 and 1",
+        ));
+    Ok(())
+}
+
+#[test]
+fn rewrite_drops_records_older_than_the_retention_period() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input_path = dir.path().join("input.ndjson");
+    let jac_path = dir.path().join("events.jac");
+    let recent_path = dir.path().join("recent.jac");
+    let now_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
+    let day_ms = 86_400_000;
+    let records = [
+        json!({"id": 1, "ts": "2020-01-01T00:00:00Z"}),
+        json!({"id": 2, "ts": now_ms - 100 * day_ms}),
+        json!({"id": 3}),
+        json!({"id": 4, "ts": now_ms - 40 * day_ms}),
+        json!({"id": 5, "ts": now_ms - day_ms}),
+        json!({"id": 6, "ts": now_ms}),
+    ];
+    let lines: Vec<String> = records.iter().map(Value::to_string).collect();
+    fs::write(&input_path, lines.join("\n"))?;
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("pack")
+        .arg(&input_path)
+        .arg("-o")
+        .arg(&jac_path)
+        .args(["--block-records", "2"])
+        .assert()
+        .success();
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("rewrite")
+        .arg(&jac_path)
+        .arg("-o")
+        .arg(&recent_path)
+        .args(["--drop-older-than", "30d", "--timestamp-field", "ts"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Kept 3 records, dropped 3 (1 blocks copied, 1 dropped, 1 re-encoded",
+        ));
+
+    let output = assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("cat")
+        .arg(&recent_path)
+        .args(["--field", "id"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert_eq!(String::from_utf8(output)?, "3\n5\n6\n");
+
+    // Without -o the input is replaced
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("rewrite")
+        .arg(&jac_path)
+        .args(["--drop-older-than", "2d", "--timestamp-field", "ts"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Kept 3 records, dropped 3"));
+    // An output naming the input by another path rewrites it too
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("rewrite")
+        .arg(&jac_path)
+        .arg("-o")
+        .arg(dir.path().join(".").join("events.jac"))
+        .args(["--drop-older-than", "1d", "--timestamp-field", "ts"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Kept 2 records, dropped 1"));
+    let output = assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("cat")
+        .arg(&jac_path)
+        .args(["--field", "id"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert_eq!(String::from_utf8(output)?, "3\n6\n");
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("rewrite")
+        .arg(&jac_path)
+        .args(["--drop-older-than", "soon", "--timestamp-field", "ts"])
+        .assert()
+        .failure();
+    Ok(())
+}
@@ -126,6 +126,71 @@ fn array_headers_mixed_types() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn array_headers_type_row_and_null_markers() -> Result<(), Box<dyn Error>> {
+    let jac = || assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"));
+    let dir = tempfile::tempdir()?;
+    let input_path = temp_output_path(&dir, "dump.json");
+    let jac_path = temp_output_path(&dir, "output.jac");
+    let ndjson_path = temp_output_path(&dir, "output.ndjson");
+    fs::write(
+        &input_path,
+        r#"[["id", "score", "ok"], ["int", "decimal", "bool"], ["1", "2.5", "true"], ["2", "N/A", ""]]"#,
+    )?;
+
+    jac()
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            jac_path.to_str().unwrap(),
+            "--wrapper-array-headers",
+            "--wrapper-array-headers-type-row",
+            "--wrapper-array-headers-null-marker",
+            "N/A",
+            "--wrapper-array-headers-null-marker",
+            "",
+        ])
+        .assert()
+        .success();
+
+    jac()
+        .args([
+            "unpack",
+            jac_path.to_str().unwrap(),
+            "-o",
+            ndjson_path.to_str().unwrap(),
+            "--ndjson",
+        ])
+        .assert()
+        .success();
+
+    let records = parse_ndjson(&ndjson_path)?;
+    assert_eq!(
+        records,
+        [
+            serde_json::json!({"id": 1, "score": 2.5, "ok": true}),
+            serde_json::json!({"id": 2, "score": null, "ok": null}),
+        ]
+    );
+
+    // Without the marker, "N/A" cannot be read as a decimal
+    jac()
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            jac_path.to_str().unwrap(),
+            "--wrapper-array-headers",
+            "--wrapper-array-headers-type-row",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Type mismatch"));
+
+    Ok(())
+}
+
 #[test]
 fn array_headers_rejects_invalid_header() -> Result<(), Box<dyn Error>> {
     let dir = tempfile::tempdir()?;
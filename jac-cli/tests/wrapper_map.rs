@@ -266,6 +266,58 @@ fn map_collision_overwrite_mode() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn two_level_map_with_nested_key_paths() -> Result<(), Box<dyn Error>> {
+    let jac = || assert_cmd::Command::new(env!("CARGO_BIN_EXE_jac"));
+    let dir = tempfile::tempdir()?;
+    let input_path = temp_output_path(&dir, "state.json");
+    let jac_path = temp_output_path(&dir, "output.jac");
+    let ndjson_path = temp_output_path(&dir, "output.ndjson");
+    fs::write(
+        &input_path,
+        r#"{"prod": {"db": {"port": 5432}, "cache": {"port": 6379}}, "dev": {"db": {"port": 15432}}}"#,
+    )?;
+
+    jac()
+        .args([
+            "pack",
+            input_path.to_str().unwrap(),
+            "-o",
+            jac_path.to_str().unwrap(),
+            "--wrapper-map",
+            "--wrapper-map-key-field",
+            "meta.env",
+            "--wrapper-map-inner-key-field",
+            "meta.service",
+            "--wrapper-map-nested-keys",
+        ])
+        .assert()
+        .success();
+
+    jac()
+        .args([
+            "unpack",
+            jac_path.to_str().unwrap(),
+            "-o",
+            ndjson_path.to_str().unwrap(),
+            "--ndjson",
+        ])
+        .assert()
+        .success();
+
+    let records = parse_ndjson(&ndjson_path)?;
+    assert_eq!(
+        records,
+        [
+            serde_json::json!({"port": 5432, "meta": {"env": "prod", "service": "db"}}),
+            serde_json::json!({"port": 6379, "meta": {"env": "prod", "service": "cache"}}),
+            serde_json::json!({"port": 15432, "meta": {"env": "dev", "service": "db"}}),
+        ]
+    );
+
+    Ok(())
+}
+
 #[test]
 fn map_flag_conflicts() -> Result<(), Box<dyn Error>> {
     let dir = tempfile::tempdir()?;
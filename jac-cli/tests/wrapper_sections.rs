@@ -223,6 +223,64 @@ fn sections_custom_pointer() {
     assert!(lines[1].contains("bob"));
 }
 
+#[test]
+fn sections_field_prefix_and_limit() {
+    let temp = TempDir::new().unwrap();
+    let input_file = temp.path().join("export.json");
+    fs::write(
+        &input_file,
+        r#"{"users": [{"id": 1}, {"id": 2}], "orders": [{"id": 10}, {"id": 11}, {"id": 12}]}"#,
+    )
+    .unwrap();
+    let output = temp.path().join("output.jac");
+
+    Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("pack")
+        .arg(&input_file)
+        .arg("-o")
+        .arg(&output)
+        .arg("--wrapper-sections")
+        .arg("users")
+        .arg("orders")
+        .arg("--wrapper-section-prefix")
+        .arg("orders=order_")
+        .arg("--wrapper-section-limit")
+        .arg("orders=1")
+        .arg("--wrapper-section-no-label")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: section 'orders' has 2 more records than its limit",
+        ));
+
+    let decompressed = temp.path().join("output.ndjson");
+    Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("unpack")
+        .arg(&output)
+        .arg("-o")
+        .arg(&decompressed)
+        .arg("--ndjson")
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(&decompressed).unwrap();
+    let lines: Vec<&str> = content.trim().lines().collect();
+    assert_eq!(lines, [r#"{"id":1}"#, r#"{"id":2}"#, r#"{"order_id":10}"#]);
+
+    Command::new(env!("CARGO_BIN_EXE_jac"))
+        .arg("pack")
+        .arg(&input_file)
+        .arg("-o")
+        .arg(&output)
+        .arg("--wrapper-sections")
+        .arg("orders")
+        .arg("--wrapper-section-limit")
+        .arg("orders=many")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid record count"));
+}
+
 #[test]
 fn sections_missing_skip() {
     let temp = TempDir::new().unwrap();
@@ -1,5 +1,8 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use jac_codec::{compress_block_segments, BlockBuilder, CompressOpts, TryAddRecordOutcome};
+use jac_codec::{
+    compress_block_segments, BlockBuilder, Codec, CompressOpts, DictionaryPolicy,
+    TryAddRecordOutcome,
+};
 use serde_json::{json, Map, Value};
 
 fn create_test_records(count: usize, cardinality: usize) -> Vec<Map<String, Value>> {
@@ -146,10 +149,78 @@ fn bench_finalize_vs_prepare(c: &mut Criterion) {
     group.finish();
 }
 
+fn create_wide_records(count: usize, fields: usize) -> Vec<Map<String, Value>> {
+    (0..count)
+        .map(|i| {
+            (0..fields)
+                .map(|field| {
+                    let value = match field % 3 {
+                        0 => json!(i * field),
+                        1 => json!(format!("v{}", (i + field) % 16)),
+                        _ => json!(i % 2 == 0),
+                    };
+                    (format!("field_{}", field), value)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Build `records` into blocks of `block_records`, replacing the builder per
+/// block or resetting it.
+fn build_blocks(records: &[Map<String, Value>], opts: &CompressOpts, reuse: bool) {
+    let mut builder = BlockBuilder::new(opts.clone());
+    for record in records {
+        if let TryAddRecordOutcome::BlockFull { record } =
+            builder.try_add_record(black_box(record.clone())).unwrap()
+        {
+            let finish = if reuse {
+                builder.finalize_and_reset().unwrap()
+            } else {
+                std::mem::replace(&mut builder, BlockBuilder::new(opts.clone()))
+                    .finalize()
+                    .unwrap()
+            };
+            black_box(finish);
+            builder.try_add_record(record).unwrap();
+        }
+    }
+    black_box(builder.finalize().unwrap());
+}
+
+fn bench_builder_reuse(c: &mut Criterion) {
+    let records = create_wide_records(20_000, 200);
+    let opts = CompressOpts {
+        block_target_records: 1_000,
+        default_codec: Codec::None,
+        ..CompressOpts::default()
+    };
+    let retaining = CompressOpts {
+        dictionary_policy: DictionaryPolicy {
+            retain_across_blocks: true,
+            ..DictionaryPolicy::default()
+        },
+        ..opts.clone()
+    };
+
+    let mut group = c.benchmark_group("block_builder_reuse");
+    group.bench_function("new_builder_per_block", |b| {
+        b.iter(|| build_blocks(&records, &opts, false));
+    });
+    group.bench_function("reset_per_block", |b| {
+        b.iter(|| build_blocks(&records, &opts, true));
+    });
+    group.bench_function("reset_retaining_dictionaries", |b| {
+        b.iter(|| build_blocks(&records, &retaining, true));
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_block_building,
     bench_dictionary_effectiveness,
-    bench_finalize_vs_prepare
+    bench_finalize_vs_prepare,
+    bench_builder_reuse
 );
 criterion_main!(benches);
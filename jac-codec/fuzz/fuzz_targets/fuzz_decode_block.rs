@@ -9,6 +9,7 @@ fuzz_target!(|data: &[u8]| {
     let opts = DecompressOpts {
         limits: Limits::default(),
         verify_checksums: false,
+        ..DecompressOpts::default()
     };
 
     let _ = BlockDecoder::new(data, &opts);
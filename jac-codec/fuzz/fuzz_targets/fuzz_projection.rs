@@ -8,6 +8,7 @@ fuzz_target!(|data: &[u8]| {
     let opts = DecompressOpts {
         limits: Limits::default(),
         verify_checksums: false,
+        ..DecompressOpts::default()
     };
 
     if let Ok(decoder) = BlockDecoder::new(data, &opts) {
@@ -133,6 +133,7 @@ fn test_malformed_input_attacks(data: &[u8]) {
         let opts = DecompressOpts {
             limits: Limits::default(),
             verify_checksums: false,
+            ..DecompressOpts::default()
         };
         let _ = BlockDecoder::new(&corrupted_data, &opts);
     }
@@ -220,6 +221,7 @@ fn test_auth_bypasses(data: &[u8]) {
             let opts = DecompressOpts {
                 limits: Limits::default(),
                 verify_checksums: false,
+                ..DecompressOpts::default()
             };
             let _ = BlockDecoder::new(&bypass_data, &opts);
         }
@@ -4,20 +4,32 @@ use crate::{
     column::{ColumnContribution, FieldSegment},
     Codec, ColumnBuilder, CompressOpts,
 };
-use jac_format::{checksum::compute_crc32c, BlockHeader, FieldDirectoryEntry, JacError, Result};
+use jac_format::{
+    checksum::Crc32cHasher,
+    constants::{ENCODING_FLAG_KEY_ORDER, KEY_ORDER_FIELD_NAME},
+    BlockHeader, FieldDirectoryEntry, JacError, Result, TypeTag,
+};
 use serde_json;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Block builder for aggregating records into a block
 pub struct BlockBuilder {
     /// Compression options
     opts: CompressOpts,
-    /// Records in this block
-    records: Vec<serde_json::Map<String, serde_json::Value>>,
+    /// Records added to this block (their values live in the column builders)
+    record_count: usize,
     /// Field names discovered across all records
     field_names: Vec<String>,
+    /// Position of each field in `field_names`
+    field_positions: HashMap<String, usize>,
+    /// Per-record top-level keys as positions in `field_names`, kept only
+    /// while a key-order sidecar may be needed
+    key_orders: Vec<Vec<usize>>,
     /// Column builders for each field
     column_builders: HashMap<String, ColumnBuilder>,
+    /// Reset column builders of the previous block's fields, reused when
+    /// those fields appear again
+    idle_builders: HashMap<String, ColumnBuilder>,
     /// Current memory usage estimate
     estimated_memory: usize,
     /// Number of times segment limit forced an early flush
@@ -41,6 +53,8 @@ pub struct BlockBuilder {
 pub struct UncompressedBlockData {
     /// Field segments in deterministic (possibly canonicalized) order.
     pub field_segments: Vec<(String, FieldSegment)>,
+    /// Indices into `field_segments` in payload layout order (hot fields first).
+    pub payload_order: Vec<usize>,
     /// Record count for this block.
     pub record_count: usize,
     /// Aggregated count of early segment flushes triggered while building the block.
@@ -53,6 +67,14 @@ pub struct UncompressedBlockData {
     pub per_field_rejection_count: HashMap<String, u64>,
     /// Maximum uncompressed segment size observed per field.
     pub per_field_max_segment: HashMap<String, usize>,
+    /// Present values per type tag for each field in this block.
+    pub per_field_type_counts: HashMap<String, BTreeMap<TypeTag, u64>>,
+    /// String values that matched an earlier distinct string in their column.
+    pub dictionary_hits: u64,
+    /// String values that added a new distinct string to their column.
+    pub dictionary_misses: u64,
+    /// Fields whose strings outgrew their dictionary limit and were stored raw.
+    pub dictionary_spills: Vec<String>,
 }
 
 /// Result of attempting to add a record to the current block.
@@ -72,9 +94,12 @@ impl BlockBuilder {
     pub fn new(opts: CompressOpts) -> Self {
         Self {
             opts,
-            records: Vec::new(),
+            record_count: 0,
             field_names: Vec::new(),
+            field_positions: HashMap::new(),
+            key_orders: Vec::new(),
             column_builders: HashMap::new(),
+            idle_builders: HashMap::new(),
             estimated_memory: 0,
             segment_limit_flushes: 0,
             segment_limit_record_rejections: 0,
@@ -96,12 +121,18 @@ impl BlockBuilder {
 
         let limits = &self.opts.limits;
         let max_segment_len = limits.max_segment_uncompressed_len;
-        let current_record_count = self.records.len();
+        let current_record_count = self.record_count;
         let next_record_count = current_record_count + 1;
 
-        let record = rec;
-        let mut existing_contribs: HashMap<String, ColumnContribution> = HashMap::new();
-        let mut new_field_contribs: HashMap<String, ColumnContribution> = HashMap::new();
+        let mut record = rec;
+        if self.opts.canonicalize_keys {
+            // Nested objects are stored as opaque JSON, so sort them here as well.
+            for value in record.values_mut() {
+                value.sort_all_objects();
+            }
+        }
+        let mut existing_contribs: HashMap<&str, ColumnContribution> = HashMap::new();
+        let mut new_field_projections: HashMap<&str, usize> = HashMap::new();
 
         // Precompute contributions for fields present in this record.
         for (field_name, value) in &record {
@@ -119,9 +150,20 @@ impl BlockBuilder {
                         field_name, single_upper, max_segment_len
                     )));
                 }
-                existing_contribs.insert(field_name.clone(), contrib);
+                existing_contribs.insert(field_name, contrib);
             } else {
-                let temp_builder = ColumnBuilder::new(self.opts.block_target_records, &self.opts);
+                let fresh_builder;
+                let temp_builder = match self.idle_builders.get(field_name) {
+                    Some(builder) => builder,
+                    None => {
+                        fresh_builder = ColumnBuilder::for_field(
+                            self.opts.block_target_records,
+                            &self.opts,
+                            field_name,
+                        );
+                        &fresh_builder
+                    }
+                };
                 let contrib = temp_builder.contribution_for_value(value)?;
                 let single_upper = temp_builder.estimated_single_value_upper_bound(value)?;
                 if single_upper > max_segment_len {
@@ -135,14 +177,17 @@ impl BlockBuilder {
                         field_name, single_upper, max_segment_len
                     )));
                 }
-                new_field_contribs.insert(field_name.clone(), contrib);
+                new_field_projections.insert(
+                    field_name,
+                    temp_builder.estimated_uncompressed_size_with(&contrib, next_record_count),
+                );
             }
         }
 
         // Evaluate existing fields (including those absent in this record) for projected size.
         for (field_name, builder) in &self.column_builders {
             let contrib = existing_contribs
-                .get(field_name)
+                .get(field_name.as_str())
                 .cloned()
                 .unwrap_or_default();
             let projected = builder.estimated_uncompressed_size_with(&contrib, next_record_count);
@@ -169,24 +214,13 @@ impl BlockBuilder {
         }
 
         // Evaluate new fields similarly (no existing state).
-        for (field_name, contrib) in &new_field_contribs {
-            let presence_bytes = (next_record_count + 7) >> 3;
-            let tag_bytes = ((3 * contrib.present_delta) + 7) >> 3;
-            let bool_bytes = ((contrib.bool_delta) + 7) >> 3;
-            let projected = presence_bytes
-                + tag_bytes
-                + bool_bytes
-                + contrib.int_encoded_bytes
-                + contrib.decimal_encoded_bytes
-                + contrib.string_raw_bytes
-                + contrib.object_raw_bytes
-                + contrib.array_raw_bytes;
+        for (field_name, &projected) in &new_field_projections {
             if projected > max_segment_len {
                 if current_record_count == 0 {
                     self.segment_limit_record_rejections += 1;
                     *self
                         .per_field_rejection_count
-                        .entry(field_name.clone())
+                        .entry(field_name.to_string())
                         .or_insert(0) += 1;
                     return Err(JacError::LimitExceeded(format!(
                         "Field '{}' segment ({}) exceeds max_segment_uncompressed_len ({})",
@@ -196,31 +230,48 @@ impl BlockBuilder {
                     self.segment_limit_flushes += 1;
                     *self
                         .per_field_flush_count
-                        .entry(field_name.clone())
+                        .entry(field_name.to_string())
                         .or_insert(0) += 1;
                     return Ok(TryAddRecordOutcome::BlockFull { record });
                 }
             }
         }
 
-        // All checks passed; commit record.
+        // All checks passed; commit the record's values to their columns. The
+        // record itself is not kept.
         let record_memory = self.estimate_record_memory(&record);
-        let record_idx = self.records.len();
-        self.records.push(record.clone());
-
-        for (field_name, value) in &record {
-            if !self.field_names.contains(field_name) {
-                self.field_names.push(field_name.clone());
+        let record_idx = self.record_count;
+        self.record_count += 1;
+        let track_key_order = self.opts.preserve_key_order && !self.opts.canonicalize_keys;
+        let mut key_order = Vec::new();
+
+        for (field_name, value) in record {
+            let position = match self.field_positions.get(&field_name) {
+                Some(&position) => position,
+                None => {
+                    let position = self.field_names.len();
+                    self.field_names.push(field_name.clone());
+                    self.field_positions.insert(field_name.clone(), position);
+                    position
+                }
+            };
+            if track_key_order {
+                key_order.push(position);
             }
 
-            let block_target_records = self.opts.block_target_records;
-            let opts_clone = self.opts.clone();
-            let column_builder = self
-                .column_builders
-                .entry(field_name.clone())
-                .or_insert_with(move || ColumnBuilder::new(block_target_records, &opts_clone));
+            let opts = &self.opts;
+            let idle_builders = &mut self.idle_builders;
+            let entry = self.column_builders.entry(field_name);
+            let column_builder = entry.or_insert_with_key(|field| {
+                idle_builders.remove(field).unwrap_or_else(|| {
+                    ColumnBuilder::for_field(opts.block_target_records, opts, field)
+                })
+            });
 
-            column_builder.add_value(record_idx, value)?;
+            column_builder.add_value(record_idx, &value)?;
+        }
+        if track_key_order {
+            self.key_orders.push(key_order);
         }
 
         self.estimated_memory += record_memory;
@@ -230,18 +281,18 @@ impl BlockBuilder {
 
     /// Check if block is full
     pub fn is_full(&self) -> bool {
-        self.records.len() >= self.opts.block_target_records
+        self.record_count >= self.opts.block_target_records
             || self.estimated_memory >= self.opts.limits.max_block_uncompressed_total
     }
 
     /// Get current record count
     pub fn record_count(&self) -> usize {
-        self.records.len()
+        self.record_count
     }
 
     /// Returns true when no records have been buffered into the block.
     pub fn is_empty(&self) -> bool {
-        self.records.is_empty()
+        self.record_count == 0
     }
 
     /// Number of times segment limit triggered a flush suggestion.
@@ -261,7 +312,50 @@ impl BlockBuilder {
     /// Callers are expected to pass the returned value to
     /// [`compress_block_segments`] (potentially on a worker thread).
     pub fn prepare_segments(mut self) -> Result<UncompressedBlockData> {
-        let record_count = self.records.len();
+        self.take_segments()
+    }
+
+    /// Prepare field segments like [`prepare_segments`](Self::prepare_segments),
+    /// then [`reset`](Self::reset) the builder for the next block.
+    pub fn prepare_segments_and_reset(&mut self) -> Result<UncompressedBlockData> {
+        let uncompressed = self.take_segments()?;
+        self.reset();
+        Ok(uncompressed)
+    }
+
+    /// Finalize block like [`finalize`](Self::finalize), then
+    /// [`reset`](Self::reset) the builder for the next block.
+    pub fn finalize_and_reset(&mut self) -> Result<BlockFinish> {
+        let codec = self.opts.default_codec;
+        let uncompressed = self.prepare_segments_and_reset()?;
+        compress_block_segments(uncompressed, codec)
+    }
+
+    /// Discard the buffered records and start a new block.
+    ///
+    /// Column builders of this block's fields are reset and kept for reuse,
+    /// so wide schemas do not reallocate every column per block; builders of
+    /// fields this block lacked are dropped.
+    pub fn reset(&mut self) {
+        self.idle_builders.clear();
+        for (field_name, mut builder) in self.column_builders.drain() {
+            builder.reset();
+            self.idle_builders.insert(field_name, builder);
+        }
+        self.record_count = 0;
+        self.field_names.clear();
+        self.field_positions.clear();
+        self.key_orders.clear();
+        self.estimated_memory = 0;
+        self.segment_limit_flushes = 0;
+        self.segment_limit_record_rejections = 0;
+        self.per_field_flush_count.clear();
+        self.per_field_rejection_count.clear();
+        self.per_field_max_segment.clear();
+    }
+
+    fn take_segments(&mut self) -> Result<UncompressedBlockData> {
+        let record_count = self.record_count;
 
         let mut sorted_field_names = self.field_names.clone();
         if self.opts.canonicalize_keys {
@@ -269,10 +363,20 @@ impl BlockBuilder {
         }
 
         let mut field_segments = Vec::with_capacity(sorted_field_names.len());
+        let mut per_field_type_counts = HashMap::with_capacity(sorted_field_names.len());
+        let (mut dictionary_hits, mut dictionary_misses) = (0, 0);
+        let mut dictionary_spills = Vec::new();
 
         for field_name in &sorted_field_names {
             if let Some(column_builder) = self.column_builders.get(field_name) {
-                let field_segment = column_builder.clone().finalize(&self.opts, record_count)?;
+                per_field_type_counts.insert(field_name.clone(), column_builder.tag_counts());
+                let (hits, misses) = column_builder.dictionary_counts();
+                dictionary_hits += hits;
+                dictionary_misses += misses;
+                if column_builder.dictionary_spilled() {
+                    dictionary_spills.push(field_name.clone());
+                }
+                let field_segment = column_builder.finalize(&self.opts, record_count)?;
 
                 let segment_size = field_segment.uncompressed_payload.len();
                 let current_max = self
@@ -289,14 +393,26 @@ impl BlockBuilder {
             }
         }
 
+        if self.opts.preserve_key_order && !self.opts.canonicalize_keys {
+            if let Some(sidecar) = self.build_key_order_segment(&sorted_field_names)? {
+                field_segments.push((KEY_ORDER_FIELD_NAME.to_string(), sidecar));
+            }
+        }
+
+        let payload_order = payload_order(&field_segments, &self.opts.hot_fields);
         Ok(UncompressedBlockData {
             field_segments,
+            payload_order,
             record_count,
             segment_limit_flushes: self.segment_limit_flushes,
             segment_limit_record_rejections: self.segment_limit_record_rejections,
-            per_field_flush_count: self.per_field_flush_count,
-            per_field_rejection_count: self.per_field_rejection_count,
-            per_field_max_segment: self.per_field_max_segment,
+            per_field_flush_count: std::mem::take(&mut self.per_field_flush_count),
+            per_field_rejection_count: std::mem::take(&mut self.per_field_rejection_count),
+            per_field_max_segment: std::mem::take(&mut self.per_field_max_segment),
+            per_field_type_counts,
+            dictionary_hits,
+            dictionary_misses,
+            dictionary_spills,
         })
     }
 
@@ -311,6 +427,53 @@ impl BlockBuilder {
         compress_block_segments(uncompressed, codec)
     }
 
+    /// Build the key-order sidecar column, or `None` when every record already
+    /// lists its keys in directory order.
+    fn build_key_order_segment(&self, directory: &[String]) -> Result<Option<FieldSegment>> {
+        let directory_positions: HashMap<&str, usize> = directory
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| (name.as_str(), idx))
+            .collect();
+        let in_directory_order = |key_order: &Vec<usize>| {
+            key_order
+                .iter()
+                .map(|&position| directory_positions.get(self.field_names[position].as_str()))
+                .collect::<Option<Vec<_>>>()
+                .map_or(false, |order| {
+                    order.windows(2).all(|pair| pair[0] < pair[1])
+                })
+        };
+        if self.key_orders.iter().all(in_directory_order) {
+            return Ok(None);
+        }
+
+        let record_count = self.record_count;
+        let mut builder = ColumnBuilder::new(record_count, &self.opts);
+        for (record_idx, key_order) in self.key_orders.iter().enumerate() {
+            let keys: Vec<&String> = key_order
+                .iter()
+                .map(|&position| &self.field_names[position])
+                .collect();
+            let encoded = serde_json::to_string(&keys)?;
+            builder.add_value(record_idx, &serde_json::Value::String(encoded))?;
+        }
+
+        let mut segment = builder.finalize(&self.opts, record_count)?;
+        let max_segment_len = self.opts.limits.max_segment_uncompressed_len;
+        if segment.uncompressed_payload.len() > max_segment_len {
+            return Err(JacError::LimitExceeded(format!(
+                "Key-order sidecar segment ({}) exceeds max_segment_uncompressed_len ({})",
+                segment.uncompressed_payload.len(),
+                max_segment_len
+            )));
+        }
+        segment.encoding_flags |= ENCODING_FLAG_KEY_ORDER;
+        segment.stats = None;
+        segment.quantiles = None;
+        Ok(Some(segment))
+    }
+
     /// Estimate memory usage for a record
     fn estimate_record_memory(&self, record: &serde_json::Map<String, serde_json::Value>) -> usize {
         let mut size = 0;
@@ -352,8 +515,6 @@ pub fn compress_block_segments(
     let record_count = uncompressed.record_count;
     let mut field_entries = Vec::with_capacity(uncompressed.field_segments.len());
     let mut segments = Vec::with_capacity(uncompressed.field_segments.len());
-    let mut current_offset = 0usize;
-
     let compressor_id = codec.compressor_id();
     let compression_level = codec.level();
 
@@ -371,14 +532,22 @@ pub fn compress_block_segments(
             dict_entry_count: field_segment.dict_entry_count,
             segment_uncompressed_len: field_segment.uncompressed_payload.len(),
             segment_compressed_len: compressed.len(),
-            segment_offset: current_offset,
+            segment_offset: 0,
+            stats: field_segment.stats,
+            quantiles: field_segment.quantiles,
         };
 
-        current_offset += compressed.len();
         field_entries.push(entry);
         segments.push(compressed);
     }
 
+    // Offsets follow the payload layout; the directory keeps its own order.
+    let mut current_offset = 0usize;
+    for &idx in &uncompressed.payload_order {
+        field_entries[idx].segment_offset = current_offset;
+        current_offset += field_entries[idx].segment_compressed_len;
+    }
+
     let header = BlockHeader {
         record_count,
         fields: field_entries,
@@ -386,17 +555,17 @@ pub fn compress_block_segments(
 
     let header_bytes = header.encode()?;
 
-    let mut crc_data = header_bytes.clone();
-    for segment in &segments {
-        crc_data.extend_from_slice(segment);
-    }
-    let crc32c = compute_crc32c(&crc_data);
-
-    let data = BlockData {
+    let mut data = BlockData {
         header,
         segments,
-        crc32c,
+        crc32c: 0,
     };
+    let mut crc = Crc32cHasher::new();
+    crc.update(&header_bytes);
+    for segment in data.payload_segments() {
+        crc.update(segment);
+    }
+    data.crc32c = crc.finalize();
 
     Ok(BlockFinish {
         data,
@@ -405,6 +574,10 @@ pub fn compress_block_segments(
         per_field_flush_count: uncompressed.per_field_flush_count,
         per_field_rejection_count: uncompressed.per_field_rejection_count,
         per_field_max_segment: uncompressed.per_field_max_segment,
+        per_field_type_counts: uncompressed.per_field_type_counts,
+        dictionary_hits: uncompressed.dictionary_hits,
+        dictionary_misses: uncompressed.dictionary_misses,
+        dictionary_spills: uncompressed.dictionary_spills,
     })
 }
 
@@ -419,6 +592,42 @@ pub struct BlockData {
     pub crc32c: u32,
 }
 
+impl BlockData {
+    /// Segments in the order they appear in the encoded block (by
+    /// `segment_offset`), which differs from `segments` when hot fields were
+    /// laid out first.
+    pub fn payload_segments(&self) -> Vec<&[u8]> {
+        let mut order: Vec<usize> = (0..self.segments.len()).collect();
+        order.sort_by_key(|&idx| {
+            self.header
+                .fields
+                .get(idx)
+                .map_or(usize::MAX, |entry| entry.segment_offset)
+        });
+        order
+            .into_iter()
+            .map(|idx| self.segments[idx].as_slice())
+            .collect()
+    }
+}
+
+/// Layout order for `segments`: `hot_fields` that are present, in the order
+/// given, then the rest in directory order.
+fn payload_order(segments: &[(String, FieldSegment)], hot_fields: &[String]) -> Vec<usize> {
+    let mut placed = vec![false; segments.len()];
+    let mut order = Vec::with_capacity(segments.len());
+    for hot in hot_fields {
+        if let Some(idx) = segments.iter().position(|(name, _)| name == hot) {
+            if !placed[idx] {
+                placed[idx] = true;
+                order.push(idx);
+            }
+        }
+    }
+    order.extend((0..segments.len()).filter(|&idx| !placed[idx]));
+    order
+}
+
 /// Metrics returned after finalizing a block.
 #[derive(Debug, Clone)]
 pub struct BlockFinish {
@@ -434,12 +643,21 @@ pub struct BlockFinish {
     pub per_field_rejection_count: HashMap<String, u64>,
     /// Per-field maximum segment sizes observed (uncompressed)
     pub per_field_max_segment: HashMap<String, usize>,
+    /// Per-field present value counts by type tag
+    pub per_field_type_counts: HashMap<String, BTreeMap<TypeTag, u64>>,
+    /// String values that matched an earlier distinct string in their column
+    pub dictionary_hits: u64,
+    /// String values that added a new distinct string to their column
+    pub dictionary_misses: u64,
+    /// Fields whose strings outgrew their dictionary limit and were stored raw
+    pub dictionary_spills: Vec<String>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::Codec;
+    use jac_format::constants::ENCODING_FLAG_DICTIONARY;
     use serde_json::json;
 
     fn add_record_expect_added(
@@ -545,6 +763,169 @@ mod tests {
         assert_eq!(field_names, vec!["apple", "banana", "zebra"]);
     }
 
+    #[test]
+    fn test_block_builder_hot_fields_lead_payload() {
+        let opts = CompressOpts {
+            canonicalize_keys: true,
+            hot_fields: vec!["zebra".to_string(), "missing".to_string()],
+            ..CompressOpts::default()
+        };
+        let mut builder = BlockBuilder::new(opts);
+        for i in 0..3 {
+            let mut record = serde_json::Map::new();
+            record.insert("apple".to_string(), json!(i));
+            record.insert("zebra".to_string(), json!(format!("z{}", i)));
+            add_record_expect_added(&mut builder, record.clone());
+        }
+
+        let block_data = builder.finalize().unwrap().data;
+        let fields = &block_data.header.fields;
+        assert_eq!(fields[0].field_name, "apple");
+        assert_eq!(fields[1].field_name, "zebra");
+        assert_eq!(fields[1].segment_offset, 0);
+        assert_eq!(fields[0].segment_offset, fields[1].segment_compressed_len);
+        assert_eq!(
+            block_data.payload_segments(),
+            vec![
+                block_data.segments[1].as_slice(),
+                block_data.segments[0].as_slice()
+            ]
+        );
+
+        let mut bytes = block_data.header.encode().unwrap();
+        for segment in block_data.payload_segments() {
+            bytes.extend_from_slice(segment);
+        }
+        bytes.extend_from_slice(&block_data.crc32c.to_le_bytes());
+        let decoder = crate::BlockDecoder::new(&bytes, &crate::DecompressOpts::default()).unwrap();
+        let records = decoder.decode_records().unwrap();
+        assert_eq!(records[2]["zebra"], json!("z2"));
+        assert_eq!(records[2].keys().next().unwrap(), "apple");
+    }
+
+    #[test]
+    fn test_block_builder_spills_dictionary_to_raw_strings() {
+        let opts = CompressOpts {
+            block_target_records: 64,
+            max_dict_entries: 4,
+            ..CompressOpts::default()
+        };
+        let mut builder = BlockBuilder::new(opts);
+        let mut expected = Vec::new();
+        for i in 0..64 {
+            let mut record = serde_json::Map::new();
+            // `host` takes a fifth distinct value halfway through the block.
+            let host = if i < 32 { i % 4 } else { i % 5 };
+            record.insert("host".to_string(), json!(format!("web-{}", host)));
+            record.insert("level".to_string(), json!(["info", "warn"][i % 2]));
+            expected.push(record.clone());
+            add_record_expect_added(&mut builder, record);
+        }
+
+        let finish = builder.finalize().unwrap();
+        assert_eq!(finish.dictionary_spills, vec!["host".to_string()]);
+        let fields = &finish.data.header.fields;
+        let host = fields.iter().find(|f| f.field_name == "host").unwrap();
+        assert_eq!(host.encoding_flags & ENCODING_FLAG_DICTIONARY, 0);
+        assert_eq!(host.dict_entry_count, 0);
+        let level = fields.iter().find(|f| f.field_name == "level").unwrap();
+        assert_ne!(level.encoding_flags & ENCODING_FLAG_DICTIONARY, 0);
+
+        let mut bytes = finish.data.header.encode().unwrap();
+        for segment in finish.data.payload_segments() {
+            bytes.extend_from_slice(segment);
+        }
+        bytes.extend_from_slice(&finish.data.crc32c.to_le_bytes());
+        let decoder = crate::BlockDecoder::new(&bytes, &crate::DecompressOpts::default()).unwrap();
+        assert_eq!(decoder.decode_records().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_block_builder_reset_reuses_columns_across_blocks() {
+        let record = |i: usize| {
+            let mut record = serde_json::Map::new();
+            record.insert("level".to_string(), json!(["info", "warn"][i % 2]));
+            if i < 8 {
+                record.insert("early".to_string(), json!(i));
+            }
+            record.insert("seq".to_string(), json!(i));
+            record
+        };
+        let encode = |finish: &BlockFinish| {
+            let mut bytes = finish.data.header.encode().unwrap();
+            for segment in finish.data.payload_segments() {
+                bytes.extend_from_slice(segment);
+            }
+            bytes.extend_from_slice(&finish.data.crc32c.to_le_bytes());
+            bytes
+        };
+
+        for retain in [false, true] {
+            let mut opts = CompressOpts {
+                block_target_records: 4,
+                ..CompressOpts::default()
+            };
+            opts.dictionary_policy.retain_across_blocks = retain;
+            let mut reused = BlockBuilder::new(opts.clone());
+            for block in 0..4 {
+                let mut fresh = BlockBuilder::new(opts.clone());
+                for i in block * 4..block * 4 + 4 {
+                    add_record_expect_added(&mut fresh, record(i));
+                    add_record_expect_added(&mut reused, record(i));
+                }
+                let finish = reused.finalize_and_reset().unwrap();
+                assert!(reused.is_empty());
+                let expected = fresh.finalize().unwrap();
+                let (hits, misses) = (finish.dictionary_hits, finish.dictionary_misses);
+                if retain && block > 0 {
+                    assert_eq!((hits, misses), (4, 0));
+                } else {
+                    assert_eq!(encode(&finish), encode(&expected));
+                    assert_eq!((hits, misses), (2, 2));
+                }
+
+                let bytes = encode(&finish);
+                let decoder =
+                    crate::BlockDecoder::new(&bytes, &crate::DecompressOpts::default()).unwrap();
+                let records = decoder.decode_records().unwrap();
+                let expected: Vec<_> = (block * 4..block * 4 + 4).map(record).collect();
+                assert_eq!(records, expected);
+            }
+            // "early" left after the second block, so its builder was dropped.
+            assert!(!reused.idle_builders.contains_key("early"));
+        }
+    }
+
+    #[test]
+    fn test_block_builder_keeps_key_order_only_when_needed() {
+        let records = [json!({"b": 1, "a": 2}), json!({"c": 3, "b": 4})];
+        for preserve_key_order in [false, true] {
+            let mut builder = BlockBuilder::new(CompressOpts {
+                preserve_key_order,
+                ..CompressOpts::default()
+            });
+            for record in &records {
+                add_record_expect_added(&mut builder, record.as_object().unwrap().clone());
+            }
+            if preserve_key_order {
+                assert_eq!(builder.key_orders, vec![vec![0, 1], vec![2, 0]]);
+            } else {
+                assert!(builder.key_orders.is_empty());
+            }
+            assert_eq!(builder.record_count(), 2);
+
+            let block_data = builder.finalize().unwrap().data;
+            assert_eq!(
+                block_data
+                    .header
+                    .fields
+                    .iter()
+                    .any(|entry| entry.is_key_order_sidecar()),
+                preserve_key_order
+            );
+        }
+    }
+
     #[test]
     fn test_block_builder_unsupported_brotli_codec() {
         let mut opts = CompressOpts::default();
@@ -662,6 +1043,43 @@ mod tests {
         assert_eq!(builder.segment_limit_flushes(), 1);
     }
 
+    #[test]
+    fn try_add_record_fills_segments_up_to_the_limit() {
+        let mut opts = CompressOpts {
+            block_target_records: 1000,
+            default_codec: Codec::None,
+            ..CompressOpts::default()
+        };
+        opts.limits.max_segment_uncompressed_len = 64;
+
+        // Dictionary-encoded levels and delta-encoded ids cost far less than
+        // their raw sizes, so the block should fill until a segment is at the limit.
+        let mut builder = BlockBuilder::new(opts);
+        let levels = ["info", "warn"];
+        let mut added = 0;
+        loop {
+            let mut record = serde_json::Map::new();
+            record.insert("level".to_string(), json!(levels[added % 2]));
+            record.insert("id".to_string(), json!(added));
+            match builder.try_add_record(record).expect("try add record") {
+                TryAddRecordOutcome::Added => added += 1,
+                TryAddRecordOutcome::BlockFull { .. } => break,
+            }
+        }
+        assert_eq!(builder.segment_limit_flushes(), 1);
+
+        let block = builder.prepare_segments().unwrap();
+        assert_eq!(block.record_count, added);
+        let largest = block
+            .field_segments
+            .iter()
+            .map(|(_, segment)| segment.uncompressed_payload.len())
+            .max()
+            .unwrap();
+        assert!(largest <= 64);
+        assert!(largest >= 62, "flushed early at {} bytes", largest);
+    }
+
     #[test]
     fn try_add_record_rejects_single_record_exceeding_limit() {
         let mut opts = CompressOpts::default();
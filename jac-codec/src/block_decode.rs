@@ -2,11 +2,18 @@
 
 use std::collections::HashMap;
 use std::convert::TryInto;
-
-use jac_format::{checksum::compute_crc32c, BlockHeader, JacError, Limits, Result};
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use jac_format::{
+    checksum::compute_crc32c, BlockHeader, BlockHeaderFormat, DeclaredLimitsPolicy, JacError,
+    Limits, Result,
+};
 use serde_json::{self, Map, Value};
 
-use crate::segment_decode::FieldSegmentDecoder;
+use crate::segment_decode::{FieldSegmentDecoder, ValueRef};
 
 /// Decoder options controlling limits and validation
 #[derive(Debug, Clone)]
@@ -15,6 +22,21 @@ pub struct DecompressOpts {
     pub limits: Limits,
     /// Verify block CRC32C (recommended)
     pub verify_checksums: bool,
+    /// Reject structural anomalies (unknown encoding flags, nonzero padding
+    /// bits, bytes left over after a segment's streams). When false they are
+    /// recorded in `warnings` and decoding continues.
+    pub strict: bool,
+    /// Anomalies tolerated while decoding in permissive mode
+    pub warnings: DecodeWarnings,
+    /// How limits declared in the file header combine with `limits`
+    pub declared_limits: DeclaredLimitsPolicy,
+    /// Check that stored nested objects and arrays are well-formed JSON when
+    /// their segment is decoded. Skipping the check speeds up
+    /// [`BlockDecoder::json_records`], which copies that text to its output
+    /// as is, at the cost of passing a corrupt value through unnoticed.
+    pub validate_nested_json: bool,
+    /// Field segments decoded with these options and the time spent on them
+    pub counters: DecodeCounters,
 }
 
 impl Default for DecompressOpts {
@@ -22,7 +44,86 @@ impl Default for DecompressOpts {
         Self {
             limits: Limits::default(),
             verify_checksums: true,
+            strict: true,
+            warnings: DecodeWarnings::default(),
+            declared_limits: DeclaredLimitsPolicy::default(),
+            validate_nested_json: true,
+            counters: DecodeCounters::default(),
+        }
+    }
+}
+
+impl DecompressOpts {
+    /// Fail with `err` in strict mode; otherwise record the warning and continue.
+    pub(crate) fn anomaly(&self, err: JacError, warning: impl FnOnce() -> String) -> Result<()> {
+        if self.strict {
+            return Err(err);
         }
+        self.warnings.push(warning());
+        Ok(())
+    }
+}
+
+/// Warnings shared by every clone of a [`DecompressOpts`].
+#[derive(Debug, Clone, Default)]
+pub struct DecodeWarnings {
+    entries: Arc<Mutex<Vec<String>>>,
+}
+
+impl DecodeWarnings {
+    /// Record a warning.
+    pub fn push(&self, warning: String) {
+        self.lock().push(warning);
+    }
+
+    /// Warnings recorded so far, oldest first.
+    pub fn snapshot(&self) -> Vec<String> {
+        self.lock().clone()
+    }
+
+    /// Remove and return the recorded warnings.
+    pub fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// Whether nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<String>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Segment decoding work shared by every clone of a [`DecompressOpts`].
+///
+/// Each [`FieldSegmentDecoder`] built from the options adds one segment and
+/// the time it took to decompress the segment and split its streams.
+#[derive(Debug, Clone, Default)]
+pub struct DecodeCounters {
+    segments: Arc<AtomicU64>,
+    nanos: Arc<AtomicU64>,
+}
+
+impl DecodeCounters {
+    /// Count one segment decoded in `elapsed`.
+    pub fn record(&self, elapsed: Duration) {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.segments.fetch_add(1, Ordering::Relaxed);
+        self.nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// Segments decoded so far.
+    pub fn segments(&self) -> u64 {
+        self.segments.load(Ordering::Relaxed)
+    }
+
+    /// Time spent decoding them.
+    pub fn time(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
     }
 }
 
@@ -32,12 +133,76 @@ pub struct BlockDecoder {
     header: BlockHeader,
     segments: Vec<Vec<u8>>,
     field_index: HashMap<String, usize>,
+    key_order_index: Option<usize>,
     opts: DecompressOpts,
 }
 
 impl BlockDecoder {
     /// Create a new block decoder from raw block bytes
     pub fn new(block_bytes: &[u8], opts: &DecompressOpts) -> Result<Self> {
+        Self::new_with(block_bytes, opts, BlockHeader::decode)
+    }
+
+    /// Create a decoder for a block whose header uses `format`.
+    ///
+    /// For interned field names, `format` must hold the definitions of every
+    /// earlier block; this block's definitions are added to it.
+    pub fn new_with_format(
+        block_bytes: &[u8],
+        opts: &DecompressOpts,
+        format: &mut BlockHeaderFormat,
+    ) -> Result<Self> {
+        Self::new_with(block_bytes, opts, |bytes, limits| {
+            BlockHeader::decode_with_format(bytes, limits, format)
+        })
+    }
+
+    /// Create a decoder for a block whose header uses `format`, which already
+    /// holds every field name of the block's archive and is left unchanged,
+    /// so threads can share it.
+    pub fn new_with_complete_format(
+        block_bytes: &[u8],
+        opts: &DecompressOpts,
+        format: &BlockHeaderFormat,
+    ) -> Result<Self> {
+        Self::new_with(block_bytes, opts, |bytes, limits| {
+            BlockHeader::decode_with_complete_format(bytes, limits, format)
+        })
+    }
+
+    /// Create a decoder from a block header and its compressed field
+    /// segments, one per `header.fields` entry in the same order, for callers
+    /// that fetch and frame blocks themselves.
+    ///
+    /// The header is checked against `opts.limits` as if it had been read
+    /// from a file, and each segment must be `segment_compressed_len` bytes;
+    /// `segment_offset` is not used. There is no block CRC to verify here.
+    pub fn from_parts<I>(header: BlockHeader, segments: I, opts: &DecompressOpts) -> Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: Into<Vec<u8>>,
+    {
+        let encoded = header.encode()?;
+        let (header, _) = BlockHeader::decode(&encoded, &opts.limits)?;
+        check_uncompressed_total(&header, opts)?;
+
+        let segments: Vec<Vec<u8>> = segments.into_iter().map(Into::into).collect();
+        if segments.len() != header.fields.len() {
+            return Err(JacError::CorruptBlock);
+        }
+        for (field, segment) in header.fields.iter().zip(&segments) {
+            if segment.len() != field.segment_compressed_len {
+                return Err(JacError::CorruptBlock);
+            }
+        }
+        Ok(Self::from_validated(header, segments, opts))
+    }
+
+    fn new_with(
+        block_bytes: &[u8],
+        opts: &DecompressOpts,
+        decode_header: impl FnOnce(&[u8], &Limits) -> Result<(BlockHeader, usize)>,
+    ) -> Result<Self> {
         if block_bytes.len() < 4 {
             return Err(JacError::UnexpectedEof);
         }
@@ -53,27 +218,13 @@ impl BlockDecoder {
         }
 
         // Decode block header (enforces per-field limits)
-        let (header, header_len) = BlockHeader::decode(block_bytes, &opts.limits)?;
+        let (header, header_len) = decode_header(block_bytes, &opts.limits)?;
 
         if header_len > crc_offset {
             return Err(JacError::CorruptBlock);
         }
 
-        // Enforce total uncompressed limit
-        let mut total_uncompressed = 0usize;
-        for field in &header.fields {
-            total_uncompressed = total_uncompressed
-                .checked_add(field.segment_uncompressed_len)
-                .ok_or_else(|| {
-                    JacError::LimitExceeded("Total uncompressed size overflow".to_string())
-                })?;
-        }
-        if total_uncompressed > opts.limits.max_block_uncompressed_total {
-            return Err(JacError::LimitExceeded(format!(
-                "Block uncompressed total {} exceeds limit {}",
-                total_uncompressed, opts.limits.max_block_uncompressed_total
-            )));
-        }
+        check_uncompressed_total(&header, opts)?;
 
         let segments_region_start = header_len;
         let segments_region_end = crc_offset;
@@ -122,17 +273,27 @@ impl BlockDecoder {
             segments.push(block_bytes[start..end].to_vec());
         }
 
+        Ok(Self::from_validated(header, segments, opts))
+    }
+
+    fn from_validated(header: BlockHeader, segments: Vec<Vec<u8>>, opts: &DecompressOpts) -> Self {
         let mut field_index = HashMap::new();
+        let mut key_order_index = None;
         for (idx, field) in header.fields.iter().enumerate() {
-            field_index.insert(field.field_name.clone(), idx);
+            if field.is_key_order_sidecar() {
+                key_order_index = Some(idx);
+            } else {
+                field_index.insert(field.field_name.clone(), idx);
+            }
         }
 
-        Ok(Self {
+        Self {
             header,
             segments,
             field_index,
+            key_order_index,
             opts: opts.clone(),
-        })
+        }
     }
 
     /// Decode all records in the block into JSON maps
@@ -141,11 +302,14 @@ impl BlockDecoder {
         let mut records = vec![Map::new(); record_count];
 
         for (idx, entry) in self.header.fields.iter().enumerate() {
-            let decoder = FieldSegmentDecoder::new(
+            if entry.is_key_order_sidecar() {
+                continue;
+            }
+            let decoder = FieldSegmentDecoder::with_opts(
                 &self.segments[idx],
                 entry,
                 record_count,
-                &self.opts.limits,
+                &self.opts,
             )?;
 
             for (record_idx, record) in records.iter_mut().enumerate() {
@@ -155,22 +319,137 @@ impl BlockDecoder {
             }
         }
 
+        if let Some(idx) = self.key_order_index {
+            self.apply_key_order(idx, &mut records)?;
+        }
+
         Ok(records)
     }
 
-    /// Project a single field across all records
-    pub fn project_field(&self, field_name: &str) -> Result<Vec<Option<Value>>> {
+    /// Reorder record keys according to the key-order sidecar segment.
+    fn apply_key_order(&self, idx: usize, records: &mut [Map<String, Value>]) -> Result<()> {
+        let decoder = FieldSegmentDecoder::with_opts(
+            &self.segments[idx],
+            &self.header.fields[idx],
+            records.len(),
+            &self.opts,
+        )?;
+
+        for (record_idx, record) in records.iter_mut().enumerate() {
+            let Some(Value::String(encoded)) = decoder.get_value(record_idx)? else {
+                continue;
+            };
+            let keys: Vec<String> =
+                serde_json::from_str(&encoded).map_err(|_| JacError::CorruptBlock)?;
+            let mut ordered = Map::with_capacity(record.len());
+            for key in keys {
+                let value = record.shift_remove(&key).ok_or(JacError::CorruptBlock)?;
+                ordered.insert(key, value);
+            }
+            // Keys missing from the sidecar keep their directory position at the end.
+            ordered.append(record);
+            *record = ordered;
+        }
+        Ok(())
+    }
+
+    /// Prepare the block's records for writing as JSON text straight from the
+    /// decoded columns, without building a [`Map`] per record.
+    ///
+    /// The text written for a record is what serializing the matching
+    /// [`Self::decode_records`] entry gives; nested objects and arrays are
+    /// copied from their stored JSON text instead of being parsed.
+    pub fn json_records(&self) -> Result<JsonRecords<'_>> {
         let record_count = self.header.record_count;
-        let Some(&idx) = self.field_index.get(field_name) else {
-            return Ok(vec![None; record_count]);
+        let mut fields = Vec::with_capacity(self.header.fields.len());
+        let mut positions = HashMap::with_capacity(self.header.fields.len());
+        let mut key_order = None;
+        for (idx, entry) in self.header.fields.iter().enumerate() {
+            let decoder = FieldSegmentDecoder::with_opts(
+                &self.segments[idx],
+                entry,
+                record_count,
+                &self.opts,
+            )?;
+            if entry.is_key_order_sidecar() {
+                key_order = Some(decoder);
+            } else {
+                positions.insert(entry.field_name.as_str(), fields.len());
+                fields.push((entry.field_name.as_str(), decoder));
+            }
+        }
+        Ok(JsonRecords {
+            record_count,
+            fields,
+            positions,
+            key_order,
+        })
+    }
+
+    /// Which records hold a value at the RFC 6901 JSON `pointer` (such as
+    /// `/error/code`).
+    ///
+    /// The first token names a top-level field, answered from that field's
+    /// column alone; deeper tokens are resolved in the stored JSON text of
+    /// nested values. The empty pointer matches every record.
+    pub fn pointer_matches(&self, pointer: &str) -> Result<Vec<bool>> {
+        let record_count = self.header.record_count;
+        if pointer.is_empty() {
+            return Ok(vec![true; record_count]);
+        }
+        let Some(path) = pointer.strip_prefix('/') else {
+            return Err(JacError::InvalidArgument(format!(
+                "JSON pointer '{}' must start with '/'",
+                pointer
+            )));
+        };
+        let (token, rest) = path.split_at(path.find('/').unwrap_or(path.len()));
+        let field = token.replace("~1", "/").replace("~0", "~");
+        let Some(&idx) = self.field_index.get(&field) else {
+            return Ok(vec![false; record_count]);
         };
 
-        let decoder = FieldSegmentDecoder::new(
+        let decoder = FieldSegmentDecoder::with_opts(
             &self.segments[idx],
             &self.header.fields[idx],
             record_count,
-            &self.opts.limits,
+            &self.opts,
         )?;
+        let mut matches = vec![false; record_count];
+        decoder.visit(|record_idx, value| {
+            matches[record_idx] = match value {
+                _ if rest.is_empty() => true,
+                ValueRef::RawJson(_) => decoder
+                    .get_value(record_idx)?
+                    .is_some_and(|value| value.pointer(rest).is_some()),
+                _ => false,
+            };
+            Ok(())
+        })?;
+        Ok(matches)
+    }
+
+    /// Decoder for one field's segment, or `None` when the block lacks the
+    /// field.
+    pub fn field_segment(&self, field_name: &str) -> Result<Option<FieldSegmentDecoder>> {
+        let Some(&idx) = self.field_index.get(field_name) else {
+            return Ok(None);
+        };
+        FieldSegmentDecoder::with_opts(
+            &self.segments[idx],
+            &self.header.fields[idx],
+            self.header.record_count,
+            &self.opts,
+        )
+        .map(Some)
+    }
+
+    /// Project a single field across all records
+    pub fn project_field(&self, field_name: &str) -> Result<Vec<Option<Value>>> {
+        let record_count = self.header.record_count;
+        let Some(decoder) = self.field_segment(field_name)? else {
+            return Ok(vec![None; record_count]);
+        };
 
         (0..record_count)
             .map(|idx| decoder.get_value(idx))
@@ -183,6 +462,110 @@ impl BlockDecoder {
     }
 }
 
+/// Records of one block written as JSON text, from
+/// [`BlockDecoder::json_records`].
+pub struct JsonRecords<'a> {
+    record_count: usize,
+    /// Decoded fields in directory order
+    fields: Vec<(&'a str, FieldSegmentDecoder)>,
+    positions: HashMap<&'a str, usize>,
+    key_order: Option<FieldSegmentDecoder>,
+}
+
+impl JsonRecords<'_> {
+    /// Number of records in the block.
+    pub fn len(&self) -> usize {
+        self.record_count
+    }
+
+    /// Whether the block holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.record_count == 0
+    }
+
+    /// Write record `record_idx` to `out` as one minified JSON object.
+    pub fn write_record<W: Write>(&self, record_idx: usize, out: &mut W) -> Result<()> {
+        if record_idx >= self.record_count {
+            return Err(JacError::Internal("Record index out of bounds".to_string()));
+        }
+        let mut first = true;
+        let mut write_field = |name: &str, value: ValueRef<'_>| -> Result<()> {
+            out.write_all(if first { b"{" } else { b"," })?;
+            first = false;
+            serde_json::to_writer(&mut *out, name)?;
+            out.write_all(b":")?;
+            value.write_json(&mut *out)
+        };
+
+        let keys = match &self.key_order {
+            Some(decoder) => match decoder.get_ref(record_idx)? {
+                Some(ValueRef::Str(encoded)) => Some(
+                    serde_json::from_str::<Vec<String>>(encoded)
+                        .map_err(|_| JacError::CorruptBlock)?,
+                ),
+                _ => None,
+            },
+            None => None,
+        };
+        match keys {
+            Some(keys) => {
+                let mut written = vec![false; self.fields.len()];
+                for key in &keys {
+                    let position = *self
+                        .positions
+                        .get(key.as_str())
+                        .ok_or(JacError::CorruptBlock)?;
+                    if written[position] {
+                        return Err(JacError::CorruptBlock);
+                    }
+                    let (name, decoder) = &self.fields[position];
+                    let value = decoder.get_ref(record_idx)?.ok_or(JacError::CorruptBlock)?;
+                    write_field(name, value)?;
+                    written[position] = true;
+                }
+                // Keys missing from the sidecar keep their directory position at the end.
+                for ((name, decoder), written) in self.fields.iter().zip(written) {
+                    if let (false, Some(value)) = (written, decoder.get_ref(record_idx)?) {
+                        write_field(name, value)?;
+                    }
+                }
+            }
+            None => {
+                for (name, decoder) in &self.fields {
+                    if let Some(value) = decoder.get_ref(record_idx)? {
+                        write_field(name, value)?;
+                    }
+                }
+            }
+        }
+
+        if first {
+            out.write_all(b"{")?;
+        }
+        out.write_all(b"}")?;
+        Ok(())
+    }
+}
+
+/// Enforce the limit on a block's total uncompressed segment size.
+fn check_uncompressed_total(header: &BlockHeader, opts: &DecompressOpts) -> Result<()> {
+    let mut total_uncompressed = 0usize;
+    for field in &header.fields {
+        total_uncompressed = total_uncompressed
+            .checked_add(field.segment_uncompressed_len)
+            .ok_or_else(|| {
+                JacError::LimitExceeded("Total uncompressed size overflow".to_string())
+            })?;
+    }
+    if total_uncompressed > opts.limits.max_block_uncompressed_total {
+        return Err(JacError::LimitExceeded(format!(
+            "Block uncompressed total {} exceeds limit {}",
+            total_uncompressed, opts.limits.max_block_uncompressed_total
+        )));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -216,7 +599,7 @@ mod tests {
     fn assemble_bytes(data: &crate::block_builder::BlockData) -> Vec<u8> {
         let header_bytes = data.header.encode().unwrap();
         let mut bytes = header_bytes;
-        for segment in &data.segments {
+        for segment in data.payload_segments() {
             bytes.extend_from_slice(segment);
         }
         bytes.extend_from_slice(&data.crc32c.to_le_bytes());
@@ -264,6 +647,149 @@ mod tests {
         assert_eq!(missing, vec![None, None, None]);
     }
 
+    fn record_keys(record: &Map<String, Value>) -> Vec<&str> {
+        record.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn test_block_decoder_restores_key_order() {
+        let records: Vec<Map<String, Value>> = [
+            r#"{"b":1,"a":2,"c":3}"#,
+            r#"{"c":4,"b":5}"#,
+            r#"{"a":6,"nested":{"z":1,"y":2}}"#,
+        ]
+        .iter()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+        let opts = CompressOpts {
+            preserve_key_order: true,
+            ..CompressOpts::default()
+        };
+        let data = block_data_from_records(opts, &records);
+        let sidecars: Vec<_> = data
+            .header
+            .fields
+            .iter()
+            .filter(|entry| entry.is_key_order_sidecar())
+            .collect();
+        assert_eq!(sidecars.len(), 1);
+
+        let bytes = assemble_bytes(&data);
+        let decoder = BlockDecoder::new(&bytes, &DecompressOpts::default()).unwrap();
+        let decoded = decoder.decode_records().unwrap();
+        assert_eq!(record_keys(&decoded[0]), vec!["b", "a", "c"]);
+        assert_eq!(record_keys(&decoded[1]), vec!["c", "b"]);
+        assert_eq!(record_keys(&decoded[2]), vec!["a", "nested"]);
+        assert_eq!(
+            serde_json::to_string(&decoded[2]).unwrap(),
+            r#"{"a":6,"nested":{"z":1,"y":2}}"#
+        );
+
+        let sidecar_name = &sidecars[0].field_name;
+        assert_eq!(
+            decoder.project_field(sidecar_name).unwrap(),
+            vec![None, None, None]
+        );
+    }
+
+    #[test]
+    fn test_block_decoder_writes_json_records() {
+        let records: Vec<Map<String, Value>> = [
+            r#"{"b":1,"a":"say \"hi\"\n","c":3.25}"#,
+            r#"{"nested":{"z":[1,{"y":null}],"x":"é"},"b":-7}"#,
+            r#"{}"#,
+            r#"{"a":"bob","list":[true,1e300,"x"],"c":-0.5}"#,
+        ]
+        .iter()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+        for preserve_key_order in [false, true] {
+            let opts = CompressOpts {
+                preserve_key_order,
+                ..CompressOpts::default()
+            };
+            let bytes = assemble_bytes(&block_data_from_records(opts, &records));
+            for validate_nested_json in [true, false] {
+                let opts = DecompressOpts {
+                    validate_nested_json,
+                    ..DecompressOpts::default()
+                };
+                let decoder = BlockDecoder::new(&bytes, &opts).unwrap();
+                let json = decoder.json_records().unwrap();
+                assert_eq!(json.len(), records.len());
+                for (idx, record) in decoder.decode_records().unwrap().iter().enumerate() {
+                    let mut out = Vec::new();
+                    json.write_record(idx, &mut out).unwrap();
+                    assert_eq!(
+                        String::from_utf8(out).unwrap(),
+                        serde_json::to_string(record).unwrap()
+                    );
+                }
+                assert!(json.write_record(records.len(), &mut Vec::new()).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_decoder_pointer_matches() {
+        let records: Vec<Map<String, Value>> = [
+            r#"{"error":{"code":500,"detail":{"a/b":[1]}}}"#,
+            r#"{"error":"timeout"}"#,
+            r#"{"ok":true}"#,
+            r#"{"error":{"list":[{"code":1}]}}"#,
+        ]
+        .iter()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+        let bytes = assemble_bytes(&block_data_from_records(CompressOpts::default(), &records));
+        let decoder = BlockDecoder::new(&bytes, &DecompressOpts::default()).unwrap();
+
+        let matches = |pointer: &str| decoder.pointer_matches(pointer).unwrap();
+        assert_eq!(matches(""), [true; 4]);
+        assert_eq!(matches("/error"), [true, true, false, true]);
+        assert_eq!(matches("/error/code"), [true, false, false, false]);
+        assert_eq!(matches("/error/detail/a~1b/0"), [true, false, false, false]);
+        assert_eq!(matches("/error/list/0/code"), [false, false, false, true]);
+        assert_eq!(matches("/missing/code"), [false; 4]);
+        assert!(matches!(
+            decoder.pointer_matches("error"),
+            Err(JacError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_block_builder_skips_key_order_when_directory_matches() {
+        let records = default_records();
+        let opts = CompressOpts {
+            preserve_key_order: true,
+            ..CompressOpts::default()
+        };
+        let data = block_data_from_records(opts, &records);
+        assert!(data
+            .header
+            .fields
+            .iter()
+            .all(|entry| !entry.is_key_order_sidecar()));
+
+        let canonical: Vec<Map<String, Value>> =
+            vec![serde_json::from_str(r#"{"b":1,"a":{"d":1,"c":2}}"#).unwrap()];
+        let opts = CompressOpts {
+            preserve_key_order: true,
+            canonicalize_keys: true,
+            ..CompressOpts::default()
+        };
+        let data = block_data_from_records(opts, &canonical);
+        assert_eq!(data.header.fields.len(), 2);
+        let bytes = assemble_bytes(&data);
+        let decoder = BlockDecoder::new(&bytes, &DecompressOpts::default()).unwrap();
+        let decoded = decoder.decode_records().unwrap();
+        assert_eq!(
+            serde_json::to_string(&decoded[0]).unwrap(),
+            r#"{"a":{"c":2,"d":1},"b":1}"#
+        );
+    }
+
     #[test]
     fn test_block_decoder_crc_verification() {
         let records = default_records();
@@ -322,6 +848,36 @@ mod tests {
         assert!(matches!(err, JacError::LimitExceeded(_)));
     }
 
+    #[test]
+    fn test_block_decoder_from_parts() {
+        let records = default_records();
+        let data = block_data_from_records(CompressOpts::default(), &records);
+        let opts = DecompressOpts::default();
+
+        let decoder =
+            BlockDecoder::from_parts(data.header.clone(), data.segments.clone(), &opts).unwrap();
+        assert_eq!(decoder.decode_records().unwrap(), records);
+        let slices: Vec<&[u8]> = data.segments.iter().map(Vec::as_slice).collect();
+        let decoder = BlockDecoder::from_parts(data.header.clone(), slices, &opts).unwrap();
+        assert_eq!(
+            decoder.project_field("name").unwrap(),
+            [Some(json!("alice")), Some(json!("bob")), None]
+        );
+
+        let missing = data.segments[..1].to_vec();
+        let err = BlockDecoder::from_parts(data.header.clone(), missing, &opts).unwrap_err();
+        assert!(matches!(err, JacError::CorruptBlock));
+        let mut truncated = data.segments.clone();
+        truncated[0].pop();
+        let err = BlockDecoder::from_parts(data.header.clone(), truncated, &opts).unwrap_err();
+        assert!(matches!(err, JacError::CorruptBlock));
+
+        let mut strict = DecompressOpts::default();
+        strict.limits.max_records_per_block = 2;
+        let err = BlockDecoder::from_parts(data.header, data.segments, &strict).unwrap_err();
+        assert!(matches!(err, JacError::LimitExceeded(_)));
+    }
+
     #[test]
     fn test_block_decoder_conformance_vector() {
         let conformance_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
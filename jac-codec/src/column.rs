@@ -1,19 +1,17 @@
 //! Column builder for converting records to columnar format
 
-use crate::{Codec, CompressOpts};
+use crate::{Codec, CompressOpts, DictionaryMode, FieldEncodingHint, NumberCanonicalization};
 use jac_format::{
     bitpack::{PresenceBitmap, TagPacker},
     varint::{encode_uleb128, zigzag_encode},
-    Decimal, JacError, Limits, Result, TypeTag,
+    Decimal, DistinctSketch, FieldStats, JacError, Limits, QuantileSketch, Result, TypeTag,
 };
 use serde_json;
 use std::cmp::{max, min};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::io::Write;
 
-type DictionaryBuild = (Option<(Vec<String>, HashMap<String, usize>)>, bool);
-
 /// Column builder for a single field across records
 #[derive(Clone)]
 pub struct ColumnBuilder {
@@ -29,8 +27,16 @@ pub struct ColumnBuilder {
     ints: Vec<i64>,
     /// Decimal values (exact representation)
     decimals: Vec<Decimal>,
-    /// String values (for dictionary or raw)
-    strings: Vec<String>,
+    /// Interned id of each string value (its dictionary index)
+    string_ids: Vec<usize>,
+    /// Distinct string values in first-occurrence order
+    interned: Vec<String>,
+    /// Hasher for interned string lookups
+    string_hasher: ahash::RandomState,
+    /// First interned id for each string hash
+    intern_table: ahash::AHashMap<u64, usize>,
+    /// Interned strings whose hash collided with an earlier, different string
+    intern_collisions: HashMap<String, usize>,
     /// Object values (minified JSON)
     objects: Vec<Vec<u8>>,
     /// Array values (minified JSON)
@@ -41,8 +47,28 @@ pub struct ColumnBuilder {
     limits: Limits,
     /// Maximum dictionary entries permitted for this field
     max_dict_entries: usize,
+    /// Whether strings are interned for a dictionary; false when the field
+    /// opts out, or once it has more distinct strings than a dictionary
+    /// could accept in this block
+    interning: bool,
+    /// Whether a block starts out interning strings (`interning` at creation)
+    intern_by_default: bool,
+    /// Keep the dictionary a block used as the start of the next block's
+    /// (`DictionaryPolicy::retain_across_blocks`)
+    retain_dictionary: bool,
+    /// Largest ratio of distinct strings to string values for a dictionary
+    /// (`None` under `DictionaryMode::Always`)
+    dict_ratio: Option<f64>,
+    /// Whether interning stopped because the field outgrew its dictionary
+    /// limit mid-block; the strings already seen are then written raw
+    dictionary_spilled: bool,
+    /// Delta encode increasing integers without the uniformity check
+    /// (`FieldEncodingHint::prefer_delta`)
+    prefer_delta: bool,
     /// Canonicalize numbers flag
     canonicalize_numbers: bool,
+    /// Decimal rewrite rules used when canonicalizing numbers
+    number_policy: NumberCanonicalization,
     /// Count of present (non-absent) values captured so far
     present_count: usize,
     /// Count of boolean values (for estimation)
@@ -57,11 +83,38 @@ pub struct ColumnBuilder {
     object_raw_bytes: usize,
     /// Estimated encoded bytes for array payloads
     array_raw_bytes: usize,
+    /// Encoded bytes of the dictionary entries in `interned`
+    dict_entry_bytes: usize,
+    /// Encoded bytes of the dictionary indices in `string_ids`
+    dict_index_bytes: usize,
+    /// String values that matched an already interned string
+    dictionary_hits: u64,
+    /// String values that were interned as a new distinct string
+    dictionary_misses: u64,
+    /// Running delta-encoding state for `ints`
+    delta: Option<DeltaState>,
 }
 
 impl ColumnBuilder {
     /// Create new column builder
     pub fn new(record_count: usize, opts: &CompressOpts) -> Self {
+        Self::with_settings(record_count, opts, &FieldEncodingHint::default())
+    }
+
+    /// Create a column builder for `field`, honoring its entry in
+    /// `opts.field_hints`
+    pub fn for_field(record_count: usize, opts: &CompressOpts, field: &str) -> Self {
+        let default_hint = FieldEncodingHint::default();
+        let hint = opts.field_hints.get(field).unwrap_or(&default_hint);
+        Self::with_settings(record_count, opts, hint)
+    }
+
+    fn with_settings(record_count: usize, opts: &CompressOpts, hint: &FieldEncodingHint) -> Self {
+        let interning = hint.dictionary != Some(DictionaryMode::Never);
+        let dict_ratio = match hint.dictionary {
+            Some(DictionaryMode::Always) => None,
+            _ => Some(opts.dictionary_policy.max_cardinality_ratio),
+        };
         Self {
             record_count,
             presence: PresenceBitmap::new(record_count),
@@ -69,13 +122,24 @@ impl ColumnBuilder {
             bools: Vec::new(),
             ints: Vec::new(),
             decimals: Vec::new(),
-            strings: Vec::new(),
+            string_ids: Vec::new(),
+            interned: Vec::new(),
+            string_hasher: ahash::RandomState::new(),
+            intern_table: ahash::AHashMap::new(),
+            intern_collisions: HashMap::new(),
             objects: Vec::new(),
             arrays: Vec::new(),
             present_idx: 0,
             limits: opts.limits.clone(),
             max_dict_entries: opts.max_dict_entries,
+            interning,
+            intern_by_default: interning,
+            retain_dictionary: opts.dictionary_policy.retain_across_blocks,
+            dict_ratio,
+            dictionary_spilled: false,
+            prefer_delta: hint.prefer_delta,
             canonicalize_numbers: opts.canonicalize_numbers,
+            number_policy: opts.number_canonicalization.clone(),
             present_count: 0,
             bool_count: 0,
             int_encoded_bytes: 0,
@@ -83,6 +147,11 @@ impl ColumnBuilder {
             string_raw_bytes: 0,
             object_raw_bytes: 0,
             array_raw_bytes: 0,
+            dict_entry_bytes: 0,
+            dict_index_bytes: 0,
+            dictionary_hits: 0,
+            dictionary_misses: 0,
+            delta: None,
         }
     }
 
@@ -115,11 +184,13 @@ impl ColumnBuilder {
                     self.tags.push(TypeTag::Int);
                     self.ints.push(i);
                     self.int_encoded_bytes += uleb128_len(zigzag_encode(i));
+                    self.delta = Some(self.next_delta(i));
                 } else if let Some(u) = n.as_u64() {
                     if u <= i64::MAX as u64 {
                         self.tags.push(TypeTag::Int);
                         self.ints.push(u as i64);
                         self.int_encoded_bytes += uleb128_len(zigzag_encode(u as i64));
+                        self.delta = Some(self.next_delta(u as i64));
                     } else {
                         // u64 > i64::MAX, must use decimal
                         let decimal = self.build_decimal(&n.to_string())?;
@@ -176,23 +247,106 @@ impl ColumnBuilder {
         Ok(())
     }
 
-    /// Estimate current uncompressed payload size using conservative assumptions.
+    /// Uncompressed payload size `finalize` would produce for `record_count` records.
     pub fn estimated_uncompressed_size(&self, record_count: usize) -> usize {
-        let presence_bytes = (record_count + 7) >> 3;
-        let tag_bytes = ((3 * self.present_count) + 7) >> 3;
-        let bool_bytes = ((self.bool_count) + 7) >> 3;
-        presence_bytes
-            + tag_bytes
-            + bool_bytes
-            + self.int_encoded_bytes
-            + self.decimal_encoded_bytes
-            + self.string_raw_bytes
-            + self.object_raw_bytes
-            + self.array_raw_bytes
+        self.estimated_uncompressed_size_with(&ColumnContribution::default(), record_count)
+    }
+
+    /// Count present values by type tag.
+    pub fn tag_counts(&self) -> BTreeMap<TypeTag, u64> {
+        let mut counts = BTreeMap::new();
+        for tag in &self.tags {
+            *counts.entry(*tag).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// String values that matched an earlier distinct string (`.0`) and those
+    /// that added a new one (`.1`).
+    pub fn dictionary_counts(&self) -> (u64, u64) {
+        (self.dictionary_hits, self.dictionary_misses)
+    }
+
+    /// Whether this column exceeded its dictionary limit (the smaller of
+    /// `max_dict_entries` and `max_dict_entries_per_field`) and fell back to
+    /// raw strings for the block.
+    pub fn dictionary_spilled(&self) -> bool {
+        self.dictionary_spilled
+    }
+
+    /// Clear the column for the next block, keeping its buffers' capacity.
+    ///
+    /// The interned strings are dropped unless the policy retains
+    /// dictionaries and this block was dictionary encoded; the entries the
+    /// block referenced then seed the next block's dictionary, so values that
+    /// repeat across blocks are not interned again.
+    pub fn reset(&mut self) {
+        let retain = self.retain_dictionary
+            && self.uses_dictionary(self.interned.len(), self.string_ids.len());
+        if retain {
+            self.retain_referenced_strings();
+        } else {
+            self.interned.clear();
+            self.intern_table.clear();
+            self.intern_collisions.clear();
+        }
+        self.dict_entry_bytes = self
+            .interned
+            .iter()
+            .map(|string| string_payload_len(string.len()))
+            .sum();
+
+        self.presence.clear();
+        self.tags.clear();
+        self.bools.clear();
+        self.ints.clear();
+        self.decimals.clear();
+        self.string_ids.clear();
+        self.objects.clear();
+        self.arrays.clear();
+        self.present_idx = 0;
+        self.interning = self.intern_by_default;
+        self.dictionary_spilled = false;
+        self.present_count = 0;
+        self.bool_count = 0;
+        self.int_encoded_bytes = 0;
+        self.decimal_encoded_bytes = 0;
+        self.string_raw_bytes = 0;
+        self.object_raw_bytes = 0;
+        self.array_raw_bytes = 0;
+        self.dict_index_bytes = 0;
+        self.dictionary_hits = 0;
+        self.dictionary_misses = 0;
+        self.delta = None;
+    }
+
+    /// Drop interned strings that no value of this block refers to, keeping
+    /// the rest in order.
+    fn retain_referenced_strings(&mut self) {
+        let mut referenced = vec![false; self.interned.len()];
+        for &id in &self.string_ids {
+            referenced[id] = true;
+        }
+        if referenced.iter().all(|&used| used) {
+            return;
+        }
+        let mut idx = 0;
+        self.interned.retain(|_| {
+            idx += 1;
+            referenced[idx - 1]
+        });
+        self.intern_table.clear();
+        self.intern_collisions.clear();
+        for (id, string) in self.interned.iter().enumerate() {
+            let hash = self.string_hasher.hash_one(string.as_str());
+            if *self.intern_table.entry(hash).or_insert(id) != id {
+                self.intern_collisions.insert(string.clone(), id);
+            }
+        }
     }
 
     /// Finalize column and create field segment
-    pub fn finalize(self, _opts: &CompressOpts, record_count: usize) -> Result<FieldSegment> {
+    pub fn finalize(&self, opts: &CompressOpts, record_count: usize) -> Result<FieldSegment> {
         let mut trimmed_presence = PresenceBitmap::new(record_count);
         for idx in 0..record_count {
             if self.presence.is_present(idx) {
@@ -209,12 +363,9 @@ impl ColumnBuilder {
             ));
         }
 
-        // Build string dictionary if beneficial
-        let (dictionary_info, use_dict) = self.build_string_dictionary()?;
-        let dict_entry_count = dictionary_info
-            .as_ref()
-            .map(|(entries, _)| entries.len())
-            .unwrap_or(0);
+        // Use the interned strings as a dictionary if beneficial
+        let use_dict = self.uses_dictionary(self.interned.len(), self.string_ids.len());
+        let dict_entry_count = if use_dict { self.interned.len() } else { 0 };
 
         if dict_entry_count > self.limits.max_dict_entries_per_field {
             return Err(JacError::LimitExceeded(
@@ -249,12 +400,10 @@ impl ColumnBuilder {
 
         // 3. String dictionary (if using dictionary encoding)
         if use_dict {
-            if let Some((entries, _)) = &dictionary_info {
-                for string in entries {
-                    let string_bytes = string.as_bytes();
-                    payload.extend_from_slice(&encode_uleb128(string_bytes.len() as u64));
-                    payload.extend_from_slice(string_bytes);
-                }
+            for string in &self.interned {
+                let string_bytes = string.as_bytes();
+                payload.extend_from_slice(&encode_uleb128(string_bytes.len() as u64));
+                payload.extend_from_slice(string_bytes);
             }
         }
 
@@ -265,9 +414,8 @@ impl ColumnBuilder {
         }
 
         // 5. Integer substream (varint/delta)
+        let use_delta = self.uses_delta(self.delta);
         if !self.ints.is_empty() {
-            // Check if delta encoding is beneficial
-            let use_delta = self.should_use_delta_encoding(&self.ints);
             if use_delta {
                 // Write base value first, then deltas
                 if let Some(&base) = self.ints.first() {
@@ -291,26 +439,13 @@ impl ColumnBuilder {
         }
 
         // 7. String substream (dictionary indices or raw strings)
-        if !self.strings.is_empty() {
+        for &id in &self.string_ids {
             if use_dict {
-                if let Some((_, dict_map)) = &dictionary_info {
-                    for string in &self.strings {
-                        if let Some(&idx) = dict_map.get(string) {
-                            payload.extend_from_slice(&encode_uleb128(idx as u64));
-                        } else {
-                            return Err(JacError::Internal(
-                                "String not found in dictionary".to_string(),
-                            ));
-                        }
-                    }
-                }
+                payload.extend_from_slice(&encode_uleb128(id as u64));
             } else {
-                // Write raw strings
-                for string in &self.strings {
-                    let string_bytes = string.as_bytes();
-                    payload.extend_from_slice(&encode_uleb128(string_bytes.len() as u64));
-                    payload.extend_from_slice(string_bytes);
-                }
+                let string_bytes = self.interned[id].as_bytes();
+                payload.extend_from_slice(&encode_uleb128(string_bytes.len() as u64));
+                payload.extend_from_slice(string_bytes);
             }
         }
 
@@ -333,107 +468,130 @@ impl ColumnBuilder {
         if use_dict {
             encoding_flags |= 1 << 0; // ENCODING_FLAG_DICTIONARY
         }
-        if !self.ints.is_empty() && self.should_use_delta_encoding(&self.ints) {
+        if use_delta {
             encoding_flags |= 1 << 1; // ENCODING_FLAG_DELTA
         }
 
+        let stats = if opts.field_stats {
+            Some(self.field_stats()?)
+        } else {
+            None
+        };
+        let quantiles = if opts.quantile_sketches {
+            self.quantiles()?
+        } else {
+            None
+        };
+
         Ok(FieldSegment {
             uncompressed_payload: payload,
             encoding_flags,
             dict_entry_count,
             value_count_present: present_count,
+            stats,
+            quantiles,
         })
     }
 
-    /// Build string dictionary if beneficial
-    ///
-    /// Dictionary entry ordering: Uses first-occurrence order (insertion order) for better cache
-    /// locality. This is implementation-defined per spec and may vary across encoder versions.
-    ///
-    /// Note: This is distinct from block-level field ordering, which is controlled by the
-    /// `canonicalize_keys` flag in CompressOpts and determines lexicographic vs insertion
-    /// order for field names in the block directory.
-    fn build_string_dictionary(&self) -> Result<DictionaryBuild> {
-        if self.strings.is_empty() {
-            return Ok((None, false));
-        }
+    /// Quantile sketch of the integers and decimals added so far, as `f64`;
+    /// `None` when there are none.
+    fn quantiles(&self) -> Result<Option<QuantileSketch>> {
+        let mut values: Vec<f64> = self.ints.iter().map(|&value| value as f64).collect();
+        values.extend(self.decimals.iter().filter_map(|decimal| {
+            decimal
+                .to_f64_if_exact()
+                .or_else(|| decimal.to_json_string().parse().ok())
+        }));
+        QuantileSketch::from_values(&mut values, QuantileSketch::DEFAULT_INTERVALS)
+    }
 
-        // Determine distinct strings in first-occurrence order
-        let mut dict_entries = Vec::new();
-        let mut dict_map = HashMap::new();
-        for string in &self.strings {
-            if dict_map.contains_key(string) {
-                continue;
+    /// Null count and distinct sketch of the values added so far; each value
+    /// is hashed from the bytes `jac_format::sketch::value_hash` documents.
+    fn field_stats(&self) -> Result<FieldStats> {
+        let mut distinct = DistinctSketch::new(DistinctSketch::DEFAULT_PRECISION)?;
+        let (mut bools, mut ints, mut decimals, mut strings, mut objects, mut arrays) =
+            (0, 0, 0, 0, 0, 0);
+        let mut null_count = 0;
+        for tag in &self.tags {
+            match tag {
+                TypeTag::Null => null_count += 1,
+                TypeTag::Bool => {
+                    distinct.insert(*tag, &[u8::from(self.bools[bools])]);
+                    bools += 1;
+                }
+                TypeTag::Int => {
+                    distinct.insert(*tag, &self.ints[ints].to_le_bytes());
+                    ints += 1;
+                }
+                TypeTag::Decimal => {
+                    distinct.insert(*tag, &self.decimals[decimals].encode()?);
+                    decimals += 1;
+                }
+                TypeTag::String => {
+                    let string = &self.interned[self.string_ids[strings]];
+                    distinct.insert(*tag, string.as_bytes());
+                    strings += 1;
+                }
+                TypeTag::Object => {
+                    distinct.insert(*tag, &self.objects[objects]);
+                    objects += 1;
+                }
+                TypeTag::Array => {
+                    distinct.insert(*tag, &self.arrays[arrays]);
+                    arrays += 1;
+                }
             }
-            let index = dict_entries.len();
-            dict_entries.push(string.clone());
-            dict_map.insert(string.clone(), index);
         }
+        Ok(FieldStats {
+            null_count,
+            distinct,
+        })
+    }
 
-        let distinct_count = dict_entries.len();
-        let dict_limit = min(
-            self.max_dict_entries,
-            self.limits.max_dict_entries_per_field,
-        );
-        if dict_limit == 0 {
-            return Ok((None, false));
-        }
-
-        // Per SPEC §4.6 and Addendum §2.2: dictionary encoding when distinct <= min(max_dict_entries, present_count / 8)
-        let threshold = min(dict_limit, max(2, self.strings.len() / 8));
-
-        if distinct_count <= threshold {
-            Ok((Some((dict_entries, dict_map)), true))
-        } else {
-            Ok((None, false))
+    /// Whether `strings` values with `distinct` distinct strings are dictionary encoded.
+    fn uses_dictionary(&self, distinct: usize, strings: usize) -> bool {
+        if strings == 0 || !self.interning {
+            return false;
         }
+        distinct <= self.dict_threshold(strings)
     }
 
-    /// Check if delta encoding is beneficial for integers
-    ///
-    /// Delta encoding heuristic:
-    /// 1. Requires strictly monotonic increasing sequence (every value > previous)
-    /// 2. Uses delta_uniformity metric: (max_delta - min_delta) / total_range < 0.5
-    ///
-    /// This differs from PLAN.md's suggested "increasing_ratio >= 0.95" approach.
-    /// The delta_uniformity metric is more conservative and better suited for compression
-    /// as it checks whether deltas are relatively uniform (low variance), not just whether
-    /// the sequence is mostly increasing. Strictly monotonic check (line 399) already
-    /// ensures 100% increasing ratio, so the uniformity check provides additional value.
-    ///
-    /// Rationale: Delta encoding is most effective when deltas are small and uniform
-    /// (e.g., sequential IDs, timestamps). High delta variance reduces compression benefit.
-    fn should_use_delta_encoding(&self, ints: &[i64]) -> bool {
-        if ints.len() < 2 {
-            return false;
+    /// Most distinct strings a dictionary may hold for `strings` string values.
+    fn dict_threshold(&self, strings: usize) -> usize {
+        match self.dict_ratio {
+            // Per SPEC §4.6 and Addendum §2.2: dictionary encoding when
+            // distinct <= min(max_dict_entries, present_count / 8), where 1/8
+            // is the default `DictionaryPolicy::max_cardinality_ratio`
+            Some(ratio) => min(self.dict_limit(), max(2, (strings as f64 * ratio) as usize)),
+            None => self.dict_limit(),
         }
+    }
 
-        // Require strict monotonicity (strictly increasing sequence)
-        for i in 1..ints.len() {
-            if ints[i] <= ints[i - 1] {
-                return false;
+    /// Whether integers with running state `delta` are delta encoded.
+    fn uses_delta(&self, delta: Option<DeltaState>) -> bool {
+        delta.is_some_and(|delta| {
+            if self.prefer_delta {
+                delta.increasing_run()
+            } else {
+                delta.beneficial()
             }
-        }
+        })
+    }
 
-        let mut min_delta = i64::MAX;
-        let mut max_delta = i64::MIN;
-        for i in 1..ints.len() {
-            let delta = ints[i] - ints[i - 1];
-            min_delta = min(min_delta, delta);
-            max_delta = max(max_delta, delta);
-        }
+    /// Maximum dictionary entries for this field.
+    fn dict_limit(&self) -> usize {
+        min(
+            self.max_dict_entries,
+            self.limits.max_dict_entries_per_field,
+        )
+    }
 
-        let first = *ints.first().unwrap() as i128;
-        let last = *ints.last().unwrap() as i128;
-        let range = last - first;
-        if range == 0 {
-            return false;
+    /// Delta-encoding state after appending `value` to the integer substream.
+    fn next_delta(&self, value: i64) -> DeltaState {
+        match self.delta {
+            Some(delta) => delta.push(value),
+            None => DeltaState::new(value),
         }
-
-        // Check delta uniformity (low variance in delta magnitudes)
-        let delta_span = (max_delta as i128) - (min_delta as i128);
-        let delta_uniformity = delta_span as f64 / range as f64;
-        delta_uniformity < 0.5
     }
 
     fn ensure_string_len(&self, len: usize) -> Result<()> {
@@ -448,10 +606,56 @@ impl ColumnBuilder {
 
     fn push_string(&mut self, value: &str) -> Result<()> {
         self.ensure_string_len(value.len())?;
-        self.strings.push(value.to_string());
+        if !self.interning {
+            self.string_ids.push(self.interned.len());
+            self.interned.push(value.to_string());
+            return Ok(());
+        }
+        let hash = self.string_hasher.hash_one(value);
+        let id = match self.interned_id(hash, value) {
+            Some(id) => {
+                self.dictionary_hits += 1;
+                id
+            }
+            None => {
+                let id = self.interned.len();
+                self.interned.push(value.to_string());
+                if *self.intern_table.entry(hash).or_insert(id) != id {
+                    self.intern_collisions.insert(value.to_string(), id);
+                }
+                self.dict_entry_bytes += string_payload_len(value.len());
+                self.dictionary_misses += 1;
+                id
+            }
+        };
+        self.dict_index_bytes += uleb128_len(id as u64);
+        self.string_ids.push(id);
+        // A block holds at most `record_count` strings for this field, so past
+        // this point the dictionary can no longer be used. The ids pushed so
+        // far still resolve through `interned`, so the segment falls back to
+        // raw strings rather than failing the block.
+        if self.interned.len() > self.dict_threshold(self.record_count) {
+            self.dictionary_spilled = self.interned.len() > self.dict_limit();
+            self.interning = false;
+            self.intern_table = ahash::AHashMap::new();
+            self.intern_collisions = HashMap::new();
+        }
         Ok(())
     }
 
+    /// Interned id of `value`, whose hash is `hash`, if it was seen before.
+    ///
+    /// Interned ids follow first-occurrence order, so they double as dictionary
+    /// indices. That order is implementation-defined per spec and is distinct
+    /// from block-level field ordering, which `canonicalize_keys` controls.
+    fn interned_id(&self, hash: u64, value: &str) -> Option<usize> {
+        match self.intern_table.get(&hash) {
+            Some(&id) if self.interned[id] == value => Some(id),
+            Some(_) => self.intern_collisions.get(value).copied(),
+            None => None,
+        }
+    }
+
     fn build_decimal(&self, source: &str) -> Result<Decimal> {
         let mut decimal = Decimal::from_str_exact(source)?;
         self.ensure_decimal_limit(&decimal)?;
@@ -463,17 +667,31 @@ impl ColumnBuilder {
         Ok(decimal)
     }
 
-    /// Canonicalize decimal per FLAG_CANONICALIZE_NUMBERS requirements:
-    /// 1. Trim trailing zeros in mantissa
-    /// 2. Use scientific notation for |exponent| > 6 (normalize to single digit before decimal point)
+    /// Canonicalize decimal per FLAG_CANONICALIZE_NUMBERS requirements, as
+    /// configured by [`NumberCanonicalization`]:
+    /// 1. Round to `max_scale` fractional digits when configured
+    /// 2. Trim trailing zeros in mantissa unless `preserve_trailing_zeros` is set
+    /// 3. Use scientific notation once |exponent| exceeds the configured threshold
+    ///    (normalize to single digit before decimal point)
     fn canonicalize_decimal(&self, decimal: &mut Decimal) -> Result<()> {
-        // First, trim trailing zeros
-        self.trim_decimal_trailing_zeros(decimal);
+        if let Some(max_scale) = self.number_policy.max_scale {
+            round_decimal_to_scale(decimal, max_scale);
+        }
+
+        if !self.number_policy.preserve_trailing_zeros {
+            self.trim_decimal_trailing_zeros(decimal);
+        }
         self.ensure_decimal_limit(decimal)?;
 
-        // Apply scientific notation normalization if |exponent| > 6
+        // Apply scientific notation normalization past the threshold
         // This moves digits to achieve a single significant digit before the decimal point
-        if decimal.exponent.abs() > 6 && !decimal.digits.is_empty() {
+        let exceeds_threshold = self
+            .number_policy
+            .scientific_exponent_threshold
+            .map_or(false, |threshold| {
+                decimal.exponent.unsigned_abs() > threshold
+            });
+        if exceeds_threshold && !decimal.digits.is_empty() {
             // Normalize to scientific form: single digit before decimal
             // Example: 12345 with exp=0 → digits="12345" becomes digits="1" with exp adjusted
             let digit_count = decimal.digits.len() as i32;
@@ -526,9 +744,11 @@ impl ColumnBuilder {
                 contrib.present_delta = 1;
                 if let Some(i) = n.as_i64() {
                     contrib.int_encoded_bytes = uleb128_len(zigzag_encode(i));
+                    contrib.int_value = Some(i);
                 } else if let Some(u) = n.as_u64() {
                     if u <= i64::MAX as u64 {
                         contrib.int_encoded_bytes = uleb128_len(zigzag_encode(u as i64));
+                        contrib.int_value = Some(u as i64);
                     } else {
                         let decimal = self.build_decimal(&n.to_string())?;
                         contrib.decimal_encoded_bytes = decimal.encode()?.len();
@@ -541,7 +761,21 @@ impl ColumnBuilder {
             serde_json::Value::String(s) => {
                 self.ensure_string_len(s.len())?;
                 contrib.present_delta = 1;
+                contrib.string_delta = 1;
                 contrib.string_raw_bytes = string_payload_len(s.len());
+                if !self.interning {
+                    return Ok(contrib);
+                }
+                let hash = self.string_hasher.hash_one(s.as_str());
+                let id = match self.interned_id(hash, s) {
+                    Some(id) => id,
+                    None => {
+                        contrib.dict_entry_delta = 1;
+                        contrib.dict_entry_bytes = string_payload_len(s.len());
+                        self.interned.len()
+                    }
+                };
+                contrib.dict_index_bytes = uleb128_len(id as u64);
             }
             serde_json::Value::Object(obj) => {
                 let minified = serde_json::to_vec(obj)
@@ -562,7 +796,11 @@ impl ColumnBuilder {
         Ok(contrib)
     }
 
-    /// Estimate uncompressed size after applying a contribution, for limit checks.
+    /// Uncompressed payload size after applying a contribution, for limit checks.
+    ///
+    /// Matches what `finalize` would produce for `next_record_count` records:
+    /// substream sizes are tracked as values arrive, and the dictionary and
+    /// delta-encoding decisions are replayed with the contribution included.
     pub fn estimated_uncompressed_size_with(
         &self,
         contrib: &ColumnContribution,
@@ -571,15 +809,34 @@ impl ColumnBuilder {
         let presence_bytes = (next_record_count + 7) >> 3;
         let tag_bytes = ((3 * (self.present_count + contrib.present_delta)) + 7) >> 3;
         let bool_bytes = ((self.bool_count + contrib.bool_delta) + 7) >> 3;
+
+        let delta = match contrib.int_value {
+            Some(value) => Some(self.next_delta(value)),
+            None => self.delta,
+        };
+        let int_bytes = match delta {
+            Some(delta) if self.uses_delta(Some(delta)) => delta.encoded_bytes,
+            _ => self.int_encoded_bytes + contrib.int_encoded_bytes,
+        };
+
+        let distinct = self.interned.len() + contrib.dict_entry_delta;
+        let strings = self.string_ids.len() + contrib.string_delta;
+        let string_bytes = if self.uses_dictionary(distinct, strings) {
+            self.dict_entry_bytes
+                + contrib.dict_entry_bytes
+                + self.dict_index_bytes
+                + contrib.dict_index_bytes
+        } else {
+            self.string_raw_bytes + contrib.string_raw_bytes
+        };
+
         presence_bytes
             + tag_bytes
             + bool_bytes
-            + self.int_encoded_bytes
-            + contrib.int_encoded_bytes
+            + int_bytes
             + self.decimal_encoded_bytes
             + contrib.decimal_encoded_bytes
-            + self.string_raw_bytes
-            + contrib.string_raw_bytes
+            + string_bytes
             + self.object_raw_bytes
             + contrib.object_raw_bytes
             + self.array_raw_bytes
@@ -600,6 +857,9 @@ impl ColumnBuilder {
         } else {
             0
         };
+        // A lone string is always dictionary encoded, adding a one-byte index.
+        let dict_index_bytes =
+            usize::from(contrib.string_delta > 0 && self.interning && self.dict_limit() > 0);
 
         Ok(presence_bytes
             + tag_bytes
@@ -607,6 +867,7 @@ impl ColumnBuilder {
             + contrib.int_encoded_bytes
             + contrib.decimal_encoded_bytes
             + contrib.string_raw_bytes
+            + dict_index_bytes
             + contrib.object_raw_bytes
             + contrib.array_raw_bytes)
     }
@@ -629,6 +890,131 @@ pub struct ColumnContribution {
     pub object_raw_bytes: usize,
     /// Additional raw array bytes contributed by the candidate record.
     pub array_raw_bytes: usize,
+    /// Additional string values contributed by the candidate record.
+    pub string_delta: usize,
+    /// Additional distinct dictionary strings contributed by the candidate record.
+    pub dict_entry_delta: usize,
+    /// Additional dictionary entry bytes (varint length + payload) contributed by the candidate record.
+    pub dict_entry_bytes: usize,
+    /// Additional dictionary index bytes contributed by the candidate record.
+    pub dict_index_bytes: usize,
+    /// Integer contributed by the candidate record, for the delta-encoding decision.
+    pub int_value: Option<i64>,
+}
+
+/// Running delta-encoding decision and delta substream size for a column's integers.
+#[derive(Debug, Clone, Copy)]
+struct DeltaState {
+    first: i64,
+    last: i64,
+    count: usize,
+    min_delta: i64,
+    max_delta: i64,
+    increasing: bool,
+    /// Encoded bytes of the base value followed by the deltas
+    encoded_bytes: usize,
+}
+
+impl DeltaState {
+    fn new(value: i64) -> Self {
+        Self {
+            first: value,
+            last: value,
+            count: 1,
+            min_delta: i64::MAX,
+            max_delta: i64::MIN,
+            increasing: true,
+            encoded_bytes: uleb128_len(zigzag_encode(value)),
+        }
+    }
+
+    fn push(mut self, value: i64) -> Self {
+        match value.checked_sub(self.last) {
+            Some(delta) if self.increasing && delta > 0 => {
+                self.min_delta = min(self.min_delta, delta);
+                self.max_delta = max(self.max_delta, delta);
+                self.encoded_bytes += uleb128_len(zigzag_encode(delta));
+            }
+            _ => self.increasing = false,
+        }
+        self.last = value;
+        self.count += 1;
+        self
+    }
+
+    /// Whether the integers seen so far are at least two, strictly increasing
+    fn increasing_run(&self) -> bool {
+        self.count >= 2 && self.increasing
+    }
+
+    /// Whether delta encoding is beneficial for the integers seen so far
+    ///
+    /// Delta encoding heuristic:
+    /// 1. Requires strictly monotonic increasing sequence (every value > previous)
+    /// 2. Uses delta_uniformity metric: (max_delta - min_delta) / total_range < 0.5
+    ///
+    /// This differs from PLAN.md's suggested "increasing_ratio >= 0.95" approach.
+    /// The delta_uniformity metric is more conservative and better suited for compression
+    /// as it checks whether deltas are relatively uniform (low variance), not just whether
+    /// the sequence is mostly increasing. The strictly monotonic check already
+    /// ensures 100% increasing ratio, so the uniformity check provides additional value.
+    ///
+    /// Rationale: Delta encoding is most effective when deltas are small and uniform
+    /// (e.g., sequential IDs, timestamps). High delta variance reduces compression benefit.
+    fn beneficial(&self) -> bool {
+        if !self.increasing_run() {
+            return false;
+        }
+
+        let range = (self.last as i128) - (self.first as i128);
+        // Check delta uniformity (low variance in delta magnitudes)
+        let delta_span = (self.max_delta as i128) - (self.min_delta as i128);
+        let delta_uniformity = delta_span as f64 / range as f64;
+        delta_uniformity < 0.5
+    }
+}
+
+/// Round `decimal` to at most `max_scale` fractional digits (half away from zero).
+fn round_decimal_to_scale(decimal: &mut Decimal, max_scale: u32) {
+    let scale = -(decimal.exponent as i64);
+    let excess = scale - i64::from(max_scale);
+    if excess <= 0 {
+        return;
+    }
+
+    let digit_count = decimal.digits.len();
+    let excess = usize::try_from(excess).unwrap_or(usize::MAX);
+    let keep = digit_count.saturating_sub(excess);
+    let round_up = if excess <= digit_count {
+        decimal.digits[keep] >= b'5'
+    } else {
+        false
+    };
+
+    decimal.digits.truncate(keep);
+    decimal.exponent = -(max_scale as i32);
+
+    if round_up {
+        let mut carry = true;
+        for digit in decimal.digits.iter_mut().rev() {
+            if *digit == b'9' {
+                *digit = b'0';
+            } else {
+                *digit += 1;
+                carry = false;
+                break;
+            }
+        }
+        if carry {
+            decimal.digits.insert(0, b'1');
+        }
+    }
+
+    if decimal.digits.iter().all(|d| *d == b'0') {
+        decimal.sign = false;
+        decimal.digits = vec![b'0'];
+        decimal.exponent = 0;
+    }
 }
 
 fn uleb128_len(mut value: u64) -> usize {
@@ -655,6 +1041,11 @@ pub struct FieldSegment {
     pub dict_entry_count: usize,
     /// Number of present values
     pub value_count_present: usize,
+    /// Null count and distinct sketch, when `CompressOpts::field_stats` is set
+    pub stats: Option<FieldStats>,
+    /// Quantile sketch of the numeric values, when
+    /// `CompressOpts::quantile_sketches` is set and the field has any
+    pub quantiles: Option<QuantileSketch>,
 }
 
 impl FieldSegment {
@@ -697,8 +1088,21 @@ impl FieldSegment {
                     JacError::DecompressError(format!("Zstd compression failed: {}", e))
                 })
             }
+            Codec::ZstdAdvanced {
+                level,
+                window_log,
+                long_distance_matching,
+                target_block_size,
+            } => crate::segment::compress_zstd_advanced(
+                &self.uncompressed_payload,
+                level,
+                window_log,
+                long_distance_matching,
+                target_block_size,
+            ),
             Codec::Brotli(_) => Err(JacError::UnsupportedCompression(2)),
             Codec::Deflate(_) => Err(JacError::UnsupportedCompression(3)),
+            Codec::Other { id, .. } => Err(JacError::UnsupportedCompression(id)),
         }
     }
 }
@@ -706,6 +1110,7 @@ impl FieldSegment {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use jac_format::constants::ENCODING_FLAG_DICTIONARY;
     use serde_json::{json, Number};
 
     #[test]
@@ -738,6 +1143,67 @@ mod tests {
         let segment = builder.finalize(&opts, 5).unwrap();
         assert_eq!(segment.value_count_present, 4);
         assert!(!segment.uncompressed_payload.is_empty());
+        assert!(segment.stats.is_none());
+    }
+
+    #[test]
+    fn test_column_builder_field_stats_count_nulls_and_distinct_values() {
+        let opts = CompressOpts {
+            field_stats: true,
+            ..CompressOpts::default()
+        };
+        let values = [
+            json!("a"),
+            json!(null),
+            json!("b"),
+            json!("a"),
+            json!(1),
+            json!("1"),
+            json!({"k": 1}),
+            json!([1]),
+            json!(2.5),
+            json!(true),
+            json!(null),
+            json!(1),
+        ];
+        let mut builder = ColumnBuilder::new(values.len() + 1, &opts);
+        for (idx, value) in values.iter().enumerate() {
+            builder.add_value(idx, value).unwrap();
+        }
+        let stats = builder
+            .finalize(&opts, values.len() + 1)
+            .unwrap()
+            .stats
+            .unwrap();
+        assert_eq!(stats.null_count, 2);
+        // "a", "b", 1, "1", the object, the array, 2.5 and true
+        assert_eq!(stats.distinct.estimate(), 8);
+    }
+
+    #[test]
+    fn test_column_builder_quantiles_cover_ints_and_decimals() {
+        let opts = CompressOpts {
+            quantile_sketches: true,
+            ..CompressOpts::default()
+        };
+        let values = [json!(10), json!("x"), json!(-2.5), json!(null), json!(40)];
+        let mut builder = ColumnBuilder::new(values.len(), &opts);
+        for (idx, value) in values.iter().enumerate() {
+            builder.add_value(idx, value).unwrap();
+        }
+        let quantiles = builder
+            .finalize(&opts, values.len())
+            .unwrap()
+            .quantiles
+            .unwrap();
+        assert_eq!(quantiles.count(), 3);
+        assert_eq!((quantiles.min(), quantiles.max()), (-2.5, 40.0));
+        assert_eq!(quantiles.quantile(0.5), 10.0);
+
+        // Fields without numbers get no sketch
+        let mut builder = ColumnBuilder::new(1, &opts);
+        builder.add_value(0, &json!("x")).unwrap();
+        assert!(builder.finalize(&opts, 1).unwrap().quantiles.is_none());
     }
 
     #[test]
@@ -852,6 +1318,105 @@ mod tests {
         assert_eq!(decimal.to_json_string(), "1e7");
     }
 
+    fn canonical_decimal_for(opts: &CompressOpts, literal: &str) -> Decimal {
+        let mut builder = ColumnBuilder::new(1, opts);
+        let number: Number = literal.parse().unwrap();
+        builder
+            .add_value(0, &serde_json::Value::Number(number))
+            .unwrap();
+
+        let segment = builder.finalize(opts, 1).unwrap();
+        let presence_len = (1 + 7) >> 3;
+        let tag_len = ((3 * segment.value_count_present) + 7) >> 3;
+        let start = presence_len + tag_len;
+        Decimal::decode(&segment.uncompressed_payload[start..])
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn test_number_policy_preserves_trailing_zeros() {
+        let mut opts = CompressOpts {
+            canonicalize_numbers: true,
+            ..CompressOpts::default()
+        };
+
+        // Trimming turns `1.0` into `1`, which decodes as an integer
+        assert_eq!(canonical_decimal_for(&opts, "1.0").to_json_string(), "1");
+
+        opts.number_canonicalization.preserve_trailing_zeros = true;
+        assert_eq!(canonical_decimal_for(&opts, "1.0").to_json_string(), "1.0");
+        assert_eq!(
+            canonical_decimal_for(&opts, "100.0").to_json_string(),
+            "100.0"
+        );
+    }
+
+    #[test]
+    fn test_number_policy_scientific_threshold() {
+        let mut opts = CompressOpts {
+            canonicalize_numbers: true,
+            ..CompressOpts::default()
+        };
+
+        // v1 default collapses to a single significant digit past |exponent| > 6
+        let decimal = canonical_decimal_for(&opts, "1.23e12");
+        assert_eq!(decimal.digits, b"1");
+        assert_eq!(decimal.exponent, 12);
+
+        opts.number_canonicalization.scientific_exponent_threshold = None;
+        let decimal = canonical_decimal_for(&opts, "1.23e12");
+        assert_eq!(decimal.digits, b"123");
+        assert_eq!(decimal.exponent, 10);
+
+        opts.number_canonicalization.scientific_exponent_threshold = Some(12);
+        let decimal = canonical_decimal_for(&opts, "1.23e12");
+        assert_eq!(decimal.digits, b"123");
+        assert_eq!(decimal.exponent, 10);
+    }
+
+    #[test]
+    fn test_number_policy_max_scale_rounds_half_away_from_zero() {
+        let opts = CompressOpts {
+            canonicalize_numbers: true,
+            number_canonicalization: NumberCanonicalization {
+                max_scale: Some(2),
+                ..NumberCanonicalization::default()
+            },
+            ..CompressOpts::default()
+        };
+
+        assert_eq!(
+            canonical_decimal_for(&opts, "1.005").to_json_string(),
+            "1.01"
+        );
+        assert_eq!(
+            canonical_decimal_for(&opts, "-2.344").to_json_string(),
+            "-2.34"
+        );
+        assert_eq!(canonical_decimal_for(&opts, "9.999").to_json_string(), "10");
+        assert_eq!(canonical_decimal_for(&opts, "0.004").to_json_string(), "0");
+        assert_eq!(
+            canonical_decimal_for(&opts, "0.005").to_json_string(),
+            "0.01"
+        );
+        assert_eq!(canonical_decimal_for(&opts, "3.5").to_json_string(), "3.5");
+    }
+
+    #[test]
+    fn test_number_policy_ignored_without_canonicalization() {
+        let opts = CompressOpts {
+            number_canonicalization: NumberCanonicalization {
+                max_scale: Some(0),
+                ..NumberCanonicalization::default()
+            },
+            ..CompressOpts::default()
+        };
+
+        let decimal = canonical_decimal_for(&opts, "2.5");
+        assert_eq!(decimal.to_json_string(), "2.5");
+    }
+
     #[test]
     fn test_column_builder_string_length_limit_enforced() {
         let mut opts = CompressOpts::default();
@@ -873,6 +1438,299 @@ mod tests {
         assert!(matches!(err, Err(JacError::LimitExceeded(_))));
     }
 
+    /// Compare each value's projected size against the finalized segment size.
+    fn assert_projections_exact(opts: &CompressOpts, values: &[Option<serde_json::Value>]) {
+        let mut builder = ColumnBuilder::for_field(values.len(), opts, "value");
+        for (idx, value) in values.iter().enumerate() {
+            let contrib = match value {
+                Some(value) => builder.contribution_for_value(value).unwrap(),
+                None => ColumnContribution::default(),
+            };
+            let projected = builder.estimated_uncompressed_size_with(&contrib, idx + 1);
+            if let Some(value) = value {
+                builder.add_value(idx, value).unwrap();
+            }
+            let actual = builder
+                .clone()
+                .finalize(opts, idx + 1)
+                .unwrap()
+                .uncompressed_payload
+                .len();
+            assert_eq!(
+                projected, actual,
+                "projection after value {} ({:?})",
+                idx, value
+            );
+            assert_eq!(builder.estimated_uncompressed_size(idx + 1), actual);
+        }
+    }
+
+    #[test]
+    fn test_column_builder_projection_matches_finalized_size() {
+        let opts = CompressOpts::default();
+
+        // Delta encoding switches on for the sequential run and off after the jump.
+        let mut ints: Vec<_> = (0..40).map(|i| Some(json!(100_000 + i))).collect();
+        ints.extend([
+            Some(json!(900_000)),
+            None,
+            Some(json!(7)),
+            Some(json!(i64::MIN)),
+        ]);
+        assert_projections_exact(&opts, &ints);
+
+        // Dictionary encoding holds for repeated levels, then falls back to raw.
+        let levels = ["info", "warn", "error"];
+        let mut strings: Vec<_> = (0..40)
+            .map(|i| (i % 5 != 4).then(|| json!(levels[i % 3])))
+            .collect();
+        strings.extend((0..40).map(|i| Some(json!(format!("user-{}", i)))));
+        assert_projections_exact(&opts, &strings);
+
+        let mixed = [
+            Some(json!(null)),
+            Some(json!(true)),
+            Some(json!(1.25)),
+            Some(json!({"a": [1, 2]})),
+            Some(json!(["x", null])),
+            None,
+            Some(json!("text")),
+            Some(json!(u64::MAX)),
+            Some(json!(false)),
+            Some(json!(3)),
+        ];
+        assert_projections_exact(&opts, &mixed);
+
+        // Overflowing a small dictionary limit drops the dictionary for good.
+        let small_dict = CompressOpts {
+            max_dict_entries: 2,
+            ..CompressOpts::default()
+        };
+        let overflow: Vec<_> = ["a", "b", "a", "c", "a", "b", "a", "b"]
+            .iter()
+            .cycle()
+            .take(40)
+            .map(|s| Some(json!(s)))
+            .collect();
+        assert_projections_exact(&small_dict, &overflow);
+    }
+
+    #[test]
+    fn test_dictionary_policy_ratio_and_field_modes() {
+        let mut opts = CompressOpts::default();
+        opts.dictionary_policy.max_cardinality_ratio = 0.5;
+        let hint = |mode| FieldEncodingHint {
+            dictionary: Some(mode),
+            ..FieldEncodingHint::default()
+        };
+        opts.field_hints = BTreeMap::from([
+            ("never".to_string(), hint(DictionaryMode::Never)),
+            ("always".to_string(), hint(DictionaryMode::Always)),
+        ]);
+        // Four distinct strings among eight: above 1/8, within 1/2.
+        let values: Vec<_> = (0..8).map(|i| Some(json!(format!("v{}", i % 4)))).collect();
+        for (field, dictionary) in [("status", true), ("never", false), ("always", true)] {
+            let mut builder = ColumnBuilder::for_field(8, &opts, field);
+            for (idx, value) in values.iter().enumerate() {
+                builder.add_value(idx, value.as_ref().unwrap()).unwrap();
+            }
+            let segment = builder.finalize(&opts, 8).unwrap();
+            assert_eq!(segment.encoding_flags & 1 != 0, dictionary, "{field}");
+        }
+
+        // Eight distinct strings among eight pass only without a ratio.
+        let unique: Vec<_> = (0..8).map(|i| Some(json!(format!("u{}", i)))).collect();
+        for (field, dictionary) in [("status", false), ("always", true)] {
+            let mut builder = ColumnBuilder::for_field(8, &opts, field);
+            for (idx, value) in unique.iter().enumerate() {
+                builder.add_value(idx, value.as_ref().unwrap()).unwrap();
+            }
+            let segment = builder.finalize(&opts, 8).unwrap();
+            assert_eq!(segment.encoding_flags & 1 != 0, dictionary, "{field}");
+        }
+        for mode in [
+            None,
+            Some(DictionaryMode::Never),
+            Some(DictionaryMode::Always),
+        ] {
+            let mut opts = opts.clone();
+            if let Some(mode) = mode {
+                opts.field_hints.insert("value".to_string(), hint(mode));
+            }
+            assert_projections_exact(&opts, &values);
+            assert_projections_exact(&opts, &unique);
+        }
+    }
+
+    #[test]
+    fn test_column_builder_stops_interning_past_dictionary_threshold() {
+        let opts = CompressOpts::default();
+        // A 40-record block accepts at most max(2, 40 / 8) = 5 distinct strings.
+        let mut builder = ColumnBuilder::new(40, &opts);
+        for idx in 0..6 {
+            builder
+                .add_value(idx, &json!(format!("id-{}", idx)))
+                .unwrap();
+        }
+        assert!(!builder.interning);
+        assert!(builder.intern_table.is_empty());
+        assert!(!builder.dictionary_spilled());
+        assert_eq!(builder.dictionary_counts(), (0, 6));
+        for idx in 6..40 {
+            builder.add_value(idx, &json!("id-0")).unwrap();
+        }
+        assert_eq!(builder.dictionary_counts(), (0, 6));
+        let segment = builder.finalize(&opts, 40).unwrap();
+        assert_eq!(segment.encoding_flags & 1, 0);
+
+        let mut values: Vec<_> = (0..6).map(|i| Some(json!(format!("id-{}", i)))).collect();
+        values.extend((6..40).map(|_| Some(json!("id-0"))));
+        assert_projections_exact(&opts, &values);
+    }
+
+    #[test]
+    fn test_column_builder_spills_dictionary_past_max_dict_entries() {
+        let opts = CompressOpts {
+            max_dict_entries: 4,
+            ..CompressOpts::default()
+        };
+        // Four levels repeat for half the block, then a fifth distinct value
+        // overflows max_dict_entries mid-block.
+        let levels = ["debug", "info", "warn", "error", "fatal"];
+        let values: Vec<_> = (0..100)
+            .map(|idx| Some(json!(levels[if idx < 50 { idx % 4 } else { idx % 5 }])))
+            .collect();
+        let mut builder = ColumnBuilder::new(100, &opts);
+        for (idx, value) in values.iter().take(50).enumerate() {
+            builder.add_value(idx, value.as_ref().unwrap()).unwrap();
+        }
+        assert!(builder.interning);
+        assert!(!builder.dictionary_spilled());
+        for (idx, value) in values.iter().enumerate().skip(50) {
+            builder.add_value(idx, value.as_ref().unwrap()).unwrap();
+        }
+        assert!(builder.dictionary_spilled());
+        let segment = builder.finalize(&opts, 100).unwrap();
+        assert_eq!(segment.encoding_flags & ENCODING_FLAG_DICTIONARY, 0);
+        assert_eq!(segment.dict_entry_count, 0);
+        assert_projections_exact(&opts, &values);
+    }
+
+    #[test]
+    fn test_column_builder_reset_reuses_and_optionally_retains_dictionary() {
+        let blocks = [
+            vec![json!("a"), json!(1), json!("b"), json!("a"), json!(true)],
+            vec![json!("b"), json!("c"), json!(2.5), json!("b"), json!(null)],
+        ];
+        let build = |builder: &mut ColumnBuilder, values: &[serde_json::Value]| {
+            for (idx, value) in values.iter().enumerate() {
+                builder.add_value(idx, value).unwrap();
+            }
+        };
+
+        let mut opts = CompressOpts::default();
+        opts.dictionary_policy.max_cardinality_ratio = 1.0;
+        let mut reused = ColumnBuilder::new(5, &opts);
+        for values in &blocks {
+            let mut fresh = ColumnBuilder::new(5, &opts);
+            build(&mut fresh, values);
+            build(&mut reused, values);
+            let expected = fresh.finalize(&opts, 5).unwrap();
+            let segment = reused.finalize(&opts, 5).unwrap();
+            assert_eq!(segment.uncompressed_payload, expected.uncompressed_payload);
+            assert_eq!(reused.dictionary_counts(), fresh.dictionary_counts());
+            reused.reset();
+        }
+
+        // Retained, "b" is already in the second block's dictionary and "a",
+        // which the first block used, leads it though the block never uses it.
+        opts.dictionary_policy.retain_across_blocks = true;
+        let mut builder = ColumnBuilder::new(5, &opts);
+        build(&mut builder, &blocks[0]);
+        builder.reset();
+        assert_eq!(builder.interned, ["a", "b"]);
+        build(&mut builder, &blocks[1]);
+        assert_eq!(builder.dictionary_counts(), (2, 1));
+        let segment = builder.finalize(&opts, 5).unwrap();
+        assert_eq!(segment.dict_entry_count, 3);
+        let projected = builder.estimated_uncompressed_size(5);
+        assert_eq!(projected, segment.uncompressed_payload.len());
+
+        // Entries the block did not reference are not carried further.
+        builder.reset();
+        assert_eq!(builder.interned, ["b", "c"]);
+        assert_eq!(
+            builder.interned_id(builder.string_hasher.hash_one("c"), "c"),
+            Some(1)
+        );
+        assert_eq!(
+            builder.interned_id(builder.string_hasher.hash_one("a"), "a"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_field_hints_skip_dictionary_and_prefer_delta() {
+        let hint = FieldEncodingHint {
+            dictionary: Some(DictionaryMode::Never),
+            prefer_delta: true,
+        };
+        let opts = CompressOpts {
+            field_hints: BTreeMap::from([("value".to_string(), hint)]),
+            ..CompressOpts::default()
+        };
+
+        // Repeated strings would normally be dictionary encoded.
+        let repeated: Vec<_> = (0..32).map(|i| Some(json!(["a", "b"][i % 2]))).collect();
+        assert_projections_exact(&opts, &repeated);
+        let mut builder = ColumnBuilder::for_field(32, &opts, "value");
+        for (idx, value) in repeated.iter().enumerate() {
+            builder.add_value(idx, value.as_ref().unwrap()).unwrap();
+        }
+        assert_eq!(builder.dictionary_counts(), (0, 0));
+        let segment = builder.finalize(&opts, 32).unwrap();
+        assert_eq!(segment.encoding_flags & 1, 0);
+        assert_eq!(segment.dict_entry_count, 0);
+
+        // Increasing timestamps with uneven gaps fail the uniformity check.
+        let timestamps = [1_700_000_000, 1_700_000_001, 1_700_000_900, 1_700_000_902];
+        let values: Vec<_> = timestamps.iter().map(|ts| Some(json!(ts))).collect();
+        assert_projections_exact(&opts, &values);
+        for (field, delta) in [("value", true), ("other", false)] {
+            let mut builder = ColumnBuilder::for_field(4, &opts, field);
+            for (idx, ts) in timestamps.iter().enumerate() {
+                builder.add_value(idx, &json!(ts)).unwrap();
+            }
+            let segment = builder.finalize(&opts, 4).unwrap();
+            assert_eq!(segment.encoding_flags & 2 != 0, delta, "{field}");
+        }
+    }
+
+    #[test]
+    fn test_column_builder_interning_counts_and_hash_collisions() {
+        let opts = CompressOpts::default();
+        let mut builder = ColumnBuilder::new(24, &opts);
+        builder.add_value(0, &json!("a")).unwrap();
+
+        // Pretend "b" hashes like "a" so the collision check has to tell them apart.
+        let b_hash = builder.string_hasher.hash_one("b");
+        builder.intern_table.insert(b_hash, 0);
+
+        for (idx, value) in ["b", "b", "a", "c", "a"].iter().enumerate() {
+            builder.add_value(idx + 1, &json!(value)).unwrap();
+        }
+        assert_eq!(builder.interned, ["a", "b", "c"]);
+        assert_eq!(builder.string_ids, [0, 1, 1, 0, 2, 0]);
+        assert_eq!(builder.dictionary_counts(), (3, 3));
+
+        for idx in 6..24 {
+            builder.add_value(idx, &json!("a")).unwrap();
+        }
+        let segment = builder.finalize(&opts, 24).unwrap();
+        assert!(segment.encoding_flags & 1 != 0);
+        assert_eq!(segment.dict_entry_count, 3);
+    }
+
     #[test]
     fn test_column_builder_dictionary_threshold_falls_back_to_raw() {
         let opts = CompressOpts::default();
@@ -26,13 +26,25 @@ pub use block_builder::{
     compress_block_segments, BlockBuilder, BlockData, BlockFinish, TryAddRecordOutcome,
     UncompressedBlockData,
 };
-pub use block_decode::{BlockDecoder, DecompressOpts};
+pub use block_decode::{BlockDecoder, DecodeCounters, DecodeWarnings, DecompressOpts, JsonRecords};
 pub use column::{ColumnBuilder, FieldSegment};
 pub use segment::FieldSegment as Segment;
-pub use segment_decode::FieldSegmentDecoder;
+pub use segment_decode::{
+    read_dictionary_histogram, read_segment_tags, DictionaryHistogram, FieldSegmentDecoder,
+    ValueRef,
+};
 
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 
+/// Largest `window_log` accepted by [`Codec::ZstdAdvanced`]: the default
+/// window limit of zstd decoders (128 MiB, above the 64 MiB segment cap)
+pub const ZSTD_MAX_WINDOW_LOG: u32 = 27;
+
+/// Smallest `window_log` zstd supports
+pub const ZSTD_MIN_WINDOW_LOG: u32 = 10;
+
 // Compression options
 
 /// Compression options for encoding
@@ -44,12 +56,33 @@ pub struct CompressOpts {
     pub default_codec: Codec,
     /// Canonicalize keys (lexicographic order)
     pub canonicalize_keys: bool,
+    /// Record per-record top-level key order in a sidecar column so decoders can
+    /// reproduce the original field order (ignored when `canonicalize_keys` is set)
+    pub preserve_key_order: bool,
+    /// Fields whose segments are laid out first in each block's payload, in
+    /// this order, so projecting them reads one contiguous prefix (the field
+    /// directory, and so decoded key order, is unchanged)
+    pub hot_fields: Vec<String>,
     /// Canonicalize numbers (scientific notation, trim trailing zeros)
     pub canonicalize_numbers: bool,
+    /// Rules applied to decimals when `canonicalize_numbers` is enabled
+    pub number_canonicalization: NumberCanonicalization,
     /// Nested objects/arrays are opaque (v1 behavior)
     pub nested_opaque: bool,
     /// Maximum dictionary entries per field
     pub max_dict_entries: usize,
+    /// When string columns are dictionary encoded
+    pub dictionary_policy: DictionaryPolicy,
+    /// Per-field encoding hints, keyed by top-level field name
+    pub field_hints: BTreeMap<String, FieldEncodingHint>,
+    /// Store each field's null count and a distinct-value sketch in its
+    /// directory entry (`ENCODING_FLAG_FIELD_STATS`), about 260 bytes per
+    /// field per block
+    pub field_stats: bool,
+    /// Store a quantile sketch of each field's numeric values in its
+    /// directory entry (`ENCODING_FLAG_QUANTILES`), about 140 bytes per
+    /// numeric field per block
+    pub quantile_sketches: bool,
     /// Security limits
     pub limits: Limits,
 }
@@ -60,16 +93,115 @@ impl Default for CompressOpts {
             block_target_records: 100_000,
             default_codec: Codec::Zstd(6),
             canonicalize_keys: false,
+            preserve_key_order: false,
+            hot_fields: Vec::new(),
             canonicalize_numbers: false,
+            number_canonicalization: NumberCanonicalization::default(),
             nested_opaque: true, // Must be true in v1
             max_dict_entries: 4_096,
+            dictionary_policy: DictionaryPolicy::default(),
+            field_hints: BTreeMap::new(),
+            field_stats: false,
+            quantile_sketches: false,
             limits: Limits::default(),
         }
     }
 }
 
+/// Policy controlling how decimals are rewritten when number canonicalization is enabled.
+///
+/// The defaults reproduce the v1 rules: trailing zeros are trimmed, values whose
+/// exponent magnitude exceeds 6 are collapsed to a single significant digit, and
+/// the scale is left untouched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NumberCanonicalization {
+    /// Keep trailing zeros in the mantissa so `1.0` and `1` stay distinct
+    pub preserve_trailing_zeros: bool,
+    /// Collapse to scientific form once `|exponent|` exceeds this value (`None` disables)
+    pub scientific_exponent_threshold: Option<u32>,
+    /// Round values with more fractional digits than this (half away from zero)
+    pub max_scale: Option<u32>,
+}
+
+impl Default for NumberCanonicalization {
+    fn default() -> Self {
+        Self {
+            preserve_trailing_zeros: false,
+            scientific_exponent_threshold: Some(6),
+            max_scale: None,
+        }
+    }
+}
+
+/// Policy deciding which string columns are dictionary encoded.
+///
+/// A block's strings for a field use a dictionary when its distinct values
+/// number at most `max_cardinality_ratio` of the field's string values (but
+/// at least 2) and at most `max_dict_entries`. Once a field holds more
+/// distinct strings than a full block could accept, the builder stops
+/// interning them, so UUID-like columns do not pay for a dictionary that
+/// would be discarded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DictionaryPolicy {
+    /// Largest ratio of distinct strings to string values (0 to 1; the
+    /// default 0.125 is the spec's one in eight); a field's
+    /// [`FieldEncodingHint::dictionary`] overrides it
+    pub max_cardinality_ratio: f64,
+    /// Start each block's dictionary for a field with the entries the
+    /// previous block's dictionary used, so values recurring across blocks
+    /// are not interned again (entries the new block never uses still count
+    /// toward its limits and are written)
+    pub retain_across_blocks: bool,
+}
+
+impl Default for DictionaryPolicy {
+    fn default() -> Self {
+        Self {
+            max_cardinality_ratio: 0.125,
+            retain_across_blocks: false,
+        }
+    }
+}
+
+/// Per-field dictionary override ([`FieldEncodingHint::dictionary`]) of the
+/// [`DictionaryPolicy`] ratio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DictionaryMode {
+    /// Store the field's strings raw without interning them
+    Never,
+    /// Dictionary encode whenever the distinct strings fit `max_dict_entries`,
+    /// whatever their ratio
+    Always,
+}
+
+/// Encoding hints for one field, overriding the column builder's heuristics.
+///
+/// Hints only steer choices the format already allows, so files written with
+/// them decode like any other.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldEncodingHint {
+    /// Whether strings are dictionary encoded whatever the
+    /// [`DictionaryPolicy`] ratio says: `Never` stores them raw without
+    /// interning them (high-cardinality values such as ids, whose dictionary
+    /// would be built and then discarded)
+    pub dictionary: Option<DictionaryMode>,
+    /// Delta encode integers whenever they strictly increase, without the
+    /// delta-uniformity check (epoch timestamps, whose deltas vary but stay
+    /// far smaller than the values)
+    pub prefer_delta: bool,
+}
+
 /// Compression codec
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Non-exhaustive: new codecs may be added, so matches outside this crate need
+/// a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
 pub enum Codec {
     /// No compression
     None,
@@ -82,10 +214,36 @@ pub enum Codec {
         /// Requested encoder threads (>= 1)
         threads: usize,
     },
+    /// Zstandard with advanced encoder parameters, for large repetitive
+    /// segments such as opaque nested JSON
+    ///
+    /// Output stays a standard zstd frame readable with default decoder
+    /// settings, so `window_log` is capped at [`ZSTD_MAX_WINDOW_LOG`].
+    ZstdAdvanced {
+        /// Compression level (compatible with FileHeader metadata)
+        level: i32,
+        /// Log2 of the match window (10..=27; `None` keeps the level's default)
+        #[serde(default)]
+        window_log: Option<u32>,
+        /// Enable long-distance matching
+        #[serde(default)]
+        long_distance_matching: bool,
+        /// Target compressed block size in bytes (`None` disables)
+        #[serde(default)]
+        target_block_size: Option<u32>,
+    },
     /// Brotli compression (not implemented in v0.1.0)
     Brotli(u8),
     /// Deflate compression (not implemented in v0.1.0)
     Deflate(u8),
+    /// Compressor id this build does not know; describes existing data and
+    /// cannot be used to encode
+    Other {
+        /// Compressor id from the wire format
+        id: u8,
+        /// Compression level from the wire format
+        level: u8,
+    },
 }
 
 impl Codec {
@@ -95,8 +253,22 @@ impl Codec {
             Codec::None => 0,
             Codec::Zstd(_) => 1,
             Codec::ZstdWithThreads { .. } => 1,
+            Codec::ZstdAdvanced { .. } => 1,
             Codec::Brotli(_) => 2,
             Codec::Deflate(_) => 3,
+            Codec::Other { id, .. } => *id,
+        }
+    }
+
+    /// Codec described by a wire-format compressor id and level; unknown ids
+    /// map to [`Codec::Other`].
+    pub fn from_compressor_id(id: u8, level: u8) -> Self {
+        match id {
+            0 => Codec::None,
+            1 => Codec::Zstd(level),
+            2 => Codec::Brotli(level),
+            3 => Codec::Deflate(level),
+            id => Codec::Other { id, level },
         }
     }
 
@@ -105,11 +277,12 @@ impl Codec {
         match self {
             Codec::None => 0,
             Codec::Zstd(level) => *level,
-            Codec::ZstdWithThreads { level, .. } => {
+            Codec::ZstdWithThreads { level, .. } | Codec::ZstdAdvanced { level, .. } => {
                 u8::try_from(*level).unwrap_or(if *level < 0 { 0 } else { u8::MAX })
             }
             Codec::Brotli(level) => *level,
             Codec::Deflate(level) => *level,
+            Codec::Other { level, .. } => *level,
         }
     }
 
@@ -118,6 +291,7 @@ impl Codec {
         match self {
             Codec::Zstd(level) => Some(i32::from(*level)),
             Codec::ZstdWithThreads { level, .. } => Some(*level),
+            Codec::ZstdAdvanced { level, .. } => Some(*level),
             _ => None,
         }
     }
@@ -136,6 +310,8 @@ impl Codec {
 /// When `single_threaded` is true, zstd codecs are wrapped to force the encoder
 /// to use a single internal thread. This prevents oversubscription when the
 /// caller executes multiple compression tasks in parallel (e.g., via Rayon).
+/// `Codec::ZstdAdvanced` always encodes on the calling thread and is returned
+/// unchanged.
 pub fn configure_codec_for_parallel(codec: Codec, single_threaded: bool) -> Codec {
     match codec {
         Codec::Zstd(level) if single_threaded => Codec::ZstdWithThreads {
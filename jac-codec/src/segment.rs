@@ -1,6 +1,6 @@
 //! Field segment encoding
 
-use crate::Codec;
+use crate::{Codec, ZSTD_MAX_WINDOW_LOG, ZSTD_MIN_WINDOW_LOG};
 use jac_format::{JacError, Result};
 use std::convert::TryFrom;
 use std::io::Write;
@@ -58,6 +58,18 @@ impl FieldSegment {
                     JacError::DecompressError(format!("Zstd compression failed: {}", e))
                 })
             }
+            Codec::ZstdAdvanced {
+                level,
+                window_log,
+                long_distance_matching,
+                target_block_size,
+            } => compress_zstd_advanced(
+                &self.uncompressed_payload,
+                level,
+                window_log,
+                long_distance_matching,
+                target_block_size,
+            ),
             Codec::Brotli(_) => {
                 // Brotli (not implemented in v0.1.0)
                 Err(JacError::UnsupportedCompression(2))
@@ -66,10 +78,59 @@ impl FieldSegment {
                 // Deflate (not implemented in v0.1.0)
                 Err(JacError::UnsupportedCompression(3))
             }
+            Codec::Other { id, .. } => Err(JacError::UnsupportedCompression(id)),
         }
     }
 }
 
+/// Compress `payload` as a single zstd frame using advanced parameters.
+///
+/// The source size is pledged so the frame header records it, letting
+/// decoders size their window to the segment rather than to `window_log`.
+pub(crate) fn compress_zstd_advanced(
+    payload: &[u8],
+    level: i32,
+    window_log: Option<u32>,
+    long_distance_matching: bool,
+    target_block_size: Option<u32>,
+) -> Result<Vec<u8>> {
+    use zstd::zstd_safe::CParameter;
+
+    if let Some(log) = window_log {
+        if !(ZSTD_MIN_WINDOW_LOG..=ZSTD_MAX_WINDOW_LOG).contains(&log) {
+            return Err(JacError::Internal(format!(
+                "Zstd window_log {} outside supported range {}..={}",
+                log, ZSTD_MIN_WINDOW_LOG, ZSTD_MAX_WINDOW_LOG
+            )));
+        }
+    }
+
+    let config_error =
+        |e: std::io::Error| JacError::DecompressError(format!("Zstd parameter rejected: {}", e));
+    let mut encoder = zstd::Encoder::new(Vec::new(), level)
+        .map_err(|e| JacError::DecompressError(format!("Zstd encoder init failed: {}", e)))?;
+    encoder
+        .set_pledged_src_size(Some(payload.len() as u64))
+        .map_err(config_error)?;
+    encoder
+        .long_distance_matching(long_distance_matching)
+        .map_err(config_error)?;
+    if let Some(log) = window_log {
+        encoder.window_log(log).map_err(config_error)?;
+    }
+    if let Some(size) = target_block_size {
+        encoder
+            .set_parameter(CParameter::TargetCBlockSize(size))
+            .map_err(config_error)?;
+    }
+    encoder
+        .write_all(payload)
+        .map_err(|e| JacError::DecompressError(format!("Zstd write failed: {}", e)))?;
+    encoder
+        .finish()
+        .map_err(|e| JacError::DecompressError(format!("Zstd compression failed: {}", e)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,4 +157,44 @@ mod tests {
         let err = segment.compress(Codec::Deflate(6)).unwrap_err();
         assert!(matches!(err, JacError::UnsupportedCompression(3)));
     }
+
+    #[test]
+    fn test_compress_unknown_compressor_returns_unsupported() {
+        let codec = Codec::from_compressor_id(99, 4);
+        assert_eq!(codec, Codec::Other { id: 99, level: 4 });
+        assert_eq!((codec.compressor_id(), codec.level()), (99, 4));
+        assert_eq!(Codec::from_compressor_id(1, 7), Codec::Zstd(7));
+
+        let err = sample_segment().compress(codec).unwrap_err();
+        assert!(matches!(err, JacError::UnsupportedCompression(99)));
+    }
+
+    #[test]
+    fn test_compress_zstd_advanced_round_trips_and_validates_window() {
+        let segment = FieldSegment {
+            uncompressed_payload: b"{\"nested\":[1,2,3]}".repeat(2_000),
+            ..sample_segment()
+        };
+        let codec = Codec::ZstdAdvanced {
+            level: 12,
+            window_log: Some(24),
+            long_distance_matching: true,
+            target_block_size: Some(16 * 1024),
+        };
+        assert_eq!((codec.compressor_id(), codec.level()), (1, 12));
+
+        let compressed = segment.compress(codec).unwrap();
+        assert!(compressed.len() < segment.uncompressed_payload.len());
+        let decoded = zstd::decode_all(&compressed[..]).unwrap();
+        assert_eq!(decoded, segment.uncompressed_payload);
+
+        let too_wide = Codec::ZstdAdvanced {
+            level: 3,
+            window_log: Some(30),
+            long_distance_matching: false,
+            target_block_size: None,
+        };
+        let err = segment.compress(too_wide).unwrap_err();
+        assert!(matches!(err, JacError::Internal(_)));
+    }
 }
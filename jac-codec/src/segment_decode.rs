@@ -1,21 +1,132 @@
 //! Field segment decoder
 
 use std::convert::TryFrom;
+use std::io::{Read, Write};
+use std::time::Instant;
 
-use bitvec::prelude::*;
 use jac_format::{
-    bitpack::{PresenceBitmap, TagUnpacker},
-    constants::{ENCODING_FLAG_DELTA, ENCODING_FLAG_DICTIONARY},
+    bitpack::{expand_bits, PresenceBitmap, TagUnpacker},
+    constants::{
+        ENCODING_FLAG_DELTA, ENCODING_FLAG_DICTIONARY, ENCODING_FLAG_FIELD_STATS,
+        ENCODING_FLAG_KEY_ORDER, ENCODING_FLAG_QUANTILES,
+    },
     decimal::Decimal,
-    varint::{decode_uleb128, zigzag_decode},
+    varint::{decode_uleb128, decode_uleb128_batch, zigzag_decode},
     FieldDirectoryEntry, JacError, Limits, Result, TypeTag,
 };
 use serde_json::{self, Value};
 
+use crate::block_decode::DecompressOpts;
+
+/// Encoding flags this decoder understands.
+const KNOWN_ENCODING_FLAGS: u64 = ENCODING_FLAG_DICTIONARY
+    | ENCODING_FLAG_DELTA
+    | ENCODING_FLAG_KEY_ORDER
+    | ENCODING_FLAG_FIELD_STATS
+    | ENCODING_FLAG_QUANTILES;
+
 /// Field segment decoder capable of projecting values from a single field
 pub struct FieldSegmentDecoder {
     record_count: usize,
-    values: Vec<Option<Value>>,
+    /// Type of each record's value and its position in that type's stream
+    /// (`None` when absent); strings are positioned by `strings` index
+    slots: Vec<Option<(TypeTag, usize)>>,
+    bools: Vec<bool>,
+    ints: Vec<i64>,
+    decimals: Vec<Decimal>,
+    /// Dictionary entries, or every string value when the segment has none
+    strings: Vec<String>,
+    /// Whether `strings` holds dictionary entries
+    dictionary_encoded: bool,
+    /// Each object value
+    objects: Vec<Nested>,
+    /// Each array value
+    arrays: Vec<Nested>,
+}
+
+/// A stored object or array: its minified JSON text and, when the segment was
+/// decoded with nested-JSON validation, the value that validation parsed, so
+/// [`FieldSegmentDecoder::get_value`] does not parse the text a second time.
+struct Nested {
+    text: String,
+    parsed: Option<Value>,
+}
+
+impl Nested {
+    fn to_value(&self) -> Result<Value> {
+        match &self.parsed {
+            Some(value) => Ok(value.clone()),
+            None => serde_json::from_str(&self.text).map_err(|_| JacError::CorruptBlock),
+        }
+    }
+}
+
+/// A decoded value borrowed from a [`FieldSegmentDecoder`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueRef<'a> {
+    /// JSON `null`
+    Null,
+    /// Boolean value
+    Bool(bool),
+    /// Integer value
+    Int(i64),
+    /// Decimal value
+    Decimal(&'a Decimal),
+    /// String value
+    Str(&'a str),
+    /// Nested object or array as stored: minified JSON text
+    RawJson(&'a str),
+}
+
+impl ValueRef<'_> {
+    /// Type tag of the value.
+    pub fn type_tag(&self) -> TypeTag {
+        match self {
+            ValueRef::Null => TypeTag::Null,
+            ValueRef::Bool(_) => TypeTag::Bool,
+            ValueRef::Int(_) => TypeTag::Int,
+            ValueRef::Decimal(_) => TypeTag::Decimal,
+            ValueRef::Str(_) => TypeTag::String,
+            ValueRef::RawJson(text) if text.trim_start().starts_with('[') => TypeTag::Array,
+            ValueRef::RawJson(_) => TypeTag::Object,
+        }
+    }
+
+    /// Convert to an owned JSON value.
+    pub fn to_value(&self) -> Result<Value> {
+        Ok(match *self {
+            ValueRef::Null => Value::Null,
+            ValueRef::Bool(value) => Value::Bool(value),
+            ValueRef::Int(value) => Value::Number(value.into()),
+            ValueRef::Decimal(decimal) => {
+                let number_value: Value = serde_json::from_str(&decimal.to_json_string())
+                    .map_err(|_| JacError::CorruptBlock)?;
+                let number = number_value
+                    .as_number()
+                    .cloned()
+                    .ok_or(JacError::CorruptBlock)?;
+                Value::Number(number)
+            }
+            ValueRef::Str(value) => Value::String(value.to_string()),
+            ValueRef::RawJson(text) => {
+                serde_json::from_str(text).map_err(|_| JacError::CorruptBlock)?
+            }
+        })
+    }
+
+    /// Write the value as JSON text, as serializing [`Self::to_value`] would;
+    /// nested values are copied from their stored text without parsing.
+    pub fn write_json<W: Write>(&self, out: &mut W) -> Result<()> {
+        match *self {
+            ValueRef::Null => out.write_all(b"null")?,
+            ValueRef::Bool(value) => out.write_all(if value { b"true" } else { b"false" })?,
+            ValueRef::Int(value) => write!(out, "{}", value)?,
+            ValueRef::Decimal(_) => serde_json::to_writer(out, &self.to_value()?)?,
+            ValueRef::Str(value) => serde_json::to_writer(out, value)?,
+            ValueRef::RawJson(text) => out.write_all(text.as_bytes())?,
+        }
+        Ok(())
+    }
 }
 
 impl FieldSegmentDecoder {
@@ -26,33 +137,67 @@ impl FieldSegmentDecoder {
         record_count: usize,
         limits: &Limits,
     ) -> Result<Self> {
-        if dir_entry.segment_uncompressed_len > limits.max_segment_uncompressed_len {
-            return Err(JacError::LimitExceeded(format!(
-                "Segment uncompressed length {} exceeds limit {}",
-                dir_entry.segment_uncompressed_len, limits.max_segment_uncompressed_len
-            )));
+        let opts = DecompressOpts {
+            limits: limits.clone(),
+            ..DecompressOpts::default()
+        };
+        Self::with_opts(compressed, dir_entry, record_count, &opts)
+    }
+
+    /// Create a segment decoder that follows `opts.strict` for structural
+    /// anomalies, recording them in `opts.warnings` when permissive. The
+    /// segment and its decode time are added to `opts.counters`.
+    pub fn with_opts(
+        compressed: &[u8],
+        dir_entry: &FieldDirectoryEntry,
+        record_count: usize,
+        opts: &DecompressOpts,
+    ) -> Result<Self> {
+        let start = Instant::now();
+        let decoder = Self::decode(compressed, dir_entry, record_count, opts)?;
+        opts.counters.record(start.elapsed());
+        Ok(decoder)
+    }
+
+    fn decode(
+        compressed: &[u8],
+        dir_entry: &FieldDirectoryEntry,
+        record_count: usize,
+        opts: &DecompressOpts,
+    ) -> Result<Self> {
+        let limits = &opts.limits;
+        let field = dir_entry.field_name.as_str();
+        let unknown_flags = dir_entry.encoding_flags & !KNOWN_ENCODING_FLAGS;
+        if unknown_flags != 0 {
+            opts.anomaly(
+                JacError::UnsupportedFeature(format!(
+                    "Encoding flags {unknown_flags:#x} on field '{field}'"
+                )),
+                || format!("field '{field}': ignoring unknown encoding flags {unknown_flags:#x}"),
+            )?;
         }
 
-        // Decompress payload according to compressor
-        let mut decompressed = Vec::with_capacity(dir_entry.segment_uncompressed_len);
-        match dir_entry.compressor {
-            0 => {
-                if compressed.len() != dir_entry.segment_compressed_len {
-                    return Err(JacError::CorruptBlock);
-                }
-                decompressed.extend_from_slice(compressed);
-            }
-            1 => {
-                decompressed = zstd::decode_all(compressed).map_err(|e| {
-                    JacError::DecompressError(format!("Zstd decompression failed: {}", e))
+        let decompressed = match decompress_segment(compressed, dir_entry, limits) {
+            // A field written with an unknown compressor reads as absent in
+            // permissive mode so the rest of the block stays readable.
+            Err(err @ JacError::UnsupportedCompression(id)) => {
+                opts.anomaly(err, || {
+                    format!("field '{field}': skipped, unsupported compressor id {id}")
                 })?;
+                return Ok(Self {
+                    record_count,
+                    slots: vec![None; record_count],
+                    bools: Vec::new(),
+                    ints: Vec::new(),
+                    decimals: Vec::new(),
+                    strings: Vec::new(),
+                    dictionary_encoded: false,
+                    objects: Vec::new(),
+                    arrays: Vec::new(),
+                });
             }
-            other => return Err(JacError::UnsupportedCompression(other)),
-        }
-
-        if decompressed.len() != dir_entry.segment_uncompressed_len {
-            return Err(JacError::CorruptBlock);
-        }
+            result => result?,
+        };
 
         if dir_entry.presence_bytes > limits.max_presence_bytes {
             return Err(JacError::LimitExceeded(format!(
@@ -83,8 +228,13 @@ impl FieldSegmentDecoder {
         if presence_end > decompressed.len() {
             return Err(JacError::UnexpectedEof);
         }
-        let presence =
-            PresenceBitmap::from_bytes(&decompressed[cursor..presence_end], record_count);
+        let presence_bytes = &decompressed[cursor..presence_end];
+        if padding_bits_set(presence_bytes, record_count) {
+            opts.anomaly(JacError::CorruptBlock, || {
+                format!("field '{field}': presence bitmap has padding bits set")
+            })?;
+        }
+        let presence = PresenceBitmap::from_bytes(presence_bytes, record_count);
         cursor = presence_end;
 
         let present_count = presence.count_present();
@@ -96,7 +246,13 @@ impl FieldSegmentDecoder {
         if tag_end > decompressed.len() {
             return Err(JacError::UnexpectedEof);
         }
-        let mut tag_unpacker = TagUnpacker::new(&decompressed[cursor..tag_end], present_count);
+        let tag_bytes = &decompressed[cursor..tag_end];
+        if padding_bits_set(tag_bytes, 3 * present_count) {
+            opts.anomaly(JacError::CorruptBlock, || {
+                format!("field '{field}': type tag stream has padding bits set")
+            })?;
+        }
+        let mut tag_unpacker = TagUnpacker::new(tag_bytes, present_count);
         cursor = tag_end;
 
         let mut tags = Vec::with_capacity(present_count);
@@ -144,7 +300,9 @@ impl FieldSegmentDecoder {
                 return Err(JacError::CorruptBlock);
             }
         } else if dir_entry.dict_entry_count != 0 {
-            return Err(JacError::CorruptBlock);
+            opts.anomaly(JacError::CorruptBlock, || {
+                format!("field '{field}': dictionary entry count set without dictionary encoding")
+            })?;
         }
 
         // Count tags for substream sizing
@@ -181,22 +339,25 @@ impl FieldSegmentDecoder {
             if end > decompressed.len() {
                 return Err(JacError::UnexpectedEof);
             }
-            let bits = BitVec::<u8, Lsb0>::from_slice(&decompressed[cursor..end]);
-            bool_values.extend(bits.iter().take(bool_count).map(|bit| *bit));
+            if padding_bits_set(&decompressed[cursor..end], bool_count) {
+                opts.anomaly(JacError::CorruptBlock, || {
+                    format!("field '{field}': boolean stream has padding bits set")
+                })?;
+            }
+            bool_values = expand_bits(&decompressed[cursor..end], bool_count);
             cursor = end;
         }
 
         // Integer substream
         let mut int_values = Vec::with_capacity(int_count);
         if int_count > 0 {
+            let mut raw_values = Vec::new();
+            cursor += decode_uleb128_batch(&decompressed[cursor..], int_count, &mut raw_values)?;
             if dir_entry.encoding_flags & ENCODING_FLAG_DELTA != 0 {
-                let (base_raw, base_bytes) = decode_uleb128(&decompressed[cursor..])?;
-                cursor += base_bytes;
-                let mut current = zigzag_decode(base_raw);
+                // The first value is the base, the rest are deltas.
+                let mut current = zigzag_decode(raw_values[0]);
                 int_values.push(current);
-                for _ in 1..int_count {
-                    let (delta_raw, delta_bytes) = decode_uleb128(&decompressed[cursor..])?;
-                    cursor += delta_bytes;
+                for &delta_raw in &raw_values[1..] {
                     let delta = zigzag_decode(delta_raw);
                     current = current
                         .checked_add(delta)
@@ -204,12 +365,7 @@ impl FieldSegmentDecoder {
                     int_values.push(current);
                 }
             } else {
-                for _ in 0..int_count {
-                    let (value_raw, value_bytes) = decode_uleb128(&decompressed[cursor..])?;
-                    cursor += value_bytes;
-                    let value = zigzag_decode(value_raw);
-                    int_values.push(value);
-                }
+                int_values.extend(raw_values.into_iter().map(zigzag_decode));
             }
         }
 
@@ -229,19 +385,21 @@ impl FieldSegmentDecoder {
         }
 
         // String substream (shared for strings, objects, arrays)
-        let mut string_values = Vec::with_capacity(string_count);
+        let mut string_ids = Vec::with_capacity(string_count);
+        let mut string_values = Vec::new();
         if has_dictionary {
-            for _ in 0..string_count {
-                let (index_raw, index_bytes) = decode_uleb128(&decompressed[cursor..])?;
-                cursor += index_bytes;
+            let mut indices = Vec::new();
+            cursor += decode_uleb128_batch(&decompressed[cursor..], string_count, &mut indices)?;
+            for index_raw in indices {
                 let index = usize::try_from(index_raw).map_err(|_| JacError::CorruptBlock)?;
-                let value = dictionary
-                    .get(index)
-                    .ok_or(JacError::DictionaryError)?
-                    .clone();
-                string_values.push(value);
+                if index >= dictionary.len() {
+                    return Err(JacError::DictionaryError);
+                }
+                string_ids.push(index);
             }
+            string_values = dictionary;
         } else {
+            string_values.reserve(string_count);
             for _ in 0..string_count {
                 let (len_raw, len_bytes) = decode_uleb128(&decompressed[cursor..])?;
                 cursor += len_bytes;
@@ -260,6 +418,7 @@ impl FieldSegmentDecoder {
                     .map_err(|_| JacError::CorruptBlock)?
                     .to_string();
                 cursor = end;
+                string_ids.push(string_values.len());
                 string_values.push(value);
             }
         }
@@ -279,13 +438,12 @@ impl FieldSegmentDecoder {
             if end > decompressed.len() {
                 return Err(JacError::UnexpectedEof);
             }
-            let value = serde_json::from_slice::<Value>(&decompressed[cursor..end])
-                .map_err(|_| JacError::CorruptBlock)?;
-            if !value.is_object() {
-                return Err(JacError::CorruptBlock);
-            }
+            object_values.push(nested_value(
+                &decompressed[cursor..end],
+                b'{',
+                opts.validate_nested_json,
+            )?);
             cursor = end;
-            object_values.push(value);
         }
 
         let mut array_values = Vec::with_capacity(array_count);
@@ -303,109 +461,335 @@ impl FieldSegmentDecoder {
             if end > decompressed.len() {
                 return Err(JacError::UnexpectedEof);
             }
-            let value = serde_json::from_slice::<Value>(&decompressed[cursor..end])
-                .map_err(|_| JacError::CorruptBlock)?;
-            if !value.is_array() {
-                return Err(JacError::CorruptBlock);
-            }
+            array_values.push(nested_value(
+                &decompressed[cursor..end],
+                b'[',
+                opts.validate_nested_json,
+            )?);
             cursor = end;
-            array_values.push(value);
         }
 
         if cursor != decompressed.len() {
-            return Err(JacError::CorruptBlock);
+            let trailing = decompressed.len() - cursor;
+            opts.anomaly(JacError::CorruptBlock, || {
+                format!("field '{field}': {trailing} trailing bytes after value streams")
+            })?;
         }
 
-        // Reconstruct values per record
-        let mut values = vec![None; record_count];
-        let mut present_idx = 0;
-        let mut bool_idx = 0;
-        let mut int_idx = 0;
-        let mut decimal_idx = 0;
-        let mut string_idx = 0;
-        let mut object_idx = 0;
-        let mut array_idx = 0;
+        // Locate each record's value in its type's stream
+        let mut slots = vec![None; record_count];
+        let mut tags = tags.into_iter();
+        let mut counts = [0usize; 7];
+        let mut string_ids = string_ids.into_iter();
 
-        for (record_idx, slot) in values.iter_mut().enumerate() {
-            if !presence.is_present(record_idx) {
+        let present = expand_bits(presence_bytes, record_count);
+        for (slot, present) in slots.iter_mut().zip(present) {
+            if !present {
                 continue;
             }
 
-            let tag = tags.get(present_idx).ok_or(JacError::CorruptBlock)?;
-            let value = match tag {
-                TypeTag::Null => Value::Null,
-                TypeTag::Bool => {
-                    let val = bool_values
-                        .get(bool_idx)
-                        .copied()
-                        .ok_or(JacError::CorruptBlock)?;
-                    bool_idx += 1;
-                    Value::Bool(val)
-                }
-                TypeTag::Int => {
-                    let val = int_values
-                        .get(int_idx)
-                        .copied()
-                        .ok_or(JacError::CorruptBlock)?;
-                    int_idx += 1;
-                    Value::Number(val.into())
-                }
-                TypeTag::Decimal => {
-                    let decimal = decimal_values
-                        .get(decimal_idx)
-                        .ok_or(JacError::CorruptBlock)?;
-                    decimal_idx += 1;
-                    let number_value: Value = serde_json::from_str(&decimal.to_json_string())
-                        .map_err(|_| JacError::CorruptBlock)?;
-                    let number = number_value
-                        .as_number()
-                        .cloned()
-                        .ok_or(JacError::CorruptBlock)?;
-                    Value::Number(number)
-                }
-                TypeTag::String => {
-                    let string = string_values
-                        .get(string_idx)
-                        .ok_or(JacError::CorruptBlock)?
-                        .clone();
-                    string_idx += 1;
-                    Value::String(string)
-                }
-                TypeTag::Object => {
-                    let obj = object_values
-                        .get(object_idx)
-                        .ok_or(JacError::CorruptBlock)?
-                        .clone();
-                    object_idx += 1;
-                    obj
-                }
-                TypeTag::Array => {
-                    let arr = array_values
-                        .get(array_idx)
-                        .ok_or(JacError::CorruptBlock)?
-                        .clone();
-                    array_idx += 1;
-                    arr
+            let tag = tags.next().ok_or(JacError::CorruptBlock)?;
+            let index = match tag {
+                TypeTag::String => string_ids.next().ok_or(JacError::CorruptBlock)?,
+                _ => {
+                    let count = &mut counts[tag as usize];
+                    *count += 1;
+                    *count - 1
                 }
             };
-
-            *slot = Some(value);
-            present_idx += 1;
+            *slot = Some((tag, index));
         }
 
         Ok(Self {
             record_count,
-            values,
+            slots,
+            bools: bool_values,
+            ints: int_values,
+            decimals: decimal_values,
+            strings: string_values,
+            dictionary_encoded: has_dictionary,
+            objects: object_values,
+            arrays: array_values,
         })
     }
 
     /// Retrieve the decoded value for a specific record index
     pub fn get_value(&self, record_idx: usize) -> Result<Option<Value>> {
-        if record_idx >= self.record_count {
-            return Err(JacError::Internal("Record index out of bounds".to_string()));
+        match self.slot(record_idx)? {
+            Some((TypeTag::Object, index)) => self.objects[index].to_value().map(Some),
+            Some((TypeTag::Array, index)) => self.arrays[index].to_value().map(Some),
+            slot => slot
+                .map(|(tag, index)| self.value_ref(tag, index).to_value())
+                .transpose(),
+        }
+    }
+
+    /// Borrow the decoded value for a specific record index
+    pub fn get_ref(&self, record_idx: usize) -> Result<Option<ValueRef<'_>>> {
+        Ok(self
+            .slot(record_idx)?
+            .map(|(tag, index)| self.value_ref(tag, index)))
+    }
+
+    fn slot(&self, record_idx: usize) -> Result<Option<(TypeTag, usize)>> {
+        self.slots
+            .get(record_idx)
+            .copied()
+            .ok_or_else(|| JacError::Internal("Record index out of bounds".to_string()))
+    }
+
+    /// Call `f` with the index and borrowed value of every present record,
+    /// in record order, without allocating owned values; stops at the first
+    /// error `f` returns.
+    pub fn visit<F>(&self, mut f: F) -> Result<()>
+    where
+        F: FnMut(usize, ValueRef<'_>) -> Result<()>,
+    {
+        for (record_idx, slot) in self.slots.iter().enumerate() {
+            if let Some((tag, index)) = *slot {
+                f(record_idx, self.value_ref(tag, index))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of records the segment covers.
+    pub fn record_count(&self) -> usize {
+        self.record_count
+    }
+
+    /// Dictionary entries of a dictionary-encoded segment, in the order
+    /// [`dictionary_index`](Self::dictionary_index) refers to them.
+    pub fn dictionary(&self) -> Option<&[String]> {
+        self.dictionary_encoded.then_some(self.strings.as_slice())
+    }
+
+    /// Dictionary entry holding a record's value, when the segment is
+    /// dictionary-encoded and the value is a string.
+    pub fn dictionary_index(&self, record_idx: usize) -> Option<usize> {
+        match self.slots.get(record_idx)? {
+            Some((TypeTag::String, index)) if self.dictionary_encoded => Some(*index),
+            _ => None,
+        }
+    }
+
+    fn value_ref(&self, tag: TypeTag, index: usize) -> ValueRef<'_> {
+        // Slots are only built for positions that exist in their stream.
+        match tag {
+            TypeTag::Null => ValueRef::Null,
+            TypeTag::Bool => ValueRef::Bool(self.bools[index]),
+            TypeTag::Int => ValueRef::Int(self.ints[index]),
+            TypeTag::Decimal => ValueRef::Decimal(&self.decimals[index]),
+            TypeTag::String => ValueRef::Str(&self.strings[index]),
+            TypeTag::Object => ValueRef::RawJson(&self.objects[index].text),
+            TypeTag::Array => ValueRef::RawJson(&self.arrays[index].text),
+        }
+    }
+}
+
+/// Stored nested value, checked to open with `open` (`{` or `[`) and, when
+/// `validate` is set, parsed to check that it is well-formed JSON.
+fn nested_value(bytes: &[u8], open: u8, validate: bool) -> Result<Nested> {
+    if bytes.iter().find(|byte| !byte.is_ascii_whitespace()) != Some(&open) {
+        return Err(JacError::CorruptBlock);
+    }
+    let parsed = if validate {
+        Some(serde_json::from_slice::<Value>(bytes).map_err(|_| JacError::CorruptBlock)?)
+    } else {
+        None
+    };
+    let text = String::from_utf8(bytes.to_vec()).map_err(|_| JacError::CorruptBlock)?;
+    Ok(Nested { text, parsed })
+}
+
+/// Whether any bit past the first `used_bits` of `bytes` is set.
+fn padding_bits_set(bytes: &[u8], used_bits: usize) -> bool {
+    let mut rest = bytes.get(used_bits / 8..).unwrap_or_default();
+    let partial = used_bits % 8;
+    if partial != 0 {
+        if let Some((&last, tail)) = rest.split_first() {
+            if last >> partial != 0 {
+                return true;
+            }
+            rest = tail;
+        }
+    }
+    rest.iter().any(|&byte| byte != 0)
+}
+
+fn decompress_segment(
+    compressed: &[u8],
+    dir_entry: &FieldDirectoryEntry,
+    limits: &Limits,
+) -> Result<Vec<u8>> {
+    if dir_entry.segment_uncompressed_len > limits.max_segment_uncompressed_len {
+        return Err(JacError::LimitExceeded(format!(
+            "Segment uncompressed length {} exceeds limit {}",
+            dir_entry.segment_uncompressed_len, limits.max_segment_uncompressed_len
+        )));
+    }
+
+    // Decompress payload according to compressor
+    let mut decompressed = Vec::with_capacity(dir_entry.segment_uncompressed_len);
+    match dir_entry.compressor {
+        0 => {
+            if compressed.len() != dir_entry.segment_compressed_len {
+                return Err(JacError::CorruptBlock);
+            }
+            decompressed.extend_from_slice(compressed);
         }
-        Ok(self.values[record_idx].clone())
+        1 => {
+            decompressed = zstd::decode_all(compressed).map_err(|e| {
+                JacError::DecompressError(format!("Zstd decompression failed: {}", e))
+            })?;
+        }
+        other => return Err(JacError::UnsupportedCompression(other)),
     }
+
+    if decompressed.len() != dir_entry.segment_uncompressed_len {
+        return Err(JacError::CorruptBlock);
+    }
+
+    Ok(decompressed)
+}
+
+/// Read only the type tags of a field segment.
+///
+/// Decompresses just the presence bitmap and tag stream at the front of the
+/// segment, so inspecting which types a column holds never materializes values.
+pub fn read_segment_tags<R: Read>(
+    compressed: R,
+    dir_entry: &FieldDirectoryEntry,
+    record_count: usize,
+    limits: &Limits,
+) -> Result<Vec<TypeTag>> {
+    if dir_entry.presence_bytes > limits.max_presence_bytes {
+        return Err(JacError::LimitExceeded(format!(
+            "Presence bytes {} exceeds limit {}",
+            dir_entry.presence_bytes, limits.max_presence_bytes
+        )));
+    }
+    if dir_entry.tag_bytes > limits.max_tag_bytes {
+        return Err(JacError::LimitExceeded(format!(
+            "Tag bytes {} exceeds limit {}",
+            dir_entry.tag_bytes, limits.max_tag_bytes
+        )));
+    }
+    if dir_entry.presence_bytes != (record_count + 7) >> 3
+        || dir_entry.tag_bytes != ((3 * dir_entry.value_count_present) + 7) >> 3
+    {
+        return Err(JacError::CorruptBlock);
+    }
+
+    let prefix_len = dir_entry.presence_bytes + dir_entry.tag_bytes;
+    if prefix_len > dir_entry.segment_uncompressed_len {
+        return Err(JacError::CorruptBlock);
+    }
+
+    let mut prefix = vec![0u8; prefix_len];
+    let read_result = match dir_entry.compressor {
+        0 => compressed.take(prefix_len as u64).read_exact(&mut prefix),
+        1 => zstd::stream::read::Decoder::new(compressed)
+            .and_then(|mut decoder| decoder.read_exact(&mut prefix)),
+        other => return Err(JacError::UnsupportedCompression(other)),
+    };
+    read_result.map_err(|e| match e.kind() {
+        std::io::ErrorKind::UnexpectedEof => JacError::UnexpectedEof,
+        _ => JacError::DecompressError(format!("Zstd decompression failed: {}", e)),
+    })?;
+
+    let tag_slice = &prefix[dir_entry.presence_bytes..];
+    let mut tags = Vec::with_capacity(dir_entry.value_count_present);
+    for raw in TagUnpacker::new(tag_slice, dir_entry.value_count_present) {
+        tags.push(TypeTag::from_u8(raw)?);
+    }
+    if tags.len() != dir_entry.value_count_present {
+        return Err(JacError::CorruptBlock);
+    }
+    Ok(tags)
+}
+
+/// Occurrence counts of each dictionary entry in a segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictionaryHistogram {
+    /// Dictionary strings in segment order
+    pub entries: Vec<String>,
+    /// Number of values referencing each entry (parallel to `entries`)
+    pub counts: Vec<u64>,
+    /// Number of explicit `null` values in the segment
+    pub nulls: u64,
+}
+
+/// Count dictionary index references without decoding per-value strings.
+///
+/// Returns `Ok(None)` when the segment is not dictionary-encoded or holds values
+/// other than strings and nulls; callers should fall back to
+/// [`FieldSegmentDecoder`] for those segments.
+pub fn read_dictionary_histogram(
+    compressed: &[u8],
+    dir_entry: &FieldDirectoryEntry,
+    record_count: usize,
+    limits: &Limits,
+) -> Result<Option<DictionaryHistogram>> {
+    if dir_entry.encoding_flags & ENCODING_FLAG_DICTIONARY == 0 || dir_entry.dict_entry_count == 0 {
+        return Ok(None);
+    }
+
+    let tags = read_segment_tags(compressed, dir_entry, record_count, limits)?;
+    if tags
+        .iter()
+        .any(|tag| !matches!(tag, TypeTag::String | TypeTag::Null))
+    {
+        return Ok(None);
+    }
+    let string_count = tags.iter().filter(|tag| **tag == TypeTag::String).count();
+
+    let decompressed = decompress_segment(compressed, dir_entry, limits)?;
+    let mut cursor = dir_entry.presence_bytes + dir_entry.tag_bytes;
+
+    let mut entries = Vec::with_capacity(dir_entry.dict_entry_count);
+    for _ in 0..dir_entry.dict_entry_count {
+        let (len_raw, len_bytes) = decode_uleb128(decompressed.get(cursor..).unwrap_or(&[]))?;
+        cursor += len_bytes;
+        let string_len = usize::try_from(len_raw).map_err(|_| JacError::CorruptBlock)?;
+        if string_len > limits.max_string_len_per_value {
+            return Err(JacError::LimitExceeded(format!(
+                "Dictionary string length {} exceeds limit {}",
+                string_len, limits.max_string_len_per_value
+            )));
+        }
+        let end = cursor + string_len;
+        if end > decompressed.len() {
+            return Err(JacError::UnexpectedEof);
+        }
+        let entry = std::str::from_utf8(&decompressed[cursor..end])
+            .map_err(|_| JacError::CorruptBlock)?
+            .to_string();
+        entries.push(entry);
+        cursor = end;
+    }
+
+    let mut counts = vec![0u64; entries.len()];
+    let mut indices = Vec::new();
+    cursor += decode_uleb128_batch(
+        decompressed.get(cursor..).unwrap_or(&[]),
+        string_count,
+        &mut indices,
+    )?;
+    for index_raw in indices {
+        let index = usize::try_from(index_raw).map_err(|_| JacError::CorruptBlock)?;
+        *counts.get_mut(index).ok_or(JacError::DictionaryError)? += 1;
+    }
+
+    if cursor != decompressed.len() {
+        return Err(JacError::CorruptBlock);
+    }
+
+    Ok(Some(DictionaryHistogram {
+        entries,
+        counts,
+        nulls: (tags.len() - string_count) as u64,
+    }))
 }
 
 #[cfg(test)]
@@ -489,6 +873,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_segment_decoder_visits_borrowed_values() {
+        let records = vec![
+            map_from_json(json!({"mixed": "text"})),
+            map_from_json(json!({})),
+            map_from_json(json!({"mixed": 7})),
+            map_from_json(json!({"mixed": 2.5})),
+            map_from_json(json!({"mixed": null})),
+            map_from_json(json!({"mixed": [1, {"a": false}]})),
+            map_from_json(json!({"mixed": {"k": "v"}})),
+            map_from_json(json!({"mixed": "text"})),
+        ];
+
+        let (block, limits) = build_block(&records, |_| {});
+        let (decoder, _) = field_decoder(&block, &limits, "mixed");
+        let mut visited = Vec::new();
+        decoder
+            .visit(|idx, value| {
+                visited.push((idx, value.type_tag()));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            visited,
+            [
+                (0, TypeTag::String),
+                (2, TypeTag::Int),
+                (3, TypeTag::Decimal),
+                (4, TypeTag::Null),
+                (5, TypeTag::Array),
+                (6, TypeTag::Object),
+                (7, TypeTag::String),
+            ]
+        );
+
+        assert_eq!(decoder.get_ref(0).unwrap(), Some(ValueRef::Str("text")));
+        assert_eq!(decoder.get_ref(1).unwrap(), None);
+        assert_eq!(decoder.get_ref(2).unwrap(), Some(ValueRef::Int(7)));
+        assert_eq!(
+            decoder.get_ref(5).unwrap(),
+            Some(ValueRef::RawJson(r#"[1,{"a":false}]"#))
+        );
+        assert!(decoder.get_ref(8).is_err());
+        let mut seen = 0;
+        let err = decoder.visit(|_, _| {
+            seen += 1;
+            Err(JacError::Internal("stop".to_string()))
+        });
+        assert!(err.is_err());
+        assert_eq!(seen, 1);
+        for (idx, record) in records.iter().enumerate() {
+            let value = decoder
+                .get_ref(idx)
+                .unwrap()
+                .map(|value| value.to_value().unwrap());
+            assert_eq!(value.as_ref(), record.get("mixed"));
+        }
+    }
+
+    #[test]
+    fn test_read_segment_tags_matches_full_decode() {
+        let records = vec![
+            map_from_json(json!({"mixed": true})),
+            map_from_json(json!({})),
+            map_from_json(json!({"mixed": "text"})),
+            map_from_json(json!({"mixed": [1, 2]})),
+        ];
+
+        for codec in [Codec::None, Codec::Zstd(3)] {
+            let (block, limits) = build_block(&records, |opts| opts.default_codec = codec);
+            let entry = &block.header.fields[0];
+            let tags = read_segment_tags(
+                block.segments[0].as_slice(),
+                entry,
+                block.header.record_count,
+                &limits,
+            )
+            .unwrap();
+            assert_eq!(tags, vec![TypeTag::Bool, TypeTag::String, TypeTag::Array]);
+        }
+    }
+
     #[test]
     fn test_segment_decoder_dictionary_strings() {
         let records = vec![
@@ -503,6 +969,64 @@ mod tests {
         assert!(entry.encoding_flags & ENCODING_FLAG_DICTIONARY != 0);
         assert_eq!(decoder.get_value(0).unwrap(), Some(json!("alice")));
         assert_eq!(decoder.get_value(2).unwrap(), Some(json!("bob")));
+
+        let dictionary = decoder.dictionary().unwrap();
+        assert_eq!(dictionary.len(), 2);
+        let names: Vec<&str> = (0..4)
+            .map(|idx| dictionary[decoder.dictionary_index(idx).unwrap()].as_str())
+            .collect();
+        assert_eq!(names, ["alice", "alice", "bob", "bob"]);
+        assert_eq!(decoder.dictionary_index(4), None);
+    }
+
+    #[test]
+    fn test_dictionary_histogram_counts_indices() {
+        let mut records: Vec<_> = [
+            "alice", "bob", "alice", "alice", "bob", "alice", "bob", "bob",
+        ]
+        .iter()
+        .map(|name| map_from_json(json!({ "name": name })))
+        .collect();
+        records.push(map_from_json(json!({ "name": null })));
+        records.push(map_from_json(json!({ "other": 1 })));
+        let (block, limits) = build_block(&records, |_| {});
+        let record_count = block.header.record_count;
+        let idx = block
+            .header
+            .fields
+            .iter()
+            .position(|entry| entry.field_name == "name")
+            .unwrap();
+        let entry = &block.header.fields[idx];
+        assert!(entry.encoding_flags & ENCODING_FLAG_DICTIONARY != 0);
+
+        let histogram =
+            read_dictionary_histogram(&block.segments[idx], entry, record_count, &limits)
+                .unwrap()
+                .expect("dictionary segment");
+        let mut pairs: Vec<_> = histogram
+            .entries
+            .iter()
+            .cloned()
+            .zip(histogram.counts.iter().copied())
+            .collect();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            vec![("alice".to_string(), 4), ("bob".to_string(), 4)]
+        );
+        assert_eq!(histogram.nulls, 1);
+
+        let other_idx = 1 - idx.min(1);
+        let other = &block.header.fields[other_idx];
+        assert!(read_dictionary_histogram(
+            &block.segments[other_idx],
+            other,
+            record_count,
+            &limits
+        )
+        .unwrap()
+        .is_none());
     }
 
     #[test]
@@ -585,6 +1109,8 @@ mod tests {
             segment_uncompressed_len: 2,
             segment_compressed_len: 2,
             segment_offset: 0,
+            stats: None,
+            quantiles: None,
         };
 
         let compressed = vec![0x01, 0x07];
@@ -611,6 +1137,8 @@ mod tests {
             segment_uncompressed_len: 2,
             segment_compressed_len: 2,
             segment_offset: 0,
+            stats: None,
+            quantiles: None,
         };
 
         let compressed = vec![0x00, 0x00];
@@ -622,4 +1150,46 @@ mod tests {
             Ok(_) => panic!("expected corrupt block error"),
         }
     }
+
+    #[test]
+    fn test_segment_decoder_strict_rejects_anomalies_permissive_warns() {
+        let null_entry = |encoding_flags: u64, len: usize| FieldDirectoryEntry {
+            field_name: "n".to_string(),
+            compressor: 0,
+            compression_level: 0,
+            presence_bytes: 1,
+            tag_bytes: 1,
+            value_count_present: 1,
+            encoding_flags,
+            dict_entry_count: 0,
+            segment_uncompressed_len: len,
+            segment_compressed_len: len,
+            segment_offset: 0,
+            stats: None,
+            quantiles: None,
+        };
+        let cases = [
+            (null_entry(1 << 7, 2), vec![0x01, 0x00], "unknown encoding"),
+            (null_entry(0, 2), vec![0x81, 0x00], "presence bitmap"),
+            (null_entry(0, 2), vec![0x01, 0x08], "type tag stream"),
+            (null_entry(0, 3), vec![0x01, 0x00, 0xFF], "trailing bytes"),
+        ];
+
+        for (entry, bytes, expected) in cases {
+            let strict = DecompressOpts::default();
+            assert!(FieldSegmentDecoder::with_opts(&bytes, &entry, 1, &strict).is_err());
+            assert!(strict.warnings.is_empty());
+
+            let permissive = DecompressOpts {
+                strict: false,
+                ..DecompressOpts::default()
+            };
+            let decoder = FieldSegmentDecoder::with_opts(&bytes, &entry, 1, &permissive)
+                .unwrap_or_else(|err| panic!("{expected}: {err:?}"));
+            assert_eq!(decoder.get_value(0).unwrap(), Some(Value::Null));
+            let warnings = permissive.warnings.take();
+            assert_eq!(warnings.len(), 1);
+            assert!(warnings[0].contains(expected), "{warnings:?}");
+        }
+    }
 }
@@ -5,11 +5,15 @@
 
 use jac_codec::block_builder::BlockBuilder;
 use jac_codec::block_decode::BlockDecoder;
-use jac_codec::{Codec, CompressOpts, DecompressOpts, TryAddRecordOutcome};
+use jac_codec::{
+    Codec, CompressOpts, DecompressOpts, DictionaryPolicy, NumberCanonicalization,
+    TryAddRecordOutcome,
+};
 use jac_format::{
     block::BlockHeader, error::JacError, header::FileHeader, limits::Limits, types::TypeTag,
 };
 use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 
 /// Test that files created with different endianness assumptions work correctly
 #[test]
@@ -26,9 +30,16 @@ fn test_endianness_compatibility() {
         block_target_records: 10,
         default_codec: Codec::Zstd(15),
         canonicalize_keys: true,
+        preserve_key_order: false,
+        hot_fields: Vec::new(),
         canonicalize_numbers: true,
+        number_canonicalization: NumberCanonicalization::default(),
         nested_opaque: true,
         max_dict_entries: 4096,
+        dictionary_policy: DictionaryPolicy::default(),
+        field_hints: BTreeMap::new(),
+        field_stats: false,
+        quantile_sketches: false,
         limits: Limits::default(),
     };
 
@@ -51,6 +62,7 @@ fn test_endianness_compatibility() {
     let decompress_opts = DecompressOpts {
         limits: Limits::default(),
         verify_checksums: true,
+        ..DecompressOpts::default()
     };
 
     let block_bytes = {
@@ -163,6 +175,15 @@ fn test_compression_codec_compatibility() {
             },
             true,
         ),
+        (
+            Codec::ZstdAdvanced {
+                level: 3,
+                window_log: Some(22),
+                long_distance_matching: true,
+                target_block_size: None,
+            },
+            true,
+        ),
         (Codec::Brotli(11), false), // Not implemented in v0.1.0
         (Codec::Deflate(6), false), // Not implemented in v0.1.0
     ];
@@ -174,9 +195,16 @@ fn test_compression_codec_compatibility() {
             block_target_records: 10,
             default_codec: codec,
             canonicalize_keys: false,
+            preserve_key_order: false,
+            hot_fields: Vec::new(),
             canonicalize_numbers: false,
+            number_canonicalization: NumberCanonicalization::default(),
             nested_opaque: true,
             max_dict_entries: 4096,
+            dictionary_policy: DictionaryPolicy::default(),
+            field_hints: BTreeMap::new(),
+            field_stats: false,
+            quantile_sketches: false,
             limits: Limits::default(),
         };
 
@@ -314,6 +342,8 @@ fn test_block_header_cross_platform() {
                 segment_uncompressed_len: 1000,
                 segment_compressed_len: 500,
                 segment_offset: 0,
+                stats: None,
+                quantiles: None,
             }],
         },
         // Multiple fields block
@@ -332,6 +362,8 @@ fn test_block_header_cross_platform() {
                     segment_uncompressed_len: 5000,
                     segment_compressed_len: 2500,
                     segment_offset: 0,
+                    stats: None,
+                    quantiles: None,
                 },
                 FieldDirectoryEntry {
                     field_name: "name".to_string(),
@@ -345,6 +377,8 @@ fn test_block_header_cross_platform() {
                     segment_uncompressed_len: 10000,
                     segment_compressed_len: 5000,
                     segment_offset: 2500,
+                    stats: None,
+                    quantiles: None,
                 },
             ],
         },
@@ -443,9 +477,16 @@ fn test_spec_conformance_cross_platform() {
         block_target_records: 10,
         default_codec: Codec::Zstd(15),
         canonicalize_keys: true,
+        preserve_key_order: false,
+        hot_fields: Vec::new(),
         canonicalize_numbers: true,
+        number_canonicalization: NumberCanonicalization::default(),
         nested_opaque: true,
         max_dict_entries: 4096,
+        dictionary_policy: DictionaryPolicy::default(),
+        field_hints: BTreeMap::new(),
+        field_stats: false,
+        quantile_sketches: false,
         limits: Limits::default(),
     };
 
@@ -468,6 +509,7 @@ fn test_spec_conformance_cross_platform() {
     let decompress_opts = DecompressOpts {
         limits: Limits::default(),
         verify_checksums: true,
+        ..DecompressOpts::default()
     };
 
     let block_bytes = {
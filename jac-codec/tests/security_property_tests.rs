@@ -153,6 +153,7 @@ proptest! {
         let opts = DecompressOpts {
             limits: Limits::default(),
             verify_checksums: false,
+            ..DecompressOpts::default()
         };
 
         let _decoder = BlockDecoder::new(&data, &opts);
@@ -0,0 +1,79 @@
+use bitvec::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use jac_format::bitpack::expand_bits;
+use jac_format::varint::{decode_uleb128, decode_uleb128_batch, encode_uleb128};
+
+const WIDE_BLOCK: usize = 100_000;
+
+fn bench_presence_expansion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("presence_expansion");
+    let bytes: Vec<u8> = (0..WIDE_BLOCK / 8).map(|i| (i * 37) as u8).collect();
+
+    group.bench_function("bit_by_bit", |b| {
+        b.iter(|| {
+            let bits = BitVec::<u8, Lsb0>::from_slice(black_box(&bytes));
+            let expanded: Vec<bool> = bits.iter().take(WIDE_BLOCK).map(|bit| *bit).collect();
+            black_box(expanded)
+        });
+    });
+    group.bench_function("lookup_table", |b| {
+        b.iter(|| black_box(expand_bits(black_box(&bytes), WIDE_BLOCK)));
+    });
+
+    group.finish();
+}
+
+fn bench_uleb128_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("uleb128_decode");
+
+    // Sequential-id deltas and dictionary indices are mostly single bytes;
+    // the mixed set has one multi-byte value in every sixteen.
+    let cases = [
+        (
+            "small",
+            (0..WIDE_BLOCK as u64).map(|i| i % 100).collect::<Vec<_>>(),
+        ),
+        (
+            "mixed",
+            (0..WIDE_BLOCK as u64)
+                .map(|i| if i % 16 == 0 { i * 1_000 } else { i % 100 })
+                .collect(),
+        ),
+    ];
+
+    // Both decoders refill one output buffer, so the timings measure decoding
+    // rather than allocating and faulting in 800 KB per iteration.
+    let mut out = Vec::with_capacity(WIDE_BLOCK);
+    for (name, values) in cases {
+        let bytes: Vec<u8> = values.iter().flat_map(|&v| encode_uleb128(v)).collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("one_at_a_time", name),
+            &bytes,
+            |b, bytes| {
+                b.iter(|| {
+                    out.clear();
+                    let mut cursor = 0;
+                    for _ in 0..WIDE_BLOCK {
+                        let (value, consumed) = decode_uleb128(&bytes[cursor..]).unwrap();
+                        out.push(value);
+                        cursor += consumed;
+                    }
+                    black_box(&out);
+                });
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("batch", name), &bytes, |b, bytes| {
+            b.iter(|| {
+                out.clear();
+                decode_uleb128_batch(black_box(bytes), WIDE_BLOCK, &mut out).unwrap();
+                black_box(&out);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_presence_expansion, bench_uleb128_decode);
+criterion_main!(benches);
@@ -23,6 +23,11 @@ impl PresenceBitmap {
         }
     }
 
+    /// Mark every record absent, keeping the allocation
+    pub fn clear(&mut self) {
+        self.bits.fill(false);
+    }
+
     /// Check if a record is present
     pub fn is_present(&self, record_idx: usize) -> bool {
         self.bits.get(record_idx).map(|b| *b).unwrap_or(false)
@@ -55,6 +60,35 @@ impl PresenceBitmap {
     }
 }
 
+/// Bits of every byte value, least significant first.
+const BYTE_BITS: [[bool; 8]; 256] = {
+    let mut table = [[false; 8]; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut bit = 0;
+        while bit < 8 {
+            table[byte][bit] = (byte >> bit) & 1 == 1;
+            bit += 1;
+        }
+        byte += 1;
+    }
+    table
+};
+
+/// Expand the first `count` bits of an LSB-first bitmap into booleans.
+///
+/// Each byte is copied from a 256-entry lookup table, eight bits at a time,
+/// instead of testing bits one by one. Bits past the end of `bytes` read as
+/// `false`.
+pub fn expand_bits(bytes: &[u8], count: usize) -> Vec<bool> {
+    let mut bits = Vec::with_capacity(count + 8);
+    for &byte in bytes.iter().take(count.div_ceil(8)) {
+        bits.extend_from_slice(&BYTE_BITS[usize::from(byte)]);
+    }
+    bits.resize(count, false);
+    bits
+}
+
 /// 3-bit type tag packer
 #[derive(Debug, Clone)]
 pub struct TagPacker {
@@ -195,6 +229,27 @@ mod tests {
         assert_eq!(bytes.len(), 2);
     }
 
+    #[test]
+    fn test_expand_bits_matches_bitmap() {
+        let mut bitmap = PresenceBitmap::new(21);
+        for i in [0, 3, 8, 15, 16, 20] {
+            bitmap.set_present(i, true);
+        }
+        let bytes = bitmap.to_bytes();
+        let bits = expand_bits(&bytes, 21);
+        assert_eq!(bits.len(), 21);
+        for (i, bit) in bits.iter().enumerate() {
+            assert_eq!(*bit, bitmap.is_present(i), "bit {}", i);
+        }
+
+        assert_eq!(expand_bits(&[0xff], 3), vec![true; 3]);
+        assert_eq!(
+            expand_bits(&[0x01], 10),
+            [&[true][..], &[false; 9]].concat()
+        );
+        assert!(expand_bits(&[], 0).is_empty());
+    }
+
     #[test]
     fn test_tag_packer_basic() {
         let mut packer = TagPacker::new();
@@ -1,8 +1,13 @@
 //! Block header and directory structures
 
-use crate::constants::BLOCK_MAGIC;
+use crate::checksum::{compute_crc32c, verify_crc32c};
+use crate::constants::{BLOCK_MAGIC, ENCODING_FLAG_FIELD_STATS, ENCODING_FLAG_QUANTILES};
+use crate::error::JacError;
+use crate::header::FileHeader;
 use crate::limits::Limits;
+use crate::sketch::{DistinctSketch, QuantileSketch};
 use crate::varint::{decode_uleb128, encode_uleb128};
+use std::collections::HashMap;
 use std::convert::TryFrom;
 
 /// Block header
@@ -39,11 +44,239 @@ pub struct FieldDirectoryEntry {
     pub segment_compressed_len: usize,
     /// Segment offset from block start
     pub segment_offset: usize,
+    /// Optional statistics; encoded after the segment offset, with
+    /// [`ENCODING_FLAG_FIELD_STATS`] set, when present
+    pub stats: Option<FieldStats>,
+    /// Optional quantile sketch of the numeric values; encoded after the
+    /// stats, with [`ENCODING_FLAG_QUANTILES`] set, when present
+    pub quantiles: Option<QuantileSketch>,
+}
+
+impl FieldDirectoryEntry {
+    /// Whether this entry is the per-record key-order sidecar rather than a user field
+    pub fn is_key_order_sidecar(&self) -> bool {
+        self.encoding_flags & crate::constants::ENCODING_FLAG_KEY_ORDER != 0
+    }
+}
+
+/// Statistics of one field in one block, stored in its directory entry so
+/// they are read without decompressing the segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldStats {
+    /// Present values that are null
+    pub null_count: usize,
+    /// Sketch of the distinct non-null values
+    pub distinct: DistinctSketch,
+}
+
+/// File-level field-name table for files written with
+/// [`FLAG_INTERNED_FIELD_NAMES`](crate::constants::FLAG_INTERNED_FIELD_NAMES).
+///
+/// IDs are assigned in order of first appearance. Each block header defines
+/// the names it introduces, starting at an explicit ID, so the table is rebuilt
+/// by decoding block headers in file order and re-decoding a block is harmless.
+#[derive(Debug, Clone, Default)]
+pub struct FieldNameTable {
+    names: Vec<String>,
+    ids: HashMap<String, u64>,
+}
+
+impl FieldNameTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Table holding `names` under IDs 0, 1, …; a repeated name is
+    /// `CorruptBlock`
+    pub fn from_names(names: Vec<String>) -> Result<Self, JacError> {
+        let mut table = Self::new();
+        table.define(0, names)?;
+        Ok(table)
+    }
+
+    /// Names in ID order
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Number of names defined so far
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether no name has been defined
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Name assigned to `id`
+    pub fn name(&self, id: u64) -> Option<&str> {
+        usize::try_from(id)
+            .ok()
+            .and_then(|idx| self.names.get(idx))
+            .map(String::as_str)
+    }
+
+    /// ID assigned to `name`
+    pub fn id(&self, name: &str) -> Option<u64> {
+        self.ids.get(name).copied()
+    }
+
+    fn intern(&mut self, name: &str) -> u64 {
+        if let Some(id) = self.id(name) {
+            return id;
+        }
+        let id = self.names.len() as u64;
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+
+    /// Check a block's definitions against a table that already holds them.
+    fn check(&self, first_id: u64, names: &[String]) -> Result<(), JacError> {
+        let known = usize::try_from(first_id)
+            .ok()
+            .and_then(|first| Some(first..first.checked_add(names.len())?))
+            .and_then(|ids| self.names.get(ids));
+        match known {
+            Some(known) if known == names => Ok(()),
+            _ => Err(JacError::CorruptBlock),
+        }
+    }
+
+    /// Apply a block's definitions: `names` take IDs from `first_id` on.
+    ///
+    /// Definitions already known must match; a `first_id` past the end of the
+    /// table means an earlier block's definitions are missing.
+    fn define(&mut self, first_id: u64, names: Vec<String>) -> Result<(), JacError> {
+        let first = usize::try_from(first_id).map_err(|_| JacError::CorruptBlock)?;
+        if first > self.names.len() {
+            return Err(JacError::CorruptBlock);
+        }
+        for (offset, name) in names.into_iter().enumerate() {
+            let idx = first + offset;
+            match self.names.get(idx) {
+                Some(existing) if *existing == name => {}
+                Some(_) => return Err(JacError::CorruptBlock),
+                None => {
+                    if self.ids.contains_key(&name) {
+                        return Err(JacError::CorruptBlock);
+                    }
+                    self.ids.insert(name.clone(), idx as u64);
+                    self.names.push(name);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Field-name table a block header is decoded against
+enum NameTable<'a> {
+    /// The header's definitions are added to the table
+    Growing(&'a mut FieldNameTable),
+    /// The table already holds every definition, which are only checked
+    Complete(&'a FieldNameTable),
+}
+
+impl NameTable<'_> {
+    fn table(&self) -> &FieldNameTable {
+        match self {
+            NameTable::Growing(table) => table,
+            NameTable::Complete(table) => table,
+        }
+    }
+}
+
+/// How the block headers of one file are encoded, as selected by its file
+/// header flags.
+///
+/// An interned format carries the file's field-name table, so one value is
+/// threaded through the blocks of a file in order.
+#[derive(Debug, Clone, Default)]
+pub struct BlockHeaderFormat {
+    /// Field-name table, for files with
+    /// [`FLAG_INTERNED_FIELD_NAMES`](crate::constants::FLAG_INTERNED_FIELD_NAMES)
+    pub field_names: Option<FieldNameTable>,
+    /// Headers end with a CRC32C of their own bytes
+    /// ([`FLAG_BLOCK_HEADER_CRC`](crate::constants::FLAG_BLOCK_HEADER_CRC))
+    pub header_crc: bool,
+}
+
+impl BlockHeaderFormat {
+    /// Format of the blocks that follow `header`
+    pub fn for_file(header: &FileHeader) -> Self {
+        Self {
+            field_names: header.interned_field_names().then(FieldNameTable::new),
+            header_crc: header.block_header_crc(),
+        }
+    }
+
+    /// Whether headers are encoded exactly as [`BlockHeader::encode`] does
+    pub fn is_plain(&self) -> bool {
+        self.field_names.is_none() && !self.header_crc
+    }
+}
+
+/// Read a ULEB128 length-prefixed UTF-8 field name at `*pos`.
+fn decode_field_name(
+    bytes: &[u8],
+    pos: &mut usize,
+    end: usize,
+    limits: &Limits,
+) -> Result<String, JacError> {
+    let (name_len_u64, name_len_bytes) = decode_uleb128(&bytes[*pos..end])?;
+    *pos += name_len_bytes;
+    let name_len = usize::try_from(name_len_u64).map_err(|_| {
+        JacError::LimitExceeded("Field name length exceeds supported size".to_string())
+    })?;
+
+    // Check field name length limit
+    if name_len > limits.max_string_len_per_value {
+        return Err(JacError::LimitExceeded(format!(
+            "Field name length {} exceeds limit {}",
+            name_len, limits.max_string_len_per_value
+        )));
+    }
+
+    // Check if we have enough bytes for the field name
+    if *pos + name_len > end {
+        return Err(JacError::UnexpectedEof);
+    }
+
+    // Field name (UTF-8)
+    let field_name = String::from_utf8(bytes[*pos..*pos + name_len].to_vec())
+        .map_err(|_| JacError::CorruptBlock)?;
+    *pos += name_len;
+    Ok(field_name)
 }
 
 impl BlockHeader {
     /// Encode block header to bytes
     pub fn encode(&self) -> Result<Vec<u8>, crate::error::JacError> {
+        self.encode_inner(None, false)
+    }
+
+    /// Encode block header in a file's block header format
+    pub fn encode_with_format(&self, format: &mut BlockHeaderFormat) -> Result<Vec<u8>, JacError> {
+        self.encode_inner(format.field_names.as_mut(), format.header_crc)
+    }
+
+    /// Encode block header for a file flagged with
+    /// [`FLAG_INTERNED_FIELD_NAMES`](crate::constants::FLAG_INTERNED_FIELD_NAMES).
+    ///
+    /// Names missing from `names` are added to it and defined by this header;
+    /// directory entries then refer to every field by its table ID.
+    pub fn encode_interned(&self, names: &mut FieldNameTable) -> Result<Vec<u8>, JacError> {
+        self.encode_inner(Some(names), false)
+    }
+
+    fn encode_inner(
+        &self,
+        names: Option<&mut FieldNameTable>,
+        header_crc: bool,
+    ) -> Result<Vec<u8>, JacError> {
         let mut header_body = Vec::new();
 
         // Record count (ULEB128)
@@ -52,32 +285,135 @@ impl BlockHeader {
         // Field count (ULEB128)
         header_body.extend_from_slice(&encode_uleb128(self.fields.len() as u64));
 
+        // Field-name definitions: first ID, count, then the new names
+        let ids: Option<Vec<u64>> = names.map(|table| {
+            let first_id = table.len();
+            let ids = self
+                .fields
+                .iter()
+                .map(|field| table.intern(&field.field_name))
+                .collect();
+            header_body.extend_from_slice(&encode_uleb128(first_id as u64));
+            header_body.extend_from_slice(&encode_uleb128((table.len() - first_id) as u64));
+            for name in &table.names[first_id..] {
+                header_body.extend_from_slice(&encode_uleb128(name.len() as u64));
+                header_body.extend_from_slice(name.as_bytes());
+            }
+            ids
+        });
+
         // Field directory entries
-        for field in &self.fields {
-            header_body.extend_from_slice(&encode_uleb128(field.field_name.len() as u64));
-            header_body.extend_from_slice(field.field_name.as_bytes());
+        for (idx, field) in self.fields.iter().enumerate() {
+            match &ids {
+                Some(ids) => header_body.extend_from_slice(&encode_uleb128(ids[idx])),
+                None => {
+                    header_body.extend_from_slice(&encode_uleb128(field.field_name.len() as u64));
+                    header_body.extend_from_slice(field.field_name.as_bytes());
+                }
+            }
+            // The stats and quantile flags always reflect whether those follow the entry
+            let mut encoding_flags =
+                field.encoding_flags & !(ENCODING_FLAG_FIELD_STATS | ENCODING_FLAG_QUANTILES);
+            if field.stats.is_some() {
+                encoding_flags |= ENCODING_FLAG_FIELD_STATS;
+            }
+            if field.quantiles.is_some() {
+                encoding_flags |= ENCODING_FLAG_QUANTILES;
+            }
             header_body.push(field.compressor);
             header_body.push(field.compression_level);
             header_body.extend_from_slice(&encode_uleb128(field.presence_bytes as u64));
             header_body.extend_from_slice(&encode_uleb128(field.tag_bytes as u64));
             header_body.extend_from_slice(&encode_uleb128(field.value_count_present as u64));
-            header_body.extend_from_slice(&encode_uleb128(field.encoding_flags));
+            header_body.extend_from_slice(&encode_uleb128(encoding_flags));
             header_body.extend_from_slice(&encode_uleb128(field.dict_entry_count as u64));
             header_body.extend_from_slice(&encode_uleb128(field.segment_uncompressed_len as u64));
             header_body.extend_from_slice(&encode_uleb128(field.segment_compressed_len as u64));
             header_body.extend_from_slice(&encode_uleb128(field.segment_offset as u64));
+            if let Some(stats) = &field.stats {
+                let sketch = stats.distinct.encode();
+                header_body.extend_from_slice(&encode_uleb128(stats.null_count as u64));
+                header_body.extend_from_slice(&encode_uleb128(sketch.len() as u64));
+                header_body.extend_from_slice(&sketch);
+            }
+            if let Some(quantiles) = &field.quantiles {
+                let sketch = quantiles.encode();
+                header_body.extend_from_slice(&encode_uleb128(sketch.len() as u64));
+                header_body.extend_from_slice(&sketch);
+            }
         }
 
-        let mut result = Vec::with_capacity(4 + 10 + header_body.len());
+        // The header CRC trails the body and is counted in header_len
+        let crc_len = if header_crc { 4 } else { 0 };
+        let mut result = Vec::with_capacity(4 + 10 + header_body.len() + crc_len);
         result.extend_from_slice(&BLOCK_MAGIC.to_le_bytes());
-        result.extend_from_slice(&encode_uleb128(header_body.len() as u64));
+        result.extend_from_slice(&encode_uleb128((header_body.len() + crc_len) as u64));
         result.extend_from_slice(&header_body);
+        if header_crc {
+            let crc = compute_crc32c(&result);
+            result.extend_from_slice(&crc.to_le_bytes());
+        }
 
         Ok(result)
     }
 
     /// Decode block header from bytes
     pub fn decode(bytes: &[u8], limits: &Limits) -> Result<(Self, usize), crate::error::JacError> {
+        Self::decode_inner(bytes, limits, None, false)
+    }
+
+    /// Decode block header in a file's block header format.
+    ///
+    /// With a header CRC, the header bytes are verified before any directory
+    /// value is interpreted.
+    pub fn decode_with_format(
+        bytes: &[u8],
+        limits: &Limits,
+        format: &mut BlockHeaderFormat,
+    ) -> Result<(Self, usize), JacError> {
+        Self::decode_inner(
+            bytes,
+            limits,
+            format.field_names.as_mut().map(NameTable::Growing),
+            format.header_crc,
+        )
+    }
+
+    /// Decode block header in a file's block header format whose field-name
+    /// table already holds every name of the block's archive, so one table
+    /// can be shared by concurrent decodes. A definition missing from the
+    /// table is `CorruptBlock`.
+    pub fn decode_with_complete_format(
+        bytes: &[u8],
+        limits: &Limits,
+        format: &BlockHeaderFormat,
+    ) -> Result<(Self, usize), JacError> {
+        Self::decode_inner(
+            bytes,
+            limits,
+            format.field_names.as_ref().map(NameTable::Complete),
+            format.header_crc,
+        )
+    }
+
+    /// Decode a block header written by [`BlockHeader::encode_interned`].
+    ///
+    /// The header's definitions are added to `names` (which must already hold
+    /// those of every earlier block) and field IDs are resolved against it.
+    pub fn decode_interned(
+        bytes: &[u8],
+        limits: &Limits,
+        names: &mut FieldNameTable,
+    ) -> Result<(Self, usize), JacError> {
+        Self::decode_inner(bytes, limits, Some(NameTable::Growing(names)), false)
+    }
+
+    fn decode_inner(
+        bytes: &[u8],
+        limits: &Limits,
+        mut names: Option<NameTable<'_>>,
+        header_crc: bool,
+    ) -> Result<(Self, usize), JacError> {
         let mut pos = 0;
 
         // Check minimum length for magic
@@ -101,13 +437,26 @@ impl BlockHeader {
         pos += header_len_bytes;
 
         let header_body_start = pos;
-        let header_body_end = header_body_start
+        let header_end = header_body_start
             .checked_add(header_len)
             .ok_or_else(|| crate::error::JacError::CorruptBlock)?;
-        if header_body_end > bytes.len() {
+        if header_end > bytes.len() {
             return Err(crate::error::JacError::UnexpectedEof);
         }
 
+        // Header CRC32C (covers magic, header_len and body)
+        let header_body_end = if header_crc {
+            let crc_start = header_end
+                .checked_sub(4)
+                .filter(|&start| start >= header_body_start)
+                .ok_or(JacError::CorruptBlock)?;
+            let stored = u32::from_le_bytes(bytes[crc_start..header_end].try_into().unwrap());
+            verify_crc32c(&bytes[..crc_start], stored)?;
+            crc_start
+        } else {
+            header_end
+        };
+
         // Record count (ULEB128)
         let (record_count_u64, count_bytes) = decode_uleb128(&bytes[pos..header_body_end])?;
         pos += count_bytes;
@@ -138,6 +487,27 @@ impl BlockHeader {
             )));
         }
 
+        // Field-name definitions (interned files only)
+        if let Some(table) = names.as_mut() {
+            let (first_id, first_id_bytes) = decode_uleb128(&bytes[pos..header_body_end])?;
+            pos += first_id_bytes;
+            let (defined_u64, defined_bytes) = decode_uleb128(&bytes[pos..header_body_end])?;
+            pos += defined_bytes;
+            // A block only defines names its own directory uses.
+            let defined = usize::try_from(defined_u64)
+                .ok()
+                .filter(|&n| n <= field_count)
+                .ok_or(JacError::CorruptBlock)?;
+            let mut new_names = Vec::with_capacity(defined);
+            for _ in 0..defined {
+                new_names.push(decode_field_name(bytes, &mut pos, header_body_end, limits)?);
+            }
+            match table {
+                NameTable::Growing(table) => table.define(first_id, new_names)?,
+                NameTable::Complete(table) => table.check(first_id, &new_names)?,
+            }
+        }
+
         // Decode field directory entries
         let mut fields = Vec::new();
         for _ in 0..field_count {
@@ -145,32 +515,14 @@ impl BlockHeader {
                 return Err(crate::error::JacError::UnexpectedEof);
             }
 
-            // Field name length (ULEB128)
-            let (name_len_u64, name_len_bytes) = decode_uleb128(&bytes[pos..header_body_end])?;
-            pos += name_len_bytes;
-            let name_len = usize::try_from(name_len_u64).map_err(|_| {
-                crate::error::JacError::LimitExceeded(
-                    "Field name length exceeds supported size".to_string(),
-                )
-            })?;
-
-            // Check field name length limit
-            if name_len > limits.max_string_len_per_value {
-                return Err(crate::error::JacError::LimitExceeded(format!(
-                    "Field name length {} exceeds limit {}",
-                    name_len, limits.max_string_len_per_value
-                )));
-            }
-
-            // Check if we have enough bytes for the field name
-            if pos + name_len > header_body_end {
-                return Err(crate::error::JacError::UnexpectedEof);
-            }
-
-            // Field name (UTF-8)
-            let field_name = String::from_utf8(bytes[pos..pos + name_len].to_vec())
-                .map_err(|_| crate::error::JacError::CorruptBlock)?;
-            pos += name_len;
+            let field_name = match names.as_ref().map(NameTable::table) {
+                Some(table) => {
+                    let (id, id_bytes) = decode_uleb128(&bytes[pos..header_body_end])?;
+                    pos += id_bytes;
+                    table.name(id).ok_or(JacError::CorruptBlock)?.to_string()
+                }
+                None => decode_field_name(bytes, &mut pos, header_body_end, limits)?,
+            };
 
             // Check remaining length for fixed fields
             if pos + 1 + 1 > bytes.len() {
@@ -293,6 +645,50 @@ impl BlockHeader {
                 )
             })?;
 
+            // Field statistics (null count, then a length-prefixed sketch)
+            let stats = if encoding_flags & ENCODING_FLAG_FIELD_STATS != 0 {
+                let (null_count, null_count_len) = decode_uleb128(&bytes[pos..header_body_end])?;
+                pos += null_count_len;
+                let null_count = usize::try_from(null_count)
+                    .ok()
+                    .filter(|&nulls| nulls <= value_count_present)
+                    .ok_or(JacError::CorruptBlock)?;
+                let (sketch_len, sketch_len_len) = decode_uleb128(&bytes[pos..header_body_end])?;
+                pos += sketch_len_len;
+                let sketch_end = usize::try_from(sketch_len)
+                    .ok()
+                    .and_then(|len| pos.checked_add(len))
+                    .filter(|&end| end <= header_body_end)
+                    .ok_or(JacError::UnexpectedEof)?;
+                let distinct = DistinctSketch::decode(&bytes[pos..sketch_end])?;
+                pos = sketch_end;
+                Some(FieldStats {
+                    null_count,
+                    distinct,
+                })
+            } else {
+                None
+            };
+
+            // Quantile sketch (length-prefixed)
+            let quantiles = if encoding_flags & ENCODING_FLAG_QUANTILES != 0 {
+                let (sketch_len, sketch_len_len) = decode_uleb128(&bytes[pos..header_body_end])?;
+                pos += sketch_len_len;
+                let sketch_end = usize::try_from(sketch_len)
+                    .ok()
+                    .and_then(|len| pos.checked_add(len))
+                    .filter(|&end| end <= header_body_end)
+                    .ok_or(JacError::UnexpectedEof)?;
+                let quantiles = QuantileSketch::decode(&bytes[pos..sketch_end])?;
+                if quantiles.count() > value_count_present as u64 {
+                    return Err(JacError::CorruptBlock);
+                }
+                pos = sketch_end;
+                Some(quantiles)
+            } else {
+                None
+            };
+
             fields.push(FieldDirectoryEntry {
                 field_name,
                 compressor,
@@ -305,6 +701,8 @@ impl BlockHeader {
                 segment_uncompressed_len,
                 segment_compressed_len,
                 segment_offset,
+                stats,
+                quantiles,
             });
         }
 
@@ -317,7 +715,7 @@ impl BlockHeader {
                 record_count,
                 fields,
             },
-            pos,
+            header_end,
         ))
     }
 }
@@ -327,6 +725,7 @@ mod tests {
     use super::*;
     use crate::constants::*;
     use crate::error::JacError;
+    use crate::types::TypeTag;
     use crate::varint::{decode_uleb128, encode_uleb128};
 
     fn create_test_limits() -> Limits {
@@ -356,6 +755,8 @@ mod tests {
             segment_uncompressed_len: 1000,
             segment_compressed_len: 500,
             segment_offset: 2000,
+            stats: None,
+            quantiles: None,
         }
     }
 
@@ -390,6 +791,8 @@ mod tests {
                 segment_uncompressed_len: 500,
                 segment_compressed_len: 250,
                 segment_offset: 1000,
+                stats: None,
+                quantiles: None,
             },
             FieldDirectoryEntry {
                 field_name: "field2".to_string(),
@@ -403,6 +806,8 @@ mod tests {
                 segment_uncompressed_len: 500,
                 segment_compressed_len: 500,
                 segment_offset: 1500,
+                stats: None,
+                quantiles: None,
             },
         ];
 
@@ -519,6 +924,8 @@ mod tests {
                 segment_uncompressed_len: 100,
                 segment_compressed_len: 50,
                 segment_offset: i * 100,
+                stats: None,
+                quantiles: None,
             });
         }
 
@@ -552,6 +959,8 @@ mod tests {
             segment_uncompressed_len: 100,
             segment_compressed_len: 50,
             segment_offset: 0,
+            stats: None,
+            quantiles: None,
         };
 
         let header = BlockHeader {
@@ -586,6 +995,8 @@ mod tests {
             segment_uncompressed_len: 100,
             segment_compressed_len: 50,
             segment_offset: 0,
+            stats: None,
+            quantiles: None,
         };
 
         let header = BlockHeader {
@@ -617,6 +1028,8 @@ mod tests {
             segment_uncompressed_len: 100,
             segment_compressed_len: 50,
             segment_offset: 0,
+            stats: None,
+            quantiles: None,
         };
 
         let header = BlockHeader {
@@ -643,6 +1056,8 @@ mod tests {
             segment_uncompressed_len: 100,
             segment_compressed_len: 50,
             segment_offset: 0,
+            stats: None,
+            quantiles: None,
         };
 
         let header = BlockHeader {
@@ -687,6 +1102,8 @@ mod tests {
             segment_uncompressed_len: 100,
             segment_compressed_len: 50,
             segment_offset: 0,
+            stats: None,
+            quantiles: None,
         };
 
         let header = BlockHeader {
@@ -718,6 +1135,8 @@ mod tests {
             segment_uncompressed_len: 100 * 1024 * 1024, // Exceeds limit
             segment_compressed_len: 50 * 1024 * 1024,
             segment_offset: 0,
+            stats: None,
+            quantiles: None,
         };
 
         let header = BlockHeader {
@@ -764,6 +1183,8 @@ mod tests {
             segment_uncompressed_len: 100,
             segment_compressed_len: 50,
             segment_offset: 0,
+            stats: None,
+            quantiles: None,
         };
 
         let header = BlockHeader {
@@ -777,6 +1198,64 @@ mod tests {
         assert_eq!(header.fields[0].field_name, decoded.fields[0].field_name);
     }
 
+    #[test]
+    fn test_block_header_field_stats_roundtrip() {
+        let mut distinct = DistinctSketch::new(DistinctSketch::MIN_PRECISION).unwrap();
+        distinct.insert(TypeTag::String, b"a");
+        let stats = FieldStats {
+            null_count: 3,
+            distinct,
+        };
+        let mut values: Vec<f64> = (0..40).map(f64::from).collect();
+        let quantiles = QuantileSketch::from_values(&mut values, 4).unwrap();
+        let header = BlockHeader {
+            record_count: 100,
+            fields: vec![
+                FieldDirectoryEntry {
+                    stats: Some(stats.clone()),
+                    quantiles: quantiles.clone(),
+                    ..create_test_field_entry()
+                },
+                FieldDirectoryEntry {
+                    field_name: "plain".to_string(),
+                    // A stale flag without stats is not written
+                    encoding_flags: ENCODING_FLAG_FIELD_STATS,
+                    ..create_test_field_entry()
+                },
+            ],
+        };
+        let encoded = header.encode().unwrap();
+        let (decoded, consumed) = BlockHeader::decode(&encoded, &create_test_limits()).unwrap();
+        assert_eq!(consumed, encoded.len());
+        assert_eq!(decoded.fields[0].stats, Some(stats));
+        assert_eq!(decoded.fields[0].quantiles, quantiles);
+        assert_eq!(
+            decoded.fields[0].encoding_flags,
+            ENCODING_FLAG_DICTIONARY | ENCODING_FLAG_FIELD_STATS | ENCODING_FLAG_QUANTILES
+        );
+        assert_eq!(decoded.fields[1].stats, None);
+        assert_eq!(decoded.fields[1].encoding_flags, 0);
+
+        // More nulls than present values is corrupt
+        let mut corrupt = header.clone();
+        corrupt.fields[0].stats.as_mut().unwrap().null_count = 81;
+        let encoded = corrupt.encode().unwrap();
+        assert!(matches!(
+            BlockHeader::decode(&encoded, &create_test_limits()),
+            Err(JacError::CorruptBlock)
+        ));
+
+        // As is a quantile sketch of more values than are present
+        let mut values: Vec<f64> = (0..81).map(f64::from).collect();
+        let mut corrupt = header.clone();
+        corrupt.fields[0].quantiles = QuantileSketch::from_values(&mut values, 4).unwrap();
+        let encoded = corrupt.encode().unwrap();
+        assert!(matches!(
+            BlockHeader::decode(&encoded, &create_test_limits()),
+            Err(JacError::CorruptBlock)
+        ));
+    }
+
     #[test]
     fn test_block_header_large_values() {
         let field = FieldDirectoryEntry {
@@ -786,11 +1265,14 @@ mod tests {
             presence_bytes: 1_000_000,
             tag_bytes: 500_000,
             value_count_present: 80_000,
-            encoding_flags: u64::MAX,
+            // Every flag but field stats and quantiles, set only with those to encode
+            encoding_flags: u64::MAX & !(ENCODING_FLAG_FIELD_STATS | ENCODING_FLAG_QUANTILES),
             dict_entry_count: 4_000,
             segment_uncompressed_len: 50 * 1024 * 1024,
             segment_compressed_len: 25 * 1024 * 1024,
             segment_offset: u64::MAX as usize,
+            stats: None,
+            quantiles: None,
         };
 
         let header = BlockHeader {
@@ -840,4 +1322,163 @@ mod tests {
         );
         assert_eq!(bytes_consumed, encoded.len());
     }
+
+    fn header_with_fields(names: &[&str]) -> BlockHeader {
+        BlockHeader {
+            record_count: 1000,
+            fields: names
+                .iter()
+                .map(|name| FieldDirectoryEntry {
+                    field_name: name.to_string(),
+                    ..create_test_field_entry()
+                })
+                .collect(),
+        }
+    }
+
+    fn field_names(header: &BlockHeader) -> Vec<&str> {
+        header
+            .fields
+            .iter()
+            .map(|field| field.field_name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_block_header_interned_names_shared_across_blocks() {
+        let limits = create_test_limits();
+        let first = header_with_fields(&["user_id", "event", "ts"]);
+        let second = header_with_fields(&["ts", "user_id", "region"]);
+
+        let mut written = FieldNameTable::new();
+        let first_bytes = first.encode_interned(&mut written).unwrap();
+        let second_bytes = second.encode_interned(&mut written).unwrap();
+        assert_eq!(written.len(), 4);
+        assert_eq!(written.id("region"), Some(3));
+        // The second block only spells out the one name it introduces.
+        assert!(second_bytes.len() < second.encode().unwrap().len());
+
+        let mut read = FieldNameTable::new();
+        let (decoded, consumed) =
+            BlockHeader::decode_interned(&first_bytes, &limits, &mut read).unwrap();
+        assert_eq!(consumed, first_bytes.len());
+        assert_eq!(field_names(&decoded), ["user_id", "event", "ts"]);
+        let (decoded, _) = BlockHeader::decode_interned(&second_bytes, &limits, &mut read).unwrap();
+        assert_eq!(field_names(&decoded), ["ts", "user_id", "region"]);
+        assert_eq!(decoded.fields[2].segment_offset, 2000);
+
+        // Re-decoding an earlier block leaves the table unchanged.
+        BlockHeader::decode_interned(&first_bytes, &limits, &mut read).unwrap();
+        assert_eq!(read.len(), 4);
+        assert_eq!(read.name(3), Some("region"));
+    }
+
+    #[test]
+    fn test_block_header_interned_requires_earlier_definitions() {
+        let limits = create_test_limits();
+        let mut written = FieldNameTable::new();
+        header_with_fields(&["a", "b"])
+            .encode_interned(&mut written)
+            .unwrap();
+        let second_bytes = header_with_fields(&["a", "c"])
+            .encode_interned(&mut written)
+            .unwrap();
+
+        let mut fresh = FieldNameTable::new();
+        assert!(matches!(
+            BlockHeader::decode_interned(&second_bytes, &limits, &mut fresh),
+            Err(JacError::CorruptBlock)
+        ));
+
+        // A table holding a different name at a defined ID is rejected too.
+        let mut conflicting = FieldNameTable::new();
+        header_with_fields(&["a", "b", "x"])
+            .encode_interned(&mut conflicting)
+            .unwrap();
+        assert!(matches!(
+            BlockHeader::decode_interned(&second_bytes, &limits, &mut conflicting),
+            Err(JacError::CorruptBlock)
+        ));
+    }
+
+    #[test]
+    fn test_block_header_complete_format_checks_definitions() {
+        let limits = create_test_limits();
+        let mut written = FieldNameTable::new();
+        let first_bytes = header_with_fields(&["a", "b"])
+            .encode_interned(&mut written)
+            .unwrap();
+        let second_bytes = header_with_fields(&["a", "c"])
+            .encode_interned(&mut written)
+            .unwrap();
+
+        // The complete table resolves a later block first and is not changed.
+        let complete = BlockHeaderFormat {
+            field_names: Some(written.clone()),
+            header_crc: false,
+        };
+        let (decoded, _) =
+            BlockHeader::decode_with_complete_format(&second_bytes, &limits, &complete).unwrap();
+        assert_eq!(field_names(&decoded), ["a", "c"]);
+        assert_eq!(complete.field_names.as_ref().unwrap().len(), 3);
+
+        // A table lacking a block's definitions is rejected.
+        let mut partial = FieldNameTable::new();
+        BlockHeader::decode_interned(&first_bytes, &limits, &mut partial).unwrap();
+        let partial = BlockHeaderFormat {
+            field_names: Some(partial),
+            header_crc: false,
+        };
+        assert!(matches!(
+            BlockHeader::decode_with_complete_format(&second_bytes, &limits, &partial),
+            Err(JacError::CorruptBlock)
+        ));
+    }
+
+    #[test]
+    fn test_block_header_crc_detected_before_directory_is_read() {
+        let limits = create_test_limits();
+        let header = header_with_fields(&["user_id", "event"]);
+        let mut format = BlockHeaderFormat {
+            header_crc: true,
+            ..BlockHeaderFormat::default()
+        };
+        let encoded = header.encode_with_format(&mut format).unwrap();
+        assert_eq!(encoded.len(), header.encode().unwrap().len() + 4);
+
+        let (decoded, consumed) =
+            BlockHeader::decode_with_format(&encoded, &limits, &mut format).unwrap();
+        assert_eq!(consumed, encoded.len());
+        assert_eq!(field_names(&decoded), ["user_id", "event"]);
+
+        // A flipped record count would otherwise decode (or trip a limit).
+        let mut corrupted = encoded.clone();
+        corrupted[6] ^= 0x40;
+        assert!(matches!(
+            BlockHeader::decode_with_format(&corrupted, &limits, &mut format),
+            Err(JacError::ChecksumMismatch)
+        ));
+        // A torn header that still has room for its CRC is caught as well.
+        let mut torn = encoded.clone();
+        let len = torn.len();
+        torn[len - 6..].fill(0);
+        assert!(matches!(
+            BlockHeader::decode_with_format(&torn, &limits, &mut format),
+            Err(JacError::ChecksumMismatch)
+        ));
+
+        // Interning and the header CRC combine.
+        let mut both = BlockHeaderFormat {
+            field_names: Some(FieldNameTable::new()),
+            header_crc: true,
+        };
+        let encoded = header.encode_with_format(&mut both).unwrap();
+        let mut read = BlockHeaderFormat {
+            field_names: Some(FieldNameTable::new()),
+            header_crc: true,
+        };
+        let (decoded, _) = BlockHeader::decode_with_format(&encoded, &limits, &mut read).unwrap();
+        assert_eq!(field_names(&decoded), ["user_id", "event"]);
+        assert!(!read.is_plain() && BlockHeaderFormat::default().is_plain());
+    }
 }
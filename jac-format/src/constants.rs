@@ -9,6 +9,14 @@ pub const BLOCK_MAGIC: u32 = 0x314B4C42; // "BLK1"
 /// Index magic: "IDX1"
 pub const INDEX_MAGIC: u32 = 0x31584449; // "IDX1"
 
+/// Stream directory magic: "STR1". Also the last four bytes of a
+/// multi-stream file.
+pub const STREAMS_MAGIC: u32 = 0x31525453; // "STR1"
+
+/// Length of the trailer ending a multi-stream file: the u64 offset of the
+/// stream directory followed by [`STREAMS_MAGIC`].
+pub const STREAMS_TRAILER_LEN: u64 = 12;
+
 /// Compressor ID for uncompressed segments.
 pub const COMPRESSOR_NONE: u8 = 0;
 /// Compressor ID for Zstandard segments.
@@ -28,6 +36,16 @@ pub const FLAG_NESTED_OPAQUE: u32 = 1 << 2;
 pub const FLAG_CONTAINER_HINT_SHIFT: u32 = 3;
 /// Mask covering the two bits reserved for the container format hint.
 pub const FLAG_CONTAINER_HINT_MASK: u32 = 0b11 << FLAG_CONTAINER_HINT_SHIFT;
+/// Flag indicating block directories name fields by IDs into a file-level
+/// field-name table that each block extends with the names it introduces.
+pub const FLAG_INTERNED_FIELD_NAMES: u32 = 1 << 5;
+/// Flag indicating block headers end with a CRC32C over the header bytes.
+pub const FLAG_BLOCK_HEADER_CRC: u32 = 1 << 6;
+/// Flag indicating every block starts at a multiple of [`BLOCK_ALIGNMENT`]
+/// bytes from the file start, preceded by zero padding.
+pub const FLAG_ALIGNED_BLOCKS: u32 = 1 << 7;
+/// Boundary of block starts in files with [`FLAG_ALIGNED_BLOCKS`] (4 KiB).
+pub const BLOCK_ALIGNMENT: u64 = 4096;
 
 /// Type tag representing a `null` value.
 pub const TAG_NULL: u8 = 0;
@@ -54,3 +72,15 @@ pub const ENCODING_FLAG_DELTA: u64 = 1 << 1;
 pub const ENCODING_FLAG_RLE: u64 = 1 << 2; // reserved
 /// Reserved field segment flag for bit-packed payloads.
 pub const ENCODING_FLAG_BIT_PACKED: u64 = 1 << 3; // reserved
+/// Field segment flag marking the per-record key-order sidecar column.
+pub const ENCODING_FLAG_KEY_ORDER: u64 = 1 << 4;
+/// Field segment flag: the directory entry ends with the field's
+/// [`FieldStats`](crate::block::FieldStats) (null count and distinct sketch).
+pub const ENCODING_FLAG_FIELD_STATS: u64 = 1 << 5;
+/// Field segment flag: the directory entry ends with a
+/// [`QuantileSketch`](crate::sketch::QuantileSketch) of the numeric values.
+pub const ENCODING_FLAG_QUANTILES: u64 = 1 << 6;
+
+/// Directory name of the key-order sidecar column (NUL-prefixed to stay out of
+/// the way of user fields; decoders identify it by `ENCODING_FLAG_KEY_ORDER`).
+pub const KEY_ORDER_FIELD_NAME: &str = "\u{0}jac.key_order";
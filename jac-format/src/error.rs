@@ -38,6 +38,10 @@ pub enum JacError {
     /// Encountered a feature that the implementation does not support.
     #[error("Unsupported feature: {0}")]
     UnsupportedFeature(String),
+    /// The caller passed an invalid argument, such as a malformed expression
+    /// or the name of a stream the file does not hold.
+    #[error("{0}")]
+    InvalidArgument(String),
     /// Encountered an unknown or unsupported compression codec.
     #[error("Unsupported compression codec: {0}")]
     UnsupportedCompression(u8),
@@ -47,6 +51,32 @@ pub enum JacError {
     /// JSON parsing or serialization failed.
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
+    /// An NDJSON input line could not be parsed as a record.
+    #[error("Invalid record at line {line} (byte offset {offset}): {source}")]
+    RecordParse {
+        /// 1-based line number in the input
+        line: u64,
+        /// Byte offset of the start of the line in the input
+        offset: u64,
+        /// Parser error for the line
+        source: serde_json::Error,
+    },
+    /// A document in a binary (MessagePack or BSON) input stream could not be
+    /// decoded as a record.
+    #[error("Invalid {format} document {document} (byte offset {offset}): {reason}")]
+    DocumentParse {
+        /// Input format name
+        format: &'static str,
+        /// 1-based document number in the input
+        document: u64,
+        /// Byte offset of the start of the document in the input
+        offset: u64,
+        /// Decoder error for the document
+        reason: String,
+    },
+    /// The operation was stopped on request (e.g. Ctrl-C) before it finished.
+    #[error("Interrupted")]
+    Interrupted,
     /// Internal invariant was violated.
     #[error("Internal error: {0}")]
     Internal(String),
@@ -54,3 +84,87 @@ pub enum JacError {
 
 /// Result type alias
 pub type Result<T> = std::result::Result<T, JacError>;
+
+/// Broad class of a [`JacError`], for callers that branch on the kind of
+/// failure rather than its message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// Reading or writing the underlying file or stream failed
+    Io,
+    /// Input bytes or records are malformed, corrupt or unsupported
+    Format,
+    /// A configured security limit was exceeded
+    Limits,
+    /// The request itself is invalid (bad arguments, missing features)
+    Usage,
+    /// The operation was interrupted before it finished
+    Interrupted,
+    /// An internal invariant was violated
+    Internal,
+}
+
+impl ErrorCategory {
+    /// Lowercase name of the category.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCategory::Io => "io",
+            ErrorCategory::Format => "format",
+            ErrorCategory::Limits => "limits",
+            ErrorCategory::Usage => "usage",
+            ErrorCategory::Interrupted => "interrupted",
+            ErrorCategory::Internal => "internal",
+        }
+    }
+}
+
+impl JacError {
+    /// Stable snake_case code of the error variant. Codes are never reused or
+    /// renamed, so scripts may match on them across releases.
+    pub fn code(&self) -> &'static str {
+        match self {
+            JacError::InvalidMagic => "invalid_magic",
+            JacError::UnsupportedVersion(_) => "unsupported_version",
+            JacError::CorruptHeader => "corrupt_header",
+            JacError::CorruptBlock => "corrupt_block",
+            JacError::ChecksumMismatch => "checksum_mismatch",
+            JacError::UnexpectedEof => "unexpected_eof",
+            JacError::DecompressError(_) => "decompress_error",
+            JacError::LimitExceeded(_) => "limit_exceeded",
+            JacError::TypeMismatch => "type_mismatch",
+            JacError::DictionaryError => "dictionary_error",
+            JacError::UnsupportedFeature(_) => "unsupported_feature",
+            JacError::InvalidArgument(_) => "invalid_argument",
+            JacError::UnsupportedCompression(_) => "unsupported_compression",
+            JacError::Io(_) => "io",
+            JacError::Json(_) => "json",
+            JacError::RecordParse { .. } => "record_parse",
+            JacError::DocumentParse { .. } => "document_parse",
+            JacError::Interrupted => "interrupted",
+            JacError::Internal(_) => "internal",
+        }
+    }
+
+    /// Category the error belongs to.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            JacError::Io(_) => ErrorCategory::Io,
+            JacError::LimitExceeded(_) => ErrorCategory::Limits,
+            JacError::UnsupportedFeature(_) | JacError::InvalidArgument(_) => ErrorCategory::Usage,
+            JacError::Interrupted => ErrorCategory::Interrupted,
+            JacError::Internal(_) => ErrorCategory::Internal,
+            JacError::InvalidMagic
+            | JacError::UnsupportedVersion(_)
+            | JacError::CorruptHeader
+            | JacError::CorruptBlock
+            | JacError::ChecksumMismatch
+            | JacError::UnexpectedEof
+            | JacError::DecompressError(_)
+            | JacError::TypeMismatch
+            | JacError::DictionaryError
+            | JacError::UnsupportedCompression(_)
+            | JacError::Json(_)
+            | JacError::RecordParse { .. }
+            | JacError::DocumentParse { .. } => ErrorCategory::Format,
+        }
+    }
+}
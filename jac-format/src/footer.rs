@@ -1,5 +1,6 @@
 //! Index footer structures
 
+use crate::block::FieldNameTable;
 use crate::checksum::{compute_crc32c, verify_crc32c};
 use crate::constants::INDEX_MAGIC;
 use crate::varint::{decode_uleb128, encode_uleb128};
@@ -10,6 +11,9 @@ use std::convert::TryFrom;
 pub struct IndexFooter {
     /// Block index entries
     pub blocks: Vec<BlockIndexEntry>,
+    /// Complete field-name table of a file with interned field names, so a
+    /// reader seeking to a block need not decode the headers before it
+    pub field_names: Option<FieldNameTable>,
 }
 
 /// Block index entry
@@ -50,6 +54,15 @@ impl IndexFooter {
             result.extend_from_slice(&encode_uleb128(block.record_count as u64));
         }
 
+        // Field-name table (interned files only): count, then names in ID order
+        if let Some(table) = &self.field_names {
+            result.extend_from_slice(&encode_uleb128(table.len() as u64));
+            for name in table.names() {
+                result.extend_from_slice(&encode_uleb128(name.len() as u64));
+                result.extend_from_slice(name.as_bytes());
+            }
+        }
+
         // Calculate and write index_len
         let index_len = result.len() - index_len_pos - 8; // Length after the index_len field
         let index_len_bytes = encode_uleb128(index_len as u64);
@@ -148,9 +161,14 @@ impl IndexFooter {
             });
         }
 
-        if pos != index_body_end {
-            return Err(crate::error::JacError::CorruptBlock);
-        }
+        // Field-name table, filling the rest of the body when present
+        let field_names = if pos < index_body_end {
+            let table = decode_field_names(&bytes[pos..index_body_end])
+                .ok_or(crate::error::JacError::CorruptBlock)?;
+            Some(table)
+        } else {
+            None
+        };
 
         // Verify CRC32C
         if index_body_end + 4 > bytes.len() {
@@ -166,10 +184,31 @@ impl IndexFooter {
         let footer_without_crc = &bytes[0..index_body_end];
         verify_crc32c(footer_without_crc, expected_crc)?;
 
-        Ok(Self { blocks })
+        Ok(Self {
+            blocks,
+            field_names,
+        })
     }
 }
 
+/// Decode a field-name table that must fill `body` exactly.
+fn decode_field_names(body: &[u8]) -> Option<FieldNameTable> {
+    let (name_count, mut pos) = decode_uleb128(body).ok()?;
+    let mut names = Vec::new();
+    for _ in 0..name_count {
+        let (name_len, name_len_bytes) = decode_uleb128(body.get(pos..)?).ok()?;
+        pos += name_len_bytes;
+        let name_end = pos.checked_add(usize::try_from(name_len).ok()?)?;
+        let name = std::str::from_utf8(body.get(pos..name_end)?).ok()?;
+        names.push(name.to_string());
+        pos = name_end;
+    }
+    if pos != body.len() {
+        return None;
+    }
+    FieldNameTable::from_names(names).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -188,6 +227,7 @@ mod tests {
     fn test_index_footer_roundtrip_basic() {
         let footer = IndexFooter {
             blocks: vec![create_test_block_entry()],
+            field_names: None,
         };
 
         let encoded = footer.encode().unwrap();
@@ -205,6 +245,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_index_footer_roundtrip_field_name_table() {
+        let names = vec!["id".to_string(), "name".to_string()];
+        let footer = IndexFooter {
+            blocks: vec![create_test_block_entry()],
+            field_names: Some(FieldNameTable::from_names(names.clone()).unwrap()),
+        };
+
+        let encoded = footer.encode().unwrap();
+        let decoded = IndexFooter::decode(&encoded).unwrap();
+        assert_eq!(decoded.blocks.len(), 1);
+        let table = decoded.field_names.unwrap();
+        assert_eq!(table.names(), &names[..]);
+        assert_eq!(table.id("name"), Some(1));
+
+        // A footer without a table ends after its entries, as before.
+        let plain = IndexFooter {
+            field_names: None,
+            ..footer
+        };
+        let plain_encoded = plain.encode().unwrap();
+        assert!(plain_encoded.len() < encoded.len());
+        assert!(IndexFooter::decode(&plain_encoded)
+            .unwrap()
+            .field_names
+            .is_none());
+        assert!(FieldNameTable::from_names(vec!["id".to_string(); 2]).is_err());
+    }
+
     #[test]
     fn test_index_footer_roundtrip_multiple_blocks() {
         let blocks = vec![
@@ -225,7 +294,10 @@ mod tests {
             },
         ];
 
-        let footer = IndexFooter { blocks };
+        let footer = IndexFooter {
+            blocks,
+            field_names: None,
+        };
         let encoded = footer.encode().unwrap();
         let decoded = IndexFooter::decode(&encoded).unwrap();
 
@@ -240,7 +312,10 @@ mod tests {
 
     #[test]
     fn test_index_footer_empty_blocks() {
-        let footer = IndexFooter { blocks: vec![] };
+        let footer = IndexFooter {
+            blocks: vec![],
+            field_names: None,
+        };
 
         let encoded = footer.encode().unwrap();
         let decoded = IndexFooter::decode(&encoded).unwrap();
@@ -280,6 +355,7 @@ mod tests {
     fn test_index_footer_crc_mismatch() {
         let footer = IndexFooter {
             blocks: vec![create_test_block_entry()],
+            field_names: None,
         };
 
         let mut encoded = footer.encode().unwrap();
@@ -304,6 +380,7 @@ mod tests {
     fn test_index_footer_index_len_mismatch_detected() {
         let footer = IndexFooter {
             blocks: vec![create_test_block_entry()],
+            field_names: None,
         };
 
         let mut encoded = footer.encode().unwrap();
@@ -321,6 +398,7 @@ mod tests {
     fn test_index_footer_endianness() {
         let footer = IndexFooter {
             blocks: vec![create_test_block_entry()],
+            field_names: None,
         };
 
         let encoded = footer.encode().unwrap();
@@ -353,7 +431,10 @@ mod tests {
             },
         ];
 
-        let footer = IndexFooter { blocks };
+        let footer = IndexFooter {
+            blocks,
+            field_names: None,
+        };
         let encoded = footer.encode().unwrap();
         let decoded = IndexFooter::decode(&encoded).unwrap();
 
@@ -389,7 +470,10 @@ mod tests {
             });
         }
 
-        let footer = IndexFooter { blocks };
+        let footer = IndexFooter {
+            blocks,
+            field_names: None,
+        };
         let encoded = footer.encode().unwrap();
         let decoded = IndexFooter::decode(&encoded).unwrap();
 
@@ -406,6 +490,7 @@ mod tests {
     fn test_index_footer_crc_verification() {
         let footer = IndexFooter {
             blocks: vec![create_test_block_entry()],
+            field_names: None,
         };
 
         let encoded = footer.encode().unwrap();
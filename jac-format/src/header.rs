@@ -162,6 +162,21 @@ impl FileHeader {
         self.flags & crate::constants::FLAG_NESTED_OPAQUE != 0
     }
 
+    /// Check if block headers carry their own CRC32C
+    pub fn block_header_crc(&self) -> bool {
+        self.flags & crate::constants::FLAG_BLOCK_HEADER_CRC != 0
+    }
+
+    /// Check if blocks start on [`BLOCK_ALIGNMENT`](crate::constants::BLOCK_ALIGNMENT) boundaries
+    pub fn aligned_blocks(&self) -> bool {
+        self.flags & crate::constants::FLAG_ALIGNED_BLOCKS != 0
+    }
+
+    /// Check if block directories use the file-level field-name table
+    pub fn interned_field_names(&self) -> bool {
+        self.flags & crate::constants::FLAG_INTERNED_FIELD_NAMES != 0
+    }
+
     /// Return the container format hint stored in the flags.
     pub fn container_format_hint(&self) -> JacResult<ContainerFormat> {
         ContainerFormat::from_flags(self.flags)
@@ -363,6 +378,7 @@ mod tests {
         assert!(header.canonicalize_keys());
         assert!(header.canonicalize_numbers());
         assert!(header.nested_opaque());
+        assert!(!header.interned_field_names());
 
         let header_no_flags = FileHeader {
             flags: 0,
@@ -375,6 +391,16 @@ mod tests {
         assert!(!header_no_flags.canonicalize_keys());
         assert!(!header_no_flags.canonicalize_numbers());
         assert!(!header_no_flags.nested_opaque());
+
+        let interned = FileHeader {
+            flags: FLAG_INTERNED_FIELD_NAMES,
+            ..header_no_flags
+        };
+        assert!(interned.interned_field_names());
+        assert_eq!(
+            interned.container_format_hint().unwrap(),
+            ContainerFormat::Unknown
+        );
     }
 
     #[test]
@@ -12,6 +12,7 @@
 //! - File/block structures
 //! - Decimal encoding
 //! - Type tags
+//! - Distinct-value and quantile sketches for field statistics
 
 #![deny(unsafe_code)]
 #![warn(missing_docs)]
@@ -25,16 +26,20 @@ pub mod error;
 pub mod footer;
 pub mod header;
 pub mod limits;
+pub mod sketch;
+pub mod streams;
 pub mod types;
 pub mod varint;
 
 // Re-export commonly used types
-pub use block::{BlockHeader, FieldDirectoryEntry};
+pub use block::{BlockHeader, BlockHeaderFormat, FieldDirectoryEntry, FieldNameTable, FieldStats};
 pub use decimal::Decimal;
-pub use error::{JacError, Result};
+pub use error::{ErrorCategory, JacError, Result};
 pub use footer::{BlockIndexEntry, IndexFooter};
 pub use header::{ContainerFormat, FileHeader};
-pub use limits::Limits;
+pub use limits::{DeclaredLimitsPolicy, Limits};
+pub use sketch::{DistinctSketch, QuantileSketch};
+pub use streams::{StreamDirectory, StreamEntry};
 pub use types::TypeTag;
 
 /// Compression codec options
@@ -1,7 +1,12 @@
 //! Security limits and configuration
 
+use serde::{Deserialize, Serialize};
+
 /// Security limits to prevent decompression bombs
-#[derive(Debug, Clone)]
+///
+/// Deserializing fills omitted fields from [`Limits::default`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Limits {
     /// Maximum records per block (default: 100,000, hard: 1,000,000)
     pub max_records_per_block: usize,
@@ -38,3 +43,73 @@ impl Default for Limits {
         }
     }
 }
+
+/// How a reader reconciles limits declared in a file header with the limits
+/// the caller passed in.
+///
+/// Writers record a raised or lowered segment limit in the header metadata so
+/// the file can be read back without extra flags; a caller reading untrusted
+/// files may prefer not to let the file loosen its limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeclaredLimitsPolicy {
+    /// Use the file's limit unless the caller changed it from the default
+    /// (default)
+    #[default]
+    FileOverridesDefault,
+    /// Always use the file's limit
+    TrustFile,
+    /// Use the stricter of the two, so a file can tighten but never loosen
+    Min,
+    /// Ignore the file's limit
+    CallerWins,
+}
+
+impl DeclaredLimitsPolicy {
+    /// Segment limit in effect when the caller asked for `caller` and the
+    /// file declares `declared`.
+    pub fn resolve_segment_limit(self, caller: usize, declared: Option<usize>) -> usize {
+        let Some(declared) = declared else {
+            return caller;
+        };
+        match self {
+            Self::FileOverridesDefault
+                if caller == Limits::default().max_segment_uncompressed_len =>
+            {
+                declared
+            }
+            Self::FileOverridesDefault | Self::CallerWins => caller,
+            Self::TrustFile => declared,
+            Self::Min => caller.min(declared),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declared_segment_limit_resolution() {
+        let default = Limits::default().max_segment_uncompressed_len;
+        let resolve = |policy: DeclaredLimitsPolicy, caller, declared| {
+            policy.resolve_segment_limit(caller, declared)
+        };
+        assert_eq!(
+            resolve(DeclaredLimitsPolicy::default(), default, Some(128)),
+            128
+        );
+        assert_eq!(resolve(DeclaredLimitsPolicy::default(), 32, Some(128)), 32);
+        assert_eq!(resolve(DeclaredLimitsPolicy::TrustFile, 32, Some(128)), 128);
+        assert_eq!(
+            resolve(DeclaredLimitsPolicy::Min, default, Some(2 * default)),
+            default
+        );
+        assert_eq!(resolve(DeclaredLimitsPolicy::Min, default, Some(128)), 128);
+        assert_eq!(
+            resolve(DeclaredLimitsPolicy::CallerWins, default, Some(128)),
+            default
+        );
+        assert_eq!(resolve(DeclaredLimitsPolicy::TrustFile, 32, None), 32);
+    }
+}
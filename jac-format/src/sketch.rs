@@ -0,0 +1,451 @@
+//! Approximate per-field statistics stored in block directories
+
+use crate::error::{JacError, Result};
+use crate::types::TypeTag;
+use crate::varint::{decode_uleb128, encode_uleb128};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Stable 64-bit hash of a value as stored in a field segment: FNV-1a over
+/// the type tag byte followed by `bytes`, finished with the MurmurHash3
+/// `fmix64` mixer.
+///
+/// `bytes` are the little-endian `i64` of an integer, `0`/`1` for a boolean,
+/// the encoded decimal, the UTF-8 of a string, or the minified JSON of an
+/// object or array. The hash is part of the format, so sketches written on
+/// one platform merge with those written on another.
+pub fn value_hash(tag: TypeTag, bytes: &[u8]) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    for &byte in std::iter::once(&(tag as u8)).chain(bytes) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+/// HyperLogLog sketch of the distinct values of a field.
+///
+/// Encoded as one precision byte `p` followed by `2^p` one-byte registers;
+/// the relative error of [`DistinctSketch::estimate`] is about
+/// `1.04 / sqrt(2^p)` (6.5% at the default precision of 8). Sketches of the
+/// same precision merge losslessly, so per-block sketches combine into a
+/// file-wide estimate without reading any values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistinctSketch {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl DistinctSketch {
+    /// Precision used by encoders unless configured otherwise
+    pub const DEFAULT_PRECISION: u8 = 8;
+    /// Smallest supported precision
+    pub const MIN_PRECISION: u8 = 4;
+    /// Largest supported precision
+    pub const MAX_PRECISION: u8 = 16;
+
+    /// Empty sketch with `2^precision` registers
+    pub fn new(precision: u8) -> Result<Self> {
+        if !(Self::MIN_PRECISION..=Self::MAX_PRECISION).contains(&precision) {
+            return Err(JacError::Internal(format!(
+                "Distinct sketch precision {} outside {}..={}",
+                precision,
+                Self::MIN_PRECISION,
+                Self::MAX_PRECISION
+            )));
+        }
+        Ok(Self {
+            precision,
+            registers: vec![0; 1 << precision],
+        })
+    }
+
+    /// Precision `p` of the sketch
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// Add a value by its [`value_hash`]
+    pub fn insert_hash(&mut self, hash: u64) {
+        let index = (hash >> (64 - self.precision)) as usize;
+        let rest = hash << self.precision;
+        let rank = (rest.leading_zeros() + 1).min(u32::from(64 - self.precision + 1)) as u8;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Add a value
+    pub fn insert(&mut self, tag: TypeTag, bytes: &[u8]) {
+        self.insert_hash(value_hash(tag, bytes));
+    }
+
+    /// Fold `other` into this sketch, as if its values had been inserted here
+    pub fn merge(&mut self, other: &DistinctSketch) -> Result<()> {
+        if other.precision != self.precision {
+            return Err(JacError::Internal(format!(
+                "Cannot merge distinct sketches of precision {} and {}",
+                self.precision, other.precision
+            )));
+        }
+        for (register, &theirs) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(theirs);
+        }
+        Ok(())
+    }
+
+    /// Estimated number of distinct values inserted
+    pub fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&rank| 2f64.powi(-i32::from(rank)))
+            .sum();
+        let raw = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&rank| rank == 0).count();
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            // Linear counting is more accurate while many registers are empty
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+
+    /// Encoded length in bytes
+    pub fn encoded_len(&self) -> usize {
+        1 + self.registers.len()
+    }
+
+    /// Encode to bytes
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        bytes.push(self.precision);
+        bytes.extend_from_slice(&self.registers);
+        bytes
+    }
+
+    /// Decode bytes written by [`DistinctSketch::encode`]
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let (&precision, registers) = bytes.split_first().ok_or(JacError::CorruptBlock)?;
+        if !(Self::MIN_PRECISION..=Self::MAX_PRECISION).contains(&precision)
+            || registers.len() != 1 << precision
+            || registers.iter().any(|&rank| rank > 64 - precision + 1)
+        {
+            return Err(JacError::CorruptBlock);
+        }
+        Ok(Self {
+            precision,
+            registers: registers.to_vec(),
+        })
+    }
+}
+
+/// Quantile summary of the numeric values of a field.
+///
+/// Holds `k + 1` points where point `i` is the value at rank `i / k` of the
+/// sorted values (interpolating between neighbours), so the first and last
+/// points are the exact minimum and maximum and the cumulative distribution
+/// is taken as linear between points. A quantile read from one sketch is
+/// off by at most `1 / k` in rank; sketches are combined by weighting their
+/// distributions by value count ([`QuantileSketch::combined_quantile`]), so
+/// the bound holds for a whole file as well.
+///
+/// Encoded as the value count (ULEB128), the point count (ULEB128) and each
+/// point as a little-endian `f64`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantileSketch {
+    count: u64,
+    points: Vec<f64>,
+}
+
+impl QuantileSketch {
+    /// Intervals `k` used by encoders unless configured otherwise
+    pub const DEFAULT_INTERVALS: usize = 16;
+    /// Largest supported number of intervals
+    pub const MAX_INTERVALS: usize = 256;
+
+    /// Sketch of `values` with `intervals` equal-rank intervals; `None` when
+    /// no value is finite. `values` is sorted in place.
+    pub fn from_values(values: &mut Vec<f64>, intervals: usize) -> Result<Option<Self>> {
+        if !(1..=Self::MAX_INTERVALS).contains(&intervals) {
+            return Err(JacError::Internal(format!(
+                "Quantile sketch intervals {} outside 1..={}",
+                intervals,
+                Self::MAX_INTERVALS
+            )));
+        }
+        values.retain(|value| value.is_finite());
+        if values.is_empty() {
+            return Ok(None);
+        }
+        values.sort_by(f64::total_cmp);
+        let last = (values.len() - 1) as f64;
+        let points = (0..=intervals)
+            .map(|i| {
+                let rank = last * i as f64 / intervals as f64;
+                let below = values[rank.floor() as usize];
+                let above = values[rank.ceil() as usize];
+                below + (above - below) * rank.fract()
+            })
+            .collect();
+        Ok(Some(Self {
+            count: values.len() as u64,
+            points,
+        }))
+    }
+
+    /// Number of values summarized
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Smallest value
+    pub fn min(&self) -> f64 {
+        self.points[0]
+    }
+
+    /// Largest value
+    pub fn max(&self) -> f64 {
+        self.points[self.points.len() - 1]
+    }
+
+    /// Points at ranks `0, 1/k, ..., 1`
+    pub fn points(&self) -> &[f64] {
+        &self.points
+    }
+
+    /// Approximate value at rank `q` (clamped to `0..=1`)
+    pub fn quantile(&self, q: f64) -> f64 {
+        Self::combined_quantile(std::slice::from_ref(self), q).unwrap_or(f64::NAN)
+    }
+
+    /// Approximate value at rank `q` (clamped to `0..=1`) of the values of
+    /// all `sketches` together; `None` when there are none.
+    pub fn combined_quantile(sketches: &[QuantileSketch], q: f64) -> Option<f64> {
+        let total: u64 = sketches.iter().map(|sketch| sketch.count).sum();
+        if total == 0 {
+            return None;
+        }
+        let q = q.clamp(0.0, 1.0);
+        let cdf = |x: f64, left: bool| -> f64 {
+            let weighted: f64 = sketches
+                .iter()
+                .map(|sketch| sketch.count as f64 * sketch.cdf(x, left))
+                .sum();
+            weighted / total as f64
+        };
+
+        let mut breakpoints: Vec<f64> = sketches
+            .iter()
+            .flat_map(|sketch| sketch.points.iter().copied())
+            .collect();
+        breakpoints.sort_by(f64::total_cmp);
+        breakpoints.dedup();
+        let mut previous: Option<(f64, f64)> = None;
+        for &x in &breakpoints {
+            let (below, at) = (cdf(x, true), cdf(x, false));
+            if let Some((prev_x, prev_at)) = previous {
+                if q <= below && below > prev_at {
+                    return Some(prev_x + (q - prev_at) / (below - prev_at) * (x - prev_x));
+                }
+            }
+            if q <= at {
+                return Some(x);
+            }
+            previous = Some((x, at));
+        }
+        breakpoints.last().copied()
+    }
+
+    /// Share of the values below `x` (`left`) or at most `x`.
+    fn cdf(&self, x: f64, left: bool) -> f64 {
+        let k = (self.points.len() - 1) as f64;
+        // Index of the first point above `x` (at or above it for `left`)
+        let upper = self
+            .points
+            .partition_point(|&point| if left { point < x } else { point <= x });
+        if upper == 0 {
+            return 0.0;
+        }
+        if upper == self.points.len() {
+            return 1.0;
+        }
+        let (low, high) = (self.points[upper - 1], self.points[upper]);
+        let within = if high > low {
+            (x - low) / (high - low)
+        } else {
+            0.0
+        };
+        ((upper - 1) as f64 + within) / k
+    }
+
+    /// Encode to bytes
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = encode_uleb128(self.count).to_vec();
+        bytes.extend_from_slice(&encode_uleb128(self.points.len() as u64));
+        for point in &self.points {
+            bytes.extend_from_slice(&point.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode bytes written by [`QuantileSketch::encode`]
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let (count, count_len) = decode_uleb128(bytes)?;
+        let (points_len, points_len_len) = decode_uleb128(&bytes[count_len..])?;
+        let data = &bytes[count_len + points_len_len..];
+        let points_len = usize::try_from(points_len).map_err(|_| JacError::CorruptBlock)?;
+        if count == 0
+            || !(2..=Self::MAX_INTERVALS + 1).contains(&points_len)
+            || data.len() != points_len * 8
+        {
+            return Err(JacError::CorruptBlock);
+        }
+        let points: Vec<f64> = data
+            .chunks_exact(8)
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().expect("8-byte chunk")))
+            .collect();
+        if points.iter().any(|point| !point.is_finite())
+            || points.windows(2).any(|pair| pair[0] > pair[1])
+        {
+            return Err(JacError::CorruptBlock);
+        }
+        Ok(Self { count, points })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sketch_of(range: std::ops::Range<i64>) -> DistinctSketch {
+        let mut sketch = DistinctSketch::new(DistinctSketch::DEFAULT_PRECISION).unwrap();
+        for value in range {
+            sketch.insert(TypeTag::Int, &value.to_le_bytes());
+        }
+        sketch
+    }
+
+    #[test]
+    fn estimates_stay_within_the_expected_error() {
+        assert_eq!(sketch_of(0..0).estimate(), 0);
+        assert_eq!(sketch_of(0..3).estimate(), 3);
+        for n in [100i64, 1_000, 50_000] {
+            let estimate = sketch_of(0..n).estimate() as f64;
+            let error = (estimate - n as f64).abs() / n as f64;
+            assert!(error < 0.2, "{} estimated as {}", n, estimate);
+        }
+    }
+
+    #[test]
+    fn merged_sketches_match_a_sketch_of_the_union() {
+        let mut merged = sketch_of(0..600);
+        merged.merge(&sketch_of(400..1_000)).unwrap();
+        assert_eq!(merged, sketch_of(0..1_000));
+        assert!(merged.merge(&DistinctSketch::new(4).unwrap()).is_err());
+    }
+
+    #[test]
+    fn encoding_roundtrips_and_rejects_malformed_bytes() {
+        let sketch = sketch_of(0..100);
+        let bytes = sketch.encode();
+        assert_eq!(bytes.len(), sketch.encoded_len());
+        assert_eq!(DistinctSketch::decode(&bytes).unwrap(), sketch);
+
+        assert!(DistinctSketch::decode(&[]).is_err());
+        assert!(DistinctSketch::decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(DistinctSketch::decode(&[3; 9]).is_err());
+        let mut high_rank = vec![4u8];
+        high_rank.extend([0; 15]);
+        high_rank.push(62);
+        assert!(DistinctSketch::decode(&high_rank).is_err());
+    }
+
+    #[test]
+    fn value_hash_is_stable_and_separates_types() {
+        assert_eq!(
+            value_hash(TypeTag::String, b"abc"),
+            value_hash(TypeTag::String, b"abc")
+        );
+        assert_ne!(
+            value_hash(TypeTag::String, b"1"),
+            value_hash(TypeTag::Object, b"1")
+        );
+        assert_eq!(value_hash(TypeTag::String, b""), 0xa77b_22d6_ed67_3fcc);
+    }
+
+    fn quantiles_of(range: std::ops::RangeInclusive<i32>, intervals: usize) -> QuantileSketch {
+        let mut values: Vec<f64> = range.map(f64::from).collect();
+        QuantileSketch::from_values(&mut values, intervals)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn quantile_sketch_keeps_exact_extremes_and_interpolates_ranks() {
+        let sketch = quantiles_of(0..=100, 4);
+        assert_eq!(sketch.points(), &[0.0, 25.0, 50.0, 75.0, 100.0]);
+        assert_eq!(
+            (sketch.count(), sketch.min(), sketch.max()),
+            (101, 0.0, 100.0)
+        );
+        assert_eq!(sketch.quantile(0.5), 50.0);
+        assert_eq!(sketch.quantile(0.9), 90.0);
+        assert_eq!(sketch.quantile(2.0), 100.0);
+
+        let mut no_numbers = vec![f64::NAN];
+        assert!(QuantileSketch::from_values(&mut no_numbers, 4)
+            .unwrap()
+            .is_none());
+        assert!(QuantileSketch::from_values(&mut vec![1.0], 0).is_err());
+    }
+
+    #[test]
+    fn combined_quantiles_weight_sketches_by_count() {
+        let low = quantiles_of(0..=99, QuantileSketch::DEFAULT_INTERVALS);
+        let high = quantiles_of(100..=399, QuantileSketch::DEFAULT_INTERVALS);
+        let both = [low, high];
+        let median = QuantileSketch::combined_quantile(&both, 0.5).unwrap();
+        assert!((median - 199.5).abs() < 400.0 / 16.0, "median {}", median);
+        assert_eq!(QuantileSketch::combined_quantile(&both, 0.0), Some(0.0));
+        assert_eq!(QuantileSketch::combined_quantile(&both, 1.0), Some(399.0));
+        assert_eq!(QuantileSketch::combined_quantile(&[], 0.5), None);
+
+        let constant = quantiles_of(5..=5, 4);
+        assert_eq!(constant.quantile(0.3), 5.0);
+        let mixed = [constant, quantiles_of(0..=9, 4)];
+        let median = QuantileSketch::combined_quantile(&mixed, 0.5).unwrap();
+        assert!((4.0..=6.0).contains(&median), "median {}", median);
+    }
+
+    #[test]
+    fn quantile_encoding_roundtrips_and_rejects_malformed_bytes() {
+        let sketch = quantiles_of(-50..=50, 8);
+        let bytes = sketch.encode();
+        assert_eq!(QuantileSketch::decode(&bytes).unwrap(), sketch);
+
+        assert!(QuantileSketch::decode(&[]).is_err());
+        assert!(QuantileSketch::decode(&bytes[..bytes.len() - 1]).is_err());
+        let mut unsorted = vec![2u8, 2];
+        unsorted.extend(2.0f64.to_le_bytes());
+        unsorted.extend(1.0f64.to_le_bytes());
+        assert!(QuantileSketch::decode(&unsorted).is_err());
+        let mut not_finite = vec![2u8, 2];
+        not_finite.extend(0.0f64.to_le_bytes());
+        not_finite.extend(f64::INFINITY.to_le_bytes());
+        assert!(QuantileSketch::decode(&not_finite).is_err());
+    }
+}
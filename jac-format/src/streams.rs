@@ -0,0 +1,120 @@
+//! Stream directory of multi-stream files
+
+use crate::checksum::{compute_crc32c, verify_crc32c};
+use crate::constants::STREAMS_MAGIC;
+use crate::error::JacError;
+use crate::varint::{decode_uleb128, encode_uleb128};
+
+/// Directory of the named streams stored in one file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamDirectory {
+    /// Streams in file order
+    pub streams: Vec<StreamEntry>,
+}
+
+/// One named stream: a complete archive embedded in the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamEntry {
+    /// Stream name (UTF-8, unique within the file)
+    pub name: String,
+    /// Offset of the stream's file header
+    pub offset: u64,
+    /// Length of the stream's archive in bytes
+    pub len: u64,
+}
+
+impl StreamDirectory {
+    /// Stream called `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&StreamEntry> {
+        self.streams.iter().find(|stream| stream.name == name)
+    }
+
+    /// Encode the directory to bytes
+    pub fn encode(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        result.extend_from_slice(&STREAMS_MAGIC.to_le_bytes());
+        result.extend_from_slice(&encode_uleb128(self.streams.len() as u64));
+        for stream in &self.streams {
+            result.extend_from_slice(&encode_uleb128(stream.name.len() as u64));
+            result.extend_from_slice(stream.name.as_bytes());
+            result.extend_from_slice(&encode_uleb128(stream.offset));
+            result.extend_from_slice(&encode_uleb128(stream.len));
+        }
+        let crc = compute_crc32c(&result);
+        result.extend_from_slice(&crc.to_le_bytes());
+        result
+    }
+
+    /// Decode a directory occupying all of `bytes`
+    pub fn decode(bytes: &[u8]) -> Result<Self, JacError> {
+        if bytes.len() < 8 {
+            return Err(JacError::UnexpectedEof);
+        }
+        let (body, crc) = bytes.split_at(bytes.len() - 4);
+        if body[..4] != STREAMS_MAGIC.to_le_bytes() {
+            return Err(JacError::CorruptBlock);
+        }
+        verify_crc32c(body, u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]]))?;
+
+        let mut pos = 4;
+        let next = |pos: &mut usize| -> Result<u64, JacError> {
+            let (value, len) = decode_uleb128(&body[*pos..])?;
+            *pos += len;
+            Ok(value)
+        };
+        let count = next(&mut pos)?;
+        let mut streams = Vec::new();
+        for _ in 0..count {
+            let name_len = usize::try_from(next(&mut pos)?).map_err(|_| JacError::CorruptBlock)?;
+            let name_end = pos
+                .checked_add(name_len)
+                .filter(|end| *end <= body.len())
+                .ok_or(JacError::UnexpectedEof)?;
+            let name = std::str::from_utf8(&body[pos..name_end])
+                .map_err(|_| JacError::CorruptBlock)?
+                .to_string();
+            pos = name_end;
+            let offset = next(&mut pos)?;
+            let len = next(&mut pos)?;
+            streams.push(StreamEntry { name, offset, len });
+        }
+        if pos != body.len() {
+            return Err(JacError::CorruptBlock);
+        }
+        Ok(Self { streams })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_directory_roundtrip_and_corruption() {
+        let directory = StreamDirectory {
+            streams: vec![
+                StreamEntry {
+                    name: "events".to_string(),
+                    offset: 0,
+                    len: 1200,
+                },
+                StreamEntry {
+                    name: "errors".to_string(),
+                    offset: 1200,
+                    len: 345,
+                },
+            ],
+        };
+        let mut bytes = directory.encode();
+        assert_eq!(StreamDirectory::decode(&bytes).unwrap(), directory);
+        assert_eq!(directory.get("errors").unwrap().offset, 1200);
+        assert!(directory.get("metadata").is_none());
+
+        bytes[6] ^= 0x20;
+        assert!(matches!(
+            StreamDirectory::decode(&bytes),
+            Err(JacError::ChecksumMismatch)
+        ));
+        assert!(StreamDirectory::decode(&bytes[..5]).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 //! Type tag enumeration
 
 /// Type tag codes (3-bit)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[repr(u8)]
 pub enum TypeTag {
     /// Present but null
@@ -40,6 +40,19 @@ impl TypeTag {
             ))),
         }
     }
+
+    /// Lowercase name of the type (`"int"`, `"decimal"`, ...).
+    pub fn name(self) -> &'static str {
+        match self {
+            TypeTag::Null => "null",
+            TypeTag::Bool => "bool",
+            TypeTag::Int => "int",
+            TypeTag::Decimal => "decimal",
+            TypeTag::String => "string",
+            TypeTag::Object => "object",
+            TypeTag::Array => "array",
+        }
+    }
 }
 
 #[cfg(test)]
@@ -40,6 +40,85 @@ pub fn decode_uleb128(bytes: &[u8]) -> Result<(u64, usize), crate::error::JacErr
     Err(crate::error::JacError::UnexpectedEof)
 }
 
+/// High bit of every byte in a 64-bit little-endian word.
+const CONTINUATION_BITS: u64 = 0x8080_8080_8080_8080;
+
+/// Decode a ULEB128 value of at most eight bytes held in the low bytes of `word`.
+///
+/// Returns `None` when the value does not end within the word. The payload
+/// bits are packed with three shift-and-mask steps instead of a loop over the
+/// bytes.
+fn decode_uleb128_word(word: u64) -> Option<(u64, usize)> {
+    let ends = !word & CONTINUATION_BITS;
+    if ends == 0 {
+        return None;
+    }
+    let len = ends.trailing_zeros() as usize / 8 + 1;
+    let mut x = word & 0x7f7f_7f7f_7f7f_7f7f;
+    if len < 8 {
+        x &= (1u64 << (len * 8)) - 1;
+    }
+    x = ((x & 0x7f00_7f00_7f00_7f00) >> 1) | (x & 0x007f_007f_007f_007f);
+    x = ((x & 0x3fff_0000_3fff_0000) >> 2) | (x & 0x0000_3fff_0000_3fff);
+    x = ((x & 0x0fff_ffff_0000_0000) >> 4) | (x & 0x0000_0000_0fff_ffff);
+    Some((x, len))
+}
+
+/// Decode `count` consecutive ULEB128 values from the start of `bytes` into `out`.
+///
+/// Returns the number of bytes consumed. Single-byte values, which are the
+/// common case for small integers, deltas and dictionary indices, are decoded
+/// eight at a time by testing the continuation bits of a whole 64-bit word.
+/// Longer values of up to eight bytes are decoded from one word as well; the
+/// rest, and values near the end of `bytes`, fall back to [`decode_uleb128`].
+/// Errors match those of repeated [`decode_uleb128`] calls.
+pub fn decode_uleb128_batch(
+    bytes: &[u8],
+    count: usize,
+    out: &mut Vec<u64>,
+) -> Result<usize, crate::error::JacError> {
+    out.reserve(count);
+    let mut cursor = 0;
+    let mut remaining = count;
+    while remaining >= 8 {
+        let Some(chunk) = bytes.get(cursor..cursor + 8) else {
+            break;
+        };
+        let chunk: &[u8; 8] = chunk.try_into().expect("eight-byte chunk");
+        let word = u64::from_le_bytes(*chunk);
+        let continuation = word & CONTINUATION_BITS;
+        if continuation == 0 {
+            out.extend(chunk.iter().map(|&byte| u64::from(byte)));
+            cursor += 8;
+            remaining -= 8;
+            continue;
+        }
+        // Bytes before the first continuation bit are complete values.
+        let single = continuation.trailing_zeros() as usize / 8;
+        out.extend(chunk[..single].iter().map(|&byte| u64::from(byte)));
+        cursor += single;
+        remaining -= single;
+
+        let word = match bytes.get(cursor..cursor + 8) {
+            Some(next) => u64::from_le_bytes(next.try_into().expect("eight-byte chunk")),
+            None => break,
+        };
+        let (value, consumed) = match decode_uleb128_word(word) {
+            Some(decoded) => decoded,
+            None => decode_uleb128(&bytes[cursor..])?,
+        };
+        out.push(value);
+        cursor += consumed;
+        remaining -= 1;
+    }
+    for _ in 0..remaining {
+        let (value, consumed) = decode_uleb128(&bytes[cursor..])?;
+        out.push(value);
+        cursor += consumed;
+    }
+    Ok(cursor)
+}
+
 /// ZigZag encode a signed integer
 pub fn zigzag_encode(v: i64) -> u64 {
     ((v << 1) ^ (v >> 63)) as u64
@@ -84,6 +163,47 @@ mod tests {
         }
     }
 
+    fn encode_all(values: &[u64]) -> Vec<u8> {
+        values.iter().flat_map(|&v| encode_uleb128(v)).collect()
+    }
+
+    proptest! {
+        #[test]
+        fn prop_uleb128_batch_matches_sequential(
+            values in proptest::collection::vec(
+                prop_oneof![0u64..128, 0u64..1 << 56, any::<u64>()],
+                0..64,
+            ),
+            trailing in proptest::collection::vec(any::<u8>(), 0..12),
+        ) {
+            let mut bytes = encode_all(&values);
+            let encoded_len = bytes.len();
+            bytes.extend_from_slice(&trailing);
+
+            let mut decoded = Vec::new();
+            let consumed = decode_uleb128_batch(&bytes, values.len(), &mut decoded).unwrap();
+            prop_assert_eq!(consumed, encoded_len);
+            prop_assert_eq!(decoded, values);
+        }
+    }
+
+    #[test]
+    fn test_uleb128_batch_errors_match_sequential() {
+        let bytes = encode_all(&[1, 2, 3, 4, 5, 6, 7, 8, 300]);
+        let mut out = Vec::new();
+        assert!(matches!(
+            decode_uleb128_batch(&bytes[..bytes.len() - 1], 9, &mut out),
+            Err(crate::error::JacError::UnexpectedEof)
+        ));
+
+        let mut too_long = vec![0; 8];
+        too_long.extend_from_slice(&[0x80; 11]);
+        assert!(matches!(
+            decode_uleb128_batch(&too_long, 9, &mut Vec::new()),
+            Err(crate::error::JacError::LimitExceeded(_))
+        ));
+    }
+
     #[test]
     fn test_uleb128_decode_truncated() {
         let encoded = encode_uleb128(1000);
@@ -0,0 +1,267 @@
+//! JAC ingest - batch a live record stream into rotated .jac files
+//!
+//! Records arrive as newline-delimited JSON objects over TCP, standard input, or
+//! (with the `kafka` feature) a Kafka topic. They are batched, written through
+//! `JacWriter`, and rotated into a new file whenever a record, byte, or age
+//! threshold is reached. Ctrl-C (SIGINT/SIGTERM) finishes the open file before
+//! exiting so no accepted record is lost: TCP and Kafka sources stop accepting
+//! input and everything they already took is written first. Kafka offsets are
+//! stored only after their records are written. With `--metrics-listen`,
+//! progress counters are served for Prometheus at `/metrics`.
+
+mod metrics;
+mod source;
+
+use clap::{Parser, ValueEnum};
+use jac_codec::Codec;
+use jac_io::{CompressOptions, RotatedFile, RotatingJacWriter, RotationPolicy, ServiceMetrics};
+use serde_json::{Map, Value};
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum SourceKind {
+    Tcp,
+    Stdin,
+    #[cfg(feature = "kafka")]
+    Kafka,
+}
+
+#[derive(Parser)]
+#[command(name = "jac-ingest")]
+#[command(about = "Batch a live NDJSON stream into rotated .jac files")]
+#[command(version)]
+struct Args {
+    /// Record source
+    #[arg(long, value_enum, default_value_t = SourceKind::Tcp)]
+    source: SourceKind,
+    /// Address to listen on for `--source tcp`
+    #[arg(long, default_value = "127.0.0.1:9000")]
+    listen: SocketAddr,
+    /// Kafka bootstrap servers for `--source kafka`
+    #[cfg(feature = "kafka")]
+    #[arg(long, default_value = "localhost:9092")]
+    brokers: String,
+    /// Kafka topic to consume (repeatable)
+    #[cfg(feature = "kafka")]
+    #[arg(long = "topic")]
+    topics: Vec<String>,
+    /// Kafka consumer group id
+    #[cfg(feature = "kafka")]
+    #[arg(long, default_value = "jac-ingest")]
+    group_id: String,
+    /// Directory receiving the .jac files
+    #[arg(short, long)]
+    output_dir: PathBuf,
+    /// File name prefix
+    #[arg(long, default_value = "ingest")]
+    prefix: String,
+    /// Records per write batch
+    #[arg(long, default_value_t = 1000)]
+    batch_size: usize,
+    /// Maximum time a partial batch waits before it is written (milliseconds)
+    #[arg(long, default_value_t = 1000)]
+    batch_timeout_ms: u64,
+    /// Rotate after this many records
+    #[arg(long)]
+    max_records: Option<u64>,
+    /// Rotate once this many bytes have been written to the current file
+    #[arg(long)]
+    max_bytes: Option<u64>,
+    /// Rotate once the current file has been open this many seconds
+    #[arg(long)]
+    max_age_secs: Option<u64>,
+    /// Target records per block
+    #[arg(long, default_value_t = 100_000)]
+    block_records: usize,
+    /// Zstd compression level (0 disables compression)
+    #[arg(long, default_value_t = 6)]
+    zstd_level: u8,
+    /// Serve Prometheus metrics at http://<addr>/metrics
+    #[arg(long)]
+    metrics_listen: Option<SocketAddr>,
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(err) = run(args) {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let options = CompressOptions {
+        block_target_records: args.block_records,
+        default_codec: if args.zstd_level == 0 {
+            Codec::None
+        } else {
+            Codec::Zstd(args.zstd_level)
+        },
+        ..CompressOptions::default()
+    };
+    let policy = RotationPolicy {
+        max_records: args.max_records,
+        max_bytes: args.max_bytes,
+        max_age: args.max_age_secs.map(Duration::from_secs),
+    };
+    let metrics = Arc::new(ServiceMetrics::default());
+    if let Some(addr) = args.metrics_listen {
+        let metrics = Arc::clone(&metrics);
+        thread::spawn(move || {
+            if let Err(err) = metrics::run_endpoint(addr, metrics) {
+                eprintln!("Metrics endpoint failed: {}", err);
+            }
+        });
+    }
+
+    let rotate_metrics = Arc::clone(&metrics);
+    let mut writer =
+        RotatingJacWriter::from_options(&args.output_dir, args.prefix.clone(), &options, policy)?
+            .on_rotate(move |file| {
+                report(file);
+                ServiceMetrics::add(&rotate_metrics.files_written, 1);
+                ServiceMetrics::add(&rotate_metrics.blocks_written, file.metrics.blocks_written);
+                ServiceMetrics::add(&rotate_metrics.bytes_written, file.metrics.bytes_written);
+                Ok(())
+            });
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    {
+        let shutdown = Arc::clone(&shutdown);
+        ctrlc::set_handler(move || shutdown.store(true, Ordering::Relaxed))?;
+    }
+
+    let (sender, lines) = mpsc::sync_channel::<source::Line>(args.batch_size.max(1) * 4);
+    let sender = source::LineSender::new(sender, Arc::clone(&metrics));
+    let source_shutdown = Arc::clone(&shutdown);
+    let source = match args.source {
+        SourceKind::Tcp => {
+            let addr = args.listen;
+            thread::spawn(move || source::run_tcp(addr, sender, source_shutdown))
+        }
+        SourceKind::Stdin => thread::spawn(move || source::run_stdin(sender, source_shutdown)),
+        #[cfg(feature = "kafka")]
+        SourceKind::Kafka => {
+            let kafka = source::KafkaSource {
+                brokers: args.brokers.clone(),
+                topics: args.topics.clone(),
+                group_id: args.group_id.clone(),
+            };
+            thread::spawn(move || source::run_kafka(kafka, sender, source_shutdown))
+        }
+    };
+
+    let batch_timeout = Duration::from_millis(args.batch_timeout_ms);
+    let mut batch: Vec<Map<String, Value>> = Vec::with_capacity(args.batch_size);
+    let mut acks: Vec<source::Ack> = Vec::new();
+    let mut batch_started = Instant::now();
+    // Stdin may block indefinitely, so it is abandoned on shutdown. Other sources
+    // return within a poll interval and are drained until they hang up.
+    let abandon_on_shutdown = args.source == SourceKind::Stdin;
+
+    let disconnected = loop {
+        let wait = batch_timeout.saturating_sub(batch_started.elapsed());
+        let disconnected = match lines.recv_timeout(wait.min(Duration::from_millis(100))) {
+            Ok(line) => {
+                if batch.is_empty() {
+                    batch_started = Instant::now();
+                }
+                accept(line, &mut batch, &mut acks, &metrics);
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+
+        let stopping = disconnected || (abandon_on_shutdown && shutdown.load(Ordering::Relaxed));
+        if stopping {
+            // Drain whatever the source already accepted before finishing up.
+            while let Ok(line) = lines.try_recv() {
+                accept(line, &mut batch, &mut acks, &metrics);
+            }
+        }
+
+        if batch.len() >= args.batch_size
+            || (!batch.is_empty() && batch_started.elapsed() >= batch_timeout)
+            || stopping
+        {
+            let written = batch.len() as u64;
+            if let Err(err) = writer.write_records(batch.drain(..)) {
+                ServiceMetrics::add(&metrics.errors, 1);
+                return Err(err.into());
+            }
+            ServiceMetrics::add(&metrics.records, written);
+            ServiceMetrics::sub(&metrics.queue_depth, written);
+            for ack in acks.drain(..) {
+                ack();
+            }
+        }
+        writer.tick()?;
+        let open_bytes = writer
+            .current_metrics()
+            .map_or(0, |current| current.bytes_written);
+        metrics.open_file_bytes.store(open_bytes, Ordering::Relaxed);
+
+        if stopping {
+            writer.finish()?;
+            break disconnected;
+        }
+    };
+
+    // A source that hung up on its own has finished; surface its error. Otherwise
+    // it may be blocked on input (stdin), so leave it to exit with the process.
+    shutdown.store(true, Ordering::Relaxed);
+    if disconnected {
+        source.join().map_err(|_| "source thread panicked")??;
+    }
+    let rejected = metrics.records_rejected.load(Ordering::Relaxed);
+    if rejected > 0 {
+        eprintln!("Skipped {} lines that were not JSON objects", rejected);
+    }
+    Ok(())
+}
+
+/// Add the record in `line` to `batch` and hold its acknowledgement until the
+/// batch is written. Blank and rejected lines leave the queue here; accepted
+/// records leave it once their batch is written.
+fn accept(
+    line: source::Line,
+    batch: &mut Vec<Map<String, Value>>,
+    acks: &mut Vec<source::Ack>,
+    metrics: &ServiceMetrics,
+) {
+    acks.extend(line.ack);
+    match parse_record(&line.text) {
+        Some(record) => batch.push(record),
+        None => {
+            ServiceMetrics::sub(&metrics.queue_depth, 1);
+            if !line.text.trim().is_empty() {
+                ServiceMetrics::add(&metrics.records_rejected, 1);
+            }
+        }
+    }
+}
+
+fn parse_record(line: &str) -> Option<Map<String, Value>> {
+    match serde_json::from_str::<Value>(line.trim()) {
+        Ok(Value::Object(record)) => Some(record),
+        _ => None,
+    }
+}
+
+fn report(file: &RotatedFile) {
+    eprintln!(
+        "Finished {} ({} records, {} blocks, {} bytes)",
+        file.path.display(),
+        file.metrics.records_written,
+        file.metrics.blocks_written,
+        file.metrics.bytes_written
+    );
+}
@@ -0,0 +1,66 @@
+//! Prometheus scrape endpoint for `--metrics-listen`.
+//!
+//! Answers `GET /metrics` with the current [`ServiceMetrics`] and anything
+//! else with 404. Requests are handled one at a time on a dedicated thread;
+//! scrapes are small and infrequent, so there is no need for more.
+
+use jac_io::ServiceMetrics;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Namespace of every exported metric name.
+pub const NAMESPACE: &str = "jac_ingest";
+
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Serve `metrics` on `addr` until the process exits.
+pub fn run_endpoint(addr: SocketAddr, metrics: Arc<ServiceMetrics>) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    eprintln!(
+        "Serving metrics on http://{}/metrics",
+        listener.local_addr()?
+    );
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("Metrics connection failed: {}", err);
+                continue;
+            }
+        };
+        if let Err(err) = respond(stream, &metrics) {
+            eprintln!("Metrics request failed: {}", err);
+        }
+    }
+    Ok(())
+}
+
+fn respond(stream: TcpStream, metrics: &ServiceMetrics) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Consume the headers so the client sees a clean close.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", metrics.render(NAMESPACE)),
+        _ => ("404 Not Found", "Not found\n".to_string()),
+    };
+    let mut stream = reader.into_inner();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
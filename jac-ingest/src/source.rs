@@ -0,0 +1,210 @@
+//! Record sources feeding the ingest loop.
+//!
+//! Every source runs on its own thread and forwards raw lines over a bounded
+//! channel, so a slow writer applies backpressure instead of buffering without
+//! limit. Sources poll the shared shutdown flag and return once it is set.
+//! A source that must confirm delivery upstream attaches an [`Ack`] to each
+//! line; the ingest loop runs it once the record has been written.
+
+use jac_io::ServiceMetrics;
+use std::io::{self, BufRead, BufReader, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::SyncSender;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Acknowledgement run after the record it travels with has been written.
+pub type Ack = Box<dyn FnOnce() + Send>;
+
+/// One raw line received from a source.
+pub struct Line {
+    pub text: String,
+    pub ack: Option<Ack>,
+}
+
+/// Sending half of the line channel. Every queued line is counted in the
+/// `queue_depth` gauge, which the ingest loop lowers once the line is written
+/// or rejected.
+#[derive(Clone)]
+pub struct LineSender {
+    lines: SyncSender<Line>,
+    metrics: Arc<ServiceMetrics>,
+}
+
+impl LineSender {
+    pub fn new(lines: SyncSender<Line>, metrics: Arc<ServiceMetrics>) -> Self {
+        Self { lines, metrics }
+    }
+
+    /// Queue `line`; returns `false` once the ingest loop has stopped receiving.
+    pub fn send(&self, line: String) -> bool {
+        self.send_line(Line {
+            text: line,
+            ack: None,
+        })
+    }
+
+    /// Queue `line` together with the acknowledgement to run once it is written.
+    #[cfg_attr(not(feature = "kafka"), allow(dead_code))]
+    pub fn send_acked(&self, line: String, ack: Ack) -> bool {
+        self.send_line(Line {
+            text: line,
+            ack: Some(ack),
+        })
+    }
+
+    fn send_line(&self, line: Line) -> bool {
+        ServiceMetrics::add(&self.metrics.queue_depth, 1);
+        if self.lines.send(line).is_err() {
+            ServiceMetrics::sub(&self.metrics.queue_depth, 1);
+            return false;
+        }
+        true
+    }
+}
+
+/// Accept TCP connections on `addr` and forward each newline-delimited record.
+pub fn run_tcp(addr: SocketAddr, lines: LineSender, shutdown: Arc<AtomicBool>) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    eprintln!("Listening on {}", listener.local_addr()?);
+
+    let mut connections: Vec<thread::JoinHandle<()>> = Vec::new();
+    while !shutdown.load(Ordering::Relaxed) {
+        connections.retain(|connection| !connection.is_finished());
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                let lines = lines.clone();
+                let shutdown = Arc::clone(&shutdown);
+                connections.push(thread::spawn(move || {
+                    if let Err(err) = read_connection(stream, lines, shutdown) {
+                        eprintln!("Connection from {} failed: {}", peer, err);
+                    }
+                }));
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(err) => return Err(err),
+        }
+    }
+
+    for connection in connections {
+        let _ = connection.join();
+    }
+    Ok(())
+}
+
+fn read_connection(
+    stream: TcpStream,
+    lines: LineSender,
+    shutdown: Arc<AtomicBool>,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+
+    while !shutdown.load(Ordering::Relaxed) {
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => break,
+            Ok(_) => {
+                if line.ends_with(b"\n") && !send_line(&lines, &mut line) {
+                    break;
+                }
+            }
+            // Partial lines stay buffered in `line` across read timeouts.
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    if !line.is_empty() {
+        send_line(&lines, &mut line);
+    }
+    Ok(())
+}
+
+fn send_line(lines: &LineSender, line: &mut Vec<u8>) -> bool {
+    let text = String::from_utf8_lossy(line).into_owned();
+    line.clear();
+    lines.send(text)
+}
+
+/// Forward newline-delimited records from standard input until EOF.
+pub fn run_stdin(lines: LineSender, shutdown: Arc<AtomicBool>) -> io::Result<()> {
+    let stdin = io::stdin();
+    for line in stdin.lock().lines() {
+        if shutdown.load(Ordering::Relaxed) || !lines.send(line?) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Kafka consumer settings.
+#[cfg(feature = "kafka")]
+pub struct KafkaSource {
+    /// Bootstrap servers (`host:port[,host:port]`)
+    pub brokers: String,
+    /// Topics to subscribe to
+    pub topics: Vec<String>,
+    /// Consumer group id
+    pub group_id: String,
+}
+
+/// Consume message payloads from Kafka and forward each as one record.
+///
+/// Offsets are stored for the next commit only once the ingest loop has
+/// written the record, so a message accepted before shutdown is never
+/// committed without reaching a file.
+#[cfg(feature = "kafka")]
+pub fn run_kafka(
+    source: KafkaSource,
+    lines: LineSender,
+    shutdown: Arc<AtomicBool>,
+) -> io::Result<()> {
+    use rdkafka::config::ClientConfig;
+    use rdkafka::consumer::{BaseConsumer, Consumer};
+    use rdkafka::Message;
+
+    let to_io = |err: rdkafka::error::KafkaError| io::Error::new(ErrorKind::Other, err);
+    let consumer: Arc<BaseConsumer> = Arc::new(
+        ClientConfig::new()
+            .set("bootstrap.servers", &source.brokers)
+            .set("group.id", &source.group_id)
+            .set("enable.auto.commit", "true")
+            .set("enable.auto.offset.store", "false")
+            .set("auto.offset.reset", "earliest")
+            .create()
+            .map_err(to_io)?,
+    );
+    let topics: Vec<&str> = source.topics.iter().map(String::as_str).collect();
+    consumer.subscribe(&topics).map_err(to_io)?;
+
+    while !shutdown.load(Ordering::Relaxed) {
+        match consumer.poll(POLL_INTERVAL) {
+            None => {}
+            Some(Ok(message)) => {
+                let Some(payload) = message.payload() else {
+                    continue;
+                };
+                let line = String::from_utf8_lossy(payload).into_owned();
+                let committer = Arc::clone(&consumer);
+                let topic = message.topic().to_owned();
+                let (partition, next_offset) = (message.partition(), message.offset() + 1);
+                let ack: Ack = Box::new(move || {
+                    if let Err(err) = committer.store_offset(&topic, partition, next_offset) {
+                        eprintln!("Kafka offset store failed: {}", err);
+                    }
+                });
+                if !lines.send_acked(line, ack) {
+                    break;
+                }
+            }
+            Some(Err(err)) => eprintln!("Kafka error: {}", err),
+        }
+    }
+    Ok(())
+}
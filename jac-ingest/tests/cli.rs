@@ -0,0 +1,66 @@
+use jac_codec::DecompressOpts;
+use jac_io::JacReader;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+fn files_with_suffix(dir: &std::path::Path, suffix: &str) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.to_string_lossy().ends_with(suffix) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[test]
+fn stdin_ingest_rotates_on_record_threshold() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let mut input = String::new();
+    for id in 0..7 {
+        input.push_str(&format!("{{\"id\":{}}}\n", id));
+        if id == 2 {
+            input.push_str("[1,2,3]\n\nnot json\n");
+        }
+    }
+
+    assert_cmd::Command::cargo_bin("jac-ingest")?
+        .args([
+            "--source",
+            "stdin",
+            "--max-records",
+            "3",
+            "-o",
+            dir.path().to_str().unwrap(),
+        ])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Skipped 2 lines that were not JSON objects",
+        ));
+
+    assert!(files_with_suffix(dir.path(), ".partial")?.is_empty());
+    let files = files_with_suffix(dir.path(), ".jac")?;
+    assert_eq!(files.len(), 3);
+
+    let mut counts = Vec::new();
+    let mut next_id = 0u64;
+    for path in &files {
+        let mut reader = JacReader::open(path, DecompressOpts::default())?;
+        let mut count = 0u64;
+        for record in reader.record_stream()? {
+            assert_eq!(record?["id"], serde_json::json!(next_id));
+            next_id += 1;
+            count += 1;
+        }
+        counts.push(count);
+    }
+    assert_eq!(counts, vec![3, 3, 1]);
+    Ok(())
+}
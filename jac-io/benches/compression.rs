@@ -1,8 +1,9 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use jac_io::{
     execute_compress, parallel::ParallelConfig, Codec, CompressOptions, CompressRequest,
-    InputSource, OutputSink, WrapperConfig,
+    InputSource, OutputSink,
 };
+use jac_test_utils::{Workload, WorkloadProfile};
 use serde_json::json;
 use std::io::{Cursor, Write};
 use std::sync::Arc;
@@ -67,6 +68,17 @@ fn generate_nested_objects(count: usize) -> Vec<u8> {
     buf
 }
 
+/// Serialize `count` records of a shared workload profile as NDJSON
+fn generate_workload(profile: WorkloadProfile, count: usize) -> Vec<u8> {
+    let mut buf = Vec::new();
+
+    for record in Workload::new(profile, 0x5eed).take(count) {
+        writeln!(&mut buf, "{}", record).unwrap();
+    }
+
+    buf
+}
+
 fn bench_compression_throughput(c: &mut Criterion) {
     let mut group = c.benchmark_group("compression_throughput");
 
@@ -74,6 +86,22 @@ fn bench_compression_throughput(c: &mut Criterion) {
         ("low_card_10k", generate_low_cardinality_logs(10_000)),
         ("high_card_10k", generate_high_cardinality_events(10_000)),
         ("nested_10k", generate_nested_objects(10_000)),
+        (
+            "web_logs_10k",
+            generate_workload(WorkloadProfile::web_logs(), 10_000),
+        ),
+        (
+            "iot_metrics_10k",
+            generate_workload(WorkloadProfile::iot_metrics(), 10_000),
+        ),
+        (
+            "nested_api_10k",
+            generate_workload(WorkloadProfile::nested_api(), 10_000),
+        ),
+        (
+            "sparse_wide_10k",
+            generate_workload(WorkloadProfile::sparse_wide(), 10_000),
+        ),
     ];
 
     for (name, data) in datasets {
@@ -85,14 +113,13 @@ fn bench_compression_throughput(c: &mut Criterion) {
                 let input = Cursor::new(data.clone());
                 let output = Cursor::new(Vec::new());
 
-                let request = CompressRequest {
-                    input: InputSource::NdjsonReader(Box::new(input)),
-                    output: OutputSink::Writer(Box::new(output)),
-                    options: CompressOptions::default(),
-                    container_hint: Some(jac_format::ContainerFormat::Ndjson),
-                    emit_index: false,
-                    wrapper_config: WrapperConfig::None,
-                };
+                let request = CompressRequest::builder()
+                    .input(InputSource::NdjsonReader(Box::new(input)))
+                    .output(OutputSink::Writer(Box::new(output)))
+                    .container_hint(jac_format::ContainerFormat::Ndjson)
+                    .emit_index(false)
+                    .build()
+                    .unwrap();
 
                 black_box(execute_compress(request).unwrap());
             });
@@ -120,14 +147,14 @@ fn bench_block_size_impact(c: &mut Criterion) {
                     let mut options = CompressOptions::default();
                     options.block_target_records = block_size;
 
-                    let request = CompressRequest {
-                        input: InputSource::NdjsonReader(Box::new(input)),
-                        output: OutputSink::Writer(Box::new(output)),
-                        options,
-                        container_hint: Some(jac_format::ContainerFormat::Ndjson),
-                        emit_index: false,
-                        wrapper_config: WrapperConfig::None,
-                    };
+                    let request = CompressRequest::builder()
+                        .input(InputSource::NdjsonReader(Box::new(input)))
+                        .output(OutputSink::Writer(Box::new(output)))
+                        .options(options)
+                        .container_hint(jac_format::ContainerFormat::Ndjson)
+                        .emit_index(false)
+                        .build()
+                        .unwrap();
 
                     black_box(execute_compress(request).unwrap());
                 });
@@ -156,14 +183,14 @@ fn bench_zstd_levels(c: &mut Criterion) {
                     let mut options = CompressOptions::default();
                     options.default_codec = Codec::Zstd(level);
 
-                    let request = CompressRequest {
-                        input: InputSource::NdjsonReader(Box::new(input)),
-                        output: OutputSink::Writer(Box::new(output)),
-                        options,
-                        container_hint: Some(jac_format::ContainerFormat::Ndjson),
-                        emit_index: false,
-                        wrapper_config: WrapperConfig::None,
-                    };
+                    let request = CompressRequest::builder()
+                        .input(InputSource::NdjsonReader(Box::new(input)))
+                        .output(OutputSink::Writer(Box::new(output)))
+                        .options(options)
+                        .container_hint(jac_format::ContainerFormat::Ndjson)
+                        .emit_index(false)
+                        .build()
+                        .unwrap();
 
                     black_box(execute_compress(request).unwrap());
                 });
@@ -212,14 +239,14 @@ fn benchmark_parallel_config(c: &mut Criterion, group_name: &str, codec: Codec)
                     };
                     options.limits.max_block_uncompressed_total = 32 * 1024 * 1024;
 
-                    let request = CompressRequest {
-                        input: InputSource::NdjsonReader(Box::new(input)),
-                        output: OutputSink::Writer(Box::new(output)),
-                        options,
-                        container_hint: Some(jac_format::ContainerFormat::Ndjson),
-                        emit_index: false,
-                        wrapper_config: WrapperConfig::None,
-                    };
+                    let request = CompressRequest::builder()
+                        .input(InputSource::NdjsonReader(Box::new(input)))
+                        .output(OutputSink::Writer(Box::new(output)))
+                        .options(options)
+                        .container_hint(jac_format::ContainerFormat::Ndjson)
+                        .emit_index(false)
+                        .build()
+                        .unwrap();
 
                     black_box(execute_compress(request).unwrap());
                 });
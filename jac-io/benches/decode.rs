@@ -1,8 +1,8 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use jac_io::{
-    execute_compress, execute_decompress, execute_project, CompressOptions, CompressRequest,
-    DecompressFormat, DecompressOptions, DecompressRequest, InputSource, JacInput, OutputSink,
-    ProjectFormat, ProjectRequest, WrapperConfig,
+    execute_compress, execute_decompress, execute_project, CompressRequest, DecompressFormat,
+    DecompressOptions, DecompressRequest, InputSource, JacInput, OutputSink, ProjectFormat,
+    ProjectRequest,
 };
 use serde_json::json;
 use std::io::{Cursor, Write};
@@ -26,6 +26,25 @@ fn generate_test_data(count: usize) -> Vec<u8> {
     buf
 }
 
+fn generate_nested_data(count: usize) -> Vec<u8> {
+    let mut buf = Vec::new();
+
+    for i in 0..count {
+        let record = json!({
+            "id": i,
+            "request": {
+                "method": if i % 3 == 0 { "POST" } else { "GET" },
+                "headers": { "accept": "application/json", "x-trace": format!("t-{}", i) },
+                "params": [i % 7, { "page": i % 10, "sort": "desc" }],
+            },
+            "response": { "status": 200, "timings": [1.5, 2.25, i % 13], "tags": ["a", "b"] },
+        });
+        writeln!(&mut buf, "{}", record).unwrap();
+    }
+
+    buf
+}
+
 fn compress_data(data: &[u8]) -> Vec<u8> {
     let input = Cursor::new(data.to_vec());
 
@@ -33,14 +52,13 @@ fn compress_data(data: &[u8]) -> Vec<u8> {
     let temp_dir = std::env::temp_dir();
     let temp_path = temp_dir.join(format!("jac_bench_{}.jac", std::process::id()));
 
-    let request = CompressRequest {
-        input: InputSource::NdjsonReader(Box::new(input)),
-        output: OutputSink::Path(temp_path.clone()),
-        options: CompressOptions::default(),
-        container_hint: Some(jac_format::ContainerFormat::Ndjson),
-        emit_index: false,
-        wrapper_config: WrapperConfig::None,
-    };
+    let request = CompressRequest::builder()
+        .input(InputSource::NdjsonReader(Box::new(input)))
+        .output(OutputSink::Path(temp_path.clone()))
+        .container_hint(jac_format::ContainerFormat::Ndjson)
+        .emit_index(false)
+        .build()
+        .unwrap();
 
     execute_compress(request).unwrap();
 
@@ -70,12 +88,12 @@ fn bench_full_decompression(c: &mut Criterion) {
                     let input = Cursor::new(compressed.clone());
                     let output = Cursor::new(Vec::new());
 
-                    let request = DecompressRequest {
-                        input: JacInput::Reader(Box::new(input)),
-                        output: OutputSink::Writer(Box::new(output)),
-                        format: DecompressFormat::Ndjson,
-                        options: DecompressOptions::default(),
-                    };
+                    let request = DecompressRequest::builder()
+                        .input(JacInput::Reader(Box::new(input)))
+                        .output(OutputSink::Writer(Box::new(output)))
+                        .format(DecompressFormat::Ndjson)
+                        .build()
+                        .unwrap();
 
                     black_box(execute_decompress(request).unwrap());
                 });
@@ -86,6 +104,43 @@ fn bench_full_decompression(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_raw_json_passthrough(c: &mut Criterion) {
+    let mut group = c.benchmark_group("raw_json_passthrough");
+
+    let compressed = compress_data(&generate_nested_data(20_000));
+    group.throughput(Throughput::Bytes(compressed.len() as u64));
+
+    let modes = [
+        ("parsed", false, true),
+        ("passthrough", true, true),
+        ("passthrough_unvalidated", true, false),
+    ];
+    for (name, raw_json_passthrough, validate_nested_json) in modes {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let input = Cursor::new(compressed.clone());
+                let output = Cursor::new(Vec::new());
+
+                let request = DecompressRequest::builder()
+                    .input(JacInput::Reader(Box::new(input)))
+                    .output(OutputSink::Writer(Box::new(output)))
+                    .format(DecompressFormat::Ndjson)
+                    .options(DecompressOptions {
+                        raw_json_passthrough,
+                        validate_nested_json,
+                        ..DecompressOptions::default()
+                    })
+                    .build()
+                    .unwrap();
+
+                black_box(execute_decompress(request).unwrap());
+            });
+        });
+    }
+
+    group.finish();
+}
+
 fn bench_field_projection(c: &mut Criterion) {
     let mut group = c.benchmark_group("field_projection");
 
@@ -104,13 +159,13 @@ fn bench_field_projection(c: &mut Criterion) {
                 let input = Cursor::new(compressed.clone());
                 let output = Cursor::new(Vec::new());
 
-                let request = ProjectRequest {
-                    input: JacInput::Reader(Box::new(input)),
-                    output: OutputSink::Writer(Box::new(output)),
-                    fields: fields.iter().map(|s| s.to_string()).collect(),
-                    format: ProjectFormat::Ndjson,
-                    options: DecompressOptions::default(),
-                };
+                let request = ProjectRequest::builder()
+                    .input(JacInput::Reader(Box::new(input)))
+                    .output(OutputSink::Writer(Box::new(output)))
+                    .fields(fields.iter().map(|s| s.to_string()))
+                    .format(ProjectFormat::Ndjson)
+                    .build()
+                    .unwrap();
 
                 black_box(execute_project(request).unwrap());
             });
@@ -135,12 +190,12 @@ fn bench_projection_speedup(c: &mut Criterion) {
             let input = Cursor::new(compressed.clone());
             let output = Cursor::new(Vec::new());
 
-            let request = DecompressRequest {
-                input: JacInput::Reader(Box::new(input)),
-                output: OutputSink::Writer(Box::new(output)),
-                format: DecompressFormat::Ndjson,
-                options: DecompressOptions::default(),
-            };
+            let request = DecompressRequest::builder()
+                .input(JacInput::Reader(Box::new(input)))
+                .output(OutputSink::Writer(Box::new(output)))
+                .format(DecompressFormat::Ndjson)
+                .build()
+                .unwrap();
 
             black_box(execute_decompress(request).unwrap());
         });
@@ -152,13 +207,13 @@ fn bench_projection_speedup(c: &mut Criterion) {
             let input = Cursor::new(compressed.clone());
             let output = Cursor::new(Vec::new());
 
-            let request = ProjectRequest {
-                input: JacInput::Reader(Box::new(input)),
-                output: OutputSink::Writer(Box::new(output)),
-                fields: vec!["user".to_string()],
-                format: ProjectFormat::Ndjson,
-                options: DecompressOptions::default(),
-            };
+            let request = ProjectRequest::builder()
+                .input(JacInput::Reader(Box::new(input)))
+                .output(OutputSink::Writer(Box::new(output)))
+                .fields(["user"])
+                .format(ProjectFormat::Ndjson)
+                .build()
+                .unwrap();
 
             black_box(execute_project(request).unwrap());
         });
@@ -183,13 +238,13 @@ fn bench_block_scanning(c: &mut Criterion) {
             let input = Cursor::new(compressed.clone());
             let output = Cursor::new(Vec::new());
 
-            let request = ProjectRequest {
-                input: JacInput::Reader(Box::new(input)),
-                output: OutputSink::Writer(Box::new(output)),
-                fields: vec!["user".to_string()],
-                format: ProjectFormat::Ndjson,
-                options: DecompressOptions::default(),
-            };
+            let request = ProjectRequest::builder()
+                .input(JacInput::Reader(Box::new(input)))
+                .output(OutputSink::Writer(Box::new(output)))
+                .fields(["user"])
+                .format(ProjectFormat::Ndjson)
+                .build()
+                .unwrap();
 
             black_box(execute_project(request).unwrap());
         });
@@ -201,6 +256,7 @@ fn bench_block_scanning(c: &mut Criterion) {
 criterion_group!(
     benches,
     bench_full_decompression,
+    bench_raw_json_passthrough,
     bench_field_projection,
     bench_projection_speedup,
     bench_block_scanning
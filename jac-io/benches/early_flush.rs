@@ -1,8 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use jac_format::Limits;
-use jac_io::{
-    execute_compress, CompressOptions, CompressRequest, InputSource, OutputSink, WrapperConfig,
-};
+use jac_io::{execute_compress, CompressOptions, CompressRequest, InputSource, OutputSink};
 use serde_json::json;
 use std::io::{Cursor, Write};
 
@@ -68,14 +66,14 @@ fn bench_early_flush_impact(c: &mut Criterion) {
                 options.limits = limits;
                 options.block_target_records = 10000; // High target to force segment limit
 
-                let request = CompressRequest {
-                    input: InputSource::NdjsonReader(Box::new(input)),
-                    output: OutputSink::Writer(Box::new(output)),
-                    options,
-                    container_hint: Some(jac_format::ContainerFormat::Ndjson),
-                    emit_index: false,
-                    wrapper_config: WrapperConfig::None,
-                };
+                let request = CompressRequest::builder()
+                    .input(InputSource::NdjsonReader(Box::new(input)))
+                    .output(OutputSink::Writer(Box::new(output)))
+                    .options(options)
+                    .container_hint(jac_format::ContainerFormat::Ndjson)
+                    .emit_index(false)
+                    .build()
+                    .unwrap();
 
                 let summary = execute_compress(request).unwrap();
                 black_box((
@@ -119,14 +117,14 @@ fn bench_block_size_vs_segment_limit(c: &mut Criterion) {
                 options.limits = limits;
                 options.block_target_records = block_size;
 
-                let request = CompressRequest {
-                    input: InputSource::NdjsonReader(Box::new(input)),
-                    output: OutputSink::Writer(Box::new(output)),
-                    options,
-                    container_hint: Some(jac_format::ContainerFormat::Ndjson),
-                    emit_index: false,
-                    wrapper_config: WrapperConfig::None,
-                };
+                let request = CompressRequest::builder()
+                    .input(InputSource::NdjsonReader(Box::new(input)))
+                    .output(OutputSink::Writer(Box::new(output)))
+                    .options(options)
+                    .container_hint(jac_format::ContainerFormat::Ndjson)
+                    .emit_index(false)
+                    .build()
+                    .unwrap();
 
                 black_box(execute_compress(request).unwrap());
             });
@@ -161,14 +159,14 @@ fn bench_growing_field_impact(c: &mut Criterion) {
                 let mut options = CompressOptions::default();
                 options.block_target_records = 10000;
 
-                let request = CompressRequest {
-                    input: InputSource::NdjsonReader(Box::new(input)),
-                    output: OutputSink::Writer(Box::new(output)),
-                    options,
-                    container_hint: Some(jac_format::ContainerFormat::Ndjson),
-                    emit_index: false,
-                    wrapper_config: WrapperConfig::None,
-                };
+                let request = CompressRequest::builder()
+                    .input(InputSource::NdjsonReader(Box::new(input)))
+                    .output(OutputSink::Writer(Box::new(output)))
+                    .options(options)
+                    .container_hint(jac_format::ContainerFormat::Ndjson)
+                    .emit_index(false)
+                    .build()
+                    .unwrap();
 
                 black_box(execute_compress(request).unwrap());
             });
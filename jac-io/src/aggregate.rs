@@ -0,0 +1,493 @@
+//! Group-by aggregation executed over projected columns.
+//!
+//! Each block is decoded once, only the grouping and aggregated fields are
+//! projected, and per-group partial states are built for that block before being
+//! merged into the running totals. Every state is mergeable, so the result does
+//! not depend on block boundaries.
+
+use crate::{DecompressOptions, JacInput};
+use jac_codec::DecompressOpts;
+use jac_format::{Decimal, DistinctSketch, JacError, Result, TypeTag};
+use serde_json::{Map, Number, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Aggregate computed for every group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Aggregate {
+    /// Number of records in the group
+    Count,
+    /// Sum of numeric values of the field (non-numeric values are ignored)
+    Sum(String),
+    /// Smallest value of the field (bools < numbers < strings; null, arrays and objects ignored)
+    Min(String),
+    /// Largest value of the field, using the same ordering as `Min`
+    Max(String),
+    /// Approximate number of distinct non-null values of the field
+    /// ([`DistinctSketch`] at precision 12, ~1.6% error)
+    DistinctApprox(String),
+}
+
+impl Aggregate {
+    /// Column label used in result rows, e.g. `count` or `sum(bytes)`.
+    pub fn label(&self) -> String {
+        match self {
+            Aggregate::Count => "count".to_string(),
+            Aggregate::Sum(field) => format!("sum({})", field),
+            Aggregate::Min(field) => format!("min({})", field),
+            Aggregate::Max(field) => format!("max({})", field),
+            Aggregate::DistinctApprox(field) => format!("distinct({})", field),
+        }
+    }
+
+    fn field(&self) -> Option<&str> {
+        match self {
+            Aggregate::Count => None,
+            Aggregate::Sum(field)
+            | Aggregate::Min(field)
+            | Aggregate::Max(field)
+            | Aggregate::DistinctApprox(field) => Some(field),
+        }
+    }
+}
+
+/// Aggregation request over a JAC file.
+pub struct AggregateRequest {
+    /// Source JAC file or reader.
+    pub input: JacInput,
+    /// Fields whose values form the group key (empty for a single global group).
+    pub group_by: Vec<String>,
+    /// Aggregates computed per group.
+    pub aggregates: Vec<Aggregate>,
+    /// Decompression options.
+    pub options: DecompressOptions,
+}
+
+/// One group of an aggregation result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregateGroup {
+    /// Group key values in `group_by` order (`None` when the field was absent).
+    pub key: Vec<Option<Value>>,
+    /// Aggregate values in request order.
+    pub values: Vec<Value>,
+}
+
+/// Result of [`execute_aggregate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregateResult {
+    /// Grouping fields from the request.
+    pub group_by: Vec<String>,
+    /// Aggregates from the request.
+    pub aggregates: Vec<Aggregate>,
+    /// Groups ordered by their JSON-encoded key.
+    pub groups: Vec<AggregateGroup>,
+}
+
+impl AggregateResult {
+    /// Render each group as a JSON object of group fields followed by aggregate labels.
+    pub fn rows(&self) -> Vec<Map<String, Value>> {
+        self.groups
+            .iter()
+            .map(|group| {
+                let mut row = Map::new();
+                for (field, value) in self.group_by.iter().zip(&group.key) {
+                    row.insert(field.clone(), value.clone().unwrap_or(Value::Null));
+                }
+                for (aggregate, value) in self.aggregates.iter().zip(&group.values) {
+                    row.insert(aggregate.label(), value.clone());
+                }
+                row
+            })
+            .collect()
+    }
+}
+
+/// Execute an aggregation request.
+pub fn execute_aggregate(request: AggregateRequest) -> Result<AggregateResult> {
+    let AggregateRequest {
+        input,
+        group_by,
+        aggregates,
+        options,
+    } = request;
+
+    if aggregates.is_empty() {
+        return Err(JacError::Internal(
+            "aggregate request requires at least one aggregate".to_string(),
+        ));
+    }
+
+    let codec_opts = DecompressOpts {
+        limits: options.limits.clone(),
+        verify_checksums: options.verify_checksums,
+        strict: options.strict,
+        declared_limits: options.declared_limits,
+        ..DecompressOpts::default()
+    };
+    let mut reader = input.open(codec_opts, options.snapshot)?;
+
+    // Project each distinct field once per block.
+    let mut fields: Vec<String> = Vec::new();
+    let group_columns: Vec<usize> = group_by
+        .iter()
+        .map(|name| column_of(name, &mut fields))
+        .collect();
+    let aggregate_columns: Vec<Option<usize>> = aggregates
+        .iter()
+        .map(|agg| agg.field().map(|name| column_of(name, &mut fields)))
+        .collect();
+
+    let mut totals: HashMap<String, GroupState> = HashMap::new();
+    let blocks = reader.blocks().collect::<Result<Vec<_>>>()?;
+    for block in &blocks {
+        let decoder = reader.decode_block(block)?;
+        let columns = fields
+            .iter()
+            .map(|field| decoder.project_field(field))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut partial: HashMap<String, GroupState> = HashMap::new();
+        for row in 0..block.record_count {
+            let key: Vec<Option<Value>> = group_columns
+                .iter()
+                .map(|&col| columns[col][row].clone())
+                .collect();
+            let key_text = serde_json::to_string(&key)?;
+            let group = partial
+                .entry(key_text)
+                .or_insert_with(|| GroupState::new(key, &aggregates));
+            for (state, column) in group.states.iter_mut().zip(&aggregate_columns) {
+                let value = column.and_then(|col| columns[col][row].as_ref());
+                state.update(value);
+            }
+        }
+
+        for (key_text, group) in partial {
+            match totals.get_mut(&key_text) {
+                Some(total) => total.merge(group),
+                None => {
+                    totals.insert(key_text, group);
+                }
+            }
+        }
+    }
+
+    let mut sorted: Vec<_> = totals.into_iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+    let groups = sorted
+        .into_iter()
+        .map(|(_, group)| AggregateGroup {
+            key: group.key,
+            values: group.states.iter().map(AggState::finish).collect(),
+        })
+        .collect();
+
+    Ok(AggregateResult {
+        group_by,
+        aggregates,
+        groups,
+    })
+}
+
+fn column_of(name: &str, fields: &mut Vec<String>) -> usize {
+    match fields.iter().position(|f| f == name) {
+        Some(idx) => idx,
+        None => {
+            fields.push(name.to_string());
+            fields.len() - 1
+        }
+    }
+}
+
+struct GroupState {
+    key: Vec<Option<Value>>,
+    states: Vec<AggState>,
+}
+
+impl GroupState {
+    fn new(key: Vec<Option<Value>>, aggregates: &[Aggregate]) -> Self {
+        Self {
+            key,
+            states: aggregates.iter().map(AggState::new).collect(),
+        }
+    }
+
+    fn merge(&mut self, other: GroupState) {
+        for (state, other) in self.states.iter_mut().zip(other.states) {
+            state.merge(other);
+        }
+    }
+}
+
+enum AggState {
+    Count(u64),
+    Sum {
+        ints: i128,
+        floats: f64,
+        has_float: bool,
+        seen: bool,
+    },
+    Min(Option<Value>),
+    Max(Option<Value>),
+    Distinct(Box<DistinctSketch>),
+}
+
+impl AggState {
+    fn new(aggregate: &Aggregate) -> Self {
+        match aggregate {
+            Aggregate::Count => AggState::Count(0),
+            Aggregate::Sum(_) => AggState::Sum {
+                ints: 0,
+                floats: 0.0,
+                has_float: false,
+                seen: false,
+            },
+            Aggregate::Min(_) => AggState::Min(None),
+            Aggregate::Max(_) => AggState::Max(None),
+            Aggregate::DistinctApprox(_) => AggState::Distinct(Box::new(
+                DistinctSketch::new(DISTINCT_PRECISION).expect("precision is in range"),
+            )),
+        }
+    }
+
+    fn update(&mut self, value: Option<&Value>) {
+        match self {
+            AggState::Count(count) => *count += 1,
+            AggState::Sum {
+                ints,
+                floats,
+                has_float,
+                seen,
+            } => {
+                if let Some(Value::Number(number)) = value {
+                    if let Some(int) = number.as_i64() {
+                        *ints += i128::from(int);
+                    } else if let Some(float) = number.as_f64() {
+                        *floats += float;
+                        *has_float = true;
+                    }
+                    *seen = true;
+                }
+            }
+            AggState::Min(current) => {
+                if let Some(value) = value {
+                    keep_extreme(current, value, Ordering::Less);
+                }
+            }
+            AggState::Max(current) => {
+                if let Some(value) = value {
+                    keep_extreme(current, value, Ordering::Greater);
+                }
+            }
+            AggState::Distinct(sketch) => {
+                if let Some(value) = value {
+                    insert_distinct(sketch, value);
+                }
+            }
+        }
+    }
+
+    fn merge(&mut self, other: AggState) {
+        match (self, other) {
+            (AggState::Count(a), AggState::Count(b)) => *a += b,
+            (
+                AggState::Sum {
+                    ints,
+                    floats,
+                    has_float,
+                    seen,
+                },
+                AggState::Sum {
+                    ints: other_ints,
+                    floats: other_floats,
+                    has_float: other_has_float,
+                    seen: other_seen,
+                },
+            ) => {
+                *ints += other_ints;
+                *floats += other_floats;
+                *has_float |= other_has_float;
+                *seen |= other_seen;
+            }
+            (AggState::Min(current), AggState::Min(Some(other))) => {
+                keep_extreme(current, &other, Ordering::Less)
+            }
+            (AggState::Max(current), AggState::Max(Some(other))) => {
+                keep_extreme(current, &other, Ordering::Greater)
+            }
+            (AggState::Distinct(a), AggState::Distinct(b)) => {
+                a.merge(&b).expect("distinct sketches share one precision")
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(&self) -> Value {
+        match self {
+            AggState::Count(count) => Value::from(*count),
+            AggState::Sum {
+                ints,
+                floats,
+                has_float,
+                seen,
+            } => {
+                if !*seen {
+                    Value::Null
+                } else if !*has_float {
+                    match i64::try_from(*ints) {
+                        Ok(int) => Value::from(int),
+                        Err(_) => float_value(*ints as f64),
+                    }
+                } else {
+                    float_value(*ints as f64 + *floats)
+                }
+            }
+            AggState::Min(value) | AggState::Max(value) => value.clone().unwrap_or(Value::Null),
+            AggState::Distinct(sketch) => Value::from(sketch.estimate()),
+        }
+    }
+}
+
+fn float_value(value: f64) -> Value {
+    Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+/// Replace `current` with `candidate` when it compares as `wanted` (or `current` is unset).
+fn keep_extreme(current: &mut Option<Value>, candidate: &Value, wanted: Ordering) {
+    if rank(candidate).is_none() {
+        return;
+    }
+    let replace = match current {
+        None => true,
+        Some(existing) => compare_values(candidate, existing) == Some(wanted),
+    };
+    if replace {
+        *current = Some(candidate.clone());
+    }
+}
+
+fn rank(value: &Value) -> Option<u8> {
+    match value {
+        Value::Bool(_) => Some(0),
+        Value::Number(_) => Some(1),
+        Value::String(_) => Some(2),
+        _ => None,
+    }
+}
+
+fn compare_values(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Bool(x), Value::Bool(y)) => Some(x.cmp(y)),
+        (Value::Number(x), Value::Number(y)) => match (x.as_i64(), y.as_i64()) {
+            (Some(x), Some(y)) => Some(x.cmp(&y)),
+            _ => x.as_f64()?.partial_cmp(&y.as_f64()?),
+        },
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        _ => Some(rank(a)?.cmp(&rank(b)?)),
+    }
+}
+
+/// Precision of the sketches behind [`Aggregate::DistinctApprox`]
+const DISTINCT_PRECISION: u8 = 12;
+
+/// Add `value` to `sketch` under the hash field segments use for it (see
+/// `jac_format::sketch::value_hash`), so estimates agree with the per-segment
+/// distinct sketches. Nulls are not counted.
+fn insert_distinct(sketch: &mut DistinctSketch, value: &Value) {
+    match value {
+        Value::Null => {}
+        Value::Bool(b) => sketch.insert(TypeTag::Bool, &[u8::from(*b)]),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => sketch.insert(TypeTag::Int, &i.to_le_bytes()),
+            None => {
+                let text = n.to_string();
+                match Decimal::from_str_exact(&text).and_then(|decimal| decimal.encode()) {
+                    Ok(bytes) => sketch.insert(TypeTag::Decimal, &bytes),
+                    Err(_) => sketch.insert(TypeTag::Decimal, text.as_bytes()),
+                }
+            }
+        },
+        Value::String(s) => sketch.insert(TypeTag::String, s.as_bytes()),
+        Value::Array(_) => sketch.insert(TypeTag::Array, value.to_string().as_bytes()),
+        Value::Object(_) => sketch.insert(TypeTag::Object, value.to_string().as_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn distinct_estimates_within_tolerance_and_merges() {
+        let aggregate = Aggregate::DistinctApprox("f".into());
+        let mut a = AggState::new(&aggregate);
+        let mut b = AggState::new(&aggregate);
+        for i in 0..20_000 {
+            a.update(Some(&json!(i)));
+            b.update(Some(&json!(i + 10_000)));
+        }
+        a.merge(b);
+        let estimate = a.finish().as_f64().unwrap();
+        assert!(
+            (estimate - 30_000.0).abs() / 30_000.0 < 0.05,
+            "{}",
+            estimate
+        );
+
+        let mut small = AggState::new(&aggregate);
+        for value in [json!("x"), json!("y"), json!("z"), json!("x"), json!(null)] {
+            small.update(Some(&value));
+        }
+        assert_eq!(small.finish(), json!(3));
+    }
+
+    #[test]
+    fn distinct_hashes_values_like_segment_sketches() {
+        let mut from_values = DistinctSketch::new(DISTINCT_PRECISION).unwrap();
+        let mut from_segments = DistinctSketch::new(DISTINCT_PRECISION).unwrap();
+        for value in [
+            json!(7),
+            json!(true),
+            json!("x"),
+            json!(2.5),
+            json!([1, {"a": 2}]),
+        ] {
+            insert_distinct(&mut from_values, &value);
+        }
+        from_segments.insert(TypeTag::Int, &7i64.to_le_bytes());
+        from_segments.insert(TypeTag::Bool, &[1]);
+        from_segments.insert(TypeTag::String, b"x");
+        let decimal = Decimal::from_str_exact("2.5").unwrap();
+        from_segments.insert(TypeTag::Decimal, &decimal.encode().unwrap());
+        from_segments.insert(TypeTag::Array, br#"[1,{"a":2}]"#);
+        assert_eq!(from_values, from_segments);
+    }
+
+    #[test]
+    fn min_max_order_numbers_before_strings_and_skip_nulls() {
+        let mut min = AggState::new(&Aggregate::Min("f".into()));
+        let mut max = AggState::new(&Aggregate::Max("f".into()));
+        for value in [json!(3), json!(null), json!("b"), json!(-1.5), json!("a")] {
+            min.update(Some(&value));
+            max.update(Some(&value));
+        }
+        assert_eq!(min.finish(), json!(-1.5));
+        assert_eq!(max.finish(), json!("b"));
+    }
+
+    #[test]
+    fn sum_stays_integral_until_a_float_appears() {
+        let mut sum = AggState::new(&Aggregate::Sum("f".into()));
+        assert_eq!(sum.finish(), Value::Null);
+        sum.update(Some(&json!(i64::MAX)));
+        sum.update(Some(&json!("ignored")));
+        let mut other = AggState::new(&Aggregate::Sum("f".into()));
+        other.update(Some(&json!(-2)));
+        sum.merge(other);
+        assert_eq!(sum.finish(), json!(i64::MAX - 2));
+
+        sum.update(Some(&json!(0.5)));
+        assert_eq!(sum.finish(), json!((i64::MAX - 2) as f64 + 0.5));
+    }
+}
@@ -0,0 +1,397 @@
+//! MessagePack and BSON document streams as record input.
+//!
+//! Services that dump MessagePack or BSON would otherwise have to convert to
+//! JSON text first, which roughly doubles ingest time. With the `msgpack` or
+//! `bson` feature, [`InputSource::MessagePackReader`](crate::InputSource) and
+//! [`InputSource::BsonReader`](crate::InputSource) decode concatenated
+//! top-level documents straight into records. Each document is bounded by the
+//! same ceiling as a JSON array element: `max_record_bytes`, or else the block
+//! budget.
+
+use crate::{IngestConfig, IngestCounters};
+use jac_format::Result;
+use serde_json::{Map, Value};
+use std::io::Read;
+
+/// Fallible record iterator produced by a binary decoder.
+pub(crate) type Documents = Box<dyn Iterator<Item = Result<Map<String, Value>>> + Send>;
+
+/// Decode a stream of MessagePack maps with string keys.
+///
+/// MessagePack carries no document lengths, so an oversized document always
+/// fails, whatever the oversized-record policy.
+#[cfg(feature = "msgpack")]
+pub(crate) fn message_pack_documents(
+    reader: Box<dyn Read + Send>,
+    ingest: IngestConfig,
+    _counters: IngestCounters,
+) -> Result<Documents> {
+    Ok(Box::new(msgpack::MessagePackStream::new(reader, ingest)))
+}
+
+/// Without the `msgpack` feature MessagePack input is rejected up front.
+#[cfg(not(feature = "msgpack"))]
+pub(crate) fn message_pack_documents(
+    _reader: Box<dyn Read + Send>,
+    _ingest: IngestConfig,
+    _counters: IngestCounters,
+) -> Result<Documents> {
+    Err(jac_format::JacError::UnsupportedFeature(
+        "MessagePack input requires the `msgpack` feature".to_string(),
+    ))
+}
+
+/// Decode a stream of concatenated BSON documents (as written by `mongodump`).
+///
+/// BSON-specific values are mapped to relaxed Extended JSON, e.g. an ObjectId
+/// becomes `{"$oid": "..."}`. Oversized documents honour the oversized-record
+/// policy, since their length prefix lets them be skipped unread.
+#[cfg(feature = "bson")]
+pub(crate) fn bson_documents(
+    reader: Box<dyn Read + Send>,
+    ingest: IngestConfig,
+    counters: IngestCounters,
+) -> Result<Documents> {
+    Ok(Box::new(bson_stream::BsonStream::new(
+        reader, ingest, counters,
+    )))
+}
+
+/// Without the `bson` feature BSON input is rejected up front.
+#[cfg(not(feature = "bson"))]
+pub(crate) fn bson_documents(
+    _reader: Box<dyn Read + Send>,
+    _ingest: IngestConfig,
+    _counters: IngestCounters,
+) -> Result<Documents> {
+    Err(jac_format::JacError::UnsupportedFeature(
+        "BSON input requires the `bson` feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "msgpack")]
+mod msgpack {
+    use crate::record_limit::{object_limit_error, ObjectLimit};
+    use crate::IngestConfig;
+    use jac_format::{JacError, Result};
+    use serde_json::{Map, Value};
+    use std::io::{BufRead, BufReader, Read};
+
+    pub(super) struct MessagePackStream {
+        reader: BufReader<Box<dyn Read + Send>>,
+        limit: ObjectLimit,
+        /// Documents started so far
+        document: u64,
+        /// Bytes consumed so far
+        offset: u64,
+        /// Set after an error, since the stream cannot be resynchronised
+        failed: bool,
+    }
+
+    impl MessagePackStream {
+        pub(super) fn new(reader: Box<dyn Read + Send>, ingest: IngestConfig) -> Self {
+            Self {
+                reader: BufReader::new(reader),
+                limit: ingest.object_limit(),
+                document: 0,
+                offset: 0,
+                failed: false,
+            }
+        }
+
+        fn read_document(&mut self) -> Result<Option<Map<String, Value>>> {
+            if self.reader.fill_buf()?.is_empty() {
+                return Ok(None);
+            }
+            self.document += 1;
+            // Read at most one byte past the ceiling so an oversized document
+            // is detected without buffering it.
+            let ceiling = self.limit.bytes as u64 + 1;
+            let mut limited = (&mut self.reader).take(ceiling);
+            let decoded: std::result::Result<Value, _> = rmp_serde::from_read(&mut limited);
+            if limited.limit() == 0 {
+                return Err(object_limit_error(self.limit));
+            }
+            let offset = self.offset;
+            self.offset += ceiling - limited.limit();
+            match decoded {
+                Ok(Value::Object(map)) => Ok(Some(map)),
+                Ok(_) => Err(self.parse_error(offset, "top-level value is not a map".into())),
+                Err(err) => Err(self.parse_error(offset, err.to_string())),
+            }
+        }
+
+        fn parse_error(&self, offset: u64, reason: String) -> JacError {
+            JacError::DocumentParse {
+                format: "MessagePack",
+                document: self.document,
+                offset,
+                reason,
+            }
+        }
+    }
+
+    impl Iterator for MessagePackStream {
+        type Item = Result<Map<String, Value>>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.failed {
+                return None;
+            }
+            let result = self.read_document();
+            self.failed = result.is_err();
+            result.transpose()
+        }
+    }
+}
+
+#[cfg(feature = "bson")]
+mod bson_stream {
+    use crate::record_limit::{object_limit_error, ObjectLimit};
+    use crate::{IngestConfig, IngestCounters, OversizedRecordPolicy};
+    use bson::Document;
+    use jac_format::{JacError, Result};
+    use serde_json::{Map, Value};
+    use std::io::{self, BufRead, BufReader, Read};
+    use std::sync::atomic::Ordering;
+
+    /// Smallest valid document: length prefix plus terminating NUL.
+    const MIN_DOCUMENT_BYTES: usize = 5;
+
+    pub(super) struct BsonStream {
+        reader: BufReader<Box<dyn Read + Send>>,
+        limit: ObjectLimit,
+        skip_oversized: bool,
+        counters: IngestCounters,
+        /// Documents started so far, including skipped ones
+        document: u64,
+        /// Bytes consumed so far
+        offset: u64,
+        /// Set after an error, since the stream cannot be resynchronised
+        failed: bool,
+    }
+
+    impl BsonStream {
+        pub(super) fn new(
+            reader: Box<dyn Read + Send>,
+            ingest: IngestConfig,
+            counters: IngestCounters,
+        ) -> Self {
+            Self {
+                reader: BufReader::new(reader),
+                limit: ingest.object_limit(),
+                skip_oversized: ingest.oversized == OversizedRecordPolicy::Skip,
+                counters,
+                document: 0,
+                offset: 0,
+                failed: false,
+            }
+        }
+
+        fn read_document(&mut self) -> Result<Option<Map<String, Value>>> {
+            loop {
+                if self.reader.fill_buf()?.is_empty() {
+                    return Ok(None);
+                }
+                self.document += 1;
+                let offset = self.offset;
+                let mut prefix = [0u8; 4];
+                self.reader.read_exact(&mut prefix).map_err(truncated)?;
+                let declared = i32::from_le_bytes(prefix);
+                let len = match usize::try_from(declared) {
+                    Ok(len) if len >= MIN_DOCUMENT_BYTES => len,
+                    _ => {
+                        return Err(self
+                            .parse_error(offset, format!("invalid document length {}", declared)))
+                    }
+                };
+                self.offset += len as u64;
+
+                if len > self.limit.bytes {
+                    if !self.skip_oversized {
+                        return Err(object_limit_error(self.limit));
+                    }
+                    let body = (len - prefix.len()) as u64;
+                    let drained = io::copy(&mut (&mut self.reader).take(body), &mut io::sink())?;
+                    if drained < body {
+                        return Err(JacError::UnexpectedEof);
+                    }
+                    self.counters
+                        .oversized_skipped
+                        .fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+
+                let mut bytes = vec![0u8; len];
+                bytes[..prefix.len()].copy_from_slice(&prefix);
+                self.reader
+                    .read_exact(&mut bytes[prefix.len()..])
+                    .map_err(truncated)?;
+                let document = Document::from_reader(&mut bytes.as_slice())
+                    .map_err(|err| self.parse_error(offset, err.to_string()))?;
+                return Ok(Some(
+                    document
+                        .into_iter()
+                        .map(|(key, value)| (key, value.into_relaxed_extjson()))
+                        .collect(),
+                ));
+            }
+        }
+
+        fn parse_error(&self, offset: u64, reason: String) -> JacError {
+            JacError::DocumentParse {
+                format: "BSON",
+                document: self.document,
+                offset,
+                reason,
+            }
+        }
+    }
+
+    fn truncated(err: io::Error) -> JacError {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            JacError::UnexpectedEof
+        } else {
+            err.into()
+        }
+    }
+
+    impl Iterator for BsonStream {
+        type Item = Result<Map<String, Value>>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.failed {
+                return None;
+            }
+            let result = self.read_document();
+            self.failed = result.is_err();
+            result.transpose()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IngestConfig, InputSource, WrapperConfig};
+    use jac_format::{JacError, Result};
+    use serde_json::{Map, Value};
+    use std::io::Cursor;
+
+    fn read_all(source: InputSource, ingest: IngestConfig) -> Result<Vec<Map<String, Value>>> {
+        source
+            .into_record_stream(&WrapperConfig::None, ingest)?
+            .collect()
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn message_pack_maps_become_records() {
+        use serde_json::json;
+
+        let records = [
+            json!({"id": 1, "user": "alice", "tags": ["a", "b"]}),
+            json!({"id": 2, "user": "bob", "score": 0.5, "extra": null}),
+        ];
+        let mut bytes = Vec::new();
+        for record in &records {
+            bytes.extend(rmp_serde::to_vec_named(record).unwrap());
+        }
+        let source = || InputSource::MessagePackReader(Box::new(Cursor::new(bytes.clone())));
+
+        let decoded = read_all(source(), IngestConfig::default()).unwrap();
+        let decoded: Vec<Value> = decoded.into_iter().map(Value::Object).collect();
+        assert_eq!(decoded, records);
+
+        let small = IngestConfig {
+            max_record_bytes: Some(16),
+            ..IngestConfig::default()
+        };
+        assert!(matches!(
+            read_all(source(), small),
+            Err(JacError::LimitExceeded(_))
+        ));
+
+        // A scalar after the first map is reported with its position.
+        let mut bad = rmp_serde::to_vec_named(&records[0]).unwrap();
+        let first_len = bad.len() as u64;
+        bad.extend(rmp_serde::to_vec(&7).unwrap());
+        match read_all(
+            InputSource::MessagePackReader(Box::new(Cursor::new(bad))),
+            IngestConfig::default(),
+        ) {
+            Err(JacError::DocumentParse {
+                document, offset, ..
+            }) => assert_eq!((document, offset), (2, first_len)),
+            other => panic!("expected DocumentParse, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "bson")]
+    #[test]
+    fn bson_documents_become_extended_json_records() {
+        use crate::OversizedRecordPolicy;
+        use bson::{doc, oid::ObjectId};
+        use serde_json::json;
+
+        let id = ObjectId::new();
+        let mut bytes = Vec::new();
+        doc! {"_id": id, "n": 1_i32, "name": "a"}
+            .to_writer(&mut bytes)
+            .unwrap();
+        doc! {"n": 2_i64, "blob": "x".repeat(200)}
+            .to_writer(&mut bytes)
+            .unwrap();
+        doc! {"n": 3.5, "nested": {"ok": true}}
+            .to_writer(&mut bytes)
+            .unwrap();
+
+        let stream = InputSource::BsonReader(Box::new(Cursor::new(bytes.clone())))
+            .into_record_stream(
+                &WrapperConfig::None,
+                IngestConfig {
+                    max_record_bytes: Some(128),
+                    oversized: OversizedRecordPolicy::Skip,
+                    ..IngestConfig::default()
+                },
+            )
+            .unwrap();
+        let counters = stream.counters();
+        let records: Vec<Value> = stream.map(|r| Value::Object(r.unwrap())).collect();
+        assert_eq!(
+            records,
+            vec![
+                json!({"_id": {"$oid": id.to_hex()}, "n": 1, "name": "a"}),
+                json!({"n": 3.5, "nested": {"ok": true}}),
+            ]
+        );
+        assert_eq!(counters.oversized_skipped(), 1);
+
+        bytes.truncate(bytes.len() - 3);
+        let result = read_all(
+            InputSource::BsonReader(Box::new(Cursor::new(bytes))),
+            IngestConfig::default(),
+        );
+        assert!(matches!(result, Err(JacError::UnexpectedEof)));
+    }
+
+    #[cfg(not(all(feature = "msgpack", feature = "bson")))]
+    #[test]
+    fn binary_input_requires_feature() {
+        #[cfg(not(feature = "msgpack"))]
+        assert!(matches!(
+            read_all(
+                InputSource::MessagePackReader(Box::new(Cursor::new(vec![0x80]))),
+                IngestConfig::default()
+            ),
+            Err(JacError::UnsupportedFeature(_))
+        ));
+        #[cfg(not(feature = "bson"))]
+        assert!(matches!(
+            read_all(
+                InputSource::BsonReader(Box::new(Cursor::new(vec![5, 0, 0, 0, 0]))),
+                IngestConfig::default()
+            ),
+            Err(JacError::UnsupportedFeature(_))
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+//! MessagePack and CBOR encoders for decompressed records.
+//!
+//! [`DecompressFormat::MessagePack`](crate::DecompressFormat) and
+//! [`DecompressFormat::Cbor`](crate::DecompressFormat) write each record as one
+//! top-level map, back to back with no framing (a CBOR sequence in the sense of
+//! RFC 8742), so binary-consuming services can skip the JSON text hop. They
+//! need the `msgpack` and `cbor` features respectively.
+
+use jac_format::Result;
+use serde_json::Value;
+use std::io::Write;
+
+/// Writes one record in a binary encoding.
+pub(crate) type Encoder = fn(&mut dyn Write, &Value) -> Result<()>;
+
+/// Encoder writing records as MessagePack maps.
+#[cfg(feature = "msgpack")]
+pub(crate) fn message_pack_encoder() -> Result<Encoder> {
+    Ok(|writer, record| {
+        rmp_serde::encode::write(&mut *writer, record)
+            .map_err(|err| jac_format::JacError::Internal(format!("MessagePack encode: {}", err)))
+    })
+}
+
+/// Without the `msgpack` feature MessagePack output is rejected up front.
+#[cfg(not(feature = "msgpack"))]
+pub(crate) fn message_pack_encoder() -> Result<Encoder> {
+    Err(jac_format::JacError::UnsupportedFeature(
+        "MessagePack output requires the `msgpack` feature".to_string(),
+    ))
+}
+
+/// Encoder writing records as CBOR maps.
+#[cfg(feature = "cbor")]
+pub(crate) fn cbor_encoder() -> Result<Encoder> {
+    Ok(|writer, record| {
+        ciborium::into_writer(record, writer).map_err(|err| match err {
+            ciborium::ser::Error::Io(err) => err.into(),
+            ciborium::ser::Error::Value(msg) => {
+                jac_format::JacError::Internal(format!("CBOR encode: {}", msg))
+            }
+        })
+    })
+}
+
+/// Without the `cbor` feature CBOR output is rejected up front.
+#[cfg(not(feature = "cbor"))]
+pub(crate) fn cbor_encoder() -> Result<Encoder> {
+    Err(jac_format::JacError::UnsupportedFeature(
+        "CBOR output requires the `cbor` feature".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        execute_compress, execute_decompress, CompressRequest, DecompressFormat, DecompressRequest,
+        InputSource, JacInput, OutputSink,
+    };
+    use serde_json::{json, Value};
+    use std::io::Cursor;
+    use std::path::{Path, PathBuf};
+
+    fn records() -> Vec<Value> {
+        (0..5)
+            .map(|i| json!({"id": i, "name": format!("n{}", i), "ratio": 0.125 + i as f64 / 4.0}))
+            .collect()
+    }
+
+    fn compress_records(dir: &Path) -> PathBuf {
+        let ndjson: String = records().iter().map(|r| r.to_string() + "\n").collect();
+        let path = dir.join("records.jac");
+        execute_compress(
+            CompressRequest::builder()
+                .input(InputSource::NdjsonReader(Box::new(Cursor::new(
+                    ndjson.into_bytes(),
+                ))))
+                .output(OutputSink::Path(path.clone()))
+                .block_target_records(2)
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+        path
+    }
+
+    fn unpack(input: &Path, output: &Path, format: DecompressFormat) -> crate::Result<u64> {
+        let request = DecompressRequest::builder()
+            .input(JacInput::Path(input.to_path_buf()))
+            .output(OutputSink::Path(output.to_path_buf()))
+            .format(format)
+            .build()?;
+        execute_decompress(request).map(|summary| summary.records_written)
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn unpack_writes_message_pack_maps() {
+        let dir = tempfile::tempdir().unwrap();
+        let jac = compress_records(dir.path());
+        let out = dir.path().join("records.msgpack");
+        assert_eq!(
+            unpack(&jac, &out, DecompressFormat::MessagePack).unwrap(),
+            5
+        );
+
+        let bytes = std::fs::read(&out).unwrap();
+        let mut cursor = Cursor::new(bytes.as_slice());
+        let mut decoded = Vec::new();
+        while (cursor.position() as usize) < bytes.len() {
+            decoded.push(rmp_serde::from_read::<_, Value>(&mut cursor).unwrap());
+        }
+        assert_eq!(decoded, records());
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn unpack_writes_cbor_sequence() {
+        let dir = tempfile::tempdir().unwrap();
+        let jac = compress_records(dir.path());
+        let out = dir.path().join("records.cbor");
+        assert_eq!(unpack(&jac, &out, DecompressFormat::Cbor).unwrap(), 5);
+
+        let bytes = std::fs::read(&out).unwrap();
+        let mut cursor = Cursor::new(bytes.as_slice());
+        let mut decoded = Vec::new();
+        while (cursor.position() as usize) < bytes.len() {
+            decoded.push(ciborium::from_reader::<Value, _>(&mut cursor).unwrap());
+        }
+        assert_eq!(decoded, records());
+    }
+
+    #[cfg(not(all(feature = "msgpack", feature = "cbor")))]
+    #[test]
+    fn binary_output_requires_feature() {
+        use crate::JacError;
+
+        let dir = tempfile::tempdir().unwrap();
+        let jac = compress_records(dir.path());
+        let out = dir.path().join("records.bin");
+        #[cfg(not(feature = "msgpack"))]
+        assert!(matches!(
+            unpack(&jac, &out, DecompressFormat::MessagePack),
+            Err(JacError::UnsupportedFeature(_))
+        ));
+        #[cfg(not(feature = "cbor"))]
+        assert!(matches!(
+            unpack(&jac, &out, DecompressFormat::Cbor),
+            Err(JacError::UnsupportedFeature(_))
+        ));
+        // The check runs before the output file is created.
+        assert!(!out.exists());
+    }
+}
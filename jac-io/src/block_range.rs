@@ -0,0 +1,193 @@
+//! Selecting blocks by number.
+//!
+//! `N`, `N-M`, `N-` and `-M` (1-based, inclusive) select blocks of an archive,
+//! as taken by `jac cat --blocks` and `jac unpack --blocks`. A [`BlockRange`]
+//! is parsed without knowing the file and checked against its block count when
+//! applied; [`DecompressRequest::blocks`](crate::DecompressRequest::blocks)
+//! turns it into a record window so blocks before the range are skipped
+//! without being decoded.
+
+use crate::JacReader;
+use jac_format::{JacError, Result};
+use std::io::{Read, Seek};
+use std::str::FromStr;
+
+/// Blocks to read, as zero-based indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockRange {
+    /// One block
+    Single(usize),
+    /// Blocks `start..=end`; `None` runs to the last block
+    Range {
+        /// First block
+        start: usize,
+        /// Last block, inclusive
+        end: Option<usize>,
+    },
+}
+
+impl BlockRange {
+    /// First and last selected block (inclusive) in a file of `block_count`
+    /// blocks; fails when the range reaches past the end.
+    pub fn bounds(&self, block_count: usize) -> Result<(usize, usize)> {
+        if block_count == 0 {
+            return Err(range_error(
+                "Block range requested, but file contains no blocks".to_string(),
+            ));
+        }
+
+        match *self {
+            BlockRange::Single(idx) => {
+                if idx >= block_count {
+                    return Err(range_error(format!(
+                        "Block {} exceeds file block count ({} blocks)",
+                        idx + 1,
+                        block_count
+                    )));
+                }
+                Ok((idx, idx))
+            }
+            BlockRange::Range { start, end } => {
+                if start >= block_count {
+                    return Err(range_error(format!(
+                        "Block range start {} exceeds file block count ({} blocks)",
+                        start + 1,
+                        block_count
+                    )));
+                }
+                let end_idx = end.unwrap_or(block_count - 1);
+                if end_idx >= block_count {
+                    return Err(range_error(format!(
+                        "Block range end {} exceeds file block count ({} blocks)",
+                        end_idx + 1,
+                        block_count
+                    )));
+                }
+                if start > end_idx {
+                    return Err(range_error(format!(
+                        "Invalid block range: start ({}) > end ({})",
+                        start + 1,
+                        end_idx + 1
+                    )));
+                }
+                Ok((start, end_idx))
+            }
+        }
+    }
+
+    /// First record and number of records in the selected blocks of `reader`.
+    /// Leaves the reader's block cursor where iteration ended.
+    pub fn record_window<R: Read + Seek>(&self, reader: &mut JacReader<R>) -> Result<(u64, u64)> {
+        let counts = reader
+            .blocks()
+            .map(|block| block.map(|block| block.record_count as u64))
+            .collect::<Result<Vec<_>>>()?;
+        let (start, end) = self.bounds(counts.len())?;
+        let first = counts[..start].iter().sum();
+        let len = counts[start..=end].iter().sum();
+        Ok((first, len))
+    }
+}
+
+impl FromStr for BlockRange {
+    type Err = JacError;
+
+    /// Parse `N`, `N-M`, `N-` or `-M` with 1-based block numbers.
+    fn from_str(spec: &str) -> Result<Self> {
+        let trimmed = spec.trim();
+        if trimmed.is_empty() {
+            return Err(range_error("Block range cannot be empty".to_string()));
+        }
+
+        if let Ok(n) = trimmed.parse::<usize>() {
+            return Ok(BlockRange::Single(block_index(n)?));
+        }
+
+        let Some((start_part, end_part)) = trimmed.split_once('-') else {
+            return Err(range_error(format!(
+                "Invalid block range syntax: '{}'. Expected 'N' or 'N-M'",
+                trimmed
+            )));
+        };
+        let start = if start_part.is_empty() {
+            0
+        } else {
+            block_index(parse_number(start_part)?)?
+        };
+        let end = if end_part.is_empty() {
+            None
+        } else {
+            Some(block_index(parse_number(end_part)?)?)
+        };
+
+        if let Some(end) = end {
+            if start > end {
+                return Err(range_error(format!(
+                    "Invalid block range: start ({}) > end ({})",
+                    start + 1,
+                    end + 1
+                )));
+            }
+        }
+        Ok(BlockRange::Range { start, end })
+    }
+}
+
+fn parse_number(part: &str) -> Result<usize> {
+    part.parse::<usize>()
+        .map_err(|_| range_error(format!("Invalid block number '{}'", part)))
+}
+
+fn block_index(number: usize) -> Result<usize> {
+    number
+        .checked_sub(1)
+        .ok_or_else(|| range_error("Block numbers start from 1".to_string()))
+}
+
+fn range_error(message: String) -> JacError {
+    JacError::InvalidArgument(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_blocks_and_ranges() {
+        assert_eq!("3".parse::<BlockRange>().unwrap(), BlockRange::Single(2));
+        assert_eq!(
+            "5-".parse::<BlockRange>().unwrap(),
+            BlockRange::Range {
+                start: 4,
+                end: None
+            }
+        );
+        assert_eq!(
+            " -2 ".parse::<BlockRange>().unwrap(),
+            BlockRange::Range {
+                start: 0,
+                end: Some(1)
+            }
+        );
+        for bad in ["", "0", "3-2", "a-b", "1-0", "x"] {
+            let result = bad.parse::<BlockRange>();
+            assert!(
+                matches!(result, Err(JacError::InvalidArgument(_))),
+                "{bad:?} should fail"
+            );
+        }
+    }
+
+    #[test]
+    fn bounds_normalize_open_ends_and_reject_overruns() {
+        let range = BlockRange::Range {
+            start: 1,
+            end: None,
+        };
+        assert_eq!(range.bounds(4).unwrap(), (1, 3));
+        assert!(matches!(range.bounds(1), Err(JacError::InvalidArgument(_))));
+        assert!(range.bounds(0).is_err());
+        assert!(BlockRange::Single(4).bounds(4).is_err());
+        assert_eq!(BlockRange::Single(3).bounds(4).unwrap(), (3, 3));
+    }
+}
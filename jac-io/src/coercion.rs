@@ -0,0 +1,262 @@
+//! Per-field type coercion for smoothing schema drift.
+//!
+//! A field whose values change type between records (an id that is usually an
+//! integer but sometimes a string, a flag sent as `"true"`) fragments its
+//! column encoding. A [`CoercionPolicy`] rewrites top-level field values into
+//! one type before encoding, and [`CoercionMetrics`] reports how often that
+//! happened and how often the raw input type drifted.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Rewrite applied to one field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoercionRule {
+    /// Convert numbers and booleans to their JSON text
+    ToString,
+    /// Convert numbers and booleans to strings once the field has carried a
+    /// string; values written before the first string are left as they were
+    PromoteToStringOnDrift,
+    /// Convert `"true"`/`"false"` (ASCII case-insensitive) to booleans
+    StringToBool,
+    /// Convert strings holding a JSON number to numbers
+    StringToNumber,
+}
+
+/// Coercion rules keyed by top-level field name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoercionPolicy {
+    /// Rule applied to each listed field; unlisted fields are untouched.
+    pub fields: BTreeMap<String, CoercionRule>,
+}
+
+impl CoercionPolicy {
+    /// Add or replace the rule for `field`.
+    pub fn with_rule(mut self, field: impl Into<String>, rule: CoercionRule) -> Self {
+        self.fields.insert(field.into(), rule);
+        self
+    }
+
+    /// Whether no field has a rule.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+/// Coercion counters for one field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FieldCoercionMetrics {
+    /// Values rewritten by the field's rule.
+    pub values_coerced: u64,
+    /// Times the raw input type differed from the previous non-null value's type.
+    pub drift_events: u64,
+}
+
+/// Coercion counters for a compression request, keyed by field name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoercionMetrics {
+    /// Per-field counters; only fields named in the policy appear.
+    pub fields: BTreeMap<String, FieldCoercionMetrics>,
+}
+
+impl CoercionMetrics {
+    /// Total values rewritten across all fields.
+    pub fn values_coerced(&self) -> u64 {
+        self.fields.values().map(|m| m.values_coerced).sum()
+    }
+
+    /// Total drift events across all fields.
+    pub fn drift_events(&self) -> u64 {
+        self.fields.values().map(|m| m.drift_events).sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+fn kind_of(value: &Value) -> Option<ValueKind> {
+    match value {
+        Value::Null => None,
+        Value::Bool(_) => Some(ValueKind::Bool),
+        Value::Number(_) => Some(ValueKind::Number),
+        Value::String(_) => Some(ValueKind::String),
+        Value::Array(_) => Some(ValueKind::Array),
+        Value::Object(_) => Some(ValueKind::Object),
+    }
+}
+
+#[derive(Default)]
+struct FieldState {
+    last_kind: Option<ValueKind>,
+    seen_string: bool,
+}
+
+/// Applies a [`CoercionPolicy`] to records in stream order.
+pub(crate) struct Coercer {
+    rules: Vec<(String, CoercionRule, FieldState)>,
+    metrics: Arc<Mutex<CoercionMetrics>>,
+}
+
+impl Coercer {
+    /// Build a coercer, or `None` for an empty policy.
+    pub(crate) fn new(
+        policy: &CoercionPolicy,
+        metrics: Arc<Mutex<CoercionMetrics>>,
+    ) -> Option<Self> {
+        if policy.is_empty() {
+            return None;
+        }
+        let rules = policy
+            .fields
+            .iter()
+            .map(|(field, rule)| (field.clone(), *rule, FieldState::default()))
+            .collect();
+        Some(Self { rules, metrics })
+    }
+
+    pub(crate) fn apply(&mut self, record: &mut Map<String, Value>) {
+        let mut updates: Vec<(&str, bool, bool)> = Vec::new();
+        for (field, rule, state) in &mut self.rules {
+            let Some(value) = record.get_mut(field.as_str()) else {
+                continue;
+            };
+            let drifted = match kind_of(value) {
+                Some(kind) => {
+                    let drifted = state.last_kind.is_some_and(|last| last != kind);
+                    state.last_kind = Some(kind);
+                    state.seen_string |= kind == ValueKind::String;
+                    drifted
+                }
+                None => false,
+            };
+            let coerced = match coerce(value, *rule, state.seen_string) {
+                Some(new_value) => {
+                    *value = new_value;
+                    true
+                }
+                None => false,
+            };
+            if drifted || coerced {
+                updates.push((field.as_str(), drifted, coerced));
+            }
+        }
+
+        if updates.is_empty() {
+            return;
+        }
+        let mut metrics = self
+            .metrics
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (field, drifted, coerced) in updates {
+            let entry = metrics.fields.entry(field.to_string()).or_default();
+            entry.drift_events += u64::from(drifted);
+            entry.values_coerced += u64::from(coerced);
+        }
+    }
+}
+
+pub(crate) fn coerce(value: &Value, rule: CoercionRule, seen_string: bool) -> Option<Value> {
+    match (rule, value) {
+        (CoercionRule::ToString, Value::Number(n)) => Some(Value::String(n.to_string())),
+        (CoercionRule::ToString, Value::Bool(b)) => Some(Value::String(b.to_string())),
+        (CoercionRule::PromoteToStringOnDrift, Value::Number(n)) if seen_string => {
+            Some(Value::String(n.to_string()))
+        }
+        (CoercionRule::PromoteToStringOnDrift, Value::Bool(b)) if seen_string => {
+            Some(Value::String(b.to_string()))
+        }
+        (CoercionRule::StringToBool, Value::String(s)) => {
+            let trimmed = s.trim();
+            if trimmed.eq_ignore_ascii_case("true") {
+                Some(Value::Bool(true))
+            } else if trimmed.eq_ignore_ascii_case("false") {
+                Some(Value::Bool(false))
+            } else {
+                None
+            }
+        }
+        (CoercionRule::StringToNumber, Value::String(s)) => {
+            serde_json::from_str::<Number>(s.trim())
+                .ok()
+                .map(Value::Number)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(
+        policy: &CoercionPolicy,
+        values: &[Value],
+        field: &str,
+    ) -> (Vec<Value>, CoercionMetrics) {
+        let metrics = Arc::new(Mutex::new(CoercionMetrics::default()));
+        let mut coercer = Coercer::new(policy, Arc::clone(&metrics)).expect("policy");
+        let out = values
+            .iter()
+            .map(|value| {
+                let mut record = Map::new();
+                record.insert(field.to_string(), value.clone());
+                coercer.apply(&mut record);
+                record[field].clone()
+            })
+            .collect();
+        let metrics = metrics.lock().unwrap().clone();
+        (out, metrics)
+    }
+
+    #[test]
+    fn promote_on_drift_only_rewrites_values_after_first_string() {
+        let policy =
+            CoercionPolicy::default().with_rule("id", CoercionRule::PromoteToStringOnDrift);
+        let (out, metrics) = run(
+            &policy,
+            &[json!(1), json!(2), json!("x3"), json!(4), Value::Null],
+            "id",
+        );
+        assert_eq!(
+            out,
+            vec![json!(1), json!(2), json!("x3"), json!("4"), Value::Null]
+        );
+        let field = metrics.fields["id"];
+        assert_eq!(field.values_coerced, 1);
+        // int -> string, then string -> int (raw types).
+        assert_eq!(field.drift_events, 2);
+    }
+
+    #[test]
+    fn string_rules_parse_booleans_and_numbers() {
+        let bools = CoercionPolicy::default().with_rule("flag", CoercionRule::StringToBool);
+        let (out, metrics) = run(
+            &bools,
+            &[json!("TRUE"), json!(" false "), json!("maybe")],
+            "flag",
+        );
+        assert_eq!(out, vec![json!(true), json!(false), json!("maybe")]);
+        assert_eq!(metrics.values_coerced(), 2);
+        assert_eq!(metrics.drift_events(), 0);
+
+        let numbers = CoercionPolicy::default().with_rule("n", CoercionRule::StringToNumber);
+        let (out, _) = run(&numbers, &[json!("42"), json!("1.5"), json!("n/a")], "n");
+        assert_eq!(out, vec![json!(42), json!(1.5), json!("n/a")]);
+
+        let strings = CoercionPolicy::default().with_rule("v", CoercionRule::ToString);
+        let (out, _) = run(&strings, &[json!(7), json!(true), json!([1])], "v");
+        assert_eq!(out, vec![json!("7"), json!("true"), json!([1])]);
+    }
+}
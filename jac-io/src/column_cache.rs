@@ -0,0 +1,221 @@
+//! Byte-budgeted cache of decoded columns.
+//!
+//! Interactive exploration tends to project or filter the same hot blocks
+//! over and over. With [`JacReader::set_column_cache`](crate::JacReader::set_column_cache)
+//! a reader keeps decoded columns keyed by (block offset, field) and evicts
+//! the least recently used ones once their estimated in-memory size exceeds
+//! the budget, so repeated projections skip decompression entirely.
+
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Decoded values of one field in one block, one entry per record.
+pub type Column = Arc<Vec<Option<Value>>>;
+
+/// Counters describing a reader's column cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColumnCacheStats {
+    /// Byte budget the cache was configured with (0 when disabled)
+    pub budget_bytes: usize,
+    /// Estimated size of the columns currently cached
+    pub cached_bytes: usize,
+    /// Columns currently cached
+    pub entries: usize,
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that had to decode the block
+    pub misses: u64,
+    /// Columns dropped to stay within the budget
+    pub evictions: u64,
+}
+
+struct CachedColumn {
+    column: Column,
+    bytes: usize,
+    last_used: u64,
+}
+
+/// Least-recently-used map from (block offset, field) to decoded columns.
+pub(crate) struct ColumnCache {
+    entries: HashMap<(u64, String), CachedColumn>,
+    /// Keys by last use, oldest first
+    recency: BTreeMap<u64, (u64, String)>,
+    tick: u64,
+    stats: ColumnCacheStats,
+}
+
+impl ColumnCache {
+    pub(crate) fn new(budget_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            stats: ColumnCacheStats {
+                budget_bytes,
+                ..ColumnCacheStats::default()
+            },
+        }
+    }
+
+    pub(crate) fn stats(&self) -> ColumnCacheStats {
+        ColumnCacheStats {
+            entries: self.entries.len(),
+            ..self.stats
+        }
+    }
+
+    /// Cached column for `field` of the block at `offset`, marking it recently used.
+    pub(crate) fn get(&mut self, offset: u64, field: &str) -> Option<Column> {
+        let key = (offset, field.to_string());
+        let Some(entry) = self.entries.get_mut(&key) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        self.stats.hits += 1;
+        self.tick += 1;
+        self.recency.remove(&entry.last_used);
+        entry.last_used = self.tick;
+        self.recency.insert(self.tick, key);
+        Some(Arc::clone(&entry.column))
+    }
+
+    /// Cache `column`, evicting older columns as needed. Columns larger than
+    /// the whole budget are not cached.
+    pub(crate) fn insert(&mut self, offset: u64, field: &str, column: &Column) {
+        let bytes = column_bytes(column);
+        if bytes > self.stats.budget_bytes {
+            return;
+        }
+        let key = (offset, field.to_string());
+        if let Some(previous) = self.entries.remove(&key) {
+            self.recency.remove(&previous.last_used);
+            self.stats.cached_bytes -= previous.bytes;
+        }
+        while self.stats.cached_bytes + bytes > self.stats.budget_bytes {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some(evicted) = self.entries.remove(&oldest) {
+                self.stats.cached_bytes -= evicted.bytes;
+                self.stats.evictions += 1;
+            }
+        }
+        self.tick += 1;
+        self.recency.insert(self.tick, key.clone());
+        self.entries.insert(
+            key,
+            CachedColumn {
+                column: Arc::clone(column),
+                bytes,
+                last_used: self.tick,
+            },
+        );
+        self.stats.cached_bytes += bytes;
+    }
+}
+
+/// Estimated in-memory size of a decoded column.
+fn column_bytes(column: &[Option<Value>]) -> usize {
+    std::mem::size_of_val(column) + column.iter().flatten().map(value_heap_bytes).sum::<usize>()
+}
+
+/// Heap bytes owned by `value`, excluding the `Value` itself.
+fn value_heap_bytes(value: &Value) -> usize {
+    match value {
+        Value::Null | Value::Bool(_) | Value::Number(_) => 0,
+        Value::String(s) => s.len(),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| std::mem::size_of::<Value>() + value_heap_bytes(item))
+            .sum(),
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, item)| key.len() + std::mem::size_of::<Value>() + value_heap_bytes(item))
+            .sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        execute_compress, CompressRequest, DecompressOpts, InputSource, JacReader, OutputSink,
+    };
+    use serde_json::json;
+    use std::io::Cursor;
+
+    fn column(values: Vec<Value>) -> Column {
+        Arc::new(values.into_iter().map(Some).collect())
+    }
+
+    #[test]
+    fn evicts_least_recently_used_columns_within_budget() {
+        let a = column(vec![json!(1), json!(2)]);
+        let per_column = column_bytes(&a);
+        let mut cache = ColumnCache::new(per_column * 2);
+        cache.insert(10, "a", &a);
+        cache.insert(10, "b", &column(vec![json!(3), json!(4)]));
+        assert!(cache.get(10, "a").is_some());
+        // "b" is now the oldest and makes room for "c".
+        cache.insert(20, "c", &column(vec![json!(5), json!(6)]));
+        assert!(cache.get(10, "b").is_none());
+        assert_eq!(cache.get(10, "a").unwrap(), a);
+        assert!(cache.get(20, "c").is_some());
+
+        // A column bigger than the budget is left out.
+        cache.insert(30, "wide", &column(vec![json!("x".repeat(4096))]));
+        assert!(cache.get(30, "wide").is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.evictions), (2, 1));
+        assert_eq!((stats.hits, stats.misses), (3, 2));
+        assert_eq!(stats.cached_bytes, per_column * 2);
+    }
+
+    #[test]
+    fn reader_reuses_cached_columns_across_projections() {
+        let ndjson: String = (0..120)
+            .map(|i| json!({"id": i, "user": format!("u{}", i % 7)}).to_string() + "\n")
+            .collect();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cached.jac");
+        execute_compress(
+            CompressRequest::builder()
+                .input(InputSource::NdjsonReader(Box::new(Cursor::new(
+                    ndjson.into_bytes(),
+                ))))
+                .output(OutputSink::Path(path.clone()))
+                .block_target_records(40)
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        let mut reader = JacReader::new(file, DecompressOpts::default()).unwrap();
+        reader.set_column_cache(1 << 20);
+        let project = |reader: &mut JacReader<std::fs::File>| {
+            reader
+                .multi_projection_stream(&["user", "id"])
+                .unwrap()
+                .collect::<crate::Result<Vec<_>>>()
+                .unwrap()
+        };
+        let first = project(&mut reader);
+        let second = project(&mut reader);
+        assert_eq!(first, second);
+        assert_eq!(first[8], vec![Some(json!("u1")), Some(json!(8))]);
+
+        let users: Vec<_> = reader
+            .projection_stream("user".to_string())
+            .unwrap()
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(users.len(), 120);
+
+        let stats = reader.column_cache_stats();
+        assert_eq!((stats.entries, stats.misses, stats.hits), (6, 6, 9));
+        assert!(stats.cached_bytes > 0 && stats.cached_bytes <= stats.budget_bytes);
+    }
+}
@@ -0,0 +1,378 @@
+//! Decoding blocks of one file from several threads.
+//!
+//! [`JacReader`] seeks a single stream, so its methods take `&mut self`. A
+//! [`ConcurrentJacReader`] lists the file's blocks once when opened and then
+//! reads each block with positional reads ([`ReadAt`]) that share no cursor;
+//! every method takes `&self`, so threads can decode different blocks of one
+//! open file at the same time.
+
+use crate::reader::{BlockCursor, BlockHandle, JacReader};
+use jac_codec::{BlockDecoder, DecompressOpts};
+use jac_format::{BlockHeaderFormat, FieldNameTable, FileHeader, JacError, Result};
+use serde_json::{Map, Value};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Byte source that can be read at arbitrary offsets through a shared
+/// reference, e.g. from several threads at once.
+pub trait ReadAt: Send + Sync {
+    /// Total size of the source in bytes.
+    fn size(&self) -> io::Result<u64>;
+
+    /// Fill `buf` with the bytes starting at `offset`.
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()>;
+}
+
+#[cfg(unix)]
+impl ReadAt for File {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        std::os::unix::fs::FileExt::read_exact_at(self, buf, offset)
+    }
+}
+
+#[cfg(windows)]
+impl ReadAt for File {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    fn read_exact_at(&self, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+        use std::os::windows::fs::FileExt;
+        while !buf.is_empty() {
+            match self.seek_read(buf, offset) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => {
+                    buf = &mut buf[n..];
+                    offset += n as u64;
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ReadAt for Vec<u8> {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        let start = usize::try_from(offset).unwrap_or(usize::MAX);
+        let bytes = start
+            .checked_add(buf.len())
+            .and_then(|end| self.get(start..end))
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        buf.copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Reader over a shared byte source whose blocks can be decoded concurrently.
+///
+/// Blocks are listed (headers only) when the reader is created; strict and
+/// permissive options behave as for [`JacReader`], so in permissive mode
+/// unreadable blocks are left out of [`ConcurrentJacReader::blocks`].
+pub struct ConcurrentJacReader<S = File> {
+    source: S,
+    file_header: FileHeader,
+    blocks: Vec<BlockHandle>,
+    /// Block header format of each archive (concatenated files hold several),
+    /// with all of its field names, by offset of the archive's first block
+    formats: Vec<(u64, Arc<BlockHeaderFormat>)>,
+    opts: DecompressOpts,
+}
+
+#[cfg(any(unix, windows))]
+impl ConcurrentJacReader<File> {
+    /// Open a file for concurrent block decoding.
+    pub fn open(path: impl AsRef<Path>, opts: DecompressOpts) -> Result<Self> {
+        Self::new(File::open(path)?, opts)
+    }
+}
+
+impl<S: ReadAt> ConcurrentJacReader<S> {
+    /// Read the file header and list every block of `source`.
+    pub fn new(source: S, opts: DecompressOpts) -> Result<Self> {
+        let len = source.size()?;
+        let cursor = SourceCursor {
+            source: &source,
+            pos: 0,
+            len,
+        };
+        let mut reader = JacReader::new(cursor, opts)?;
+        let mut cursor = BlockCursor::new(&reader);
+        let mut blocks = Vec::new();
+        let mut formats = Vec::new();
+        let mut archive = ArchiveNames::of(&reader);
+        while let Some(block) = reader.next_block_handle(&mut cursor) {
+            blocks.push(block?);
+            if reader.names_start() == archive.start {
+                archive.catch_up(reader.block_format());
+            } else {
+                formats.push(archive.finish()?);
+                archive = ArchiveNames::of(&reader);
+            }
+        }
+        formats.push(archive.finish()?);
+        let file_header = reader.file_header().clone();
+        let opts = reader.decode_opts().clone();
+        drop(reader);
+
+        Ok(Self {
+            source,
+            file_header,
+            blocks,
+            formats,
+            opts,
+        })
+    }
+
+    /// Access the decoded file header
+    pub fn file_header(&self) -> &FileHeader {
+        &self.file_header
+    }
+
+    /// Blocks in file order.
+    pub fn blocks(&self) -> &[BlockHandle] {
+        &self.blocks
+    }
+
+    /// Total records across all blocks.
+    pub fn record_count(&self) -> u64 {
+        self.blocks
+            .iter()
+            .map(|block| block.record_count as u64)
+            .sum()
+    }
+
+    /// Decode a block, verifying CRC and limits.
+    pub fn decode_block(&self, block: &BlockHandle) -> Result<BlockDecoder> {
+        let mut bytes = vec![0u8; block.size];
+        self.source.read_exact_at(&mut bytes, block.offset)?;
+        // Listing the blocks collected every field name of each archive, so
+        // the block's archive table resolves it without being changed.
+        let archive = self
+            .formats
+            .partition_point(|(start, _)| *start <= block.offset)
+            .saturating_sub(1);
+        BlockDecoder::new_with_complete_format(&bytes, &self.opts, &self.formats[archive].1)
+    }
+
+    /// Decode the records of the `index`-th block.
+    pub fn decode_records(&self, index: usize) -> Result<Vec<Map<String, Value>>> {
+        let block = self.blocks.get(index).ok_or_else(|| {
+            JacError::Internal(format!(
+                "block {} out of range ({} blocks)",
+                index,
+                self.blocks.len()
+            ))
+        })?;
+        self.decode_block(block)?.decode_records()
+    }
+
+    /// Anomalies tolerated so far in permissive mode, oldest first.
+    pub fn warnings(&self) -> Vec<String> {
+        self.opts.warnings.snapshot()
+    }
+
+    /// Release the underlying source.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+/// Field names of the archive being listed, gathered as its blocks define them.
+struct ArchiveNames {
+    start: u64,
+    header_crc: bool,
+    names: Option<Vec<String>>,
+}
+
+impl ArchiveNames {
+    /// Start gathering at the archive `reader` is in.
+    fn of<R: Read + Seek>(reader: &JacReader<R>) -> Self {
+        let format = reader.block_format();
+        Self {
+            start: reader.names_start(),
+            header_crc: format.header_crc,
+            names: format
+                .field_names
+                .as_ref()
+                .map(|table| table.names().to_vec()),
+        }
+    }
+
+    /// Copy the names defined since the last call.
+    fn catch_up(&mut self, format: &BlockHeaderFormat) {
+        if let (Some(names), Some(table)) = (self.names.as_mut(), format.field_names.as_ref()) {
+            if let Some(new) = table.names().get(names.len()..) {
+                names.extend_from_slice(new);
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(u64, Arc<BlockHeaderFormat>)> {
+        let format = BlockHeaderFormat {
+            field_names: self.names.map(FieldNameTable::from_names).transpose()?,
+            header_crc: self.header_crc,
+        };
+        Ok((self.start, Arc::new(format)))
+    }
+}
+
+/// `Read + Seek` view of a [`ReadAt`] source, used to list blocks.
+struct SourceCursor<'a, S> {
+    source: &'a S,
+    pos: u64,
+    len: u64,
+}
+
+impl<S: ReadAt> Read for SourceCursor<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = (buf.len() as u64).min(self.len.saturating_sub(self.pos)) as usize;
+        if n > 0 {
+            self.source.read_exact_at(&mut buf[..n], self.pos)?;
+            self.pos += n as u64;
+        }
+        Ok(n)
+    }
+}
+
+impl<S> Seek for SourceCursor<'_, S> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        };
+        self.pos = target.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "seek before start of source")
+        })?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{execute_compress, CompressOptions, CompressRequest, InputSource, OutputSink};
+    use serde_json::json;
+
+    #[test]
+    fn threads_decode_blocks_of_one_file_concurrently() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("concurrent.jac");
+        let ndjson: String = (0..200)
+            .map(|i| json!({"id": i, "even": i % 2 == 0}).to_string() + "\n")
+            .collect();
+        execute_compress(
+            CompressRequest::builder()
+                .input(InputSource::NdjsonReader(Box::new(io::Cursor::new(
+                    ndjson.into_bytes(),
+                ))))
+                .output(OutputSink::Path(path.clone()))
+                .block_target_records(25)
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+
+        let reader = ConcurrentJacReader::open(&path, DecompressOpts::default()).unwrap();
+        assert_eq!(reader.blocks().len(), 8);
+        assert_eq!(reader.record_count(), 200);
+        let mut decoded: Vec<Vec<u64>> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..4)
+                .map(|worker| {
+                    let reader = &reader;
+                    scope.spawn(move || {
+                        (worker..8)
+                            .step_by(4)
+                            .map(|index| {
+                                let records = reader.decode_records(index).unwrap();
+                                records.iter().map(|r| r["id"].as_u64().unwrap()).collect()
+                            })
+                            .collect::<Vec<Vec<u64>>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        });
+        decoded.sort();
+        let expected: Vec<Vec<u64>> = (0..8).map(|b| (b * 25..(b + 1) * 25).collect()).collect();
+        assert_eq!(decoded, expected);
+
+        let in_memory =
+            ConcurrentJacReader::new(std::fs::read(&path).unwrap(), DecompressOpts::default())
+                .unwrap();
+        assert_eq!(in_memory.decode_records(7).unwrap().len(), 25);
+        assert!(in_memory.decode_records(8).is_err());
+    }
+
+    #[test]
+    fn concatenated_interned_archives_decode_with_their_own_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = |name: &str, fields: [&str; 2]| {
+            let path = dir.path().join(name);
+            let records: Vec<_> = (0..40u64)
+                .map(|i| {
+                    json!({ fields[0]: i, fields[1]: format!("v{}", i) })
+                        .as_object()
+                        .unwrap()
+                        .clone()
+                })
+                .collect();
+            execute_compress(
+                CompressRequest::builder()
+                    .input(InputSource::Iterator(Box::new(records.into_iter())))
+                    .output(OutputSink::Path(path.clone()))
+                    .options(CompressOptions {
+                        block_target_records: 10,
+                        intern_field_names: true,
+                        ..CompressOptions::default()
+                    })
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+            std::fs::read(path).unwrap()
+        };
+        let mut bytes = archive("first.jac", ["a", "b"]);
+        bytes.extend(archive("second.jac", ["x", "y"]));
+
+        let reader = ConcurrentJacReader::new(bytes, DecompressOpts::default()).unwrap();
+        assert_eq!(reader.blocks().len(), 8);
+        assert_eq!(reader.formats.len(), 2);
+        let keys: Vec<Vec<String>> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..8)
+                .rev()
+                .map(|index| {
+                    let reader = &reader;
+                    scope.spawn(move || {
+                        let records = reader.decode_records(index).unwrap();
+                        records[0].keys().cloned().collect()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .collect()
+        });
+        let expected = |fields: [&str; 2]| fields.map(String::from).to_vec();
+        for (index, keys) in keys.iter().rev().enumerate() {
+            let fields = if index < 4 { ["a", "b"] } else { ["x", "y"] };
+            assert_eq!(keys, &expected(fields), "block {}", index);
+        }
+    }
+}
@@ -0,0 +1,378 @@
+//! Dropping duplicate records during compression.
+//!
+//! Log shippers often re-send whole batches after a retry. With
+//! [`CompressOptions::dedupe`](crate::CompressOptions) set, each record is
+//! reduced to a 128-bit hash of its canonical JSON (or of its
+//! [`DedupeConfig::key_fields`] only), and a record whose hash was already seen
+//! is dropped before field injection and encoding. Hashes are kept for the most
+//! recent [`DedupeConfig::window`] distinct records, or for the whole input;
+//! in the latter case, once more than [`DedupeConfig::max_memory_keys`] are
+//! held they are sorted and spilled to a run file in the temporary directory
+//! that later lookups binary-search. Every [`MAX_SPILL_RUNS`] spills the runs
+//! are merged into one, so memory stays within `max_memory_keys` hashes and
+//! each lookup probes a bounded number of files.
+
+use crate::fingerprint::canonical_hash;
+use jac_format::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tempfile::NamedTempFile;
+
+/// Spill runs kept before they are merged into one.
+pub const MAX_SPILL_RUNS: usize = 16;
+
+/// Which records count as duplicates and how long they are remembered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DedupeConfig {
+    /// Top-level fields identifying a record; empty compares whole records.
+    /// A missing field and an explicit `null` are told apart.
+    pub key_fields: Vec<String>,
+    /// Compare against the most recent `window` distinct records only;
+    /// `None` compares against the whole input
+    pub window: Option<usize>,
+    /// Hashes held in memory before they are spilled to disk (whole-input mode)
+    pub max_memory_keys: usize,
+    /// Directory for spill runs (default: `CompressOptions::temp_dir`)
+    pub spill_dir: Option<PathBuf>,
+}
+
+impl Default for DedupeConfig {
+    fn default() -> Self {
+        Self {
+            key_fields: Vec::new(),
+            window: None,
+            max_memory_keys: 1 << 22,
+            spill_dir: None,
+        }
+    }
+}
+
+/// Deduplication counters for a compression request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DedupeMetrics {
+    /// Records dropped as duplicates
+    pub duplicates_dropped: u64,
+    /// Hash runs spilled to disk
+    pub spill_runs: u64,
+}
+
+/// Stateful duplicate filter for one compression request.
+pub(crate) struct Deduper {
+    key_fields: Vec<String>,
+    window: Option<usize>,
+    max_memory_keys: usize,
+    spill_dir: PathBuf,
+    seen: HashSet<u128>,
+    /// Insertion order of `seen`, kept in window mode only
+    order: VecDeque<u128>,
+    runs: Vec<SpillRun>,
+    metrics: Arc<Mutex<DedupeMetrics>>,
+}
+
+impl Deduper {
+    /// Build a filter, or `None` when deduplication is off.
+    pub(crate) fn new(
+        config: Option<&DedupeConfig>,
+        temp_dir: &Path,
+        metrics: Arc<Mutex<DedupeMetrics>>,
+    ) -> Option<Self> {
+        let config = config?;
+        Some(Self {
+            key_fields: config.key_fields.clone(),
+            window: config.window,
+            max_memory_keys: config.max_memory_keys.max(1),
+            spill_dir: config
+                .spill_dir
+                .clone()
+                .unwrap_or_else(|| temp_dir.to_path_buf()),
+            seen: HashSet::new(),
+            order: VecDeque::new(),
+            runs: Vec::new(),
+            metrics,
+        })
+    }
+
+    /// Remember `record`, returning whether an identical one was already seen.
+    pub(crate) fn is_duplicate(&mut self, record: &Map<String, Value>) -> Result<bool> {
+        let key = self.key(record);
+        let duplicate = self.seen.contains(&key) || self.in_runs(key)?;
+        if duplicate {
+            self.update_metrics(|metrics| metrics.duplicates_dropped += 1);
+            return Ok(true);
+        }
+
+        self.seen.insert(key);
+        match self.window {
+            Some(window) => {
+                self.order.push_back(key);
+                if self.order.len() > window {
+                    if let Some(oldest) = self.order.pop_front() {
+                        self.seen.remove(&oldest);
+                    }
+                }
+            }
+            None if self.seen.len() >= self.max_memory_keys => self.spill()?,
+            None => {}
+        }
+        Ok(false)
+    }
+
+    fn key(&self, record: &Map<String, Value>) -> u128 {
+        let hash = if self.key_fields.is_empty() {
+            canonical_hash(record, &[])
+        } else {
+            let mut keys = Map::with_capacity(self.key_fields.len());
+            for field in &self.key_fields {
+                if let Some(value) = record.get(field) {
+                    keys.insert(field.clone(), value.clone());
+                }
+            }
+            canonical_hash(&keys, &[])
+        };
+        let mut prefix = [0u8; 16];
+        prefix.copy_from_slice(&hash.as_bytes()[..16]);
+        u128::from_le_bytes(prefix)
+    }
+
+    fn in_runs(&mut self, key: u128) -> Result<bool> {
+        for run in &mut self.runs {
+            if run.contains(key)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn spill(&mut self) -> Result<()> {
+        let mut keys: Vec<u128> = self.seen.drain().collect();
+        keys.sort_unstable();
+        self.runs.push(SpillRun::write(&self.spill_dir, &keys)?);
+        self.update_metrics(|metrics| metrics.spill_runs += 1);
+        if self.runs.len() >= MAX_SPILL_RUNS {
+            let merged = SpillRun::merge(&self.spill_dir, &self.runs)?;
+            self.runs = vec![merged];
+        }
+        Ok(())
+    }
+
+    fn update_metrics(&self, update: impl FnOnce(&mut DedupeMetrics)) {
+        let mut metrics = self
+            .metrics
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        update(&mut metrics);
+    }
+}
+
+/// Sorted hashes on disk, 16 little-endian bytes each, in a freshly created
+/// temporary file that is removed on drop.
+struct SpillRun {
+    file: NamedTempFile,
+    len: u64,
+}
+
+impl SpillRun {
+    fn create(dir: &Path) -> Result<NamedTempFile> {
+        Ok(tempfile::Builder::new()
+            .prefix("jac-dedupe-")
+            .suffix(".run")
+            .tempfile_in(dir)?)
+    }
+
+    fn write(dir: &Path, keys: &[u128]) -> Result<Self> {
+        let file = Self::create(dir)?;
+        let mut writer = BufWriter::new(file.as_file());
+        for key in keys {
+            writer.write_all(&key.to_le_bytes())?;
+        }
+        writer.flush()?;
+        drop(writer);
+        Ok(Self {
+            file,
+            len: keys.len() as u64,
+        })
+    }
+
+    /// Merge `runs` into one run, reading each of them once in order.
+    fn merge(dir: &Path, runs: &[SpillRun]) -> Result<Self> {
+        let mut readers = Vec::with_capacity(runs.len());
+        for run in runs {
+            let mut file = run.file.as_file();
+            file.seek(SeekFrom::Start(0))?;
+            readers.push((BufReader::new(file), run.len));
+        }
+        let mut heap = BinaryHeap::with_capacity(readers.len());
+        for (idx, reader) in readers.iter_mut().enumerate() {
+            if let Some(key) = next_key(reader)? {
+                heap.push(Reverse((key, idx)));
+            }
+        }
+
+        let file = Self::create(dir)?;
+        let mut writer = BufWriter::new(file.as_file());
+        let mut len = 0;
+        while let Some(Reverse((key, idx))) = heap.pop() {
+            writer.write_all(&key.to_le_bytes())?;
+            len += 1;
+            if let Some(key) = next_key(&mut readers[idx])? {
+                heap.push(Reverse((key, idx)));
+            }
+        }
+        writer.flush()?;
+        drop(writer);
+        Ok(Self { file, len })
+    }
+
+    fn contains(&mut self, key: u128) -> Result<bool> {
+        let (mut lo, mut hi) = (0u64, self.len);
+        let mut bytes = [0u8; 16];
+        let mut file = self.file.as_file();
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            file.seek(SeekFrom::Start(mid * 16))?;
+            file.read_exact(&mut bytes)?;
+            match u128::from_le_bytes(bytes).cmp(&key) {
+                std::cmp::Ordering::Equal => return Ok(true),
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Next hash of a run being read in order, with the count still unread.
+fn next_key(reader: &mut (BufReader<&File>, u64)) -> Result<Option<u128>> {
+    if reader.1 == 0 {
+        return Ok(None);
+    }
+    let mut bytes = [0u8; 16];
+    reader.0.read_exact(&mut bytes)?;
+    reader.1 -= 1;
+    Ok(Some(u128::from_le_bytes(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    fn record(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    fn dropped(config: DedupeConfig, records: &[Value]) -> (Vec<bool>, DedupeMetrics) {
+        let metrics = Arc::new(Mutex::new(DedupeMetrics::default()));
+        let temp_dir = std::env::temp_dir();
+        let mut deduper = Deduper::new(Some(&config), &temp_dir, Arc::clone(&metrics)).unwrap();
+        let flags = records
+            .iter()
+            .map(|r| deduper.is_duplicate(&record(r.clone())).unwrap())
+            .collect();
+        let metrics = *metrics.lock().unwrap();
+        (flags, metrics)
+    }
+
+    #[test]
+    fn whole_records_and_key_fields() {
+        let records = [
+            json!({"id": 1, "msg": "a"}),
+            json!({"msg": "a", "id": 1}),
+            json!({"id": 1, "msg": "b"}),
+            json!({"id": null, "msg": "c"}),
+            json!({"msg": "d"}),
+        ];
+        let (flags, metrics) = dropped(DedupeConfig::default(), &records);
+        assert_eq!(flags, [false, true, false, false, false]);
+        assert_eq!(metrics.duplicates_dropped, 1);
+
+        let by_id = DedupeConfig {
+            key_fields: vec!["id".to_string()],
+            ..DedupeConfig::default()
+        };
+        let (flags, _) = dropped(by_id, &records);
+        assert_eq!(flags, [false, true, true, false, false]);
+    }
+
+    #[test]
+    fn window_forgets_old_records() {
+        let records = [
+            json!({"n": 1}),
+            json!({"n": 2}),
+            json!({"n": 3}),
+            json!({"n": 1}),
+        ];
+        let config = DedupeConfig {
+            window: Some(2),
+            ..DedupeConfig::default()
+        };
+        assert_eq!(dropped(config, &records).0, [false; 4]);
+        let config = DedupeConfig {
+            window: Some(3),
+            ..DedupeConfig::default()
+        };
+        assert_eq!(dropped(config, &records).0, [false, false, false, true]);
+    }
+
+    #[test]
+    fn spilled_runs_still_catch_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DedupeConfig {
+            max_memory_keys: 4,
+            spill_dir: Some(dir.path().to_path_buf()),
+            ..DedupeConfig::default()
+        };
+        let records: Vec<Value> = (0..10).chain(0..10).map(|n| json!({ "n": n })).collect();
+        let metrics = Arc::new(Mutex::new(DedupeMetrics::default()));
+        // An explicit spill_dir wins over the request's temporary directory.
+        let other = tempfile::tempdir().unwrap();
+        let mut deduper = Deduper::new(Some(&config), other.path(), Arc::clone(&metrics)).unwrap();
+        for (idx, value) in records.iter().enumerate() {
+            assert_eq!(
+                deduper.is_duplicate(&record(value.clone())).unwrap(),
+                idx >= 10
+            );
+        }
+        assert_eq!(
+            *metrics.lock().unwrap(),
+            DedupeMetrics {
+                duplicates_dropped: 10,
+                spill_runs: 2,
+            }
+        );
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+        assert_eq!(fs::read_dir(other.path()).unwrap().count(), 0);
+        drop(deduper);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn spill_runs_are_merged_once_they_pile_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DedupeConfig {
+            max_memory_keys: 2,
+            spill_dir: Some(dir.path().to_path_buf()),
+            ..DedupeConfig::default()
+        };
+        let metrics = Arc::new(Mutex::new(DedupeMetrics::default()));
+        let mut deduper = Deduper::new(Some(&config), dir.path(), Arc::clone(&metrics)).unwrap();
+        for (idx, n) in (0..40).chain((0..40).rev()).enumerate() {
+            assert_eq!(
+                deduper.is_duplicate(&record(json!({ "n": n }))).unwrap(),
+                idx >= 40
+            );
+        }
+        assert_eq!(metrics.lock().unwrap().spill_runs, 20);
+        // Sixteen runs merged into one, then four more
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 5);
+        assert_eq!(deduper.runs[0].len, 32);
+    }
+}
@@ -0,0 +1,494 @@
+//! Input format sniffing.
+//!
+//! [`detect_input_format`] looks at the first bytes of an input and tells
+//! NDJSON from a JSON array or a single JSON object, recognises `.jac` files,
+//! and, when the input looks like an API envelope (`{"data": [...]}`, an
+//! object of objects, an array of rows under a header row), suggests the
+//! [`WrapperConfig`] that would unwrap it. The result carries a confidence so
+//! callers can fall back to other hints, such as the file extension, when the
+//! prefix is ambiguous or cut off inside the first value. [`suggest_wrappers`]
+//! lists every candidate envelope found in the prefix, for error messages that
+//! name them.
+
+use crate::{ContainerFormat, WrapperConfig, WrapperLimits};
+use jac_format::constants::FILE_MAGIC;
+use jac_format::Result;
+use serde_json::{Map, Value};
+use std::io::Read;
+
+/// Bytes read from the input by [`detect_input_format`].
+pub const DETECTION_PREFIX_BYTES: usize = 64 * 1024;
+
+pub(crate) const UTF8_BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+
+/// Layout of an input as seen from its first bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    /// One JSON value per line
+    Ndjson,
+    /// A top-level JSON array
+    JsonArray,
+    /// A single top-level JSON object: one record, or an envelope around them
+    SingleObject,
+    /// Already a JAC file
+    Jac,
+    /// Empty, or not JSON
+    Unknown,
+}
+
+/// Result of [`detect_input_format`].
+#[derive(Debug, Clone)]
+pub struct InputDetection {
+    /// Detected layout
+    pub format: InputFormat,
+    /// Confidence in `format` and `suggested_wrapper`, from 0.0 to 1.0
+    pub confidence: f64,
+    /// Wrapper that would turn the input into records, when it looks like an
+    /// envelope
+    pub suggested_wrapper: Option<WrapperConfig>,
+    /// The first value runs past the prefix, so `format` is a guess from its
+    /// start: a long NDJSON line reads the same as a large envelope, and a
+    /// file extension naming the format should be preferred
+    pub truncated: bool,
+}
+
+impl InputDetection {
+    fn new(format: InputFormat, confidence: f64) -> Self {
+        Self {
+            format,
+            confidence,
+            suggested_wrapper: None,
+            truncated: false,
+        }
+    }
+
+    fn suggest(mut self, wrapper: WrapperConfig) -> Self {
+        self.suggested_wrapper = Some(wrapper);
+        self
+    }
+
+    /// Container format to read the input as, if it is JSON: a single object
+    /// is read like a one-element array.
+    pub fn container_format(&self) -> Option<ContainerFormat> {
+        match self.format {
+            InputFormat::Ndjson => Some(ContainerFormat::Ndjson),
+            InputFormat::JsonArray | InputFormat::SingleObject => Some(ContainerFormat::JsonArray),
+            InputFormat::Jac | InputFormat::Unknown => None,
+        }
+    }
+}
+
+/// Sniff the format of `reader` from its first [`DETECTION_PREFIX_BYTES`]
+/// bytes. The bytes read are consumed, so pass a handle opened for detection
+/// (or a [`std::io::BufRead`] whose buffer is inspected some other way) and
+/// reopen the input to compress it.
+pub fn detect_input_format<R: Read>(reader: R) -> Result<InputDetection> {
+    let (prefix, complete) = read_prefix(reader)?;
+    Ok(detect_prefix(&prefix, complete))
+}
+
+/// Candidate envelope found by [`suggest_wrappers`].
+#[derive(Debug, Clone)]
+pub struct WrapperSuggestion {
+    /// JSON Pointer to the records (empty for the whole input)
+    pub pointer: String,
+    /// Wrapper extracting them
+    pub wrapper: WrapperConfig,
+    /// Records seen there within the sampled prefix
+    pub records_seen: usize,
+}
+
+/// Wrappers that could extract records from `reader`, judged from its first
+/// [`DETECTION_PREFIX_BYTES`] bytes and most records first: arrays of objects
+/// and objects of objects up to three levels into a top-level object, and
+/// header rows in a top-level array of arrays. Arrays cut off by the end of
+/// the prefix are found only directly under the top-level object.
+pub fn suggest_wrappers<R: Read>(reader: R) -> Result<Vec<WrapperSuggestion>> {
+    let (prefix, complete) = read_prefix(reader)?;
+    let text = prefix.strip_prefix(&UTF8_BOM[..]).unwrap_or(&prefix);
+    let start = skip_whitespace(text, 0);
+    Ok(match text.get(start) {
+        Some(b'{') => object_suggestions(&text[start..], complete),
+        Some(b'[') if text.get(skip_whitespace(text, start + 1)) == Some(&b'[') => {
+            vec![WrapperSuggestion {
+                pointer: String::new(),
+                wrapper: WrapperConfig::ArrayWithHeaders {
+                    limits: WrapperLimits::default(),
+                    type_row: false,
+                    null_markers: Vec::new(),
+                },
+                records_seen: count_elements(text, start).saturating_sub(1),
+            }]
+        }
+        _ => Vec::new(),
+    })
+}
+
+fn read_prefix<R: Read>(reader: R) -> Result<(Vec<u8>, bool)> {
+    let mut prefix = Vec::with_capacity(4096);
+    reader
+        .take(DETECTION_PREFIX_BYTES as u64)
+        .read_to_end(&mut prefix)?;
+    let complete = prefix.len() < DETECTION_PREFIX_BYTES;
+    Ok((prefix, complete))
+}
+
+/// Sniff the format from `prefix`; `complete` says it is the whole input.
+pub(crate) fn detect_prefix(prefix: &[u8], complete: bool) -> InputDetection {
+    if prefix.starts_with(&FILE_MAGIC) {
+        return InputDetection::new(InputFormat::Jac, 1.0);
+    }
+    let text = prefix.strip_prefix(&UTF8_BOM[..]).unwrap_or(prefix);
+    let start = skip_whitespace(text, 0);
+    match text.get(start) {
+        None => InputDetection::new(InputFormat::Unknown, 0.0),
+        Some(b'[') => {
+            let detection = InputDetection::new(InputFormat::JsonArray, 0.95);
+            let first = skip_whitespace(text, start + 1);
+            if text.get(first) == Some(&b'[') {
+                detection.suggest(WrapperConfig::ArrayWithHeaders {
+                    limits: WrapperLimits::default(),
+                    type_row: false,
+                    null_markers: Vec::new(),
+                })
+            } else {
+                detection
+            }
+        }
+        Some(b'{') => detect_object(&text[start..], complete),
+        Some(b'"' | b'-' | b'0'..=b'9' | b't' | b'f' | b'n') => {
+            InputDetection::new(InputFormat::Ndjson, 0.4)
+        }
+        Some(_) => InputDetection::new(InputFormat::Unknown, 0.0),
+    }
+}
+
+fn detect_object(text: &[u8], complete: bool) -> InputDetection {
+    let mut values = serde_json::Deserializer::from_slice(text).into_iter::<Value>();
+    match values.next() {
+        Some(Ok(Value::Object(object))) => {
+            let rest = skip_whitespace(text, values.byte_offset());
+            if rest < text.len() {
+                // Another value follows the first object.
+                let starts_line = text[values.byte_offset()..rest].contains(&b'\n');
+                return InputDetection::new(
+                    InputFormat::Ndjson,
+                    if starts_line { 0.95 } else { 0.7 },
+                );
+            }
+            if !complete {
+                return InputDetection::new(InputFormat::Ndjson, 0.5);
+            }
+            match envelope_suggestions(&object).into_iter().next() {
+                Some(best) => {
+                    InputDetection::new(InputFormat::SingleObject, 0.8).suggest(best.wrapper)
+                }
+                None => InputDetection::new(InputFormat::SingleObject, 0.9),
+            }
+        }
+        Some(Err(err)) if err.is_eof() && !complete => {
+            // An object larger than the prefix is an envelope when it holds an
+            // array of objects; otherwise it may as well be a long NDJSON line.
+            let detection = match partial_suggestions(text).into_iter().next() {
+                Some(best) => {
+                    InputDetection::new(InputFormat::SingleObject, 0.6).suggest(best.wrapper)
+                }
+                None => InputDetection::new(InputFormat::SingleObject, 0.2),
+            };
+            InputDetection {
+                truncated: true,
+                ..detection
+            }
+        }
+        _ => InputDetection::new(InputFormat::Unknown, 0.0),
+    }
+}
+
+fn object_suggestions(text: &[u8], complete: bool) -> Vec<WrapperSuggestion> {
+    let mut values = serde_json::Deserializer::from_slice(text).into_iter::<Value>();
+    match values.next() {
+        Some(Ok(Value::Object(object))) => envelope_suggestions(&object),
+        Some(Err(err)) if err.is_eof() && !complete => partial_suggestions(text),
+        _ => Vec::new(),
+    }
+}
+
+/// Candidate envelopes in a complete top-level object.
+fn envelope_suggestions(object: &Map<String, Value>) -> Vec<WrapperSuggestion> {
+    /// Deepest pointer suggested, matching the default wrapper depth limit
+    const MAX_DEPTH: usize = 3;
+
+    fn visit<'a>(
+        object: &'a Map<String, Value>,
+        path: &mut Vec<&'a str>,
+        found: &mut Vec<WrapperSuggestion>,
+    ) {
+        // An object of record-like objects, each holding some scalar field.
+        let record_like = |value: &Value| {
+            value.as_object().is_some_and(|record| {
+                record
+                    .values()
+                    .any(|field| !field.is_object() && !field.is_array())
+            })
+        };
+        if object.len() >= 2 && object.values().all(record_like) {
+            found.push(WrapperSuggestion {
+                pointer: pointer(path),
+                wrapper: WrapperConfig::KeyedMap {
+                    pointer: pointer(path),
+                    key_field: "_key".to_string(),
+                    inner_key_field: None,
+                    nested_key_fields: false,
+                    limits: WrapperLimits::default(),
+                    collision_mode: Default::default(),
+                },
+                records_seen: object.len(),
+            });
+        }
+        for (key, value) in object {
+            path.push(key);
+            match value {
+                Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object) => {
+                    found.push(pointer_suggestion(path, items.len()));
+                }
+                Value::Object(child) if path.len() < MAX_DEPTH => visit(child, path, found),
+                _ => {}
+            }
+            path.pop();
+        }
+    }
+
+    let mut found = Vec::new();
+    visit(object, &mut Vec::new(), &mut found);
+    found.sort_by_key(|suggestion| std::cmp::Reverse(suggestion.records_seen));
+    found
+}
+
+/// Candidate envelopes directly under a top-level object cut off by the end
+/// of the prefix, scanning `text` (which starts with `{`) up to where it ends.
+fn partial_suggestions(text: &[u8]) -> Vec<WrapperSuggestion> {
+    let mut found = Vec::new();
+    let mut pos = 1;
+    loop {
+        pos = skip_whitespace(text, pos);
+        let Some(key_end) = string_end(text, pos) else {
+            break;
+        };
+        let Ok(key) = serde_json::from_slice::<String>(&text[pos..key_end]) else {
+            break;
+        };
+        pos = skip_whitespace(text, key_end);
+        if text.get(pos) != Some(&b':') {
+            break;
+        }
+        pos = skip_whitespace(text, pos + 1);
+        if text.get(pos) == Some(&b'[') && text.get(skip_whitespace(text, pos + 1)) == Some(&b'{') {
+            found.push(pointer_suggestion(
+                &[key.as_str()],
+                count_elements(text, pos),
+            ));
+        }
+        let Some(end) = value_end(text, pos) else {
+            break;
+        };
+        pos = skip_whitespace(text, end);
+        if text.get(pos) != Some(&b',') {
+            break;
+        }
+        pos += 1;
+    }
+    found.sort_by_key(|suggestion| std::cmp::Reverse(suggestion.records_seen));
+    found
+}
+
+/// Elements of the array starting at `pos` that begin within `text`.
+fn count_elements(text: &[u8], pos: usize) -> usize {
+    let mut count = 0;
+    let mut idx = skip_whitespace(text, pos + 1);
+    while idx < text.len() && text[idx] != b']' {
+        count += 1;
+        let Some(end) = value_end(text, idx) else {
+            break;
+        };
+        idx = skip_whitespace(text, end);
+        if text.get(idx) != Some(&b',') {
+            break;
+        }
+        idx = skip_whitespace(text, idx + 1);
+    }
+    count
+}
+
+fn pointer_suggestion(path: &[&str], records_seen: usize) -> WrapperSuggestion {
+    WrapperSuggestion {
+        pointer: pointer(path),
+        wrapper: WrapperConfig::Pointer {
+            path: pointer(path),
+            limits: WrapperLimits::default(),
+        },
+        records_seen,
+    }
+}
+
+/// RFC 6901 pointer to `path`.
+fn pointer(path: &[&str]) -> String {
+    path.iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect::<Vec<_>>()
+        .concat()
+}
+
+/// Position just past the string starting at `pos`.
+fn string_end(text: &[u8], pos: usize) -> Option<usize> {
+    if text.get(pos) != Some(&b'"') {
+        return None;
+    }
+    let mut idx = pos + 1;
+    while idx < text.len() {
+        match text[idx] {
+            b'\\' => idx += 2,
+            b'"' => return Some(idx + 1),
+            _ => idx += 1,
+        }
+    }
+    None
+}
+
+/// Position just past the value starting at `pos`.
+fn value_end(text: &[u8], pos: usize) -> Option<usize> {
+    match text.get(pos)? {
+        b'"' => string_end(text, pos),
+        b'{' | b'[' => {
+            let mut depth = 0usize;
+            let mut idx = pos;
+            while idx < text.len() {
+                match text[idx] {
+                    b'"' => {
+                        idx = string_end(text, idx)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(idx + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                idx += 1;
+            }
+            None
+        }
+        _ => text[pos..]
+            .iter()
+            .position(|byte| matches!(byte, b',' | b'}' | b']') || byte.is_ascii_whitespace())
+            .map(|len| pos + len),
+    }
+}
+
+fn skip_whitespace(text: &[u8], mut pos: usize) -> usize {
+    while text.get(pos).is_some_and(u8::is_ascii_whitespace) {
+        pos += 1;
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(text: &str) -> InputDetection {
+        detect_input_format(text.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn formats_are_told_apart() {
+        let ndjson = detect("{\"a\":1}\n{\"a\":2}\n");
+        assert_eq!(ndjson.format, InputFormat::Ndjson);
+        assert!(ndjson.confidence > 0.9);
+        assert_eq!(ndjson.container_format(), Some(ContainerFormat::Ndjson));
+
+        assert_eq!(
+            detect("\u{feff} [{\"a\":1}]").format,
+            InputFormat::JsonArray
+        );
+        let single = detect("{\"a\":1,\"b\":\"x\"}");
+        assert_eq!(single.format, InputFormat::SingleObject);
+        assert!(single.suggested_wrapper.is_none());
+        assert_eq!(single.container_format(), Some(ContainerFormat::JsonArray));
+
+        let mut jac = FILE_MAGIC.to_vec();
+        jac.extend_from_slice(&[0; 16]);
+        assert_eq!(
+            detect_input_format(jac.as_slice()).unwrap().format,
+            InputFormat::Jac
+        );
+        assert_eq!(detect("").format, InputFormat::Unknown);
+        assert_eq!(detect("\x00\x01binary").format, InputFormat::Unknown);
+    }
+
+    #[test]
+    fn envelopes_get_a_wrapper_suggestion() {
+        let envelope = detect(r#"{"meta":{"page":1},"data":[{"id":1},{"id":2}],"tags":[]}"#);
+        assert_eq!(envelope.format, InputFormat::SingleObject);
+        assert!(matches!(
+            envelope.suggested_wrapper,
+            Some(WrapperConfig::Pointer { ref path, .. }) if path == "/data"
+        ));
+
+        let map = detect(r#"{"alice":{"age":3},"bob":{"age":4}}"#);
+        assert!(matches!(
+            map.suggested_wrapper,
+            Some(WrapperConfig::KeyedMap { ref pointer, .. }) if pointer.is_empty()
+        ));
+
+        let rows = detect(r#"[["id","name"],[1,"a"]]"#);
+        assert!(matches!(
+            rows.suggested_wrapper,
+            Some(WrapperConfig::ArrayWithHeaders { .. })
+        ));
+
+        let nested = suggest_wrappers(
+            r#"{"errors":[{"code":1}],"response":{"results":[{"id":1},{"id":2},{"id":3}]}}"#
+                .as_bytes(),
+        )
+        .unwrap();
+        let pointers: Vec<(&str, usize)> = nested
+            .iter()
+            .map(|suggestion| (suggestion.pointer.as_str(), suggestion.records_seen))
+            .collect();
+        assert_eq!(pointers, [("/response/results", 3), ("/errors", 1)]);
+        assert!(suggest_wrappers(r#"{"id":1,"tags":["a"]}"#.as_bytes())
+            .unwrap()
+            .is_empty());
+
+        // An envelope larger than the prefix is scanned up to the cut.
+        let mut big = String::from(r#"{"status":"ok","paging":{"next":"x,}"},"a/b": ["#);
+        while big.len() < DETECTION_PREFIX_BYTES {
+            big.push_str(r#"{"id":1},"#);
+        }
+        let truncated = detect(&big);
+        assert_eq!(truncated.format, InputFormat::SingleObject);
+        assert!(truncated.truncated);
+        assert!(matches!(
+            truncated.suggested_wrapper,
+            Some(WrapperConfig::Pointer { ref path, .. }) if path == "/a~1b"
+        ));
+        let partial = suggest_wrappers(big.as_bytes()).unwrap();
+        assert_eq!(partial.len(), 1);
+        assert!(partial[0].records_seen > 1000);
+    }
+
+    #[test]
+    fn ndjson_line_longer_than_the_prefix_is_marked_truncated() {
+        let mut first = String::from(r#"{"events":[{"k":0},{"k":1}],"pad":""#);
+        first.push_str(&"x".repeat(DETECTION_PREFIX_BYTES + 4096));
+        first.push_str("\"}\n{\"events\":[]}\n");
+        let detection = detect(&first);
+        assert!(detection.truncated);
+        assert!(detection.confidence < 0.8);
+        assert!(!detect("{\"a\":1}\n{\"a\":2}\n").truncated);
+        assert!(!detect(r#"{"data":[{"id":1},{"id":2}]}"#).truncated);
+    }
+}
@@ -0,0 +1,211 @@
+//! Duplicate-key aware JSON object deserialization.
+//!
+//! `serde_json` keeps the last occurrence of a repeated key without reporting it.
+//! The seeds in this module walk objects at every nesting level and apply a
+//! [`DuplicateKeyPolicy`] instead.
+
+use crate::DuplicateKeyPolicy;
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use std::fmt;
+
+/// Deserialize a top-level JSON object, applying `policy` to repeated keys.
+pub(crate) fn deserialize_record<'de, D>(
+    deserializer: D,
+    policy: DuplicateKeyPolicy,
+) -> Result<Map<String, Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match policy {
+        // serde_json already keeps the last value; skip the custom visitor entirely.
+        DuplicateKeyPolicy::LastWins => Map::<String, Value>::deserialize(deserializer),
+        _ => deserializer.deserialize_map(ObjectVisitor { policy }),
+    }
+}
+
+/// Parse a single JSON object from `input`, rejecting trailing characters.
+pub(crate) fn parse_record_str(
+    input: &str,
+    policy: DuplicateKeyPolicy,
+) -> serde_json::Result<Map<String, Value>> {
+    let mut de = serde_json::Deserializer::from_str(input);
+    let map = deserialize_record(&mut de, policy)?;
+    de.end()?;
+    Ok(map)
+}
+
+struct ValueSeed {
+    policy: DuplicateKeyPolicy,
+}
+
+impl<'de> DeserializeSeed<'de> for ValueSeed {
+    type Value = Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor {
+            policy: self.policy,
+        })
+    }
+}
+
+struct ValueVisitor {
+    policy: DuplicateKeyPolicy,
+}
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(Number::from_f64(value).map_or(Value::Null, Value::Number))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        ValueSeed {
+            policy: self.policy,
+        }
+        .deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element_seed(ValueSeed {
+            policy: self.policy,
+        })? {
+            values.push(value);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        ObjectVisitor {
+            policy: self.policy,
+        }
+        .visit_map(map)
+        .map(Value::Object)
+    }
+}
+
+struct ObjectVisitor {
+    policy: DuplicateKeyPolicy,
+}
+
+impl<'de> Visitor<'de> for ObjectVisitor {
+    type Value = Map<String, Value>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON object")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut map = Map::new();
+        while let Some(key) = access.next_key::<String>()? {
+            if map.contains_key(&key) {
+                match self.policy {
+                    DuplicateKeyPolicy::Error => {
+                        return Err(de::Error::custom(format!("duplicate key '{}'", key)));
+                    }
+                    DuplicateKeyPolicy::FirstWins => {
+                        access.next_value::<IgnoredAny>()?;
+                        continue;
+                    }
+                    DuplicateKeyPolicy::LastWins => {}
+                }
+            }
+            let value = access.next_value_seed(ValueSeed {
+                policy: self.policy,
+            })?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn last_wins_matches_serde_json() {
+        let map = parse_record_str(r#"{"a":1,"a":2}"#, DuplicateKeyPolicy::LastWins).unwrap();
+        assert_eq!(map["a"], json!(2));
+    }
+
+    #[test]
+    fn first_wins_keeps_first_value_at_every_depth() {
+        let map = parse_record_str(
+            r#"{"a":1,"b":{"c":true,"c":false},"a":2,"d":[{"e":"x","e":"y"}]}"#,
+            DuplicateKeyPolicy::FirstWins,
+        )
+        .unwrap();
+        assert_eq!(
+            Value::Object(map),
+            json!({"a":1,"b":{"c":true},"d":[{"e":"x"}]})
+        );
+    }
+
+    #[test]
+    fn error_policy_reports_nested_duplicates() {
+        let err =
+            parse_record_str(r#"{"a":{"b":1,"b":1}}"#, DuplicateKeyPolicy::Error).unwrap_err();
+        assert!(err.to_string().contains("duplicate key 'b'"));
+
+        let ok = parse_record_str(r#"{"a":{"b":1},"b":1.5}"#, DuplicateKeyPolicy::Error).unwrap();
+        assert_eq!(ok["b"], json!(1.5));
+    }
+
+    #[test]
+    fn rejects_non_object_and_trailing_input() {
+        assert!(parse_record_str("[1,2]", DuplicateKeyPolicy::Error).is_err());
+        assert!(parse_record_str(r#"{"a":1} x"#, DuplicateKeyPolicy::FirstWins).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+//! Reading archives stored inside another file.
+//!
+//! A `.jac` file can travel as a member of a tar archive or a custom bundle.
+//! [`EmbeddedReader`] exposes the bytes `base..base + len` of its inner reader
+//! as a stream of their own, so offsets in the archive's header, blocks and
+//! index resolve relative to `base` and the trailing index pointer is found at
+//! the end of the member rather than the end of the bundle.
+//! [`JacReader::new_at`] and [`JacReader::new_bounded`] open archives through
+//! it without copying them out first.
+
+use crate::JacReader;
+use jac_codec::DecompressOpts;
+use jac_format::Result;
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Window `base..base + len` of a seekable reader, itself seekable from 0.
+///
+/// Reads stop at the end of the window; seeks beyond it are allowed, as for
+/// files, and read nothing.
+#[derive(Debug)]
+pub struct EmbeddedReader<R> {
+    inner: R,
+    base: u64,
+    len: u64,
+    pos: u64,
+}
+
+impl<R: Read + Seek> EmbeddedReader<R> {
+    /// Window starting at `base` and running for `len` bytes, or to the end
+    /// of `inner` when `len` is `None`. Fails when the window does not fit
+    /// inside `inner`.
+    pub fn new(mut inner: R, base: u64, len: Option<u64>) -> io::Result<Self> {
+        let end = inner.seek(SeekFrom::End(0))?;
+        let available = end.checked_sub(base).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "base offset {} is past the end of the input ({})",
+                    base, end
+                ),
+            )
+        })?;
+        let len = match len {
+            Some(len) if len > available => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "embedded archive of {} bytes at offset {} overruns the input ({} bytes)",
+                        len, base, end
+                    ),
+                ))
+            }
+            Some(len) => len,
+            None => available,
+        };
+        inner.seek(SeekFrom::Start(base))?;
+        Ok(Self {
+            inner,
+            base,
+            len,
+            pos: 0,
+        })
+    }
+
+    /// Offset of the window within the inner reader.
+    pub fn base_offset(&self) -> u64 {
+        self.base
+    }
+
+    /// Length of the window in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the window is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the inner reader, positioned arbitrarily.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for EmbeddedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.len.saturating_sub(self.pos);
+        let max = buf
+            .len()
+            .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        if max == 0 {
+            return Ok(0);
+        }
+        let read = self.inner.read(&mut buf[..max])?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for EmbeddedReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek before the start of the embedded archive",
+            )
+        })?;
+        let absolute = self.base.checked_add(target).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek past the end of the addressable range",
+            )
+        })?;
+        self.inner.seek(SeekFrom::Start(absolute))?;
+        self.pos = target;
+        Ok(target)
+    }
+}
+
+impl<R: Read + Seek> JacReader<EmbeddedReader<R>> {
+    /// Open an archive that starts at `base_offset` within `reader` and runs
+    /// to its end.
+    pub fn new_at(reader: R, base_offset: u64, opts: DecompressOpts) -> Result<Self> {
+        JacReader::new(EmbeddedReader::new(reader, base_offset, None)?, opts)
+    }
+
+    /// Open an archive occupying the `len` bytes at `base_offset` within
+    /// `reader`, such as a tar member; nothing outside them is read.
+    pub fn new_bounded(
+        reader: R,
+        base_offset: u64,
+        len: u64,
+        opts: DecompressOpts,
+    ) -> Result<Self> {
+        JacReader::new(EmbeddedReader::new(reader, base_offset, Some(len))?, opts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compress_records;
+    use serde_json::{json, Map, Value};
+    use std::io::Cursor;
+
+    fn archive() -> (Vec<Map<String, Value>>, Vec<u8>) {
+        let records: Vec<Map<String, Value>> = (0..30)
+            .map(|i| json!({ "id": i }).as_object().unwrap().clone())
+            .collect();
+        let options = crate::CompressOptions {
+            block_target_records: 10,
+            ..crate::CompressOptions::default()
+        };
+        let bytes = compress_records(records.clone(), options).unwrap();
+        (records, bytes)
+    }
+
+    #[test]
+    fn archives_are_read_in_place_inside_a_bundle() {
+        let (records, bytes) = archive();
+        let mut bundle = vec![0xAB; 100];
+        bundle.extend_from_slice(&bytes);
+        bundle.extend_from_slice(&[0xCD; 37]);
+
+        let mut reader = JacReader::new_bounded(
+            Cursor::new(bundle.clone()),
+            100,
+            bytes.len() as u64,
+            DecompressOpts::default(),
+        )
+        .unwrap();
+        assert!(reader.is_finalized());
+        assert_eq!(reader.block_count().unwrap(), 3);
+        let read: Vec<_> = reader
+            .record_stream()
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(read, records);
+
+        // Without a length the archive runs to the end of the input.
+        bundle.truncate(100 + bytes.len());
+        let mut reader =
+            JacReader::new_at(Cursor::new(bundle.clone()), 100, DecompressOpts::default()).unwrap();
+        assert!(reader.is_finalized());
+        assert_eq!(reader.record_stream().unwrap().count(), 30);
+
+        assert!(JacReader::new_bounded(
+            Cursor::new(bundle),
+            100,
+            bytes.len() as u64 + 1,
+            DecompressOpts::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn window_reads_and_seeks_stay_inside() {
+        let mut window =
+            EmbeddedReader::new(Cursor::new(b"0123456789".to_vec()), 2, Some(5)).unwrap();
+        let mut out = String::new();
+        window.read_to_string(&mut out).unwrap();
+        assert_eq!(out, "23456");
+        assert_eq!(window.seek(SeekFrom::End(-2)).unwrap(), 3);
+        out.clear();
+        window.read_to_string(&mut out).unwrap();
+        assert_eq!(out, "56");
+        assert_eq!(window.seek(SeekFrom::Start(9)).unwrap(), 9);
+        assert_eq!(window.read(&mut [0; 4]).unwrap(), 0);
+        assert!(window.seek(SeekFrom::Current(-10)).is_err());
+        assert!(window.seek(SeekFrom::Start(u64::MAX)).is_err());
+        assert_eq!(window.stream_position().unwrap(), 9);
+    }
+}
@@ -0,0 +1,1060 @@
+//! Record filters shared by queries, `jac unpack --where` and `jac cat --where`.
+//!
+//! Filter grammar (keywords are case-insensitive):
+//!
+//! ```text
+//! predicate := term [OR term]*
+//! term      := factor [AND factor]*
+//! factor    := NOT factor | '(' predicate ')' | field op literal | field IS [NOT] NULL
+//!            | field IN '(' literal [, literal]* ')' | field STARTS WITH 'string'
+//!            | field MATCHES 'regex'
+//! op        := = | != | <> | < | <= | > | >=
+//! literal   := number | 'string' | true | false
+//! ```
+//!
+//! Fields may be double-quoted to use reserved words or punctuation. Tests of
+//! a missing or `null` field, or of a value of a different type, are false;
+//! `MATCHES` needs the `regex` feature.
+//!
+//! A [`Predicate`] is evaluated against one record with
+//! [`Predicate::matches`] or against the decoded columns of a whole block with
+//! [`Predicate::evaluate`]. [`Predicate::may_match`] decides from a block's
+//! [`BlockStats`] (its field directory and string dictionaries) whether the
+//! block can be skipped without decoding it.
+//!
+//! On a decoded block ([`Predicate::evaluate_block`]), tests of a
+//! dictionary-encoded field run once per dictionary entry and each record
+//! takes the result of the entry it references, so a regex or prefix costs
+//! one evaluation per distinct string rather than per record.
+//! [`FilterSummary`] counts that work.
+
+use crate::{BlockHandle, JacReader};
+use jac_codec::{read_dictionary_histogram, BlockDecoder, FieldSegmentDecoder, ValueRef};
+use jac_format::{JacError, Result};
+use serde_json::{Number, Value};
+use std::cmp::Ordering;
+use std::io::{Read, Seek};
+
+/// Comparison operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    /// `=`
+    Eq,
+    /// `!=` or `<>`
+    Ne,
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+}
+
+/// Row filter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    /// `field op literal`
+    Compare {
+        /// Field name
+        field: String,
+        /// Operator
+        op: CompareOp,
+        /// Literal operand
+        value: Value,
+    },
+    /// `field IS NULL` (`negated` for `IS NOT NULL`); missing fields count as null
+    IsNull {
+        /// Field name
+        field: String,
+        /// Whether the test is `IS NOT NULL`
+        negated: bool,
+    },
+    /// `field IN (literal, ...)`: the value equals one of the literals
+    In {
+        /// Field name
+        field: String,
+        /// Accepted literals
+        values: Vec<Value>,
+    },
+    /// `field STARTS WITH 'prefix'`: a string value beginning with `prefix`
+    Prefix {
+        /// Field name
+        field: String,
+        /// Required prefix
+        prefix: String,
+    },
+    /// `field MATCHES 'regex'`: a string value the pattern matches anywhere
+    #[cfg(feature = "regex")]
+    Matches {
+        /// Field name
+        field: String,
+        /// Compiled pattern
+        pattern: Pattern,
+    },
+    /// Both predicates hold
+    And(Box<Predicate>, Box<Predicate>),
+    /// Either predicate holds
+    Or(Box<Predicate>, Box<Predicate>),
+    /// Predicate does not hold
+    Not(Box<Predicate>),
+}
+
+/// Compiled regular expression of a [`Predicate::Matches`] test, compared by
+/// its source text.
+#[cfg(feature = "regex")]
+#[derive(Debug, Clone)]
+pub struct Pattern(regex::Regex);
+
+#[cfg(feature = "regex")]
+impl Pattern {
+    /// Compile `pattern` (`regex` crate syntax).
+    pub fn new(pattern: &str) -> Result<Self> {
+        regex::Regex::new(pattern).map(Pattern).map_err(|err| {
+            JacError::InvalidArgument(format!("invalid regex '{}': {}", pattern, err))
+        })
+    }
+
+    /// Source text of the pattern.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Whether the pattern matches anywhere in `text`.
+    pub fn is_match(&self, text: &str) -> bool {
+        self.0.is_match(text)
+    }
+}
+
+#[cfg(feature = "regex")]
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+/// Work done applying a filter to a file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FilterSummary {
+    /// Blocks decoded and evaluated
+    pub blocks_scanned: u64,
+    /// Blocks skipped from their statistics alone
+    pub blocks_pruned: u64,
+    /// Records of scanned blocks the filter accepted
+    pub records_matched: u64,
+    /// Dictionary entries tested in place of the records referencing them
+    pub dictionary_entries_tested: u64,
+    /// Dictionary entries the tests accepted
+    pub dictionary_entries_matched: u64,
+}
+
+/// Statistics of a block available before it is decoded, consulted by
+/// [`Predicate::may_match`].
+pub trait BlockStats {
+    /// Records holding `field`, nulls included, or `None` when the block
+    /// lacks the field.
+    fn present_count(&self, field: &str) -> Option<usize>;
+
+    /// Present values of `field` that are null, when the block stores field
+    /// statistics.
+    fn null_count(&self, field: &str) -> Option<usize> {
+        let _ = field;
+        None
+    }
+
+    /// Smallest and largest numeric value of `field`, when the block stores
+    /// a quantile sketch of them.
+    fn numeric_range(&self, field: &str) -> Option<(f64, f64)> {
+        let _ = field;
+        None
+    }
+
+    /// Every distinct non-null value of `field` in the block when they are
+    /// all strings and known without decoding the column (from its
+    /// dictionary); `None` otherwise.
+    fn strings(&mut self, field: &str) -> Result<Option<Vec<String>>> {
+        let _ = field;
+        Ok(None)
+    }
+}
+
+impl Predicate {
+    /// Parse filter text such as `level = 'ERROR' and code >= 500`.
+    pub fn parse(text: &str) -> Result<Predicate> {
+        let mut parser = Parser::new(text, "filter")?;
+        let predicate = parser.predicate()?;
+        if let Some(token) = parser.peek() {
+            return Err(parser.error(format!("unexpected {}", token.describe())));
+        }
+        Ok(predicate)
+    }
+
+    /// Fields the predicate reads, in first-use order.
+    pub fn fields(&self) -> Vec<String> {
+        let mut fields = Vec::new();
+        self.collect_fields(&mut fields);
+        fields
+    }
+
+    pub(crate) fn collect_fields(&self, fields: &mut Vec<String>) {
+        match self {
+            Predicate::And(a, b) | Predicate::Or(a, b) => {
+                a.collect_fields(fields);
+                b.collect_fields(fields);
+            }
+            Predicate::Not(inner) => inner.collect_fields(fields),
+            leaf => {
+                let field = leaf.field();
+                if !fields.iter().any(|known| known == field) {
+                    fields.push(field.to_string());
+                }
+            }
+        }
+    }
+
+    /// Evaluate the predicate against a field lookup.
+    pub fn matches<'a>(&self, lookup: &impl Fn(&str) -> Option<&'a Value>) -> bool {
+        match self {
+            Predicate::And(a, b) => a.matches(lookup) && b.matches(lookup),
+            Predicate::Or(a, b) => a.matches(lookup) || b.matches(lookup),
+            Predicate::Not(inner) => !inner.matches(lookup),
+            leaf => leaf.test(lookup(leaf.field())),
+        }
+    }
+
+    /// Evaluate the predicate for every record of a block from its decoded
+    /// columns, giving one flag per record. `column` returns the values of a
+    /// field (one per record, `None` where absent), or `None` when the block
+    /// lacks the field.
+    pub fn evaluate<'a>(
+        &self,
+        record_count: usize,
+        column: &impl Fn(&str) -> Option<&'a [Option<Value>]>,
+    ) -> Vec<bool> {
+        match self {
+            Predicate::And(a, b) => {
+                let mut selected = a.evaluate(record_count, column);
+                if selected.contains(&true) {
+                    for (keep, other) in selected.iter_mut().zip(b.evaluate(record_count, column)) {
+                        *keep &= other;
+                    }
+                }
+                selected
+            }
+            Predicate::Or(a, b) => {
+                let mut selected = a.evaluate(record_count, column);
+                if selected.contains(&false) {
+                    for (keep, other) in selected.iter_mut().zip(b.evaluate(record_count, column)) {
+                        *keep |= other;
+                    }
+                }
+                selected
+            }
+            Predicate::Not(inner) => inner
+                .evaluate(record_count, column)
+                .into_iter()
+                .map(|keep| !keep)
+                .collect(),
+            leaf => match column(leaf.field()) {
+                Some(values) => values
+                    .iter()
+                    .map(|value| leaf.test(value.as_ref()))
+                    .collect(),
+                None => vec![leaf.test(None); record_count],
+            },
+        }
+    }
+
+    /// Evaluate the predicate for every record of a decoded block, reading
+    /// only the fields it tests. Strings are tested in place, and a
+    /// dictionary-encoded field once per entry; `summary` counts the
+    /// dictionary entries tested and accepted.
+    pub fn evaluate_block(
+        &self,
+        decoder: &BlockDecoder,
+        summary: &mut FilterSummary,
+    ) -> Result<Vec<bool>> {
+        let fields = self.fields();
+        let segments = fields
+            .iter()
+            .map(|field| decoder.field_segment(field))
+            .collect::<Result<Vec<_>>>()?;
+        let segment = |name: &str| {
+            fields
+                .iter()
+                .position(|field| field == name)
+                .and_then(|idx| segments[idx].as_ref())
+        };
+        self.evaluate_segments(decoder.header().record_count, &segment, summary)
+    }
+
+    fn evaluate_segments<'a>(
+        &self,
+        record_count: usize,
+        segment: &impl Fn(&str) -> Option<&'a FieldSegmentDecoder>,
+        summary: &mut FilterSummary,
+    ) -> Result<Vec<bool>> {
+        Ok(match self {
+            Predicate::And(a, b) => {
+                let mut selected = a.evaluate_segments(record_count, segment, summary)?;
+                if selected.contains(&true) {
+                    let other = b.evaluate_segments(record_count, segment, summary)?;
+                    for (keep, other) in selected.iter_mut().zip(other) {
+                        *keep &= other;
+                    }
+                }
+                selected
+            }
+            Predicate::Or(a, b) => {
+                let mut selected = a.evaluate_segments(record_count, segment, summary)?;
+                if selected.contains(&false) {
+                    let other = b.evaluate_segments(record_count, segment, summary)?;
+                    for (keep, other) in selected.iter_mut().zip(other) {
+                        *keep |= other;
+                    }
+                }
+                selected
+            }
+            Predicate::Not(inner) => inner
+                .evaluate_segments(record_count, segment, summary)?
+                .into_iter()
+                .map(|keep| !keep)
+                .collect(),
+            leaf => match segment(leaf.field()) {
+                Some(segment) => leaf.test_segment(segment, summary)?,
+                None => vec![leaf.test(None); record_count],
+            },
+        })
+    }
+
+    /// Apply a leaf predicate to every record of its field's segment.
+    fn test_segment(
+        &self,
+        segment: &FieldSegmentDecoder,
+        summary: &mut FilterSummary,
+    ) -> Result<Vec<bool>> {
+        let entries: Vec<bool> = segment
+            .dictionary()
+            .unwrap_or_default()
+            .iter()
+            .map(|entry| self.test_str(entry))
+            .collect();
+        if segment.dictionary().is_some() {
+            summary.dictionary_entries_tested += entries.len() as u64;
+            summary.dictionary_entries_matched += entries.iter().filter(|hit| **hit).count() as u64;
+        }
+        (0..segment.record_count())
+            .map(|idx| {
+                if let Some(entry) = segment.dictionary_index(idx) {
+                    return Ok(entries[entry]);
+                }
+                Ok(match segment.get_ref(idx)? {
+                    Some(ValueRef::Str(text)) => self.test_str(text),
+                    Some(ValueRef::Null) | None => self.test(None),
+                    Some(_) => self.test(segment.get_value(idx)?.as_ref()),
+                })
+            })
+            .collect()
+    }
+
+    /// Conservatively decide from `stats` whether any record of a block can
+    /// satisfy the predicate; `false` means the block can be skipped.
+    pub fn may_match(&self, stats: &mut impl BlockStats) -> Result<bool> {
+        Ok(match self {
+            Predicate::And(a, b) => a.may_match(stats)? && b.may_match(stats)?,
+            Predicate::Or(a, b) => a.may_match(stats)? || b.may_match(stats)?,
+            Predicate::Not(_) | Predicate::IsNull { negated: false, .. } => true,
+            // Every other leaf needs a non-null value
+            leaf => {
+                let field = leaf.field();
+                let present = stats.present_count(field).unwrap_or(0);
+                if present == stats.null_count(field).unwrap_or(0) {
+                    return Ok(false);
+                }
+                if let Predicate::IsNull { .. } = leaf {
+                    return Ok(true);
+                }
+                if let Some(range) = stats.numeric_range(field) {
+                    if !leaf.may_match_range(range) {
+                        return Ok(false);
+                    }
+                }
+                match stats.strings(field)? {
+                    Some(strings) => strings.iter().any(|value| leaf.test_str(value)),
+                    None => true,
+                }
+            }
+        })
+    }
+
+    /// Whether a leaf with a numeric literal can accept a value within
+    /// `min..=max`, the range of the field's numeric values; only numbers
+    /// compare with such a literal. Other leaves are not ruled out.
+    fn may_match_range(&self, (min, max): (f64, f64)) -> bool {
+        // Values and literals are compared as rounded `f64`, which keeps the
+        // order strict inequalities rely on
+        let within = |literal: f64| min <= literal && literal <= max;
+        match self {
+            Predicate::Compare {
+                op,
+                value: Value::Number(literal),
+                ..
+            } => {
+                let Some(literal) = literal.as_f64() else {
+                    return true;
+                };
+                match op {
+                    CompareOp::Eq => within(literal),
+                    CompareOp::Ne => true,
+                    CompareOp::Lt | CompareOp::Le => min <= literal,
+                    CompareOp::Gt | CompareOp::Ge => max >= literal,
+                }
+            }
+            Predicate::In { values, .. } if values.iter().all(Value::is_number) => values
+                .iter()
+                .any(|literal| literal.as_f64().map_or(true, within)),
+            _ => true,
+        }
+    }
+
+    /// Field tested by a leaf predicate.
+    fn field(&self) -> &str {
+        match self {
+            Predicate::Compare { field, .. }
+            | Predicate::IsNull { field, .. }
+            | Predicate::In { field, .. }
+            | Predicate::Prefix { field, .. } => field,
+            #[cfg(feature = "regex")]
+            Predicate::Matches { field, .. } => field,
+            Predicate::And(..) | Predicate::Or(..) | Predicate::Not(_) => {
+                unreachable!("boolean operators have no field of their own")
+            }
+        }
+    }
+
+    /// Apply a leaf predicate to one value of its field.
+    fn test(&self, value: Option<&Value>) -> bool {
+        if let Some(Value::String(text)) = value {
+            return self.test_str(text);
+        }
+        let value = value.filter(|value| !value.is_null());
+        match self {
+            Predicate::Compare {
+                op, value: literal, ..
+            } => value
+                .and_then(|value| compare(value, literal))
+                .is_some_and(|ordering| op.accepts(ordering)),
+            Predicate::IsNull { negated, .. } => value.is_none() != *negated,
+            Predicate::In { values, .. } => value.is_some_and(|value| {
+                values
+                    .iter()
+                    .any(|literal| compare(value, literal) == Some(Ordering::Equal))
+            }),
+            Predicate::Prefix { .. } => false,
+            #[cfg(feature = "regex")]
+            Predicate::Matches { .. } => false,
+            Predicate::And(..) | Predicate::Or(..) | Predicate::Not(_) => {
+                unreachable!("boolean operators are evaluated through their operands")
+            }
+        }
+    }
+
+    /// Apply a leaf predicate to a string value of its field.
+    fn test_str(&self, text: &str) -> bool {
+        match self {
+            Predicate::Compare {
+                op,
+                value: Value::String(literal),
+                ..
+            } => op.accepts(text.cmp(literal.as_str())),
+            Predicate::Compare { .. } => false,
+            Predicate::IsNull { negated, .. } => *negated,
+            Predicate::In { values, .. } => {
+                values.iter().any(|literal| literal.as_str() == Some(text))
+            }
+            Predicate::Prefix { prefix, .. } => text.starts_with(prefix.as_str()),
+            #[cfg(feature = "regex")]
+            Predicate::Matches { pattern, .. } => pattern.is_match(text),
+            Predicate::And(..) | Predicate::Or(..) | Predicate::Not(_) => {
+                unreachable!("boolean operators are evaluated through their operands")
+            }
+        }
+    }
+}
+
+impl CompareOp {
+    fn accepts(self, ordering: Ordering) -> bool {
+        match self {
+            CompareOp::Eq => ordering == Ordering::Equal,
+            CompareOp::Ne => ordering != Ordering::Equal,
+            CompareOp::Lt => ordering == Ordering::Less,
+            CompareOp::Le => ordering != Ordering::Greater,
+            CompareOp::Gt => ordering == Ordering::Greater,
+            CompareOp::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+fn compare(actual: &Value, literal: &Value) -> Option<Ordering> {
+    match (actual, literal) {
+        (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            _ => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        },
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+/// [`BlockStats`] of one block of a reader: presence and null counts from
+/// its field directory and strings from the dictionaries of its raw segments.
+pub(crate) struct ReaderBlockStats<'a, R: Read + Seek> {
+    pub(crate) reader: &'a mut JacReader<R>,
+    pub(crate) block: &'a BlockHandle,
+}
+
+impl<R: Read + Seek> BlockStats for ReaderBlockStats<'_, R> {
+    fn present_count(&self, field: &str) -> Option<usize> {
+        self.block
+            .field_entry(field)
+            .map(|entry| entry.value_count_present)
+    }
+
+    fn null_count(&self, field: &str) -> Option<usize> {
+        let entry = self.block.field_entry(field)?;
+        entry.stats.as_ref().map(|stats| stats.null_count)
+    }
+
+    fn numeric_range(&self, field: &str) -> Option<(f64, f64)> {
+        let entry = self.block.field_entry(field)?;
+        let quantiles = entry.quantiles.as_ref()?;
+        Some((quantiles.min(), quantiles.max()))
+    }
+
+    fn strings(&mut self, field: &str) -> Result<Option<Vec<String>>> {
+        let segment = self.reader.read_raw_segment(self.block, field)?;
+        let histogram = read_dictionary_histogram(
+            &segment.bytes,
+            &segment.entry,
+            self.block.record_count,
+            self.reader.limits(),
+        )?;
+        Ok(histogram.map(|histogram| {
+            histogram
+                .entries
+                .into_iter()
+                .zip(histogram.counts)
+                .filter(|(_, count)| *count > 0)
+                .map(|(entry, _)| entry)
+                .collect()
+        }))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Ident(String),
+    Quoted(String),
+    Str(String),
+    Number(Value),
+    Op(CompareOp),
+    Comma,
+    Star,
+    LParen,
+    RParen,
+}
+
+impl Token {
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Token::Ident(s) | Token::Quoted(s) => format!("'{}'", s),
+            Token::Str(s) => format!("string '{}'", s),
+            Token::Number(n) => format!("number {}", n),
+            Token::Op(_) => "operator".to_string(),
+            Token::Comma => "','".to_string(),
+            Token::Star => "'*'".to_string(),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+        }
+    }
+
+    pub(crate) fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Ident(s) if s.eq_ignore_ascii_case(keyword))
+    }
+}
+
+fn syntax_error(kind: &str, message: String) -> JacError {
+    JacError::InvalidArgument(format!("Invalid {}: {}", kind, message))
+}
+
+fn tokenize(text: &str, kind: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '*' => {
+                tokens.push(Token::Star);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '=' => {
+                tokens.push(Token::Op(CompareOp::Eq));
+                i += 1;
+            }
+            '!' | '<' | '>' => {
+                let next = chars.get(i + 1).copied();
+                let (op, len) = match (c, next) {
+                    ('!', Some('=')) => (CompareOp::Ne, 2),
+                    ('<', Some('>')) => (CompareOp::Ne, 2),
+                    ('<', Some('=')) => (CompareOp::Le, 2),
+                    ('>', Some('=')) => (CompareOp::Ge, 2),
+                    ('<', _) => (CompareOp::Lt, 1),
+                    ('>', _) => (CompareOp::Gt, 1),
+                    _ => return Err(syntax_error(kind, "expected '!='".to_string())),
+                };
+                tokens.push(Token::Op(op));
+                i += len;
+            }
+            '\'' | '"' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => {
+                            return Err(syntax_error(kind, format!("unterminated {} quote", c)))
+                        }
+                        Some(&ch) if ch == c => {
+                            if chars.get(i + 1) == Some(&c) {
+                                value.push(c);
+                                i += 2;
+                            } else {
+                                i += 1;
+                                break;
+                            }
+                        }
+                        Some(&ch) => {
+                            value.push(ch);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push(if c == '\'' {
+                    Token::Str(value)
+                } else {
+                    Token::Quoted(value)
+                });
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let start = i;
+                i += 1;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || matches!(chars[i], '.' | '+' | '-'))
+                {
+                    i += 1;
+                }
+                let literal: String = chars[start..i].iter().collect();
+                let number = literal
+                    .parse::<i64>()
+                    .map(Value::from)
+                    .ok()
+                    .or_else(|| {
+                        literal
+                            .parse::<f64>()
+                            .ok()
+                            .and_then(Number::from_f64)
+                            .map(Value::Number)
+                    })
+                    .ok_or_else(|| syntax_error(kind, format!("invalid number '{}'", literal)))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '.' | '$'))
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            other => {
+                return Err(syntax_error(
+                    kind,
+                    format!("unexpected character '{}'", other),
+                ))
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+const RESERVED: &[&str] = &[
+    "select", "where", "limit", "and", "or", "not", "is", "null", "true", "false", "in", "starts",
+    "matches",
+];
+
+/// Recursive-descent parser over filter tokens; queries extend it with their
+/// own clauses.
+pub(crate) struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// What is being parsed, for error messages ("query" or "filter")
+    kind: &'static str,
+}
+
+impl Parser {
+    pub(crate) fn new(text: &str, kind: &'static str) -> Result<Self> {
+        Ok(Self {
+            tokens: tokenize(text, kind)?,
+            pos: 0,
+            kind,
+        })
+    }
+
+    pub(crate) fn error(&self, message: String) -> JacError {
+        syntax_error(self.kind, message)
+    }
+
+    pub(crate) fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    pub(crate) fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    pub(crate) fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek().is_some_and(|t| t.is_keyword(keyword)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn field(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Token::Quoted(name)) => Ok(name),
+            Some(Token::Ident(name))
+                if !RESERVED.iter().any(|kw| name.eq_ignore_ascii_case(kw)) =>
+            {
+                Ok(name)
+            }
+            Some(token) => {
+                Err(self.error(format!("expected field name, found {}", token.describe())))
+            }
+            None => Err(self.error("expected field name".into())),
+        }
+    }
+
+    pub(crate) fn predicate(&mut self) -> Result<Predicate> {
+        let mut predicate = self.and()?;
+        while self.eat_keyword("or") {
+            predicate = Predicate::Or(Box::new(predicate), Box::new(self.and()?));
+        }
+        Ok(predicate)
+    }
+
+    fn and(&mut self) -> Result<Predicate> {
+        let mut predicate = self.factor()?;
+        while self.eat_keyword("and") {
+            predicate = Predicate::And(Box::new(predicate), Box::new(self.factor()?));
+        }
+        Ok(predicate)
+    }
+
+    fn factor(&mut self) -> Result<Predicate> {
+        if self.eat_keyword("not") {
+            return Ok(Predicate::Not(Box::new(self.factor()?)));
+        }
+        if self.eat(&Token::LParen) {
+            let inner = self.predicate()?;
+            if !self.eat(&Token::RParen) {
+                return Err(self.error("expected ')'".into()));
+            }
+            return Ok(inner);
+        }
+
+        let field = self.field()?;
+        if self.eat_keyword("is") {
+            let negated = self.eat_keyword("not");
+            if !self.eat_keyword("null") {
+                return Err(self.error("expected NULL after IS".into()));
+            }
+            return Ok(Predicate::IsNull { field, negated });
+        }
+        if self.eat_keyword("in") {
+            if !self.eat(&Token::LParen) {
+                return Err(self.error(format!("expected '(' after '{} IN'", field)));
+            }
+            let mut values = vec![self.literal(&field)?];
+            while self.eat(&Token::Comma) {
+                values.push(self.literal(&field)?);
+            }
+            if !self.eat(&Token::RParen) {
+                return Err(self.error("expected ')'".into()));
+            }
+            return Ok(Predicate::In { field, values });
+        }
+        if self.eat_keyword("starts") {
+            if !self.eat_keyword("with") {
+                return Err(self.error("expected WITH after STARTS".into()));
+            }
+            let prefix = self.string(&field)?;
+            return Ok(Predicate::Prefix { field, prefix });
+        }
+        if self.eat_keyword("matches") {
+            let pattern = self.string(&field)?;
+            #[cfg(feature = "regex")]
+            return Ok(Predicate::Matches {
+                field,
+                pattern: Pattern::new(&pattern).map_err(|err| self.error(err.to_string()))?,
+            });
+            #[cfg(not(feature = "regex"))]
+            return Err(self.error(format!(
+                "MATCHES '{}' requires the `regex` feature",
+                pattern
+            )));
+        }
+
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            _ => return Err(self.error(format!("expected comparison operator after '{}'", field))),
+        };
+        let value = self.literal(&field)?;
+        Ok(Predicate::Compare { field, op, value })
+    }
+
+    fn literal(&mut self, field: &str) -> Result<Value> {
+        match self.next() {
+            Some(Token::Str(s)) => Ok(Value::String(s)),
+            Some(Token::Number(n)) => Ok(n),
+            Some(token) if token.is_keyword("true") => Ok(Value::Bool(true)),
+            Some(token) if token.is_keyword("false") => Ok(Value::Bool(false)),
+            Some(token) if token.is_keyword("null") => {
+                Err(self.error(format!("use '{} IS NULL' to test for null", field)))
+            }
+            _ => Err(self.error(format!("expected literal after '{}'", field))),
+        }
+    }
+
+    fn string(&mut self, field: &str) -> Result<String> {
+        match self.next() {
+            Some(Token::Str(s)) => Ok(s),
+            _ => Err(self.error(format!("expected string literal after '{}'", field))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Default)]
+    struct FakeStats {
+        present: Vec<(&'static str, usize)>,
+        nulls: Vec<(&'static str, usize)>,
+        ranges: Vec<(&'static str, (f64, f64))>,
+        strings: Vec<(&'static str, Vec<&'static str>)>,
+    }
+
+    impl BlockStats for FakeStats {
+        fn present_count(&self, field: &str) -> Option<usize> {
+            self.present
+                .iter()
+                .find(|(name, _)| *name == field)
+                .map(|(_, count)| *count)
+        }
+
+        fn null_count(&self, field: &str) -> Option<usize> {
+            self.nulls
+                .iter()
+                .find(|(name, _)| *name == field)
+                .map(|(_, count)| *count)
+        }
+
+        fn numeric_range(&self, field: &str) -> Option<(f64, f64)> {
+            self.ranges
+                .iter()
+                .find(|(name, _)| *name == field)
+                .map(|(_, range)| *range)
+        }
+
+        fn strings(&mut self, field: &str) -> Result<Option<Vec<String>>> {
+            Ok(self
+                .strings
+                .iter()
+                .find(|(name, _)| *name == field)
+                .map(|(_, values)| values.iter().map(|s| s.to_string()).collect()))
+        }
+    }
+
+    #[test]
+    fn parses_in_lists_prefixes_and_precedence() {
+        let predicate =
+            Predicate::parse("level IN ('WARN', 'ERROR') and not path starts with '/health'")
+                .unwrap();
+        assert_eq!(
+            predicate,
+            Predicate::And(
+                Box::new(Predicate::In {
+                    field: "level".into(),
+                    values: vec![json!("WARN"), json!("ERROR")],
+                }),
+                Box::new(Predicate::Not(Box::new(Predicate::Prefix {
+                    field: "path".into(),
+                    prefix: "/health".into(),
+                }))),
+            )
+        );
+        assert_eq!(predicate.fields(), ["level", "path"]);
+
+        for text in [
+            "level in ()",
+            "level in 'a'",
+            "path starts 'a'",
+            "path starts with 5",
+            "level = 'a' extra",
+            "level in (null)",
+        ] {
+            assert!(Predicate::parse(text).is_err(), "{}", text);
+        }
+        let err = Predicate::parse("a = ").unwrap_err();
+        assert!(
+            matches!(&err, JacError::InvalidArgument(message) if message.contains("Invalid filter"))
+        );
+    }
+
+    #[test]
+    fn columnar_evaluation_agrees_with_record_evaluation() {
+        let records = [
+            json!({"level": "ERROR", "code": 500, "path": "/api/a"}),
+            json!({"level": "INFO", "code": 200, "path": "/health"}),
+            json!({"level": "WARN", "path": null}),
+            json!({"code": 404}),
+        ];
+        let fields = ["level", "code", "path"];
+        let columns: Vec<Vec<Option<Value>>> = fields
+            .iter()
+            .map(|field| records.iter().map(|r| r.get(*field).cloned()).collect())
+            .collect();
+        let column = |name: &str| {
+            fields
+                .iter()
+                .position(|field| *field == name)
+                .map(|idx| columns[idx].as_slice())
+        };
+
+        for text in [
+            "level in ('WARN', 'ERROR')",
+            "code in (404, 500.0) or path starts with '/he'",
+            "not path starts with '/api' and code is not null",
+            "missing in (1) or level is null",
+            "code > 250 and not level = 'ERROR'",
+        ] {
+            let predicate = Predicate::parse(text).unwrap();
+            let expected: Vec<bool> = records
+                .iter()
+                .map(|record| predicate.matches(&|field: &str| record.get(field)))
+                .collect();
+            assert_eq!(
+                predicate.evaluate(records.len(), &column),
+                expected,
+                "{}",
+                text
+            );
+        }
+        assert_eq!(
+            Predicate::parse("level in ('WARN', 'ERROR')")
+                .unwrap()
+                .evaluate(records.len(), &column),
+            [true, false, true, false]
+        );
+    }
+
+    #[test]
+    fn block_stats_prune_conservatively() {
+        let mut stats = FakeStats {
+            present: vec![("level", 8), ("code", 8), ("empty", 0), ("nulls", 8)],
+            nulls: vec![("code", 2), ("nulls", 8)],
+            ranges: vec![("code", (200.0, 404.0))],
+            strings: vec![("level", vec!["INFO", "WARN"])],
+        };
+        let mut may_match = |text: &str| {
+            Predicate::parse(text)
+                .unwrap()
+                .may_match(&mut stats)
+                .unwrap()
+        };
+
+        assert!(!may_match("level = 'ERROR'"));
+        assert!(may_match("level in ('ERROR', 'WARN')"));
+        assert!(!may_match("level starts with 'ERR'"));
+        assert!(!may_match("level > 5"));
+        assert!(may_match("code > 5"));
+        assert!(!may_match("missing = 1 or empty = 1"));
+        assert!(!may_match("empty is not null"));
+        assert!(may_match("empty is null"));
+        assert!(may_match("not level = 'INFO'"));
+        // Null counts rule out blocks where every present value is null
+        assert!(!may_match("nulls > 0 or nulls is not null"));
+        assert!(may_match("code is not null and nulls is null"));
+        // Numeric ranges rule out comparisons no value in them satisfies
+        assert!(!may_match("code > 500"));
+        assert!(may_match("code >= 404"));
+        assert!(!may_match("code < 150.5 or code = 100"));
+        assert!(may_match("code <= 200"));
+        assert!(may_match("code != 100"));
+        assert!(!may_match("code in (100, 500)"));
+        assert!(may_match("code in (100, 300)"));
+        assert!(may_match("code in (100, 'x')"));
+        assert!(may_match("code > 'a'"));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn matches_tests_string_values_with_a_regex() {
+        let predicate = Predicate::parse("msg matches '^time(out|d out)$'").unwrap();
+        let test = |value: Value| predicate.matches(&|_: &str| Some(&value));
+        assert!(test(json!("timeout")));
+        assert!(!test(json!("timeouts")));
+        assert!(!test(json!(42)));
+        assert!(matches!(
+            Predicate::parse("msg matches '('"),
+            Err(JacError::InvalidArgument(_))
+        ));
+        assert_eq!(
+            predicate,
+            Predicate::parse("msg MATCHES '^time(out|d out)$'").unwrap()
+        );
+    }
+}
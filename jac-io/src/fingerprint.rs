@@ -0,0 +1,137 @@
+//! Stable per-record fingerprints.
+//!
+//! A fingerprint is the BLAKE3 hash, as 64 lowercase hex digits, of a record's
+//! canonical JSON: object keys sorted bytewise at every depth, no whitespace,
+//! and integral floats within ±2^53 written as integers, so `1.0` and `1`
+//! hash alike. Setting [`InjectFields::fingerprint`](crate::InjectFields)
+//! stores it as a synthetic column at pack time, and
+//! [`JacReader::verify_fingerprints`](crate::JacReader::verify_fingerprints) and
+//! [`JacReader::fingerprint_index`](crate::JacReader::fingerprint_index) check or
+//! join on it when reading. The same canonical JSON is what unpacking writes
+//! with [`DecompressOptions::canonical_json`](crate::DecompressOptions).
+
+use serde_json::{Map, Value};
+
+/// Largest integer every `f64` below it represents exactly.
+const MAX_EXACT_F64_INT: f64 = 9_007_199_254_740_992.0;
+
+/// Fingerprint of `record`, ignoring the top-level fields in `exclude`.
+pub fn record_fingerprint(record: &Map<String, Value>, exclude: &[&str]) -> String {
+    canonical_hash(record, exclude).to_hex().to_string()
+}
+
+/// Append the canonical JSON of `record` to `out`.
+pub fn write_canonical_json(record: &Map<String, Value>, out: &mut Vec<u8>) {
+    write_object(record, &[], out);
+}
+
+/// BLAKE3 hash of the canonical JSON of `record` without the `exclude` fields.
+pub(crate) fn canonical_hash(record: &Map<String, Value>, exclude: &[&str]) -> blake3::Hash {
+    let mut canonical = Vec::with_capacity(256);
+    write_object(record, exclude, &mut canonical);
+    blake3::hash(&canonical)
+}
+
+/// Outcome of [`JacReader::verify_fingerprints`](crate::JacReader::verify_fingerprints).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FingerprintCheck {
+    /// Records read
+    pub records: u64,
+    /// Records whose fingerprint field is absent or not a string
+    pub missing: u64,
+    /// Zero-based indices of records whose content no longer matches their
+    /// stored fingerprint
+    pub mismatched: Vec<u64>,
+}
+
+impl FingerprintCheck {
+    /// Whether every record carries a matching fingerprint.
+    pub fn is_ok(&self) -> bool {
+        self.missing == 0 && self.mismatched.is_empty()
+    }
+}
+
+fn write_object(map: &Map<String, Value>, exclude: &[&str], out: &mut Vec<u8>) {
+    let mut entries: Vec<(&String, &Value)> = map
+        .iter()
+        .filter(|(key, _)| !exclude.contains(&key.as_str()))
+        .collect();
+    entries.sort_unstable_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+
+    out.push(b'{');
+    for (idx, (key, value)) in entries.into_iter().enumerate() {
+        if idx > 0 {
+            out.push(b',');
+        }
+        write_string(key, out);
+        out.push(b':');
+        write_value(value, out);
+    }
+    out.push(b'}');
+}
+
+fn write_value(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.extend_from_slice(b"null"),
+        Value::Bool(true) => out.extend_from_slice(b"true"),
+        Value::Bool(false) => out.extend_from_slice(b"false"),
+        Value::Number(number) => match number.as_f64() {
+            Some(float)
+                if !number.is_i64()
+                    && !number.is_u64()
+                    && float.fract() == 0.0
+                    && float.abs() < MAX_EXACT_F64_INT =>
+            {
+                out.extend_from_slice((float as i64).to_string().as_bytes())
+            }
+            _ => out.extend_from_slice(number.to_string().as_bytes()),
+        },
+        Value::String(s) => write_string(s, out),
+        Value::Array(items) => {
+            out.push(b'[');
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    out.push(b',');
+                }
+                write_value(item, out);
+            }
+            out.push(b']');
+        }
+        Value::Object(map) => write_object(map, &[], out),
+    }
+}
+
+fn write_string(s: &str, out: &mut Vec<u8>) {
+    serde_json::to_writer(&mut *out, s).expect("writing to a Vec cannot fail");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn fingerprint_ignores_key_order_float_form_and_excluded_fields() {
+        let a = object(json!({"b": [1.0, {"y": 2, "x": "é"}], "a": null, "_hash": "x"}));
+        let b = object(json!({"a": null, "b": [1, {"x": "é", "y": 2.0}]}));
+        assert_eq!(
+            record_fingerprint(&a, &["_hash"]),
+            record_fingerprint(&b, &[])
+        );
+        assert_eq!(record_fingerprint(&b, &[]).len(), 64);
+
+        let mut canonical = Vec::new();
+        write_object(&a, &["_hash"], &mut canonical);
+        assert_eq!(
+            String::from_utf8(canonical).unwrap(),
+            r#"{"a":null,"b":[1,{"x":"é","y":2}]}"#
+        );
+
+        let c = object(json!({"a": null, "b": [1.5, {"x": "é", "y": 2}]}));
+        assert_ne!(record_fingerprint(&b, &[]), record_fingerprint(&c, &[]));
+    }
+}
@@ -0,0 +1,206 @@
+//! Adding an index footer to files written without one.
+//!
+//! Files finished with [`JacWriter::finish_without_index`](crate::JacWriter::finish_without_index)
+//! (or cut off before their writer finished) can still be read front to back,
+//! but every reader has to scan them block by block. [`execute_build_index`]
+//! performs that scan once and appends the footer and trailing pointer the
+//! writer would have written, in place, so later projections and seeks use the
+//! index. A stale or corrupt footer (see [`IndexStatus`]) is replaced the same
+//! way.
+
+use crate::{IndexStatus, JacReader};
+use jac_codec::DecompressOpts;
+use jac_format::{BlockIndexEntry, IndexFooter, Result};
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Outcome of [`execute_build_index`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexBuildSummary {
+    /// How the footer was located before the build
+    pub previous_status: IndexStatus,
+    /// Whether a footer was written; `false` when the file already had a valid one
+    pub written: bool,
+    /// Blocks listed in the index
+    pub blocks: usize,
+    /// Records in those blocks
+    pub records: u64,
+    /// Offset the index footer starts at
+    pub index_offset: u64,
+}
+
+/// Scan the blocks of the `.jac` file at `path` and append an index footer.
+///
+/// Files whose footer is already valid are left untouched. Otherwise anything
+/// after the last complete block (an unusable footer, trailing padding, a final
+/// block cut off part way through) is truncated and a fresh footer and pointer
+/// are written. The scan reads the file as a [`JacReader::snapshot`], so only a
+/// partial final block is tolerated: a corrupt complete block fails the build
+/// before the file is modified.
+pub fn execute_build_index(path: impl AsRef<Path>) -> Result<IndexBuildSummary> {
+    let path = path.as_ref();
+    let mut reader = JacReader::open_snapshot(path, DecompressOpts::default())?;
+    let previous_status = reader.index_status();
+
+    let mut entries = Vec::new();
+    let mut records = 0u64;
+    let mut data_end = reader.data_start();
+    for block in reader.blocks() {
+        let block = block?;
+        records += block.record_count as u64;
+        data_end = block.offset + block.size as u64;
+        entries.push(BlockIndexEntry {
+            block_offset: block.offset,
+            block_size: block.size,
+            record_count: block.record_count,
+        });
+    }
+    let field_names = reader.index_field_names();
+    drop(reader);
+
+    let blocks = entries.len();
+    if previous_status == IndexStatus::Valid {
+        return Ok(IndexBuildSummary {
+            previous_status,
+            written: false,
+            blocks,
+            records,
+            index_offset: data_end,
+        });
+    }
+
+    let index_bytes = IndexFooter {
+        blocks: entries,
+        field_names,
+    }
+    .encode()?;
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    file.set_len(data_end)?;
+    file.seek(SeekFrom::Start(data_end))?;
+    file.write_all(&index_bytes)?;
+    file.write_all(&data_end.to_le_bytes())?;
+    file.sync_all()?;
+
+    Ok(IndexBuildSummary {
+        previous_status,
+        written: true,
+        blocks,
+        records,
+        index_offset: data_end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CompressOpts, JacWriter};
+    use serde_json::json;
+    use std::fs::File;
+
+    fn write_unindexed(path: &Path, records: u64) {
+        let opts = CompressOpts {
+            block_target_records: 10,
+            ..CompressOpts::default()
+        };
+        let header = jac_format::FileHeader {
+            flags: 0,
+            default_compressor: opts.default_codec.compressor_id(),
+            default_compression_level: opts.default_codec.level(),
+            block_size_hint_records: opts.block_target_records,
+            user_metadata: Vec::new(),
+        };
+        let mut writer = JacWriter::new(File::create(path).unwrap(), header, opts).unwrap();
+        for i in 0..records {
+            let record = json!({"id": i, "level": if i % 3 == 0 { "warn" } else { "info" }});
+            writer.write_record(record.as_object().unwrap()).unwrap();
+        }
+        writer.finish_without_index().unwrap();
+    }
+
+    #[test]
+    fn index_is_appended_to_unindexed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.jac");
+        write_unindexed(&path, 35);
+        assert_eq!(
+            JacReader::open(&path, DecompressOpts::default())
+                .unwrap()
+                .index_status(),
+            IndexStatus::Absent
+        );
+
+        let summary = execute_build_index(&path).unwrap();
+        assert_eq!(summary.previous_status, IndexStatus::Absent);
+        assert!(summary.written);
+        assert_eq!((summary.blocks, summary.records), (4, 35));
+
+        let mut reader = JacReader::open(&path, DecompressOpts::default()).unwrap();
+        assert_eq!(reader.index_status(), IndexStatus::Valid);
+        assert_eq!(reader.count_records().unwrap(), 35);
+        let ids: Vec<_> = reader
+            .projection_stream("id".to_string())
+            .unwrap()
+            .map(|value| value.unwrap())
+            .collect();
+        assert_eq!(ids.len(), 35);
+        assert_eq!(ids[34], Some(json!(34)));
+
+        let again = execute_build_index(&path).unwrap();
+        assert!(!again.written);
+        assert_eq!(again.index_offset, summary.index_offset);
+    }
+
+    #[test]
+    fn corrupt_footer_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.jac");
+        write_unindexed(&path, 12);
+        let data_len = std::fs::metadata(&path).unwrap().len();
+        // A pointer leading to a footer that lists no blocks.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(
+            &IndexFooter {
+                blocks: Vec::new(),
+                field_names: None,
+            }
+            .encode()
+            .unwrap(),
+        )
+        .unwrap();
+        file.write_all(&data_len.to_le_bytes()).unwrap();
+        drop(file);
+
+        let summary = execute_build_index(&path).unwrap();
+        assert_eq!(
+            summary.previous_status,
+            IndexStatus::Invalid { pointer: data_len }
+        );
+        assert_eq!(summary.index_offset, data_len);
+        let mut reader = JacReader::open(&path, DecompressOpts::default()).unwrap();
+        assert_eq!(reader.index_status(), IndexStatus::Valid);
+        assert_eq!(reader.count_records().unwrap(), 12);
+    }
+
+    #[test]
+    fn partial_final_block_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.jac");
+        write_unindexed(&path, 35);
+        let len = std::fs::metadata(&path).unwrap().len();
+        // Cut the last block (5 records) off part way through.
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 10)
+            .unwrap();
+
+        let summary = execute_build_index(&path).unwrap();
+        assert!(summary.written);
+        assert_eq!((summary.blocks, summary.records), (3, 30));
+        let mut reader = JacReader::open(&path, DecompressOpts::default()).unwrap();
+        assert_eq!(reader.index_status(), IndexStatus::Valid);
+        assert_eq!(reader.count_records().unwrap(), 30);
+    }
+}
@@ -0,0 +1,103 @@
+//! Provenance field injection applied to records before they are encoded.
+
+use crate::{record_fingerprint, InjectFields};
+use serde_json::{Map, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Stateful injector for one compression request.
+pub(crate) struct FieldInjector {
+    fields: InjectFields,
+    next_row_id: u64,
+}
+
+impl FieldInjector {
+    /// Build an injector, or `None` when no field is configured.
+    pub(crate) fn new(fields: &InjectFields) -> Option<Self> {
+        fields.is_enabled().then(|| Self {
+            fields: fields.clone(),
+            next_row_id: fields.row_id_start,
+        })
+    }
+
+    pub(crate) fn apply(&mut self, record: &mut Map<String, Value>) {
+        // Hash before anything is injected; a stale fingerprint from the input
+        // is left out so re-packing a file keeps its fingerprints.
+        let fingerprint = self
+            .fields
+            .fingerprint
+            .as_deref()
+            .map(|field| record_fingerprint(record, &[field]));
+        if let Some(field) = &self.fields.row_id {
+            record.insert(field.clone(), Value::from(self.next_row_id));
+            self.next_row_id += 1;
+        }
+        if let Some(field) = &self.fields.ingest_timestamp {
+            let millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default();
+            record.insert(field.clone(), Value::from(millis));
+        }
+        if let Some(field) = &self.fields.source_field {
+            record.insert(
+                field.clone(),
+                Value::String(self.fields.source_label.clone()),
+            );
+        }
+        if let (Some(field), Some(fingerprint)) = (&self.fields.fingerprint, fingerprint) {
+            record.insert(field.clone(), Value::String(fingerprint));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn assigns_sequential_row_ids_and_overrides_input_values() {
+        let fields = InjectFields {
+            row_id: Some("_row_id".to_string()),
+            row_id_start: 10,
+            source_field: Some("_source".to_string()),
+            source_label: "edge-1".to_string(),
+            ..InjectFields::default()
+        };
+        let mut injector = FieldInjector::new(&fields).expect("enabled");
+
+        let mut first = json!({"a": 1, "_row_id": "stale"})
+            .as_object()
+            .unwrap()
+            .clone();
+        let mut second = Map::new();
+        injector.apply(&mut first);
+        injector.apply(&mut second);
+
+        assert_eq!(first["_row_id"], json!(10));
+        assert_eq!(second["_row_id"], json!(11));
+        assert_eq!(second["_source"], json!("edge-1"));
+        assert!(!second.contains_key("_ingest_ts"));
+        assert!(FieldInjector::new(&InjectFields::default()).is_none());
+    }
+
+    #[test]
+    fn fingerprint_excludes_other_injected_fields() {
+        let fields = InjectFields {
+            row_id: Some("_row_id".to_string()),
+            fingerprint: Some("_hash".to_string()),
+            ..InjectFields::default()
+        };
+        let mut injector = FieldInjector::new(&fields).expect("enabled");
+        let original = json!({"b": 2, "a": 1}).as_object().unwrap().clone();
+        let mut first = original.clone();
+        let mut second = original.clone();
+        second.insert("_hash".to_string(), json!("stale"));
+        injector.apply(&mut first);
+        injector.apply(&mut second);
+
+        assert_ne!(first["_row_id"], second["_row_id"]);
+        assert_eq!(first["_hash"], second["_hash"]);
+        assert_eq!(first["_hash"], json!(record_fingerprint(&original, &[])));
+    }
+}
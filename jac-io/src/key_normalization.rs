@@ -0,0 +1,243 @@
+//! Top-level key normalization for inputs merged from several producers.
+//!
+//! Archives aggregated from multiple producers often spell the same field
+//! differently (`userId` vs `userid`, or a key in decomposed vs composed
+//! Unicode), which splits one logical column into several dictionaries. A
+//! [`KeyNormalization`] rewrites top-level keys to one spelling before
+//! encoding; keys of a record that normalize to the same name are resolved by
+//! its collision policy. [`KeyNormalizationMetrics`] reports what was merged.
+
+use crate::DuplicateKeyPolicy;
+use jac_format::{JacError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+/// Distinct keys whose normalized form is remembered; later keys are
+/// normalized on every occurrence.
+const CACHE_LIMIT: usize = 16_384;
+
+/// Rewrites applied to top-level record keys.
+///
+/// Nested object keys are left alone; with `nested_opaque` they are stored
+/// verbatim anyway. Type coercion rules name keys in their normalized form.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyNormalization {
+    /// Map keys to lowercase (Unicode lowercase mapping, e.g. `userId` → `userid`)
+    pub case_fold: bool,
+    /// Convert keys to Unicode Normalization Form C
+    pub unicode_nfc: bool,
+    /// Behavior when two keys of one record normalize to the same name
+    /// (`LastWins`/`FirstWins` follow input order)
+    pub on_collision: DuplicateKeyPolicy,
+}
+
+impl Default for KeyNormalization {
+    fn default() -> Self {
+        Self {
+            case_fold: false,
+            unicode_nfc: false,
+            on_collision: DuplicateKeyPolicy::Error,
+        }
+    }
+}
+
+impl KeyNormalization {
+    /// Whether any rewrite is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.case_fold || self.unicode_nfc
+    }
+
+    /// Normalized form of `key`, or `None` when it is already normalized.
+    pub fn normalize(&self, key: &str) -> Option<String> {
+        let mut changed = None;
+        if self.case_fold {
+            changed = Some(key.to_lowercase()).filter(|lowered| lowered != key);
+        }
+        if self.unicode_nfc {
+            let current = changed.as_deref().unwrap_or(key);
+            if !is_nfc(current) {
+                changed = Some(current.nfc().collect());
+            }
+        }
+        changed.filter(|normalized| normalized != key)
+    }
+}
+
+/// Key normalization counters for a compression request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyNormalizationMetrics {
+    /// Key occurrences whose spelling changed and whose value was kept.
+    pub keys_renamed: u64,
+    /// Key occurrences discarded because another key normalized to the same name.
+    pub keys_dropped: u64,
+    /// Records in which two or more keys normalized to the same name.
+    pub collisions: u64,
+    /// Original spellings folded into each normalized name (changed keys only).
+    pub renamed: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Applies a [`KeyNormalization`] to records in stream order.
+pub(crate) struct KeyNormalizer {
+    config: KeyNormalization,
+    cache: HashMap<String, Option<String>>,
+    metrics: Arc<Mutex<KeyNormalizationMetrics>>,
+}
+
+impl KeyNormalizer {
+    /// Build a normalizer, or `None` when no rewrite is enabled.
+    pub(crate) fn new(
+        config: &KeyNormalization,
+        metrics: Arc<Mutex<KeyNormalizationMetrics>>,
+    ) -> Option<Self> {
+        config.is_enabled().then(|| Self {
+            config: config.clone(),
+            cache: HashMap::new(),
+            metrics,
+        })
+    }
+
+    fn normalized(&mut self, key: &str) -> Option<String> {
+        if let Some(cached) = self.cache.get(key) {
+            return cached.clone();
+        }
+        let normalized = self.config.normalize(key);
+        if self.cache.len() < CACHE_LIMIT {
+            self.cache.insert(key.to_string(), normalized.clone());
+        }
+        normalized
+    }
+
+    pub(crate) fn apply(&mut self, record: &mut Map<String, Value>) -> Result<()> {
+        let renames: Vec<Option<String>> = record.keys().map(|k| self.normalized(k)).collect();
+        if renames.iter().all(Option::is_none) {
+            return Ok(());
+        }
+
+        let original = std::mem::take(record);
+        let key_count = original.len();
+        // Original spelling behind each output key, for collision messages.
+        let mut sources: HashMap<String, String> = HashMap::with_capacity(original.len());
+        let mut renamed = Vec::new();
+        let mut collided = false;
+        for ((key, value), rename) in original.into_iter().zip(renames) {
+            let name = match rename {
+                Some(name) => {
+                    renamed.push((name.clone(), key.clone()));
+                    name
+                }
+                None => key.clone(),
+            };
+            if let Some(previous) = sources.get(&name) {
+                collided = true;
+                match self.config.on_collision {
+                    DuplicateKeyPolicy::Error => {
+                        return Err(JacError::Internal(format!(
+                            "keys '{}' and '{}' both normalize to '{}'",
+                            previous, key, name
+                        )));
+                    }
+                    DuplicateKeyPolicy::FirstWins => continue,
+                    DuplicateKeyPolicy::LastWins => {}
+                }
+            }
+            sources.insert(name.clone(), key);
+            record.insert(name, value);
+        }
+
+        let mut metrics = self
+            .metrics
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        metrics.keys_renamed += sources.iter().filter(|(name, key)| name != key).count() as u64;
+        metrics.keys_dropped += (key_count - record.len()) as u64;
+        metrics.collisions += u64::from(collided);
+        for (name, key) in renamed {
+            metrics.renamed.entry(name).or_default().insert(key);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn normalizer(
+        config: KeyNormalization,
+    ) -> (KeyNormalizer, Arc<Mutex<KeyNormalizationMetrics>>) {
+        let metrics = Arc::new(Mutex::new(KeyNormalizationMetrics::default()));
+        let normalizer = KeyNormalizer::new(&config, Arc::clone(&metrics)).expect("enabled");
+        (normalizer, metrics)
+    }
+
+    fn record(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn normalize_folds_case_and_composes_unicode() {
+        let config = KeyNormalization {
+            case_fold: true,
+            unicode_nfc: true,
+            ..KeyNormalization::default()
+        };
+        assert_eq!(config.normalize("userId").as_deref(), Some("userid"));
+        // "Cafe" + combining acute accent composes to a single code point.
+        assert_eq!(
+            config.normalize("Cafe\u{301}").as_deref(),
+            Some("caf\u{e9}")
+        );
+        assert_eq!(config.normalize("user_id"), None);
+        assert!(!KeyNormalization::default().is_enabled());
+    }
+
+    #[test]
+    fn collisions_follow_policy_and_are_counted() {
+        let input = json!({"userId": 1, "ts": 5, "userid": 2, "USERID": 3});
+
+        let (mut first, first_metrics) = normalizer(KeyNormalization {
+            case_fold: true,
+            on_collision: DuplicateKeyPolicy::FirstWins,
+            ..KeyNormalization::default()
+        });
+        let mut kept = record(input.clone());
+        first.apply(&mut kept).unwrap();
+        assert_eq!(Value::Object(kept), json!({"userid": 1, "ts": 5}));
+        let metrics = first_metrics.lock().unwrap().clone();
+        assert_eq!((metrics.keys_renamed, metrics.keys_dropped), (1, 2));
+
+        let (mut last, metrics) = normalizer(KeyNormalization {
+            case_fold: true,
+            on_collision: DuplicateKeyPolicy::LastWins,
+            ..KeyNormalization::default()
+        });
+        let mut kept = record(input.clone());
+        last.apply(&mut kept).unwrap();
+        assert_eq!(Value::Object(kept), json!({"userid": 3, "ts": 5}));
+        let metrics = metrics.lock().unwrap().clone();
+        assert_eq!(
+            (
+                metrics.keys_renamed,
+                metrics.keys_dropped,
+                metrics.collisions
+            ),
+            (1, 2, 1)
+        );
+        assert_eq!(
+            metrics.renamed["userid"].iter().collect::<Vec<_>>(),
+            vec!["USERID", "userId"]
+        );
+
+        let (mut strict, _) = normalizer(KeyNormalization {
+            case_fold: true,
+            ..KeyNormalization::default()
+        });
+        let err = strict.apply(&mut record(input)).unwrap_err();
+        assert!(err.to_string().contains("'userId' and 'userid'"), "{}", err);
+    }
+}
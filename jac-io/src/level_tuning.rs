@@ -0,0 +1,267 @@
+//! Automatic zstd level selection by sampling.
+//!
+//! With [`CompressOptions::zstd_auto_level`](crate::CompressOptions) set, the
+//! pipeline encodes the first records of the input into a trial block,
+//! compresses its segments at each candidate level, and keeps the cheapest
+//! level whose output is close to the best one. The decision, with every
+//! trial, is reported in [`CompressSummary::level_tuning`](crate::CompressSummary).
+
+use jac_codec::{BlockBuilder, Codec, CompressOpts, TryAddRecordOutcome};
+use jac_format::{JacError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::time::{Duration, Instant};
+
+/// Targets for automatic zstd level selection.
+///
+/// Only candidates compressing the sample within `max_time_factor` times the
+/// fastest candidate's time are eligible; among those, the lowest level whose
+/// output is at most `max_ratio_loss` larger than the smallest eligible output
+/// is chosen.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoLevelPolicy {
+    /// Zstd levels to try (tried in ascending order)
+    pub candidates: Vec<u8>,
+    /// Records encoded into the trial block (capped by the block target)
+    pub sample_records: usize,
+    /// Accepted size overhead relative to the best eligible level (0.02 = 2%)
+    pub max_ratio_loss: f64,
+    /// Accepted compression time relative to the fastest candidate
+    pub max_time_factor: f64,
+}
+
+impl Default for AutoLevelPolicy {
+    fn default() -> Self {
+        Self {
+            candidates: vec![1, 3, 6, 9, 12, 15, 19],
+            sample_records: 10_000,
+            max_ratio_loss: 0.02,
+            max_time_factor: 2.0,
+        }
+    }
+}
+
+/// Measurement for one candidate level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelTrial {
+    /// Zstd level tried
+    pub level: u8,
+    /// Compressed size of all sample segments
+    pub compressed_bytes: usize,
+    /// Time spent compressing the sample
+    pub elapsed: Duration,
+}
+
+/// Level chosen by automatic tuning and the measurements behind it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelTuning {
+    /// Level used for the whole file
+    pub chosen_level: u8,
+    /// Records in the trial block
+    pub sample_records: usize,
+    /// Uncompressed size of the trial block's segments
+    pub sample_bytes: usize,
+    /// One entry per candidate, in ascending level order
+    pub trials: Vec<LevelTrial>,
+}
+
+/// Outcome of sampling a record stream.
+#[derive(Default)]
+pub(crate) struct Sampled {
+    /// Records consumed from the stream; they must be encoded before the rest.
+    pub(crate) records: Vec<Map<String, Value>>,
+    /// `None` when the stream yielded no records.
+    pub(crate) tuning: Option<LevelTuning>,
+}
+
+/// Consume a sample from `stream` and pick a level under `policy`.
+pub(crate) fn sample_and_tune<I>(
+    stream: &mut I,
+    opts: &CompressOpts,
+    policy: &AutoLevelPolicy,
+) -> Result<Sampled>
+where
+    I: Iterator<Item = Result<Map<String, Value>>>,
+{
+    let mut candidates = policy.candidates.clone();
+    candidates.sort_unstable();
+    candidates.dedup();
+    if candidates.is_empty() {
+        return Err(JacError::Internal(
+            "zstd auto level requires at least one candidate level".to_string(),
+        ));
+    }
+
+    let mut builder = BlockBuilder::new(CompressOpts {
+        default_codec: Codec::None,
+        ..opts.clone()
+    });
+    let limit = policy.sample_records.min(opts.block_target_records).max(1);
+    let mut records = Vec::new();
+    for record in stream.by_ref() {
+        let record = record?;
+        let full = matches!(
+            builder.try_add_record(record.clone())?,
+            TryAddRecordOutcome::BlockFull { .. }
+        );
+        records.push(record);
+        if full || records.len() >= limit {
+            break;
+        }
+    }
+    if builder.is_empty() {
+        return Ok(Sampled {
+            records,
+            tuning: None,
+        });
+    }
+
+    let sample_records = builder.record_count();
+    let segments = builder.prepare_segments()?.field_segments;
+    let sample_bytes = segments
+        .iter()
+        .map(|(_, segment)| segment.uncompressed_payload.len())
+        .sum();
+
+    let mut trials = Vec::with_capacity(candidates.len());
+    for level in candidates {
+        let start = Instant::now();
+        let mut compressed_bytes = 0;
+        for (_, segment) in &segments {
+            compressed_bytes += segment.compress(Codec::Zstd(level))?.len();
+        }
+        trials.push(LevelTrial {
+            level,
+            compressed_bytes,
+            elapsed: start.elapsed(),
+        });
+    }
+
+    Ok(Sampled {
+        records,
+        tuning: Some(LevelTuning {
+            chosen_level: choose_level(&trials, policy),
+            sample_records,
+            sample_bytes,
+            trials,
+        }),
+    })
+}
+
+/// Apply the selection rule described on [`AutoLevelPolicy`] to `trials`.
+fn choose_level(trials: &[LevelTrial], policy: &AutoLevelPolicy) -> u8 {
+    let fastest = trials
+        .iter()
+        .map(|trial| trial.elapsed)
+        .min()
+        .unwrap_or_default();
+    let time_budget = fastest.mul_f64(policy.max_time_factor.max(1.0));
+    let eligible: Vec<&LevelTrial> = trials
+        .iter()
+        .filter(|trial| trial.elapsed <= time_budget)
+        .collect();
+    let best = eligible
+        .iter()
+        .map(|trial| trial.compressed_bytes)
+        .min()
+        .unwrap_or_default();
+    let size_budget = best as f64 * (1.0 + policy.max_ratio_loss.max(0.0));
+    eligible
+        .iter()
+        .find(|trial| trial.compressed_bytes as f64 <= size_budget)
+        .map(|trial| trial.level)
+        .unwrap_or(trials[0].level)
+}
+
+/// `codec` with its zstd level replaced by `level`; other codecs are unchanged.
+pub(crate) fn with_zstd_level(codec: Codec, level: u8) -> Codec {
+    match codec {
+        Codec::Zstd(_) => Codec::Zstd(level),
+        Codec::ZstdWithThreads { threads, .. } => Codec::ZstdWithThreads {
+            level: i32::from(level),
+            threads,
+        },
+        Codec::ZstdAdvanced {
+            window_log,
+            long_distance_matching,
+            target_block_size,
+            ..
+        } => Codec::ZstdAdvanced {
+            level: i32::from(level),
+            window_log,
+            long_distance_matching,
+            target_block_size,
+        },
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn trial(level: u8, compressed_bytes: usize, millis: u64) -> LevelTrial {
+        LevelTrial {
+            level,
+            compressed_bytes,
+            elapsed: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn choose_level_prefers_cheapest_level_near_best_ratio() {
+        let policy = AutoLevelPolicy::default();
+        // Level 9 is smallest within 2x of the fastest; level 6 is within 2% of it.
+        let trials = [
+            trial(1, 1_000, 10),
+            trial(3, 900, 12),
+            trial(6, 815, 15),
+            trial(9, 800, 19),
+            trial(19, 700, 200),
+        ];
+        assert_eq!(choose_level(&trials, &policy), 6);
+
+        let generous = AutoLevelPolicy {
+            max_time_factor: 100.0,
+            ..AutoLevelPolicy::default()
+        };
+        assert_eq!(choose_level(&trials, &generous), 19);
+    }
+
+    #[test]
+    fn sample_and_tune_returns_consumed_records_in_order() {
+        let records: Vec<Map<String, Value>> = (0..50)
+            .map(|i| {
+                json!({"id": i, "msg": "hello hello hello", "level": "info"})
+                    .as_object()
+                    .unwrap()
+                    .clone()
+            })
+            .collect();
+        let mut stream = records.clone().into_iter().map(Ok);
+        let policy = AutoLevelPolicy {
+            candidates: vec![9, 1, 3, 1],
+            sample_records: 20,
+            ..AutoLevelPolicy::default()
+        };
+
+        let sampled = sample_and_tune(&mut stream, &CompressOpts::default(), &policy).unwrap();
+        assert_eq!(sampled.records, records[..20]);
+        assert_eq!(stream.count(), 30);
+        let tuning = sampled.tuning.unwrap();
+        assert_eq!(tuning.sample_records, 20);
+        let levels: Vec<u8> = tuning.trials.iter().map(|t| t.level).collect();
+        assert_eq!(levels, vec![1, 3, 9]);
+        assert!(levels.contains(&tuning.chosen_level));
+
+        let empty = sample_and_tune(
+            &mut std::iter::empty(),
+            &CompressOpts::default(),
+            &AutoLevelPolicy::default(),
+        )
+        .unwrap();
+        assert!(empty.records.is_empty() && empty.tuning.is_none());
+    }
+}
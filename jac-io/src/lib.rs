@@ -10,33 +10,123 @@
 //! - Parallel processing support
 //! - Field projection APIs
 
+pub mod aggregate;
+pub(crate) mod binary_input;
+pub(crate) mod binary_output;
+pub mod block_range;
+pub mod coercion;
+pub mod column_cache;
+pub mod concurrent;
+pub mod dedupe;
+pub mod detect;
+pub(crate) mod duplicate_keys;
+pub mod embedded;
+pub mod filter;
+pub mod fingerprint;
+pub mod index_build;
+pub(crate) mod inject;
+pub mod key_normalization;
+pub mod level_tuning;
+pub mod memory;
+pub mod multi;
+pub mod multi_stream;
 pub mod parallel;
+#[cfg(feature = "polars")]
+pub mod polars_interop;
+#[cfg(feature = "query")]
+pub mod query;
+pub mod read_metrics;
 pub mod reader;
+pub(crate) mod record_limit;
+pub mod relaxed_json;
+pub mod retention;
+pub mod rotating;
 pub(crate) mod runtime;
+pub mod schema_mapping;
+pub mod service_metrics;
+pub mod text_output;
+pub(crate) mod utf8_repair;
+pub mod vectors;
 pub mod wrapper;
 pub mod writer;
 
 // Re-export commonly used types
-pub use jac_codec::{Codec, CompressOpts, DecompressOpts};
-pub use jac_format::{ContainerFormat, FileHeader, JacError, Limits, Result, TypeTag};
-use reader::BlockCursor;
+pub use aggregate::{
+    execute_aggregate, Aggregate, AggregateGroup, AggregateRequest, AggregateResult,
+};
+pub use block_range::BlockRange;
+pub use coercion::{CoercionMetrics, CoercionPolicy, CoercionRule, FieldCoercionMetrics};
+pub use column_cache::ColumnCacheStats;
+pub use concurrent::{ConcurrentJacReader, ReadAt};
+pub use dedupe::{DedupeConfig, DedupeMetrics};
+pub use detect::{
+    detect_input_format, suggest_wrappers, InputDetection, InputFormat, WrapperSuggestion,
+};
+pub use embedded::EmbeddedReader;
+pub use filter::{BlockStats, CompareOp, FilterSummary, Predicate};
+pub use fingerprint::{record_fingerprint, FingerprintCheck};
+pub use index_build::{execute_build_index, IndexBuildSummary};
+pub use jac_codec::{
+    BlockData, BlockDecoder, Codec, CompressOpts, DecompressOpts, DictionaryMode, DictionaryPolicy,
+    FieldEncodingHint, NumberCanonicalization, ValueRef,
+};
+pub use jac_format::{
+    BlockHeader, ContainerFormat, DeclaredLimitsPolicy, ErrorCategory, FileHeader, JacError,
+    Limits, Result, TypeTag,
+};
+pub use key_normalization::{KeyNormalization, KeyNormalizationMetrics};
+pub use level_tuning::{AutoLevelPolicy, LevelTrial, LevelTuning};
+pub use memory::{compress_records, decompress_to_records, MAX_IN_MEMORY_BYTES};
+pub use multi::{ChainedProjectionStream, ChainedRecordStream, MultiJacReader};
+pub use multi_stream::{MultiStreamFinish, MultiStreamReader, MultiStreamWriter, StreamWriter};
+#[cfg(feature = "polars")]
+pub use polars_interop::scan_jac;
+#[cfg(feature = "query")]
+pub use query::{execute_query, Query, QueryRequest, QuerySummary};
+#[cfg(feature = "latency-histogram")]
+pub use read_metrics::LatencyHistogram;
+pub use read_metrics::ReaderMetrics;
 pub use reader::{
-    BlockHandle, FieldIterator, JacReader, ProjectionStream, RecordStream as ReaderRecordStream,
+    BlockHandle, FieldCatalogEntry, FieldCatalogOptions, FieldIterator, FieldQuery,
+    FieldStatsSummary, IndexStatus, JacReader, LimitsDecision, MultiProjectionStream,
+    ProjectionStream, RawSegment, RecordStream as ReaderRecordStream, ValueCounts,
 };
+pub use relaxed_json::{NonFiniteNumbers, RelaxedJson};
+pub use retention::{
+    execute_retention, parse_rfc3339_millis, RetentionPolicy, RetentionRequest, RetentionSummary,
+    TimestampUnit,
+};
+pub use rotating::{RotatedFile, RotatingJacWriter, RotationPolicy};
+pub use schema_mapping::SchemaMapping;
+pub use service_metrics::ServiceMetrics;
+pub use text_output::{LineEnding, TextEncoding};
+pub use utf8_repair::INVALID_UTF8_BASE64_MARKER;
+pub use vectors::{check_vectors, generate_vectors, VectorCheck, VectorManifest};
 pub use wrapper::{
-    ArrayHeadersStream, FieldHint, FieldType, KeyedMapStream, PointerArrayStream, SchemaHints,
-    SectionsStream, WrapperError, WrapperPlugin, WrapperPluginMetadata, WrapperPluginRegistry,
+    ArrayHeadersOptions, ArrayHeadersStream, BuiltinWrapperError, FieldHint, FieldType,
+    KeyedMapStream, MapKeyFields, PluginRecords, PointerArrayStream, SchemaHints, SectionsStream,
+    WrapperError, WrapperLimits, WrapperPlugin, WrapperPluginMetadata, WrapperPluginRegistry,
 };
-pub use writer::{JacWriter, WriterFinish, WriterMetrics};
+pub use writer::{FieldTypeDrift, JacWriter, WriterFinish, WriterMetrics};
 
 use runtime::RuntimeMeasurement;
 
+use coercion::Coercer;
+use dedupe::Deduper;
+use inject::FieldInjector;
+use key_normalization::KeyNormalizer;
+use record_limit::{BoundedRead, ObjectLimit};
 use serde::{Deserialize, Serialize};
-use serde_json::{Deserializer, Map, Value};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Read, Seek, Write};
-use std::path::PathBuf;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use text_output::TextWriter;
+use utf8_repair::{Utf8Counters, Utf8RepairReader};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct HeaderMetadata {
@@ -74,7 +164,8 @@ pub trait WriteSend: Write + Send {}
 impl<T: Write + Send> WriteSend for T {}
 
 /// High-level compression options
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CompressOptions {
     /// Target number of records per block
     pub block_target_records: usize,
@@ -82,16 +173,85 @@ pub struct CompressOptions {
     pub default_codec: Codec,
     /// Canonicalize keys (lexicographic order)
     pub canonicalize_keys: bool,
+    /// Store per-record key order so unpack reproduces the original field order
+    pub preserve_key_order: bool,
+    /// Fields whose segments come first in each block, in this order, so
+    /// range readers projecting them fetch one contiguous prefix
+    pub hot_fields: Vec<String>,
     /// Canonicalize numbers (scientific notation, trim trailing zeros)
     pub canonicalize_numbers: bool,
+    /// Rules applied to decimals when `canonicalize_numbers` is enabled
+    pub number_canonicalization: NumberCanonicalization,
     /// Nested objects/arrays are opaque (v1 behavior)
     pub nested_opaque: bool,
     /// Maximum dictionary entries per field
     pub max_dict_entries: usize,
+    /// Cardinality ratio up to which string columns are dictionary encoded,
+    /// with per-field opt-outs for UUID-like columns
+    pub dictionary_policy: DictionaryPolicy,
+    /// Per-field encoding hints keyed by top-level field name, such as
+    /// skipping the dictionary for id columns; a plugin wrapper's
+    /// [`SchemaHints`] fill in fields not listed (see
+    /// [`CompressOptions::apply_schema_hints`])
+    pub field_hints: BTreeMap<String, FieldEncodingHint>,
+    /// Store per-field null counts and distinct-value sketches in every
+    /// block directory, read back by [`JacReader::field_stats`] and used to
+    /// prune filters
+    pub field_stats: bool,
+    /// Store a quantile sketch of each field's numeric values in every block
+    /// directory, read back as percentiles by [`JacReader::field_stats`] and
+    /// used to prune numeric comparisons
+    pub quantile_sketches: bool,
     /// Security limits
     pub limits: Limits,
     /// Parallel execution tuning parameters.
     pub parallel_config: parallel::ParallelConfig,
+    /// Handling of repeated keys within NDJSON/JSON array input objects.
+    pub duplicate_key_policy: DuplicateKeyPolicy,
+    /// Handling of invalid UTF-8 inside NDJSON/JSON array string values.
+    pub utf8_policy: Utf8Policy,
+    /// Maximum raw bytes of a single NDJSON line or JSON array element (`None`
+    /// disables for NDJSON; JSON array elements stay bounded by
+    /// `limits.max_block_uncompressed_total`).
+    pub max_record_bytes: Option<usize>,
+    /// Behavior when a record exceeds `max_record_bytes`.
+    pub oversized_record_policy: OversizedRecordPolicy,
+    /// Synthetic provenance fields added to every record before encoding.
+    pub inject_fields: InjectFields,
+    /// Drop records identical to an earlier one (after key normalization and
+    /// coercion, before field injection); `None` keeps every record.
+    pub dedupe: Option<DedupeConfig>,
+    /// Rewrites of top-level keys (case folding, NFC) applied before coercion.
+    pub key_normalization: KeyNormalization,
+    /// Per-field type coercion applied to input records before encoding.
+    pub type_coercion: CoercionPolicy,
+    /// Pick the zstd level by sampling the input; `None` keeps the level in
+    /// `default_codec`. Ignored for non-zstd codecs.
+    pub zstd_auto_level: Option<AutoLevelPolicy>,
+    /// Accept input with no records, writing a file with zero blocks. When
+    /// false, such input fails before the output is created.
+    pub allow_empty: bool,
+    /// Name fields in block directories by IDs into a file-level table
+    /// (`FLAG_INTERNED_FIELD_NAMES`), shrinking metadata for wide schemas.
+    pub intern_field_names: bool,
+    /// Give each block header its own CRC32C (`FLAG_BLOCK_HEADER_CRC`), so a
+    /// damaged directory is rejected before its sizes are acted on.
+    pub block_header_crc: bool,
+    /// Start every block on a 4 KiB boundary (`FLAG_ALIGNED_BLOCKS`), zero
+    /// padding the gaps, for `O_DIRECT` and page-aligned reads.
+    pub align_blocks: bool,
+    /// Accept comments, trailing commas and non-finite numbers in NDJSON and
+    /// JSON array input (requires the `relaxed-json` feature).
+    pub relaxed_json: Option<RelaxedJson>,
+    /// Largest size of an output file; when the next block would exceed it,
+    /// the file is finished and the output continues into a new one (see
+    /// [`OutputSink::Parts`]). Requires a path output. `None` writes one file.
+    pub max_output_bytes: Option<u64>,
+    /// Directory for temporary files: dedupe spill runs, and the stream
+    /// spools of a [`MultiStreamWriter::from_options`]. `None` uses the output
+    /// file's directory, or the system temporary directory when writing to a
+    /// `Write` sink.
+    pub temp_dir: Option<PathBuf>,
 }
 
 impl Default for CompressOptions {
@@ -100,107 +260,184 @@ impl Default for CompressOptions {
             block_target_records: 100_000,
             default_codec: Codec::Zstd(15),
             canonicalize_keys: false,
+            preserve_key_order: false,
+            hot_fields: Vec::new(),
             canonicalize_numbers: false,
+            number_canonicalization: NumberCanonicalization::default(),
             nested_opaque: true,
             max_dict_entries: 4_096,
+            dictionary_policy: DictionaryPolicy::default(),
+            field_hints: BTreeMap::new(),
+            field_stats: false,
+            quantile_sketches: false,
             limits: Limits::default(),
             parallel_config: parallel::ParallelConfig::default(),
+            duplicate_key_policy: DuplicateKeyPolicy::default(),
+            utf8_policy: Utf8Policy::default(),
+            max_record_bytes: None,
+            oversized_record_policy: OversizedRecordPolicy::default(),
+            inject_fields: InjectFields::default(),
+            dedupe: None,
+            key_normalization: KeyNormalization::default(),
+            type_coercion: CoercionPolicy::default(),
+            zstd_auto_level: None,
+            allow_empty: true,
+            intern_field_names: false,
+            block_header_crc: false,
+            align_blocks: false,
+            relaxed_json: None,
+            max_output_bytes: None,
+            temp_dir: None,
         }
     }
 }
 
-/// High-level decompression options
-#[derive(Debug, Clone)]
-pub struct DecompressOptions {
-    /// Security limits
-    pub limits: Limits,
-    /// Verify block CRC32C (recommended)
-    pub verify_checksums: bool,
-}
-
-impl Default for DecompressOptions {
-    fn default() -> Self {
+impl CompressOptions {
+    /// Options for a named [`CompressionProfile`]; other fields keep their defaults.
+    pub fn from_profile(profile: CompressionProfile) -> Self {
+        let (level, block_target_records, max_dict_entries, canonicalize_keys) = match profile {
+            CompressionProfile::Fast => (1, 20_000, 1_024, false),
+            CompressionProfile::Balanced => (6, 100_000, 4_096, false),
+            CompressionProfile::MaxCompression => (19, 100_000, 4_096, true),
+        };
         Self {
-            limits: Limits::default(),
-            verify_checksums: true,
+            block_target_records,
+            default_codec: Codec::Zstd(level),
+            canonicalize_keys,
+            max_dict_entries,
+            ..Self::default()
         }
     }
-}
-
-/// Wrapper-specific limits enforced during input preprocessing.
-#[derive(Debug, Clone)]
-pub struct WrapperLimits {
-    /// Maximum JSON pointer depth (default: 3, hard max: 10).
-    pub max_depth: usize,
-    /// Maximum bytes buffered before reaching target (default: 16 MiB, hard max: 128 MiB).
-    pub max_buffer_bytes: usize,
-    /// Maximum pointer string length (default: 256, hard max: 2048).
-    pub max_pointer_length: usize,
-}
 
-impl Default for WrapperLimits {
-    fn default() -> Self {
-        Self {
-            max_depth: 3,
-            max_buffer_bytes: 16 * 1024 * 1024,
-            max_pointer_length: 256,
+    /// Derive `field_hints` from a wrapper's schema hints; fields already in
+    /// `field_hints` keep their entry.
+    ///
+    /// Fields expected to hold integers prefer delta encoding, and fields
+    /// expected to hold more distinct values than `max_dict_entries` skip the
+    /// dictionary, which would overflow and be discarded.
+    pub fn apply_schema_hints(&mut self, hints: &SchemaHints) {
+        for field in &hints.fields {
+            let hint = FieldEncodingHint {
+                dictionary: field
+                    .estimated_cardinality
+                    .is_some_and(|cardinality| cardinality > self.max_dict_entries)
+                    .then_some(DictionaryMode::Never),
+                prefer_delta: field.expected_type == Some(FieldType::Int),
+            };
+            if hint != FieldEncodingHint::default() {
+                self.field_hints.entry(field.name.clone()).or_insert(hint);
+            }
         }
     }
-}
 
-impl WrapperLimits {
-    /// Hard maximum limits that cannot be exceeded
-    pub fn hard_maximums() -> Self {
-        Self {
-            max_depth: 10,
-            max_buffer_bytes: 128 * 1024 * 1024,
-            max_pointer_length: 2048,
+    /// Settings passed down to the column and block builders.
+    pub(crate) fn codec_opts(&self) -> CompressOpts {
+        CompressOpts {
+            block_target_records: self.block_target_records,
+            default_codec: self.default_codec,
+            canonicalize_keys: self.canonicalize_keys,
+            preserve_key_order: self.preserve_key_order,
+            hot_fields: self.hot_fields.clone(),
+            canonicalize_numbers: self.canonicalize_numbers,
+            number_canonicalization: self.number_canonicalization.clone(),
+            nested_opaque: self.nested_opaque,
+            max_dict_entries: self.max_dict_entries,
+            dictionary_policy: self.dictionary_policy.clone(),
+            field_hints: self.field_hints.clone(),
+            field_stats: self.field_stats,
+            quantile_sketches: self.quantile_sketches,
+            limits: self.limits.clone(),
         }
     }
+}
 
-    /// Validate limits against hard maximums
-    pub fn validate(&self) -> std::result::Result<(), WrapperError> {
-        let hard = Self::hard_maximums();
+/// Named presets covering codec level, block size, dictionary size and key
+/// canonicalization together.
+///
+/// Every profile stays within the default [`Limits`], so files remain readable
+/// with default decompression options. Number canonicalization is left off
+/// because it can rewrite decimal values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionProfile {
+    /// Zstd level 1, 20k-record blocks and 1,024 dictionary entries: lowest
+    /// latency and memory
+    Fast,
+    /// Zstd level 6 and 100k-record blocks (the CLI defaults)
+    #[default]
+    Balanced,
+    /// Zstd level 19, 100k-record blocks and canonical key order: smallest
+    /// output, slowest packing
+    MaxCompression,
+}
 
-        if self.max_depth > hard.max_depth {
-            return Err(WrapperError::ConfigurationExceedsHardLimits {
-                reason: format!("max_depth {} exceeds {}", self.max_depth, hard.max_depth),
-                max_depth: hard.max_depth,
-                max_buffer: hard.max_buffer_bytes,
-                max_ptr_len: hard.max_pointer_length,
-            });
+impl CompressionProfile {
+    /// Short name used by the CLI (`fast`, `balanced`, `max`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            CompressionProfile::Fast => "fast",
+            CompressionProfile::Balanced => "balanced",
+            CompressionProfile::MaxCompression => "max",
         }
+    }
+}
 
-        if self.max_buffer_bytes > hard.max_buffer_bytes {
-            return Err(WrapperError::ConfigurationExceedsHardLimits {
-                reason: format!(
-                    "max_buffer_bytes {} exceeds {}",
-                    self.max_buffer_bytes, hard.max_buffer_bytes
-                ),
-                max_depth: hard.max_depth,
-                max_buffer: hard.max_buffer_bytes,
-                max_ptr_len: hard.max_pointer_length,
-            });
-        }
+/// High-level decompression options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecompressOptions {
+    /// Security limits
+    pub limits: Limits,
+    /// Verify block CRC32C (recommended)
+    pub verify_checksums: bool,
+    /// Fail on any structural anomaly (conformance checking). When false,
+    /// anomalies are reported in the summary's `warnings` and unreadable
+    /// blocks are skipped (recovery).
+    pub strict: bool,
+    /// How limits declared in the file header combine with `limits`
+    pub declared_limits: DeclaredLimitsPolicy,
+    /// Mapping of stored records to the current schema, applied to every
+    /// record read (before `DecompressRequest::rename`)
+    pub schema_mapping: Option<SchemaMapping>,
+    /// Write NDJSON and JSON array output straight from the decoded columns,
+    /// copying nested objects and arrays from their stored JSON text instead
+    /// of parsing and re-serializing them. The output is the same either way;
+    /// records are still built as maps when a schema mapping or renames apply.
+    pub raw_json_passthrough: bool,
+    /// Write NDJSON and JSON array output as canonical JSON (keys sorted
+    /// bytewise at every depth, integral floats as integers; see
+    /// [`fingerprint`]) regardless of how the archive was packed, so archives
+    /// of the same logical data unpack to identical bytes. Records are built
+    /// as maps, so `raw_json_passthrough` does not apply.
+    pub canonical_json: bool,
+    /// Check that stored nested values are well-formed JSON while decoding.
+    /// Turning this off speeds up passthrough output further, but a corrupt
+    /// archive may then produce malformed JSON instead of an error.
+    pub validate_nested_json: bool,
+    /// Read the input as a file a writer may still be appending to (see
+    /// [`JacReader::snapshot`]): reading stops cleanly before the first block
+    /// that is not fully written instead of failing with `UnexpectedEof`.
+    pub snapshot: bool,
+}
 
-        if self.max_pointer_length > hard.max_pointer_length {
-            return Err(WrapperError::ConfigurationExceedsHardLimits {
-                reason: format!(
-                    "max_pointer_length {} exceeds {}",
-                    self.max_pointer_length, hard.max_pointer_length
-                ),
-                max_depth: hard.max_depth,
-                max_buffer: hard.max_buffer_bytes,
-                max_ptr_len: hard.max_pointer_length,
-            });
+impl Default for DecompressOptions {
+    fn default() -> Self {
+        Self {
+            limits: Limits::default(),
+            verify_checksums: true,
+            strict: true,
+            declared_limits: DeclaredLimitsPolicy::default(),
+            schema_mapping: None,
+            raw_json_passthrough: true,
+            canonical_json: false,
+            validate_nested_json: true,
+            snapshot: false,
         }
-
-        Ok(())
     }
 }
 
 /// Specification for a single section in multi-section wrapper mode.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SectionSpec {
     /// Name of the section (used for identification)
     pub name: String,
@@ -208,10 +445,19 @@ pub struct SectionSpec {
     pub pointer: String,
     /// Optional label to inject into records from this section
     pub label: Option<String>,
+    /// Prefix prepended to the top-level field names of this section's
+    /// records, keeping apart sections that share field names (e.g. `id`)
+    #[serde(default)]
+    pub field_prefix: Option<String>,
+    /// Maximum records taken from this section; later elements are skipped
+    /// and reported in [`WrapperMetrics::sections_truncated`]
+    #[serde(default)]
+    pub max_records: Option<usize>,
 }
 
 /// Behavior when a section is not found in the input.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum MissingSectionBehavior {
     /// Skip the section silently (default)
     Skip,
@@ -226,7 +472,8 @@ impl Default for MissingSectionBehavior {
 }
 
 /// Behavior when a key field collision occurs in map mode.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum KeyCollisionMode {
     /// Return an error (default)
     Error,
@@ -240,8 +487,202 @@ impl Default for KeyCollisionMode {
     }
 }
 
+/// Behavior when an input JSON object repeats a key.
+///
+/// Applies to objects at every nesting depth of NDJSON and JSON array input.
+/// Wrapper modes and iterator sources keep `serde_json`'s last-wins semantics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateKeyPolicy {
+    /// Keep the last occurrence (serde_json behavior, default)
+    #[default]
+    LastWins,
+    /// Keep the first occurrence and ignore later ones
+    FirstWins,
+    /// Fail the record with a JSON error naming the repeated key
+    Error,
+}
+
+/// Behavior when NDJSON or JSON array input contains invalid UTF-8.
+///
+/// Only string literals are repaired; invalid bytes elsewhere are JSON syntax
+/// errors regardless of policy. Repairs are counted in [`Utf8Metrics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Utf8Policy {
+    /// Reject input that is not valid UTF-8 (default)
+    #[default]
+    Strict,
+    /// Replace each invalid sequence with U+FFFD
+    Lossy,
+    /// Replace the whole offending string with [`INVALID_UTF8_BASE64_MARKER`]
+    /// followed by the base64 of its raw (still JSON-escaped) bytes
+    Base64,
+}
+
+/// Counts of invalid UTF-8 repaired during ingestion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Utf8Metrics {
+    /// String literals that contained invalid UTF-8.
+    pub invalid_strings: u64,
+    /// Invalid sequences replaced with U+FFFD (lossy policy).
+    pub replaced_sequences: u64,
+    /// String literals rewritten as base64 (base64 policy).
+    pub base64_wrapped: u64,
+}
+
+/// Behavior when an input record exceeds `CompressOptions::max_record_bytes`.
+///
+/// The size is measured on raw input bytes before parsing, so an oversized
+/// record is never fully buffered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OversizedRecordPolicy {
+    /// Fail with `JacError::LimitExceeded` (default)
+    #[default]
+    Error,
+    /// Drop the record and count it in `CompressSummary::oversized_records_skipped`
+    Skip,
+}
+
+/// Synthetic fields written into every record (`CompressOptions::inject_fields`).
+///
+/// Each field is disabled while its name is `None`. Injected values replace any
+/// input value under the same key, and they are applied to every input source,
+/// including wrappers and iterators, in input order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InjectFields {
+    /// Field receiving a monotonically increasing row id (e.g. `_row_id`)
+    pub row_id: Option<String>,
+    /// Row id assigned to the first record
+    pub row_id_start: u64,
+    /// Field receiving the ingest time in Unix epoch milliseconds
+    pub ingest_timestamp: Option<String>,
+    /// Field receiving `source_label`
+    pub source_field: Option<String>,
+    /// Constant label identifying where the records came from
+    pub source_label: String,
+    /// Field receiving the record's [`record_fingerprint`], computed over the
+    /// record before any other injected field is added (e.g. `_hash`)
+    pub fingerprint: Option<String>,
+}
+
+impl InjectFields {
+    /// Whether any field is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.row_id.is_some()
+            || self.ingest_timestamp.is_some()
+            || self.source_field.is_some()
+            || self.fingerprint.is_some()
+    }
+}
+
+/// Per-request settings applied while parsing NDJSON/JSON array input.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct IngestConfig {
+    pub(crate) duplicate_keys: DuplicateKeyPolicy,
+    pub(crate) utf8: Utf8Policy,
+    pub(crate) max_record_bytes: Option<usize>,
+    pub(crate) oversized: OversizedRecordPolicy,
+    /// `Limits::max_string_len_per_value`, enforced while JSON array elements are read
+    pub(crate) max_string_len: usize,
+    /// `Limits::max_block_uncompressed_total`, the JSON array element ceiling
+    /// when `max_record_bytes` is unset
+    pub(crate) max_block_bytes: usize,
+    pub(crate) relaxed_json: Option<RelaxedJson>,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        let limits = Limits::default();
+        Self {
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            utf8: Utf8Policy::default(),
+            max_record_bytes: None,
+            oversized: OversizedRecordPolicy::default(),
+            max_string_len: limits.max_string_len_per_value,
+            max_block_bytes: limits.max_block_uncompressed_total,
+            relaxed_json: None,
+        }
+    }
+}
+
+impl IngestConfig {
+    pub(crate) fn from_options(options: &CompressOptions) -> Self {
+        Self {
+            duplicate_keys: options.duplicate_key_policy,
+            utf8: options.utf8_policy,
+            max_record_bytes: options.max_record_bytes,
+            oversized: options.oversized_record_policy,
+            max_string_len: options.limits.max_string_len_per_value,
+            max_block_bytes: options.limits.max_block_uncompressed_total,
+            relaxed_json: options.relaxed_json,
+        }
+    }
+
+    /// Ceiling for one buffered object: `max_record_bytes`, or else the block budget.
+    pub(crate) fn object_limit(&self) -> ObjectLimit {
+        match self.max_record_bytes {
+            Some(limit) => ObjectLimit {
+                bytes: limit,
+                setting: "max_record_bytes",
+            },
+            None => ObjectLimit {
+                bytes: self.max_block_bytes,
+                setting: "max_block_uncompressed_total",
+            },
+        }
+    }
+}
+
+/// Counters updated by a record stream while it is consumed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IngestCounters {
+    utf8: Arc<Utf8Counters>,
+    oversized_skipped: Arc<AtomicU64>,
+    coercion: Arc<Mutex<CoercionMetrics>>,
+    key_normalization: Arc<Mutex<KeyNormalizationMetrics>>,
+    dedupe: Arc<Mutex<DedupeMetrics>>,
+}
+
+impl IngestCounters {
+    pub(crate) fn utf8_metrics(&self) -> Utf8Metrics {
+        self.utf8.snapshot()
+    }
+
+    pub(crate) fn oversized_skipped(&self) -> u64 {
+        self.oversized_skipped.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn coercion_metrics(&self) -> CoercionMetrics {
+        self.coercion
+            .lock()
+            .map(|metrics| metrics.clone())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().clone())
+    }
+
+    pub(crate) fn key_normalization_metrics(&self) -> KeyNormalizationMetrics {
+        self.key_normalization
+            .lock()
+            .map(|metrics| metrics.clone())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().clone())
+    }
+
+    pub(crate) fn dedupe_metrics(&self) -> DedupeMetrics {
+        *self
+            .dedupe
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
 /// Configuration for JSON wrapper preprocessing.
-#[derive(Debug, Clone)]
+///
+/// Non-exhaustive; [`WrapperConfig::Plugin`] covers wrappers not built in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
 pub enum WrapperConfig {
     /// No wrapper preprocessing
     None,
@@ -250,6 +691,7 @@ pub enum WrapperConfig {
         /// RFC 6901 JSON Pointer path
         path: String,
         /// Limits for this wrapper
+        #[serde(default)]
         limits: WrapperLimits,
     },
     /// Multi-section array concatenation
@@ -257,12 +699,14 @@ pub enum WrapperConfig {
         /// Section specifications (order determines output order)
         entries: Vec<SectionSpec>,
         /// Limits for this wrapper
+        #[serde(default)]
         limits: WrapperLimits,
         /// Field name for injected section label (default: "_section")
         label_field: Option<String>,
         /// Whether to inject section labels into records (default: true)
         inject_label: bool,
         /// Behavior when a section is not found
+        #[serde(default)]
         missing_behavior: MissingSectionBehavior,
     },
     /// Keyed map object flattening (object-of-objects to records)
@@ -271,23 +715,43 @@ pub enum WrapperConfig {
         pointer: String,
         /// Field name for the injected key (default: "_key")
         key_field: String,
+        /// Field name for the inner key of a two-level map (object of
+        /// objects of objects); when set, each inner entry becomes a record
+        /// carrying both keys
+        #[serde(default)]
+        inner_key_field: Option<String>,
+        /// Treat key fields as dotted paths (`meta.id`) into nested objects
+        #[serde(default)]
+        nested_key_fields: bool,
         /// Limits for this wrapper
+        #[serde(default)]
         limits: WrapperLimits,
         /// Behavior when key field already exists in a record
+        #[serde(default)]
         collision_mode: KeyCollisionMode,
     },
     /// Array-with-headers wrapper (CSV-like format)
     ArrayWithHeaders {
         /// Limits for this wrapper
+        #[serde(default)]
         limits: WrapperLimits,
+        /// Read the second row as column types (`int`, `decimal`, `bool`,
+        /// `string`) and convert cells to them
+        #[serde(default)]
+        type_row: bool,
+        /// String cells read as null (e.g. `""`, `"NULL"`, `"N/A"`)
+        #[serde(default)]
+        null_markers: Vec<String>,
     },
     /// Custom plugin-based wrapper
     Plugin {
         /// Name of the registered plugin to use
         plugin_name: String,
         /// Plugin-specific configuration (JSON value)
+        #[serde(default)]
         config: Value,
         /// Limits for this wrapper
+        #[serde(default)]
         limits: WrapperLimits,
     },
 }
@@ -313,6 +777,8 @@ pub struct WrapperMetrics {
     pub pointer_path: Option<String>,
     /// Section-specific record counts (if applicable)
     pub section_counts: Option<Vec<(String, usize)>>,
+    /// Sections cut short by their record limit (section name, elements skipped)
+    pub sections_truncated: Vec<(String, usize)>,
     /// Map entry count (if applicable)
     pub map_entry_count: Option<usize>,
     /// Header field count for array-with-headers mode (if applicable)
@@ -333,12 +799,34 @@ pub enum InputSource {
     JsonArrayReader(Box<dyn Read + Send>),
     /// Iterator yielding JSON objects.
     Iterator(Box<dyn Iterator<Item = Map<String, Value>> + Send>),
+    /// Concatenated MessagePack maps from a reader (requires the `msgpack` feature).
+    MessagePackReader(Box<dyn Read + Send>),
+    /// Concatenated BSON documents from a reader (requires the `bson` feature).
+    BsonReader(Box<dyn Read + Send>),
+}
+
+/// Names continuation file `part` (2, 3, ...) of a size-capped output whose
+/// first file is at the given path.
+pub type PartNamer = Box<dyn Fn(&Path, u32) -> PathBuf + Send>;
+
+/// Default name of continuation file `part`: `out.jac` continues into
+/// `out.part2.jac`, `out.part3.jac` and so on.
+pub fn default_part_path(first: &Path, part: u32) -> PathBuf {
+    let stem = first.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match first.extension() {
+        Some(ext) => format!("{}.part{}.{}", stem, part, ext.to_string_lossy()),
+        None => format!("{}.part{}", stem, part),
+    };
+    first.with_file_name(name)
 }
 
 /// Outputs supported by high-level APIs.
 pub enum OutputSink {
-    /// Write to a file path (created or truncated).
+    /// Write to a file path (created or truncated). Continuation files under
+    /// `CompressOptions::max_output_bytes` are named by [`default_part_path`].
     Path(PathBuf),
+    /// Write to a file path, naming continuation files with the callback.
+    Parts(PathBuf, PartNamer),
     /// Write to an arbitrary `Write` implementation.
     Writer(Box<dyn WriteSend>),
 }
@@ -349,10 +837,16 @@ pub enum JacInput {
     Path(PathBuf),
     /// Input from an arbitrary `Read + Seek` source.
     Reader(Box<dyn ReadSeekSend>),
+    /// Archive already in memory (e.g. a network response body); blocks are
+    /// decoded from slices of it without copying.
+    Bytes(bytes::Bytes),
+    /// Archive already in memory as an owned buffer (decoded like `Bytes`).
+    Memory(Vec<u8>),
 }
 
 /// Output formats for full decompression.
 #[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
 pub enum DecompressFormat {
     /// Follow the container hint stored in the file header (default to NDJSON).
     Auto,
@@ -360,10 +854,15 @@ pub enum DecompressFormat {
     Ndjson,
     /// Emit a JSON array (`[ {...}, {...} ]`).
     JsonArray,
+    /// Emit concatenated MessagePack maps (requires the `msgpack` feature).
+    MessagePack,
+    /// Emit a sequence of CBOR maps (requires the `cbor` feature).
+    Cbor,
 }
 
 /// Output formats for projection operations.
 #[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
 pub enum ProjectFormat {
     /// Emit NDJSON (one object per line).
     Ndjson,
@@ -377,6 +876,10 @@ pub enum ProjectFormat {
 }
 
 /// Compression request describing input, output and options.
+///
+/// Build one with [`CompressRequest::builder`]; the struct is non-exhaustive
+/// so new options can be added without breaking callers.
+#[non_exhaustive]
 pub struct CompressRequest {
     /// Source of JSON records.
     pub input: InputSource,
@@ -390,6 +893,9 @@ pub struct CompressRequest {
     pub emit_index: bool,
     /// Wrapper configuration for input preprocessing.
     pub wrapper_config: WrapperConfig,
+    /// Stop reading input once this flag is set (e.g. by a Ctrl-C handler)
+    /// and finish the output with the records read so far.
+    pub interrupt: Option<Arc<AtomicBool>>,
 }
 
 impl Default for CompressRequest {
@@ -401,11 +907,110 @@ impl Default for CompressRequest {
             container_hint: None,
             emit_index: true,
             wrapper_config: WrapperConfig::None,
+            interrupt: None,
         }
     }
 }
 
+impl CompressRequest {
+    /// Start building a request. Unset fields keep their [`Default`] values
+    /// except `input` and `output`, which must be provided.
+    pub fn builder() -> CompressRequestBuilder {
+        CompressRequestBuilder::default()
+    }
+}
+
+/// Builder for [`CompressRequest`].
+#[derive(Default)]
+pub struct CompressRequestBuilder {
+    input: Option<InputSource>,
+    output: Option<OutputSink>,
+    request: CompressRequest,
+}
+
+impl CompressRequestBuilder {
+    /// Source of JSON records.
+    pub fn input(mut self, input: InputSource) -> Self {
+        self.input = Some(input);
+        self
+    }
+
+    /// Destination for the resulting JAC payload.
+    pub fn output(mut self, output: OutputSink) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// Replace all compression options.
+    pub fn options(mut self, options: CompressOptions) -> Self {
+        self.request.options = options;
+        self
+    }
+
+    /// Replace all compression options with a [`CompressionProfile`]; set
+    /// individual options afterwards to adjust it.
+    pub fn profile(mut self, profile: CompressionProfile) -> Self {
+        self.request.options = CompressOptions::from_profile(profile);
+        self
+    }
+
+    /// Compression codec (shorthand for `options.default_codec`).
+    pub fn codec(mut self, codec: Codec) -> Self {
+        self.request.options.default_codec = codec;
+        self
+    }
+
+    /// Target records per block (shorthand for `options.block_target_records`).
+    pub fn block_target_records(mut self, records: usize) -> Self {
+        self.request.options.block_target_records = records;
+        self
+    }
+
+    /// Container format hint recorded in the file header (`None` clears it).
+    pub fn container_hint(mut self, hint: impl Into<Option<ContainerFormat>>) -> Self {
+        self.request.container_hint = hint.into();
+        self
+    }
+
+    /// Emit the index footer when finishing (default: `true`).
+    pub fn emit_index(mut self, emit_index: bool) -> Self {
+        self.request.emit_index = emit_index;
+        self
+    }
+
+    /// Wrapper configuration for input preprocessing.
+    pub fn wrapper_config(mut self, wrapper_config: WrapperConfig) -> Self {
+        self.request.wrapper_config = wrapper_config;
+        self
+    }
+
+    /// Flag that stops compression early; see [`CompressRequest::interrupt`].
+    pub fn interrupt(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.request.interrupt = Some(flag);
+        self
+    }
+
+    /// Finish the request; fails when `input` or `output` was not set.
+    pub fn build(self) -> Result<CompressRequest> {
+        let input = self
+            .input
+            .ok_or_else(|| JacError::Internal("compress request requires an input".to_string()))?;
+        let output = self
+            .output
+            .ok_or_else(|| JacError::Internal("compress request requires an output".to_string()))?;
+        Ok(CompressRequest {
+            input,
+            output,
+            ..self.request
+        })
+    }
+}
+
 /// Decompression request describing input, output and options.
+///
+/// Build one with [`DecompressRequest::builder`]; the struct is
+/// non-exhaustive so new options can be added without breaking callers.
+#[non_exhaustive]
 pub struct DecompressRequest {
     /// Source JAC file or reader.
     pub input: JacInput,
@@ -415,9 +1020,195 @@ pub struct DecompressRequest {
     pub format: DecompressFormat,
     /// Decompression options.
     pub options: DecompressOptions,
+    /// Records to skip before the first one written; whole blocks before the
+    /// offset are skipped without being decoded.
+    pub offset: Option<u64>,
+    /// Maximum number of records to write.
+    pub limit: Option<u64>,
+    /// Blocks to decode; `offset` and `limit` then count from the first
+    /// record of the range. Blocks before the range are skipped without
+    /// being decoded.
+    pub blocks: Option<BlockRange>,
+    /// Field renames (`old` -> `new`) applied to top-level keys as records are
+    /// reconstructed, keeping key order. When a record already holds `new`
+    /// (and `new` is not itself renamed), that value is kept and `old` dropped.
+    pub rename: HashMap<String, String>,
+    /// Terminator written after each NDJSON record.
+    pub line_ending: LineEnding,
+    /// Character encoding of NDJSON and JSON array output; binary formats
+    /// ignore it.
+    pub encoding: TextEncoding,
+    /// Fail with `InvalidData` when the JSON text written is not well-formed
+    /// UTF-8, whatever the `encoding` (ASCII output is always checked).
+    pub validate_utf8: bool,
+    /// Only write records where this RFC 6901 JSON pointer (e.g.
+    /// `/error/code`) resolves, against records as stored (before the schema
+    /// mapping and renames). `offset` and `blocks` still count stored
+    /// records; `limit` counts records written.
+    pub require_pointer: Option<String>,
+    /// Only write records satisfying this filter, evaluated like
+    /// `require_pointer` against records as stored. Blocks whose statistics
+    /// rule out a match are not decoded.
+    pub filter: Option<Predicate>,
+}
+
+impl DecompressRequest {
+    /// Start building a request. `input` and `output` must be provided; other
+    /// fields default to automatic format, default options, `\n`-terminated
+    /// UTF-8 text and no offset, limit, block range or renames.
+    pub fn builder() -> DecompressRequestBuilder {
+        DecompressRequestBuilder::default()
+    }
+}
+
+/// Builder for [`DecompressRequest`].
+pub struct DecompressRequestBuilder {
+    input: Option<JacInput>,
+    output: Option<OutputSink>,
+    format: DecompressFormat,
+    options: DecompressOptions,
+    offset: Option<u64>,
+    limit: Option<u64>,
+    blocks: Option<BlockRange>,
+    rename: HashMap<String, String>,
+    line_ending: LineEnding,
+    encoding: TextEncoding,
+    validate_utf8: bool,
+    require_pointer: Option<String>,
+    filter: Option<Predicate>,
+}
+
+impl Default for DecompressRequestBuilder {
+    fn default() -> Self {
+        Self {
+            input: None,
+            output: None,
+            format: DecompressFormat::Auto,
+            options: DecompressOptions::default(),
+            offset: None,
+            limit: None,
+            blocks: None,
+            rename: HashMap::new(),
+            line_ending: LineEnding::default(),
+            encoding: TextEncoding::default(),
+            validate_utf8: false,
+            require_pointer: None,
+            filter: None,
+        }
+    }
+}
+
+impl DecompressRequestBuilder {
+    /// Source JAC file or reader.
+    pub fn input(mut self, input: JacInput) -> Self {
+        self.input = Some(input);
+        self
+    }
+
+    /// Destination for decompressed JSON.
+    pub fn output(mut self, output: OutputSink) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// Output format (default: [`DecompressFormat::Auto`]).
+    pub fn format(mut self, format: DecompressFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Replace all decompression options.
+    pub fn options(mut self, options: DecompressOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Records to skip before the first one written.
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Maximum number of records to write.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Decode only the blocks in `blocks`.
+    pub fn blocks(mut self, blocks: BlockRange) -> Self {
+        self.blocks = Some(blocks);
+        self
+    }
+
+    /// Replace the field renames (`old` -> `new`).
+    pub fn rename(mut self, rename: HashMap<String, String>) -> Self {
+        self.rename = rename;
+        self
+    }
+
+    /// Terminator after each NDJSON record (default: [`LineEnding::Lf`]).
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Encoding of JSON text output (default: [`TextEncoding::Utf8`]).
+    pub fn encoding(mut self, encoding: TextEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Check that JSON text output is well-formed UTF-8 (default: only for
+    /// [`TextEncoding::Ascii`]).
+    pub fn validate_utf8(mut self, validate: bool) -> Self {
+        self.validate_utf8 = validate;
+        self
+    }
+
+    /// Only write records where the JSON pointer `pointer` resolves.
+    pub fn require_pointer(mut self, pointer: impl Into<String>) -> Self {
+        self.require_pointer = Some(pointer.into());
+        self
+    }
+
+    /// Only write records satisfying `predicate`.
+    pub fn filter(mut self, predicate: Predicate) -> Self {
+        self.filter = Some(predicate);
+        self
+    }
+
+    /// Finish the request; fails when `input` or `output` was not set.
+    pub fn build(self) -> Result<DecompressRequest> {
+        let input = self.input.ok_or_else(|| {
+            JacError::Internal("decompress request requires an input".to_string())
+        })?;
+        let output = self.output.ok_or_else(|| {
+            JacError::Internal("decompress request requires an output".to_string())
+        })?;
+        Ok(DecompressRequest {
+            input,
+            output,
+            format: self.format,
+            options: self.options,
+            offset: self.offset,
+            limit: self.limit,
+            blocks: self.blocks,
+            rename: self.rename,
+            line_ending: self.line_ending,
+            encoding: self.encoding,
+            validate_utf8: self.validate_utf8,
+            require_pointer: self.require_pointer,
+            filter: self.filter,
+        })
+    }
 }
 
 /// Projection request across selected fields.
+///
+/// Build one with [`ProjectRequest::builder`]; the struct is non-exhaustive
+/// so new options can be added without breaking callers.
+#[non_exhaustive]
 pub struct ProjectRequest {
     /// Source JAC file or reader.
     pub input: JacInput,
@@ -429,6 +1220,140 @@ pub struct ProjectRequest {
     pub format: ProjectFormat,
     /// Decompression options.
     pub options: DecompressOptions,
+    /// Keep null and absent values distinguishable in the output.
+    ///
+    /// JSON output always emits `null` for null values and omits absent keys.
+    /// CSV renders both as an empty cell, so this adds a `<field>_presence`
+    /// column after each field holding `present`, `null`, or `absent`.
+    pub include_presence: bool,
+    /// Rows to skip before the first one written; whole blocks before the
+    /// offset are skipped without being decoded.
+    pub offset: Option<u64>,
+    /// Maximum number of rows to write.
+    pub limit: Option<u64>,
+    /// Field renames (`old` -> `new`). `fields` name output columns, so a
+    /// requested `new` is read from `new` or, where absent, from any `old`
+    /// renamed to it, matching the records `execute_decompress` would produce.
+    pub rename: HashMap<String, String>,
+}
+
+impl ProjectRequest {
+    /// Start building a request. `input` and `output` must be provided; other
+    /// fields default to no fields, NDJSON rows, default options and no
+    /// presence columns, offset, limit or renames.
+    pub fn builder() -> ProjectRequestBuilder {
+        ProjectRequestBuilder::default()
+    }
+}
+
+/// Builder for [`ProjectRequest`].
+pub struct ProjectRequestBuilder {
+    input: Option<JacInput>,
+    output: Option<OutputSink>,
+    fields: Vec<String>,
+    format: ProjectFormat,
+    options: DecompressOptions,
+    include_presence: bool,
+    offset: Option<u64>,
+    limit: Option<u64>,
+    rename: HashMap<String, String>,
+}
+
+impl Default for ProjectRequestBuilder {
+    fn default() -> Self {
+        Self {
+            input: None,
+            output: None,
+            fields: Vec::new(),
+            format: ProjectFormat::Ndjson,
+            options: DecompressOptions::default(),
+            include_presence: false,
+            offset: None,
+            limit: None,
+            rename: HashMap::new(),
+        }
+    }
+}
+
+impl ProjectRequestBuilder {
+    /// Source JAC file or reader.
+    pub fn input(mut self, input: JacInput) -> Self {
+        self.input = Some(input);
+        self
+    }
+
+    /// Destination for projected data.
+    pub fn output(mut self, output: OutputSink) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// Replace the fields to project.
+    pub fn fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fields = fields.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Output format (default: [`ProjectFormat::Ndjson`]).
+    pub fn format(mut self, format: ProjectFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Replace all decompression options.
+    pub fn options(mut self, options: DecompressOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Add a `<field>_presence` column after each CSV field.
+    pub fn include_presence(mut self, include: bool) -> Self {
+        self.include_presence = include;
+        self
+    }
+
+    /// Rows to skip before the first one written.
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Maximum number of rows to write.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Replace the field renames (`old` -> `new`).
+    pub fn rename(mut self, rename: HashMap<String, String>) -> Self {
+        self.rename = rename;
+        self
+    }
+
+    /// Finish the request; fails when `input` or `output` was not set.
+    pub fn build(self) -> Result<ProjectRequest> {
+        let input = self
+            .input
+            .ok_or_else(|| JacError::Internal("project request requires an input".to_string()))?;
+        let output = self
+            .output
+            .ok_or_else(|| JacError::Internal("project request requires an output".to_string()))?;
+        Ok(ProjectRequest {
+            input,
+            output,
+            fields: self.fields,
+            format: self.format,
+            options: self.options,
+            include_presence: self.include_presence,
+            offset: self.offset,
+            limit: self.limit,
+            rename: self.rename,
+        })
+    }
 }
 
 /// Summary returned after a compression request.
@@ -442,6 +1367,24 @@ pub struct CompressSummary {
     pub runtime_stats: CompressionRuntimeStats,
     /// Wrapper preprocessing metrics (if wrapper was used).
     pub wrapper_metrics: Option<WrapperMetrics>,
+    /// Invalid UTF-8 repaired under the configured [`Utf8Policy`].
+    pub utf8_metrics: Utf8Metrics,
+    /// Records dropped for exceeding `max_record_bytes` under [`OversizedRecordPolicy::Skip`].
+    pub oversized_records_skipped: u64,
+    /// Values rewritten and type drift observed under `CompressOptions::type_coercion`.
+    pub coercion_metrics: CoercionMetrics,
+    /// Keys rewritten and collisions resolved under `CompressOptions::key_normalization`.
+    pub key_normalization_metrics: KeyNormalizationMetrics,
+    /// Records dropped by `CompressOptions::dedupe`
+    pub dedupe_metrics: DedupeMetrics,
+    /// Level picked under `CompressOptions::zstd_auto_level`, with its trials.
+    pub level_tuning: Option<LevelTuning>,
+    /// Files written for a path output, in order; more than one when
+    /// `CompressOptions::max_output_bytes` split the output.
+    pub output_files: Vec<PathBuf>,
+    /// Input was cut short by `CompressRequest::interrupt`; the output holds
+    /// the records read before it and is finished as usual.
+    pub interrupted: bool,
 }
 
 /// Runtime statistics captured during compression.
@@ -463,19 +1406,29 @@ impl Default for CompressionRuntimeStats {
 }
 
 /// Summary returned after decompression.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct DecompressSummary {
     /// Number of records emitted.
     pub records_written: u64,
     /// Number of blocks processed.
     pub blocks_processed: usize,
+    /// Anomalies tolerated in permissive mode.
+    pub warnings: Vec<String>,
+    /// Bytes read and decode work done by the reader.
+    pub metrics: ReaderMetrics,
+    /// Work done by the request's filter, when it had one.
+    pub filter: Option<FilterSummary>,
 }
 
 /// Summary returned after projection.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct ProjectSummary {
     /// Number of rows written to the sink.
     pub rows_written: u64,
+    /// Anomalies tolerated in permissive mode.
+    pub warnings: Vec<String>,
+    /// Bytes read and decode work done by the reader.
+    pub metrics: ReaderMetrics,
 }
 
 /// Build a `FileHeader` configured according to the provided compression options.
@@ -493,6 +1446,15 @@ pub(crate) fn build_file_header(
     if options.nested_opaque {
         flags |= jac_format::constants::FLAG_NESTED_OPAQUE;
     }
+    if options.intern_field_names {
+        flags |= jac_format::constants::FLAG_INTERNED_FIELD_NAMES;
+    }
+    if options.block_header_crc {
+        flags |= jac_format::constants::FLAG_BLOCK_HEADER_CRC;
+    }
+    if options.align_blocks {
+        flags |= jac_format::constants::FLAG_ALIGNED_BLOCKS;
+    }
 
     let mut header = FileHeader {
         flags,
@@ -534,36 +1496,53 @@ pub(crate) fn execute_compress_sequential(request: CompressRequest) -> Result<Co
     let CompressRequest {
         input,
         output,
-        options,
+        mut options,
         container_hint,
         emit_index,
         wrapper_config,
+        interrupt,
     } = request;
 
-    let mut stream = input.into_record_stream(&wrapper_config)?;
+    let mut stream = input
+        .into_record_stream(&wrapper_config, IngestConfig::from_options(&options))?
+        .with_key_normalization(&options.key_normalization)
+        .with_coercion(&options.type_coercion)
+        .with_dedupe(options.dedupe.as_ref(), &output.temp_dir(&options))
+        .with_injected_fields(&options.inject_fields);
     let detected_hint = stream.container_format();
     let final_hint = container_hint.unwrap_or(detected_hint);
     let wrapper_metrics = stream.take_wrapper_metrics();
-    let writer_target = output.into_writer()?;
-    let buf_writer = BufWriter::new(writer_target);
+    if let Some(hints) = stream.take_schema_hints() {
+        options.apply_schema_hints(&hints);
+    }
+    let mut sampled = tune_zstd_level(&mut stream, &mut options)?;
+    require_records(&mut stream, &mut sampled.records, &options)?;
     let header = build_file_header(&options, Some(final_hint))?;
+    let (writer_target, mut parts) = output.open_parts(&options, &header, emit_index)?;
+    let buf_writer = BufWriter::new(writer_target);
 
-    let codec_opts = CompressOpts {
-        block_target_records: options.block_target_records,
-        default_codec: options.default_codec,
-        canonicalize_keys: options.canonicalize_keys,
-        canonicalize_numbers: options.canonicalize_numbers,
-        nested_opaque: options.nested_opaque,
-        max_dict_entries: options.max_dict_entries,
-        limits: options.limits,
-    };
+    let codec_opts = options.codec_opts();
 
     let mut jac_writer = JacWriter::new(buf_writer, header, codec_opts)?;
+    jac_writer.set_size_cap(options.max_output_bytes);
 
-    for record in stream {
+    for record in sampled.records {
+        jac_writer.write_record(&record)?;
+        parts.continue_if_full(&mut jac_writer)?;
+    }
+    let mut interrupted = false;
+    for record in stream.by_ref() {
+        if is_interrupted(interrupt.as_deref()) {
+            interrupted = true;
+            break;
+        }
         let record = record?;
         jac_writer.write_record(&record)?;
+        parts.continue_if_full(&mut jac_writer)?;
     }
+    jac_writer.flush()?;
+    parts.continue_if_full(&mut jac_writer)?;
+    let counters = stream.counters();
 
     let finish = if emit_index {
         jac_writer.finish_with_index()?
@@ -582,9 +1561,67 @@ pub(crate) fn execute_compress_sequential(request: CompressRequest) -> Result<Co
         parallel_decision: None,
         runtime_stats,
         wrapper_metrics,
+        utf8_metrics: counters.utf8_metrics(),
+        oversized_records_skipped: counters.oversized_skipped(),
+        coercion_metrics: counters.coercion_metrics(),
+        key_normalization_metrics: counters.key_normalization_metrics(),
+        dedupe_metrics: counters.dedupe_metrics(),
+        level_tuning: sampled.tuning,
+        output_files: parts.files,
+        interrupted,
     })
 }
 
+/// Whether a `CompressRequest::interrupt` flag has been raised.
+pub(crate) fn is_interrupted(flag: Option<&AtomicBool>) -> bool {
+    flag.is_some_and(|flag| flag.load(Ordering::Relaxed))
+}
+
+/// Enforce `CompressOptions::allow_empty` before any output is created.
+///
+/// Without a sample in hand, one record is pulled from `stream` into `sample`
+/// to prove the input is non-empty; callers encode `sample` first as usual.
+pub(crate) fn require_records(
+    stream: &mut RecordStream,
+    sample: &mut Vec<Map<String, Value>>,
+    options: &CompressOptions,
+) -> Result<()> {
+    if options.allow_empty || !sample.is_empty() {
+        return Ok(());
+    }
+    match stream.next() {
+        Some(record) => {
+            sample.push(record?);
+            Ok(())
+        }
+        None => Err(JacError::Internal(
+            "input contains no records (allow_empty is disabled)".to_string(),
+        )),
+    }
+}
+
+/// Run `CompressOptions::zstd_auto_level` sampling, if enabled, and switch
+/// `options.default_codec` to the chosen level.
+///
+/// The returned sample holds the records consumed from `stream`, which callers
+/// must encode before the rest of the stream.
+pub(crate) fn tune_zstd_level(
+    stream: &mut RecordStream,
+    options: &mut CompressOptions,
+) -> Result<level_tuning::Sampled> {
+    let policy = match &options.zstd_auto_level {
+        Some(policy) if options.default_codec.zstd_level_i32().is_some() => policy,
+        _ => return Ok(level_tuning::Sampled::default()),
+    };
+    let codec_opts = options.codec_opts();
+    let sampled = level_tuning::sample_and_tune(stream, &codec_opts, policy)?;
+    if let Some(tuning) = &sampled.tuning {
+        options.default_codec =
+            level_tuning::with_zstd_level(options.default_codec, tuning.chosen_level);
+    }
+    Ok(sampled)
+}
+
 /// Execute a decompression request.
 pub fn execute_decompress(request: DecompressRequest) -> Result<DecompressSummary> {
     let DecompressRequest {
@@ -592,14 +1629,33 @@ pub fn execute_decompress(request: DecompressRequest) -> Result<DecompressSummar
         output,
         format,
         options,
+        offset,
+        limit,
+        blocks,
+        rename,
+        line_ending,
+        encoding,
+        validate_utf8,
+        require_pointer,
+        filter,
     } = request;
 
-    let reader_source = input.into_reader()?;
     let codec_opts = DecompressOpts {
         limits: options.limits.clone(),
         verify_checksums: options.verify_checksums,
+        strict: options.strict,
+        declared_limits: options.declared_limits,
+        validate_nested_json: options.validate_nested_json,
+        ..DecompressOpts::default()
     };
-    let mut reader = JacReader::new(reader_source, codec_opts)?;
+    let mut reader = input.open(codec_opts, options.snapshot)?;
+    reader.set_schema_mapping(options.schema_mapping);
+    let canonical = options.canonical_json;
+    let passthrough = options.raw_json_passthrough
+        && !canonical
+        && reader.schema_mapping().is_none()
+        && rename.is_empty();
+    let (offset, window) = select_records(&mut reader, blocks, offset)?;
     let header_hint = reader.file_header().container_format_hint()?;
     let resolved_format = match format {
         DecompressFormat::Auto => match header_hint {
@@ -608,46 +1664,176 @@ pub fn execute_decompress(request: DecompressRequest) -> Result<DecompressSummar
         },
         other => other,
     };
-
-    let mut buf_writer = BufWriter::new(output.into_writer()?);
-    let mut record_stream = reader.record_stream()?;
-    let mut summary = DecompressSummary {
-        records_written: 0,
-        blocks_processed: 0,
+    let (encoder, encoding) = match resolved_format {
+        DecompressFormat::MessagePack => (
+            Some(binary_output::message_pack_encoder()?),
+            TextEncoding::Utf8,
+        ),
+        DecompressFormat::Cbor => (Some(binary_output::cbor_encoder()?), TextEncoding::Utf8),
+        _ => (None, encoding),
     };
 
+    let mut buf_writer = BufWriter::new(TextWriter::new(
+        output.into_writer()?,
+        encoding,
+        validate_utf8,
+    ));
+    let mut record_stream = reader.record_stream_from(offset)?;
+    if let Some(window) = window {
+        record_stream.stop_after(window);
+    }
+    if let Some(pointer) = &require_pointer {
+        record_stream.require_pointer(pointer)?;
+    }
+    let filtered = filter.is_some();
+    if let Some(filter) = filter {
+        record_stream.set_filter(filter);
+    }
+    let limit = request_limit(limit);
+    let mut summary = DecompressSummary::default();
+    let mut scratch = Vec::new();
+
     match resolved_format {
+        DecompressFormat::Ndjson | DecompressFormat::JsonArray if passthrough => {
+            let array = matches!(resolved_format, DecompressFormat::JsonArray);
+            summary.records_written =
+                record_stream.write_json(limit, &mut buf_writer, array, line_ending)?;
+        }
         DecompressFormat::Ndjson => {
-            for record in record_stream.by_ref() {
-                let record = record?;
-                serde_json::to_writer(&mut buf_writer, &Value::Object(record))?;
-                buf_writer.write_all(b"\n")?;
+            for record in record_stream.by_ref().take(limit) {
+                let record = rename_fields(record?, &rename);
+                write_json_record(&mut buf_writer, &record, canonical, &mut scratch)?;
+                buf_writer.write_all(line_ending.as_bytes())?;
                 summary.records_written += 1;
             }
         }
         DecompressFormat::JsonArray => {
             buf_writer.write_all(b"[")?;
             let mut first = true;
-            for record in record_stream.by_ref() {
-                let record = record?;
+            for record in record_stream.by_ref().take(limit) {
+                let record = rename_fields(record?, &rename);
                 if first {
                     first = false;
                 } else {
                     buf_writer.write_all(b",")?;
                 }
-                serde_json::to_writer(&mut buf_writer, &Value::Object(record))?;
+                write_json_record(&mut buf_writer, &record, canonical, &mut scratch)?;
                 summary.records_written += 1;
             }
             buf_writer.write_all(b"]")?;
         }
-        DecompressFormat::Auto => unreachable!("auto must resolve to a concrete format"),
-    }
+        DecompressFormat::MessagePack | DecompressFormat::Cbor => {
+            let encode = encoder.expect("binary formats resolve an encoder");
+            for record in record_stream.by_ref().take(limit) {
+                let record = rename_fields(record?, &rename);
+                encode(&mut buf_writer, &Value::Object(record))?;
+                summary.records_written += 1;
+            }
+        }
+        DecompressFormat::Auto => unreachable!("auto must resolve to a concrete format"),
+    }
 
     summary.blocks_processed = record_stream.blocks_processed();
+    summary.filter = filtered.then(|| record_stream.filter_summary());
+    drop(record_stream);
+    summary.warnings = reader.warnings();
+    summary.metrics = reader.metrics();
     buf_writer.flush()?;
     Ok(summary)
 }
 
+/// Serialize `record` as JSON text, in canonical form when `canonical` is set
+/// (built in `scratch` first).
+fn write_json_record<W: Write>(
+    out: &mut W,
+    record: &Map<String, Value>,
+    canonical: bool,
+    scratch: &mut Vec<u8>,
+) -> Result<()> {
+    if canonical {
+        scratch.clear();
+        fingerprint::write_canonical_json(record, scratch);
+        out.write_all(scratch)?;
+    } else {
+        serde_json::to_writer(out, record)?;
+    }
+    Ok(())
+}
+
+/// Execute a decompression request, handing records to `on_batch` instead of
+/// serializing them to the request's output.
+///
+/// Records arrive in file order in one batch per decoded block, trimmed by the
+/// request's `offset` and `limit` and with its renames applied; `output` and
+/// `format` are not used. An error returned by `on_batch` stops decoding and
+/// is returned as is.
+pub fn execute_decompress_with<F>(
+    request: DecompressRequest,
+    mut on_batch: F,
+) -> Result<DecompressSummary>
+where
+    F: FnMut(Vec<Map<String, Value>>) -> Result<()>,
+{
+    let DecompressRequest {
+        input,
+        options,
+        offset,
+        limit,
+        blocks,
+        rename,
+        require_pointer,
+        filter,
+        ..
+    } = request;
+
+    let codec_opts = DecompressOpts {
+        limits: options.limits.clone(),
+        verify_checksums: options.verify_checksums,
+        strict: options.strict,
+        declared_limits: options.declared_limits,
+        ..DecompressOpts::default()
+    };
+    let mut reader = input.open(codec_opts, options.snapshot)?;
+    reader.set_schema_mapping(options.schema_mapping);
+    let (offset, window) = select_records(&mut reader, blocks, offset)?;
+
+    let mut record_stream = reader.record_stream_from(offset)?;
+    if let Some(window) = window {
+        record_stream.stop_after(window);
+    }
+    if let Some(pointer) = &require_pointer {
+        record_stream.require_pointer(pointer)?;
+    }
+    let filtered = filter.is_some();
+    if let Some(filter) = filter {
+        record_stream.set_filter(filter);
+    }
+    let mut remaining = request_limit(limit);
+    let mut summary = DecompressSummary::default();
+    while remaining > 0 {
+        let Some(batch) = record_stream.next_batch() else {
+            break;
+        };
+        let mut batch = batch?;
+        batch.truncate(remaining);
+        remaining -= batch.len();
+        summary.records_written += batch.len() as u64;
+        on_batch(
+            batch
+                .into_iter()
+                .map(|record| rename_fields(record, &rename))
+                .collect(),
+        )?;
+    }
+
+    summary.blocks_processed = record_stream.blocks_processed();
+    summary.filter = filtered.then(|| record_stream.filter_summary());
+    drop(record_stream);
+    summary.warnings = reader.warnings();
+    summary.metrics = reader.metrics();
+    Ok(summary)
+}
+
 /// Execute a projection request.
 pub fn execute_project(request: ProjectRequest) -> Result<ProjectSummary> {
     let ProjectRequest {
@@ -656,6 +1842,10 @@ pub fn execute_project(request: ProjectRequest) -> Result<ProjectSummary> {
         fields,
         format,
         options,
+        include_presence,
+        offset,
+        limit,
+        rename,
     } = request;
 
     if fields.is_empty() {
@@ -664,103 +1854,169 @@ pub fn execute_project(request: ProjectRequest) -> Result<ProjectSummary> {
         ));
     }
 
-    let reader_source = input.into_reader()?;
     let codec_opts = DecompressOpts {
         limits: options.limits.clone(),
         verify_checksums: options.verify_checksums,
+        strict: options.strict,
+        declared_limits: options.declared_limits,
+        ..DecompressOpts::default()
     };
-    let mut reader = JacReader::new(reader_source, codec_opts)?;
+    let mut reader = input.open(codec_opts, options.snapshot)?;
 
     let mut buf_writer = BufWriter::new(output.into_writer()?);
-    let mut cursor = BlockCursor::new(&reader);
-    let mut summary = ProjectSummary { rows_written: 0 };
+    let mut summary = ProjectSummary::default();
+    let (source_fields, sources) = projection_sources(&fields, &rename);
+    let rows = reader
+        .multi_projection_stream_from(&source_fields, offset.unwrap_or(0))?
+        .take(request_limit(limit))
+        .map(move |row| {
+            row.map(|mut row| {
+                sources
+                    .iter()
+                    .map(|range| row[range.clone()].iter_mut().find_map(Option::take))
+                    .collect::<Vec<_>>()
+            })
+        });
+
+    let to_object = |row: Vec<Option<Value>>| {
+        let mut projected = Map::new();
+        for (field, value) in fields.iter().zip(row) {
+            if let Some(value) = value {
+                projected.insert(field.clone(), value);
+            }
+        }
+        projected.sort_keys();
+        Value::Object(projected)
+    };
 
     match format {
         ProjectFormat::Ndjson => {
-            while let Some(block) = reader.next_block_handle(&mut cursor) {
-                let block = block?;
-                let decoder = reader.decode_block(&block)?;
-                let mut columns = Vec::with_capacity(fields.len());
-                for field in &fields {
-                    columns.push(decoder.project_field(field)?);
-                }
-
-                for record_idx in 0..block.record_count {
-                    let mut projected = Map::new();
-                    for (field, column) in fields.iter().zip(columns.iter()) {
-                        if let Some(Some(value)) = column.get(record_idx) {
-                            projected.insert(field.clone(), value.clone());
-                        }
-                    }
-                    serde_json::to_writer(&mut buf_writer, &Value::Object(projected))?;
-                    buf_writer.write_all(b"\n")?;
-                    summary.rows_written += 1;
-                }
+            for row in rows {
+                serde_json::to_writer(&mut buf_writer, &to_object(row?))?;
+                buf_writer.write_all(b"\n")?;
+                summary.rows_written += 1;
             }
         }
         ProjectFormat::JsonArray => {
             buf_writer.write_all(b"[")?;
-            let mut first = true;
-            while let Some(block) = reader.next_block_handle(&mut cursor) {
-                let block = block?;
-                let decoder = reader.decode_block(&block)?;
-                let mut columns = Vec::with_capacity(fields.len());
-                for field in &fields {
-                    columns.push(decoder.project_field(field)?);
-                }
-
-                for record_idx in 0..block.record_count {
-                    if first {
-                        first = false;
-                    } else {
-                        buf_writer.write_all(b",")?;
-                    }
-                    let mut projected = Map::new();
-                    for (field, column) in fields.iter().zip(columns.iter()) {
-                        if let Some(Some(value)) = column.get(record_idx) {
-                            projected.insert(field.clone(), value.clone());
-                        }
-                    }
-                    serde_json::to_writer(&mut buf_writer, &Value::Object(projected))?;
-                    summary.rows_written += 1;
+            for row in rows {
+                if summary.rows_written > 0 {
+                    buf_writer.write_all(b",")?;
                 }
+                serde_json::to_writer(&mut buf_writer, &to_object(row?))?;
+                summary.rows_written += 1;
             }
             buf_writer.write_all(b"]")?;
         }
         ProjectFormat::Csv { headers } => {
             if headers {
-                write_csv_row(&mut buf_writer, fields.iter().map(|s| s.as_str()))?;
+                let names: Vec<String> = if include_presence {
+                    fields
+                        .iter()
+                        .flat_map(|f| [f.clone(), format!("{f}_presence")])
+                        .collect()
+                } else {
+                    fields.clone()
+                };
+                write_csv_row(&mut buf_writer, names.iter().map(|s| s.as_str()))?;
             }
 
-            while let Some(block) = reader.next_block_handle(&mut cursor) {
-                let block = block?;
-                let decoder = reader.decode_block(&block)?;
-                let mut columns = Vec::with_capacity(fields.len());
-                for field in &fields {
-                    columns.push(decoder.project_field(field)?);
-                }
-
-                for record_idx in 0..block.record_count {
-                    let mut row = Vec::with_capacity(fields.len());
-                    for column in &columns {
-                        let cell = column
-                            .get(record_idx)
-                            .and_then(|opt| opt.as_ref())
-                            .map(csv_cell_value)
-                            .unwrap_or_default();
-                        row.push(cell);
+            for row in rows {
+                let mut cells = Vec::with_capacity(fields.len() * 2);
+                for cell in row? {
+                    cells.push(cell.as_ref().map(csv_cell_value).unwrap_or_default());
+                    if include_presence {
+                        let presence = match cell {
+                            None => "absent",
+                            Some(Value::Null) => "null",
+                            Some(_) => "present",
+                        };
+                        cells.push(presence.to_string());
                     }
-                    write_csv_row(&mut buf_writer, row.iter().map(|s| s.as_str()))?;
-                    summary.rows_written += 1;
                 }
+                write_csv_row(&mut buf_writer, cells.iter().map(|s| s.as_str()))?;
+                summary.rows_written += 1;
             }
         }
     }
 
+    summary.warnings = reader.warnings();
+    summary.metrics = reader.metrics();
     buf_writer.flush()?;
     Ok(summary)
 }
 
+/// Apply a rename map to a reconstructed record, keeping key order.
+pub(crate) fn rename_fields(
+    record: Map<String, Value>,
+    rename: &HashMap<String, String>,
+) -> Map<String, Value> {
+    if rename.is_empty() || !record.keys().any(|key| rename.contains_key(key)) {
+        return record;
+    }
+    let shadowed = |new: &String| record.contains_key(new) && !rename.contains_key(new);
+    let dropped: Vec<String> = rename
+        .iter()
+        .filter(|(old, new)| record.contains_key(*old) && shadowed(new))
+        .map(|(old, _)| old.clone())
+        .collect();
+
+    let mut renamed = Map::with_capacity(record.len());
+    for (key, value) in record {
+        if dropped.contains(&key) {
+            continue;
+        }
+        let key = rename.get(&key).cloned().unwrap_or(key);
+        renamed.insert(key, value);
+    }
+    renamed
+}
+
+/// Stored field names to read for each requested output field, and the range
+/// of those names that feeds each output field in order of preference.
+fn projection_sources(
+    fields: &[String],
+    rename: &HashMap<String, String>,
+) -> (Vec<String>, Vec<std::ops::Range<usize>>) {
+    let mut names = Vec::with_capacity(fields.len());
+    let mut ranges = Vec::with_capacity(fields.len());
+    for field in fields {
+        let start = names.len();
+        if !rename.contains_key(field) {
+            names.push(field.clone());
+        }
+        let mut olds: Vec<&String> = rename
+            .iter()
+            .filter(|(_, new)| *new == field)
+            .map(|(old, _)| old)
+            .collect();
+        olds.sort();
+        names.extend(olds.into_iter().cloned());
+        ranges.push(start..names.len());
+    }
+    (names, ranges)
+}
+
+/// Convert a request's record limit into an iterator `take` count.
+fn request_limit(limit: Option<u64>) -> usize {
+    limit.map_or(usize::MAX, |n| usize::try_from(n).unwrap_or(usize::MAX))
+}
+
+/// First record of a decompression request and, when `blocks` is given, how
+/// many stored records of the range follow it.
+fn select_records<R: Read + Seek>(
+    reader: &mut JacReader<R>,
+    blocks: Option<BlockRange>,
+    offset: Option<u64>,
+) -> Result<(u64, Option<u64>)> {
+    let offset = offset.unwrap_or(0);
+    let Some(blocks) = blocks else {
+        return Ok((offset, None));
+    };
+    let (first, len) = blocks.record_window(reader)?;
+    Ok((first + offset.min(len), Some(len.saturating_sub(offset))))
+}
+
 #[deprecated(note = "use `execute_compress` with `CompressRequest` instead")]
 /// Backward-compatible compression helper (NDJSON input, NDJSON output).
 pub fn compress<R, W>(input: R, output: W, opts: CompressOptions) -> Result<()>
@@ -775,6 +2031,7 @@ where
         container_hint: Some(ContainerFormat::Ndjson),
         emit_index: true,
         wrapper_config: WrapperConfig::None,
+        interrupt: None,
     };
     execute_compress(request).map(|_| ())
 }
@@ -791,6 +2048,15 @@ where
         output: OutputSink::Writer(Box::new(output)),
         format: DecompressFormat::Ndjson,
         options: opts,
+        offset: None,
+        limit: None,
+        blocks: None,
+        rename: HashMap::new(),
+        line_ending: LineEnding::default(),
+        encoding: TextEncoding::default(),
+        validate_utf8: false,
+        require_pointer: None,
+        filter: None,
     };
     execute_decompress(request).map(|_| ())
 }
@@ -812,39 +2078,57 @@ where
             ProjectFormat::JsonArray
         },
         options: DecompressOptions::default(),
+        include_presence: false,
+        offset: None,
+        limit: None,
+        rename: HashMap::new(),
     };
     execute_project(request).map(|_| ())
 }
 
 impl InputSource {
-    pub(crate) fn into_record_stream(self, wrapper_config: &WrapperConfig) -> Result<RecordStream> {
+    pub(crate) fn into_record_stream(
+        self,
+        wrapper_config: &WrapperConfig,
+        ingest: IngestConfig,
+    ) -> Result<RecordStream> {
+        let source = match ingest.relaxed_json {
+            Some(config) => relaxed_json::apply(self, config)?,
+            None => self,
+        };
         // Apply wrapper if configured
         match wrapper_config {
             WrapperConfig::None => {
                 // No wrapper, use standard streams
-                match self {
+                match source {
                     InputSource::NdjsonPath(path) => {
                         let file = File::open(path)?;
-                        Ok(RecordStream::ndjson(BufReader::new(file)))
+                        Ok(RecordStream::ndjson(BufReader::new(file), ingest))
                     }
                     InputSource::JsonArrayPath(path) => {
                         let file = File::open(path)?;
-                        RecordStream::json_array_reader(BufReader::new(file))
+                        RecordStream::json_array_reader(BufReader::new(file), ingest)
                     }
                     InputSource::NdjsonReader(reader) => {
-                        Ok(RecordStream::ndjson(BufReader::new(reader)))
+                        Ok(RecordStream::ndjson(BufReader::new(reader), ingest))
                     }
                     InputSource::JsonArrayReader(reader) => {
-                        RecordStream::json_array_reader(BufReader::new(reader))
+                        RecordStream::json_array_reader(BufReader::new(reader), ingest)
                     }
                     InputSource::Iterator(iter) => Ok(RecordStream::iter(iter)),
+                    InputSource::MessagePackReader(reader) => RecordStream::documents(|counters| {
+                        binary_input::message_pack_documents(reader, ingest, counters)
+                    }),
+                    InputSource::BsonReader(reader) => RecordStream::documents(|counters| {
+                        binary_input::bson_documents(reader, ingest, counters)
+                    }),
                 }
             }
             WrapperConfig::Pointer { path, limits } => {
                 // Apply pointer wrapper
                 use wrapper::pointer::{PointerArrayStream, PointerLimits};
 
-                let reader: Box<dyn Read + Send> = match self {
+                let reader: Box<dyn Read + Send> = match source {
                     InputSource::NdjsonPath(p) => Box::new(File::open(p)?),
                     InputSource::JsonArrayPath(p) => Box::new(File::open(p)?),
                     InputSource::NdjsonReader(r) => r,
@@ -855,6 +2139,12 @@ impl InputSource {
                                 .to_string(),
                         ));
                     }
+                    InputSource::MessagePackReader(_) | InputSource::BsonReader(_) => {
+                        return Err(JacError::Internal(
+                            "Wrapper configuration cannot be applied to binary input sources"
+                                .to_string(),
+                        ));
+                    }
                 };
 
                 let pointer_limits = PointerLimits {
@@ -873,6 +2163,7 @@ impl InputSource {
                     processing_duration: stream.metrics().processing_duration,
                     pointer_path: Some(path.clone()),
                     section_counts: None,
+                    sections_truncated: Vec::new(),
                     map_entry_count: None,
                     header_field_count: None,
                     plugin_name: None,
@@ -890,7 +2181,7 @@ impl InputSource {
                 // Apply sections wrapper
                 use wrapper::sections::SectionsStream;
 
-                let reader: Box<dyn Read + Send> = match self {
+                let reader: Box<dyn Read + Send> = match source {
                     InputSource::NdjsonPath(p) => Box::new(File::open(p)?),
                     InputSource::JsonArrayPath(p) => Box::new(File::open(p)?),
                     InputSource::NdjsonReader(r) => r,
@@ -901,6 +2192,12 @@ impl InputSource {
                                 .to_string(),
                         ));
                     }
+                    InputSource::MessagePackReader(_) | InputSource::BsonReader(_) => {
+                        return Err(JacError::Internal(
+                            "Wrapper configuration cannot be applied to binary input sources"
+                                .to_string(),
+                        ));
+                    }
                 };
 
                 let stream = SectionsStream::new(
@@ -920,6 +2217,7 @@ impl InputSource {
                     processing_duration: stream.metrics().processing_duration,
                     pointer_path: None,
                     section_counts: Some(stream.metrics().section_counts.clone()),
+                    sections_truncated: stream.metrics().sections_truncated.clone(),
                     map_entry_count: None,
                     header_field_count: None,
                     plugin_name: None,
@@ -930,13 +2228,15 @@ impl InputSource {
             WrapperConfig::KeyedMap {
                 pointer,
                 key_field,
+                inner_key_field,
+                nested_key_fields,
                 limits,
                 collision_mode,
             } => {
                 // Apply keyed map wrapper
-                use wrapper::map::KeyedMapStream;
+                use wrapper::map::{KeyedMapStream, MapKeyFields};
 
-                let reader: Box<dyn Read + Send> = match self {
+                let reader: Box<dyn Read + Send> = match source {
                     InputSource::NdjsonPath(p) => Box::new(File::open(p)?),
                     InputSource::JsonArrayPath(p) => Box::new(File::open(p)?),
                     InputSource::NdjsonReader(r) => r,
@@ -947,12 +2247,23 @@ impl InputSource {
                                 .to_string(),
                         ));
                     }
+                    InputSource::MessagePackReader(_) | InputSource::BsonReader(_) => {
+                        return Err(JacError::Internal(
+                            "Wrapper configuration cannot be applied to binary input sources"
+                                .to_string(),
+                        ));
+                    }
                 };
 
-                let stream = KeyedMapStream::new(
+                let keys = MapKeyFields {
+                    key_field: key_field.clone(),
+                    inner_key_field: inner_key_field.clone(),
+                    nested: *nested_key_fields,
+                };
+                let stream = KeyedMapStream::with_key_fields(
                     reader,
                     pointer.clone(),
-                    key_field.clone(),
+                    keys,
                     limits.clone(),
                     *collision_mode,
                 )
@@ -969,6 +2280,7 @@ impl InputSource {
                         Some(pointer.clone())
                     },
                     section_counts: None,
+                    sections_truncated: Vec::new(),
                     map_entry_count: Some(stream.metrics().map_entry_count),
                     header_field_count: None,
                     plugin_name: None,
@@ -976,11 +2288,15 @@ impl InputSource {
 
                 Ok(RecordStream::wrapper(Box::new(stream), metrics))
             }
-            WrapperConfig::ArrayWithHeaders { limits } => {
+            WrapperConfig::ArrayWithHeaders {
+                limits,
+                type_row,
+                null_markers,
+            } => {
                 // Apply array-with-headers wrapper
-                use wrapper::array_headers::ArrayHeadersStream;
+                use wrapper::array_headers::{ArrayHeadersOptions, ArrayHeadersStream};
 
-                let reader: Box<dyn Read + Send> = match self {
+                let reader: Box<dyn Read + Send> = match source {
                     InputSource::NdjsonPath(p) => Box::new(File::open(p)?),
                     InputSource::JsonArrayPath(p) => Box::new(File::open(p)?),
                     InputSource::NdjsonReader(r) => r,
@@ -991,9 +2307,19 @@ impl InputSource {
                                 .to_string(),
                         ));
                     }
+                    InputSource::MessagePackReader(_) | InputSource::BsonReader(_) => {
+                        return Err(JacError::Internal(
+                            "Wrapper configuration cannot be applied to binary input sources"
+                                .to_string(),
+                        ));
+                    }
                 };
 
-                let stream = ArrayHeadersStream::new(reader, limits.clone())
+                let options = ArrayHeadersOptions {
+                    type_row: *type_row,
+                    null_markers: null_markers.clone(),
+                };
+                let stream = ArrayHeadersStream::with_options(reader, limits.clone(), options)
                     .map_err(|e| JacError::Internal(format!("Wrapper error: {}", e)))?;
 
                 let metrics = WrapperMetrics {
@@ -1003,6 +2329,7 @@ impl InputSource {
                     processing_duration: stream.metrics().processing_duration,
                     pointer_path: None,
                     section_counts: None,
+                    sections_truncated: Vec::new(),
                     map_entry_count: None,
                     header_field_count: Some(stream.metrics().header_field_count),
                     plugin_name: None,
@@ -1018,7 +2345,7 @@ impl InputSource {
                 // Apply plugin wrapper
                 use wrapper::plugin::WrapperPluginRegistry;
 
-                let reader: Box<dyn Read + Send> = match self {
+                let reader: Box<dyn Read + Send> = match source {
                     InputSource::NdjsonPath(p) => Box::new(File::open(p)?),
                     InputSource::JsonArrayPath(p) => Box::new(File::open(p)?),
                     InputSource::NdjsonReader(r) => r,
@@ -1029,6 +2356,12 @@ impl InputSource {
                                 .to_string(),
                         ));
                     }
+                    InputSource::MessagePackReader(_) | InputSource::BsonReader(_) => {
+                        return Err(JacError::Internal(
+                            "Wrapper configuration cannot be applied to binary input sources"
+                                .to_string(),
+                        ));
+                    }
                 };
 
                 let registry = WrapperPluginRegistry::global();
@@ -1056,12 +2389,15 @@ impl InputSource {
                     processing_duration,
                     pointer_path: None,
                     section_counts: None,
+                    sections_truncated: Vec::new(),
                     map_entry_count: None,
                     header_field_count: None,
                     plugin_name: Some(plugin_name.clone()),
                 };
 
-                Ok(RecordStream::wrapper(iterator, metrics))
+                let mut stream = RecordStream::wrapper(iterator, metrics);
+                stream.schema_hints = plugin.schema_hints(config);
+                Ok(stream)
             }
         }
     }
@@ -1070,18 +2406,117 @@ impl InputSource {
 impl OutputSink {
     pub(crate) fn into_writer(self) -> Result<Box<dyn WriteSend>> {
         match self {
-            OutputSink::Path(path) => Ok(Box::new(File::create(path)?)),
+            OutputSink::Path(path) | OutputSink::Parts(path, _) => {
+                Ok(Box::new(File::create(path)?))
+            }
             OutputSink::Writer(writer) => Ok(writer),
         }
     }
-}
 
-impl JacInput {
-    fn into_reader(self) -> Result<Box<dyn ReadSeekSend>> {
+    /// Directory for the temporary files of a compression run writing here.
+    pub(crate) fn temp_dir(&self, options: &CompressOptions) -> PathBuf {
+        if let Some(dir) = &options.temp_dir {
+            return dir.clone();
+        }
         match self {
-            JacInput::Path(path) => Ok(Box::new(File::open(path)?)),
-            JacInput::Reader(reader) => Ok(reader),
+            OutputSink::Path(path) | OutputSink::Parts(path, _) => match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                _ => PathBuf::from("."),
+            },
+            OutputSink::Writer(_) => std::env::temp_dir(),
+        }
+    }
+
+    /// Open the sink for a compression run, with the state needed to
+    /// continue into further files under `options.max_output_bytes`.
+    pub(crate) fn open_parts(
+        self,
+        options: &CompressOptions,
+        header: &FileHeader,
+        emit_index: bool,
+    ) -> Result<(Box<dyn WriteSend>, OutputParts)> {
+        let (path, namer): (PathBuf, PartNamer) = match self {
+            OutputSink::Path(path) => (path, Box::new(default_part_path)),
+            OutputSink::Parts(path, namer) => (path, namer),
+            OutputSink::Writer(writer) => {
+                if options.max_output_bytes.is_some() {
+                    return Err(JacError::UnsupportedFeature(
+                        "max_output_bytes requires a path output".to_string(),
+                    ));
+                }
+                return Ok((writer, OutputParts::default()));
+            }
+        };
+        let file: Box<dyn WriteSend> = Box::new(File::create(&path)?);
+        Ok((
+            file,
+            OutputParts {
+                files: vec![path],
+                namer: Some(namer),
+                header: Some(header.clone()),
+                emit_index,
+            },
+        ))
+    }
+}
+
+/// Files of a compression output and how to start the next one.
+#[derive(Default)]
+pub(crate) struct OutputParts {
+    /// Paths written so far; empty for writer outputs
+    files: Vec<PathBuf>,
+    namer: Option<PartNamer>,
+    header: Option<FileHeader>,
+    emit_index: bool,
+}
+
+impl OutputParts {
+    /// Move on to the next file while `writer` holds blocks back at its size cap.
+    pub(crate) fn continue_if_full(
+        &mut self,
+        writer: &mut JacWriter<BufWriter<Box<dyn WriteSend>>>,
+    ) -> Result<()> {
+        while writer.at_size_cap() {
+            let (Some(namer), Some(header), Some(first)) =
+                (&self.namer, &self.header, self.files.first())
+            else {
+                return Err(JacError::Internal(
+                    "size-capped output has no path to continue into".to_string(),
+                ));
+            };
+            let path = namer(first, self.files.len() as u32 + 1);
+            let next: Box<dyn WriteSend> = Box::new(File::create(&path)?);
+            let mut finished =
+                writer.continue_into(BufWriter::new(next), header.clone(), self.emit_index)?;
+            finished.flush()?;
+            self.files.push(path);
         }
+        Ok(())
+    }
+}
+
+impl JacInput {
+    /// Open the input, as a [`JacReader::snapshot`] when `snapshot` is set.
+    pub(crate) fn open(
+        self,
+        opts: DecompressOpts,
+        snapshot: bool,
+    ) -> Result<JacReader<Box<dyn ReadSeekSend>>> {
+        let open = |reader: Box<dyn ReadSeekSend>| {
+            if snapshot {
+                JacReader::snapshot(reader, opts)
+            } else {
+                JacReader::new(reader, opts)
+            }
+        };
+        let bytes = match self {
+            JacInput::Path(path) => return open(Box::new(File::open(path)?)),
+            JacInput::Reader(reader) => return open(reader),
+            JacInput::Bytes(bytes) => bytes,
+            JacInput::Memory(buffer) => bytes::Bytes::from(buffer),
+        };
+        let cursor: Box<dyn ReadSeekSend> = Box::new(std::io::Cursor::new(bytes.clone()));
+        Ok(open(cursor)?.with_memory(bytes))
     }
 }
 
@@ -1132,37 +2567,76 @@ fn csv_cell_value(value: &Value) -> String {
 }
 
 /// Record stream used during compression.
-struct RecordStream {
+pub(crate) struct RecordStream {
     inner: RecordStreamInner,
     format: ContainerFormat,
     wrapper_metrics: Option<WrapperMetrics>,
+    /// Schema hints of the plugin wrapper producing the records
+    schema_hints: Option<SchemaHints>,
+    counters: IngestCounters,
+    normalizer: Option<KeyNormalizer>,
+    coercer: Option<Coercer>,
+    deduper: Option<Deduper>,
+    injector: Option<FieldInjector>,
 }
 
 enum RecordStreamInner {
     Ndjson(NdjsonStream),
     JsonArray(JsonArrayStream),
     Iterator(Box<dyn Iterator<Item = Map<String, Value>> + Send>),
+    Documents(binary_input::Documents),
     Wrapper(Box<dyn Iterator<Item = std::result::Result<Map<String, Value>, WrapperError>> + Send>),
 }
 
 impl RecordStream {
-    fn ndjson<R: BufRead + Send + 'static>(reader: R) -> Self {
+    fn ndjson<R: BufRead + Send + 'static>(reader: R, ingest: IngestConfig) -> Self {
+        let counters = IngestCounters::default();
         Self {
             inner: RecordStreamInner::Ndjson(NdjsonStream {
-                reader: Box::new(reader),
-                buffer: String::new(),
+                reader: CountingReader {
+                    inner: Box::new(reader),
+                    consumed: 0,
+                },
+                line: 0,
+                buffer: Vec::new(),
+                ingest,
+                counters: counters.clone(),
             }),
             format: ContainerFormat::Ndjson,
             wrapper_metrics: None,
+            schema_hints: None,
+            counters,
+            normalizer: None,
+            coercer: None,
+            deduper: None,
+            injector: None,
         }
     }
 
-    fn json_array_reader<R: BufRead + Send + 'static>(reader: R) -> Result<Self> {
-        let stream = JsonArrayStream::from_reader(Box::new(reader))?;
+    fn json_array_reader<R: BufRead + Send + 'static>(
+        reader: R,
+        ingest: IngestConfig,
+    ) -> Result<Self> {
+        let counters = IngestCounters::default();
+        let reader: Box<dyn BufRead + Send> = match ingest.utf8 {
+            Utf8Policy::Strict => Box::new(reader),
+            policy => Box::new(BufReader::new(Utf8RepairReader::new(
+                reader,
+                policy,
+                Arc::clone(&counters.utf8),
+            ))),
+        };
+        let stream = JsonArrayStream::from_reader(reader, ingest, counters.clone())?;
         Ok(Self {
             inner: RecordStreamInner::JsonArray(stream),
             format: ContainerFormat::JsonArray,
             wrapper_metrics: None,
+            schema_hints: None,
+            counters,
+            normalizer: None,
+            coercer: None,
+            deduper: None,
+            injector: None,
         })
     }
 
@@ -1171,9 +2645,33 @@ impl RecordStream {
             inner: RecordStreamInner::Iterator(iter),
             format: ContainerFormat::Unknown,
             wrapper_metrics: None,
+            schema_hints: None,
+            counters: IngestCounters::default(),
+            normalizer: None,
+            coercer: None,
+            deduper: None,
+            injector: None,
         }
     }
 
+    /// Records decoded by a binary document reader sharing this stream's counters.
+    fn documents(
+        open: impl FnOnce(IngestCounters) -> Result<binary_input::Documents>,
+    ) -> Result<Self> {
+        let counters = IngestCounters::default();
+        Ok(Self {
+            inner: RecordStreamInner::Documents(open(counters.clone())?),
+            format: ContainerFormat::Unknown,
+            wrapper_metrics: None,
+            schema_hints: None,
+            counters,
+            normalizer: None,
+            coercer: None,
+            deduper: None,
+            injector: None,
+        })
+    }
+
     fn wrapper(
         iter: Box<
             dyn Iterator<Item = std::result::Result<Map<String, Value>, WrapperError>> + Send,
@@ -1184,9 +2682,39 @@ impl RecordStream {
             inner: RecordStreamInner::Wrapper(iter),
             format: ContainerFormat::JsonArray, // Wrappers always produce array-like output
             wrapper_metrics: Some(metrics),
+            schema_hints: None,
+            counters: IngestCounters::default(),
+            normalizer: None,
+            coercer: None,
+            deduper: None,
+            injector: None,
         }
     }
 
+    /// Rewrite top-level keys of every record yielded.
+    fn with_key_normalization(mut self, config: &KeyNormalization) -> Self {
+        self.normalizer = KeyNormalizer::new(config, Arc::clone(&self.counters.key_normalization));
+        self
+    }
+
+    /// Apply `policy` to every record yielded, counting into this stream's counters.
+    fn with_coercion(mut self, policy: &CoercionPolicy) -> Self {
+        self.coercer = Coercer::new(policy, Arc::clone(&self.counters.coercion));
+        self
+    }
+
+    /// Drop records the configured filter has already seen.
+    fn with_dedupe(mut self, config: Option<&DedupeConfig>, temp_dir: &Path) -> Self {
+        self.deduper = Deduper::new(config, temp_dir, Arc::clone(&self.counters.dedupe));
+        self
+    }
+
+    /// Add the configured provenance fields to every record yielded.
+    fn with_injected_fields(mut self, fields: &InjectFields) -> Self {
+        self.injector = FieldInjector::new(fields);
+        self
+    }
+
     fn container_format(&self) -> ContainerFormat {
         self.format
     }
@@ -1194,26 +2722,90 @@ impl RecordStream {
     fn take_wrapper_metrics(&mut self) -> Option<WrapperMetrics> {
         self.wrapper_metrics.take()
     }
+
+    fn take_schema_hints(&mut self) -> Option<SchemaHints> {
+        self.schema_hints.take()
+    }
+
+    fn counters(&self) -> IngestCounters {
+        self.counters.clone()
+    }
 }
 
 impl Iterator for RecordStream {
     type Item = Result<Map<String, Value>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match &mut self.inner {
-            RecordStreamInner::Ndjson(stream) => stream.next(),
-            RecordStreamInner::JsonArray(stream) => stream.next(),
-            RecordStreamInner::Iterator(iter) => iter.next().map(Ok),
-            RecordStreamInner::Wrapper(iter) => iter
-                .next()
-                .map(|r| r.map_err(|e| JacError::Internal(format!("Wrapper error: {}", e)))),
+        loop {
+            let next = match &mut self.inner {
+                RecordStreamInner::Ndjson(stream) => stream.next(),
+                RecordStreamInner::JsonArray(stream) => stream.next(),
+                RecordStreamInner::Iterator(iter) => iter.next().map(Ok),
+                RecordStreamInner::Documents(iter) => iter.next(),
+                RecordStreamInner::Wrapper(iter) => iter
+                    .next()
+                    .map(|r| r.map_err(|e| JacError::Internal(format!("Wrapper error: {}", e)))),
+            };
+            return match next {
+                Some(Ok(mut record)) => {
+                    if let Some(normalizer) = self.normalizer.as_mut() {
+                        if let Err(err) = normalizer.apply(&mut record) {
+                            return Some(Err(err));
+                        }
+                    }
+                    if let Some(coercer) = self.coercer.as_mut() {
+                        coercer.apply(&mut record);
+                    }
+                    if let Some(deduper) = self.deduper.as_mut() {
+                        match deduper.is_duplicate(&record) {
+                            Ok(true) => continue,
+                            Ok(false) => {}
+                            Err(err) => return Some(Err(err)),
+                        }
+                    }
+                    if let Some(injector) = self.injector.as_mut() {
+                        injector.apply(&mut record);
+                    }
+                    Some(Ok(record))
+                }
+                other => other,
+            };
         }
     }
 }
 
 struct NdjsonStream {
-    reader: Box<dyn BufRead + Send>,
-    buffer: String,
+    reader: CountingReader,
+    /// Lines read so far, including blank and skipped ones
+    line: u64,
+    buffer: Vec<u8>,
+    ingest: IngestConfig,
+    counters: IngestCounters,
+}
+
+/// `BufRead` adapter counting the bytes consumed through it.
+struct CountingReader {
+    inner: Box<dyn BufRead + Send>,
+    consumed: u64,
+}
+
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.consumed += read as u64;
+        Ok(read)
+    }
+}
+
+impl BufRead for CountingReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.consumed += amt as u64;
+    }
 }
 
 impl Iterator for NdjsonStream {
@@ -1222,29 +2814,80 @@ impl Iterator for NdjsonStream {
     fn next(&mut self) -> Option<Self::Item> {
         loop {
             self.buffer.clear();
-            match self.reader.read_line(&mut self.buffer) {
-                Ok(0) => return None,
-                Ok(_) => {
-                    if self.buffer.starts_with('\u{feff}') {
-                        let bom_len = '\u{feff}'.len_utf8();
-                        if self.buffer.len() >= bom_len {
-                            self.buffer.drain(..bom_len);
-                        }
+            let offset = self.reader.consumed;
+            self.line += 1;
+            match self.read_line() {
+                Ok(BoundedRead::Eof) => return None,
+                Ok(BoundedRead::Skipped) => {
+                    self.counters
+                        .oversized_skipped
+                        .fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+                Ok(BoundedRead::Record) => {
+                    if self.buffer.starts_with(&[0xEF, 0xBB, 0xBF]) {
+                        self.buffer.drain(..3);
                     }
-                    if self.buffer.trim().is_empty() {
+                    if self.buffer.iter().all(u8::is_ascii_whitespace) {
                         continue;
                     }
-                    match serde_json::from_str::<Map<String, Value>>(&self.buffer) {
-                        Ok(map) => return Some(Ok(map)),
-                        Err(err) => return Some(Err(JacError::from(err))),
-                    }
+                    return Some(self.parse_line(offset));
                 }
-                Err(err) => return Some(Err(JacError::from(err))),
+                Err(err) => return Some(Err(err)),
             }
         }
     }
 }
 
+impl NdjsonStream {
+    fn read_line(&mut self) -> Result<BoundedRead> {
+        match self.ingest.max_record_bytes {
+            Some(limit) => record_limit::read_line(
+                &mut self.reader,
+                &mut self.buffer,
+                limit,
+                self.ingest.oversized == OversizedRecordPolicy::Skip,
+            ),
+            None => match self.reader.read_until(b'\n', &mut self.buffer)? {
+                0 => Ok(BoundedRead::Eof),
+                _ => Ok(BoundedRead::Record),
+            },
+        }
+    }
+
+    /// Parse the buffered line, which starts at byte `offset` of the input.
+    fn parse_line(&self, offset: u64) -> Result<Map<String, Value>> {
+        let repaired;
+        let line = match std::str::from_utf8(&self.buffer) {
+            Ok(line) => line,
+            Err(_) if self.ingest.utf8 == Utf8Policy::Strict => {
+                return Err(JacError::Io(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "input line is not valid UTF-8 (see the lossy and base64 UTF-8 policies)",
+                )));
+            }
+            Err(_) => {
+                let bytes =
+                    utf8_repair::repair_bytes(&self.buffer, self.ingest.utf8, &self.counters.utf8);
+                repaired = String::from_utf8(bytes).map_err(|_| {
+                    JacError::Io(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "input line has invalid UTF-8 outside string values",
+                    ))
+                })?;
+                repaired.as_str()
+            }
+        };
+        duplicate_keys::parse_record_str(line, self.ingest.duplicate_keys).map_err(|source| {
+            JacError::RecordParse {
+                line: self.line,
+                offset,
+                source,
+            }
+        })
+    }
+}
+
 struct JsonArrayStream {
     reader: Box<dyn BufRead + Send>,
     mode: JsonArrayMode,
@@ -1252,6 +2895,9 @@ struct JsonArrayStream {
     array_expect_value: bool,
     emitted_any: bool,
     consumed_single_object: bool,
+    ingest: IngestConfig,
+    counters: IngestCounters,
+    object_buffer: Vec<u8>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -1261,7 +2907,11 @@ enum JsonArrayMode {
 }
 
 impl JsonArrayStream {
-    fn from_reader(reader: Box<dyn BufRead + Send>) -> Result<Self> {
+    fn from_reader(
+        reader: Box<dyn BufRead + Send>,
+        ingest: IngestConfig,
+        counters: IngestCounters,
+    ) -> Result<Self> {
         let mut stream = Self {
             reader,
             mode: JsonArrayMode::Array,
@@ -1269,6 +2919,9 @@ impl JsonArrayStream {
             array_expect_value: true,
             emitted_any: false,
             consumed_single_object: false,
+            ingest,
+            counters,
+            object_buffer: Vec::new(),
         };
         stream.consume_bom()?;
         stream.initialize_mode()?;
@@ -1277,8 +2930,8 @@ impl JsonArrayStream {
 
     fn consume_bom(&mut self) -> Result<()> {
         let buf = self.reader.fill_buf()?;
-        if buf.starts_with(&[0xEF, 0xBB, 0xBF]) {
-            self.reader.consume(3);
+        if buf.starts_with(&detect::UTF8_BOM) {
+            self.reader.consume(detect::UTF8_BOM.len());
         }
         Ok(())
     }
@@ -1337,9 +2990,43 @@ impl JsonArrayStream {
         }
     }
 
-    fn parse_object(&mut self) -> Result<Map<String, Value>> {
-        let mut de = Deserializer::from_reader(&mut self.reader);
-        Map::<String, Value>::deserialize(&mut de).map_err(JacError::from)
+    /// Parse the next object, returning `None` when it was skipped as oversized.
+    ///
+    /// The object's bytes are bounded while they are read: by `max_record_bytes`,
+    /// or by the block size limit when that is unset, and no string may exceed
+    /// `max_string_len_per_value`. Non-object values are rejected before any of
+    /// them is read.
+    fn parse_object(&mut self) -> Result<Option<Map<String, Value>>> {
+        match self.peek_non_whitespace()? {
+            Some(b'{') => {}
+            Some(_) => return Err(JacError::TypeMismatch),
+            None => return Err(JacError::UnexpectedEof),
+        }
+        let limit = self.ingest.object_limit();
+
+        self.object_buffer.clear();
+        let skip = self.ingest.oversized == OversizedRecordPolicy::Skip;
+        match record_limit::read_object(
+            &mut self.reader,
+            &mut self.object_buffer,
+            limit,
+            self.ingest.max_string_len,
+            skip,
+        )? {
+            BoundedRead::Record => {
+                let mut de = serde_json::Deserializer::from_slice(&self.object_buffer);
+                duplicate_keys::deserialize_record(&mut de, self.ingest.duplicate_keys)
+                    .map(Some)
+                    .map_err(JacError::from)
+            }
+            BoundedRead::Skipped => {
+                self.counters
+                    .oversized_skipped
+                    .fetch_add(1, Ordering::Relaxed);
+                Ok(None)
+            }
+            BoundedRead::Eof => Err(JacError::UnexpectedEof),
+        }
     }
 
     fn next_from_array(&mut self) -> Result<Option<Map<String, Value>>> {
@@ -1359,10 +3046,13 @@ impl JsonArrayStream {
                     return Ok(None);
                 }
 
-                let map = self.parse_object()?;
+                let parsed = self.parse_object()?;
                 self.array_expect_value = false;
                 self.emitted_any = true;
-                return Ok(Some(map));
+                match parsed {
+                    Some(map) => return Ok(Some(map)),
+                    None => continue,
+                }
             } else {
                 match next {
                     b',' => {
@@ -1396,14 +3086,18 @@ impl Iterator for JsonArrayStream {
                     self.finished = true;
                     return None;
                 }
-                Some(match self.parse_object() {
-                    Ok(map) => {
+                match self.parse_object() {
+                    Ok(Some(map)) => {
                         self.consumed_single_object = true;
                         self.finished = true;
-                        Ok(map)
+                        Some(Ok(map))
                     }
-                    Err(err) => Err(err),
-                })
+                    Ok(None) => {
+                        self.finished = true;
+                        return None;
+                    }
+                    Err(err) => Some(Err(err)),
+                }
             }
             JsonArrayMode::Array => match self.next_from_array() {
                 Ok(Some(map)) => Some(Ok(map)),
@@ -1431,6 +3125,12 @@ impl JacReader<File> {
         let file = File::open(path.into())?;
         JacReader::new(file, opts)
     }
+
+    /// Open a file that may still be written to; see [`JacReader::snapshot`].
+    pub fn open_snapshot(path: impl Into<PathBuf>, opts: DecompressOpts) -> Result<Self> {
+        let file = File::open(path.into())?;
+        JacReader::snapshot(file, opts)
+    }
 }
 
 #[cfg(feature = "async")]
@@ -1472,14 +3172,14 @@ mod tests {
     use std::fs;
     use std::io::Cursor;
     use std::path::PathBuf;
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
     use tempfile::tempdir;
 
     #[test]
     fn ndjson_input_streams_records() {
         let data = "{\"a\":1}\n{\"b\":2}\n";
         let mut stream = InputSource::NdjsonReader(Box::new(data.as_bytes()))
-            .into_record_stream(&WrapperConfig::None)
+            .into_record_stream(&WrapperConfig::None, IngestConfig::default())
             .unwrap();
         let first = stream.next().unwrap().unwrap();
         assert_eq!(first.get("a").unwrap(), &Value::from(1));
@@ -1493,7 +3193,7 @@ mod tests {
         let data = r#"[{"a":1},{"b":2}]"#;
         let reader = Cursor::new(data.as_bytes().to_vec());
         let mut stream = InputSource::JsonArrayReader(Box::new(reader))
-            .into_record_stream(&WrapperConfig::None)
+            .into_record_stream(&WrapperConfig::None, IngestConfig::default())
             .unwrap();
         let first = stream.next().unwrap().unwrap();
         assert_eq!(first.get("a").unwrap(), &Value::from(1));
@@ -1503,54 +3203,1432 @@ mod tests {
     }
 
     #[test]
-    fn ndjson_input_handles_bom_and_mixed_newlines() {
-        let data = "\u{feff}{\"a\":1}\r\n\r\n{\"b\":2}\n{\"c\":3}";
-        let reader = Cursor::new(data.as_bytes().to_vec());
-        let mut stream = InputSource::NdjsonReader(Box::new(reader))
-            .into_record_stream(&WrapperConfig::None)
+    fn request_builders_fill_defaults_and_require_endpoints() {
+        let dir = tempdir().unwrap();
+        let jac_path = dir.path().join("built.jac");
+        let out_path = dir.path().join("built.ndjson");
+
+        let request = CompressRequest::builder()
+            .input(InputSource::NdjsonReader(Box::new(
+                "{\"a\":1}\n{\"a\":2}\n".as_bytes(),
+            )))
+            .output(OutputSink::Path(jac_path.clone()))
+            .codec(Codec::None)
+            .block_target_records(1)
+            .container_hint(ContainerFormat::Ndjson)
+            .build()
             .unwrap();
+        assert!(request.emit_index);
+        assert!(matches!(request.wrapper_config, WrapperConfig::None));
+        let summary = execute_compress(request).unwrap();
+        assert_eq!(summary.metrics.blocks_written, 2);
 
-        let first = stream.next().unwrap().unwrap();
-        assert_eq!(first.get("a"), Some(&Value::from(1)));
+        let request = DecompressRequest::builder()
+            .input(JacInput::Path(jac_path.clone()))
+            .output(OutputSink::Path(out_path.clone()))
+            .offset(1)
+            .build()
+            .unwrap();
+        assert!(matches!(request.format, DecompressFormat::Auto));
+        assert!(request.options.verify_checksums);
+        execute_decompress(request).unwrap();
+        assert_eq!(fs::read_to_string(&out_path).unwrap(), "{\"a\":2}\n");
+
+        let request = ProjectRequest::builder()
+            .input(JacInput::Path(jac_path))
+            .output(OutputSink::Path(out_path.clone()))
+            .fields(["a"])
+            .limit(1)
+            .build()
+            .unwrap();
+        assert!(matches!(request.format, ProjectFormat::Ndjson));
+        assert!(!request.include_presence);
+        execute_project(request).unwrap();
+        assert_eq!(fs::read_to_string(&out_path).unwrap(), "{\"a\":1}\n");
+
+        let err = CompressRequest::builder()
+            .output(OutputSink::Writer(Box::new(Vec::new())))
+            .build()
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("requires an input"));
+        let err = DecompressRequest::builder()
+            .input(JacInput::Reader(Box::new(Cursor::new(Vec::new()))))
+            .build()
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("requires an output"));
+        let err = ProjectRequest::builder()
+            .output(OutputSink::Writer(Box::new(Vec::new())))
+            .build()
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("requires an input"));
+    }
 
-        let second = stream.next().unwrap().unwrap();
-        assert_eq!(second.get("b"), Some(&Value::from(2)));
+    #[test]
+    fn compression_profiles_stay_within_default_limits() {
+        let limits = Limits::default();
+        let mut levels = Vec::new();
+        for profile in [
+            CompressionProfile::Fast,
+            CompressionProfile::Balanced,
+            CompressionProfile::MaxCompression,
+        ] {
+            let options = CompressOptions::from_profile(profile);
+            assert!(options.block_target_records <= limits.max_records_per_block);
+            assert!(options.max_dict_entries <= limits.max_dict_entries_per_field);
+            assert!(!options.canonicalize_numbers, "{}", profile.name());
+            levels.push(options.default_codec.level());
+        }
+        assert_eq!(levels, vec![1, 6, 19]);
+        assert!(
+            CompressOptions::from_profile(CompressionProfile::MaxCompression).canonicalize_keys
+        );
 
-        let third = stream.next().unwrap().unwrap();
-        assert_eq!(third.get("c"), Some(&Value::from(3)));
+        let request = CompressRequest::builder()
+            .input(InputSource::Iterator(Box::new(std::iter::empty())))
+            .output(OutputSink::Writer(Box::new(Vec::new())))
+            .profile(CompressionProfile::Fast)
+            .block_target_records(10)
+            .build()
+            .unwrap();
+        assert_eq!(request.options.default_codec, Codec::Zstd(1));
+        assert_eq!(request.options.block_target_records, 10);
+    }
 
-        assert!(stream.next().is_none());
+    #[test]
+    fn zstd_auto_level_records_decision_and_round_trips() {
+        let ndjson: String = (0..300)
+            .map(|i| json!({"id": i, "msg": format!("event {}", i % 7)}).to_string() + "\n")
+            .collect();
+        let dir = tempdir().unwrap();
+        let jac_path = dir.path().join("auto.jac");
+        let request = CompressRequest::builder()
+            .input(InputSource::NdjsonReader(Box::new(Cursor::new(
+                ndjson.into_bytes(),
+            ))))
+            .output(OutputSink::Path(jac_path.clone()))
+            .options(CompressOptions {
+                block_target_records: 100,
+                zstd_auto_level: Some(AutoLevelPolicy {
+                    candidates: vec![1, 19],
+                    sample_records: 250,
+                    ..AutoLevelPolicy::default()
+                }),
+                ..CompressOptions::default()
+            })
+            .build()
+            .unwrap();
+        let summary = execute_compress(request).unwrap();
+        let tuning = summary.level_tuning.expect("tuning recorded");
+        // The sample never spans more than one block.
+        assert_eq!(tuning.sample_records, 100);
+        assert_eq!(tuning.trials.len(), 2);
+        assert_eq!(summary.metrics.records_written, 300);
+
+        let mut reader = JacReader::open(&jac_path, DecompressOpts::default()).unwrap();
+        assert_eq!(
+            reader.file_header().default_compression_level,
+            tuning.chosen_level
+        );
+        let ids: Vec<i64> = reader
+            .record_stream()
+            .unwrap()
+            .map(|record| record.unwrap()["id"].as_i64().unwrap())
+            .collect();
+        assert_eq!(ids, (0..300).collect::<Vec<_>>());
     }
 
     #[test]
-    fn compress_and_decompress_roundtrip_ndjson() {
-        let data = "{\"id\":1}\n{\"id\":2,\"name\":\"alice\"}\n";
-        let paths = TempPaths::new("roundtrip");
+    fn zstd_advanced_codec_round_trips_through_pipeline() {
+        let ndjson: String = (0..200)
+            .map(|i| json!({"id": i, "blob": {"payload": "x".repeat(64)}}).to_string() + "\n")
+            .collect();
+        let dir = tempdir().unwrap();
+        let jac_path = dir.path().join("advanced.jac");
+        let request = CompressRequest::builder()
+            .input(InputSource::NdjsonReader(Box::new(Cursor::new(
+                ndjson.into_bytes(),
+            ))))
+            .output(OutputSink::Path(jac_path.clone()))
+            .options(CompressOptions {
+                block_target_records: 64,
+                default_codec: Codec::ZstdAdvanced {
+                    level: 9,
+                    window_log: Some(20),
+                    long_distance_matching: true,
+                    target_block_size: None,
+                },
+                ..CompressOptions::default()
+            })
+            .build()
+            .unwrap();
+        let summary = execute_compress(request).unwrap();
+        assert_eq!(summary.metrics.records_written, 200);
+
+        let mut reader = JacReader::open(&jac_path, DecompressOpts::default()).unwrap();
+        assert_eq!(reader.file_header().default_compression_level, 9);
+        let ids: Vec<i64> = reader
+            .record_stream()
+            .unwrap()
+            .map(|record| record.unwrap()["id"].as_i64().unwrap())
+            .collect();
+        assert_eq!(ids, (0..200).collect::<Vec<_>>());
+    }
 
-        fs::write(&paths.input_ndjson, data).unwrap();
+    #[test]
+    fn options_deserialize_with_defaults_and_round_trip() {
+        let options: CompressOptions = serde_json::from_value(json!({
+            "block_target_records": 500,
+            "default_codec": {"zstd": 3},
+            "limits": {"max_records_per_block": 1000},
+            "utf8_policy": "lossy",
+            "type_coercion": {"fields": {"id": "to_string"}},
+        }))
+        .unwrap();
+        assert_eq!(options.block_target_records, 500);
+        assert_eq!(options.default_codec, Codec::Zstd(3));
+        assert_eq!(options.limits.max_records_per_block, 1000);
+        assert_eq!(
+            options.limits.max_fields_per_block,
+            Limits::default().max_fields_per_block
+        );
+        assert_eq!(options.utf8_policy, Utf8Policy::Lossy);
+        assert_eq!(
+            options.type_coercion,
+            CoercionPolicy::default().with_rule("id", CoercionRule::ToString)
+        );
+        assert_eq!(options.max_dict_entries, 4_096);
+        assert!(options.nested_opaque);
 
-        let compress_request = CompressRequest {
-            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
-            output: OutputSink::Path(paths.output_jac.clone()),
-            options: CompressOptions::default(),
-            container_hint: None,
-            emit_index: true,
-            wrapper_config: WrapperConfig::None,
-        };
+        let encoded = serde_json::to_value(&options).unwrap();
+        let decoded: CompressOptions = serde_json::from_value(encoded.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), encoded);
 
-        execute_compress(compress_request).unwrap();
+        let wrapper: WrapperConfig =
+            serde_json::from_value(json!({"pointer": {"path": "/data"}})).unwrap();
+        match wrapper {
+            WrapperConfig::Pointer { path, limits } => {
+                assert_eq!(path, "/data");
+                assert_eq!(limits.max_depth, WrapperLimits::default().max_depth);
+            }
+            other => panic!("unexpected wrapper {:?}", other),
+        }
+    }
 
-        let decompress_request = DecompressRequest {
-            input: JacInput::Path(paths.output_jac.clone()),
-            output: OutputSink::Path(paths.output_json.clone()),
-            format: DecompressFormat::Auto,
+    #[test]
+    fn input_streams_apply_duplicate_key_policy() {
+        let ndjson = "{\"a\":1,\"a\":2}\n";
+        let mut stream = InputSource::NdjsonReader(Box::new(ndjson.as_bytes()))
+            .into_record_stream(
+                &WrapperConfig::None,
+                IngestConfig {
+                    duplicate_keys: DuplicateKeyPolicy::FirstWins,
+                    ..IngestConfig::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(stream.next().unwrap().unwrap()["a"], Value::from(1));
+
+        let array = r#"[{"a":{"b":1,"b":2}}]"#;
+        let mut stream = InputSource::JsonArrayReader(Box::new(Cursor::new(array.as_bytes())))
+            .into_record_stream(
+                &WrapperConfig::None,
+                IngestConfig {
+                    duplicate_keys: DuplicateKeyPolicy::Error,
+                    ..IngestConfig::default()
+                },
+            )
+            .unwrap();
+        let err = stream.next().unwrap().unwrap_err();
+        assert!(matches!(err, JacError::Json(_)));
+        assert!(err.to_string().contains("duplicate key 'b'"));
+    }
+
+    #[test]
+    fn input_streams_apply_utf8_policy() {
+        let ndjson: &[u8] = b"{\"a\":\"x\xffy\"}\n";
+        let mut stream = InputSource::NdjsonReader(Box::new(ndjson))
+            .into_record_stream(&WrapperConfig::None, IngestConfig::default())
+            .unwrap();
+        assert!(matches!(stream.next().unwrap(), Err(JacError::Io(_))));
+
+        let mut stream = InputSource::NdjsonReader(Box::new(ndjson))
+            .into_record_stream(
+                &WrapperConfig::None,
+                IngestConfig {
+                    utf8: Utf8Policy::Lossy,
+                    ..IngestConfig::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(stream.next().unwrap().unwrap()["a"], json!("x\u{FFFD}y"));
+        assert_eq!(stream.counters().utf8_metrics().replaced_sequences, 1);
+
+        let array: &[u8] = b"[{\"a\":\"ok\"},{\"a\":\"\xc3(\"}]";
+        let mut stream = InputSource::JsonArrayReader(Box::new(array))
+            .into_record_stream(
+                &WrapperConfig::None,
+                IngestConfig {
+                    utf8: Utf8Policy::Base64,
+                    ..IngestConfig::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(stream.next().unwrap().unwrap()["a"], json!("ok"));
+        assert_eq!(
+            stream.next().unwrap().unwrap()["a"],
+            json!("jac:base64:wyg=")
+        );
+        assert!(stream.next().is_none());
+        assert_eq!(
+            stream.counters().utf8_metrics(),
+            Utf8Metrics {
+                invalid_strings: 1,
+                replaced_sequences: 0,
+                base64_wrapped: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn ndjson_input_handles_bom_and_mixed_newlines() {
+        let data = "\u{feff}{\"a\":1}\r\n\r\n{\"b\":2}\n{\"c\":3}";
+        let reader = Cursor::new(data.as_bytes().to_vec());
+        let mut stream = InputSource::NdjsonReader(Box::new(reader))
+            .into_record_stream(&WrapperConfig::None, IngestConfig::default())
+            .unwrap();
+
+        let first = stream.next().unwrap().unwrap();
+        assert_eq!(first.get("a"), Some(&Value::from(1)));
+
+        let second = stream.next().unwrap().unwrap();
+        assert_eq!(second.get("b"), Some(&Value::from(2)));
+
+        let third = stream.next().unwrap().unwrap();
+        assert_eq!(third.get("c"), Some(&Value::from(3)));
+
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn compress_and_decompress_roundtrip_ndjson() {
+        let data = "{\"id\":1}\n{\"id\":2,\"name\":\"alice\"}\n";
+        let paths = TempPaths::new("roundtrip");
+
+        fs::write(&paths.input_ndjson, data).unwrap();
+
+        let compress_request = CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            options: CompressOptions::default(),
+            container_hint: None,
+            emit_index: true,
+            wrapper_config: WrapperConfig::None,
+            interrupt: None,
+        };
+
+        execute_compress(compress_request).unwrap();
+
+        let decompress_request = DecompressRequest {
+            input: JacInput::Path(paths.output_jac.clone()),
+            output: OutputSink::Path(paths.output_json.clone()),
+            format: DecompressFormat::Auto,
             options: DecompressOptions::default(),
+            offset: None,
+            limit: None,
+            blocks: None,
+            rename: HashMap::new(),
+            line_ending: LineEnding::default(),
+            encoding: TextEncoding::default(),
+            validate_utf8: false,
+            require_pointer: None,
+            filter: None,
+        };
+
+        execute_decompress(decompress_request).unwrap();
+
+        let result = fs::read_to_string(&paths.output_json).unwrap();
+        assert_eq!(normalize_ndjson(&result), normalize_ndjson(data));
+    }
+
+    #[test]
+    fn preserve_key_order_reproduces_input_field_order() {
+        let data = "{\"z\":1,\"a\":2}\n{\"a\":3,\"m\":{\"y\":true,\"b\":null},\"z\":4}\n";
+        let paths = TempPaths::new("key_order");
+
+        fs::write(&paths.input_ndjson, data).unwrap();
+
+        let compress_request = CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            options: CompressOptions {
+                preserve_key_order: true,
+                ..CompressOptions::default()
+            },
+            container_hint: None,
+            emit_index: true,
+            wrapper_config: WrapperConfig::None,
+            interrupt: None,
+        };
+
+        execute_compress(compress_request).unwrap();
+
+        let decompress_request = DecompressRequest {
+            input: JacInput::Path(paths.output_jac.clone()),
+            output: OutputSink::Path(paths.output_json.clone()),
+            format: DecompressFormat::Ndjson,
+            options: DecompressOptions::default(),
+            offset: None,
+            limit: None,
+            blocks: None,
+            rename: HashMap::new(),
+            line_ending: LineEnding::default(),
+            encoding: TextEncoding::default(),
+            validate_utf8: false,
+            require_pointer: None,
+            filter: None,
+        };
+
+        execute_decompress(decompress_request).unwrap();
+
+        let result = fs::read_to_string(&paths.output_json).unwrap();
+        assert_eq!(result, data);
+    }
+
+    #[test]
+    fn fingerprints_verify_and_index_after_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("fingerprint.jac");
+        let ndjson = "{\"id\":1,\"x\":0.0}\n{\"x\":0.0,\"id\":1}\n{\"id\":2,\"tags\":[\"a\"]}\n";
+        execute_compress(
+            CompressRequest::builder()
+                .input(InputSource::NdjsonReader(Box::new(Cursor::new(
+                    ndjson.as_bytes().to_vec(),
+                ))))
+                .output(OutputSink::Path(path.clone()))
+                .options(CompressOptions {
+                    block_target_records: 2,
+                    inject_fields: InjectFields {
+                        row_id: Some("_row_id".to_string()),
+                        fingerprint: Some("_hash".to_string()),
+                        ..InjectFields::default()
+                    },
+                    ..CompressOptions::default()
+                })
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+
+        let mut reader = JacReader::open(&path, DecompressOpts::default()).unwrap();
+        let check = reader.verify_fingerprints("_hash", &["_row_id"]).unwrap();
+        assert!(check.is_ok(), "{:?}", check);
+        assert_eq!(check.records, 3);
+        // Without excluding the row id every record looks modified.
+        let check = reader.verify_fingerprints("_hash", &[]).unwrap();
+        assert_eq!(check.mismatched, vec![0, 1, 2]);
+
+        let index = reader.fingerprint_index("_hash").unwrap();
+        assert_eq!(index.len(), 2);
+        let expected = record_fingerprint(json!({"id": 1, "x": 0.0}).as_object().unwrap(), &[]);
+        assert_eq!(index[&expected], vec![0, 1]);
+    }
+
+    #[test]
+    fn dedupe_drops_repeated_records_before_injection() {
+        let records = || {
+            [1, 2, 1, 3, 2]
+                .into_iter()
+                .map(|n| json!({"n": n}).as_object().unwrap().clone())
+                .collect::<Vec<_>>()
+        };
+        let dir = tempdir().unwrap();
+        for parallel in [false, true] {
+            let path = dir.path().join(format!("dedupe-{}.jac", parallel));
+            let request = CompressRequest {
+                input: InputSource::Iterator(Box::new(records().into_iter())),
+                output: OutputSink::Path(path.clone()),
+                options: CompressOptions {
+                    block_target_records: 2,
+                    dedupe: Some(DedupeConfig::default()),
+                    inject_fields: InjectFields {
+                        row_id: Some("_row_id".to_string()),
+                        ..InjectFields::default()
+                    },
+                    ..CompressOptions::default()
+                },
+                container_hint: Some(ContainerFormat::Ndjson),
+                emit_index: true,
+                wrapper_config: WrapperConfig::None,
+                interrupt: None,
+            };
+            let summary = if parallel {
+                crate::parallel::execute_compress_parallel(request, 2).unwrap()
+            } else {
+                execute_compress_sequential(request).unwrap()
+            };
+            assert_eq!(summary.dedupe_metrics.duplicates_dropped, 2);
+            assert_eq!(summary.metrics.records_written, 3);
+
+            let mut reader = JacReader::open(&path, DecompressOpts::default()).unwrap();
+            let rows: Vec<Value> = reader
+                .record_stream()
+                .unwrap()
+                .map(|r| Value::Object(r.unwrap()))
+                .collect();
+            assert_eq!(
+                rows,
+                vec![
+                    json!({"n": 1, "_row_id": 0}),
+                    json!({"n": 2, "_row_id": 1}),
+                    json!({"n": 3, "_row_id": 2}),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn injected_fields_survive_sequential_and_parallel_pipelines() {
+        let dir = tempdir().unwrap();
+        let options = CompressOptions {
+            block_target_records: 2,
+            default_codec: Codec::None,
+            inject_fields: InjectFields {
+                row_id: Some("_row_id".to_string()),
+                row_id_start: 100,
+                ingest_timestamp: Some("_ingest_ms".to_string()),
+                source_field: Some("_source".to_string()),
+                source_label: "unit-test".to_string(),
+                ..InjectFields::default()
+            },
+            ..CompressOptions::default()
+        };
+        let records = || {
+            (0..5)
+                .map(|i| {
+                    let mut record = Map::new();
+                    record.insert("n".to_string(), Value::from(i));
+                    record
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for parallel in [false, true] {
+            let path = dir.path().join(format!("inject-{}.jac", parallel));
+            let request = CompressRequest {
+                input: InputSource::Iterator(Box::new(records().into_iter())),
+                output: OutputSink::Path(path.clone()),
+                options: options.clone(),
+                container_hint: Some(ContainerFormat::Ndjson),
+                emit_index: true,
+                wrapper_config: WrapperConfig::None,
+                interrupt: None,
+            };
+            if parallel {
+                crate::parallel::execute_compress_parallel(request, 2).unwrap();
+            } else {
+                execute_compress_sequential(request).unwrap();
+            }
+
+            let mut reader = JacReader::open(&path, DecompressOpts::default()).unwrap();
+            let rows: Vec<Map<String, Value>> = reader
+                .record_stream()
+                .unwrap()
+                .map(|r| r.unwrap())
+                .collect();
+            assert_eq!(rows.len(), 5);
+            for (i, row) in rows.iter().enumerate() {
+                assert_eq!(row["n"], Value::from(i));
+                assert_eq!(row["_row_id"], Value::from(100 + i as u64));
+                assert_eq!(row["_source"], Value::from("unit-test"));
+                assert!(row["_ingest_ms"].as_u64().unwrap() > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_input_writes_indexed_zero_block_file_on_both_paths() {
+        let dir = tempdir().unwrap();
+        let request = |path: PathBuf, allow_empty: bool| CompressRequest {
+            input: InputSource::Iterator(Box::new(std::iter::empty())),
+            output: OutputSink::Path(path),
+            options: CompressOptions {
+                allow_empty,
+                ..CompressOptions::default()
+            },
+            container_hint: Some(ContainerFormat::Ndjson),
+            emit_index: true,
+            wrapper_config: WrapperConfig::None,
+            interrupt: None,
         };
 
-        execute_decompress(decompress_request).unwrap();
+        let mut files = Vec::new();
+        for parallel in [false, true] {
+            let path = dir.path().join(format!("empty-{}.jac", parallel));
+            let summary = if parallel {
+                crate::parallel::execute_compress_parallel(request(path.clone(), true), 2).unwrap()
+            } else {
+                execute_compress_sequential(request(path.clone(), true)).unwrap()
+            };
+            assert_eq!(summary.metrics.records_written, 0);
+            assert_eq!(summary.metrics.blocks_written, 0);
+
+            let mut reader = JacReader::open(&path, DecompressOpts::default()).unwrap();
+            assert_eq!(reader.index_status(), IndexStatus::Valid);
+            assert_eq!(reader.block_count().unwrap(), 0);
+            assert_eq!(reader.record_stream().unwrap().count(), 0);
+            files.push(std::fs::read(&path).unwrap());
+
+            let refused = dir.path().join(format!("refused-{}.jac", parallel));
+            let err = if parallel {
+                crate::parallel::execute_compress_parallel(request(refused.clone(), false), 2)
+            } else {
+                execute_compress_sequential(request(refused.clone(), false))
+            }
+            .unwrap_err();
+            assert!(err.to_string().contains("no records"), "{}", err);
+            assert!(!refused.exists());
+        }
+        assert_eq!(files[0], files[1]);
+    }
+
+    #[test]
+    fn block_header_crc_rejects_damaged_directories() {
+        let dir = tempdir().unwrap();
+        let request = |path: PathBuf| CompressRequest {
+            input: InputSource::Iterator(Box::new(
+                (0..60).map(|i| json!({"id": i}).as_object().unwrap().clone()),
+            )),
+            output: OutputSink::Path(path),
+            options: CompressOptions {
+                block_target_records: 20,
+                default_codec: Codec::Zstd(1),
+                block_header_crc: true,
+                ..CompressOptions::default()
+            },
+            container_hint: Some(ContainerFormat::Ndjson),
+            emit_index: false,
+            wrapper_config: WrapperConfig::None,
+            interrupt: None,
+        };
+        let sequential = dir.path().join("sequential.jac");
+        let parallel = dir.path().join("parallel.jac");
+        execute_compress_sequential(request(sequential.clone())).unwrap();
+        crate::parallel::execute_compress_parallel(request(parallel.clone()), 2).unwrap();
+        let mut bytes = fs::read(&sequential).unwrap();
+        assert_eq!(bytes, fs::read(&parallel).unwrap());
+
+        let mut reader =
+            JacReader::new(Cursor::new(bytes.clone()), DecompressOpts::default()).unwrap();
+        assert!(reader.file_header().block_header_crc());
+        let second = reader.blocks().nth(1).unwrap().unwrap().offset as usize;
+        assert_eq!(reader.record_stream().unwrap().count(), 60);
+
+        // Damage the second block's field count.
+        bytes[second + 6] ^= 0x7f;
+        let mut strict =
+            JacReader::new(Cursor::new(bytes.clone()), DecompressOpts::default()).unwrap();
+        let err = strict
+            .record_stream()
+            .unwrap()
+            .find_map(Result::err)
+            .unwrap();
+        assert!(matches!(err, JacError::ChecksumMismatch), "{err:?}");
+
+        let mut permissive =
+            JacReader::with_strict_mode(Cursor::new(bytes), DecompressOpts::default(), false)
+                .unwrap();
+        let ids: Vec<u64> = permissive
+            .record_stream()
+            .unwrap()
+            .map(|record| record.unwrap()["id"].as_u64().unwrap())
+            .collect();
+        assert_eq!(ids, (0..20).chain(40..60).collect::<Vec<_>>());
+        assert_eq!(permissive.warnings().len(), 1);
+    }
+
+    #[test]
+    fn aligned_blocks_start_on_page_boundaries() {
+        let dir = tempdir().unwrap();
+        let record = |i: u64| {
+            json!({"id": i, "name": format!("n{}", i)})
+                .as_object()
+                .unwrap()
+                .clone()
+        };
+        let request = |path: PathBuf, emit_index: bool| CompressRequest {
+            input: InputSource::Iterator(Box::new((0..60).map(record))),
+            output: OutputSink::Path(path),
+            options: CompressOptions {
+                block_target_records: 20,
+                align_blocks: true,
+                intern_field_names: true,
+                ..CompressOptions::default()
+            },
+            container_hint: Some(ContainerFormat::Ndjson),
+            emit_index,
+            wrapper_config: WrapperConfig::None,
+            interrupt: None,
+        };
+        for emit_index in [true, false] {
+            let sequential = dir.path().join(format!("sequential-{emit_index}.jac"));
+            let parallel = dir.path().join(format!("parallel-{emit_index}.jac"));
+            execute_compress_sequential(request(sequential.clone(), emit_index)).unwrap();
+            crate::parallel::execute_compress_parallel(request(parallel.clone(), emit_index), 2)
+                .unwrap();
+            let bytes = fs::read(&sequential).unwrap();
+            assert_eq!(bytes, fs::read(&parallel).unwrap());
+            assert!(bytes[..4096].ends_with(&[0; 64]));
+
+            let mut reader = JacReader::new(Cursor::new(bytes), DecompressOpts::default()).unwrap();
+            assert!(reader.file_header().aligned_blocks());
+            assert_eq!(reader.is_finalized(), emit_index);
+            let offsets: Vec<u64> = reader.blocks().map(|block| block.unwrap().offset).collect();
+            assert_eq!(offsets, [4096, 8192, 12288]);
+            let tail: Vec<Map<String, Value>> = reader
+                .record_stream_from(45)
+                .unwrap()
+                .map(Result::unwrap)
+                .collect();
+            assert_eq!(tail, (45..60).map(record).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn interrupt_finishes_the_output_early() {
+        let dir = tempdir().unwrap();
+        let request = |path: PathBuf| {
+            let flag = Arc::new(AtomicBool::new(false));
+            let raise = Arc::clone(&flag);
+            // The flag goes up as record 29 is read, so 29 records are kept.
+            let records = (0..100u64).map(move |i| {
+                if i == 29 {
+                    raise.store(true, Ordering::Relaxed);
+                }
+                json!({ "id": i }).as_object().unwrap().clone()
+            });
+            CompressRequest::builder()
+                .input(InputSource::Iterator(Box::new(records)))
+                .output(OutputSink::Path(path))
+                .block_target_records(10)
+                .interrupt(flag)
+                .build()
+                .unwrap()
+        };
+
+        let sequential = dir.path().join("sequential.jac");
+        let parallel = dir.path().join("parallel.jac");
+        let summaries = [
+            execute_compress_sequential(request(sequential.clone())).unwrap(),
+            crate::parallel::execute_compress_parallel(request(parallel.clone()), 2).unwrap(),
+        ];
+        for (summary, path) in summaries.iter().zip([&sequential, &parallel]) {
+            assert!(summary.interrupted);
+            assert_eq!(summary.metrics.records_written, 29);
+            let mut reader =
+                JacReader::new(File::open(path).unwrap(), DecompressOpts::default()).unwrap();
+            assert!(reader.is_finalized());
+            assert_eq!(reader.record_stream().unwrap().count(), 29);
+        }
+    }
+
+    #[test]
+    fn temp_dir_defaults_to_the_output_directory() {
+        let options = CompressOptions::default();
+        let sink = OutputSink::Path(PathBuf::from("/data/out/archive.jac"));
+        assert_eq!(sink.temp_dir(&options), Path::new("/data/out"));
+        let sink = OutputSink::Path(PathBuf::from("archive.jac"));
+        assert_eq!(sink.temp_dir(&options), Path::new("."));
+        let sink = OutputSink::Writer(Box::new(Vec::new()));
+        assert_eq!(sink.temp_dir(&options), std::env::temp_dir());
+
+        let options = CompressOptions {
+            temp_dir: Some(PathBuf::from("/scratch")),
+            ..CompressOptions::default()
+        };
+        let sink = OutputSink::Path(PathBuf::from("/data/out/archive.jac"));
+        assert_eq!(sink.temp_dir(&options), Path::new("/scratch"));
+    }
+
+    #[test]
+    fn max_output_bytes_continues_into_part_files() {
+        let dir = tempdir().unwrap();
+        let record = |i: u64| {
+            json!({"id": i, "name": format!("n{}", i)})
+                .as_object()
+                .unwrap()
+                .clone()
+        };
+        let request = |output: OutputSink, max_output_bytes: Option<u64>| CompressRequest {
+            input: InputSource::Iterator(Box::new((0..120).map(record))),
+            output,
+            options: CompressOptions {
+                block_target_records: 20,
+                intern_field_names: true,
+                max_output_bytes,
+                ..CompressOptions::default()
+            },
+            container_hint: Some(ContainerFormat::Ndjson),
+            emit_index: true,
+            wrapper_config: WrapperConfig::None,
+            interrupt: None,
+        };
+        let read_ids = |path: &Path| -> Vec<u64> {
+            let file = File::open(path).unwrap();
+            let mut reader = JacReader::new(file, DecompressOpts::default()).unwrap();
+            reader
+                .record_stream()
+                .unwrap()
+                .map(|record| record.unwrap()["id"].as_u64().unwrap())
+                .collect()
+        };
+
+        let whole = dir.path().join("whole.jac");
+        execute_compress_sequential(request(OutputSink::Path(whole.clone()), None)).unwrap();
+        let mut reader =
+            JacReader::new(File::open(&whole).unwrap(), DecompressOpts::default()).unwrap();
+        let blocks: Vec<u64> = reader.blocks().map(|block| block.unwrap().offset).collect();
+        assert_eq!(blocks.len(), 6);
+        // Room for two blocks and the index, not three
+        let cap = blocks[2] + 64;
+
+        let sequential = dir.path().join("out.jac");
+        let summary =
+            execute_compress_sequential(request(OutputSink::Path(sequential.clone()), Some(cap)))
+                .unwrap();
+        assert_eq!(
+            summary.output_files,
+            [
+                sequential.clone(),
+                dir.path().join("out.part2.jac"),
+                dir.path().join("out.part3.jac"),
+            ]
+        );
+        assert_eq!(summary.metrics.records_written, 120);
+        let mut ids = Vec::new();
+        for path in &summary.output_files {
+            assert!(fs::metadata(path).unwrap().len() <= cap);
+            ids.extend(read_ids(path));
+        }
+        assert_eq!(ids, (0..120).collect::<Vec<_>>());
+
+        let namer: PartNamer =
+            Box::new(|first, part| first.with_file_name(format!("chunk-{part}.jac")));
+        let parallel = dir.path().join("chunk-1.jac");
+        let summary = crate::parallel::execute_compress_parallel(
+            request(OutputSink::Parts(parallel, namer), Some(cap)),
+            2,
+        )
+        .unwrap();
+        assert_eq!(summary.output_files.len(), 3);
+        for (part, path) in summary.output_files.iter().enumerate() {
+            assert_eq!(*path, dir.path().join(format!("chunk-{}.jac", part + 1)));
+            let other = if part == 0 {
+                sequential.clone()
+            } else {
+                default_part_path(&sequential, part as u32 + 1)
+            };
+            assert_eq!(fs::read(path).unwrap(), fs::read(other).unwrap());
+        }
+
+        let err = execute_compress(request(OutputSink::Writer(Box::new(Vec::new())), Some(cap)))
+            .err()
+            .unwrap();
+        assert!(matches!(err, JacError::UnsupportedFeature(_)), "{err:?}");
+    }
+
+    #[test]
+    fn interned_field_names_shrink_wide_files_and_decode_everywhere() {
+        let record = |i: u64| {
+            let mut record: Map<String, Value> = (0..40)
+                .map(|f| (format!("attribute_{:02}", f), Value::from(i % 7)))
+                .collect();
+            if i >= 150 {
+                record.insert("late_field".to_string(), Value::from(i));
+            }
+            record
+        };
+        let dir = tempdir().unwrap();
+        let request = |path: PathBuf, intern_field_names: bool| CompressRequest {
+            input: InputSource::Iterator(Box::new((0..300).map(record))),
+            output: OutputSink::Path(path),
+            options: CompressOptions {
+                block_target_records: 20,
+                default_codec: Codec::Zstd(1),
+                intern_field_names,
+                ..CompressOptions::default()
+            },
+            container_hint: Some(ContainerFormat::Ndjson),
+            emit_index: true,
+            wrapper_config: WrapperConfig::None,
+            interrupt: None,
+        };
+
+        let mut files = Vec::new();
+        for (intern, parallel) in [(false, false), (true, false), (true, true)] {
+            let path = dir.path().join(format!("wide-{}-{}.jac", intern, parallel));
+            if parallel {
+                crate::parallel::execute_compress_parallel(request(path.clone(), intern), 2)
+                    .unwrap();
+            } else {
+                execute_compress_sequential(request(path.clone(), intern)).unwrap();
+            }
+            files.push(fs::read(&path).unwrap());
+
+            let mut reader = JacReader::open(&path, DecompressOpts::default()).unwrap();
+            assert_eq!(reader.file_header().interned_field_names(), intern);
+            let rows: Vec<Map<String, Value>> = reader
+                .record_stream()
+                .unwrap()
+                .map(Result::unwrap)
+                .collect();
+            assert_eq!(rows, (0..300).map(record).collect::<Vec<_>>());
+            // Seeking past indexed blocks still resolves names they defined.
+            let tail: Vec<Map<String, Value>> = reader
+                .record_stream_from(290)
+                .unwrap()
+                .map(Result::unwrap)
+                .collect();
+            assert_eq!(tail, (290..300).map(record).collect::<Vec<_>>());
+        }
+        assert!(files[1].len() < files[0].len());
+        assert_eq!(files[1], files[2]);
+
+        // Blocks copied out of an interned file verify in a plain one.
+        let mut reader =
+            JacReader::new(Cursor::new(files[1].clone()), DecompressOpts::default()).unwrap();
+        let handles: Vec<BlockHandle> = reader.blocks().map(Result::unwrap).collect();
+        let header = build_file_header(&CompressOptions::default(), None).unwrap();
+        let mut writer = JacWriter::new(Vec::new(), header, CompressOpts::default()).unwrap();
+        for handle in &handles {
+            writer
+                .write_raw_block(reader.read_raw_block(handle).unwrap())
+                .unwrap();
+        }
+        let copied = writer.finish_with_index().unwrap().writer;
+        let mut copy = JacReader::new(Cursor::new(copied), DecompressOpts::default()).unwrap();
+        assert_eq!(copy.record_stream().unwrap().count(), 300);
+    }
+
+    #[test]
+    fn interned_index_footer_lets_seeks_skip_earlier_block_headers() {
+        /// Records the lowest position read from.
+        struct ReadLog {
+            inner: Cursor<Vec<u8>>,
+            lowest: std::rc::Rc<std::cell::Cell<u64>>,
+        }
+        impl Read for ReadLog {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.lowest
+                    .set(self.lowest.get().min(self.inner.position()));
+                self.inner.read(buf)
+            }
+        }
+        impl Seek for ReadLog {
+            fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+                self.inner.seek(pos)
+            }
+        }
+
+        // Every block of 20 records introduces one field name.
+        let record = |i: u64| {
+            json!({ format!("f{}", i / 20): i })
+                .as_object()
+                .unwrap()
+                .clone()
+        };
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("interned.jac");
+        execute_compress(
+            CompressRequest::builder()
+                .input(InputSource::Iterator(Box::new((0..100).map(record))))
+                .output(OutputSink::Path(path.clone()))
+                .options(CompressOptions {
+                    block_target_records: 20,
+                    intern_field_names: true,
+                    ..CompressOptions::default()
+                })
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        let pointer_at = bytes.len() - 8;
+        let index_offset = u64::from_le_bytes(bytes[pointer_at..].try_into().unwrap()) as usize;
+        let footer = jac_format::IndexFooter::decode(&bytes[index_offset..pointer_at]).unwrap();
+        assert_eq!(
+            footer
+                .field_names
+                .as_ref()
+                .map(jac_format::FieldNameTable::names),
+            Some(&["f0", "f1", "f2", "f3", "f4"].map(String::from)[..])
+        );
+
+        let lowest = std::rc::Rc::new(std::cell::Cell::new(u64::MAX));
+        let log = ReadLog {
+            inner: Cursor::new(bytes),
+            lowest: lowest.clone(),
+        };
+        let mut reader = JacReader::new(log, DecompressOpts::default()).unwrap();
+        lowest.set(u64::MAX);
+        let tail: Vec<Map<String, Value>> = reader
+            .record_stream_from(80)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(tail, (80..100).map(record).collect::<Vec<_>>());
+        assert_eq!(lowest.get(), footer.blocks[4].block_offset);
+    }
+
+    #[test]
+    fn in_memory_inputs_decode_like_files() {
+        let records: Vec<Map<String, Value>> = (0..90)
+            .map(|i| json!({"id": i, "tag": format!("t{}", i % 4)}))
+            .map(|value| value.as_object().unwrap().clone())
+            .collect();
+        let options = CompressOptions {
+            block_target_records: 40,
+            ..CompressOptions::default()
+        };
+        let bytes = compress_records(records.clone(), options).unwrap();
+        let expected: String = records
+            .iter()
+            .map(|record| Value::from(record.clone()).to_string() + "\n")
+            .collect();
+
+        let inputs = [
+            JacInput::Bytes(bytes::Bytes::from(bytes.clone())),
+            JacInput::Memory(bytes.clone()),
+        ];
+        for input in inputs {
+            let dir = tempdir().unwrap();
+            let out = dir.path().join("out.ndjson");
+            let summary = execute_decompress(
+                DecompressRequest::builder()
+                    .input(input)
+                    .output(OutputSink::Path(out.clone()))
+                    .format(DecompressFormat::Ndjson)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(summary.records_written, 90);
+            assert_eq!(fs::read_to_string(&out).unwrap(), expected);
+        }
+
+        let shared = bytes::Bytes::from(bytes);
+        let mut reader = JacReader::from_bytes(shared.clone(), DecompressOpts::default()).unwrap();
+        let handles: Vec<BlockHandle> = reader.blocks().map(Result::unwrap).collect();
+        assert_eq!(handles.len(), 3);
+        let raw = reader.read_raw_block(&handles[1]).unwrap();
+        assert_eq!(raw.header.record_count, 40);
+        let rows: Vec<Map<String, Value>> = reader
+            .record_stream()
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(rows, records);
+
+        // A truncated buffer fails like a truncated file.
+        let truncated = shared.slice(..handles[2].offset as usize + 10);
+        let mut reader = JacReader::from_bytes(truncated, DecompressOpts::default()).unwrap();
+        assert!(reader.record_stream().unwrap().any(|row| row.is_err()));
+    }
+
+    #[test]
+    fn hot_fields_lead_each_block_payload() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("hot.jac");
+        let record = |i: u64| {
+            json!({"id": i, "msg": format!("message {}", i), "status": i % 3})
+                .as_object()
+                .unwrap()
+                .clone()
+        };
+        let ndjson: String = (0..50)
+            .map(|i| Value::from(record(i)).to_string() + "\n")
+            .collect();
+        execute_compress(
+            CompressRequest::builder()
+                .input(InputSource::NdjsonReader(Box::new(Cursor::new(
+                    ndjson.into_bytes(),
+                ))))
+                .output(OutputSink::Path(path.clone()))
+                .options(CompressOptions {
+                    block_target_records: 20,
+                    hot_fields: vec!["status".to_string(), "msg".to_string()],
+                    intern_field_names: true,
+                    ..CompressOptions::default()
+                })
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+
+        let mut reader = JacReader::open(&path, DecompressOpts::default()).unwrap();
+        let handles: Vec<BlockHandle> = reader.blocks().map(Result::unwrap).collect();
+        assert_eq!(handles.len(), 3);
+        for handle in &handles {
+            let fields = &handle.header.fields;
+            let names: Vec<&str> = fields.iter().map(|f| f.field_name.as_str()).collect();
+            assert_eq!(names, ["id", "msg", "status"]);
+            assert_eq!(fields[2].segment_offset, 0);
+            assert_eq!(fields[1].segment_offset, fields[2].segment_compressed_len);
+        }
+        let rows: Vec<Map<String, Value>> = reader
+            .record_stream()
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(rows, (0..50).map(record).collect::<Vec<_>>());
+
+        // Raw copies keep the layout and still verify.
+        let header = build_file_header(&CompressOptions::default(), None).unwrap();
+        let mut writer = JacWriter::new(Vec::new(), header, CompressOpts::default()).unwrap();
+        for handle in &handles {
+            writer
+                .write_raw_block(reader.read_raw_block(handle).unwrap())
+                .unwrap();
+        }
+        let copied = writer.finish_with_index().unwrap().writer;
+        let mut copy = JacReader::new(Cursor::new(copied), DecompressOpts::default()).unwrap();
+        assert_eq!(copy.record_stream().unwrap().count(), 50);
+    }
+
+    #[test]
+    fn writer_metrics_report_per_field_type_drift() {
+        let dir = tempdir().unwrap();
+        let options = CompressOptions {
+            block_target_records: 2,
+            ..CompressOptions::default()
+        };
+        let ids = [
+            json!(1),
+            json!(2),
+            json!(3),
+            json!("x4"),
+            json!("x5"),
+            Value::Null,
+        ];
+        let records = || {
+            ids.iter()
+                .map(|id| {
+                    let mut record = Map::new();
+                    record.insert("id".to_string(), id.clone());
+                    record.insert("level".to_string(), json!("info"));
+                    record
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for parallel in [false, true] {
+            let request = CompressRequest {
+                input: InputSource::Iterator(Box::new(records().into_iter())),
+                output: OutputSink::Path(dir.path().join(format!("drift-{}.jac", parallel))),
+                options: options.clone(),
+                container_hint: Some(ContainerFormat::Ndjson),
+                emit_index: true,
+                wrapper_config: WrapperConfig::None,
+                interrupt: None,
+            };
+            let summary = if parallel {
+                crate::parallel::execute_compress_parallel(request, 2).unwrap()
+            } else {
+                execute_compress_sequential(request).unwrap()
+            };
+
+            let drift = &summary.metrics.type_drift["id"];
+            assert_eq!(
+                drift.types(),
+                vec![TypeTag::Null, TypeTag::Int, TypeTag::String]
+            );
+            assert_eq!(drift.counts[&TypeTag::Int], 3);
+            assert_eq!(drift.counts[&TypeTag::String], 2);
+            assert_eq!(drift.counts[&TypeTag::Null], 1);
+            // Only the [3, "x4"] block mixes types; ["x5", null] does not.
+            assert_eq!(drift.mixed_blocks, 1);
+            assert!(!summary.metrics.type_drift["level"].is_mixed());
+            let drifting: Vec<&String> = summary
+                .metrics
+                .drifting_fields()
+                .map(|(name, _)| name)
+                .collect();
+            assert_eq!(drifting, vec!["id"]);
+            // "level" repeats within each two-record block; string ids never do.
+            assert_eq!(summary.metrics.dictionary_hits, 3);
+            assert_eq!(summary.metrics.dictionary_misses, 5);
+        }
+    }
+
+    #[test]
+    fn writer_metrics_report_dictionary_spills() {
+        let dir = tempdir().unwrap();
+        let options = CompressOptions {
+            block_target_records: 40,
+            max_dict_entries: 3,
+            ..CompressOptions::default()
+        };
+        // The first block of `user` repeats three names; the second adds new
+        // ones after twenty records.
+        let records = || {
+            (0..80)
+                .map(|i| {
+                    let user = if i < 60 { i % 3 } else { i };
+                    let mut record = Map::new();
+                    record.insert("user".to_string(), json!(format!("u{}", user)));
+                    record.insert("level".to_string(), json!("info"));
+                    record
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for parallel in [false, true] {
+            let output = dir.path().join(format!("spill-{}.jac", parallel));
+            let request = CompressRequest {
+                input: InputSource::Iterator(Box::new(records().into_iter())),
+                output: OutputSink::Path(output.clone()),
+                options: options.clone(),
+                container_hint: Some(ContainerFormat::Ndjson),
+                emit_index: true,
+                wrapper_config: WrapperConfig::None,
+                interrupt: None,
+            };
+            let summary = if parallel {
+                crate::parallel::execute_compress_parallel(request, 2).unwrap()
+            } else {
+                execute_compress_sequential(request).unwrap()
+            };
+
+            assert_eq!(summary.metrics.dictionary_spills, 1);
+            let per_field = &summary.metrics.per_field_metrics;
+            assert_eq!(per_field["user"].dictionary_spills, 1);
+            assert!(per_field
+                .get("level")
+                .map_or(true, |field| field.dictionary_spills == 0));
+
+            let file = File::open(&output).unwrap();
+            let mut reader = JacReader::new(file, DecompressOpts::default()).unwrap();
+            let decoded: Vec<_> = reader
+                .record_stream()
+                .unwrap()
+                .map(Result::unwrap)
+                .collect();
+            assert_eq!(decoded, records());
+        }
+    }
+
+    #[test]
+    fn input_streams_enforce_max_record_bytes() {
+        let skip = IngestConfig {
+            max_record_bytes: Some(12),
+            oversized: OversizedRecordPolicy::Skip,
+            ..IngestConfig::default()
+        };
+        let ndjson = "{\"a\":1}\n{\"a\":\"too long\"}\n{\"a\":3}\n";
+        let stream = InputSource::NdjsonReader(Box::new(ndjson.as_bytes()))
+            .into_record_stream(&WrapperConfig::None, skip)
+            .unwrap();
+        let counters = stream.counters();
+        let values: Vec<Value> = stream.map(|r| r.unwrap()["a"].clone()).collect();
+        assert_eq!(values, vec![json!(1), json!(3)]);
+        assert_eq!(counters.oversized_skipped(), 1);
+
+        let array = r#"[{"a":"too long"}, {"a":2}]"#;
+        let stream = InputSource::JsonArrayReader(Box::new(Cursor::new(array.as_bytes())))
+            .into_record_stream(&WrapperConfig::None, skip)
+            .unwrap();
+        let counters = stream.counters();
+        let values: Vec<Value> = stream.map(|r| r.unwrap()["a"].clone()).collect();
+        assert_eq!(values, vec![json!(2)]);
+        assert_eq!(counters.oversized_skipped(), 1);
+
+        let error = IngestConfig {
+            oversized: OversizedRecordPolicy::Error,
+            ..skip
+        };
+        let mut stream = InputSource::JsonArrayReader(Box::new(Cursor::new(array.as_bytes())))
+            .into_record_stream(&WrapperConfig::None, error)
+            .unwrap();
+        assert!(matches!(
+            stream.next().unwrap(),
+            Err(JacError::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn json_array_input_bounds_elements_while_reading() {
+        let read_first = |array: &str, ingest: IngestConfig| {
+            InputSource::JsonArrayReader(Box::new(Cursor::new(array.to_string().into_bytes())))
+                .into_record_stream(&WrapperConfig::None, ingest)
+                .unwrap()
+                .next()
+                .unwrap()
+        };
+
+        let short_strings = IngestConfig {
+            max_string_len: 8,
+            ..IngestConfig::default()
+        };
+        let err = read_first(r#"[{"a":"far too long"}]"#, short_strings).unwrap_err();
+        assert!(
+            err.to_string().contains("max_string_len_per_value"),
+            "{}",
+            err
+        );
+        assert!(read_first(r#"[{"a":"short"}]"#, short_strings).is_ok());
+
+        // Without max_record_bytes the block size limit bounds each element.
+        let small_blocks = IngestConfig {
+            max_block_bytes: 16,
+            ..IngestConfig::default()
+        };
+        let err = read_first(r#"{"a":[1,2,3,4,5,6,7,8]}"#, small_blocks).unwrap_err();
+        assert!(
+            err.to_string().contains("max_block_uncompressed_total"),
+            "{}",
+            err
+        );
+
+        assert!(matches!(
+            read_first("[[1, 2]]", IngestConfig::default()),
+            Err(JacError::TypeMismatch)
+        ));
+    }
+
+    #[cfg(not(feature = "relaxed-json"))]
+    #[test]
+    fn relaxed_json_input_requires_feature() {
+        let err = InputSource::NdjsonReader(Box::new("{\"a\":1}\n".as_bytes()))
+            .into_record_stream(
+                &WrapperConfig::None,
+                IngestConfig {
+                    relaxed_json: Some(RelaxedJson::default()),
+                    ..IngestConfig::default()
+                },
+            )
+            .err()
+            .unwrap();
+        assert!(matches!(err, JacError::UnsupportedFeature(_)));
+    }
+
+    #[test]
+    fn compress_summary_reports_utf8_repairs() {
+        let paths = TempPaths::new("utf8_lossy");
+        fs::write(&paths.input_ndjson, b"{\"a\":\"\xff\"}\n{\"a\":\"fine\"}\n").unwrap();
+
+        let compress_request = CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            options: CompressOptions {
+                utf8_policy: Utf8Policy::Lossy,
+                ..CompressOptions::default()
+            },
+            container_hint: None,
+            emit_index: true,
+            wrapper_config: WrapperConfig::None,
+            interrupt: None,
+        };
+
+        let summary = execute_compress(compress_request).unwrap();
+        assert_eq!(summary.metrics.records_written, 2);
+        assert_eq!(summary.utf8_metrics.invalid_strings, 1);
+        assert_eq!(summary.utf8_metrics.replaced_sequences, 1);
+    }
+
+    #[test]
+    fn compress_summary_reports_type_coercion() {
+        let paths = TempPaths::new("coercion");
+        fs::write(
+            &paths.input_ndjson,
+            "{\"id\":1,\"ok\":\"true\"}\n{\"id\":\"2\",\"ok\":false}\n{\"id\":3}\n",
+        )
+        .unwrap();
+
+        let compress_request = CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            options: CompressOptions {
+                type_coercion: CoercionPolicy::default()
+                    .with_rule("id", CoercionRule::ToString)
+                    .with_rule("ok", CoercionRule::StringToBool),
+                ..CompressOptions::default()
+            },
+            container_hint: None,
+            emit_index: true,
+            wrapper_config: WrapperConfig::None,
+            interrupt: None,
+        };
+
+        let summary = execute_compress(compress_request).unwrap();
+        let id = summary.coercion_metrics.fields["id"];
+        assert_eq!(id.values_coerced, 2);
+        assert_eq!(id.drift_events, 2);
+        let ok = summary.coercion_metrics.fields["ok"];
+        assert_eq!(ok.values_coerced, 1);
+        assert_eq!(ok.drift_events, 1);
+
+        let mut reader = JacReader::open(&paths.output_jac, DecompressOpts::default()).unwrap();
+        let ids: Vec<Value> = reader
+            .record_stream()
+            .unwrap()
+            .map(|r| r.unwrap()["id"].clone())
+            .collect();
+        assert_eq!(ids, vec![json!("1"), json!("2"), json!("3")]);
+    }
+
+    #[test]
+    fn key_normalization_merges_producer_spellings_before_coercion() {
+        let data = concat!(
+            "{\"userId\":1,\"ts\":10}\n",
+            "{\"userid\":2,\"ts\":11}\n",
+            "{\"USERID\":3,\"ts\":12}\n",
+        );
+        let paths = TempPaths::new("key-normalization");
+        fs::write(&paths.input_ndjson, data).unwrap();
+
+        let request = CompressRequest::builder()
+            .input(InputSource::NdjsonPath(paths.input_ndjson.clone()))
+            .output(OutputSink::Path(paths.output_jac.clone()))
+            .options(CompressOptions {
+                key_normalization: KeyNormalization {
+                    case_fold: true,
+                    ..KeyNormalization::default()
+                },
+                type_coercion: CoercionPolicy::default()
+                    .with_rule("userid", CoercionRule::ToString),
+                ..CompressOptions::default()
+            })
+            .build()
+            .unwrap();
+        let summary = execute_compress(request).unwrap();
+        let metrics = &summary.key_normalization_metrics;
+        assert_eq!(
+            (
+                metrics.keys_renamed,
+                metrics.keys_dropped,
+                metrics.collisions
+            ),
+            (2, 0, 0)
+        );
+        assert_eq!(summary.coercion_metrics.fields["userid"].values_coerced, 3);
 
-        let result = fs::read_to_string(&paths.output_json).unwrap();
-        assert_eq!(normalize_ndjson(&result), normalize_ndjson(data));
+        let mut reader = JacReader::open(&paths.output_jac, DecompressOpts::default()).unwrap();
+        let rows: Vec<Value> = reader
+            .record_stream()
+            .unwrap()
+            .map(|r| Value::Object(r.unwrap()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                json!({"userid": "1", "ts": 10}),
+                json!({"userid": "2", "ts": 11}),
+                json!({"userid": "3", "ts": 12}),
+            ]
+        );
     }
 
     #[test]
@@ -1567,6 +4645,7 @@ mod tests {
             container_hint: None,
             emit_index: true,
             wrapper_config: WrapperConfig::None,
+            interrupt: None,
         };
         execute_compress(compress_request).unwrap();
 
@@ -1579,6 +4658,10 @@ mod tests {
             fields: vec!["user".to_string()],
             format: ProjectFormat::JsonArray,
             options: DecompressOptions::default(),
+            include_presence: false,
+            offset: None,
+            limit: None,
+            rename: HashMap::new(),
         };
         execute_project(project_request_json).unwrap();
 
@@ -1588,6 +4671,10 @@ mod tests {
             fields: vec!["user".to_string(), "visits".to_string()],
             format: ProjectFormat::Csv { headers: true },
             options: DecompressOptions::default(),
+            include_presence: false,
+            offset: None,
+            limit: None,
+            rename: HashMap::new(),
         };
         execute_project(project_request_csv).unwrap();
 
@@ -1605,6 +4692,901 @@ mod tests {
         let _ = fs::remove_file(&projection_csv);
     }
 
+    #[test]
+    fn decompress_and_project_page_with_offset_and_limit() {
+        let paths = TempPaths::new("paging");
+        let data: Vec<String> = (0..10).map(|i| format!("{{\"n\":{}}}", i)).collect();
+        fs::write(&paths.input_ndjson, data.join("\n")).unwrap();
+        execute_compress(CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            options: CompressOptions {
+                block_target_records: 3,
+                ..CompressOptions::default()
+            },
+            ..CompressRequest::default()
+        })
+        .unwrap();
+
+        let read_n = |path: &PathBuf| -> Vec<u64> {
+            fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .map(|line| {
+                    serde_json::from_str::<Value>(line).unwrap()["n"]
+                        .as_u64()
+                        .unwrap()
+                })
+                .collect()
+        };
+
+        let summary = execute_decompress(DecompressRequest {
+            input: JacInput::Path(paths.output_jac.clone()),
+            output: OutputSink::Path(paths.output_json.clone()),
+            format: DecompressFormat::Ndjson,
+            options: DecompressOptions::default(),
+            offset: Some(4),
+            limit: Some(3),
+            blocks: None,
+            rename: HashMap::new(),
+            line_ending: LineEnding::default(),
+            encoding: TextEncoding::default(),
+            validate_utf8: false,
+            require_pointer: None,
+            filter: None,
+        })
+        .unwrap();
+        assert_eq!(read_n(&paths.output_json), vec![4, 5, 6]);
+        assert_eq!(summary.records_written, 3);
+        // Block [0..3) is skipped undecoded; [3..6) and [6..9) are decoded.
+        assert_eq!(summary.blocks_processed, 2);
+
+        let projection = paths.output_json.with_extension("page.ndjson");
+        let summary = execute_project(ProjectRequest {
+            input: JacInput::Path(paths.output_jac.clone()),
+            output: OutputSink::Path(projection.clone()),
+            fields: vec!["n".to_string()],
+            format: ProjectFormat::Ndjson,
+            options: DecompressOptions::default(),
+            include_presence: false,
+            offset: Some(8),
+            limit: None,
+            rename: HashMap::new(),
+        })
+        .unwrap();
+        assert_eq!(read_n(&projection), vec![8, 9]);
+        assert_eq!(summary.rows_written, 2);
+
+        execute_decompress(DecompressRequest {
+            input: JacInput::Path(paths.output_jac.clone()),
+            output: OutputSink::Path(paths.output_json.clone()),
+            format: DecompressFormat::Ndjson,
+            options: DecompressOptions::default(),
+            offset: Some(20),
+            limit: None,
+            blocks: None,
+            rename: HashMap::new(),
+            line_ending: LineEnding::default(),
+            encoding: TextEncoding::default(),
+            validate_utf8: false,
+            require_pointer: None,
+            filter: None,
+        })
+        .unwrap();
+        assert!(read_n(&paths.output_json).is_empty());
+
+        // Blocks 2-3 hold records 3..9; offset and limit count from record 3.
+        let summary = execute_decompress(
+            DecompressRequest::builder()
+                .input(JacInput::Path(paths.output_jac.clone()))
+                .output(OutputSink::Path(paths.output_json.clone()))
+                .format(DecompressFormat::Ndjson)
+                .blocks("2-3".parse().unwrap())
+                .offset(1)
+                .limit(10)
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(read_n(&paths.output_json), vec![4, 5, 6, 7, 8]);
+        assert_eq!(summary.blocks_processed, 2);
+
+        let past_end = DecompressRequest::builder()
+            .input(JacInput::Path(paths.output_jac.clone()))
+            .output(OutputSink::Path(paths.output_json.clone()))
+            .blocks(BlockRange::Single(4))
+            .build()
+            .unwrap();
+        assert!(execute_decompress(past_end).is_err());
+        let _ = fs::remove_file(&projection);
+    }
+
+    #[test]
+    fn raw_json_passthrough_matches_parsed_output() {
+        let paths = TempPaths::new("passthrough");
+        let data: Vec<String> = (0..10)
+            .map(|i| {
+                format!(
+                    "{{\"z\":{i},\"doc\":{{\"tags\":[\"a\",{{\"k\":{i}.5}}],\"note\":\"line\\n{i}\"}},\"price\":1.{i}5}}"
+                )
+            })
+            .collect();
+        fs::write(&paths.input_ndjson, data.join("\n")).unwrap();
+        execute_compress(CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            options: CompressOptions {
+                block_target_records: 4,
+                nested_opaque: true,
+                preserve_key_order: true,
+                ..CompressOptions::default()
+            },
+            ..CompressRequest::default()
+        })
+        .unwrap();
+
+        let unpack = |format: DecompressFormat, raw_json_passthrough: bool| {
+            let summary = execute_decompress(
+                DecompressRequest::builder()
+                    .input(JacInput::Path(paths.output_jac.clone()))
+                    .output(OutputSink::Path(paths.output_json.clone()))
+                    .format(format)
+                    .options(DecompressOptions {
+                        raw_json_passthrough,
+                        ..DecompressOptions::default()
+                    })
+                    .offset(3)
+                    .limit(6)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+            (fs::read_to_string(&paths.output_json).unwrap(), summary)
+        };
+        for format in [DecompressFormat::Ndjson, DecompressFormat::JsonArray] {
+            let (raw, raw_summary) = unpack(format, true);
+            let (parsed, parsed_summary) = unpack(format, false);
+            assert_eq!(raw, parsed);
+            assert_eq!(raw_summary.records_written, 6);
+            assert_eq!(
+                raw_summary.blocks_processed,
+                parsed_summary.blocks_processed
+            );
+        }
+        let (ndjson, _) = unpack(DecompressFormat::Ndjson, true);
+        assert_eq!(ndjson.lines().collect::<Vec<_>>(), data[3..9]);
+    }
+
+    #[test]
+    fn canonical_json_output_ignores_how_records_were_packed() {
+        let paths = TempPaths::new("canonical");
+        let unpack = |input: &str, options: CompressOptions, format: DecompressFormat| {
+            fs::write(&paths.input_ndjson, input).unwrap();
+            execute_compress(CompressRequest {
+                input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+                output: OutputSink::Path(paths.output_jac.clone()),
+                options,
+                ..CompressRequest::default()
+            })
+            .unwrap();
+            execute_decompress(
+                DecompressRequest::builder()
+                    .input(JacInput::Path(paths.output_jac.clone()))
+                    .output(OutputSink::Path(paths.output_json.clone()))
+                    .format(format)
+                    .options(DecompressOptions {
+                        canonical_json: true,
+                        ..DecompressOptions::default()
+                    })
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+            fs::read_to_string(&paths.output_json).unwrap()
+        };
+
+        let first = unpack(
+            "{\"b\":1.0,\"a\":{\"y\":[2.50,{\"d\":1,\"c\":2}],\"x\":\"é\"}}\n{\"z\":null,\"k\":-3}\n",
+            CompressOptions {
+                preserve_key_order: true,
+                ..CompressOptions::default()
+            },
+            DecompressFormat::Ndjson,
+        );
+        let second = unpack(
+            "{\"a\":{\"x\":\"é\",\"y\":[2.5,{\"c\":2,\"d\":1}]},\"b\":1}\n{\"k\":-3.0,\"z\":null}\n",
+            CompressOptions {
+                canonicalize_keys: true,
+                canonicalize_numbers: true,
+                ..CompressOptions::default()
+            },
+            DecompressFormat::Ndjson,
+        );
+        assert_eq!(first, second);
+        assert_eq!(
+            first,
+            "{\"a\":{\"x\":\"é\",\"y\":[2.5,{\"c\":2,\"d\":1}]},\"b\":1}\n{\"k\":-3,\"z\":null}\n"
+        );
+
+        let array = unpack(
+            "{\"b\":2,\"a\":1}\n{\"d\":4,\"c\":3}\n",
+            CompressOptions::default(),
+            DecompressFormat::JsonArray,
+        );
+        assert_eq!(array, r#"[{"a":1,"b":2},{"c":3,"d":4}]"#);
+    }
+
+    #[test]
+    fn decompress_line_ending_and_encoding_apply_to_json_output() {
+        let paths = TempPaths::new("text-output");
+        fs::write(
+            &paths.input_ndjson,
+            "{\"name\":\"Zoë\",\"doc\":{\"emoji\":\"🙂\"}}\n{\"name\":\"Ann\",\"doc\":{}}\n",
+        )
+        .unwrap();
+        execute_compress(CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            ..CompressRequest::default()
+        })
+        .unwrap();
+
+        let unpack = |format, line_ending, encoding, raw_json_passthrough| {
+            execute_decompress(
+                DecompressRequest::builder()
+                    .input(JacInput::Path(paths.output_jac.clone()))
+                    .output(OutputSink::Path(paths.output_json.clone()))
+                    .format(format)
+                    .options(DecompressOptions {
+                        raw_json_passthrough,
+                        ..DecompressOptions::default()
+                    })
+                    .line_ending(line_ending)
+                    .encoding(encoding)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+            fs::read(&paths.output_json).unwrap()
+        };
+
+        for passthrough in [true, false] {
+            let crlf = unpack(
+                DecompressFormat::Ndjson,
+                LineEnding::CrLf,
+                TextEncoding::Utf8Bom,
+                passthrough,
+            );
+            let expected = "\u{feff}{\"name\":\"Zoë\",\"doc\":{\"emoji\":\"🙂\"}}\r\n{\"name\":\"Ann\",\"doc\":{}}\r\n";
+            assert_eq!(String::from_utf8(crlf).unwrap(), expected);
+
+            let ascii = unpack(
+                DecompressFormat::JsonArray,
+                LineEnding::CrLf,
+                TextEncoding::Ascii,
+                passthrough,
+            );
+            assert_eq!(
+                String::from_utf8(ascii).unwrap(),
+                r#"[{"name":"Zo\u00eb","doc":{"emoji":"\ud83d\ude42"}},{"name":"Ann","doc":{}}]"#
+            );
+        }
+    }
+
+    #[test]
+    fn require_pointer_keeps_records_where_it_resolves() {
+        let paths = TempPaths::new("require-pointer");
+        let data: Vec<String> = (0..12)
+            .map(|i| match i % 3 {
+                0 => format!("{{\"id\":{i},\"error\":{{\"code\":{i}}}}}"),
+                1 => format!("{{\"id\":{i},\"error\":\"timeout\"}}"),
+                _ => format!("{{\"id\":{i}}}"),
+            })
+            .collect();
+        fs::write(&paths.input_ndjson, data.join("\n")).unwrap();
+        execute_compress(CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            options: CompressOptions {
+                block_target_records: 4,
+                ..CompressOptions::default()
+            },
+            ..CompressRequest::default()
+        })
+        .unwrap();
+
+        let unpack = |pointer: &str, offset: u64, limit: u64, raw_json_passthrough: bool| {
+            let summary = execute_decompress(
+                DecompressRequest::builder()
+                    .input(JacInput::Path(paths.output_jac.clone()))
+                    .output(OutputSink::Path(paths.output_json.clone()))
+                    .format(DecompressFormat::Ndjson)
+                    .options(DecompressOptions {
+                        raw_json_passthrough,
+                        ..DecompressOptions::default()
+                    })
+                    .require_pointer(pointer)
+                    .offset(offset)
+                    .limit(limit)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+            let ids: Vec<u64> = fs::read_to_string(&paths.output_json)
+                .unwrap()
+                .lines()
+                .map(|line| {
+                    serde_json::from_str::<Value>(line).unwrap()["id"]
+                        .as_u64()
+                        .unwrap()
+                })
+                .collect();
+            assert_eq!(summary.records_written, ids.len() as u64);
+            ids
+        };
+
+        for passthrough in [true, false] {
+            assert_eq!(unpack("/error/code", 0, 100, passthrough), [0, 3, 6, 9]);
+            assert_eq!(unpack("/error", 2, 100, passthrough), [3, 4, 6, 7, 9, 10]);
+            assert_eq!(unpack("/error", 2, 3, passthrough), [3, 4, 6]);
+            assert!(unpack("/missing", 0, 100, passthrough).is_empty());
+        }
+
+        let invalid = DecompressRequest::builder()
+            .input(JacInput::Path(paths.output_jac.clone()))
+            .output(OutputSink::Path(paths.output_json.clone()))
+            .require_pointer("error/~2")
+            .build()
+            .unwrap();
+        assert!(matches!(
+            execute_decompress(invalid),
+            Err(JacError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn filter_selects_records_and_skips_blocks_it_rules_out() {
+        let paths = TempPaths::new("filter");
+        let data: Vec<String> = (0..12)
+            .map(|i| {
+                let level = if i / 4 == 1 && i % 2 == 1 {
+                    "ERROR"
+                } else {
+                    "INFO"
+                };
+                format!("{{\"id\":{i},\"level\":\"{level}\",\"code\":{}}}", i * 100)
+            })
+            .collect();
+        fs::write(&paths.input_ndjson, data.join("\n")).unwrap();
+        execute_compress(CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            options: CompressOptions {
+                block_target_records: 4,
+                ..CompressOptions::default()
+            },
+            ..CompressRequest::default()
+        })
+        .unwrap();
+
+        let unpack = |filter: &str, blocks: Option<BlockRange>, raw_json_passthrough: bool| {
+            let mut request = DecompressRequest::builder()
+                .input(JacInput::Path(paths.output_jac.clone()))
+                .output(OutputSink::Path(paths.output_json.clone()))
+                .format(DecompressFormat::Ndjson)
+                .options(DecompressOptions {
+                    raw_json_passthrough,
+                    ..DecompressOptions::default()
+                })
+                .filter(Predicate::parse(filter).unwrap());
+            if let Some(blocks) = blocks {
+                request = request.blocks(blocks);
+            }
+            let summary = execute_decompress(request.build().unwrap()).unwrap();
+            let ids: Vec<u64> = fs::read_to_string(&paths.output_json)
+                .unwrap()
+                .lines()
+                .map(|line| {
+                    serde_json::from_str::<Value>(line).unwrap()["id"]
+                        .as_u64()
+                        .unwrap()
+                })
+                .collect();
+            assert_eq!(summary.records_written, ids.len() as u64);
+            (ids, summary.metrics.blocks_decoded)
+        };
+
+        for passthrough in [true, false] {
+            assert_eq!(
+                unpack("level = 'ERROR'", None, passthrough),
+                (vec![5, 7], 1)
+            );
+            assert_eq!(
+                unpack("code >= 900 or level in ('ERROR')", None, passthrough).0,
+                [5, 7, 9, 10, 11]
+            );
+            assert_eq!(
+                unpack(
+                    "not level = 'ERROR'",
+                    Some(BlockRange::Single(1)),
+                    passthrough
+                )
+                .0,
+                [4, 6]
+            );
+            assert_eq!(
+                unpack("level starts with 'W'", None, passthrough),
+                (vec![], 0)
+            );
+        }
+
+        // Only the middle block holds an ERROR; its two dictionary entries
+        // are tested once each rather than once per record.
+        let summary = execute_decompress(
+            DecompressRequest::builder()
+                .input(JacInput::Path(paths.output_jac.clone()))
+                .output(OutputSink::Path(paths.output_json.clone()))
+                .filter(Predicate::parse("level = 'ERROR'").unwrap())
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            summary.filter,
+            Some(FilterSummary {
+                blocks_scanned: 1,
+                blocks_pruned: 2,
+                records_matched: 2,
+                dictionary_entries_tested: 2,
+                dictionary_entries_matched: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn field_stats_count_nulls_and_distinct_values_without_decoding() {
+        let paths = TempPaths::new("field_stats");
+        let data: Vec<String> = (0..12)
+            .map(|i| {
+                let note = if i >= 8 { "\"late\"" } else { "null" };
+                format!("{{\"id\":{i},\"level\":\"INFO\",\"note\":{note}}}")
+            })
+            .collect();
+        fs::write(&paths.input_ndjson, data.join("\n") + "\n").unwrap();
+        let pack = |field_stats: bool| {
+            execute_compress(CompressRequest {
+                input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+                output: OutputSink::Path(paths.output_jac.clone()),
+                options: CompressOptions {
+                    block_target_records: 4,
+                    field_stats,
+                    ..CompressOptions::default()
+                },
+                ..CompressRequest::default()
+            })
+            .unwrap();
+            JacReader::open(&paths.output_jac, DecompressOpts::default()).unwrap()
+        };
+
+        let mut reader = pack(true);
+        let ids = reader.field_stats("id").unwrap();
+        assert!(ids.complete());
+        assert_eq!((ids.blocks, ids.present, ids.nulls), (3, 12, 0));
+        assert_eq!(ids.approx_distinct(), Some(12));
+        let notes = reader.field_stats("note").unwrap();
+        assert_eq!((notes.present, notes.nulls), (12, 8));
+        assert_eq!(notes.approx_distinct(), Some(1));
+
+        // Blocks whose notes are all null are ruled out from their stats
+        let filter = Predicate::parse("note is not null").unwrap();
+        let blocks: Vec<BlockHandle> = reader.blocks().collect::<Result<_>>().unwrap();
+        let may_match: Vec<bool> = blocks
+            .iter()
+            .map(|block| reader.block_may_match(block, &filter).unwrap())
+            .collect();
+        assert_eq!(may_match, [false, false, true]);
+
+        execute_decompress(
+            DecompressRequest::builder()
+                .input(JacInput::Path(paths.output_jac.clone()))
+                .output(OutputSink::Path(paths.output_json.clone()))
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&paths.output_json).unwrap(),
+            fs::read_to_string(&paths.input_ndjson).unwrap()
+        );
+
+        let without = pack(false).field_stats("id").unwrap();
+        assert!(!without.complete());
+        assert_eq!((without.blocks, without.blocks_with_stats), (3, 0));
+        assert_eq!(without.approx_distinct(), None);
+    }
+
+    #[test]
+    fn quantile_sketches_give_percentiles_and_prune_numeric_filters() {
+        let paths = TempPaths::new("quantiles");
+        let data: Vec<String> = (0..300)
+            .map(|i| format!("{{\"latency\":{},\"host\":\"a\"}}", i + 1))
+            .collect();
+        fs::write(&paths.input_ndjson, data.join("\n") + "\n").unwrap();
+        execute_compress(CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            options: CompressOptions {
+                block_target_records: 100,
+                quantile_sketches: true,
+                ..CompressOptions::default()
+            },
+            ..CompressRequest::default()
+        })
+        .unwrap();
+
+        let mut reader = JacReader::open(&paths.output_jac, DecompressOpts::default()).unwrap();
+        let latency = reader.field_stats("latency").unwrap();
+        assert_eq!(
+            (latency.quantiles.len(), latency.numeric_values()),
+            (3, 300)
+        );
+        assert_eq!(latency.quantile(0.0), Some(1.0));
+        assert_eq!(latency.quantile(1.0), Some(300.0));
+        let p90 = latency.quantile(0.9).unwrap();
+        assert!((p90 - 270.0).abs() < 300.0 / 16.0, "p90 {}", p90);
+        assert!(reader.field_stats("host").unwrap().quantiles.is_empty());
+
+        let blocks: Vec<BlockHandle> = reader.blocks().collect::<Result<_>>().unwrap();
+        let mut may_match = |text: &str| -> Vec<bool> {
+            let filter = Predicate::parse(text).unwrap();
+            blocks
+                .iter()
+                .map(|block| reader.block_may_match(block, &filter).unwrap())
+                .collect()
+        };
+        assert_eq!(may_match("latency > 250"), [false, false, true]);
+        assert_eq!(may_match("latency = 150"), [false, true, false]);
+        assert_eq!(may_match("latency <= 100"), [true, false, false]);
+        assert_eq!(may_match("latency != 150"), [true, true, true]);
+    }
+
+    #[test]
+    fn directory_records_describe_every_field_of_every_block() {
+        let paths = TempPaths::new("directory_records");
+        let data: Vec<String> = (0..6)
+            .map(|i| format!("{{\"id\":{i},\"level\":\"INFO\"}}"))
+            .collect();
+        fs::write(&paths.input_ndjson, data.join("\n") + "\n").unwrap();
+        execute_compress(CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            options: CompressOptions {
+                block_target_records: 4,
+                field_stats: true,
+                quantile_sketches: true,
+                preserve_key_order: true,
+                ..CompressOptions::default()
+            },
+            ..CompressRequest::default()
+        })
+        .unwrap();
+
+        let mut reader = JacReader::open(&paths.output_jac, DecompressOpts::default()).unwrap();
+        let records = reader.directory_records().unwrap();
+        let keys: Vec<(u64, &str)> = records
+            .iter()
+            .map(|r| (r["block"].as_u64().unwrap(), r["field"].as_str().unwrap()))
+            .collect();
+        assert_eq!(keys, [(1, "id"), (1, "level"), (2, "id"), (2, "level")]);
+        let ids = &records[2];
+        assert_eq!(ids["block_records"], json!(2));
+        assert_eq!(ids["present"], json!(2));
+        assert_eq!(ids["nulls"], json!(0));
+        assert_eq!(ids["approx_distinct"], json!(2));
+        assert_eq!((&ids["min"], &ids["max"]), (&json!(4.0), &json!(5.0)));
+        let levels = &records[1];
+        assert_eq!(levels["dictionary"], json!(true));
+        assert_eq!(levels["dict_entries"], json!(1));
+        assert!(levels.get("min").is_none());
+
+        // The reader is rewound for further reads
+        assert_eq!(reader.record_stream().unwrap().count(), 6);
+    }
+
+    #[test]
+    fn rename_map_unifies_field_generations() {
+        let paths = TempPaths::new("rename");
+        let data =
+            "{\"uid\":1,\"msg\":\"a\"}\n{\"user_id\":2,\"msg\":\"b\"}\n{\"uid\":9,\"user_id\":3}\n";
+        fs::write(&paths.input_ndjson, data).unwrap();
+        execute_compress(CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            ..CompressRequest::default()
+        })
+        .unwrap();
+        let rename: HashMap<String, String> = [("uid".to_string(), "user_id".to_string())]
+            .into_iter()
+            .collect();
+
+        execute_decompress(DecompressRequest {
+            input: JacInput::Path(paths.output_jac.clone()),
+            output: OutputSink::Path(paths.output_json.clone()),
+            format: DecompressFormat::Ndjson,
+            options: DecompressOptions::default(),
+            offset: None,
+            limit: None,
+            blocks: None,
+            rename: rename.clone(),
+            line_ending: LineEnding::default(),
+            encoding: TextEncoding::default(),
+            validate_utf8: false,
+            require_pointer: None,
+            filter: None,
+        })
+        .unwrap();
+        let rows: Vec<Value> = fs::read_to_string(&paths.output_json)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                json!({"user_id": 1, "msg": "a"}),
+                json!({"user_id": 2, "msg": "b"}),
+                // A value already stored under the new name wins over the renamed one.
+                json!({"user_id": 3}),
+            ]
+        );
+
+        let row = json!({"a": 1, "uid": 2, "z": 3});
+        let renamed = rename_fields(row.as_object().unwrap().clone(), &rename);
+        assert_eq!(
+            renamed.keys().collect::<Vec<_>>(),
+            vec!["a", "user_id", "z"]
+        );
+
+        let projection = paths.output_json.with_extension("rename.csv");
+        execute_project(ProjectRequest {
+            input: JacInput::Path(paths.output_jac.clone()),
+            output: OutputSink::Path(projection.clone()),
+            fields: vec!["user_id".to_string(), "msg".to_string()],
+            format: ProjectFormat::Csv { headers: true },
+            options: DecompressOptions::default(),
+            include_presence: false,
+            offset: None,
+            limit: None,
+            rename,
+        })
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&projection).unwrap(),
+            "user_id,msg\n1,a\n2,b\n3,\n"
+        );
+        let _ = fs::remove_file(&projection);
+    }
+
+    #[test]
+    fn decompress_with_delivers_block_batches_to_callback() {
+        let dir = tempdir().unwrap();
+        let jac_path = dir.path().join("batches.jac");
+        let ndjson: String = (0..50)
+            .map(|i| json!({"uid": i}).to_string() + "\n")
+            .collect();
+        execute_compress(
+            CompressRequest::builder()
+                .input(InputSource::NdjsonReader(Box::new(Cursor::new(
+                    ndjson.into_bytes(),
+                ))))
+                .output(OutputSink::Path(jac_path.clone()))
+                .block_target_records(20)
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+        let request = |offset: u64, limit: Option<u64>| {
+            let mut builder = DecompressRequest::builder()
+                .input(JacInput::Path(jac_path.clone()))
+                .output(OutputSink::Writer(Box::new(io::sink())))
+                .offset(offset)
+                .rename([("uid".to_string(), "id".to_string())].into());
+            if let Some(limit) = limit {
+                builder = builder.limit(limit);
+            }
+            builder.build().unwrap()
+        };
+
+        let mut batches: Vec<Vec<u64>> = Vec::new();
+        let summary = execute_decompress_with(request(5, Some(30)), |batch| {
+            batches.push(batch.iter().map(|r| r["id"].as_u64().unwrap()).collect());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(
+            batches,
+            vec![(5..20).collect::<Vec<_>>(), (20..35).collect()]
+        );
+        assert_eq!(summary.records_written, 30);
+        assert_eq!(summary.blocks_processed, 2);
+
+        let mut sizes = Vec::new();
+        execute_decompress_with(request(0, None), |batch| {
+            sizes.push(batch.len());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(sizes, vec![20, 20, 10]);
+
+        // A failing callback stops decoding.
+        let mut calls = 0;
+        let err = execute_decompress_with(request(0, None), |_| {
+            calls += 1;
+            Err(JacError::Internal("sink unavailable".to_string()))
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("sink unavailable"));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn read_summaries_report_decode_metrics() {
+        let dir = tempdir().unwrap();
+        let jac_path = dir.path().join("metrics.jac");
+        let ndjson: String = (0..50)
+            .map(|i| json!({"id": i, "tag": format!("t{}", i % 3)}).to_string() + "\n")
+            .collect();
+        execute_compress(
+            CompressRequest::builder()
+                .input(InputSource::NdjsonReader(Box::new(Cursor::new(
+                    ndjson.into_bytes(),
+                ))))
+                .output(OutputSink::Path(jac_path.clone()))
+                .block_target_records(20)
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+        let mut reader = JacReader::new(
+            fs::File::open(&jac_path).unwrap(),
+            DecompressOpts::default(),
+        )
+        .unwrap();
+        let block_sizes: Vec<u64> = reader
+            .blocks()
+            .map(|block| block.unwrap().size as u64)
+            .collect();
+
+        let summary = execute_decompress(
+            DecompressRequest::builder()
+                .input(JacInput::Path(jac_path.clone()))
+                .output(OutputSink::Writer(Box::new(io::sink())))
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+        let metrics = &summary.metrics;
+        assert_eq!(metrics.bytes_read, block_sizes.iter().sum::<u64>());
+        assert_eq!(
+            (metrics.blocks_decoded, metrics.segments_decompressed),
+            (3, 6)
+        );
+        #[cfg(feature = "latency-histogram")]
+        assert_eq!(metrics.block_latency.count(), 3);
+
+        // The first block is skipped by offset and never read.
+        let summary = execute_project(ProjectRequest {
+            input: JacInput::Path(jac_path),
+            output: OutputSink::Writer(Box::new(io::sink())),
+            fields: vec!["tag".to_string()],
+            format: ProjectFormat::Ndjson,
+            options: DecompressOptions::default(),
+            include_presence: false,
+            offset: Some(25),
+            limit: None,
+            rename: HashMap::new(),
+        })
+        .unwrap();
+        assert_eq!(summary.rows_written, 25);
+        assert_eq!(
+            summary.metrics.bytes_read,
+            block_sizes[1..].iter().sum::<u64>()
+        );
+        // Only the projected field's segment is decompressed in each block.
+        assert_eq!(
+            (
+                summary.metrics.blocks_decoded,
+                summary.metrics.segments_decompressed
+            ),
+            (2, 2)
+        );
+        assert!(summary.metrics.decode_time > Duration::ZERO);
+    }
+
+    #[test]
+    fn record_channel_feeds_records_from_background_thread() {
+        let dir = tempdir().unwrap();
+        let jac_path = dir.path().join("channel.jac");
+        let ndjson: String = (0..100)
+            .map(|i| json!({"id": i}).to_string() + "\n")
+            .collect();
+        execute_compress(
+            CompressRequest::builder()
+                .input(InputSource::NdjsonReader(Box::new(Cursor::new(
+                    ndjson.into_bytes(),
+                ))))
+                .output(OutputSink::Path(jac_path.clone()))
+                .block_target_records(10)
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+
+        let reader = JacReader::open(&jac_path, DecompressOpts::default()).unwrap();
+        let ids: Vec<u64> = reader
+            .record_channel(4)
+            .into_iter()
+            .map(|record| record.unwrap()["id"].as_u64().unwrap())
+            .collect();
+        assert_eq!(ids, (0..100).collect::<Vec<_>>());
+
+        // Dropping the receiver early lets the feeder thread exit.
+        let reader = JacReader::open(&jac_path, DecompressOpts::default()).unwrap();
+        let receiver = reader.record_channel(1);
+        assert_eq!(receiver.recv().unwrap().unwrap()["id"], json!(0));
+        drop(receiver);
+
+        // Errors are delivered once, then the channel closes.
+        let mut bytes = fs::read(&jac_path).unwrap();
+        let len = bytes.len();
+        bytes.truncate(len / 2);
+        let reader = JacReader::new(Cursor::new(bytes), DecompressOpts::default()).unwrap();
+        let results: Vec<_> = reader.record_channel(8).into_iter().collect();
+        assert!(results.last().unwrap().is_err());
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+    }
+
+    #[test]
+    fn project_include_presence_separates_null_from_absent() {
+        let data = "{\"user\":\"alice\",\"team\":null}\n{\"user\":\"bob\"}\n{\"user\":\"cy\",\"team\":\"ops\"}\n";
+        let paths = TempPaths::new("project-presence");
+        fs::write(&paths.input_ndjson, data).unwrap();
+        execute_compress(CompressRequest {
+            input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+            output: OutputSink::Path(paths.output_jac.clone()),
+            ..CompressRequest::default()
+        })
+        .unwrap();
+
+        let project = |format: ProjectFormat, include_presence: bool| {
+            let out = paths.output_json.with_extension("presence.out");
+            execute_project(ProjectRequest {
+                input: JacInput::Path(paths.output_jac.clone()),
+                output: OutputSink::Path(out.clone()),
+                fields: vec!["team".to_string()],
+                format,
+                options: DecompressOptions::default(),
+                include_presence,
+                offset: None,
+                limit: None,
+                rename: HashMap::new(),
+            })
+            .unwrap();
+            let text = fs::read_to_string(&out).unwrap();
+            let _ = fs::remove_file(&out);
+            text
+        };
+
+        let ndjson = project(ProjectFormat::Ndjson, true);
+        assert_eq!(ndjson, "{\"team\":null}\n{}\n{\"team\":\"ops\"}\n");
+
+        let plain_csv = project(ProjectFormat::Csv { headers: true }, false);
+        assert_eq!(plain_csv, "team\n\n\nops\n");
+
+        let csv = project(ProjectFormat::Csv { headers: true }, true);
+        assert_eq!(csv, "team,team_presence\n,null\n,absent\nops,present\n");
+    }
+
     #[test]
     fn compress_json_array_and_auto_decompress_to_array() {
         let dir = tempdir().unwrap();
@@ -1621,6 +5603,7 @@ mod tests {
             container_hint: Some(ContainerFormat::JsonArray),
             emit_index: true,
             wrapper_config: WrapperConfig::None,
+            interrupt: None,
         };
         execute_compress(compress_request).unwrap();
 
@@ -1629,6 +5612,15 @@ mod tests {
             output: OutputSink::Path(output_path.clone()),
             format: DecompressFormat::Auto,
             options: DecompressOptions::default(),
+            offset: None,
+            limit: None,
+            blocks: None,
+            rename: HashMap::new(),
+            line_ending: LineEnding::default(),
+            encoding: TextEncoding::default(),
+            validate_utf8: false,
+            require_pointer: None,
+            filter: None,
         };
         execute_decompress(decompress_request).unwrap();
 
@@ -1660,6 +5652,7 @@ mod tests {
             container_hint: Some(ContainerFormat::Ndjson),
             emit_index: false,
             wrapper_config: WrapperConfig::None,
+            interrupt: None,
         };
 
         let summary = execute_compress(request).expect("compress succeeds");
@@ -1714,6 +5707,7 @@ mod tests {
             container_hint: Some(ContainerFormat::Ndjson),
             emit_index: true,
             wrapper_config: WrapperConfig::None,
+            interrupt: None,
         };
         let seq_summary = execute_compress_sequential(sequential_request).unwrap();
 
@@ -1724,6 +5718,7 @@ mod tests {
             container_hint: Some(ContainerFormat::Ndjson),
             emit_index: true,
             wrapper_config: WrapperConfig::None,
+            interrupt: None,
         };
         let par_summary = crate::parallel::execute_compress_parallel(parallel_request, 2).unwrap();
 
@@ -1790,6 +5785,7 @@ mod tests {
             container_hint: Some(ContainerFormat::Ndjson),
             emit_index: true,
             wrapper_config: WrapperConfig::None,
+            interrupt: None,
         };
         execute_compress_sequential(sequential_request).unwrap();
 
@@ -1800,6 +5796,7 @@ mod tests {
             container_hint: Some(ContainerFormat::Ndjson),
             emit_index: true,
             wrapper_config: WrapperConfig::None,
+            interrupt: None,
         };
         crate::parallel::execute_compress_parallel(parallel_request, 2).unwrap();
 
@@ -1836,6 +5833,7 @@ mod tests {
             container_hint: Some(ContainerFormat::Ndjson),
             emit_index: false,
             wrapper_config: WrapperConfig::None,
+            interrupt: None,
         };
 
         let seq_error = execute_compress_sequential(sequential_request).unwrap_err();
@@ -1851,6 +5849,7 @@ mod tests {
             container_hint: Some(ContainerFormat::Ndjson),
             emit_index: false,
             wrapper_config: WrapperConfig::None,
+            interrupt: None,
         };
 
         let par_error =
@@ -1882,9 +5881,16 @@ mod tests {
             block_target_records: options.block_target_records,
             default_codec: options.default_codec,
             canonicalize_keys: options.canonicalize_keys,
+            preserve_key_order: options.preserve_key_order,
+            hot_fields: options.hot_fields.clone(),
             canonicalize_numbers: options.canonicalize_numbers,
+            number_canonicalization: options.number_canonicalization.clone(),
             nested_opaque: options.nested_opaque,
             max_dict_entries: options.max_dict_entries,
+            dictionary_policy: options.dictionary_policy.clone(),
+            field_hints: options.field_hints.clone(),
+            field_stats: options.field_stats,
+            quantile_sketches: options.quantile_sketches,
             limits: options.limits,
         };
 
@@ -1899,8 +5905,36 @@ mod tests {
 
         let mut custom_opts = DecompressOpts::default();
         custom_opts.limits.max_segment_uncompressed_len = 32;
-        let reader_custom = JacReader::new(Cursor::new(bytes), custom_opts).unwrap();
+        let reader_custom = JacReader::new(Cursor::new(bytes.clone()), custom_opts).unwrap();
         assert_eq!(reader_custom.limits().max_segment_uncompressed_len, 32);
+        assert_eq!(
+            reader_custom
+                .declared_limits()
+                .map(|limits| limits.max_segment_uncompressed_len),
+            Some(128)
+        );
+        let decision = reader_custom.limits_decision();
+        assert_eq!(decision.declared_segment_limit, Some(128));
+        assert!(!decision.file_applied());
+
+        let trusting = DecompressOpts {
+            limits: reader_custom.limits().clone(),
+            declared_limits: DeclaredLimitsPolicy::TrustFile,
+            ..DecompressOpts::default()
+        };
+        let reader_trusting = JacReader::new(Cursor::new(bytes.clone()), trusting).unwrap();
+        assert_eq!(reader_trusting.limits().max_segment_uncompressed_len, 128);
+        assert!(reader_trusting.limits_decision().file_applied());
+
+        let caller_wins = DecompressOpts {
+            declared_limits: DeclaredLimitsPolicy::CallerWins,
+            ..DecompressOpts::default()
+        };
+        let reader_caller = JacReader::new(Cursor::new(bytes), caller_wins).unwrap();
+        assert_eq!(
+            reader_caller.limits().max_segment_uncompressed_len,
+            Limits::default().max_segment_uncompressed_len
+        );
     }
 
     #[test]
@@ -1925,6 +5959,7 @@ mod tests {
             container_hint: Some(ContainerFormat::JsonArray),
             emit_index: true,
             wrapper_config: WrapperConfig::None,
+            interrupt: None,
         };
         execute_compress(compress_request).unwrap();
 
@@ -1933,6 +5968,15 @@ mod tests {
             output: OutputSink::Path(output_path.clone()),
             format: DecompressFormat::JsonArray,
             options: DecompressOptions::default(),
+            offset: None,
+            limit: None,
+            blocks: None,
+            rename: HashMap::new(),
+            line_ending: LineEnding::default(),
+            encoding: TextEncoding::default(),
+            validate_utf8: false,
+            require_pointer: None,
+            filter: None,
         };
         execute_decompress(decompress_request).unwrap();
 
@@ -1966,6 +6010,7 @@ mod tests {
                 container_hint: None,
                 emit_index: true,
                 wrapper_config: WrapperConfig::None,
+                interrupt: None,
             };
 
             super::async_io::compress(compress_request)
@@ -1977,6 +6022,15 @@ mod tests {
                 output: OutputSink::Path(paths.output_json.clone()),
                 format: DecompressFormat::Auto,
                 options: DecompressOptions::default(),
+                offset: None,
+                limit: None,
+                blocks: None,
+                rename: HashMap::new(),
+                line_ending: LineEnding::default(),
+                encoding: TextEncoding::default(),
+                validate_utf8: false,
+                require_pointer: None,
+                filter: None,
             };
 
             super::async_io::decompress(decompress_request)
@@ -1986,6 +6040,26 @@ mod tests {
             let result = fs::read_to_string(&paths.output_json).unwrap();
             assert_eq!(normalize_ndjson(&result), normalize_ndjson(data));
         }
+
+        #[tokio::test]
+        async fn record_channel_async_delivers_records() {
+            let paths = TempPaths::new("async_channel");
+            fs::write(&paths.input_ndjson, "{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n").unwrap();
+            execute_compress(CompressRequest {
+                input: InputSource::NdjsonPath(paths.input_ndjson.clone()),
+                output: OutputSink::Path(paths.output_jac.clone()),
+                ..CompressRequest::default()
+            })
+            .unwrap();
+
+            let reader = JacReader::open(&paths.output_jac, DecompressOpts::default()).unwrap();
+            let mut receiver = reader.record_channel_async(1);
+            let mut ids = Vec::new();
+            while let Some(record) = receiver.recv().await {
+                ids.push(record.unwrap()["id"].as_u64().unwrap());
+            }
+            assert_eq!(ids, vec![1, 2, 3]);
+        }
     }
 
     struct TempPaths {
@@ -0,0 +1,180 @@
+//! One-call compression to and from in-memory buffers.
+//!
+//! [`compress_records`] and [`decompress_to_records`] wrap the request API for
+//! tests and small embedders that hold their records in memory anyway. Both
+//! refuse to grow past [`MAX_IN_MEMORY_BYTES`]: compression stops once the
+//! `.jac` output would exceed it, and decompression checks the uncompressed
+//! segment sizes in every block directory before decoding anything.
+
+use crate::{
+    execute_compress, CompressOptions, CompressRequest, InputSource, JacReader, OutputSink,
+    WrapperConfig,
+};
+use jac_codec::DecompressOpts;
+use jac_format::{JacError, Result};
+use serde_json::{Map, Value};
+use std::io::{self, Cursor, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Largest `.jac` buffer, and largest total of uncompressed segments when
+/// decoding one, that the in-memory helpers handle (256 MiB).
+pub const MAX_IN_MEMORY_BYTES: usize = 256 << 20;
+
+/// Compress `records` into the bytes of an indexed `.jac` file.
+///
+/// Fails with [`JacError::LimitExceeded`] when the output would exceed
+/// [`MAX_IN_MEMORY_BYTES`].
+pub fn compress_records<I>(records: I, opts: CompressOptions) -> Result<Vec<u8>>
+where
+    I: IntoIterator<Item = Map<String, Value>>,
+    I::IntoIter: Send + 'static,
+{
+    let sink = MemorySink::default();
+    let request = CompressRequest {
+        input: InputSource::Iterator(Box::new(records.into_iter())),
+        output: OutputSink::Writer(Box::new(sink.clone())),
+        options: opts,
+        container_hint: None,
+        emit_index: true,
+        wrapper_config: WrapperConfig::None,
+        interrupt: None,
+    };
+    match execute_compress(request) {
+        Ok(_) => Ok(sink.take()),
+        Err(_) if sink.overflowed.load(Ordering::Relaxed) => Err(output_limit_error()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Decode every record of the `.jac` file in `bytes`.
+///
+/// Fails with [`JacError::LimitExceeded`] before decoding when `bytes` or the
+/// uncompressed segments of its blocks exceed [`MAX_IN_MEMORY_BYTES`].
+pub fn decompress_to_records(bytes: &[u8]) -> Result<Vec<Map<String, Value>>> {
+    if bytes.len() > MAX_IN_MEMORY_BYTES {
+        return Err(JacError::LimitExceeded(format!(
+            "Input of {} bytes exceeds the in-memory limit of {} bytes",
+            bytes.len(),
+            MAX_IN_MEMORY_BYTES
+        )));
+    }
+    let mut reader = JacReader::new(Cursor::new(bytes), DecompressOpts::default())?;
+
+    let mut records = 0usize;
+    let mut uncompressed = 0usize;
+    for block in reader.blocks() {
+        let block = block?;
+        records = records.saturating_add(block.record_count);
+        for field in &block.header.fields {
+            uncompressed = uncompressed.saturating_add(field.segment_uncompressed_len);
+        }
+        if uncompressed > MAX_IN_MEMORY_BYTES {
+            return Err(JacError::LimitExceeded(format!(
+                "Uncompressed segments exceed the in-memory limit of {} bytes",
+                MAX_IN_MEMORY_BYTES
+            )));
+        }
+    }
+
+    let mut out = Vec::with_capacity(records);
+    for record in reader.record_stream()? {
+        out.push(record?);
+    }
+    Ok(out)
+}
+
+fn output_limit_error() -> JacError {
+    JacError::LimitExceeded(format!(
+        "Compressed output exceeds the in-memory limit of {} bytes",
+        MAX_IN_MEMORY_BYTES
+    ))
+}
+
+/// Shared growable buffer that refuses writes past `MAX_IN_MEMORY_BYTES`.
+#[derive(Clone, Default)]
+struct MemorySink {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    overflowed: Arc<AtomicBool>,
+}
+
+impl MemorySink {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<u8>> {
+        self.buffer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Write for MemorySink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut buffer = self.lock();
+        if buffer.len().saturating_add(buf.len()) > MAX_IN_MEMORY_BYTES {
+            self.overflowed.store(true, Ordering::Relaxed);
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                output_limit_error().to_string(),
+            ));
+        }
+        buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CompressionProfile;
+    use serde_json::json;
+
+    fn records() -> Vec<Map<String, Value>> {
+        (0..250)
+            .map(|i| {
+                let level = if i % 2 == 0 { "info" } else { "warn" };
+                json!({"id": i, "level": level, "ratio": 0.5 + i as f64})
+                    .as_object()
+                    .unwrap()
+                    .clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn records_round_trip_through_memory() {
+        let opts = CompressOptions {
+            block_target_records: 100,
+            ..CompressOptions::from_profile(CompressionProfile::Fast)
+        };
+        let bytes = compress_records(records(), opts).unwrap();
+        let mut reader = JacReader::new(Cursor::new(&bytes), DecompressOpts::default()).unwrap();
+        assert!(reader.is_finalized());
+        assert_eq!(reader.block_count().unwrap(), 3);
+        assert_eq!(decompress_to_records(&bytes).unwrap(), records());
+
+        let empty = compress_records(Vec::new(), CompressOptions::default()).unwrap();
+        assert!(decompress_to_records(&empty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn oversized_buffers_are_refused() {
+        let mut sink = MemorySink::default();
+        sink.write_all(&[0; 16]).unwrap();
+        assert!(sink.write_all(&vec![0; MAX_IN_MEMORY_BYTES]).is_err());
+        assert!(sink.overflowed.load(Ordering::Relaxed));
+        assert_eq!(sink.take().len(), 16);
+
+        let huge = vec![0u8; MAX_IN_MEMORY_BYTES + 1];
+        assert!(matches!(
+            decompress_to_records(&huge),
+            Err(JacError::LimitExceeded(_))
+        ));
+    }
+}
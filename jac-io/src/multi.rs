@@ -0,0 +1,257 @@
+//! Several archives read as one logical stream.
+//!
+//! [`MultiJacReader`] keeps one [`JacReader`] per archive and presents them in
+//! concatenation order: record and projection streams run through each archive
+//! in turn, and field catalogs are merged with block indices numbered across
+//! the whole sequence. Block skipping, pruning and projection still happen per
+//! file, against that file's own index and block directories.
+
+use crate::reader::{
+    FieldCatalogEntry, FieldCatalogOptions, JacReader, MultiProjectionStream, RecordStream,
+};
+use jac_codec::DecompressOpts;
+use jac_format::{JacError, Result};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+/// Readers over several archives, iterated in order.
+pub struct MultiJacReader<R: Read + Seek = File> {
+    readers: Vec<JacReader<R>>,
+}
+
+impl MultiJacReader<File> {
+    /// Open every path in order; fails if any archive cannot be opened.
+    pub fn open<P: AsRef<Path>>(paths: &[P], opts: DecompressOpts) -> Result<Self> {
+        let readers = paths
+            .iter()
+            .map(|path| JacReader::open(path.as_ref(), opts.clone()))
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_readers(readers)
+    }
+}
+
+impl<R: Read + Seek> MultiJacReader<R> {
+    /// Combine already opened readers, keeping their order.
+    pub fn from_readers(readers: Vec<JacReader<R>>) -> Result<Self> {
+        if readers.is_empty() {
+            return Err(JacError::Internal(
+                "multi-archive reader requires at least one archive".to_string(),
+            ));
+        }
+        Ok(Self { readers })
+    }
+
+    /// Number of archives.
+    pub fn archive_count(&self) -> usize {
+        self.readers.len()
+    }
+
+    /// Per-archive readers, in concatenation order.
+    pub fn readers_mut(&mut self) -> &mut [JacReader<R>] {
+        &mut self.readers
+    }
+
+    /// Release the per-archive readers.
+    pub fn into_readers(self) -> Vec<JacReader<R>> {
+        self.readers
+    }
+
+    /// Total records across all archives; see [`JacReader::count_records`].
+    pub fn count_records(&mut self) -> Result<u64> {
+        self.readers
+            .iter_mut()
+            .map(|reader| reader.count_records())
+            .sum()
+    }
+
+    /// Total blocks across all archives; see [`JacReader::block_count`].
+    pub fn block_count(&mut self) -> Result<usize> {
+        self.readers
+            .iter_mut()
+            .map(|reader| reader.block_count())
+            .sum()
+    }
+
+    /// Merged field catalog across all archives. Entries are sorted by name.
+    pub fn field_catalog(&mut self) -> Result<Vec<FieldCatalogEntry>> {
+        self.field_catalog_with(&FieldCatalogOptions::default())
+    }
+
+    /// Merged field catalog with explicit options.
+    ///
+    /// Block indices count from the first block of the first archive, so a
+    /// field's `blocks` point into the concatenated sequence. Types are the
+    /// union of the types each archive stores for the field.
+    pub fn field_catalog_with(
+        &mut self,
+        options: &FieldCatalogOptions,
+    ) -> Result<Vec<FieldCatalogEntry>> {
+        let mut merged: BTreeMap<String, FieldCatalogEntry> = BTreeMap::new();
+        let mut block_base = 0usize;
+        for reader in &mut self.readers {
+            for entry in reader.field_catalog_with(options)? {
+                let summary =
+                    merged
+                        .entry(entry.name.clone())
+                        .or_insert_with(|| FieldCatalogEntry {
+                            name: entry.name.clone(),
+                            ..FieldCatalogEntry::default()
+                        });
+                for tag in entry.types {
+                    if !summary.types.contains(&tag) {
+                        summary.types.push(tag);
+                    }
+                }
+                summary
+                    .blocks
+                    .extend(entry.blocks.iter().map(|idx| idx + block_base));
+                summary.value_count += entry.value_count;
+                summary.compressed_bytes += entry.compressed_bytes;
+                summary.uncompressed_bytes += entry.uncompressed_bytes;
+            }
+            block_base += reader.block_count()?;
+        }
+
+        Ok(merged
+            .into_values()
+            .map(|mut entry| {
+                entry.types.sort_by_key(|tag| *tag as u8);
+                entry
+            })
+            .collect())
+    }
+
+    /// Stream all records of every archive in order.
+    pub fn record_stream(&mut self) -> ChainedRecordStream<'_, R> {
+        ChainedRecordStream {
+            readers: self.readers.iter_mut(),
+            current: None,
+            archive: None,
+        }
+    }
+
+    /// Stream aligned rows of several fields across every archive in order.
+    ///
+    /// Rows follow [`JacReader::multi_projection_stream`]: one entry per
+    /// requested field, `None` where the field is absent.
+    pub fn multi_projection_stream<S: AsRef<str>>(
+        &mut self,
+        fields: &[S],
+    ) -> Result<ChainedProjectionStream<'_, R>> {
+        if fields.is_empty() {
+            return Err(JacError::Internal(
+                "multi-field projection requires at least one field".to_string(),
+            ));
+        }
+        Ok(ChainedProjectionStream {
+            readers: self.readers.iter_mut(),
+            fields: fields.iter().map(|f| f.as_ref().to_string()).collect(),
+            current: None,
+            archive: None,
+        })
+    }
+
+    /// Run a query over every archive in order, writing matching rows as NDJSON.
+    ///
+    /// Blocks are pruned per archive; `LIMIT` applies to the combined output.
+    #[cfg(feature = "query")]
+    pub fn query<W: std::io::Write>(
+        &mut self,
+        query: &crate::Query,
+        output: W,
+    ) -> Result<crate::QuerySummary> {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(output);
+        let mut summary = crate::QuerySummary::default();
+        for reader in &mut self.readers {
+            if query
+                .limit
+                .is_some_and(|limit| summary.rows_written as usize >= limit)
+            {
+                break;
+            }
+            crate::query::query_reader(reader, query, &mut writer, &mut summary)?;
+        }
+        writer.flush()?;
+        Ok(summary)
+    }
+}
+
+/// Record iterator over several archives, see [`MultiJacReader::record_stream`].
+pub struct ChainedRecordStream<'a, R: Read + Seek> {
+    readers: std::slice::IterMut<'a, JacReader<R>>,
+    current: Option<RecordStream<'a, R>>,
+    archive: Option<usize>,
+}
+
+impl<'a, R: Read + Seek> ChainedRecordStream<'a, R> {
+    /// Zero-based index of the archive the last record came from.
+    pub fn archive_index(&self) -> Option<usize> {
+        self.archive
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for ChainedRecordStream<'a, R> {
+    type Item = Result<Map<String, Value>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(stream) = &mut self.current {
+                if let Some(record) = stream.next() {
+                    return Some(record);
+                }
+            }
+            let reader = self.readers.next()?;
+            self.archive = Some(self.archive.map_or(0, |idx| idx + 1));
+            match reader.record_stream() {
+                Ok(stream) => self.current = Some(stream),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// Projection iterator over several archives, see
+/// [`MultiJacReader::multi_projection_stream`].
+pub struct ChainedProjectionStream<'a, R: Read + Seek> {
+    readers: std::slice::IterMut<'a, JacReader<R>>,
+    fields: Vec<String>,
+    current: Option<MultiProjectionStream<'a, R>>,
+    archive: Option<usize>,
+}
+
+impl<'a, R: Read + Seek> ChainedProjectionStream<'a, R> {
+    /// Requested field names, in the order values appear in each row.
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Zero-based index of the archive the last row came from.
+    pub fn archive_index(&self) -> Option<usize> {
+        self.archive
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for ChainedProjectionStream<'a, R> {
+    type Item = Result<Vec<Option<Value>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(stream) = &mut self.current {
+                if let Some(row) = stream.next() {
+                    return Some(row);
+                }
+            }
+            let reader = self.readers.next()?;
+            self.archive = Some(self.archive.map_or(0, |idx| idx + 1));
+            match reader.multi_projection_stream(&self.fields) {
+                Ok(stream) => self.current = Some(stream),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! Several named datasets in one physical file.
+//!
+//! Related datasets (say `events`, `errors` and `metadata` of one job) often
+//! belong together, and storing each in a file of its own multiplies file
+//! counts. A multi-stream file holds one complete archive per stream, one after
+//! another, followed by a [`StreamDirectory`] naming them and a trailer
+//! pointing at it (see the spec addendum). [`MultiStreamWriter`] accepts
+//! records for any stream in any order, spooling each stream to a temporary
+//! file until [`MultiStreamWriter::finish`] lays them out; [`MultiStreamReader`]
+//! opens a stream by name as an ordinary [`JacReader`].
+
+use crate::embedded::EmbeddedReader;
+use crate::writer::{JacWriter, WriterMetrics};
+use crate::{CompressOptions, JacReader};
+use jac_codec::{CompressOpts, DecompressOpts};
+use jac_format::constants::{STREAMS_MAGIC, STREAMS_TRAILER_LEN};
+use jac_format::{FileHeader, JacError, Result, StreamDirectory, StreamEntry};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+/// Writer of one stream inside a [`MultiStreamWriter`].
+pub type StreamWriter = JacWriter<BufWriter<Spool>>;
+
+/// Writer for files holding several named streams.
+pub struct MultiStreamWriter<W: Write> {
+    output: W,
+    header: FileHeader,
+    opts: CompressOpts,
+    /// Directory for stream spools; `None` uses the system temporary directory
+    temp_dir: Option<PathBuf>,
+    /// Streams in the order they were first written
+    streams: Vec<(String, StreamWriter)>,
+}
+
+impl<W: Write> MultiStreamWriter<W> {
+    /// Create a writer; every stream gets `header` and `opts`.
+    pub fn new(output: W, header: FileHeader, opts: CompressOpts) -> Self {
+        Self {
+            output,
+            header,
+            opts,
+            temp_dir: None,
+            streams: Vec::new(),
+        }
+    }
+
+    /// Create a writer whose streams are encoded with the settings of
+    /// `options` and spooled in its [`CompressOptions::temp_dir`].
+    pub fn from_options(output: W, header: FileHeader, options: &CompressOptions) -> Self {
+        Self {
+            temp_dir: options.temp_dir.clone(),
+            ..Self::new(output, header, options.codec_opts())
+        }
+    }
+
+    /// Spool streams in `dir` instead of the system temporary directory.
+    pub fn with_temp_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.temp_dir = Some(dir.into());
+        self
+    }
+
+    /// Writer of the stream called `name`, created on first use.
+    pub fn stream(&mut self, name: &str) -> Result<&mut StreamWriter> {
+        let idx = match self
+            .streams
+            .iter()
+            .position(|(existing, _)| existing == name)
+        {
+            Some(idx) => idx,
+            None => {
+                let spool = Spool::create(self.temp_dir.as_deref())?;
+                let writer = JacWriter::new(
+                    BufWriter::new(spool),
+                    self.header.clone(),
+                    self.opts.clone(),
+                )?;
+                self.streams.push((name.to_string(), writer));
+                self.streams.len() - 1
+            }
+        };
+        Ok(&mut self.streams[idx].1)
+    }
+
+    /// Names of the streams written so far, in file order.
+    pub fn stream_names(&self) -> impl Iterator<Item = &str> {
+        self.streams.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Finish every stream with its index and write the file: the streams in
+    /// the order they were first written, then the stream directory.
+    pub fn finish(mut self) -> Result<MultiStreamFinish<W>> {
+        let mut directory = StreamDirectory::default();
+        let mut metrics = Vec::with_capacity(self.streams.len());
+        let mut offset = 0u64;
+        for (name, writer) in self.streams.drain(..) {
+            let finish = writer.finish_with_index()?;
+            let mut spool = finish.writer.into_inner().map_err(|err| err.into_error())?;
+            spool.file.seek(SeekFrom::Start(0))?;
+            let len = io::copy(&mut spool.file, &mut self.output)?;
+            directory.streams.push(StreamEntry {
+                name: name.clone(),
+                offset,
+                len,
+            });
+            metrics.push((name, finish.metrics));
+            offset += len;
+        }
+
+        let directory_bytes = directory.encode();
+        self.output.write_all(&directory_bytes)?;
+        self.output.write_all(&offset.to_le_bytes())?;
+        self.output.write_all(&STREAMS_MAGIC.to_le_bytes())?;
+        self.output.flush()?;
+
+        Ok(MultiStreamFinish {
+            writer: self.output,
+            directory,
+            metrics,
+        })
+    }
+}
+
+/// Result of [`MultiStreamWriter::finish`].
+pub struct MultiStreamFinish<W> {
+    /// The output writer
+    pub writer: W,
+    /// Streams as laid out in the file
+    pub directory: StreamDirectory,
+    /// Writer metrics of each stream, in file order
+    pub metrics: Vec<(String, WriterMetrics)>,
+}
+
+/// Temporary file holding a stream until the file is laid out; removed on
+/// drop.
+pub struct Spool {
+    file: NamedTempFile,
+}
+
+impl Spool {
+    fn create(dir: Option<&Path>) -> Result<Self> {
+        let mut builder = tempfile::Builder::new();
+        builder.prefix("jac-stream-").suffix(".spool");
+        let file = match dir {
+            Some(dir) => builder.tempfile_in(dir)?,
+            None => builder.tempfile()?,
+        };
+        Ok(Self { file })
+    }
+}
+
+impl Write for Spool {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Reader selecting the streams of a multi-stream file by name.
+pub struct MultiStreamReader<R: Read + Seek> {
+    reader: R,
+    directory: StreamDirectory,
+}
+
+impl<R: Read + Seek> MultiStreamReader<R> {
+    /// Open a multi-stream file; fails when `reader` has no stream directory.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let file_size = reader.seek(SeekFrom::End(0))?;
+        let directory = read_stream_directory(&mut reader, file_size)?.ok_or_else(|| {
+            JacError::InvalidArgument("not a multi-stream file (no stream directory)".to_string())
+        })?;
+        Ok(Self { reader, directory })
+    }
+
+    /// Streams in file order.
+    pub fn streams(&self) -> &[StreamEntry] {
+        &self.directory.streams
+    }
+
+    /// Open the stream called `name`.
+    pub fn stream(
+        &mut self,
+        name: &str,
+        opts: DecompressOpts,
+    ) -> Result<JacReader<EmbeddedReader<&mut R>>> {
+        let entry = self
+            .directory
+            .get(name)
+            .ok_or_else(|| JacError::InvalidArgument(format!("no stream named '{}'", name)))?;
+        JacReader::new_bounded(&mut self.reader, entry.offset, entry.len, opts)
+    }
+
+    /// Return the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Stream directory of the file behind `reader`, if it ends with one.
+///
+/// A file whose last bytes merely look like the trailer (an unfinished plain
+/// archive) does not pass the directory's checksum and yields `None`.
+pub(crate) fn read_stream_directory<R: Read + Seek>(
+    reader: &mut R,
+    file_size: u64,
+) -> Result<Option<StreamDirectory>> {
+    let Some(directory_end) = file_size.checked_sub(STREAMS_TRAILER_LEN) else {
+        return Ok(None);
+    };
+    reader.seek(SeekFrom::Start(directory_end))?;
+    let mut trailer = [0u8; STREAMS_TRAILER_LEN as usize];
+    reader.read_exact(&mut trailer)?;
+    if trailer[8..] != STREAMS_MAGIC.to_le_bytes() {
+        return Ok(None);
+    }
+    let mut offset = [0u8; 8];
+    offset.copy_from_slice(&trailer[..8]);
+    let directory_start = u64::from_le_bytes(offset);
+    let Some(len) = directory_end.checked_sub(directory_start) else {
+        return Ok(None);
+    };
+    let Ok(len) = usize::try_from(len) else {
+        return Ok(None);
+    };
+    reader.seek(SeekFrom::Start(directory_start))?;
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    Ok(StreamDirectory::decode(&bytes).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build_file_header;
+    use serde_json::{json, Map, Value};
+    use std::fs;
+    use std::io::Cursor;
+
+    fn record(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    fn read_stream(reader: &mut MultiStreamReader<Cursor<Vec<u8>>>, name: &str) -> Vec<Value> {
+        let mut stream = reader.stream(name, DecompressOpts::default()).unwrap();
+        assert!(stream.is_finalized());
+        stream
+            .record_stream()
+            .unwrap()
+            .map(|record| Value::Object(record.unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn streams_are_written_interleaved_and_read_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = CompressOptions {
+            block_target_records: 4,
+            intern_field_names: true,
+            temp_dir: Some(dir.path().to_path_buf()),
+            ..CompressOptions::default()
+        };
+        let header = build_file_header(&options, None).unwrap();
+        let mut writer = MultiStreamWriter::from_options(Vec::new(), header, &options);
+        for i in 0..10 {
+            let events = writer.stream("events").unwrap();
+            events.write_record(&record(json!({ "id": i }))).unwrap();
+            if i % 3 == 0 {
+                let errors = writer.stream("errors").unwrap();
+                errors
+                    .write_record(&record(json!({ "id": i, "error": "timeout" })))
+                    .unwrap();
+            }
+        }
+        writer
+            .stream("metadata")
+            .unwrap()
+            .write_record(&record(json!({ "job": "nightly" })))
+            .unwrap();
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+
+        let finish = writer.finish().unwrap();
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+        let names: Vec<&str> = finish
+            .directory
+            .streams
+            .iter()
+            .map(|stream| stream.name.as_str())
+            .collect();
+        assert_eq!(names, ["events", "errors", "metadata"]);
+        assert_eq!(finish.metrics[1].1.records_written, 4);
+
+        let bytes = finish.writer;
+        let mut reader = MultiStreamReader::new(Cursor::new(bytes.clone())).unwrap();
+        assert_eq!(reader.streams().len(), 3);
+        let errors = read_stream(&mut reader, "errors");
+        assert_eq!(
+            errors,
+            [0, 3, 6, 9]
+                .map(|i| json!({ "id": i, "error": "timeout" }))
+                .to_vec()
+        );
+        let events = read_stream(&mut reader, "events");
+        assert_eq!(
+            events,
+            (0..10).map(|i| json!({ "id": i })).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            read_stream(&mut reader, "metadata"),
+            [json!({ "job": "nightly" })]
+        );
+        assert!(matches!(
+            reader.stream("missing", DecompressOpts::default()),
+            Err(JacError::InvalidArgument(_))
+        ));
+
+        // A plain reader does not look for the stream directory: it reads the
+        // streams as concatenated archives and fails on the directory.
+        let mut plain = JacReader::new(Cursor::new(bytes), DecompressOpts::default()).unwrap();
+        let mut records = plain.record_stream().unwrap();
+        assert_eq!(records.by_ref().take(15).filter(Result::is_ok).count(), 15);
+        assert!(matches!(records.next(), Some(Err(JacError::CorruptBlock))));
+
+        assert!(matches!(
+            MultiStreamReader::new(Cursor::new(vec![0u8; 64])),
+            Err(JacError::InvalidArgument(_))
+        ));
+    }
+}
@@ -6,12 +6,16 @@
 
 use crate::{runtime::RuntimeMeasurement, InputSource};
 use jac_format::{Limits, Result};
+use serde::{Deserialize, Serialize};
 
 #[cfg(not(target_arch = "wasm32"))]
 use sysinfo::System;
 
 #[cfg(not(target_arch = "wasm32"))]
-use crate::{build_file_header, writer::JacWriter, CompressOpts, CompressRequest, CompressSummary};
+use crate::{
+    build_file_header, is_interrupted, require_records, tune_zstd_level, writer::JacWriter,
+    CompressRequest, CompressSummary, IngestConfig,
+};
 #[cfg(not(target_arch = "wasm32"))]
 use jac_codec::{
     compress_block_segments, configure_codec_for_parallel, BlockBuilder, BlockFinish,
@@ -25,7 +29,6 @@ use rayon::ThreadPoolBuilder;
 use std::{
     collections::BTreeMap,
     io::{BufWriter, Write},
-    mem,
     sync::{mpsc::sync_channel, Arc, Mutex},
     thread,
 };
@@ -36,7 +39,8 @@ const MAX_PARALLEL_THREADS: usize = 16;
 const DEFAULT_MEMORY_RESERVATION_FACTOR: f64 = 0.75;
 
 /// Configuration controlling how the parallel compression heuristic behaves.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ParallelConfig {
     /// Fraction of available memory considered usable for in-flight blocks.
     pub memory_reservation_factor: f64,
@@ -96,7 +100,9 @@ pub(crate) fn should_use_parallel(
         }
         InputSource::NdjsonReader(_)
         | InputSource::JsonArrayReader(_)
-        | InputSource::Iterator(_) => None,
+        | InputSource::Iterator(_)
+        | InputSource::MessagePackReader(_)
+        | InputSource::BsonReader(_) => None,
     };
 
     Ok(evaluate_parallel_decision(
@@ -272,33 +278,40 @@ pub(crate) fn execute_compress_parallel(
     let CompressRequest {
         input,
         output,
-        options,
+        mut options,
         container_hint,
         emit_index,
         wrapper_config,
+        interrupt,
     } = request;
 
-    let mut record_stream = input.into_record_stream(&wrapper_config)?;
+    let mut record_stream = input
+        .into_record_stream(&wrapper_config, IngestConfig::from_options(&options))?
+        .with_key_normalization(&options.key_normalization)
+        .with_coercion(&options.type_coercion)
+        .with_dedupe(options.dedupe.as_ref(), &output.temp_dir(&options))
+        .with_injected_fields(&options.inject_fields);
     let detected_hint = record_stream.container_format();
     let final_hint = container_hint.unwrap_or(detected_hint);
     let wrapper_metrics = record_stream.take_wrapper_metrics();
+    if let Some(hints) = record_stream.take_schema_hints() {
+        options.apply_schema_hints(&hints);
+    }
+    let ingest_counters = record_stream.counters();
+    let mut sampled = tune_zstd_level(&mut record_stream, &mut options)?;
+    require_records(&mut record_stream, &mut sampled.records, &options)?;
+    let level_tuning = sampled.tuning;
+    let sample = sampled.records;
 
-    let writer_target = output.into_writer()?;
-    let buf_writer = BufWriter::new(writer_target);
     let header = build_file_header(&options, Some(final_hint))?;
+    let (writer_target, mut parts) = output.open_parts(&options, &header, emit_index)?;
+    let buf_writer = BufWriter::new(writer_target);
 
-    let codec_opts = CompressOpts {
-        block_target_records: options.block_target_records,
-        default_codec: options.default_codec,
-        canonicalize_keys: options.canonicalize_keys,
-        canonicalize_numbers: options.canonicalize_numbers,
-        nested_opaque: options.nested_opaque,
-        max_dict_entries: options.max_dict_entries,
-        limits: options.limits.clone(),
-    };
+    let codec_opts = options.codec_opts();
 
     let builder_opts = codec_opts.clone();
     let mut writer = JacWriter::new(buf_writer, header, codec_opts)?;
+    writer.set_size_cap(options.max_output_bytes);
 
     let worker_codec = configure_codec_for_parallel(options.default_codec, true);
 
@@ -311,19 +324,22 @@ pub(crate) fn execute_compress_parallel(
         let builder_opts = builder_opts.clone();
         thread::Builder::new()
             .name("jac-builder".to_string())
-            .spawn(move || -> Result<()> {
+            .spawn(move || -> Result<bool> {
                 let mut block_idx = 0usize;
-                let mut builder = BlockBuilder::new(builder_opts.clone());
-                let mut stream = record_stream;
+                let mut builder = BlockBuilder::new(builder_opts);
+                let mut stream = sample.into_iter().map(Ok).chain(record_stream);
+                let mut interrupted = false;
 
                 while let Some(record_result) = stream.next() {
+                    if is_interrupted(interrupt.as_deref()) {
+                        interrupted = true;
+                        break;
+                    }
                     let record = record_result?;
                     match builder.try_add_record(record)? {
                         TryAddRecordOutcome::Added => {}
                         TryAddRecordOutcome::BlockFull { record } => {
-                            let full_builder =
-                                mem::replace(&mut builder, BlockBuilder::new(builder_opts.clone()));
-                            let uncompressed = full_builder.prepare_segments()?;
+                            let uncompressed = builder.prepare_segments_and_reset()?;
 
                             if uncompressed_tx.send((block_idx, uncompressed)).is_err() {
                                 return Err(JacError::Internal(
@@ -354,7 +370,7 @@ pub(crate) fn execute_compress_parallel(
                 }
 
                 drop(uncompressed_tx);
-                Ok(())
+                Ok(interrupted)
             })?
     };
 
@@ -415,7 +431,10 @@ pub(crate) fn execute_compress_parallel(
 
         if block_idx == next_block_idx {
             let record_count = block_finish.data.header.record_count as u64;
-            if let Err(err) = writer.write_compressed_block(block_finish) {
+            if let Err(err) = writer
+                .write_compressed_block(block_finish)
+                .and_then(|()| parts.continue_if_full(&mut writer))
+            {
                 let mut slot = compression_error.lock().unwrap();
                 if slot.is_none() {
                     *slot = Some(err);
@@ -428,7 +447,10 @@ pub(crate) fn execute_compress_parallel(
 
             while let Some(pending) = pending_blocks.remove(&next_block_idx) {
                 let record_count = pending.data.header.record_count as u64;
-                if let Err(err) = writer.write_compressed_block(pending) {
+                if let Err(err) = writer
+                    .write_compressed_block(pending)
+                    .and_then(|()| parts.continue_if_full(&mut writer))
+                {
                     let mut slot = compression_error.lock().unwrap();
                     if slot.is_none() {
                         *slot = Some(err);
@@ -450,9 +472,7 @@ pub(crate) fn execute_compress_parallel(
     let builder_result = builder_handle
         .join()
         .map_err(|e| JacError::Internal(format!("Builder thread panicked: {:?}", e)))?;
-    if let Err(err) = builder_result {
-        return Err(err);
-    }
+    let interrupted = builder_result?;
 
     let compress_result = compress_handle
         .join()
@@ -489,6 +509,14 @@ pub(crate) fn execute_compress_parallel(
         parallel_decision: None,
         runtime_stats,
         wrapper_metrics,
+        utf8_metrics: ingest_counters.utf8_metrics(),
+        oversized_records_skipped: ingest_counters.oversized_skipped(),
+        coercion_metrics: ingest_counters.coercion_metrics(),
+        key_normalization_metrics: ingest_counters.key_normalization_metrics(),
+        dedupe_metrics: ingest_counters.dedupe_metrics(),
+        level_tuning,
+        output_files: parts.files,
+        interrupted,
     })
 }
 
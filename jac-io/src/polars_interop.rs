@@ -0,0 +1,224 @@
+//! Polars conversions (feature `polars`).
+//!
+//! Column types come from the block directories and segment type tags, so eager
+//! and lazy reads agree on the schema: integer-only fields become `Int64`, other
+//! numeric fields `Float64`, boolean fields `Boolean`, fields that never hold a
+//! value `Null`, and everything else `String` (non-string values as JSON text).
+
+use crate::JacReader;
+use jac_codec::DecompressOpts;
+use jac_format::{JacError, Result, TypeTag};
+use polars::prelude::{
+    AnonymousScan, AnonymousScanArgs, DataFrame, DataType, Field, LazyFrame, NamedFrom,
+    PolarsError, PolarsResult, ScanArgsAnonymous, Schema, SchemaRef, Series,
+};
+use serde_json::Value;
+use std::any::Any;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+impl<R: Read + Seek> JacReader<R> {
+    /// Materialize the given fields as a polars `DataFrame`, one column per field.
+    ///
+    /// Records missing a field yield nulls. The reader is rewound afterwards.
+    pub fn to_polars_df<S: AsRef<str>>(&mut self, fields: &[S]) -> Result<DataFrame> {
+        let schema = self.polars_schema()?;
+        let fields: Vec<String> = fields.iter().map(|f| f.as_ref().to_string()).collect();
+        let dtypes: Vec<DataType> = fields
+            .iter()
+            .map(|field| schema.get(field).cloned().unwrap_or(DataType::Null))
+            .collect();
+
+        let columns = read_columns(self, &fields, None)?;
+        self.rewind()?;
+        build_frame(&fields, &dtypes, columns)
+    }
+
+    /// Polars schema covering every field in the file, sorted by name.
+    pub fn polars_schema(&mut self) -> Result<Schema> {
+        let catalog = self.field_catalog()?;
+        Ok(catalog
+            .into_iter()
+            .map(|entry| Field::new(&entry.name, polars_dtype(&entry.types)))
+            .collect())
+    }
+}
+
+/// Lazily scan a JAC file as a polars `LazyFrame`.
+///
+/// Only the schema is read up front. Blocks are decoded when the frame is
+/// collected; projection and slice pushdown limit decoding to the selected
+/// columns and to the blocks needed to satisfy a row limit.
+pub fn scan_jac(path: impl Into<PathBuf>, opts: DecompressOpts) -> Result<LazyFrame> {
+    let path = path.into();
+    let schema = Arc::new(JacReader::open(path.clone(), opts.clone())?.polars_schema()?);
+    let scan = JacScan {
+        path,
+        opts,
+        schema: Arc::clone(&schema),
+    };
+
+    LazyFrame::anonymous_scan(
+        Arc::new(scan),
+        ScanArgsAnonymous {
+            schema: Some(schema),
+            name: "JAC SCAN",
+            ..ScanArgsAnonymous::default()
+        },
+    )
+    .map_err(polars_error)
+}
+
+struct JacScan {
+    path: PathBuf,
+    opts: DecompressOpts,
+    schema: SchemaRef,
+}
+
+impl AnonymousScan for JacScan {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn scan(&self, scan_opts: AnonymousScanArgs) -> PolarsResult<DataFrame> {
+        let fields: Vec<String> = match &scan_opts.with_columns {
+            Some(columns) => columns.iter().cloned().collect(),
+            None => self
+                .schema
+                .iter_names()
+                .map(|name| name.to_string())
+                .collect(),
+        };
+        let dtypes: Vec<DataType> = fields
+            .iter()
+            .map(|field| self.schema.get(field).cloned().unwrap_or(DataType::Null))
+            .collect();
+
+        let frame = File::open(&self.path)
+            .map_err(JacError::from)
+            .and_then(|file| JacReader::new(file, self.opts.clone()))
+            .and_then(|mut reader| read_columns(&mut reader, &fields, scan_opts.n_rows))
+            .and_then(|columns| build_frame(&fields, &dtypes, columns));
+        frame.map_err(|e| PolarsError::ComputeError(e.to_string().into()))
+    }
+
+    fn schema(&self, _infer_schema_length: Option<usize>) -> PolarsResult<SchemaRef> {
+        Ok(Arc::clone(&self.schema))
+    }
+
+    fn allows_projection_pushdown(&self) -> bool {
+        true
+    }
+
+    fn allows_slice_pushdown(&self) -> bool {
+        true
+    }
+}
+
+/// Decode the requested columns block by block, stopping once `limit` rows are read.
+fn read_columns<R: Read + Seek>(
+    reader: &mut JacReader<R>,
+    fields: &[String],
+    limit: Option<usize>,
+) -> Result<Vec<Vec<Option<Value>>>> {
+    let limit = limit.unwrap_or(usize::MAX);
+    let mut columns: Vec<Vec<Option<Value>>> = vec![Vec::new(); fields.len()];
+    let mut rows = 0usize;
+
+    let blocks = reader.blocks().collect::<Result<Vec<_>>>()?;
+    for block in &blocks {
+        if rows >= limit {
+            break;
+        }
+        let take = block.record_count.min(limit - rows);
+        let decoder = reader.decode_block(block)?;
+        for (column, field) in columns.iter_mut().zip(fields) {
+            let mut values = decoder.project_field(field)?;
+            values.truncate(take);
+            column.extend(values);
+        }
+        rows += take;
+    }
+
+    Ok(columns)
+}
+
+fn build_frame(
+    fields: &[String],
+    dtypes: &[DataType],
+    columns: Vec<Vec<Option<Value>>>,
+) -> Result<DataFrame> {
+    let series = fields
+        .iter()
+        .zip(dtypes)
+        .zip(columns)
+        .map(|((name, dtype), values)| build_series(name, dtype, values))
+        .collect();
+    DataFrame::new(series).map_err(polars_error)
+}
+
+fn build_series(name: &str, dtype: &DataType, values: Vec<Option<Value>>) -> Series {
+    match dtype {
+        DataType::Int64 => {
+            let values: Vec<Option<i64>> = values
+                .iter()
+                .map(|v| v.as_ref().and_then(Value::as_i64))
+                .collect();
+            Series::new(name, values)
+        }
+        DataType::Float64 => {
+            let values: Vec<Option<f64>> = values
+                .iter()
+                .map(|v| v.as_ref().and_then(Value::as_f64))
+                .collect();
+            Series::new(name, values)
+        }
+        DataType::Boolean => {
+            let values: Vec<Option<bool>> = values
+                .iter()
+                .map(|v| v.as_ref().and_then(Value::as_bool))
+                .collect();
+            Series::new(name, values)
+        }
+        DataType::Null => Series::full_null(name, values.len(), &DataType::Null),
+        _ => {
+            let values: Vec<Option<String>> = values
+                .into_iter()
+                .map(|v| match v {
+                    None | Some(Value::Null) => None,
+                    Some(Value::String(s)) => Some(s),
+                    Some(other) => Some(other.to_string()),
+                })
+                .collect();
+            Series::new(name, values)
+        }
+    }
+}
+
+fn polars_dtype(types: &[TypeTag]) -> DataType {
+    let non_null: Vec<TypeTag> = types
+        .iter()
+        .copied()
+        .filter(|tag| *tag != TypeTag::Null)
+        .collect();
+    if non_null.is_empty() {
+        DataType::Null
+    } else if non_null.iter().all(|tag| *tag == TypeTag::Int) {
+        DataType::Int64
+    } else if non_null
+        .iter()
+        .all(|tag| matches!(tag, TypeTag::Int | TypeTag::Decimal))
+    {
+        DataType::Float64
+    } else if non_null.iter().all(|tag| *tag == TypeTag::Bool) {
+        DataType::Boolean
+    } else {
+        DataType::String
+    }
+}
+
+fn polars_error(error: PolarsError) -> JacError {
+    JacError::Internal(format!("polars error: {}", error))
+}
@@ -0,0 +1,307 @@
+//! Tiny SQL subset over JAC archives (feature `query`).
+//!
+//! Supported grammar (keywords are case-insensitive):
+//!
+//! ```text
+//! [SELECT] (* | field [, field]*) [WHERE predicate] [LIMIT n]
+//! ```
+//!
+//! where `predicate` is a [`filter`](crate::filter) expression. Fields may be
+//! double-quoted to use reserved words or punctuation.
+//!
+//! Queries are planned per block: blocks whose directory lacks a field required by
+//! the filter, or whose string dictionaries hold no value it accepts, are
+//! skipped without decoding (see [`Predicate::may_match`]). Only the selected
+//! and filtered fields are projected, and the filter is evaluated over their
+//! decoded columns.
+
+use crate::filter::{FilterSummary, Parser, Token};
+use crate::{DecompressOptions, JacInput, JacReader, OutputSink};
+use jac_codec::DecompressOpts;
+use jac_format::Result;
+use serde_json::{Map, Value};
+use std::io::{BufWriter, Read, Seek, Write};
+
+pub use crate::filter::{CompareOp, Predicate};
+
+/// Parsed query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    /// Fields to output.
+    pub select: Selection,
+    /// Optional row filter.
+    pub filter: Option<Predicate>,
+    /// Maximum number of rows to output.
+    pub limit: Option<usize>,
+}
+
+/// Output columns of a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    /// Every field of each record (`*`)
+    All,
+    /// Listed fields, in output order
+    Fields(Vec<String>),
+}
+
+impl Query {
+    /// Parse query text.
+    pub fn parse(text: &str) -> Result<Query> {
+        let mut parser = Parser::new(text, "query")?;
+        let query = parse_query(&mut parser)?;
+        if let Some(token) = parser.peek() {
+            return Err(parser.error(format!("unexpected {}", token.describe())));
+        }
+        Ok(query)
+    }
+}
+
+fn parse_query(parser: &mut Parser) -> Result<Query> {
+    parser.eat_keyword("select");
+
+    let select = if parser.eat(&Token::Star) {
+        Selection::All
+    } else {
+        let mut fields = vec![parser.field()?];
+        while parser.eat(&Token::Comma) {
+            fields.push(parser.field()?);
+        }
+        Selection::Fields(fields)
+    };
+
+    let filter = if parser.eat_keyword("where") {
+        Some(parser.predicate()?)
+    } else {
+        None
+    };
+
+    let limit = if parser.eat_keyword("limit") {
+        match parser.next() {
+            Some(Token::Number(Value::Number(n))) if n.as_u64().is_some() => {
+                Some(n.as_u64().unwrap() as usize)
+            }
+            _ => return Err(parser.error("LIMIT expects a non-negative integer".into())),
+        }
+    } else {
+        None
+    };
+
+    Ok(Query {
+        select,
+        filter,
+        limit,
+    })
+}
+
+/// Query request over a JAC file, writing matching rows as NDJSON.
+pub struct QueryRequest {
+    /// Source JAC file or reader.
+    pub input: JacInput,
+    /// Destination for NDJSON rows.
+    pub output: OutputSink,
+    /// Query to execute.
+    pub query: Query,
+    /// Decompression options.
+    pub options: DecompressOptions,
+}
+
+/// Summary returned after a query request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuerySummary {
+    /// Number of rows written to the sink.
+    pub rows_written: u64,
+    /// Blocks decoded and filtered row by row.
+    pub blocks_scanned: usize,
+    /// Blocks skipped from their directory or dictionary alone.
+    pub blocks_pruned: usize,
+    /// Work done by the `WHERE` clause (all zero without one), including
+    /// how many dictionary entries it tested and accepted.
+    pub filter: FilterSummary,
+}
+
+/// Execute a query request.
+pub fn execute_query(request: QueryRequest) -> Result<QuerySummary> {
+    let QueryRequest {
+        input,
+        output,
+        query,
+        options,
+    } = request;
+
+    let codec_opts = DecompressOpts {
+        limits: options.limits.clone(),
+        verify_checksums: options.verify_checksums,
+        strict: options.strict,
+        declared_limits: options.declared_limits,
+        ..DecompressOpts::default()
+    };
+    let mut reader = input.open(codec_opts, options.snapshot)?;
+    let mut buf_writer = BufWriter::new(output.into_writer()?);
+    let mut summary = QuerySummary::default();
+
+    query_reader(&mut reader, &query, &mut buf_writer, &mut summary)?;
+    buf_writer.flush()?;
+    Ok(summary)
+}
+
+/// Run `query` over one reader, adding to `summary`. The row limit counts rows
+/// already in `summary`, so several readers can share one query.
+pub(crate) fn query_reader<R: Read + Seek, W: Write>(
+    reader: &mut JacReader<R>,
+    query: &Query,
+    writer: &mut W,
+    summary: &mut QuerySummary,
+) -> Result<()> {
+    let filter = query.filter.as_ref();
+    let limit = query.limit.unwrap_or(usize::MAX);
+
+    let blocks = reader.blocks().collect::<Result<Vec<_>>>()?;
+    for block in &blocks {
+        if summary.rows_written as usize >= limit {
+            break;
+        }
+
+        let (rows, selected): (Box<dyn Iterator<Item = Map<String, Value>>>, _) = match &query
+            .select
+        {
+            Selection::All => {
+                if let Some(filter) = filter {
+                    if !reader.block_may_match(block, filter)? {
+                        summary.blocks_pruned += 1;
+                        summary.filter.blocks_pruned += 1;
+                        continue;
+                    }
+                    summary.filter.blocks_scanned += 1;
+                }
+                let decoder = reader.decode_block(block)?;
+                let selected = filter
+                    .map(|filter| filter.evaluate_block(&decoder, &mut summary.filter))
+                    .transpose()?;
+                if let Some(selected) = &selected {
+                    summary.filter.records_matched +=
+                        selected.iter().filter(|keep| **keep).count() as u64;
+                }
+                (Box::new(decoder.decode_records()?.into_iter()), selected)
+            }
+            Selection::Fields(fields) => {
+                let (columns, selected) = match filter {
+                    Some(filter) => {
+                        match reader.filter_columns(block, fields, filter, &mut summary.filter)? {
+                            Some((columns, selected)) => (columns, Some(selected)),
+                            None => {
+                                summary.blocks_pruned += 1;
+                                continue;
+                            }
+                        }
+                    }
+                    None => (reader.project_columns(block, fields)?, None),
+                };
+                let rows = (0..block.record_count).map(move |row| {
+                    let mut record = Map::new();
+                    for (field, column) in fields.iter().zip(&columns) {
+                        if let Some(value) = &column[row] {
+                            record.insert(field.clone(), value.clone());
+                        }
+                    }
+                    record
+                });
+                (Box::new(rows), selected)
+            }
+        };
+        summary.blocks_scanned += 1;
+
+        for (row, record) in rows.enumerate() {
+            if selected.as_ref().is_some_and(|selected| !selected[row]) {
+                continue;
+            }
+            serde_json::to_writer(&mut *writer, &Value::Object(record))?;
+            writer.write_all(b"\n")?;
+            summary.rows_written += 1;
+            if summary.rows_written as usize >= limit {
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_select_where_limit() {
+        let query = Query::parse(
+            "select ts, \"msg\" where level='ERROR' and (code >= 500 or retry is not null) limit 10",
+        )
+        .unwrap();
+        assert_eq!(
+            query.select,
+            Selection::Fields(vec!["ts".into(), "msg".into()])
+        );
+        assert_eq!(query.limit, Some(10));
+        assert_eq!(
+            query.filter,
+            Some(Predicate::And(
+                Box::new(Predicate::Compare {
+                    field: "level".into(),
+                    op: CompareOp::Eq,
+                    value: json!("ERROR"),
+                }),
+                Box::new(Predicate::Or(
+                    Box::new(Predicate::Compare {
+                        field: "code".into(),
+                        op: CompareOp::Ge,
+                        value: json!(500),
+                    }),
+                    Box::new(Predicate::IsNull {
+                        field: "retry".into(),
+                        negated: true,
+                    }),
+                )),
+            ))
+        );
+
+        let all = Query::parse("SELECT * WHERE NOT ok = true").unwrap();
+        assert_eq!(all.select, Selection::All);
+        assert_eq!(all.limit, None);
+    }
+
+    #[test]
+    fn rejects_malformed_queries() {
+        for text in [
+            "select",
+            "select a where",
+            "select a where b = null",
+            "select a limit -1",
+            "select a where b = 'open",
+            "select a b",
+            "select where",
+        ] {
+            assert!(Query::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn predicates_treat_missing_and_mismatched_values_as_false() {
+        let record = json!({"n": 7, "s": "b", "z": null});
+        let lookup = |field: &str| record.get(field);
+        let check = |text: &str| {
+            Query::parse(&format!("select n where {}", text))
+                .unwrap()
+                .filter
+                .unwrap()
+                .matches(&lookup)
+        };
+
+        assert!(check("n > 6.5"));
+        assert!(check("n <> 8"));
+        assert!(!check("n = 'seven'"));
+        assert!(check("s < 'c'"));
+        assert!(!check("missing != 1"));
+        assert!(check("missing is null and z is null"));
+        assert!(check("not s = 'a'"));
+    }
+}
@@ -0,0 +1,192 @@
+//! Decode metrics gathered on read paths.
+//!
+//! A [`JacReader`](crate::JacReader) counts the block bytes it reads and the
+//! blocks and segments it decompresses, and times the decoding work, so slow
+//! reads can be told apart from slow sinks. With the `latency-histogram`
+//! feature it also keeps a histogram of per-block decode latency.
+//!
+//! Segments are counted and timed where they are decoded, through the
+//! [`DecodeCounters`] of the reader's options, so a projection counts only
+//! the segments it touches and a block decoded by the caller after
+//! [`JacReader::decode_block`](crate::JacReader::decode_block) still counts.
+
+use jac_codec::DecodeCounters;
+use std::time::Duration;
+
+/// Decode work done by a reader.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReaderMetrics {
+    /// Block bytes read from the source (headers, segments and CRCs)
+    pub bytes_read: u64,
+    /// Blocks verified and decompressed
+    pub blocks_decoded: u64,
+    /// Field segments decompressed, including key-order sidecars
+    pub segments_decompressed: u64,
+    /// Time spent verifying blocks and decompressing and decoding their
+    /// segments
+    pub decode_time: Duration,
+    /// Decode latency of each block: verifying it, then decoding segments
+    /// until the next block is verified
+    #[cfg(feature = "latency-histogram")]
+    pub block_latency: LatencyHistogram,
+}
+
+/// Running [`ReaderMetrics`] of one reader.
+#[derive(Debug, Default)]
+pub(crate) struct MetricsTracker {
+    /// Bytes, blocks and block verification time; segment work is read from
+    /// `counters`
+    totals: ReaderMetrics,
+    counters: DecodeCounters,
+    /// Verification time of the latest block, and the segment time counted
+    /// before it
+    #[cfg(feature = "latency-histogram")]
+    current: Option<(Duration, Duration)>,
+}
+
+impl MetricsTracker {
+    pub(crate) fn new(counters: DecodeCounters) -> Self {
+        Self {
+            counters,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn add_bytes_read(&mut self, bytes: u64) {
+        self.totals.bytes_read += bytes;
+    }
+
+    /// Count a block verified in `elapsed`. Segments decoded from now until
+    /// the next block add to its latency.
+    pub(crate) fn start_block(&mut self, elapsed: Duration) {
+        self.totals.blocks_decoded += 1;
+        self.totals.decode_time += elapsed;
+        #[cfg(feature = "latency-histogram")]
+        {
+            if let Some(latency) = self.current_latency() {
+                self.totals.block_latency.record(latency);
+            }
+            self.current = Some((elapsed, self.counters.time()));
+        }
+    }
+
+    /// Metrics so far, the latest block included.
+    pub(crate) fn snapshot(&self) -> ReaderMetrics {
+        let mut metrics = self.totals.clone();
+        metrics.segments_decompressed = self.counters.segments();
+        metrics.decode_time += self.counters.time();
+        #[cfg(feature = "latency-histogram")]
+        if let Some(latency) = self.current_latency() {
+            metrics.block_latency.record(latency);
+        }
+        metrics
+    }
+
+    #[cfg(feature = "latency-histogram")]
+    fn current_latency(&self) -> Option<Duration> {
+        self.current
+            .map(|(verify, before)| verify + self.counters.time().saturating_sub(before))
+    }
+}
+
+/// Histogram with power-of-two microsecond buckets.
+///
+/// Bucket 0 counts latencies under 1µs; bucket `i` counts latencies in
+/// `[2^(i-1), 2^i)` µs. The last bucket also holds everything slower.
+#[cfg(feature = "latency-histogram")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: Vec<u64>,
+    count: u64,
+    max: Duration,
+}
+
+#[cfg(feature = "latency-histogram")]
+impl LatencyHistogram {
+    /// Buckets kept; the last one starts at 2^30 µs (about 18 minutes).
+    const BUCKETS: usize = 32;
+
+    /// Add one observation.
+    pub fn record(&mut self, latency: Duration) {
+        if self.buckets.is_empty() {
+            self.buckets = vec![0; Self::BUCKETS];
+        }
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+        self.buckets[bucket.min(Self::BUCKETS - 1)] += 1;
+        self.count += 1;
+        self.max = self.max.max(latency);
+    }
+
+    /// Observations recorded.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Slowest observation.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Non-empty buckets as (exclusive upper bound, count), fastest first.
+    pub fn buckets(&self) -> Vec<(Duration, u64)> {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(bucket, &count)| (Self::upper_bound(bucket), count))
+            .collect()
+    }
+
+    /// Upper bound of the bucket holding the `quantile` (0.0–1.0) observation,
+    /// or `None` when nothing was recorded.
+    pub fn quantile(&self, quantile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((self.count as f64 * quantile.clamp(0.0, 1.0)).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(Self::upper_bound(bucket).min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    fn upper_bound(bucket: usize) -> Duration {
+        if bucket == Self::BUCKETS - 1 {
+            Duration::MAX
+        } else {
+            Duration::from_micros(1 << bucket)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "latency-histogram"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_by_power_of_two_micros() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.quantile(0.5), None);
+        for micros in [0, 3, 3, 100, 5_000] {
+            histogram.record(Duration::from_micros(micros));
+        }
+        assert_eq!(histogram.count(), 5);
+        assert_eq!(histogram.max(), Duration::from_micros(5_000));
+        assert_eq!(
+            histogram.buckets(),
+            vec![
+                (Duration::from_micros(1), 1),
+                (Duration::from_micros(4), 2),
+                (Duration::from_micros(128), 1),
+                (Duration::from_micros(8_192), 1),
+            ]
+        );
+        assert_eq!(histogram.quantile(0.5), Some(Duration::from_micros(4)));
+        assert_eq!(histogram.quantile(1.0), Some(Duration::from_micros(5_000)));
+    }
+}
@@ -2,31 +2,150 @@
 
 use std::cmp::min;
 use std::convert::TryFrom;
-use std::io::{Read, Seek, SeekFrom};
-
-use jac_codec::{BlockDecoder, DecompressOpts, FieldSegmentDecoder};
-use jac_format::constants::{BLOCK_MAGIC, FILE_MAGIC, INDEX_MAGIC};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use bytes::Bytes;
+use jac_codec::{
+    read_dictionary_histogram, read_segment_tags, BlockData, BlockDecoder, DecodeCounters,
+    DecompressOpts, FieldSegmentDecoder, ValueRef,
+};
+use jac_format::checksum::{compute_crc32c, Crc32cHasher};
+use jac_format::constants::{
+    BLOCK_ALIGNMENT, BLOCK_MAGIC, ENCODING_FLAG_DELTA, ENCODING_FLAG_DICTIONARY, FILE_MAGIC,
+    INDEX_MAGIC,
+};
 use jac_format::varint::decode_uleb128;
 use jac_format::{
-    BlockHeader, BlockIndexEntry, FieldDirectoryEntry, FileHeader, IndexFooter, JacError, Limits,
-    Result,
+    BlockHeader, BlockHeaderFormat, BlockIndexEntry, DeclaredLimitsPolicy, DistinctSketch,
+    FieldDirectoryEntry, FieldNameTable, FileHeader, IndexFooter, JacError, Limits, QuantileSketch,
+    Result, TypeTag,
 };
 use serde_json::{Map, Value};
 
+use crate::column_cache::{Column, ColumnCache, ColumnCacheStats};
+use crate::filter::{FilterSummary, Predicate, ReaderBlockStats};
+use crate::fingerprint::{record_fingerprint, FingerprintCheck};
+use crate::read_metrics::{MetricsTracker, ReaderMetrics};
+use crate::schema_mapping::SchemaMapping;
+use crate::text_output::LineEnding;
+use crate::wrapper::pointer::PointerLimits;
+use crate::wrapper::utils::parse_pointer;
+
+/// How far back from the end of the file to search for an index footer when
+/// the trailing pointer does not lead to one.
+const FOOTER_SCAN_WINDOW: u64 = 1 << 20;
+
+/// Outcome of locating the index footer when a reader is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexStatus {
+    /// The trailing pointer leads to a valid footer.
+    Valid,
+    /// No footer was found; blocks are scanned from the start.
+    Absent,
+    /// The pointer was wrong but a valid footer was found near the end of the
+    /// file and is used instead.
+    Recovered {
+        /// Offset stored in the trailing pointer.
+        pointer: u64,
+        /// Offset the footer actually starts at.
+        found_at: u64,
+    },
+    /// A footer is present but unusable (corrupt, or its entries do not match
+    /// the blocks in front of it); blocks are scanned up to it instead.
+    Invalid {
+        /// Offset stored in the trailing pointer.
+        pointer: u64,
+    },
+}
+
+impl IndexStatus {
+    /// Human-readable warning with a repair suggestion, or `None` when the
+    /// footer is valid or simply absent.
+    pub fn warning(&self) -> Option<String> {
+        match self {
+            IndexStatus::Valid | IndexStatus::Absent => None,
+            IndexStatus::Recovered { pointer, found_at } => Some(format!(
+                "index pointer says {pointer} but the index footer starts at {found_at}; \
+                 run `jac index` or rewrite the file (e.g. `jac unpack` then `jac pack`) to \
+                 fix the pointer"
+            )),
+            IndexStatus::Invalid { pointer } => Some(format!(
+                "index footer (pointer {pointer}) does not match the blocks in the file and was \
+                 ignored; blocks are scanned instead. Run `jac index`, or rewrite the file \
+                 (e.g. `jac unpack` then `jac pack`), to rebuild the index"
+            )),
+        }
+    }
+}
+
+/// Outcome of reconciling the segment limit declared in the file header with
+/// the one passed in [`DecompressOpts::limits`], under
+/// [`DecompressOpts::declared_limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitsDecision {
+    /// Policy that was applied
+    pub policy: DeclaredLimitsPolicy,
+    /// Segment limit declared by the file, if any
+    pub declared_segment_limit: Option<usize>,
+    /// Segment limit the caller asked for
+    pub requested_segment_limit: usize,
+    /// Segment limit in effect
+    pub effective_segment_limit: usize,
+}
+
+impl LimitsDecision {
+    /// Whether the file's declaration replaced the requested limit.
+    pub fn file_applied(&self) -> bool {
+        self.effective_segment_limit != self.requested_segment_limit
+    }
+}
+
+struct IndexProbe {
+    index: Option<IndexFooter>,
+    index_offset: Option<u64>,
+    status: IndexStatus,
+}
+
 /// Streaming reader for JAC containers with optional index support
 pub struct JacReader<R: Read + Seek> {
     reader: R,
     file_header: FileHeader,
     index: Option<IndexFooter>,
     index_offset: Option<u64>,
+    index_status: IndexStatus,
     opts: DecompressOpts,
+    limits_decision: LimitsDecision,
     strict_mode: bool,
+    snapshot: bool,
     file_size: u64,
     data_start: u64,
+    /// Block header format, with the field names defined by blocks from
+    /// `names_start` up to `names_scanned_to` when those blocks intern them
+    block_format: BlockHeaderFormat,
+    names_start: u64,
+    names_scanned_to: u64,
+    /// In-memory archive behind `reader`, sliced directly when blocks are read
+    memory: Option<Bytes>,
+    /// Whether blocks of the current archive may be preceded by zero padding
+    /// (`FLAG_ALIGNED_BLOCKS`)
+    aligned_blocks: bool,
+    column_cache: Option<ColumnCache>,
+    /// Mapping applied to records returned by record streams
+    schema_mapping: Option<SchemaMapping>,
+    metrics: MetricsTracker,
 }
 
 impl<R: Read + Seek> JacReader<R> {
-    /// Create a new reader using default strict mode (errors stop iteration)
+    /// Create a new reader. With `opts.strict` (the default) structural
+    /// anomalies and corrupt blocks stop iteration; otherwise anomalies are
+    /// recorded as warnings and corrupt blocks are skipped.
     pub fn new(mut reader: R, opts: DecompressOpts) -> Result<Self> {
         let file_size = reader.seek(SeekFrom::End(0))?;
         reader.seek(SeekFrom::Start(0))?;
@@ -35,38 +154,98 @@ impl<R: Read + Seek> JacReader<R> {
         let data_start = file_header_len as u64;
         let after_header_pos = reader.stream_position()?;
 
-        let (index, index_offset) = Self::try_read_index(&mut reader, file_size)?;
+        let probe = Self::try_read_index(&mut reader, file_size, data_start)?;
 
         // Restore reader position to just after the file header so sequential reads start correctly
         reader.seek(SeekFrom::Start(after_header_pos))?;
 
+        let strict_mode = opts.strict;
         let mut opts = opts;
-        if let Some(limit) = crate::decode_segment_limit(&file_header.user_metadata) {
-            let default_limit = Limits::default().max_segment_uncompressed_len;
-            if opts.limits.max_segment_uncompressed_len == default_limit {
-                opts.limits.max_segment_uncompressed_len = limit;
-            }
-        }
+        // Count this reader's work only, whoever else holds the options
+        opts.counters = DecodeCounters::default();
+        let requested = opts.limits.max_segment_uncompressed_len;
+        let declared = crate::decode_segment_limit(&file_header.user_metadata);
+        opts.limits.max_segment_uncompressed_len = opts
+            .declared_limits
+            .resolve_segment_limit(requested, declared);
+        let limits_decision = LimitsDecision {
+            policy: opts.declared_limits,
+            declared_segment_limit: declared,
+            requested_segment_limit: requested,
+            effective_segment_limit: opts.limits.max_segment_uncompressed_len,
+        };
 
-        Ok(Self {
+        let block_format = BlockHeaderFormat::for_file(&file_header);
+        let mut jac_reader = Self {
             reader,
+            aligned_blocks: file_header.aligned_blocks(),
             file_header,
-            index,
-            index_offset,
+            block_format,
+            names_start: data_start,
+            names_scanned_to: data_start,
+            memory: None,
+            column_cache: None,
+            schema_mapping: None,
+            metrics: MetricsTracker::new(opts.counters.clone()),
+            index: probe.index,
+            index_offset: probe.index_offset,
+            index_status: probe.status,
             opts,
-            strict_mode: true,
+            limits_decision,
+            strict_mode,
+            snapshot: false,
             file_size,
             data_start,
-        })
+        };
+        jac_reader.reset_to_first_archive();
+        Ok(jac_reader)
+    }
+
+    /// Create a reader with explicit strict mode behaviour, overriding `opts.strict`
+    pub fn with_strict_mode(reader: R, mut opts: DecompressOpts, strict: bool) -> Result<Self> {
+        opts.strict = strict;
+        Self::new(reader, opts)
+    }
+
+    /// Anomalies tolerated so far in permissive mode, oldest first.
+    pub fn warnings(&self) -> Vec<String> {
+        self.opts.warnings.snapshot()
     }
 
-    /// Create a reader with explicit strict mode behaviour
-    pub fn with_strict_mode(reader: R, opts: DecompressOpts, strict: bool) -> Result<Self> {
+    /// Bytes read, blocks and segments decompressed, and decode time so far.
+    pub fn metrics(&self) -> ReaderMetrics {
+        self.metrics.snapshot()
+    }
+
+    /// Open a file that a writer may still be appending to.
+    ///
+    /// The file length is captured once, here, and nothing past it is read. A
+    /// valid index footer within that length is trusted as is (the writer has
+    /// finished). Without one, blocks are scanned from the start and iteration
+    /// ends cleanly at the first block that is not fully written yet, instead
+    /// of reporting `UnexpectedEof`. Corruption inside complete blocks is still
+    /// reported as usual.
+    pub fn snapshot(reader: R, opts: DecompressOpts) -> Result<Self> {
         let mut this = Self::new(reader, opts)?;
-        this.strict_mode = strict;
+        this.snapshot = true;
         Ok(this)
     }
 
+    /// Whether the file carries a valid index footer, i.e. its writer finished.
+    pub fn is_finalized(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// How the index footer was located when the reader was opened.
+    pub fn index_status(&self) -> IndexStatus {
+        self.index_status
+    }
+
+    /// Offset of the first block, right after the file header.
+    pub(crate) fn data_start(&self) -> u64 {
+        self.data_start
+    }
+
     /// Access the decoded file header
     pub fn file_header(&self) -> &FileHeader {
         &self.file_header
@@ -77,6 +256,40 @@ impl<R: Read + Seek> JacReader<R> {
         &self.opts.limits
     }
 
+    /// Limits declared in the file header metadata, or `None` when the file
+    /// declares none. Headers only record the segment limit; the other fields
+    /// are the defaults.
+    pub fn declared_limits(&self) -> Option<Limits> {
+        self.limits_decision
+            .declared_segment_limit
+            .map(|limit| Limits {
+                max_segment_uncompressed_len: limit,
+                ..Limits::default()
+            })
+    }
+
+    /// How the declared and requested limits were reconciled when the reader
+    /// was opened.
+    pub fn limits_decision(&self) -> LimitsDecision {
+        self.limits_decision
+    }
+
+    /// Decode options in effect, including limits taken from the file header.
+    pub(crate) fn decode_opts(&self) -> &DecompressOpts {
+        &self.opts
+    }
+
+    /// Block header format, with every field name defined so far.
+    pub(crate) fn block_format(&self) -> &BlockHeaderFormat {
+        &self.block_format
+    }
+
+    /// Offset of the first block of the archive whose field names
+    /// [`Self::block_format`] holds.
+    pub(crate) fn names_start(&self) -> u64 {
+        self.names_start
+    }
+
     /// Iterate over blocks in the file
     pub fn blocks(&mut self) -> BlockIterator<'_, R> {
         BlockIterator::new(self)
@@ -84,7 +297,16 @@ impl<R: Read + Seek> JacReader<R> {
 
     /// Stream all records lazily across the file.
     pub fn record_stream(&mut self) -> Result<RecordStream<'_, R>> {
-        RecordStream::new(self)
+        RecordStream::new(self, 0)
+    }
+
+    /// Stream records starting at the `offset`-th record (zero-based).
+    ///
+    /// Whole blocks before the offset are skipped using the index footer (or
+    /// their headers when there is none) without being decoded; only the block
+    /// containing the offset is decoded and trimmed.
+    pub fn record_stream_from(&mut self, offset: u64) -> Result<RecordStream<'_, R>> {
+        RecordStream::new(self, offset)
     }
 
     /// Stream projected values for the supplied field.
@@ -92,6 +314,28 @@ impl<R: Read + Seek> JacReader<R> {
         ProjectionStream::new(self, field)
     }
 
+    /// Stream aligned rows of projected values for several fields at once.
+    ///
+    /// Each item holds one entry per requested field, in request order; fields
+    /// absent from a record (or from a whole block) yield `None`.
+    pub fn multi_projection_stream<S: AsRef<str>>(
+        &mut self,
+        fields: &[S],
+    ) -> Result<MultiProjectionStream<'_, R>> {
+        self.multi_projection_stream_from(fields, 0)
+    }
+
+    /// Like [`JacReader::multi_projection_stream`], starting at the `offset`-th
+    /// record and skipping whole blocks before it without decoding them.
+    pub fn multi_projection_stream_from<S: AsRef<str>>(
+        &mut self,
+        fields: &[S],
+        offset: u64,
+    ) -> Result<MultiProjectionStream<'_, R>> {
+        let fields = fields.iter().map(|f| f.as_ref().to_string()).collect();
+        MultiProjectionStream::new(self, fields, offset)
+    }
+
     /// Restart projection/record iteration from the first block.
     pub fn restart_projection(&mut self) -> Result<()> {
         self.rewind()
@@ -108,32 +352,138 @@ impl<R: Read + Seek> JacReader<R> {
         self.reader
     }
 
+    /// Read blocks as slices of `memory`, which must hold the same bytes as
+    /// the underlying stream.
+    pub(crate) fn with_memory(mut self, memory: Bytes) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
     /// Read the entire block payload into memory
     pub fn read_block_bytes(&mut self, block: &BlockHandle) -> Result<Vec<u8>> {
-        self.reader.seek(SeekFrom::Start(block.offset))?;
-        let mut buf = vec![0u8; block.size];
-        self.reader.read_exact(&mut buf)?;
-        Ok(buf)
+        self.block_bytes(block).map(Vec::from)
+    }
+
+    /// The block's bytes: a slice of the archive when it is held in memory,
+    /// otherwise a fresh read.
+    fn block_bytes(&mut self, block: &BlockHandle) -> Result<Bytes> {
+        let bytes = match &self.memory {
+            Some(memory) => {
+                let start = usize::try_from(block.offset).map_err(|_| JacError::UnexpectedEof)?;
+                let end = start
+                    .checked_add(block.size)
+                    .filter(|&end| end <= memory.len())
+                    .ok_or(JacError::UnexpectedEof)?;
+                memory.slice(start..end)
+            }
+            None => {
+                self.reader.seek(SeekFrom::Start(block.offset))?;
+                let mut buf = vec![0u8; block.size];
+                self.reader.read_exact(&mut buf)?;
+                Bytes::from(buf)
+            }
+        };
+        self.metrics.add_bytes_read(bytes.len() as u64);
+        Ok(bytes)
     }
 
     /// Decode an entire block, verifying CRC and limits
     pub fn decode_block(&mut self, block: &BlockHandle) -> Result<BlockDecoder> {
-        let block_bytes = self.read_block_bytes(block)?;
-        BlockDecoder::new(&block_bytes, &self.opts)
+        self.scan_field_names_to(block.offset)?;
+        let block_bytes = self.block_bytes(block)?;
+        self.decode_block_bytes(&block_bytes)
+    }
+
+    fn decode_block_bytes(&mut self, block_bytes: &[u8]) -> Result<BlockDecoder> {
+        let start = Instant::now();
+        let decoder =
+            BlockDecoder::new_with_format(block_bytes, &self.opts, &mut self.block_format)?;
+        self.metrics.start_block(start.elapsed());
+        Ok(decoder)
+    }
+
+    /// Keep up to `budget_bytes` of decoded columns, keyed by block offset
+    /// and field, for later projections of the same blocks. Replaces any
+    /// existing cache; 0 disables caching.
+    ///
+    /// The size of a column is estimated from its decoded values. Columns
+    /// larger than the whole budget are never cached.
+    pub fn set_column_cache(&mut self, budget_bytes: usize) {
+        self.column_cache = (budget_bytes > 0).then(|| ColumnCache::new(budget_bytes));
+    }
+
+    /// Rewrite the records of record streams (and `record_channel`) into the
+    /// current schema with `mapping`; `None` returns them as stored.
+    /// Projections and field statistics keep seeing the stored field names.
+    pub fn set_schema_mapping(&mut self, mapping: Option<SchemaMapping>) {
+        self.schema_mapping = mapping.filter(|mapping| !mapping.is_empty());
+    }
+
+    /// Schema mapping applied by record streams, if any.
+    pub fn schema_mapping(&self) -> Option<&SchemaMapping> {
+        self.schema_mapping.as_ref()
+    }
+
+    /// Column cache counters; all zero when no cache is configured.
+    pub fn column_cache_stats(&self) -> ColumnCacheStats {
+        self.column_cache
+            .as_ref()
+            .map(ColumnCache::stats)
+            .unwrap_or_default()
     }
 
     /// Project a single field from the supplied block
     pub fn project_field(&mut self, block: &BlockHandle, field: &str) -> Result<FieldIterator> {
+        if let Some(values) = self.cached_column(block, field) {
+            return Ok(FieldIterator {
+                values,
+                current_idx: 0,
+            });
+        }
+
+        let decoder = self.field_segment_decoder(block, field)?;
+        let values: Column = Arc::new(
+            (0..block.record_count)
+                .map(|idx| decoder.get_value(idx))
+                .collect::<Result<_>>()?,
+        );
+        if let Some(cache) = &mut self.column_cache {
+            cache.insert(block.offset, field, &values);
+        }
+
+        Ok(FieldIterator {
+            values,
+            current_idx: 0,
+        })
+    }
+
+    /// Call `f` with the record index and borrowed value of every record of
+    /// `block` holding `field`, straight from the decoded segment (see
+    /// [`FieldSegmentDecoder::visit`]): no `Value` is built and the column
+    /// cache is bypassed. Stops at the first error `f` returns.
+    pub fn visit_field<F>(&mut self, block: &BlockHandle, field: &str, f: F) -> Result<()>
+    where
+        F: FnMut(usize, ValueRef<'_>) -> Result<()>,
+    {
+        self.field_segment_decoder(block, field)?.visit(f)
+    }
+
+    fn field_segment_decoder(
+        &mut self,
+        block: &BlockHandle,
+        field: &str,
+    ) -> Result<FieldSegmentDecoder> {
         // Validate block integrity first (CRC + layout)
-        let block_bytes = self.read_block_bytes(block)?;
-        BlockDecoder::new(&block_bytes, &self.opts)?;
+        let block_bytes = self.block_bytes(block)?;
+        self.scan_field_names_to(block.offset)?;
+        self.decode_block_bytes(&block_bytes)?;
 
         // Locate field entry
         let field_entry = block
             .header
             .fields
             .iter()
-            .find(|entry| entry.field_name == field)
+            .find(|entry| entry.field_name == field && !entry.is_key_order_sidecar())
             .ok_or_else(|| JacError::Internal(format!("Field '{}' not found in block", field)))?;
 
         let segment_offset = block.header_size + field_entry.segment_offset;
@@ -143,20 +493,573 @@ impl<R: Read + Seek> JacReader<R> {
         }
 
         let segment_bytes = &block_bytes[segment_offset..segment_end];
-        let decoder = FieldSegmentDecoder::new(
-            segment_bytes,
-            field_entry,
-            block.record_count,
-            &self.opts.limits,
-        )?;
+        FieldSegmentDecoder::with_opts(segment_bytes, field_entry, block.record_count, &self.opts)
+    }
 
-        Ok(FieldIterator {
-            decoder,
-            record_count: block.record_count,
-            current_idx: 0,
+    /// Decode `fields` of `block` as columns, one per field in order. A field
+    /// missing from the block yields a column of `None`.
+    ///
+    /// Columns come from the column cache when one is configured; the block
+    /// is decoded only if some field is not cached.
+    pub fn project_columns<S: AsRef<str>>(
+        &mut self,
+        block: &BlockHandle,
+        fields: &[S],
+    ) -> Result<Vec<Column>> {
+        self.project_columns_with(block, fields, &mut None)
+    }
+
+    /// [`project_columns`](Self::project_columns) decoding the block into
+    /// `decoder` unless an earlier step already did.
+    fn project_columns_with<S: AsRef<str>>(
+        &mut self,
+        block: &BlockHandle,
+        fields: &[S],
+        decoder: &mut Option<BlockDecoder>,
+    ) -> Result<Vec<Column>> {
+        let mut columns: Vec<Option<Column>> = fields
+            .iter()
+            .map(|field| self.cached_column(block, field.as_ref()))
+            .collect();
+        if columns.iter().any(Option::is_none) {
+            let decoder = match decoder {
+                Some(decoder) => decoder,
+                None => decoder.insert(self.decode_block(block)?),
+            };
+            for (field, column) in fields.iter().zip(&mut columns) {
+                if column.is_some() {
+                    continue;
+                }
+                let values: Column = Arc::new(decoder.project_field(field.as_ref())?);
+                if let Some(cache) = &mut self.column_cache {
+                    cache.insert(block.offset, field.as_ref(), &values);
+                }
+                *column = Some(values);
+            }
+        }
+        Ok(columns.into_iter().flatten().collect())
+    }
+
+    /// Whether any record of `block` can satisfy `predicate`, judged from the
+    /// block's field directory and string dictionaries without decoding it
+    /// (see [`Predicate::may_match`]).
+    pub fn block_may_match(&mut self, block: &BlockHandle, predicate: &Predicate) -> Result<bool> {
+        predicate.may_match(&mut ReaderBlockStats {
+            reader: self,
+            block,
         })
     }
 
+    /// Which records of `block` satisfy `predicate`, one flag per record, or
+    /// `None` when [`block_may_match`](Self::block_may_match) rules the block
+    /// out. The work done is added to `summary`.
+    pub fn filter_block(
+        &mut self,
+        block: &BlockHandle,
+        predicate: &Predicate,
+        summary: &mut FilterSummary,
+    ) -> Result<Option<Vec<bool>>> {
+        let no_fields: &[&str] = &[];
+        Ok(self
+            .filter_columns(block, no_fields, predicate, summary)?
+            .map(|(_, selected)| selected))
+    }
+
+    /// [`filter_block`](Self::filter_block) together with the columns of
+    /// `fields`, as [`project_columns`](Self::project_columns) gives them,
+    /// decoding the block at most once.
+    ///
+    /// When the column cache holds every field the predicate reads, it is
+    /// evaluated over those columns; otherwise over the decoded segments,
+    /// testing dictionary-encoded fields once per entry.
+    pub fn filter_columns<S: AsRef<str>>(
+        &mut self,
+        block: &BlockHandle,
+        fields: &[S],
+        predicate: &Predicate,
+        summary: &mut FilterSummary,
+    ) -> Result<Option<(Vec<Column>, Vec<bool>)>> {
+        if !self.block_may_match(block, predicate)? {
+            summary.blocks_pruned += 1;
+            return Ok(None);
+        }
+        summary.blocks_scanned += 1;
+
+        let mut decoder = None;
+        let filter_fields = predicate.fields();
+        let cached: Option<Vec<Column>> = filter_fields
+            .iter()
+            .map(|field| self.cached_column(block, field))
+            .collect();
+        let selected = match cached {
+            Some(columns) => {
+                let column = |name: &str| {
+                    filter_fields
+                        .iter()
+                        .position(|field| field == name)
+                        .map(|idx| columns[idx].as_slice())
+                };
+                predicate.evaluate(block.record_count, &column)
+            }
+            None => {
+                let decoder = decoder.insert(self.decode_block(block)?);
+                predicate.evaluate_block(decoder, summary)?
+            }
+        };
+        summary.records_matched += selected.iter().filter(|keep| **keep).count() as u64;
+        let columns = self.project_columns_with(block, fields, &mut decoder)?;
+        Ok(Some((columns, selected)))
+    }
+
+    fn cached_column(&mut self, block: &BlockHandle, field: &str) -> Option<Column> {
+        self.column_cache.as_mut()?.get(block.offset, field)
+    }
+
+    /// Total number of records in the file.
+    ///
+    /// Answered from the index footer when present; otherwise block headers
+    /// are scanned without decompressing any segment. The reader is rewound
+    /// afterwards.
+    pub fn count_records(&mut self) -> Result<u64> {
+        if let Some(index) = &self.index {
+            return Ok(index.blocks.iter().map(|b| b.record_count as u64).sum());
+        }
+        let mut total = 0u64;
+        let mut cursor = BlockCursor::new(self);
+        while let Some(block) = self.next_block_handle(&mut cursor) {
+            total += block?.record_count as u64;
+        }
+        self.rewind()?;
+        Ok(total)
+    }
+
+    /// Number of blocks in the file, from the index footer when present.
+    ///
+    /// Without an index, block headers are scanned. The reader is rewound
+    /// afterwards.
+    pub fn block_count(&mut self) -> Result<usize> {
+        if let Some(index) = &self.index {
+            return Ok(index.blocks.len());
+        }
+        let mut count = 0usize;
+        let mut cursor = BlockCursor::new(self);
+        while let Some(block) = self.next_block_handle(&mut cursor) {
+            block?;
+            count += 1;
+        }
+        self.rewind()?;
+        Ok(count)
+    }
+
+    /// Number of records in which `field` is present, explicit nulls included.
+    ///
+    /// Summed from the field directory of each block header; no segment is
+    /// decompressed. The reader is rewound afterwards.
+    pub fn count_present(&mut self, field: &str) -> Result<u64> {
+        let mut total = 0u64;
+        let mut cursor = BlockCursor::new(self);
+        while let Some(block) = self.next_block_handle(&mut cursor) {
+            if let Some(entry) = block?.field_entry(field) {
+                total += entry.value_count_present as u64;
+            }
+        }
+        self.rewind()?;
+        Ok(total)
+    }
+
+    /// Summarize every field across all blocks from the block directories.
+    ///
+    /// Value types are gathered by decompressing only each segment's tag prefix;
+    /// use [`JacReader::field_catalog_with`] to skip that and read headers alone.
+    /// The reader is rewound so later iteration starts at the first block.
+    pub fn field_catalog(&mut self) -> Result<Vec<FieldCatalogEntry>> {
+        self.field_catalog_with(&FieldCatalogOptions::default())
+    }
+
+    /// Build a field catalog with explicit options. Entries are sorted by name.
+    pub fn field_catalog_with(
+        &mut self,
+        options: &FieldCatalogOptions,
+    ) -> Result<Vec<FieldCatalogEntry>> {
+        let mut catalog: BTreeMap<String, FieldCatalogEntry> = BTreeMap::new();
+        let mut cursor = BlockCursor::new(self);
+        let mut block_idx = 0usize;
+
+        while let Some(block) = self.next_block_handle(&mut cursor) {
+            let block = block?;
+            for entry in &block.header.fields {
+                if entry.is_key_order_sidecar() {
+                    continue;
+                }
+                let summary = catalog.entry(entry.field_name.clone()).or_default();
+                summary.blocks.push(block_idx);
+                summary.value_count += entry.value_count_present as u64;
+                summary.compressed_bytes += entry.segment_compressed_len as u64;
+                summary.uncompressed_bytes += entry.segment_uncompressed_len as u64;
+
+                if options.include_types && entry.value_count_present > 0 {
+                    for tag in self.read_field_tags(&block, entry)? {
+                        if !summary.types.contains(&tag) {
+                            summary.types.push(tag);
+                        }
+                    }
+                }
+            }
+            block_idx += 1;
+        }
+
+        self.rewind()?;
+        Ok(catalog
+            .into_iter()
+            .map(|(name, mut entry)| {
+                entry.name = name;
+                entry.types.sort_by_key(|tag| *tag as u8);
+                entry
+            })
+            .collect())
+    }
+
+    /// Catalog entries of the fields matching `query`, sorted by name, or an
+    /// empty list when the file does not match: when a listed field is
+    /// missing, or does not store `field_type`. Segment tags are only read
+    /// when a type is asked for. The reader is rewound afterwards.
+    pub fn find_fields(&mut self, query: &FieldQuery) -> Result<Vec<FieldCatalogEntry>> {
+        let catalog = self.field_catalog_with(&FieldCatalogOptions {
+            include_types: query.field_type.is_some(),
+        })?;
+        let has_type = |entry: &FieldCatalogEntry| {
+            query
+                .field_type
+                .map_or(true, |tag| entry.types.contains(&tag))
+        };
+
+        if query.has_fields.is_empty() {
+            return Ok(catalog.into_iter().filter(has_type).collect());
+        }
+        let matched: Vec<_> = catalog
+            .into_iter()
+            .filter(|entry| query.has_fields.contains(&entry.name))
+            .collect();
+        let all_present = query
+            .has_fields
+            .iter()
+            .all(|name| matched.iter().any(|entry| &entry.name == name));
+        if all_present && matched.iter().all(has_type) {
+            Ok(matched)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Count occurrences of every distinct value of a field across all blocks.
+    ///
+    /// Dictionary-encoded string segments are answered by summing their index
+    /// histograms, so per-value strings are never materialized; other segments
+    /// are decoded. Block CRCs are not verified. The reader is rewound afterwards.
+    pub fn value_counts(&mut self, field: &str) -> Result<ValueCounts> {
+        let mut strings: HashMap<String, u64> = HashMap::new();
+        let mut others: HashMap<String, (Value, u64)> = HashMap::new();
+        let mut counts = ValueCounts::default();
+        let mut cursor = BlockCursor::new(self);
+
+        while let Some(block) = self.next_block_handle(&mut cursor) {
+            let block = block?;
+            match block.field_entry(field) {
+                Some(entry) if entry.value_count_present > 0 => {}
+                _ => continue,
+            }
+
+            let segment = self.read_raw_segment(&block, field)?;
+            let histogram = read_dictionary_histogram(
+                &segment.bytes,
+                &segment.entry,
+                block.record_count,
+                &self.opts.limits,
+            )?;
+            if let Some(histogram) = histogram {
+                for (entry, count) in histogram.entries.into_iter().zip(histogram.counts) {
+                    if count > 0 {
+                        *strings.entry(entry).or_default() += count;
+                    }
+                }
+                if histogram.nulls > 0 {
+                    others
+                        .entry(Value::Null.to_string())
+                        .or_insert((Value::Null, 0))
+                        .1 += histogram.nulls;
+                }
+                counts.dictionary_blocks += 1;
+                continue;
+            }
+
+            let decoder = FieldSegmentDecoder::with_opts(
+                &segment.bytes,
+                &segment.entry,
+                block.record_count,
+                &self.opts,
+            )?;
+            decoder.visit(|_, value| {
+                match value {
+                    // Repeated strings are counted without allocating them.
+                    ValueRef::Str(value) => match strings.get_mut(value) {
+                        Some(count) => *count += 1,
+                        None => {
+                            strings.insert(value.to_string(), 1);
+                        }
+                    },
+                    value => {
+                        let value = value.to_value()?;
+                        others.entry(value.to_string()).or_insert((value, 0)).1 += 1;
+                    }
+                }
+                Ok(())
+            })?;
+            counts.decoded_blocks += 1;
+        }
+
+        self.rewind()?;
+        counts.values = strings
+            .into_iter()
+            .map(|(value, count)| (Value::String(value), count))
+            .chain(others.into_values())
+            .collect();
+        counts
+            .values
+            .sort_by_cached_key(|(value, count)| (Reverse(*count), value.to_string()));
+        counts.total = counts.values.iter().map(|(_, count)| count).sum();
+        Ok(counts)
+    }
+
+    /// Combine the statistics stored in the field directories of every block
+    /// holding `field` (see [`crate::CompressOptions::field_stats`] and
+    /// [`crate::CompressOptions::quantile_sketches`]).
+    ///
+    /// Only block headers are read; no segment is decompressed. The reader
+    /// is rewound afterwards.
+    pub fn field_stats(&mut self, field: &str) -> Result<FieldStatsSummary> {
+        let mut summary = FieldStatsSummary::default();
+        let mut cursor = BlockCursor::new(self);
+        while let Some(block) = self.next_block_handle(&mut cursor) {
+            let block = block?;
+            let Some(entry) = block.field_entry(field) else {
+                continue;
+            };
+            summary.blocks += 1;
+            summary.present += entry.value_count_present as u64;
+            if let Some(quantiles) = &entry.quantiles {
+                summary.quantiles.push(quantiles.clone());
+            }
+            let Some(stats) = &entry.stats else {
+                continue;
+            };
+            summary.blocks_with_stats += 1;
+            summary.nulls += stats.null_count as u64;
+            match &mut summary.distinct {
+                Some(distinct) => distinct.merge(&stats.distinct)?,
+                None => summary.distinct = Some(stats.distinct.clone()),
+            }
+        }
+        self.rewind()?;
+        Ok(summary)
+    }
+
+    /// One record per field of every block describing its directory entry:
+    /// block position and size, segment sizes, encoding flags, and the
+    /// statistics and quantiles stored with the entry, if any.
+    ///
+    /// Blocks are numbered from 1 as in [`crate::BlockRange`]. Only block
+    /// headers are read, and the key-order sidecar is left out. The reader is
+    /// rewound afterwards.
+    pub fn directory_records(&mut self) -> Result<Vec<Map<String, Value>>> {
+        let mut records = Vec::new();
+        let mut cursor = BlockCursor::new(self);
+        let mut block_index = 0u64;
+        while let Some(block) = self.next_block_handle(&mut cursor) {
+            let block = block?;
+            block_index += 1;
+            for entry in &block.header.fields {
+                if entry.is_key_order_sidecar() {
+                    continue;
+                }
+                let mut record = Map::new();
+                record.insert("block".into(), block_index.into());
+                record.insert("block_offset".into(), block.offset.into());
+                record.insert("block_size".into(), block.size.into());
+                record.insert("block_records".into(), block.record_count.into());
+                record.insert("field".into(), entry.field_name.clone().into());
+                record.insert("compressor".into(), entry.compressor.into());
+                record.insert("compression_level".into(), entry.compression_level.into());
+                record.insert("present".into(), entry.value_count_present.into());
+                record.insert("encoding_flags".into(), entry.encoding_flags.into());
+                record.insert(
+                    "dictionary".into(),
+                    (entry.encoding_flags & ENCODING_FLAG_DICTIONARY != 0).into(),
+                );
+                record.insert(
+                    "delta".into(),
+                    (entry.encoding_flags & ENCODING_FLAG_DELTA != 0).into(),
+                );
+                record.insert("dict_entries".into(), entry.dict_entry_count.into());
+                record.insert("presence_bytes".into(), entry.presence_bytes.into());
+                record.insert("tag_bytes".into(), entry.tag_bytes.into());
+                record.insert("segment_offset".into(), entry.segment_offset.into());
+                record.insert(
+                    "compressed_bytes".into(),
+                    entry.segment_compressed_len.into(),
+                );
+                record.insert(
+                    "uncompressed_bytes".into(),
+                    entry.segment_uncompressed_len.into(),
+                );
+                if let Some(stats) = &entry.stats {
+                    record.insert("nulls".into(), stats.null_count.into());
+                    record.insert("approx_distinct".into(), stats.distinct.estimate().into());
+                }
+                if let Some(quantiles) = &entry.quantiles {
+                    record.insert("numeric_values".into(), quantiles.count().into());
+                    record.insert("min".into(), quantiles.min().into());
+                    record.insert("median".into(), quantiles.quantile(0.5).into());
+                    record.insert("max".into(), quantiles.max().into());
+                }
+                records.push(record);
+            }
+        }
+        self.rewind()?;
+        Ok(records)
+    }
+
+    /// Recompute every record's fingerprint and compare it with the one stored
+    /// in `field` (see [`crate::InjectFields::fingerprint`]).
+    ///
+    /// The stored field itself is always left out of the hash; list any other
+    /// fields injected at pack time (row ids, timestamps, source labels) in
+    /// `exclude`. The reader is rewound afterwards.
+    pub fn verify_fingerprints(
+        &mut self,
+        field: &str,
+        exclude: &[&str],
+    ) -> Result<FingerprintCheck> {
+        let mut ignored = Vec::with_capacity(exclude.len() + 1);
+        ignored.push(field);
+        ignored.extend_from_slice(exclude);
+
+        let mut check = FingerprintCheck::default();
+        for record in self.record_stream()? {
+            let record = record?;
+            match record.get(field) {
+                Some(Value::String(stored)) => {
+                    if *stored != record_fingerprint(&record, &ignored) {
+                        check.mismatched.push(check.records);
+                    }
+                }
+                _ => check.missing += 1,
+            }
+            check.records += 1;
+        }
+        self.rewind()?;
+        Ok(check)
+    }
+
+    /// Map each stored fingerprint in `field` to the zero-based indices of the
+    /// records carrying it, for joining or diffing against another file.
+    ///
+    /// Only the fingerprint column is decoded; records without a string
+    /// fingerprint are skipped. The reader is rewound afterwards.
+    pub fn fingerprint_index(&mut self, field: &str) -> Result<HashMap<String, Vec<u64>>> {
+        let mut index: HashMap<String, Vec<u64>> = HashMap::new();
+        for (idx, value) in self.projection_stream(field.to_string())?.enumerate() {
+            if let Some(Value::String(fingerprint)) = value? {
+                index.entry(fingerprint).or_default().push(idx as u64);
+            }
+        }
+        self.rewind()?;
+        Ok(index)
+    }
+
+    /// Read a block's header, compressed segments, and CRC without decompressing.
+    ///
+    /// The result can be handed to [`crate::JacWriter::write_raw_block`] to copy
+    /// the block into another file, which verifies it on the way through.
+    pub fn read_raw_block(&mut self, block: &BlockHandle) -> Result<BlockData> {
+        let bytes = self.block_bytes(block)?;
+        if bytes.len() < block.header_size + 4 {
+            return Err(JacError::CorruptBlock);
+        }
+
+        let crc_offset = bytes.len() - 4;
+        let mut crc32c = u32::from_le_bytes(bytes[crc_offset..].try_into().unwrap());
+
+        let mut segments = Vec::with_capacity(block.header.fields.len());
+        for entry in &block.header.fields {
+            let start = block.header_size + entry.segment_offset;
+            let end = start
+                .checked_add(entry.segment_compressed_len)
+                .ok_or(JacError::CorruptBlock)?;
+            if end > crc_offset {
+                return Err(JacError::CorruptBlock);
+            }
+            segments.push(bytes[start..end].to_vec());
+        }
+
+        if !self.block_format.is_plain() {
+            // The stored CRC covers the header as written; hand out the one
+            // for the plain encoding, which is what `BlockData` carries.
+            if compute_crc32c(&bytes[..crc_offset]) != crc32c {
+                return Err(JacError::ChecksumMismatch);
+            }
+            let mut plain = Crc32cHasher::new();
+            plain.update(&block.header.encode()?);
+            plain.update(&bytes[block.header_size..crc_offset]);
+            crc32c = plain.finalize();
+        }
+
+        Ok(BlockData {
+            header: block.header.clone(),
+            segments,
+            crc32c,
+        })
+    }
+
+    /// Read a field's compressed segment bytes and directory entry without decompressing.
+    ///
+    /// Only the segment's bounds within the block are checked; the block CRC is not
+    /// verified (use [`JacReader::decode_block`] for that).
+    pub fn read_raw_segment(&mut self, block: &BlockHandle, field: &str) -> Result<RawSegment> {
+        let entry = block
+            .field_entry(field)
+            .ok_or_else(|| JacError::Internal(format!("Field '{}' not found in block", field)))?
+            .clone();
+
+        self.seek_to_segment(block, &entry)?;
+        let mut bytes = vec![0u8; entry.segment_compressed_len];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(RawSegment { entry, bytes })
+    }
+
+    fn read_field_tags(
+        &mut self,
+        block: &BlockHandle,
+        entry: &FieldDirectoryEntry,
+    ) -> Result<Vec<TypeTag>> {
+        self.seek_to_segment(block, entry)?;
+        let segment = (&mut self.reader).take(entry.segment_compressed_len as u64);
+        read_segment_tags(segment, entry, block.record_count, &self.opts.limits)
+    }
+
+    fn seek_to_segment(&mut self, block: &BlockHandle, entry: &FieldDirectoryEntry) -> Result<()> {
+        let segment_start = block.header_size + entry.segment_offset;
+        let segment_end = segment_start
+            .checked_add(entry.segment_compressed_len)
+            .ok_or(JacError::CorruptBlock)?;
+        if segment_end > block.size {
+            return Err(JacError::CorruptBlock);
+        }
+
+        self.reader
+            .seek(SeekFrom::Start(block.offset + segment_start as u64))?;
+        Ok(())
+    }
+
     fn data_end(&self) -> u64 {
         self.index_offset.unwrap_or(self.file_size)
     }
@@ -193,55 +1096,200 @@ impl<R: Read + Seek> JacReader<R> {
         Ok((header, consumed))
     }
 
-    fn try_read_index(
-        reader: &mut R,
-        file_size: u64,
-    ) -> Result<(Option<IndexFooter>, Option<u64>)> {
-        if file_size < 8 {
-            return Ok((None, None));
+    fn try_read_index(reader: &mut R, file_size: u64, data_start: u64) -> Result<IndexProbe> {
+        let current_pos = reader.stream_position()?;
+        let probe = Self::probe_index(reader, file_size, data_start);
+        reader.seek(SeekFrom::Start(current_pos))?;
+        probe
+    }
+
+    fn probe_index(reader: &mut R, file_size: u64, data_start: u64) -> Result<IndexProbe> {
+        let absent = IndexProbe {
+            index: None,
+            index_offset: None,
+            status: IndexStatus::Absent,
+        };
+        if file_size < data_start.saturating_add(8) {
+            return Ok(absent);
         }
 
-        let current_pos = reader.stream_position()?;
         reader.seek(SeekFrom::End(-8))?;
         let mut pointer_bytes = [0u8; 8];
         reader.read_exact(&mut pointer_bytes)?;
-        let index_offset = u64::from_le_bytes(pointer_bytes);
-
-        if index_offset == 0 || index_offset >= file_size.saturating_sub(4) {
-            reader.seek(SeekFrom::Start(current_pos))?;
-            return Ok((None, None));
-        }
+        let pointer = u64::from_le_bytes(pointer_bytes);
+        let footer_end = file_size - 8;
 
         // Index occupies the region [index_offset, file_size - 8)
-        if index_offset >= file_size.saturating_sub(8) {
-            reader.seek(SeekFrom::Start(current_pos))?;
-            return Ok((None, None));
+        let mut pointer_hits_footer = false;
+        if pointer >= data_start && pointer < footer_end {
+            reader.seek(SeekFrom::Start(pointer))?;
+            let mut index_bytes = vec![0u8; (footer_end - pointer) as usize];
+            reader.read_exact(&mut index_bytes)?;
+            if index_bytes.len() >= 4 && index_bytes[0..4] == INDEX_MAGIC.to_le_bytes() {
+                pointer_hits_footer = true;
+                if let Some(index) = Self::decode_footer_candidate(&index_bytes) {
+                    if Self::index_is_plausible(reader, &index, pointer, data_start)? {
+                        return Ok(IndexProbe {
+                            index: Some(index),
+                            index_offset: Some(pointer),
+                            status: IndexStatus::Valid,
+                        });
+                    }
+                }
+            }
         }
 
-        let index_len = file_size - 8 - index_offset;
-        reader.seek(SeekFrom::Start(index_offset))?;
-        let mut index_bytes = vec![0u8; index_len as usize];
-        reader.read_exact(&mut index_bytes)?;
+        // The pointer does not lead to a usable footer. Look for one behind the
+        // pointer bytes (the footer is likely intact but the pointer is stale),
+        // nearest to the end first.
+        let window_start = footer_end
+            .saturating_sub(FOOTER_SCAN_WINDOW)
+            .max(data_start);
+        reader.seek(SeekFrom::Start(window_start))?;
+        let mut window = vec![0u8; (footer_end - window_start) as usize];
+        reader.read_exact(&mut window)?;
+        let magic = INDEX_MAGIC.to_le_bytes();
+        for pos in (0..window.len().saturating_sub(3)).rev() {
+            let found_at = window_start + pos as u64;
+            if window[pos..pos + 4] != magic || found_at == pointer {
+                continue;
+            }
+            let Some(index) = Self::decode_footer_candidate(&window[pos..]) else {
+                continue;
+            };
+            if Self::index_is_plausible(reader, &index, found_at, data_start)? {
+                return Ok(IndexProbe {
+                    index: Some(index),
+                    index_offset: Some(found_at),
+                    status: IndexStatus::Recovered { pointer, found_at },
+                });
+            }
+            // A complete footer whose blocks do not line up with it, e.g. the
+            // footer of the second of two concatenated files. Its entries
+            // cannot be trusted, but block scanning must still stop before it.
+            return Ok(IndexProbe {
+                index: None,
+                index_offset: Some(found_at),
+                status: IndexStatus::Invalid { pointer },
+            });
+        }
 
-        // Validate magic before attempting to decode fully
-        if index_bytes.len() < 4 || index_bytes[0..4] != INDEX_MAGIC.to_le_bytes() {
-            reader.seek(SeekFrom::Start(current_pos))?;
-            return Ok((None, None));
+        if pointer_hits_footer {
+            return Ok(IndexProbe {
+                index: None,
+                index_offset: Some(pointer),
+                status: IndexStatus::Invalid { pointer },
+            });
         }
+        Ok(absent)
+    }
 
-        let index = match IndexFooter::decode(&index_bytes) {
-            Ok(idx) => idx,
-            Err(_) => {
-                reader.seek(SeekFrom::Start(current_pos))?;
-                return Ok((None, None));
-            }
+    /// Decode a footer that must end exactly where the trailing pointer begins.
+    fn decode_footer_candidate(bytes: &[u8]) -> Option<IndexFooter> {
+        let index = IndexFooter::decode(bytes).ok()?;
+        let encoded_len = index.encode().ok()?.len();
+        (encoded_len == bytes.len()).then_some(index)
+    }
+
+    /// Check that index entries describe blocks laid out in front of the footer.
+    fn index_is_plausible(
+        reader: &mut R,
+        index: &IndexFooter,
+        index_offset: u64,
+        data_start: u64,
+    ) -> Result<bool> {
+        let Some(first) = index.blocks.first() else {
+            return Ok(index_offset == data_start);
         };
+        let mut expected_end = 0u64;
+        for entry in &index.blocks {
+            if entry.block_offset < data_start.max(expected_end) {
+                return Ok(false);
+            }
+            expected_end = match entry.block_offset.checked_add(entry.block_size as u64) {
+                Some(end) => end,
+                None => return Ok(false),
+            };
+        }
+        // The writer emits the footer directly after the last block.
+        if expected_end != index_offset {
+            return Ok(false);
+        }
 
-        reader.seek(SeekFrom::Start(current_pos))?;
-        Ok((Some(index), Some(index_offset)))
+        reader.seek(SeekFrom::Start(first.block_offset))?;
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        Ok(magic == BLOCK_MAGIC.to_le_bytes())
+    }
+
+    /// Restart field-name tracking at `start` (a first block boundary) of an
+    /// archive with file header `header`.
+    fn reset_block_format(&mut self, start: u64, header: &FileHeader) {
+        self.block_format = BlockHeaderFormat::for_file(header);
+        self.aligned_blocks = header.aligned_blocks();
+        self.names_start = start;
+        self.names_scanned_to = start;
+    }
+
+    /// Restart field-name tracking at the first archive. The field-name table
+    /// of the index footer, when there is one, covers every block up to the
+    /// footer, so no block header needs scanning.
+    fn reset_to_first_archive(&mut self) {
+        let header = self.file_header.clone();
+        self.reset_block_format(self.data_start, &header);
+        let table = self
+            .index
+            .as_ref()
+            .and_then(|index| index.field_names.clone());
+        if let (Some(names), Some(table)) = (self.block_format.field_names.as_mut(), table) {
+            *names = table;
+            self.names_scanned_to = self.data_end();
+        }
+    }
+
+    /// Field-name table for an index footer covering the blocks read so far:
+    /// `None` unless the file interns field names and those blocks all belong
+    /// to the first archive.
+    pub(crate) fn index_field_names(&self) -> Option<FieldNameTable> {
+        if self.names_start != self.data_start {
+            return None;
+        }
+        self.block_format.field_names.clone()
+    }
+
+    /// Make sure the field-name table holds the definitions of every block
+    /// before `offset`, reading skipped block headers in file order.
+    fn scan_field_names_to(&mut self, offset: u64) -> Result<()> {
+        if offset < self.names_start {
+            self.reset_to_first_archive();
+        }
+        while self.block_format.field_names.is_some() && self.names_scanned_to < offset {
+            let at = self.skip_block_padding(self.names_scanned_to);
+            self.names_scanned_to = at;
+            if at >= offset {
+                break;
+            }
+            if let Err(err) = self.read_block_handle_uncached(at) {
+                if self.skip_embedded_archive_start(at).is_some() {
+                    continue;
+                }
+                if self.strict_mode {
+                    return Err(err);
+                }
+                // Definitions from here on are lost; blocks relying on them
+                // fail to decode and are skipped like other corrupt blocks.
+                self.names_scanned_to = offset;
+            }
+        }
+        Ok(())
     }
 
     fn read_block_handle_at(&mut self, offset: u64) -> Result<BlockHandle> {
+        self.scan_field_names_to(offset)?;
+        self.read_block_handle_uncached(offset)
+    }
+
+    fn read_block_handle_uncached(&mut self, offset: u64) -> Result<BlockHandle> {
         if offset < self.data_start || offset >= self.data_end() {
             return Err(JacError::UnexpectedEof);
         }
@@ -272,7 +1320,11 @@ impl<R: Read + Seek> JacReader<R> {
         self.reader.read_exact(&mut rest)?;
         header_bytes.extend_from_slice(&rest);
 
-        let (header, consumed) = BlockHeader::decode(&header_bytes, &self.opts.limits)?;
+        let (header, consumed) = BlockHeader::decode_with_format(
+            &header_bytes,
+            &self.opts.limits,
+            &mut self.block_format,
+        )?;
         debug_assert_eq!(consumed, header_bytes.len());
 
         let segments_len = header.fields.iter().try_fold(0usize, |acc, field| {
@@ -296,6 +1348,9 @@ impl<R: Read + Seek> JacReader<R> {
 
         // Position reader at the end of this block so streaming iteration can continue
         self.reader.seek(SeekFrom::Start(next_offset))?;
+        if offset == self.names_scanned_to {
+            self.names_scanned_to = next_offset;
+        }
 
         Ok(BlockHandle {
             offset,
@@ -306,6 +1361,61 @@ impl<R: Read + Seek> JacReader<R> {
         })
     }
 
+    fn index_starts_at(&mut self, offset: u64) -> bool {
+        let mut magic = [0u8; 4];
+        self.reader.seek(SeekFrom::Start(offset)).is_ok()
+            && self.reader.read_exact(&mut magic).is_ok()
+            && magic == INDEX_MAGIC.to_le_bytes()
+    }
+
+    /// If an index footer, its pointer and another file header start at
+    /// `offset`, return the offset of the first block after them.
+    fn skip_embedded_archive_start(&mut self, offset: u64) -> Option<u64> {
+        let data_end = self.data_end();
+        let available = data_end.checked_sub(offset)?.min(FOOTER_SCAN_WINDOW);
+        if available < 4 || !self.index_starts_at(offset) {
+            return None;
+        }
+        self.reader.seek(SeekFrom::Start(offset)).ok()?;
+        let mut bytes = vec![0u8; available as usize];
+        self.reader.read_exact(&mut bytes).ok()?;
+        let footer_len = IndexFooter::decode(&bytes).ok()?.encode().ok()?.len() as u64;
+
+        let header_start = offset.checked_add(footer_len)?.checked_add(8)?;
+        if header_start >= data_end {
+            return None;
+        }
+        self.reader.seek(SeekFrom::Start(header_start)).ok()?;
+        let (header, header_len) = Self::read_file_header(&mut self.reader).ok()?;
+        let resume = header_start + header_len as u64;
+        if resume >= data_end {
+            return None;
+        }
+        // Field names are scoped to the embedded archive.
+        self.reset_block_format(resume, &header);
+        Some(resume)
+    }
+
+    /// Offset of the first non-zero byte at or after `offset`, when blocks are
+    /// aligned and fewer than `BLOCK_ALIGNMENT` zero bytes precede it. Any
+    /// other layout returns `offset` and is left to the block reader.
+    fn skip_block_padding(&mut self, offset: u64) -> u64 {
+        if !self.aligned_blocks {
+            return offset;
+        }
+        let available = self
+            .data_end()
+            .saturating_sub(offset)
+            .min(BLOCK_ALIGNMENT - 1);
+        let mut padding = vec![0u8; available as usize];
+        if self.reader.seek(SeekFrom::Start(offset)).is_err()
+            || self.reader.read_exact(&mut padding).is_err()
+        {
+            return offset;
+        }
+        offset + padding.iter().take_while(|&&byte| byte == 0).count() as u64
+    }
+
     fn resync_from(&mut self, start_offset: u64) -> Result<Option<u64>> {
         let mut offset = start_offset;
         let data_end = self.data_end();
@@ -355,6 +1465,63 @@ impl<R: Read + Seek> JacReader<R> {
     }
 }
 
+impl JacReader<Cursor<Bytes>> {
+    /// Open an archive held in memory, such as one received over the network.
+    /// Blocks are decoded from slices of `bytes` instead of being copied out.
+    pub fn from_bytes(bytes: Bytes, opts: DecompressOpts) -> Result<Self> {
+        Ok(Self::new(Cursor::new(bytes.clone()), opts)?.with_memory(bytes))
+    }
+}
+
+impl<R: Read + Seek + Send + 'static> JacReader<R> {
+    /// Decode records on a background thread and deliver them through a
+    /// channel holding at most `buffer_size` (minimum 1) undelivered records.
+    ///
+    /// Decoding pauses while the channel is full, so the consumer sets the pace.
+    /// The first error is delivered and ends the stream; dropping the receiver
+    /// stops the thread at the next record.
+    pub fn record_channel(self, buffer_size: usize) -> Receiver<Result<Map<String, Value>>> {
+        let (sender, receiver) = mpsc::sync_channel(buffer_size.max(1));
+        self.spawn_record_feeder(move |record| sender.send(record).is_ok());
+        receiver
+    }
+
+    /// Async counterpart of [`JacReader::record_channel`], for consumers on a
+    /// Tokio runtime. Decoding still runs on a dedicated thread.
+    #[cfg(feature = "async")]
+    pub fn record_channel_async(
+        self,
+        buffer_size: usize,
+    ) -> tokio::sync::mpsc::Receiver<Result<Map<String, Value>>> {
+        let (sender, receiver) = tokio::sync::mpsc::channel(buffer_size.max(1));
+        self.spawn_record_feeder(move |record| sender.blocking_send(record).is_ok());
+        receiver
+    }
+
+    /// Stream records into `send` on a new thread until it returns false, the
+    /// file ends, or an error has been sent.
+    fn spawn_record_feeder<F>(mut self, mut send: F)
+    where
+        F: FnMut(Result<Map<String, Value>>) -> bool + Send + 'static,
+    {
+        thread::spawn(move || {
+            let stream = match self.record_stream() {
+                Ok(stream) => stream,
+                Err(err) => {
+                    send(Err(err));
+                    return;
+                }
+            };
+            for record in stream {
+                let failed = record.is_err();
+                if !send(record) || failed {
+                    break;
+                }
+            }
+        });
+    }
+}
+
 /// Iterator over blocks in the file
 pub struct BlockIterator<'a, R: Read + Seek> {
     reader: &'a mut JacReader<R>,
@@ -415,6 +1582,46 @@ pub(crate) enum BlockIterMode {
 }
 
 impl<R: Read + Seek> JacReader<R> {
+    /// Advance `cursor` past leading blocks that hold no more than `records`
+    /// records in total, without decoding them. Returns how many records are
+    /// still to be skipped inside the next block.
+    pub(crate) fn skip_blocks(&mut self, cursor: &mut BlockCursor, records: u64) -> u64 {
+        let mut remaining = records;
+        match &mut cursor.mode {
+            BlockIterMode::Indexed {
+                entries,
+                cursor: idx,
+            } => {
+                while let Some(entry) = entries.get(*idx) {
+                    if entry.record_count as u64 > remaining {
+                        break;
+                    }
+                    remaining -= entry.record_count as u64;
+                    *idx += 1;
+                }
+            }
+            BlockIterMode::Streaming { next_offset } => {
+                let data_end = self.data_end();
+                while remaining > 0 && *next_offset < data_end {
+                    *next_offset = self.skip_block_padding(*next_offset);
+                    // Errors are left for `next_block_handle` to report or recover from.
+                    let Ok(handle) = self.read_block_handle_at(*next_offset) else {
+                        break;
+                    };
+                    if handle.record_count as u64 > remaining {
+                        break;
+                    }
+                    remaining -= handle.record_count as u64;
+                    *next_offset = handle
+                        .offset
+                        .checked_add(handle.size as u64)
+                        .unwrap_or(data_end);
+                }
+            }
+        }
+        remaining
+    }
+
     pub(crate) fn next_block_handle(
         &mut self,
         cursor: &mut BlockCursor,
@@ -446,6 +1653,7 @@ impl<R: Read + Seek> JacReader<R> {
             BlockIterMode::Streaming { next_offset } => {
                 let data_end = self.data_end();
                 loop {
+                    *next_offset = self.skip_block_padding(*next_offset);
                     if *next_offset >= data_end {
                         return None;
                     }
@@ -459,12 +1667,29 @@ impl<R: Read + Seek> JacReader<R> {
                             return Some(Ok(handle));
                         }
                         Err(err) => {
+                            if let Some(resume) = self.skip_embedded_archive_start(*next_offset) {
+                                // Archives concatenated into one file: step over
+                                // the earlier archive's footer and the next header.
+                                *next_offset = resume;
+                                continue;
+                            }
+                            if self.snapshot
+                                && (is_truncation(&err) || self.index_starts_at(*next_offset))
+                            {
+                                // The writer has not finished this block, or is
+                                // part way through writing the index footer.
+                                return None;
+                            }
                             if self.strict_mode {
                                 return Some(Err(err));
                             }
                             let start = next_offset.saturating_add(1);
                             match self.resync_from(start) {
                                 Ok(Some(new_offset)) => {
+                                    self.opts.warnings.push(format!(
+                                        "skipped unreadable block at offset {} ({err}); resumed at {new_offset}",
+                                        *next_offset
+                                    ));
                                     *next_offset = new_offset;
                                     continue;
                                 }
@@ -500,14 +1725,136 @@ impl BlockHandle {
         self.header
             .fields
             .iter()
-            .find(|entry| entry.field_name == field)
+            .find(|entry| entry.field_name == field && !entry.is_key_order_sidecar())
+    }
+}
+
+/// Compressed field segment as stored in a block
+#[derive(Debug, Clone)]
+pub struct RawSegment {
+    /// Directory entry describing the segment (compressor, sizes, encoding flags)
+    pub entry: FieldDirectoryEntry,
+    /// Compressed segment payload
+    pub bytes: Vec<u8>,
+}
+
+/// Options for [`JacReader::field_catalog_with`].
+#[derive(Debug, Clone)]
+pub struct FieldCatalogOptions {
+    /// Decompress each segment's tag prefix to report the value types seen
+    pub include_types: bool,
+}
+
+impl Default for FieldCatalogOptions {
+    fn default() -> Self {
+        Self {
+            include_types: true,
+        }
+    }
+}
+
+/// Conditions for [`JacReader::find_fields`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldQuery {
+    /// Fields that must all be present in the file
+    pub has_fields: Vec<String>,
+    /// Value type the matched fields must store. Without `has_fields`, every
+    /// field storing it matches.
+    pub field_type: Option<TypeTag>,
+}
+
+impl FieldQuery {
+    /// Whether the query has no conditions, matching every field.
+    pub fn is_empty(&self) -> bool {
+        self.has_fields.is_empty() && self.field_type.is_none()
+    }
+}
+
+/// Field summary merged across the block directories of a file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldCatalogEntry {
+    /// Field name
+    pub name: String,
+    /// Distinct value types stored for the field (empty when types were not requested)
+    pub types: Vec<TypeTag>,
+    /// Zero-based indices of the blocks containing the field
+    pub blocks: Vec<usize>,
+    /// Total number of present values
+    pub value_count: u64,
+    /// Total compressed segment bytes
+    pub compressed_bytes: u64,
+    /// Total uncompressed segment bytes
+    pub uncompressed_bytes: u64,
+}
+
+/// Value frequencies for a field, most frequent first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValueCounts {
+    /// Distinct values with their counts (ties ordered by JSON encoding)
+    pub values: Vec<(Value, u64)>,
+    /// Total number of present values
+    pub total: u64,
+    /// Blocks answered from dictionary index histograms
+    pub dictionary_blocks: usize,
+    /// Blocks whose values had to be decoded
+    pub decoded_blocks: usize,
+}
+
+impl ValueCounts {
+    /// Exact number of distinct values
+    pub fn distinct(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The `k` most frequent values
+    pub fn top(&self, k: usize) -> &[(Value, u64)] {
+        &self.values[..k.min(self.values.len())]
+    }
+}
+
+/// Field statistics of a file, from [`JacReader::field_stats`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldStatsSummary {
+    /// Blocks holding the field
+    pub blocks: usize,
+    /// Of those, blocks whose directory entry carries statistics
+    pub blocks_with_stats: usize,
+    /// Present values in all blocks holding the field, nulls included
+    pub present: u64,
+    /// Null values in blocks with statistics
+    pub nulls: u64,
+    /// Merged distinct-value sketch of blocks with statistics
+    pub distinct: Option<DistinctSketch>,
+    /// Quantile sketches of the numeric values, one per block carrying one
+    pub quantiles: Vec<QuantileSketch>,
+}
+
+impl FieldStatsSummary {
+    /// Whether every block holding the field carries statistics
+    pub fn complete(&self) -> bool {
+        self.blocks_with_stats == self.blocks
+    }
+
+    /// Estimated distinct non-null values in blocks with statistics
+    pub fn approx_distinct(&self) -> Option<u64> {
+        self.distinct.as_ref().map(DistinctSketch::estimate)
+    }
+
+    /// Numeric values summarized by the quantile sketches
+    pub fn numeric_values(&self) -> u64 {
+        self.quantiles.iter().map(QuantileSketch::count).sum()
+    }
+
+    /// Approximate numeric value at rank `q` (`0.0` is the minimum, `1.0`
+    /// the maximum) of blocks with quantile sketches
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        QuantileSketch::combined_quantile(&self.quantiles, q)
     }
 }
 
 /// Iterator over projected field values
 pub struct FieldIterator {
-    decoder: FieldSegmentDecoder,
-    record_count: usize,
+    values: Column,
     current_idx: usize,
 }
 
@@ -515,13 +1862,16 @@ impl Iterator for FieldIterator {
     type Item = Result<Option<serde_json::Value>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current_idx >= self.record_count {
+        let idx = self.current_idx;
+        if idx >= self.values.len() {
             return None;
         }
-
-        let idx = self.current_idx;
         self.current_idx += 1;
-        Some(self.decoder.get_value(idx))
+        // Values not shared with the column cache are moved out, not cloned.
+        Some(Ok(match Arc::get_mut(&mut self.values) {
+            Some(values) => values[idx].take(),
+            None => self.values[idx].clone(),
+        }))
     }
 }
 
@@ -532,21 +1882,144 @@ pub struct RecordStream<'a, R: Read + Seek> {
     blocks_seen: usize,
     total_blocks_hint: Option<usize>,
     current_records: Option<std::vec::IntoIter<Map<String, Value>>>,
+    skip: u64,
+    stored_remaining: Option<u64>,
+    required_pointer: Option<String>,
+    filter: Option<Predicate>,
+    filter_summary: FilterSummary,
 }
 
 impl<'a, R: Read + Seek> RecordStream<'a, R> {
-    pub(crate) fn new(reader: &'a mut JacReader<R>) -> Result<Self> {
-        let cursor = BlockCursor::new(reader);
+    pub(crate) fn new(reader: &'a mut JacReader<R>, offset: u64) -> Result<Self> {
+        let mut cursor = BlockCursor::new(reader);
         let total_blocks_hint = cursor.total_blocks;
+        let skip = reader.skip_blocks(&mut cursor, offset);
         Ok(Self {
             reader,
             cursor,
             blocks_seen: 0,
             total_blocks_hint,
             current_records: None,
+            skip,
+            stored_remaining: None,
+            required_pointer: None,
+            filter: None,
+            filter_summary: FilterSummary::default(),
         })
     }
 
+    /// Stop after `records` more stored records (after the offset), however
+    /// many of them the filters keep.
+    pub(crate) fn stop_after(&mut self, records: u64) {
+        self.stored_remaining = Some(records);
+    }
+
+    /// Only yield records holding a value at the RFC 6901 JSON `pointer`
+    /// (see [`BlockDecoder::pointer_matches`]), resolved against records as
+    /// stored, before the schema mapping. The stream's offset keeps counting
+    /// stored records.
+    pub fn require_pointer(&mut self, pointer: &str) -> Result<()> {
+        let limits = PointerLimits::hard_maximums();
+        parse_pointer(pointer, limits.max_pointer_length, limits.max_depth)
+            .map_err(|err| JacError::InvalidArgument(err.to_string()))?;
+        self.required_pointer = Some(pointer.to_string());
+        Ok(())
+    }
+
+    /// Only yield records satisfying `predicate`, evaluated against records
+    /// as stored, before the schema mapping. Blocks its statistics rule out
+    /// are skipped without decoding (see [`JacReader::block_may_match`]); the
+    /// stream's offset keeps counting stored records.
+    pub fn set_filter(&mut self, predicate: Predicate) {
+        self.filter = Some(predicate);
+    }
+
+    /// Work the filter has done so far.
+    pub fn filter_summary(&self) -> FilterSummary {
+        self.filter_summary
+    }
+
+    /// Decode the block behind `block` as the records still to be yielded:
+    /// offset skipped, filtered and mapped to the current schema.
+    fn decode_records(&mut self, block: &BlockHandle) -> Result<Vec<Map<String, Value>>> {
+        let Some(decoder) = self.decode_unless_pruned(block)? else {
+            self.take_window(block.record_count);
+            return Ok(Vec::new());
+        };
+        let selected = self.selected_records(&decoder)?;
+        let mut records = decoder.decode_records()?;
+        let window = self.take_window(records.len());
+        records.truncate(window.end);
+        records.drain(..window.start);
+        if let Some(selected) = selected {
+            let mut keep = selected[window].iter();
+            records.retain(|_| keep.next().copied().unwrap_or(false));
+        }
+        if let Some(mapping) = &self.reader.schema_mapping {
+            records = records
+                .into_iter()
+                .map(|record| mapping.apply(record))
+                .collect();
+        }
+        Ok(records)
+    }
+
+    /// Decode `block`, or `None` when the filter rules it out beforehand.
+    fn decode_unless_pruned(&mut self, block: &BlockHandle) -> Result<Option<BlockDecoder>> {
+        if let Some(filter) = &self.filter {
+            if !self.reader.block_may_match(block, filter)? {
+                self.filter_summary.blocks_pruned += 1;
+                return Ok(None);
+            }
+            self.filter_summary.blocks_scanned += 1;
+        }
+        self.blocks_seen += 1;
+        self.reader.decode_block(block).map(Some)
+    }
+
+    /// Records of `decoder` passing the pointer requirement and the filter,
+    /// or `None` when neither is set.
+    fn selected_records(&mut self, decoder: &BlockDecoder) -> Result<Option<Vec<bool>>> {
+        let mut selected = self
+            .required_pointer
+            .as_deref()
+            .map(|pointer| decoder.pointer_matches(pointer))
+            .transpose()?;
+        if let Some(filter) = &self.filter {
+            let matches = filter.evaluate_block(decoder, &mut self.filter_summary)?;
+            self.filter_summary.records_matched +=
+                matches.iter().filter(|keep| **keep).count() as u64;
+            selected = Some(match selected {
+                Some(mut selected) => {
+                    for (keep, matched) in selected.iter_mut().zip(matches) {
+                        *keep &= matched;
+                    }
+                    selected
+                }
+                None => matches,
+            });
+        }
+        Ok(selected)
+    }
+
+    /// Consume a block of `records` against the pending offset and the stored
+    /// record window, returning the range of its records still to consider.
+    fn take_window(&mut self, records: usize) -> std::ops::Range<usize> {
+        let skip = self.skip.min(records as u64);
+        self.skip -= skip;
+        let mut end = records as u64;
+        if let Some(remaining) = &mut self.stored_remaining {
+            end = end.min(skip + *remaining);
+            *remaining -= end - skip;
+        }
+        skip as usize..end as usize
+    }
+
+    /// Whether the stored record window is used up.
+    fn window_exhausted(&self) -> bool {
+        self.stored_remaining == Some(0)
+    }
+
     /// Hint for total blocks when an index footer is present; otherwise counts processed blocks.
     pub fn block_count(&self) -> usize {
         self.total_blocks_hint.unwrap_or(self.blocks_seen)
@@ -556,6 +2029,82 @@ impl<'a, R: Read + Seek> RecordStream<'a, R> {
     pub fn blocks_processed(&self) -> usize {
         self.blocks_seen
     }
+
+    /// Records left in the current block, or else all records of the next one.
+    ///
+    /// Batches follow block boundaries (the first one is trimmed by the
+    /// stream's offset). Returns `None` once the file is exhausted.
+    pub fn next_batch(&mut self) -> Option<Result<Vec<Map<String, Value>>>> {
+        if let Some(records) = self.current_records.take() {
+            let rest: Vec<_> = records.collect();
+            if !rest.is_empty() {
+                return Some(Ok(rest));
+            }
+        }
+        // `next` decodes the following block and yields its first record.
+        let first = match self.next()? {
+            Ok(record) => record,
+            Err(err) => return Some(Err(err)),
+        };
+        let mut batch = vec![first];
+        if let Some(records) = self.current_records.take() {
+            batch.extend(records);
+        }
+        Some(Ok(batch))
+    }
+
+    /// Write up to `limit` of the stream's remaining records to `out` as
+    /// NDJSON ending lines with `line_ending` or, with `array`, as one JSON
+    /// array, straight from the decoded
+    /// columns (see [`BlockDecoder::json_records`]). Returns how many records
+    /// were written.
+    ///
+    /// The schema mapping is not applied, and records already taken with
+    /// `next` are not revisited; callers use this in place of iterating.
+    pub(crate) fn write_json<W: Write>(
+        &mut self,
+        limit: usize,
+        out: &mut W,
+        array: bool,
+        line_ending: LineEnding,
+    ) -> Result<u64> {
+        let mut written = 0usize;
+        if array {
+            out.write_all(b"[")?;
+        }
+        while written < limit && !self.window_exhausted() {
+            let Some(block) = self.reader.next_block_handle(&mut self.cursor) else {
+                break;
+            };
+            let block = block?;
+            let Some(decoder) = self.decode_unless_pruned(&block)? else {
+                self.take_window(block.record_count);
+                continue;
+            };
+            let selected = self.selected_records(&decoder)?;
+            let records = decoder.json_records()?;
+            for idx in self.take_window(records.len()) {
+                if written == limit {
+                    break;
+                }
+                if selected.as_ref().is_some_and(|selected| !selected[idx]) {
+                    continue;
+                }
+                if array && written > 0 {
+                    out.write_all(b",")?;
+                }
+                records.write_record(idx, out)?;
+                if !array {
+                    out.write_all(line_ending.as_bytes())?;
+                }
+                written += 1;
+            }
+        }
+        if array {
+            out.write_all(b"]")?;
+        }
+        Ok(written as u64)
+    }
 }
 
 impl<'a, R: Read + Seek> Iterator for RecordStream<'a, R> {
@@ -568,18 +2117,16 @@ impl<'a, R: Read + Seek> Iterator for RecordStream<'a, R> {
                     return Some(Ok(record));
                 }
             }
+            if self.window_exhausted() {
+                return None;
+            }
 
-            match self.reader.next_block_handle(&mut self.cursor)? {
-                Ok(block) => match self.reader.decode_block(&block) {
-                    Ok(decoder) => match decoder.decode_records() {
-                        Ok(records) => {
-                            self.blocks_seen += 1;
-                            self.current_records = Some(records.into_iter());
-                        }
-                        Err(err) => return Some(Err(err)),
-                    },
-                    Err(err) => return Some(Err(err)),
-                },
+            let records = self
+                .reader
+                .next_block_handle(&mut self.cursor)?
+                .and_then(|block| self.decode_records(&block));
+            match records {
+                Ok(records) => self.current_records = Some(records.into_iter()),
                 Err(err) => return Some(Err(err)),
             }
         }
@@ -630,6 +2177,92 @@ impl<'a, R: Read + Seek> Iterator for ProjectionStream<'a, R> {
     }
 }
 
+/// Iterator yielding aligned values for several fields across the file.
+pub struct MultiProjectionStream<'a, R: Read + Seek> {
+    reader: &'a mut JacReader<R>,
+    fields: Vec<String>,
+    cursor: BlockCursor,
+    columns: Vec<std::vec::IntoIter<Option<Value>>>,
+    remaining: usize,
+    skip: u64,
+}
+
+impl<'a, R: Read + Seek> MultiProjectionStream<'a, R> {
+    pub(crate) fn new(
+        reader: &'a mut JacReader<R>,
+        fields: Vec<String>,
+        offset: u64,
+    ) -> Result<Self> {
+        if fields.is_empty() {
+            return Err(JacError::Internal(
+                "multi-field projection requires at least one field".to_string(),
+            ));
+        }
+        let mut cursor = BlockCursor::new(reader);
+        let skip = reader.skip_blocks(&mut cursor, offset);
+        Ok(Self {
+            reader,
+            fields,
+            cursor,
+            columns: Vec::new(),
+            remaining: 0,
+            skip,
+        })
+    }
+
+    /// Requested field names, in the order values appear in each row.
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    fn load_block(&mut self, block: &BlockHandle) -> Result<()> {
+        let projected = self.reader.project_columns(block, &self.fields)?;
+        let skip = self.skip.min(block.record_count as u64) as usize;
+        self.skip -= skip as u64;
+        let mut columns = Vec::with_capacity(self.fields.len());
+        for column in projected {
+            let mut values = Arc::try_unwrap(column).unwrap_or_else(|shared| (*shared).clone());
+            values.drain(..skip.min(values.len()));
+            columns.push(values.into_iter());
+        }
+        self.columns = columns;
+        self.remaining = block.record_count - skip;
+        Ok(())
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for MultiProjectionStream<'a, R> {
+    type Item = Result<Vec<Option<Value>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining == 0 {
+            let block = match self.reader.next_block_handle(&mut self.cursor)? {
+                Ok(block) => block,
+                Err(err) => return Some(Err(err)),
+            };
+            if let Err(err) = self.load_block(&block) {
+                return Some(Err(err));
+            }
+        }
+
+        self.remaining -= 1;
+        let row = self
+            .columns
+            .iter_mut()
+            .map(|column| column.next().flatten())
+            .collect();
+        Some(Ok(row))
+    }
+}
+
+fn is_truncation(err: &JacError) -> bool {
+    match err {
+        JacError::UnexpectedEof => true,
+        JacError::Io(io) => io.kind() == std::io::ErrorKind::UnexpectedEof,
+        _ => false,
+    }
+}
+
 fn find_magic(buffer: &[u8], magic: &[u8; 4]) -> Option<usize> {
     buffer.windows(4).position(|window| window == magic)
 }
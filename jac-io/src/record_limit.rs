@@ -0,0 +1,333 @@
+//! Size-bounded reads of raw NDJSON lines and JSON objects.
+//!
+//! Both readers stop buffering once a record exceeds the configured ceiling, so a
+//! pathological record never has to fit in memory: it is either rejected as soon
+//! as the limit is crossed or drained byte-by-byte and skipped.
+
+use jac_format::{JacError, Result};
+use std::io::BufRead;
+
+/// Outcome of a bounded read.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum BoundedRead {
+    /// The record fit; its bytes were appended to the caller's buffer.
+    Record,
+    /// The record exceeded the limit and was consumed without buffering.
+    Skipped,
+    /// End of input before any record bytes.
+    Eof,
+}
+
+/// Byte ceiling for one JSON object and the setting it comes from.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ObjectLimit {
+    pub(crate) bytes: usize,
+    pub(crate) setting: &'static str,
+}
+
+fn limit_error(limit: usize) -> JacError {
+    object_limit_error(ObjectLimit {
+        bytes: limit,
+        setting: "max_record_bytes",
+    })
+}
+
+pub(crate) fn object_limit_error(limit: ObjectLimit) -> JacError {
+    JacError::LimitExceeded(format!(
+        "Record exceeds {} ({} bytes)",
+        limit.setting, limit.bytes
+    ))
+}
+
+/// Read one line (terminator included) into `buffer`.
+///
+/// The limit applies to the line without its `\n`/`\r\n` terminator. With
+/// `skip == false` the error is raised as soon as the limit is crossed.
+pub(crate) fn read_line(
+    reader: &mut dyn BufRead,
+    buffer: &mut Vec<u8>,
+    limit: usize,
+    skip: bool,
+) -> Result<BoundedRead> {
+    let mut total = 0usize;
+    let mut read_any = false;
+    let mut oversized = false;
+
+    loop {
+        let (used, done) = {
+            let chunk = reader.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+            match chunk.iter().position(|&b| b == b'\n') {
+                Some(idx) => (idx + 1, true),
+                None => (chunk.len(), false),
+            }
+        };
+        read_any = true;
+        total += used;
+
+        if !oversized {
+            // Allow room for a "\r\n" terminator before the exact check below.
+            if total > limit.saturating_add(2) {
+                if !skip {
+                    return Err(limit_error(limit));
+                }
+                oversized = true;
+                buffer.clear();
+            } else {
+                buffer.extend_from_slice(&reader.fill_buf()?[..used]);
+            }
+        }
+        reader.consume(used);
+        if done {
+            break;
+        }
+    }
+
+    if !read_any {
+        return Ok(BoundedRead::Eof);
+    }
+    if !oversized && content_len(buffer) > limit {
+        if !skip {
+            return Err(limit_error(limit));
+        }
+        oversized = true;
+        buffer.clear();
+    }
+    Ok(if oversized {
+        BoundedRead::Skipped
+    } else {
+        BoundedRead::Record
+    })
+}
+
+fn content_len(line: &[u8]) -> usize {
+    let mut len = line.len();
+    if line[..len].ends_with(b"\n") {
+        len -= 1;
+    }
+    if line[..len].ends_with(b"\r") {
+        len -= 1;
+    }
+    len
+}
+
+/// Read one JSON object starting at the next byte (which must be `{`) into `buffer`.
+///
+/// Only string and nesting state is tracked; the bytes are validated later by the
+/// JSON parser. Truncated input is reported as [`JacError::UnexpectedEof`].
+///
+/// A string longer than `max_string_len` fails the read as soon as it is
+/// crossed, unless the object is already being skipped. Lengths are counted conservatively, with
+/// each escape sequence as one byte, so only strings that are certainly too
+/// long are rejected here.
+pub(crate) fn read_object(
+    reader: &mut dyn BufRead,
+    buffer: &mut Vec<u8>,
+    limit: ObjectLimit,
+    max_string_len: usize,
+    skip: bool,
+) -> Result<BoundedRead> {
+    let mut total = 0usize;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escape = false;
+    let mut hex_digits_left = 0u8;
+    let mut string_len = 0usize;
+    let mut oversized = false;
+
+    loop {
+        let (used, done) = {
+            let chunk = reader.fill_buf()?;
+            if chunk.is_empty() {
+                return Err(JacError::UnexpectedEof);
+            }
+            let mut end = None;
+            for (idx, &byte) in chunk.iter().enumerate() {
+                if in_string {
+                    if hex_digits_left > 0 {
+                        hex_digits_left -= 1;
+                        continue;
+                    }
+                    if escape {
+                        escape = false;
+                        if byte == b'u' {
+                            hex_digits_left = 4;
+                        }
+                    } else if byte == b'\\' {
+                        escape = true;
+                        continue;
+                    } else if byte == b'"' {
+                        in_string = false;
+                        continue;
+                    }
+                    string_len += 1;
+                    if string_len > max_string_len && !oversized {
+                        return Err(JacError::LimitExceeded(format!(
+                            "String exceeds max_string_len_per_value ({} bytes)",
+                            max_string_len
+                        )));
+                    }
+                    continue;
+                }
+                match byte {
+                    b'"' => {
+                        in_string = true;
+                        string_len = 0;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth = depth.saturating_sub(1);
+                        if depth == 0 {
+                            end = Some(idx + 1);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            match end {
+                Some(used) => (used, true),
+                None => (chunk.len(), false),
+            }
+        };
+        total += used;
+
+        if !oversized {
+            if total > limit.bytes {
+                if !skip {
+                    return Err(object_limit_error(limit));
+                }
+                oversized = true;
+                buffer.clear();
+            } else {
+                buffer.extend_from_slice(&reader.fill_buf()?[..used]);
+            }
+        }
+        reader.consume(used);
+        if done {
+            break;
+        }
+    }
+
+    Ok(if oversized {
+        BoundedRead::Skipped
+    } else {
+        BoundedRead::Record
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    fn record_limit(bytes: usize) -> ObjectLimit {
+        ObjectLimit {
+            bytes,
+            setting: "max_record_bytes",
+        }
+    }
+
+    #[test]
+    fn line_limit_ignores_terminator_and_skips_oversized() {
+        let input: &[u8] = b"{\"a\":1}\r\n{\"a\":\"long\"}\n{\"b\":2}";
+        let mut reader = BufReader::with_capacity(4, input);
+        let mut buf = Vec::new();
+
+        assert_eq!(
+            read_line(&mut reader, &mut buf, 7, true).unwrap(),
+            BoundedRead::Record
+        );
+        assert_eq!(buf, b"{\"a\":1}\r\n");
+        buf.clear();
+        assert_eq!(
+            read_line(&mut reader, &mut buf, 7, true).unwrap(),
+            BoundedRead::Skipped
+        );
+        assert!(buf.is_empty());
+        assert_eq!(
+            read_line(&mut reader, &mut buf, 7, true).unwrap(),
+            BoundedRead::Record
+        );
+        assert_eq!(buf, b"{\"b\":2}");
+        buf.clear();
+        assert_eq!(
+            read_line(&mut reader, &mut buf, 7, true).unwrap(),
+            BoundedRead::Eof
+        );
+    }
+
+    #[test]
+    fn line_limit_errors_without_skip() {
+        let mut reader: &[u8] = b"{\"a\":\"long\"}\n";
+        let err = read_line(&mut reader, &mut Vec::new(), 4, false).unwrap_err();
+        assert!(matches!(err, JacError::LimitExceeded(_)));
+    }
+
+    #[test]
+    fn object_reader_tracks_nesting_and_strings() {
+        let input: &[u8] = br#"{"a":{"b":"}]\""},"c":[1]},{"d":2}"#;
+        let mut reader = BufReader::with_capacity(3, input);
+        let mut buf = Vec::new();
+
+        assert_eq!(
+            read_object(&mut reader, &mut buf, record_limit(64), usize::MAX, true).unwrap(),
+            BoundedRead::Record
+        );
+        assert_eq!(buf, br#"{"a":{"b":"}]\""},"c":[1]}"#);
+
+        let mut rest = Vec::new();
+        reader.read_until(b'{', &mut rest).unwrap();
+        assert_eq!(rest, b",{");
+    }
+
+    #[test]
+    fn object_reader_skips_and_reports_truncation() {
+        let mut reader: &[u8] = br#"{"a":"0123456789"} ]"#;
+        let mut buf = Vec::new();
+        assert_eq!(
+            read_object(&mut reader, &mut buf, record_limit(8), usize::MAX, true).unwrap(),
+            BoundedRead::Skipped
+        );
+        assert_eq!(reader, b" ]");
+
+        let mut truncated: &[u8] = br#"{"a":1"#;
+        let err =
+            read_object(&mut truncated, &mut buf, record_limit(64), usize::MAX, true).unwrap_err();
+        assert!(matches!(err, JacError::UnexpectedEof));
+    }
+
+    #[test]
+    fn object_reader_rejects_long_strings_before_buffering_them() {
+        let input = format!(r#"{{"k":"{}"}}"#, "x".repeat(100));
+        let mut reader = BufReader::with_capacity(8, input.as_bytes());
+        let mut buf = Vec::new();
+        let err = read_object(&mut reader, &mut buf, record_limit(1024), 16, false).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("max_string_len_per_value (16 bytes)"),
+            "{}",
+            err
+        );
+        assert!(buf.len() < 32, "buffered {} bytes", buf.len());
+
+        // Escapes count as one byte each: "\u00e9\n" is two.
+        let mut reader: &[u8] = br#"{"k":"\u00e9\n"}"#;
+        assert_eq!(
+            read_object(&mut reader, &mut buf, record_limit(64), 2, false).unwrap(),
+            BoundedRead::Record
+        );
+
+        let mut reader: &[u8] = br#"{"a":"0123456789"}"#;
+        let limit = ObjectLimit {
+            bytes: 8,
+            setting: "max_block_uncompressed_total",
+        };
+        let err = read_object(&mut reader, &mut Vec::new(), limit, usize::MAX, false).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("max_block_uncompressed_total (8 bytes)"));
+    }
+}
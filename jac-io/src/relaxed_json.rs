@@ -0,0 +1,385 @@
+//! Relaxed JSON input: comments, trailing commas and non-finite numbers.
+//!
+//! Exported configuration files often contain `//` and `/* */` comments,
+//! commas before a closing bracket, or bare `NaN`/`Infinity`. With
+//! [`CompressOptions::relaxed_json`](crate::CompressOptions) set (and the
+//! `relaxed-json` feature enabled), NDJSON and JSON array input is rewritten
+//! into strict JSON on the fly, before the usual parsers and wrappers see it.
+//! Comments and dropped commas become spaces, with newlines kept, so line
+//! numbers in parse errors still match the input.
+
+use crate::InputSource;
+use jac_format::Result;
+use serde::{Deserialize, Serialize};
+
+/// Relaxations accepted in NDJSON and JSON array input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RelaxedJson {
+    /// Accept `//` line comments and `/* */` block comments
+    pub comments: bool,
+    /// Accept a comma before a closing `]` or `}`
+    pub trailing_commas: bool,
+    /// Handling of bare `NaN`, `Infinity` and `-Infinity`
+    pub non_finite: NonFiniteNumbers,
+}
+
+impl Default for RelaxedJson {
+    fn default() -> Self {
+        Self {
+            comments: true,
+            trailing_commas: true,
+            non_finite: NonFiniteNumbers::default(),
+        }
+    }
+}
+
+/// Handling of the bare words `NaN`, `Infinity` and `-Infinity`, which JSON
+/// itself cannot represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NonFiniteNumbers {
+    /// Leave them in place, so parsing fails as for strict JSON (default)
+    #[default]
+    Reject,
+    /// Replace them with `null`
+    Null,
+    /// Replace them with their name as a string (`"NaN"`, `"-Infinity"`)
+    String,
+}
+
+/// Wrap `source` so its bytes pass through the relaxed rewriter.
+#[cfg(feature = "relaxed-json")]
+pub(crate) fn apply(source: InputSource, config: RelaxedJson) -> Result<InputSource> {
+    use std::fs::File;
+    use std::io::BufReader;
+
+    let relax = |reader: Box<dyn std::io::Read + Send>| -> Box<dyn std::io::Read + Send> {
+        Box::new(RelaxedJsonReader::new(BufReader::new(reader), config))
+    };
+    Ok(match source {
+        InputSource::NdjsonPath(path) => {
+            InputSource::NdjsonReader(relax(Box::new(File::open(path)?)))
+        }
+        InputSource::JsonArrayPath(path) => {
+            InputSource::JsonArrayReader(relax(Box::new(File::open(path)?)))
+        }
+        InputSource::NdjsonReader(reader) => InputSource::NdjsonReader(relax(reader)),
+        InputSource::JsonArrayReader(reader) => InputSource::JsonArrayReader(relax(reader)),
+        other @ (InputSource::Iterator(_)
+        | InputSource::MessagePackReader(_)
+        | InputSource::BsonReader(_)) => other,
+    })
+}
+
+/// Without the `relaxed-json` feature relaxed input is rejected up front.
+#[cfg(not(feature = "relaxed-json"))]
+pub(crate) fn apply(_source: InputSource, _config: RelaxedJson) -> Result<InputSource> {
+    Err(jac_format::JacError::UnsupportedFeature(
+        "relaxed JSON input requires the `relaxed-json` feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "relaxed-json")]
+pub(crate) use reader::RelaxedJsonReader;
+
+#[cfg(feature = "relaxed-json")]
+mod reader {
+    use super::{NonFiniteNumbers, RelaxedJson};
+    use std::io::{self, BufRead, Read};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum State {
+        Normal,
+        String {
+            escape: bool,
+        },
+        /// A `/` that may open a comment
+        Slash,
+        LineComment,
+        BlockComment {
+            star: bool,
+        },
+    }
+
+    /// Rewriting state carried across input chunks.
+    struct Rewriter {
+        config: RelaxedJson,
+        state: State,
+        /// A comma whose fate depends on the next significant byte
+        pending_comma: bool,
+        /// Whitespace and blanked comments after the pending comma
+        held: Vec<u8>,
+        /// Bare word (or a leading `-`) collected outside strings
+        word: Vec<u8>,
+    }
+
+    impl Rewriter {
+        fn push_chunk(&mut self, chunk: &[u8], out: &mut Vec<u8>) {
+            for &byte in chunk {
+                self.push(byte, out);
+            }
+        }
+
+        fn push(&mut self, byte: u8, out: &mut Vec<u8>) {
+            match self.state {
+                State::String { escape } => {
+                    out.push(byte);
+                    self.state = match (escape, byte) {
+                        (false, b'\\') => State::String { escape: true },
+                        (false, b'"') => State::Normal,
+                        _ => State::String { escape: false },
+                    };
+                }
+                State::LineComment => {
+                    if byte == b'\n' {
+                        self.state = State::Normal;
+                    }
+                    self.blank(byte, out);
+                }
+                State::BlockComment { star } => {
+                    if star && byte == b'/' {
+                        self.state = State::Normal;
+                    } else {
+                        self.state = State::BlockComment { star: byte == b'*' };
+                    }
+                    self.blank(byte, out);
+                }
+                State::Slash => match byte {
+                    b'/' => {
+                        self.state = State::LineComment;
+                        self.blank(b'/', out);
+                        self.blank(byte, out);
+                    }
+                    b'*' => {
+                        self.state = State::BlockComment { star: false };
+                        self.blank(b'/', out);
+                        self.blank(byte, out);
+                    }
+                    _ => {
+                        self.state = State::Normal;
+                        self.significant(b'/', out);
+                        self.push(byte, out);
+                    }
+                },
+                State::Normal => self.push_normal(byte, out),
+            }
+        }
+
+        fn push_normal(&mut self, byte: u8, out: &mut Vec<u8>) {
+            if !self.word.is_empty() {
+                if byte.is_ascii_alphabetic() {
+                    self.word.push(byte);
+                    return;
+                }
+                self.finish_word(out);
+            }
+            match byte {
+                b'/' if self.config.comments => self.state = State::Slash,
+                b',' if self.config.trailing_commas => {
+                    self.flush_pending(false, out);
+                    self.pending_comma = true;
+                }
+                b'"' => {
+                    self.significant(byte, out);
+                    self.state = State::String { escape: false };
+                }
+                b'-' | b'N' | b'I' if self.config.non_finite != NonFiniteNumbers::Reject => {
+                    self.flush_pending(false, out);
+                    self.word.push(byte);
+                }
+                b' ' | b'\t' | b'\r' | b'\n' => self.whitespace(byte, out),
+                _ => self.significant(byte, out),
+            }
+        }
+
+        fn finish_word(&mut self, out: &mut Vec<u8>) {
+            let word = std::mem::take(&mut self.word);
+            match word.as_slice() {
+                b"NaN" | b"Infinity" | b"-Infinity" => match self.config.non_finite {
+                    NonFiniteNumbers::Null => out.extend_from_slice(b"null"),
+                    NonFiniteNumbers::String => {
+                        out.push(b'"');
+                        out.extend_from_slice(&word);
+                        out.push(b'"');
+                    }
+                    NonFiniteNumbers::Reject => out.extend_from_slice(&word),
+                },
+                _ => out.extend_from_slice(&word),
+            }
+        }
+
+        /// Emit `byte`, settling a pending comma first.
+        fn significant(&mut self, byte: u8, out: &mut Vec<u8>) {
+            self.flush_pending(matches!(byte, b']' | b'}'), out);
+            out.push(byte);
+        }
+
+        fn flush_pending(&mut self, closes: bool, out: &mut Vec<u8>) {
+            if self.pending_comma {
+                out.push(if closes { b' ' } else { b',' });
+                out.append(&mut self.held);
+                self.pending_comma = false;
+            }
+        }
+
+        fn whitespace(&mut self, byte: u8, out: &mut Vec<u8>) {
+            if self.pending_comma {
+                self.held.push(byte);
+            } else {
+                out.push(byte);
+            }
+        }
+
+        /// Replace a comment byte with a space, keeping line breaks.
+        fn blank(&mut self, byte: u8, out: &mut Vec<u8>) {
+            let blank = if byte == b'\n' { b'\n' } else { b' ' };
+            self.whitespace(blank, out);
+        }
+
+        fn finish(&mut self, out: &mut Vec<u8>) {
+            if self.state == State::Slash {
+                self.state = State::Normal;
+                self.significant(b'/', out);
+            }
+            if !self.word.is_empty() {
+                self.finish_word(out);
+            }
+            self.flush_pending(false, out);
+        }
+    }
+
+    /// `BufRead` adapter yielding strict JSON for relaxed input.
+    pub(crate) struct RelaxedJsonReader<R> {
+        inner: R,
+        rewriter: Rewriter,
+        out: Vec<u8>,
+        out_pos: usize,
+        done: bool,
+    }
+
+    impl<R: BufRead> RelaxedJsonReader<R> {
+        pub(crate) fn new(inner: R, config: RelaxedJson) -> Self {
+            Self {
+                inner,
+                rewriter: Rewriter {
+                    config,
+                    state: State::Normal,
+                    pending_comma: false,
+                    held: Vec::new(),
+                    word: Vec::new(),
+                },
+                out: Vec::new(),
+                out_pos: 0,
+                done: false,
+            }
+        }
+    }
+
+    impl<R: BufRead> BufRead for RelaxedJsonReader<R> {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            while self.out_pos >= self.out.len() && !self.done {
+                self.out.clear();
+                self.out_pos = 0;
+                let chunk = self.inner.fill_buf()?;
+                if chunk.is_empty() {
+                    self.rewriter.finish(&mut self.out);
+                    self.done = true;
+                    break;
+                }
+                let len = chunk.len();
+                self.rewriter.push_chunk(chunk, &mut self.out);
+                self.inner.consume(len);
+            }
+            Ok(&self.out[self.out_pos..])
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.out_pos = (self.out_pos + amt).min(self.out.len());
+        }
+    }
+
+    impl<R: BufRead> Read for RelaxedJsonReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let available = self.fill_buf()?;
+            let len = available.len().min(buf.len());
+            buf[..len].copy_from_slice(&available[..len]);
+            self.consume(len);
+            Ok(len)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "relaxed-json"))]
+mod tests {
+    use super::*;
+    use crate::{execute_compress, CompressOptions, CompressRequest, OutputSink};
+    use std::io::{BufReader, Cursor, Read};
+
+    fn rewrite(input: &str, config: RelaxedJson) -> String {
+        // A tiny buffer splits comments, words and escapes across chunks.
+        let mut reader =
+            RelaxedJsonReader::new(BufReader::with_capacity(3, input.as_bytes()), config);
+        let mut out = String::new();
+        reader.read_to_string(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn rewrites_comments_and_trailing_commas_into_strict_json() {
+        let input = "[\n  // first\n  {\"a\": \"x // y, /* z */\", \"b\": [1, 2,],}, /* gap */\n  {\"c\": 3},\n]";
+        let out = rewrite(input, RelaxedJson::default());
+        assert_eq!(out.lines().count(), input.lines().count());
+        let parsed: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(
+            parsed,
+            serde_json::json!([{"a": "x // y, /* z */", "b": [1, 2]}, {"c": 3}])
+        );
+        assert_eq!(
+            rewrite("[1, -2, 3/4]", RelaxedJson::default()),
+            "[1, -2, 3/4]"
+        );
+    }
+
+    #[test]
+    fn non_finite_numbers_follow_policy() {
+        let input = r#"{"a": NaN, "b": -Infinity, "c": [Infinity, -1], "d": "NaN", "e": null}"#;
+        let with = |non_finite| RelaxedJson {
+            non_finite,
+            ..RelaxedJson::default()
+        };
+        assert_eq!(rewrite(input, with(NonFiniteNumbers::Reject)), input);
+        let nulls: serde_json::Value =
+            serde_json::from_str(&rewrite(input, with(NonFiniteNumbers::Null))).unwrap();
+        assert_eq!(
+            nulls,
+            serde_json::json!({"a": null, "b": null, "c": [null, -1], "d": "NaN", "e": null})
+        );
+        let strings: serde_json::Value =
+            serde_json::from_str(&rewrite(input, with(NonFiniteNumbers::String))).unwrap();
+        assert_eq!(strings["b"], "-Infinity");
+        assert_eq!(strings["c"], serde_json::json!(["Infinity", -1]));
+    }
+
+    #[test]
+    fn compress_accepts_relaxed_json_array_input() {
+        let input =
+            "// exported settings\n[{\"id\": 1, \"ratio\": NaN,},\n {\"id\": 2}, // last\n]\n";
+        let request = CompressRequest::builder()
+            .input(InputSource::JsonArrayReader(Box::new(Cursor::new(
+                input.as_bytes().to_vec(),
+            ))))
+            .output(OutputSink::Writer(Box::new(Cursor::new(Vec::new()))))
+            .options(CompressOptions {
+                relaxed_json: Some(RelaxedJson {
+                    non_finite: NonFiniteNumbers::Null,
+                    ..RelaxedJson::default()
+                }),
+                ..CompressOptions::default()
+            })
+            .build()
+            .unwrap();
+        let summary = execute_compress(request).unwrap();
+        assert_eq!(summary.metrics.records_written, 2);
+    }
+}
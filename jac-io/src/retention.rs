@@ -0,0 +1,563 @@
+//! Retention rewrites dropping records older than a cutoff.
+//!
+//! [`execute_retention`] copies a `.jac` file without the records whose
+//! timestamp field is before [`RetentionPolicy::cutoff_millis`]. Blocks are
+//! decided as cheaply as their metadata allows: a block lacking the field is
+//! copied as is, a block whose quantile sketch (see
+//! [`CompressOptions::quantile_sketches`](crate::CompressOptions::quantile_sketches))
+//! puts every timestamp on one side of the cutoff is dropped or copied without
+//! decoding, and any other block has its timestamp column scanned. Only blocks
+//! holding both expired and retained records are decoded and re-encoded.
+
+use crate::{BlockHandle, JacReader, JacWriter};
+use jac_codec::{Codec, CompressOpts, DecompressOpts};
+use jac_format::constants::FLAG_CANONICALIZE_KEYS;
+use jac_format::{JacError, Result};
+use serde_json::Value;
+use std::io::{BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+/// Unit of numeric timestamps, counted from the Unix epoch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampUnit {
+    /// Seconds
+    Seconds,
+    /// Milliseconds, as written by
+    /// [`InjectFields::ingest_timestamp`](crate::InjectFields::ingest_timestamp)
+    #[default]
+    Millis,
+    /// Microseconds
+    Micros,
+    /// Nanoseconds
+    Nanos,
+}
+
+impl TimestampUnit {
+    /// Milliseconds in one unit.
+    fn millis(self) -> f64 {
+        match self {
+            TimestampUnit::Seconds => 1_000.0,
+            TimestampUnit::Millis => 1.0,
+            TimestampUnit::Micros => 1e-3,
+            TimestampUnit::Nanos => 1e-6,
+        }
+    }
+}
+
+/// Which records a retention rewrite keeps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Top-level field holding each record's timestamp
+    pub timestamp_field: String,
+    /// Records timestamped before this instant (Unix epoch milliseconds) are dropped
+    pub cutoff_millis: i64,
+    /// Unit of numeric timestamps; strings are read as RFC 3339
+    pub unit: TimestampUnit,
+}
+
+impl RetentionPolicy {
+    /// Whether a record whose timestamp field holds `value` is dropped.
+    /// Records without a readable timestamp are kept.
+    pub fn drops(&self, value: Option<&Value>) -> bool {
+        let millis = match value {
+            Some(Value::Number(number)) => number.as_f64().map(|n| self.number_millis(n)),
+            Some(Value::String(text)) => parse_rfc3339_millis(text).map(|ms| ms as f64),
+            _ => None,
+        };
+        millis.is_some_and(|millis| self.expired(millis))
+    }
+
+    fn number_millis(&self, value: f64) -> f64 {
+        value * self.unit.millis()
+    }
+
+    fn expired(&self, millis: f64) -> bool {
+        millis < self.cutoff_millis as f64
+    }
+}
+
+/// Retention rewrite of one file, run by [`execute_retention`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionRequest {
+    /// File to rewrite
+    pub input: PathBuf,
+    /// Where to write the retained records; `None` replaces the input
+    pub output: Option<PathBuf>,
+    /// Records to drop
+    pub policy: RetentionPolicy,
+}
+
+/// Outcome of [`execute_retention`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionSummary {
+    /// Blocks copied unchanged
+    pub blocks_kept: u64,
+    /// Blocks dropped whole
+    pub blocks_dropped: u64,
+    /// Blocks re-encoded without their expired records
+    pub blocks_filtered: u64,
+    /// Blocks kept or dropped from their directory entry alone, without
+    /// reading the timestamp column
+    pub blocks_decided_from_metadata: u64,
+    /// Records written
+    pub records_kept: u64,
+    /// Records dropped
+    pub records_dropped: u64,
+}
+
+/// What becomes of one block.
+enum BlockAction {
+    Keep,
+    Drop,
+    /// Keep the records whose flag is set
+    Filter(Vec<bool>),
+}
+
+/// Rewrite `request.input` without the records `request.policy` drops.
+///
+/// The rewritten file keeps the input's file header, so its codec, block
+/// size, field-name interning and header CRCs carry over; field statistics,
+/// quantile sketches and key-order sidecars are written for re-encoded blocks
+/// when the input has them. The records go to a new temporary file next to
+/// the output, which replaces the output only once the rewrite has finished,
+/// so an output naming the input (by any path) is rewritten safely.
+pub fn execute_retention(request: RetentionRequest) -> Result<RetentionSummary> {
+    let RetentionRequest {
+        input,
+        output,
+        policy,
+    } = request;
+    let target = output.unwrap_or_else(|| input.clone());
+    let temp_dir = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let temp = NamedTempFile::new_in(temp_dir)?;
+
+    let mut reader = JacReader::open(&input, DecompressOpts::default())?;
+    let header = reader.file_header().clone();
+    let blocks: Vec<BlockHandle> = reader.blocks().collect::<Result<_>>()?;
+    let entries = || blocks.iter().flat_map(|block| &block.header.fields);
+    let opts = CompressOpts {
+        block_target_records: header.block_size_hint_records.max(1),
+        default_codec: Codec::from_compressor_id(
+            header.default_compressor,
+            header.default_compression_level,
+        ),
+        canonicalize_keys: header.flags & FLAG_CANONICALIZE_KEYS != 0,
+        preserve_key_order: entries().any(|entry| entry.is_key_order_sidecar()),
+        field_stats: entries().any(|entry| entry.stats.is_some()),
+        quantile_sketches: entries().any(|entry| entry.quantiles.is_some()),
+        ..CompressOpts::default()
+    };
+
+    let file = BufWriter::new(temp.as_file().try_clone()?);
+    let mut writer = JacWriter::new(file, header, opts)?;
+    let mut summary = RetentionSummary::default();
+    let rewrite = (|| -> Result<()> {
+        for block in &blocks {
+            let records = block.record_count as u64;
+            let action = match block_action(block, &policy) {
+                Some(action) => {
+                    summary.blocks_decided_from_metadata += 1;
+                    action
+                }
+                None => scan_block(&mut reader, block, &policy)?,
+            };
+            match action {
+                BlockAction::Keep => {
+                    writer.write_raw_block(reader.read_raw_block(block)?)?;
+                    summary.blocks_kept += 1;
+                    summary.records_kept += records;
+                }
+                BlockAction::Drop => {
+                    summary.blocks_dropped += 1;
+                    summary.records_dropped += records;
+                }
+                BlockAction::Filter(keep) => {
+                    let decoded = reader.decode_block(block)?.decode_records()?;
+                    let mut kept = 0;
+                    for (record, keep) in decoded.iter().zip(keep) {
+                        if keep {
+                            writer.write_record(record)?;
+                            kept += 1;
+                        }
+                    }
+                    summary.blocks_filtered += 1;
+                    summary.records_kept += kept;
+                    summary.records_dropped += records - kept;
+                }
+            }
+        }
+        writer.finish_with_index()?.writer.flush()?;
+        Ok(())
+    })();
+    drop(reader);
+
+    // On error the temporary file is removed as `temp` drops
+    rewrite?;
+    temp.persist(&target).map_err(|err| err.error)?;
+    Ok(summary)
+}
+
+/// Decide a block from its directory entry for the timestamp field, when
+/// that settles every record.
+fn block_action(block: &BlockHandle, policy: &RetentionPolicy) -> Option<BlockAction> {
+    let Some(entry) = block.field_entry(&policy.timestamp_field) else {
+        // No record has a timestamp to expire
+        return Some(BlockAction::Keep);
+    };
+    let quantiles = entry.quantiles.as_ref()?;
+    if quantiles.count() == block.record_count as u64
+        && policy.expired(policy.number_millis(quantiles.max()))
+    {
+        return Some(BlockAction::Drop);
+    }
+    // Present values that are not numbers stay out of the sketch; only a
+    // sketch of all of them vouches for the whole block
+    if quantiles.count() == entry.value_count_present as u64
+        && !policy.expired(policy.number_millis(quantiles.min()))
+    {
+        return Some(BlockAction::Keep);
+    }
+    None
+}
+
+/// Decide a block from its timestamp column.
+fn scan_block<R: Read + Seek>(
+    reader: &mut JacReader<R>,
+    block: &BlockHandle,
+    policy: &RetentionPolicy,
+) -> Result<BlockAction> {
+    let keep: Vec<bool> = reader
+        .project_field(block, &policy.timestamp_field)?
+        .map(|value| value.map(|value| !policy.drops(value.as_ref())))
+        .collect::<Result<_>>()?;
+    if keep.len() != block.record_count {
+        return Err(JacError::CorruptBlock);
+    }
+    Ok(if keep.iter().all(|keep| *keep) {
+        BlockAction::Keep
+    } else if keep.iter().all(|keep| !*keep) {
+        BlockAction::Drop
+    } else {
+        BlockAction::Filter(keep)
+    })
+}
+
+/// Unix epoch milliseconds of an RFC 3339 timestamp such as
+/// `2024-05-01T12:00:00Z` or `2024-05-01 14:00:00.25+02:00`; digits past
+/// milliseconds are truncated, and a bare `2024-05-01` is midnight UTC.
+pub fn parse_rfc3339_millis(text: &str) -> Option<i64> {
+    let bytes = text.as_bytes();
+    if bytes.len() < 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    let (year, month, day) = (
+        digits(&bytes[..4])?,
+        digits(&bytes[5..7])?,
+        digits(&bytes[8..10])?,
+    );
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    let mut millis = days_from_civil(year, month, day) * 86_400_000;
+    if bytes.len() == 10 {
+        return Some(millis);
+    }
+
+    if bytes.len() < 20
+        || !matches!(bytes[10], b'T' | b't' | b' ')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+    let (hour, minute, second) = (
+        digits(&bytes[11..13])?,
+        digits(&bytes[14..16])?,
+        digits(&bytes[17..19])?,
+    );
+    // Second 60 is a leap second
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    millis += ((hour * 60 + minute) * 60 + second) * 1_000;
+
+    let mut rest = &text[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return None;
+        }
+        let ms: String = fraction[..len]
+            .chars()
+            .chain("00".chars())
+            .take(3)
+            .collect();
+        millis += digits(ms.as_bytes())?;
+        rest = &fraction[len..];
+    }
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            if rest.len() != 6 || rest.as_bytes()[3] != b':' {
+                return None;
+            }
+            let zone = rest.as_bytes();
+            let (hours, minutes) = (digits(&zone[1..3])?, digits(&zone[4..6])?);
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            sign * (hours * 60 + minutes) * 60_000
+        }
+    };
+    Some(millis - offset)
+}
+
+/// Value of a run of ASCII digits. Works on bytes so that multi-byte characters
+/// in a fixed-width field are rejected instead of splitting a `str`.
+fn digits(bytes: &[u8]) -> Option<i64> {
+    if bytes.is_empty() || !bytes.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    bytes.iter().try_fold(0i64, |value, byte| {
+        value.checked_mul(10)?.checked_add(i64::from(byte - b'0'))
+    })
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days from 1970-01-01 to a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{execute_compress, CompressOptions, CompressRequest, InputSource, OutputSink};
+    use serde_json::{json, Map};
+    use std::fs;
+
+    const DAY: i64 = 86_400_000;
+
+    fn pack(path: &std::path::Path, records: Vec<Map<String, Value>>, quantiles: bool) {
+        execute_compress(CompressRequest {
+            input: InputSource::Iterator(Box::new(records.into_iter())),
+            output: OutputSink::Path(path.to_path_buf()),
+            options: CompressOptions {
+                block_target_records: 10,
+                quantile_sketches: quantiles,
+                ..CompressOptions::default()
+            },
+            ..CompressRequest::default()
+        })
+        .unwrap();
+    }
+
+    fn policy(cutoff_millis: i64) -> RetentionPolicy {
+        RetentionPolicy {
+            timestamp_field: "ts".to_string(),
+            cutoff_millis,
+            unit: TimestampUnit::Millis,
+        }
+    }
+
+    fn ids(path: &std::path::Path) -> Vec<Value> {
+        JacReader::open(path, DecompressOpts::default())
+            .unwrap()
+            .record_stream()
+            .unwrap()
+            .map(|record| record.unwrap()["id"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn parses_rfc3339_timestamps() {
+        assert_eq!(parse_rfc3339_millis("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_rfc3339_millis("1970-01-02"), Some(DAY));
+        assert_eq!(
+            parse_rfc3339_millis("2024-02-29T12:30:00.25Z"),
+            Some(1_709_209_800_250)
+        );
+        assert_eq!(
+            parse_rfc3339_millis("2024-02-29 14:30:00.250999+02:00"),
+            Some(1_709_209_800_250)
+        );
+        assert_eq!(
+            parse_rfc3339_millis("1969-12-31T23:59:59-00:00"),
+            Some(-1_000)
+        );
+        for text in [
+            "2023-02-29",
+            "2024-13-01T00:00:00Z",
+            "2024-01-01T00:00:00",
+            "2024-01-01T00:00:00.Z",
+            "2024-01-01T24:00:00Z",
+            "2024-01-01T00:00:00+0200",
+            "yesterday",
+            // Multi-byte characters inside the fixed-width fields
+            "2024-01-0é",
+            "2024-01-01T0é:00:00Z",
+            "2024-01-01T00:00:0é",
+            "2024-01-01T00:00:0éZ",
+        ] {
+            assert_eq!(parse_rfc3339_millis(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn numeric_and_string_timestamps_expire_by_unit() {
+        let policy = RetentionPolicy {
+            unit: TimestampUnit::Seconds,
+            ..policy(10 * DAY)
+        };
+        assert!(policy.drops(Some(&json!(9 * 86_400))));
+        assert!(!policy.drops(Some(&json!(10 * 86_400))));
+        assert!(policy.drops(Some(&json!("1970-01-10T23:59:59Z"))));
+        assert!(!policy.drops(Some(&json!("1970-01-11"))));
+        // Unreadable timestamps are kept
+        assert!(!policy.drops(Some(&json!("soon"))));
+        assert!(!policy.drops(Some(&json!(null))));
+        assert!(!policy.drops(None));
+    }
+
+    #[test]
+    fn expired_blocks_are_dropped_and_mixed_blocks_filtered() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("data.jac");
+        let output = dir.path().join("kept.jac");
+        // Blocks of 10 records, timestamped one day apart
+        let records = (0..30)
+            .map(|i| {
+                let record = match i {
+                    // A record without a timestamp in the expired block
+                    3 => json!({"id": i}),
+                    _ => json!({"id": i, "ts": i * DAY}),
+                };
+                record.as_object().unwrap().clone()
+            })
+            .collect();
+        pack(&input, records, true);
+
+        let summary = execute_retention(RetentionRequest {
+            input: input.clone(),
+            output: Some(output.clone()),
+            policy: policy(15 * DAY),
+        })
+        .unwrap();
+        assert_eq!(
+            summary,
+            RetentionSummary {
+                blocks_kept: 1,
+                blocks_dropped: 0,
+                blocks_filtered: 2,
+                blocks_decided_from_metadata: 1,
+                records_kept: 16,
+                records_dropped: 14,
+            }
+        );
+        let mut expected = vec![json!(3)];
+        expected.extend((15..30).map(|i| json!(i)));
+        assert_eq!(ids(&output), expected);
+
+        // In place, and from quantile sketches alone
+        let summary = execute_retention(RetentionRequest {
+            input: output.clone(),
+            output: None,
+            policy: policy(20 * DAY),
+        })
+        .unwrap();
+        assert_eq!(summary.blocks_decided_from_metadata, 1);
+        assert_eq!((summary.records_kept, summary.records_dropped), (11, 5));
+        assert_eq!(
+            ids(&output)[1..],
+            (20..30).map(|i| json!(i)).collect::<Vec<_>>()
+        );
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn output_naming_the_input_by_another_path_is_rewritten_safely() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("data.jac");
+        let alias = dir.path().join(".").join("data.jac");
+        let records = (0..20)
+            .map(|i| json!({"id": i, "ts": i * DAY}).as_object().unwrap().clone())
+            .collect();
+        pack(&input, records, false);
+
+        let summary = execute_retention(RetentionRequest {
+            input: input.clone(),
+            output: Some(alias),
+            policy: policy(15 * DAY),
+        })
+        .unwrap();
+        assert_eq!((summary.records_kept, summary.records_dropped), (5, 15));
+        assert_eq!(ids(&input), (15..20).map(|i| json!(i)).collect::<Vec<_>>());
+
+        // A failed rewrite leaves the input and its directory untouched
+        let before = fs::read(&input).unwrap();
+        fs::write(dir.path().join("garbage.jac"), b"not a jac file").unwrap();
+        let err = execute_retention(RetentionRequest {
+            input: dir.path().join("garbage.jac"),
+            output: Some(input.clone()),
+            policy: policy(15 * DAY),
+        });
+        assert!(err.is_err());
+        assert_eq!(fs::read(&input).unwrap(), before);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn blocks_without_sketches_are_decided_by_scanning_timestamps() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("data.jac");
+        let records = (0..20)
+            .map(|i| {
+                let day = if i < 10 { "2024-01-01" } else { "2024-06-01" };
+                json!({"id": i, "ts": format!("{}T00:00:{:02}Z", day, i)})
+                    .as_object()
+                    .unwrap()
+                    .clone()
+            })
+            .collect();
+        pack(&input, records, false);
+
+        let summary = execute_retention(RetentionRequest {
+            input: input.clone(),
+            output: None,
+            policy: policy(parse_rfc3339_millis("2024-03-01").unwrap()),
+        })
+        .unwrap();
+        assert_eq!(
+            (
+                summary.blocks_dropped,
+                summary.blocks_kept,
+                summary.blocks_filtered
+            ),
+            (1, 1, 0)
+        );
+        assert_eq!(summary.blocks_decided_from_metadata, 0);
+        assert_eq!(ids(&input), (10..20).map(|i| json!(i)).collect::<Vec<_>>());
+    }
+}
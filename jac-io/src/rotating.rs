@@ -0,0 +1,399 @@
+//! Rotating writer that splits a long-running stream across files
+
+use crate::writer::{JacWriter, WriterMetrics};
+use crate::CompressOptions;
+use jac_codec::CompressOpts;
+use jac_format::{FileHeader, Result};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Suffix carried by a file while it is still being written.
+pub const PARTIAL_SUFFIX: &str = ".partial";
+
+/// Thresholds that trigger a rotation; `None` disables a threshold.
+#[derive(Debug, Clone, Default)]
+pub struct RotationPolicy {
+    /// Rotate after this many records
+    pub max_records: Option<u64>,
+    /// Rotate once this many bytes have been flushed to the current file
+    ///
+    /// Bytes are counted as blocks are flushed, so a file may exceed the limit
+    /// by up to one block plus the index footer.
+    pub max_bytes: Option<u64>,
+    /// Rotate once the current file has been open this long
+    pub max_age: Option<Duration>,
+}
+
+/// A file finished by a `RotatingJacWriter`.
+#[derive(Debug, Clone)]
+pub struct RotatedFile {
+    /// Final path of the file
+    pub path: PathBuf,
+    /// Zero-based position of the file in the rotation sequence
+    pub sequence: u64,
+    /// Writer metrics for the file
+    pub metrics: WriterMetrics,
+}
+
+type RotateCallback = Box<dyn FnMut(&RotatedFile) -> Result<()> + Send>;
+
+struct OpenFile {
+    writer: JacWriter<File>,
+    partial_path: PathBuf,
+    final_path: PathBuf,
+    sequence: u64,
+    opened_at: Instant,
+}
+
+/// Writes records into a sequence of JAC files under one directory.
+///
+/// Each file is written as `<prefix>-<unix millis>-<sequence>.jac.partial` and
+/// renamed to `.jac` once its index footer is on disk, so a directory listing
+/// for `*.jac` only ever shows complete files. A file is opened lazily on the
+/// first record after a rotation, so idle periods never leave empty files.
+///
+/// Writes go straight to the file without extra buffering, so every completed
+/// block is visible to a [`crate::JacReader::snapshot`] reader of the partial
+/// file as soon as it is flushed.
+pub struct RotatingJacWriter {
+    dir: PathBuf,
+    prefix: String,
+    header: FileHeader,
+    opts: CompressOpts,
+    policy: RotationPolicy,
+    on_rotate: Option<RotateCallback>,
+    sequence: u64,
+    current: Option<OpenFile>,
+}
+
+impl RotatingJacWriter {
+    /// Create a rotating writer in `dir` (created if missing).
+    ///
+    /// `header` is written at the start of every file.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        prefix: impl Into<String>,
+        header: FileHeader,
+        opts: CompressOpts,
+        policy: RotationPolicy,
+    ) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            prefix: prefix.into(),
+            header,
+            opts,
+            policy,
+            on_rotate: None,
+            sequence: 0,
+            current: None,
+        })
+    }
+
+    /// Create a rotating writer whose files carry the header `execute_compress`
+    /// would write for `options` (flags and declared limits included).
+    pub fn from_options(
+        dir: impl Into<PathBuf>,
+        prefix: impl Into<String>,
+        options: &CompressOptions,
+        policy: RotationPolicy,
+    ) -> Result<Self> {
+        let header = crate::build_file_header(options, None)?;
+        Self::new(dir, prefix, header, options.codec_opts(), policy)
+    }
+
+    /// Invoke `callback` with every finished file.
+    ///
+    /// An error returned by the callback is propagated from the call that
+    /// triggered the rotation; the file itself is already complete on disk.
+    pub fn on_rotate<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&RotatedFile) -> Result<()> + Send + 'static,
+    {
+        self.on_rotate = Some(Box::new(callback));
+        self
+    }
+
+    /// Write one record, rotating first if a threshold has been reached.
+    pub fn write_record(&mut self, rec: &Map<String, Value>) -> Result<()> {
+        if self.should_rotate() {
+            self.rotate()?;
+        }
+        let file = match self.current.as_mut() {
+            Some(file) => file,
+            None => self.open_next()?,
+        };
+        file.writer.write_record(rec)
+    }
+
+    /// Write multiple records from an iterator.
+    pub fn write_records<I>(&mut self, records: I) -> Result<()>
+    where
+        I: IntoIterator<Item = Map<String, Value>>,
+    {
+        for record in records {
+            self.write_record(&record)?;
+        }
+        Ok(())
+    }
+
+    /// Rotate if the open file has aged past `max_age`.
+    ///
+    /// Long-running services should call this periodically so a quiet stream
+    /// still closes files on time.
+    pub fn tick(&mut self) -> Result<Option<RotatedFile>> {
+        if self.should_rotate() {
+            self.rotate()
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Finish the open file now, regardless of thresholds.
+    pub fn rotate(&mut self) -> Result<Option<RotatedFile>> {
+        let Some(file) = self.current.take() else {
+            return Ok(None);
+        };
+        let finish = file.writer.finish_with_index()?;
+        finish.writer.sync_all()?;
+        fs::rename(&file.partial_path, &file.final_path)?;
+
+        let rotated = RotatedFile {
+            path: file.final_path,
+            sequence: file.sequence,
+            metrics: finish.metrics,
+        };
+        if let Some(callback) = self.on_rotate.as_mut() {
+            callback(&rotated)?;
+        }
+        Ok(Some(rotated))
+    }
+
+    /// Finish the open file and consume the writer.
+    pub fn finish(mut self) -> Result<Option<RotatedFile>> {
+        self.rotate()
+    }
+
+    /// Metrics for the file currently being written, if one is open.
+    pub fn current_metrics(&self) -> Option<WriterMetrics> {
+        self.current.as_ref().map(|file| file.writer.metrics())
+    }
+
+    /// Path of the file currently being written, if one is open.
+    pub fn current_path(&self) -> Option<&PathBuf> {
+        self.current.as_ref().map(|file| &file.partial_path)
+    }
+
+    fn should_rotate(&self) -> bool {
+        let Some(file) = &self.current else {
+            return false;
+        };
+        let writer = &file.writer;
+        self.policy
+            .max_records
+            .is_some_and(|max| writer.records_written() >= max)
+            || self
+                .policy
+                .max_bytes
+                .is_some_and(|max| writer.bytes_written() >= max)
+            || self
+                .policy
+                .max_age
+                .is_some_and(|max| file.opened_at.elapsed() >= max)
+    }
+
+    fn open_next(&mut self) -> Result<&mut OpenFile> {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let sequence = self.sequence;
+        self.sequence += 1;
+
+        let name = format!("{}-{}-{:06}.jac", self.prefix, stamp, sequence);
+        let final_path = self.dir.join(&name);
+        let partial_path = self.dir.join(format!("{}{}", name, PARTIAL_SUFFIX));
+        let writer = JacWriter::new(
+            File::create(&partial_path)?,
+            self.header.clone(),
+            self.opts.clone(),
+        )?;
+
+        Ok(self.current.insert(OpenFile {
+            writer,
+            partial_path,
+            final_path,
+            sequence,
+            opened_at: Instant::now(),
+        }))
+    }
+}
+
+impl fmt::Debug for RotatingJacWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RotatingJacWriter")
+            .field("dir", &self.dir)
+            .field("prefix", &self.prefix)
+            .field("policy", &self.policy)
+            .field("sequence", &self.sequence)
+            .field("current", &self.current_path())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JacReader;
+    use jac_codec::DecompressOpts;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    fn record(id: u64) -> Map<String, Value> {
+        json!({ "id": id }).as_object().unwrap().clone()
+    }
+
+    fn header() -> FileHeader {
+        crate::build_file_header(&crate::CompressOptions::default(), None).unwrap()
+    }
+
+    #[test]
+    fn rotates_on_record_threshold_and_reports_each_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = RotationPolicy {
+            max_records: Some(3),
+            ..RotationPolicy::default()
+        };
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let mut writer = RotatingJacWriter::new(
+            dir.path(),
+            "events",
+            header(),
+            CompressOpts::default(),
+            policy,
+        )
+        .unwrap()
+        .on_rotate(move |file| {
+            sink.lock().unwrap().push(file.clone());
+            Ok(())
+        });
+
+        writer.write_records((0..7).map(record)).unwrap();
+        writer.finish().unwrap();
+
+        let seen = seen.lock().unwrap();
+        let counts: Vec<u64> = seen.iter().map(|f| f.metrics.records_written).collect();
+        assert_eq!(counts, vec![3, 3, 1]);
+        let sequences: Vec<u64> = seen.iter().map(|f| f.sequence).collect();
+        assert_eq!(sequences, vec![0, 1, 2]);
+
+        let mut next_id = 0u64;
+        for file in seen.iter() {
+            assert_eq!(file.path.extension().unwrap(), "jac");
+            let mut reader = JacReader::open(&file.path, DecompressOpts::default()).unwrap();
+            for rec in reader.record_stream().unwrap() {
+                assert_eq!(rec.unwrap()["id"], json!(next_id));
+                next_id += 1;
+            }
+        }
+        assert_eq!(next_id, 7);
+
+        let partials = fs::read_dir(dir.path())
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().ends_with(PARTIAL_SUFFIX)
+            })
+            .count();
+        assert_eq!(partials, 0);
+    }
+
+    #[test]
+    fn open_partial_file_is_readable_up_to_its_last_block() {
+        let dir = tempfile::tempdir().unwrap();
+        let opts = CompressOpts {
+            block_target_records: 2,
+            ..CompressOpts::default()
+        };
+        let mut writer = RotatingJacWriter::new(
+            dir.path(),
+            "events",
+            header(),
+            opts,
+            RotationPolicy::default(),
+        )
+        .unwrap();
+        writer.write_records((0..3).map(record)).unwrap();
+
+        let partial = writer.current_path().unwrap().clone();
+        let mut reader = JacReader::open_snapshot(&partial, DecompressOpts::default()).unwrap();
+        assert!(!reader.is_finalized());
+        assert_eq!(reader.record_stream().unwrap().count(), 2);
+
+        let rotated = writer.finish().unwrap().unwrap();
+        let mut reader =
+            JacReader::open_snapshot(&rotated.path, DecompressOpts::default()).unwrap();
+        assert!(reader.is_finalized());
+        assert_eq!(reader.record_stream().unwrap().count(), 3);
+    }
+
+    #[test]
+    fn tick_rotates_aged_files_and_skips_idle_periods() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = RotationPolicy {
+            max_age: Some(Duration::ZERO),
+            ..RotationPolicy::default()
+        };
+        let mut writer = RotatingJacWriter::new(
+            dir.path(),
+            "events",
+            header(),
+            CompressOpts::default(),
+            policy,
+        )
+        .unwrap();
+
+        assert!(writer.tick().unwrap().is_none());
+        writer.write_record(&record(1)).unwrap();
+        assert!(writer
+            .current_path()
+            .unwrap()
+            .to_string_lossy()
+            .ends_with(PARTIAL_SUFFIX));
+        let rotated = writer.tick().unwrap().expect("aged file rotated");
+        assert_eq!(rotated.metrics.records_written, 1);
+        assert!(writer.tick().unwrap().is_none());
+        assert!(writer.finish().unwrap().is_none());
+    }
+
+    #[test]
+    fn from_options_writes_the_compress_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = CompressOptions {
+            intern_field_names: true,
+            align_blocks: true,
+            ..CompressOptions::default()
+        };
+        let mut writer = RotatingJacWriter::from_options(
+            dir.path(),
+            "events",
+            &options,
+            RotationPolicy::default(),
+        )
+        .unwrap();
+        writer.write_records((0..3).map(record)).unwrap();
+        let rotated = writer.finish().unwrap().unwrap();
+
+        let expected = crate::build_file_header(&options, None).unwrap();
+        let mut reader = JacReader::open(&rotated.path, DecompressOpts::default()).unwrap();
+        assert_eq!(reader.file_header().flags, expected.flags);
+        assert_eq!(reader.file_header().user_metadata, expected.user_metadata);
+        assert!(reader.file_header().aligned_blocks());
+        assert_eq!(reader.record_stream().unwrap().count(), 3);
+    }
+}
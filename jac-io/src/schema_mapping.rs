@@ -0,0 +1,164 @@
+//! Schema evolution applied as records are read.
+//!
+//! Archives written over several years rarely share one schema: fields get
+//! renamed, change type, or are added later. A [`SchemaMapping`], usually
+//! loaded from a JSON file, rewrites each record read back into the current
+//! schema so applications see uniform records without the old files being
+//! rewritten. It is installed with [`JacReader::set_schema_mapping`] or
+//! [`DecompressOptions::schema_mapping`].
+//!
+//! [`JacReader::set_schema_mapping`]: crate::JacReader::set_schema_mapping
+//! [`DecompressOptions::schema_mapping`]: crate::DecompressOptions::schema_mapping
+
+use crate::coercion::{coerce, CoercionRule};
+use crate::rename_fields;
+use jac_format::{JacError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Mapping from stored records to the current schema.
+///
+/// Applied to top-level fields in three steps: `rename` (keeping key order;
+/// a record that already holds the new name keeps that value), then `cast` by
+/// the new names, then `defaults` for fields still missing. Example file:
+///
+/// ```json
+/// {
+///   "rename": { "user": "user_id" },
+///   "cast": { "user_id": "to_string" },
+///   "defaults": { "region": "unknown" }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchemaMapping {
+    /// Field renames (`old` -> `new`)
+    pub rename: HashMap<String, String>,
+    /// Type cast applied to each listed field; values the rule does not
+    /// cover are kept as they are. `promote_to_string_on_drift` depends on
+    /// write order and is rejected.
+    pub cast: BTreeMap<String, CoercionRule>,
+    /// Value given to each listed field when a record lacks it; `null`
+    /// values are kept.
+    pub defaults: Map<String, Value>,
+}
+
+impl SchemaMapping {
+    /// Parse and validate a mapping from JSON text.
+    pub fn from_json(text: &str) -> Result<Self> {
+        let mapping: Self = serde_json::from_str(text)
+            .map_err(|err| JacError::InvalidArgument(format!("invalid schema mapping: {}", err)))?;
+        mapping.validate()?;
+        Ok(mapping)
+    }
+
+    /// Read a mapping from a JSON file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::from_json(&text)
+            .map_err(|err| JacError::InvalidArgument(format!("{}: {}", path.display(), err)))
+    }
+
+    /// Reject renames onto the same name and casts the reader cannot apply.
+    pub fn validate(&self) -> Result<()> {
+        let mut targets: Vec<&String> = self.rename.values().collect();
+        targets.sort();
+        if let Some(pair) = targets.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(JacError::InvalidArgument(format!(
+                "schema mapping renames several fields to '{}'",
+                pair[0]
+            )));
+        }
+        if let Some((field, _)) = self
+            .cast
+            .iter()
+            .find(|(_, rule)| **rule == CoercionRule::PromoteToStringOnDrift)
+        {
+            return Err(JacError::InvalidArgument(format!(
+                "schema mapping cannot cast '{}' with promote_to_string_on_drift",
+                field
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether the mapping leaves records unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.rename.is_empty() && self.cast.is_empty() && self.defaults.is_empty()
+    }
+
+    /// Rewrite one record into the current schema.
+    pub fn apply(&self, record: Map<String, Value>) -> Map<String, Value> {
+        let mut record = rename_fields(record, &self.rename);
+        for (field, rule) in &self.cast {
+            if let Some(value) = record.get_mut(field) {
+                if let Some(cast) = coerce(value, *rule, false) {
+                    *value = cast;
+                }
+            }
+        }
+        for (field, default) in &self.defaults {
+            if !record.contains_key(field) {
+                record.insert(field.clone(), default.clone());
+            }
+        }
+        record
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn mapping_renames_casts_and_fills_defaults() {
+        let mapping = SchemaMapping::from_json(
+            r#"{
+                "rename": { "user": "user_id", "ts": "timestamp" },
+                "cast": { "user_id": "to_string", "ok": "string_to_bool" },
+                "defaults": { "region": "unknown", "ok": false }
+            }"#,
+        )
+        .unwrap();
+
+        let old = mapping.apply(record(json!({ "ts": 1, "user": 42, "ok": "TRUE" })));
+        assert_eq!(
+            Value::Object(old.clone()),
+            json!({ "timestamp": 1, "user_id": "42", "ok": true, "region": "unknown" })
+        );
+        assert_eq!(
+            old.keys().collect::<Vec<_>>(),
+            ["timestamp", "user_id", "ok", "region"]
+        );
+
+        let current = record(json!({ "timestamp": 2, "user_id": "7", "region": null }));
+        assert_eq!(
+            Value::Object(mapping.apply(current)),
+            json!({ "timestamp": 2, "user_id": "7", "region": null, "ok": false })
+        );
+    }
+
+    #[test]
+    fn invalid_mappings_are_rejected() {
+        for text in [
+            r#"{"renames": {}}"#,
+            r#"{"rename": {"a": "c", "b": "c"}}"#,
+            r#"{"cast": {"a": "promote_to_string_on_drift"}}"#,
+        ] {
+            let result = SchemaMapping::from_json(text);
+            assert!(
+                matches!(result, Err(JacError::InvalidArgument(_))),
+                "{}",
+                text
+            );
+        }
+        assert!(SchemaMapping::from_json("{}").unwrap().is_empty());
+    }
+}
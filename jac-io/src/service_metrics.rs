@@ -0,0 +1,156 @@
+//! Counters for long-running services, in the Prometheus text format.
+//!
+//! `jac-ingest` and `jac serve` run until they are stopped, so operators watch
+//! them from outside: a stuck archiver shows up as a flat record counter next
+//! to a growing queue. [`ServiceMetrics`] holds the counters such services
+//! keep, as atomics shared between their threads, and
+//! [`ServiceMetrics::render`] formats them for a `/metrics` scrape.
+
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counters and gauges of a long-running reader or writer.
+///
+/// Fields are updated directly with atomic operations; a service leaves the
+/// ones that do not apply to it at zero.
+#[derive(Debug, Default)]
+pub struct ServiceMetrics {
+    /// Records written (ingest) or returned to clients (serve)
+    pub records: AtomicU64,
+    /// Input lines that were not JSON objects
+    pub records_rejected: AtomicU64,
+    /// Blocks in finished output files
+    pub blocks_written: AtomicU64,
+    /// Bytes in finished output files
+    pub bytes_written: AtomicU64,
+    /// Output files finished
+    pub files_written: AtomicU64,
+    /// Blocks decoded to answer requests
+    pub blocks_read: AtomicU64,
+    /// Block bytes read to answer requests
+    pub bytes_read: AtomicU64,
+    /// Requests received
+    pub requests: AtomicU64,
+    /// Failed writes or failed requests
+    pub errors: AtomicU64,
+    /// Gauge: records or requests accepted but not yet completed
+    pub queue_depth: AtomicU64,
+    /// Gauge: bytes written so far to the file currently open
+    pub open_file_bytes: AtomicU64,
+}
+
+impl ServiceMetrics {
+    /// Add `n` to a counter.
+    pub fn add(counter: &AtomicU64, n: u64) {
+        counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Subtract `n` from a gauge, stopping at zero.
+    pub fn sub(gauge: &AtomicU64, n: u64) {
+        let _ = gauge.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |value| {
+            Some(value.saturating_sub(n))
+        });
+    }
+
+    /// Every metric in the Prometheus text exposition format, with names
+    /// prefixed by `namespace` (e.g. `jac_ingest_records_total`).
+    pub fn render(&self, namespace: &str) -> String {
+        let metrics: [(&str, &str, &str, &AtomicU64); 11] = [
+            (
+                "records_total",
+                "counter",
+                "Records written or returned",
+                &self.records,
+            ),
+            (
+                "records_rejected_total",
+                "counter",
+                "Input lines that were not JSON objects",
+                &self.records_rejected,
+            ),
+            (
+                "blocks_written_total",
+                "counter",
+                "Blocks in finished output files",
+                &self.blocks_written,
+            ),
+            (
+                "bytes_written_total",
+                "counter",
+                "Bytes in finished output files",
+                &self.bytes_written,
+            ),
+            (
+                "files_written_total",
+                "counter",
+                "Output files finished",
+                &self.files_written,
+            ),
+            (
+                "blocks_read_total",
+                "counter",
+                "Blocks decoded to answer requests",
+                &self.blocks_read,
+            ),
+            (
+                "bytes_read_total",
+                "counter",
+                "Block bytes read to answer requests",
+                &self.bytes_read,
+            ),
+            (
+                "requests_total",
+                "counter",
+                "Requests received",
+                &self.requests,
+            ),
+            (
+                "errors_total",
+                "counter",
+                "Failed writes or requests",
+                &self.errors,
+            ),
+            (
+                "queue_depth",
+                "gauge",
+                "Records or requests accepted but not yet completed",
+                &self.queue_depth,
+            ),
+            (
+                "open_file_bytes",
+                "gauge",
+                "Bytes written to the file currently open",
+                &self.open_file_bytes,
+            ),
+        ];
+
+        let mut out = String::new();
+        for (name, kind, help, value) in metrics {
+            let _ = writeln!(out, "# HELP {namespace}_{name} {help}");
+            let _ = writeln!(out, "# TYPE {namespace}_{name} {kind}");
+            let _ = writeln!(out, "{namespace}_{name} {}", value.load(Ordering::Relaxed));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_render_as_prometheus_text() {
+        let metrics = ServiceMetrics::default();
+        ServiceMetrics::add(&metrics.records, 42);
+        ServiceMetrics::add(&metrics.queue_depth, 3);
+        ServiceMetrics::sub(&metrics.queue_depth, 5);
+        let text = metrics.render("jac_ingest");
+        assert!(text.contains(
+            "# HELP jac_ingest_records_total Records written or returned\n\
+             # TYPE jac_ingest_records_total counter\n\
+             jac_ingest_records_total 42\n"
+        ));
+        assert!(text.contains("# TYPE jac_ingest_queue_depth gauge\njac_ingest_queue_depth 0\n"));
+        assert_eq!(text.lines().count(), 33);
+    }
+}
@@ -0,0 +1,210 @@
+//! Line endings and character encoding of JSON text output.
+//!
+//! Unpacked NDJSON ends each record with `\n` and is plain UTF-8 by default.
+//! Some consumers, mostly on Windows, expect `\r\n` line endings, a leading
+//! byte order mark, or pure ASCII; [`LineEnding`] and [`TextEncoding`] select
+//! those on a [`DecompressRequest`](crate::DecompressRequest), whose
+//! `validate_utf8` checks that the text written is well-formed UTF-8.
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+
+/// UTF-8 byte order mark.
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Terminator written after each NDJSON record.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+impl LineEnding {
+    /// Bytes of the terminator.
+    pub fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+}
+
+/// Character encoding of NDJSON and JSON array output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextEncoding {
+    /// UTF-8 without a byte order mark
+    #[default]
+    Utf8,
+    /// UTF-8 starting with a byte order mark (`EF BB BF`)
+    Utf8Bom,
+    /// ASCII only: every other character is written as a `\uXXXX` escape
+    /// (a surrogate pair above U+FFFF), which JSON parsers read back as the
+    /// same text
+    Ascii,
+}
+
+/// Writer applying a [`TextEncoding`] to the JSON text passing through it.
+///
+/// Escaping relies on the text being JSON, where characters outside ASCII
+/// only occur inside strings. When escaping or validating, writes may split a
+/// character; its leading bytes are held until the rest arrives, and text
+/// that is not UTF-8 fails with [`io::ErrorKind::InvalidData`]. Without
+/// either, bytes pass through unchecked.
+pub(crate) struct TextWriter<W: Write> {
+    inner: W,
+    escape: bool,
+    check: bool,
+    bom_pending: bool,
+    partial: Vec<u8>,
+}
+
+impl<W: Write> TextWriter<W> {
+    /// ASCII output is always checked; `validate_utf8` checks the other
+    /// encodings too.
+    pub(crate) fn new(inner: W, encoding: TextEncoding, validate_utf8: bool) -> Self {
+        let escape = encoding == TextEncoding::Ascii;
+        Self {
+            inner,
+            escape,
+            check: escape || validate_utf8,
+            bom_pending: encoding == TextEncoding::Utf8Bom,
+            partial: Vec::new(),
+        }
+    }
+
+    fn write_checked(&mut self, mut bytes: &[u8]) -> io::Result<()> {
+        while !bytes.is_empty() {
+            let ascii = bytes
+                .iter()
+                .position(|byte| !byte.is_ascii())
+                .unwrap_or(bytes.len());
+            self.inner.write_all(&bytes[..ascii])?;
+            bytes = &bytes[ascii..];
+            let Some(&lead) = bytes.first() else {
+                break;
+            };
+            let len = match lead {
+                0xC2..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF4 => 4,
+                _ => return Err(invalid_utf8()),
+            };
+            if bytes.len() < len {
+                self.partial.extend_from_slice(bytes);
+                break;
+            }
+            let ch = std::str::from_utf8(&bytes[..len])
+                .map_err(|_| invalid_utf8())?
+                .chars()
+                .next()
+                .ok_or_else(invalid_utf8)?;
+            if self.escape {
+                for unit in ch.encode_utf16(&mut [0; 2]) {
+                    write!(self.inner, "\\u{:04x}", unit)?;
+                }
+            } else {
+                self.inner.write_all(&bytes[..len])?;
+            }
+            bytes = &bytes[len..];
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for TextWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.bom_pending {
+            self.inner.write_all(UTF8_BOM)?;
+            self.bom_pending = false;
+        }
+        if !self.check {
+            return self.inner.write(buf);
+        }
+        if self.partial.is_empty() {
+            self.write_checked(buf)?;
+        } else {
+            let mut joined = std::mem::take(&mut self.partial);
+            joined.extend_from_slice(buf);
+            self.write_checked(&joined)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.partial.is_empty() {
+            return Err(invalid_utf8());
+        }
+        self.inner.flush()
+    }
+}
+
+fn invalid_utf8() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "JSON output is not valid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(encoding: TextEncoding, chunks: &[&[u8]]) -> io::Result<Vec<u8>> {
+        write(TextWriter::new(Vec::new(), encoding, false), chunks)
+    }
+
+    fn write(mut writer: TextWriter<Vec<u8>>, chunks: &[&[u8]]) -> io::Result<Vec<u8>> {
+        for chunk in chunks {
+            writer.write_all(chunk)?;
+        }
+        writer.flush()?;
+        Ok(writer.inner)
+    }
+
+    #[test]
+    fn ascii_encoding_escapes_split_characters() {
+        let text = r#"{"name":"Zoë","emoji":"🙂"}"#.as_bytes();
+        let (head, tail) = text.split_at(text.len() - 4);
+        let escaped = encode(TextEncoding::Ascii, &[head, tail]).unwrap();
+        assert_eq!(
+            String::from_utf8(escaped.clone()).unwrap(),
+            r#"{"name":"Zo\u00eb","emoji":"\ud83d\ude42"}"#
+        );
+        let parsed: serde_json::Value = serde_json::from_slice(&escaped).unwrap();
+        assert_eq!(
+            parsed,
+            serde_json::from_slice::<serde_json::Value>(text).unwrap()
+        );
+
+        assert!(encode(TextEncoding::Ascii, &[b"\"\xFF\""]).is_err());
+        assert!(encode(TextEncoding::Ascii, &["ë".as_bytes()[..1].as_ref()]).is_err());
+    }
+
+    #[test]
+    fn bom_is_written_once_before_the_text() {
+        let bytes = encode(TextEncoding::Utf8Bom, &[b"{}", b"\r\n"]).unwrap();
+        assert_eq!(bytes, b"\xEF\xBB\xBF{}\r\n");
+        assert_eq!(encode(TextEncoding::Utf8, &[b"{}"]).unwrap(), b"{}");
+        assert_eq!(LineEnding::CrLf.as_bytes(), b"\r\n");
+    }
+
+    #[test]
+    fn utf8_validation_is_independent_of_escaping() {
+        let validating = || TextWriter::new(Vec::new(), TextEncoding::Utf8Bom, true);
+        let text = "\"Zoë\"".as_bytes();
+        let (head, tail) = text.split_at(3);
+        assert_eq!(
+            write(validating(), &[head, tail]).unwrap(),
+            [UTF8_BOM, text].concat()
+        );
+        assert!(write(validating(), &[b"\"\xFF\""]).is_err());
+        assert!(write(validating(), &["ë".as_bytes()[..1].as_ref()]).is_err());
+        // Unchecked output passes the bytes through.
+        assert_eq!(
+            encode(TextEncoding::Utf8, &[b"\"\xFF\""]).unwrap(),
+            b"\"\xFF\""
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Repair of invalid UTF-8 inside JSON string literals.
+//!
+//! JSON structure is pure ASCII, so invalid byte sequences in otherwise well-formed
+//! input can only appear inside string literals. [`StringScanner`] tracks literal
+//! boundaries across arbitrarily split chunks and rewrites offending literals
+//! according to a [`Utf8Policy`]; bytes outside literals pass through untouched.
+
+use crate::{Utf8Metrics, Utf8Policy};
+use std::io::{self, BufRead, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Prefix placed in front of the base64 payload of a string that was not valid UTF-8.
+pub const INVALID_UTF8_BASE64_MARKER: &str = "jac:base64:";
+
+/// Counters shared between a record stream and the summary it reports into.
+#[derive(Debug, Default)]
+pub(crate) struct Utf8Counters {
+    invalid_strings: AtomicU64,
+    replaced_sequences: AtomicU64,
+    base64_wrapped: AtomicU64,
+}
+
+impl Utf8Counters {
+    pub(crate) fn snapshot(&self) -> Utf8Metrics {
+        Utf8Metrics {
+            invalid_strings: self.invalid_strings.load(Ordering::Relaxed),
+            replaced_sequences: self.replaced_sequences.load(Ordering::Relaxed),
+            base64_wrapped: self.base64_wrapped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Incremental scanner that rewrites string literals containing invalid UTF-8.
+pub(crate) struct StringScanner {
+    policy: Utf8Policy,
+    counters: Arc<Utf8Counters>,
+    in_string: bool,
+    escape: bool,
+    literal: Vec<u8>,
+}
+
+impl StringScanner {
+    pub(crate) fn new(policy: Utf8Policy, counters: Arc<Utf8Counters>) -> Self {
+        Self {
+            policy,
+            counters,
+            in_string: false,
+            escape: false,
+            literal: Vec::new(),
+        }
+    }
+
+    /// Feed a chunk of input, appending repaired output to `out`.
+    pub(crate) fn feed(&mut self, input: &[u8], out: &mut Vec<u8>) {
+        for &byte in input {
+            if !self.in_string {
+                out.push(byte);
+                if byte == b'"' {
+                    self.in_string = true;
+                    self.literal.clear();
+                }
+                continue;
+            }
+
+            if self.escape {
+                self.escape = false;
+                self.literal.push(byte);
+            } else if byte == b'\\' {
+                self.escape = true;
+                self.literal.push(byte);
+            } else if byte == b'"' {
+                self.flush_literal(out);
+                out.push(byte);
+                self.in_string = false;
+            } else {
+                self.literal.push(byte);
+            }
+        }
+    }
+
+    /// Emit any unterminated literal verbatim so the JSON parser reports it.
+    pub(crate) fn finish(&mut self, out: &mut Vec<u8>) {
+        if self.in_string {
+            out.extend_from_slice(&self.literal);
+            self.literal.clear();
+            self.in_string = false;
+            self.escape = false;
+        }
+    }
+
+    fn flush_literal(&mut self, out: &mut Vec<u8>) {
+        if std::str::from_utf8(&self.literal).is_ok() {
+            out.extend_from_slice(&self.literal);
+            return;
+        }
+
+        self.counters
+            .invalid_strings
+            .fetch_add(1, Ordering::Relaxed);
+        match self.policy {
+            Utf8Policy::Strict => out.extend_from_slice(&self.literal),
+            Utf8Policy::Lossy => {
+                let mut replaced = 0u64;
+                for chunk in self.literal.utf8_chunks() {
+                    out.extend_from_slice(chunk.valid().as_bytes());
+                    if !chunk.invalid().is_empty() {
+                        out.extend_from_slice(char::REPLACEMENT_CHARACTER.to_string().as_bytes());
+                        replaced += 1;
+                    }
+                }
+                self.counters
+                    .replaced_sequences
+                    .fetch_add(replaced, Ordering::Relaxed);
+            }
+            Utf8Policy::Base64 => {
+                out.extend_from_slice(INVALID_UTF8_BASE64_MARKER.as_bytes());
+                encode_base64(&self.literal, out);
+                self.counters.base64_wrapped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Repair a complete JSON text (e.g. one NDJSON line) in one call.
+pub(crate) fn repair_bytes(
+    input: &[u8],
+    policy: Utf8Policy,
+    counters: &Arc<Utf8Counters>,
+) -> Vec<u8> {
+    let mut scanner = StringScanner::new(policy, Arc::clone(counters));
+    let mut out = Vec::with_capacity(input.len() + 8);
+    scanner.feed(input, &mut out);
+    scanner.finish(&mut out);
+    out
+}
+
+/// Reader adapter applying [`StringScanner`] to a byte stream.
+pub(crate) struct Utf8RepairReader<R> {
+    inner: R,
+    scanner: StringScanner,
+    pending: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: BufRead> Utf8RepairReader<R> {
+    pub(crate) fn new(inner: R, policy: Utf8Policy, counters: Arc<Utf8Counters>) -> Self {
+        Self {
+            inner,
+            scanner: StringScanner::new(policy, counters),
+            pending: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Read for Utf8RepairReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.pending.len() {
+            self.pending.clear();
+            self.pos = 0;
+            let chunk = self.inner.fill_buf()?;
+            if chunk.is_empty() {
+                self.scanner.finish(&mut self.pending);
+                if self.pending.is_empty() {
+                    return Ok(0);
+                }
+                break;
+            }
+            let len = chunk.len();
+            self.scanner.feed(chunk, &mut self.pending);
+            self.inner.consume(len);
+        }
+
+        let available = &self.pending[self.pos..];
+        let count = available.len().min(buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.pos += count;
+        Ok(count)
+    }
+}
+
+fn encode_base64(input: &[u8], out: &mut Vec<u8>) {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    for chunk in input.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let triple = (b0 << 16) | (b1 << 8) | b2;
+
+        out.push(ALPHABET[(triple >> 18) as usize & 0x3F]);
+        out.push(ALPHABET[(triple >> 12) as usize & 0x3F]);
+        out.push(if chunk.len() > 1 {
+            ALPHABET[(triple >> 6) as usize & 0x3F]
+        } else {
+            b'='
+        });
+        out.push(if chunk.len() > 2 {
+            ALPHABET[triple as usize & 0x3F]
+        } else {
+            b'='
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repair(input: &[u8], policy: Utf8Policy) -> (String, Utf8Metrics) {
+        let counters = Arc::new(Utf8Counters::default());
+        let out = repair_bytes(input, policy, &counters);
+        (String::from_utf8(out).unwrap(), counters.snapshot())
+    }
+
+    #[test]
+    fn lossy_replaces_each_invalid_sequence() {
+        let (out, metrics) = repair(b"{\"a\":\"x\xffy\xfe\",\"b\":\"ok\"}", Utf8Policy::Lossy);
+        assert_eq!(out, "{\"a\":\"x\u{FFFD}y\u{FFFD}\",\"b\":\"ok\"}");
+        assert_eq!(metrics.invalid_strings, 1);
+        assert_eq!(metrics.replaced_sequences, 2);
+        assert_eq!(metrics.base64_wrapped, 0);
+    }
+
+    #[test]
+    fn base64_wraps_offending_literal_including_keys() {
+        let (out, metrics) = repair(b"{\"k\xff\":\"v\\\"\"}", Utf8Policy::Base64);
+        assert_eq!(out, "{\"jac:base64:a/8=\":\"v\\\"\"}");
+        assert_eq!(metrics.invalid_strings, 1);
+        assert_eq!(metrics.base64_wrapped, 1);
+    }
+
+    #[test]
+    fn reader_handles_literals_split_across_chunks() {
+        let counters = Arc::new(Utf8Counters::default());
+        let input: &[u8] = b"[{\"a\":\"\xc3\"},{\"a\":\"\xc3\xa9\"}]";
+        let reader = io::BufReader::with_capacity(3, input);
+        let mut repaired = Utf8RepairReader::new(reader, Utf8Policy::Lossy, Arc::clone(&counters));
+        let mut out = String::new();
+        repaired.read_to_string(&mut out).unwrap();
+        assert_eq!(out, "[{\"a\":\"\u{FFFD}\"},{\"a\":\"\u{e9}\"}]");
+        assert_eq!(counters.snapshot().replaced_sequences, 1);
+    }
+
+    #[test]
+    fn base64_encoding_matches_rfc4648_vectors() {
+        for (input, expected) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foobar", "Zm9vYmFy"),
+        ] {
+            let mut out = Vec::new();
+            encode_base64(input, &mut out);
+            assert_eq!(out, expected.as_bytes());
+        }
+    }
+}
@@ -0,0 +1,504 @@
+//! Conformance test vectors (SPEC §12).
+//!
+//! [`generate_vectors`] writes every built-in vector as an NDJSON input, the
+//! `.jac` file this crate encodes from it, and an entry in `manifest.json`
+//! describing what a conforming decoder must reproduce. [`check_vectors`]
+//! reads such a directory back and verifies each `.jac` file against its
+//! input, so another implementation can decode the files written here or have
+//! the files it writes checked by this crate.
+
+use crate::{JacReader, JacWriter};
+use jac_codec::{Codec, CompressOpts, DecompressOpts};
+use jac_format::constants::{ENCODING_FLAG_DELTA, ENCODING_FLAG_DICTIONARY, FLAG_NESTED_OPAQUE};
+use jac_format::{FileHeader, JacError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Name of the manifest written next to the vector files.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Manifest format version written by [`generate_vectors`].
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Field encoding a vector is designed to exercise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorEncoding {
+    /// Neither dictionary nor delta encoding in any block
+    Plain,
+    /// Dictionary-encoded strings in at least one block
+    Dictionary,
+    /// Delta-encoded integers in at least one block
+    Delta,
+}
+
+/// One built-in conformance vector.
+#[derive(Debug, Clone)]
+pub struct TestVector {
+    /// File stem used for the vector's files
+    pub name: &'static str,
+    /// What the vector covers
+    pub description: &'static str,
+    /// Input records, in order
+    pub records: Vec<Map<String, Value>>,
+    /// Records per block when encoding
+    pub block_target_records: usize,
+    /// Compression codec used for every segment
+    pub codec: Codec,
+    /// Maximum dictionary entries per field when encoding
+    pub max_dict_entries: usize,
+    /// Encodings the generated file must use, by field
+    pub encodings: Vec<(&'static str, VectorEncoding)>,
+    /// Fields whose projections are recorded in the manifest
+    pub projections: Vec<&'static str>,
+}
+
+/// Manifest listing the vectors in a directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VectorManifest {
+    /// Manifest format version
+    pub version: u32,
+    /// One entry per vector
+    pub vectors: Vec<VectorEntry>,
+}
+
+/// Manifest entry for one vector.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VectorEntry {
+    /// Vector name
+    pub name: String,
+    /// What the vector covers
+    pub description: String,
+    /// NDJSON input file, relative to the manifest
+    pub input: String,
+    /// Encoded `.jac` file, relative to the manifest
+    pub jac: String,
+    /// Codec used by the generator (`none` or `zstd`)
+    pub codec: String,
+    /// Records per block used by the generator
+    pub block_target_records: usize,
+    /// Total records
+    pub records: u64,
+    /// Blocks in the generated file
+    pub blocks: usize,
+    /// Encodings the vector exercises, by field
+    pub encodings: BTreeMap<String, VectorEncoding>,
+    /// Expected projection per field; `null` marks null or absent values
+    pub projections: BTreeMap<String, Vec<Value>>,
+}
+
+/// Result of checking one vector.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VectorCheck {
+    /// Vector name
+    pub name: String,
+    /// Conformance failures (decoding errors, value mismatches)
+    pub failures: Vec<String>,
+    /// Differences that are encoder choices (block layout, encodings)
+    pub warnings: Vec<String>,
+}
+
+impl VectorCheck {
+    /// Whether the vector decoded to its expected input.
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+fn records(values: Vec<Value>) -> Vec<Map<String, Value>> {
+    values
+        .into_iter()
+        .filter_map(|value| match value {
+            Value::Object(map) => Some(map),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The built-in vectors: the SPEC §12.1 sample plus edge encodings.
+pub fn builtin_vectors() -> Vec<TestVector> {
+    vec![
+        TestVector {
+            name: "spec_12_1",
+            description: "SPEC §12.1 sample log records, uncompressed segments",
+            records: records(vec![
+                json!({"ts":1623000000,"level":"INFO","msg":"Started","user":"alice"}),
+                json!({"ts":1623000005,"level":"INFO","msg":"Step1","user":"alice"}),
+                json!({"ts":1623000010,"level":"WARN","msg":"Low disk","user":"bob"}),
+                json!({"ts":1623000020,"user":"carol","error":"Disk failure"}),
+            ]),
+            block_target_records: 4,
+            codec: Codec::None,
+            max_dict_entries: 4_096,
+            encodings: vec![
+                ("ts", VectorEncoding::Delta),
+                ("level", VectorEncoding::Dictionary),
+            ],
+            projections: vec!["user", "error"],
+        },
+        TestVector {
+            name: "dict_overflow",
+            description: "one field fits the dictionary, one has more distinct values than fit",
+            records: records(
+                (0..64)
+                    .map(|i| json!({"level": format!("L{}", i % 4), "host": format!("h{}", i % 5)}))
+                    .collect(),
+            ),
+            block_target_records: 64,
+            codec: Codec::Zstd(3),
+            max_dict_entries: 4,
+            encodings: vec![
+                ("level", VectorEncoding::Dictionary),
+                ("host", VectorEncoding::Plain),
+            ],
+            projections: vec!["host"],
+        },
+        TestVector {
+            name: "delta_ints",
+            description: "delta-encoded integers near the i64 limits, and a non-monotonic column",
+            records: records(
+                (0..32i64)
+                    .map(|i| {
+                        json!({
+                            "seq": 1_000 + i * 5,
+                            "high": i64::MAX - 31 + i,
+                            "low": i64::MIN + i * 2,
+                            "zigzag": if i % 2 == 0 { i } else { -i },
+                        })
+                    })
+                    .collect(),
+            ),
+            block_target_records: 16,
+            codec: Codec::Zstd(3),
+            max_dict_entries: 4_096,
+            encodings: vec![
+                ("seq", VectorEncoding::Delta),
+                ("high", VectorEncoding::Delta),
+                ("low", VectorEncoding::Delta),
+                ("zigzag", VectorEncoding::Plain),
+            ],
+            projections: vec!["high"],
+        },
+        TestVector {
+            name: "decimals",
+            description: "fractional, exponent, and out-of-range integer numbers",
+            records: records(vec![
+                json!({"n": 0.1, "price": 19.99}),
+                json!({"n": -2.5e-7, "price": 0.5}),
+                json!({"n": 1.5e300, "price": 100.25}),
+                json!({"n": 1.2345678901234567, "price": -0.01}),
+                json!({"n": 18446744073709551615u64, "price": 6.02214076e23}),
+            ]),
+            block_target_records: 8,
+            codec: Codec::Zstd(3),
+            max_dict_entries: 4_096,
+            encodings: vec![("price", VectorEncoding::Plain)],
+            projections: vec!["n"],
+        },
+        TestVector {
+            name: "unicode",
+            description: "multi-byte text, escapes, and non-ASCII keys in raw and dictionary data",
+            records: records(
+                [
+                    "héllo wörld",
+                    "日本語テキスト",
+                    "emoji 🎉🚀",
+                    "quote \" backslash \\ tab \t",
+                    "control \u{0001} and line separator \u{2028}",
+                ]
+                .iter()
+                .cycle()
+                .take(20)
+                .enumerate()
+                .map(|(i, text)| {
+                    let greek = ["α", "β"][i % 2];
+                    json!({
+                        "text": format!("{text} #{i}"),
+                        "ключ": greek,
+                        "emoji_🔑": *text,
+                    })
+                })
+                .collect(),
+            ),
+            block_target_records: 20,
+            codec: Codec::Zstd(3),
+            max_dict_entries: 4_096,
+            encodings: vec![
+                ("ключ", VectorEncoding::Dictionary),
+                ("text", VectorEncoding::Plain),
+            ],
+            projections: vec!["emoji_🔑"],
+        },
+        TestVector {
+            name: "mixed_types",
+            description: "every type tag in one column, plus null and absent values",
+            records: records(vec![
+                json!({"v": null, "id": 1}),
+                json!({"v": true, "id": 2}),
+                json!({"v": -42, "id": 3}),
+                json!({"v": 2.75, "id": 4}),
+                json!({"v": "text", "id": 5}),
+                json!({"v": {"nested": {"deep": [1, null]}}, "id": 6}),
+                json!({"v": [1, "two", {"three": 3}], "id": 7}),
+                json!({"id": 8}),
+            ]),
+            block_target_records: 3,
+            codec: Codec::Zstd(3),
+            max_dict_entries: 4_096,
+            encodings: vec![("id", VectorEncoding::Delta)],
+            projections: vec!["v"],
+        },
+    ]
+}
+
+impl TestVector {
+    fn compress_opts(&self) -> CompressOpts {
+        CompressOpts {
+            block_target_records: self.block_target_records,
+            default_codec: self.codec,
+            max_dict_entries: self.max_dict_entries,
+            ..CompressOpts::default()
+        }
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let opts = self.compress_opts();
+        let header = FileHeader {
+            flags: FLAG_NESTED_OPAQUE,
+            default_compressor: opts.default_codec.compressor_id(),
+            default_compression_level: opts.default_codec.level(),
+            block_size_hint_records: opts.block_target_records,
+            user_metadata: Vec::new(),
+        };
+        let mut writer = JacWriter::new(Vec::new(), header, opts)?;
+        writer.write_records(self.records.iter().cloned())?;
+        writer.finish(true)
+    }
+
+    fn projection(&self, field: &str) -> Vec<Value> {
+        self.records
+            .iter()
+            .map(|record| record.get(field).cloned().unwrap_or(Value::Null))
+            .collect()
+    }
+}
+
+fn codec_name(codec: Codec) -> &'static str {
+    match codec {
+        Codec::None => "none",
+        Codec::Zstd(_) | Codec::ZstdWithThreads { .. } | Codec::ZstdAdvanced { .. } => "zstd",
+        Codec::Brotli(_) => "brotli",
+        _ => "other",
+    }
+}
+
+/// Write the built-in vectors and their manifest into `dir` (created if missing).
+///
+/// Fails if this crate no longer produces an encoding a vector is meant to
+/// exercise, since the vector would then not cover its edge case.
+pub fn generate_vectors(dir: &Path) -> Result<VectorManifest> {
+    fs::create_dir_all(dir)?;
+    let mut manifest = VectorManifest {
+        version: MANIFEST_VERSION,
+        vectors: Vec::new(),
+    };
+
+    for vector in builtin_vectors() {
+        let input = format!("{}.ndjson", vector.name);
+        let mut ndjson = BufWriter::new(File::create(dir.join(&input))?);
+        for record in &vector.records {
+            serde_json::to_writer(&mut ndjson, record)?;
+            ndjson.write_all(b"\n")?;
+        }
+        ndjson.flush()?;
+
+        let jac = format!("{}.jac", vector.name);
+        let bytes = vector.encode()?;
+        fs::write(dir.join(&jac), &bytes)?;
+
+        let mut reader = JacReader::new(std::io::Cursor::new(bytes), DecompressOpts::default())?;
+        let used = field_encodings(&mut reader)?;
+        for (field, expected) in &vector.encodings {
+            if !encoding_matches(used.get(*field), *expected) {
+                return Err(JacError::Internal(format!(
+                    "vector '{}' expects {:?} encoding for field '{}'",
+                    vector.name, expected, field
+                )));
+            }
+        }
+
+        manifest.vectors.push(VectorEntry {
+            name: vector.name.to_string(),
+            description: vector.description.to_string(),
+            input,
+            jac,
+            codec: codec_name(vector.codec).to_string(),
+            block_target_records: vector.block_target_records,
+            records: vector.records.len() as u64,
+            blocks: reader.block_count()?,
+            encodings: vector
+                .encodings
+                .iter()
+                .map(|(field, encoding)| (field.to_string(), *encoding))
+                .collect(),
+            projections: vector
+                .projections
+                .iter()
+                .map(|field| (field.to_string(), vector.projection(field)))
+                .collect(),
+        });
+    }
+
+    let manifest_file = File::create(dir.join(MANIFEST_FILE))?;
+    serde_json::to_writer_pretty(BufWriter::new(manifest_file), &manifest)?;
+    Ok(manifest)
+}
+
+/// Check every vector listed in `dir`'s manifest.
+///
+/// Returns one [`VectorCheck`] per vector; only a missing or unreadable
+/// manifest is an error.
+pub fn check_vectors(dir: &Path) -> Result<Vec<VectorCheck>> {
+    let manifest: VectorManifest = serde_json::from_slice(&fs::read(dir.join(MANIFEST_FILE))?)?;
+    if manifest.version > MANIFEST_VERSION {
+        return Err(JacError::UnsupportedFeature(format!(
+            "vector manifest version {}",
+            manifest.version
+        )));
+    }
+
+    Ok(manifest
+        .vectors
+        .iter()
+        .map(|entry| {
+            let mut check = VectorCheck {
+                name: entry.name.clone(),
+                ..VectorCheck::default()
+            };
+            if let Err(err) = check_entry(dir, entry, &mut check) {
+                check.failures.push(format!("{err}"));
+            }
+            check
+        })
+        .collect())
+}
+
+fn check_entry(dir: &Path, entry: &VectorEntry, check: &mut VectorCheck) -> Result<()> {
+    let expected = fs::read_to_string(dir.join(&entry.input))?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str::<Value>)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if expected.len() as u64 != entry.records {
+        check.failures.push(format!(
+            "input has {} records, manifest lists {}",
+            expected.len(),
+            entry.records
+        ));
+    }
+
+    let mut reader = JacReader::open(dir.join(&entry.jac), DecompressOpts::default())?;
+    let decoded = reader
+        .record_stream()?
+        .map(|record| record.map(Value::Object))
+        .collect::<Result<Vec<_>>>()?;
+    if decoded.len() != expected.len() {
+        check.failures.push(format!(
+            "decoded {} records, expected {}",
+            decoded.len(),
+            expected.len()
+        ));
+    }
+    if let Some(idx) = decoded.iter().zip(&expected).position(|(a, b)| a != b) {
+        check
+            .failures
+            .push(format!("record {idx} does not match the input"));
+    }
+
+    for (field, values) in &entry.projections {
+        let projected = reader
+            .multi_projection_stream(&[field.as_str()])?
+            .map(|row| row.map(|mut row| row.pop().flatten().unwrap_or(Value::Null)))
+            .collect::<Result<Vec<_>>>()?;
+        if &projected != values {
+            check
+                .failures
+                .push(format!("projection of '{field}' does not match"));
+        }
+    }
+
+    let blocks = reader.block_count()?;
+    if blocks != entry.blocks {
+        check
+            .warnings
+            .push(format!("{blocks} blocks, generator wrote {}", entry.blocks));
+    }
+    let used = field_encodings(&mut reader)?;
+    for (field, encoding) in &entry.encodings {
+        if !encoding_matches(used.get(field.as_str()), *encoding) {
+            check.warnings.push(format!(
+                "field '{field}' does not use {encoding:?} encoding"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Union of encoding flags per field across all blocks.
+fn field_encodings<R: std::io::Read + std::io::Seek>(
+    reader: &mut JacReader<R>,
+) -> Result<BTreeMap<String, u64>> {
+    let mut flags: BTreeMap<String, u64> = BTreeMap::new();
+    for block in reader.blocks() {
+        for entry in &block?.header.fields {
+            *flags.entry(entry.field_name.clone()).or_default() |= entry.encoding_flags;
+        }
+    }
+    Ok(flags)
+}
+
+fn encoding_matches(flags: Option<&u64>, expected: VectorEncoding) -> bool {
+    let Some(&flags) = flags else {
+        return false;
+    };
+    match expected {
+        VectorEncoding::Plain => flags & (ENCODING_FLAG_DICTIONARY | ENCODING_FLAG_DELTA) == 0,
+        VectorEncoding::Dictionary => flags & ENCODING_FLAG_DICTIONARY != 0,
+        VectorEncoding::Delta => flags & ENCODING_FLAG_DELTA != 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_vectors_check_clean_and_detect_mismatches() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = generate_vectors(dir.path()).unwrap();
+        assert_eq!(manifest.vectors.len(), builtin_vectors().len());
+        let spec = &manifest.vectors[0];
+        assert_eq!(spec.projections["user"], ["alice", "alice", "bob", "carol"]);
+
+        let checks = check_vectors(dir.path()).unwrap();
+        for check in &checks {
+            assert!(check.passed(), "{check:?}");
+            assert!(check.warnings.is_empty(), "{check:?}");
+        }
+
+        // A file encoding different records fails its vector.
+        fs::copy(
+            dir.path().join("unicode.jac"),
+            dir.path().join("decimals.jac"),
+        )
+        .unwrap();
+        let checks = check_vectors(dir.path()).unwrap();
+        let decimals = checks.iter().find(|c| c.name == "decimals").unwrap();
+        assert!(!decimals.passed());
+        assert!(checks.iter().filter(|c| !c.passed()).count() == 1);
+    }
+}
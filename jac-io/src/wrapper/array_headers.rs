@@ -14,13 +14,110 @@
 //! ```
 //!
 //! Each data row is converted to a JSON object using the header as field names.
-
+//!
+//! CSV-like dumps often carry every cell as a string. With
+//! [`ArrayHeadersOptions::type_row`] the second element declares each column's
+//! type (`"int"`, `"decimal"`, `"bool"` or `"string"`) and cells are converted
+//! to it; [`ArrayHeadersOptions::null_markers`] lists strings (such as `""`,
+//! `"NULL"` or `"N/A"`) that stand for null. Decimal cells are parsed as exact
+//! decimals and only accepted when no digit is lost on the way to a JSON
+//! number; declare longer ones `"string"` to keep them as written.
+
+use super::builtin_error::BuiltinWrapperError;
 use super::error::WrapperError;
+use crate::coercion::{coerce, CoercionRule};
 use crate::WrapperLimits;
-use serde_json::{Map, Value};
+use jac_format::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
 use std::io::Read;
 use std::time::Instant;
 
+/// Optional typing of array-with-headers cells
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArrayHeadersOptions {
+    /// Read the element after the header row as column type declarations
+    pub type_row: bool,
+    /// String cells that become null (matched exactly, before type conversion)
+    pub null_markers: Vec<String>,
+}
+
+/// Column type declared in a type row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Int,
+    Decimal,
+    Bool,
+    String,
+}
+
+impl ColumnType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "int" => Some(Self::Int),
+            "decimal" => Some(Self::Decimal),
+            "bool" => Some(Self::Bool),
+            "string" => Some(Self::String),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Int => "int",
+            Self::Decimal => "decimal",
+            Self::Bool => "bool",
+            Self::String => "string",
+        }
+    }
+
+    /// `value` converted to this type, or `None` when it does not hold one
+    fn convert(self, value: Value) -> Option<Value> {
+        if let (Self::Decimal, Value::String(cell)) = (self, &value) {
+            return exact_number(cell).map(Value::Number);
+        }
+        let rule = match self {
+            Self::Int | Self::Decimal => CoercionRule::StringToNumber,
+            Self::Bool => CoercionRule::StringToBool,
+            Self::String => CoercionRule::ToString,
+        };
+        let value = coerce(&value, rule, false).unwrap_or(value);
+        let matches = match (&value, self) {
+            (Value::Null, _) => true,
+            (Value::Number(n), Self::Int) => n.is_i64() || n.is_u64(),
+            (Value::Number(_), Self::Decimal) => true,
+            (Value::Bool(_), Self::Bool) => true,
+            (Value::String(_), Self::String) => true,
+            _ => false,
+        };
+        matches.then_some(value)
+    }
+}
+
+/// The JSON number written in a decimal cell, or `None` when the cell is not a
+/// number or a JSON number cannot carry all of its digits (such as a 25-digit
+/// amount, which would silently round through `f64`)
+fn exact_number(cell: &str) -> Option<Number> {
+    let decimal = normalized(Decimal::from_str_exact(cell).ok()?);
+    let number = serde_json::from_str::<Number>(cell.trim()).ok()?;
+    let stored = normalized(Decimal::from_str_exact(&number.to_string()).ok()?);
+    (stored == decimal).then_some(number)
+}
+
+/// `decimal` without trailing zeros in its digits, so equal values compare equal
+fn normalized(mut decimal: Decimal) -> Decimal {
+    while decimal.digits.len() > 1 && decimal.digits.last() == Some(&b'0') {
+        decimal.digits.pop();
+        decimal.exponent += 1;
+    }
+    if decimal.digits == [b'0'] {
+        decimal.sign = false;
+        decimal.exponent = 0;
+    }
+    decimal
+}
+
 /// Metrics for array-with-headers processing
 #[derive(Debug, Clone)]
 pub struct ArrayHeadersMetrics {
@@ -71,9 +168,20 @@ impl ArrayHeadersStream {
     /// {"id": 1, "name": "Alice", "active": true}
     /// {"id": 2, "name": "Bob", "active": false}
     /// ```
-    pub fn new(
+    pub fn new(reader: Box<dyn Read + Send>, limits: WrapperLimits) -> Result<Self, WrapperError> {
+        Self::with_options(reader, limits, ArrayHeadersOptions::default())
+    }
+
+    /// Create an ArrayHeadersStream that types cells as `options` describe
+    ///
+    /// With `options.type_row`, the second element must be an array of type
+    /// names as long as the header; data rows then start at the third
+    /// element, and a cell that cannot be converted to its column's type is
+    /// an error. Null cells and null markers are accepted in every column.
+    pub fn with_options(
         mut reader: Box<dyn Read + Send>,
         limits: WrapperLimits,
+        options: ArrayHeadersOptions,
     ) -> Result<Self, WrapperError> {
         let start = Instant::now();
 
@@ -102,13 +210,14 @@ impl ArrayHeadersStream {
         })?;
 
         if arr.is_empty() {
-            return Err(WrapperError::InvalidHeaderRow {
+            return Err(BuiltinWrapperError::InvalidHeaderRow {
                 reason: "Array is empty, expected at least a header row".to_string(),
-            });
+            }
+            .into());
         }
 
         // Extract and validate header
-        let header = arr[0].as_array().ok_or_else(|| WrapperError::InvalidHeaderRow {
+        let header = arr[0].as_array().ok_or_else(|| BuiltinWrapperError::InvalidHeaderRow {
             reason: "First element is not an array".to_string(),
         })?;
 
@@ -116,7 +225,7 @@ impl ArrayHeadersStream {
             .iter()
             .enumerate()
             .map(|(i, v)| {
-                v.as_str().ok_or_else(|| WrapperError::InvalidHeaderRow {
+                v.as_str().ok_or_else(|| BuiltinWrapperError::InvalidHeaderRow {
                     reason: format!("Header element {} is not a string: {:?}", i, v),
                 })
                 .map(|s| s.to_string())
@@ -127,30 +236,55 @@ impl ArrayHeadersStream {
         let header_field_count = header_strings.len();
 
         if header_field_count == 0 {
-            return Err(WrapperError::InvalidHeaderRow {
+            return Err(BuiltinWrapperError::InvalidHeaderRow {
                 reason: "Header row is empty".to_string(),
-            });
+            }
+            .into());
         }
 
+        let column_types = if options.type_row {
+            Some(parse_type_row(arr.get(1), header_field_count)?)
+        } else {
+            None
+        };
+        let first_data_row = if column_types.is_some() { 2 } else { 1 };
+
         // Convert data rows to records
-        let mut records = Vec::with_capacity(arr.len() - 1);
+        let mut records = Vec::with_capacity(arr.len().saturating_sub(first_data_row));
 
-        for (row_index, row_value) in arr.iter().skip(1).enumerate() {
-            let row = row_value.as_array().ok_or_else(|| WrapperError::InvalidHeaderRow {
-                reason: format!("Row {} is not an array: {:?}", row_index + 1, row_value),
+        for (row_index, row_value) in arr.iter().enumerate().skip(first_data_row) {
+            let row = row_value.as_array().ok_or_else(|| BuiltinWrapperError::InvalidHeaderRow {
+                reason: format!("Row {} is not an array: {:?}", row_index, row_value),
             })?;
 
             if row.len() != header_field_count {
-                return Err(WrapperError::ArrayRowLengthMismatch {
-                    row_index: row_index + 1,
+                return Err(BuiltinWrapperError::ArrayRowLengthMismatch {
+                    row_index,
                     actual: row.len(),
                     expected: header_field_count,
-                });
+                }
+                .into());
             }
 
             let mut record = Map::new();
-            for (field_name, field_value) in header_strings.iter().zip(row.iter()) {
-                record.insert(field_name.clone(), field_value.clone());
+            for (column, (field_name, field_value)) in
+                header_strings.iter().zip(row.iter()).enumerate()
+            {
+                let mut value = match field_value {
+                    Value::String(s) if options.null_markers.contains(s) => Value::Null,
+                    other => other.clone(),
+                };
+                if let Some(column_type) = column_types.as_ref().map(|types| types[column]) {
+                    value = column_type.convert(value).ok_or_else(|| {
+                        BuiltinWrapperError::CellTypeMismatch {
+                            row_index,
+                            field: field_name.clone(),
+                            expected: column_type.name().to_string(),
+                            value: field_value.to_string(),
+                        }
+                    })?;
+                }
+                record.insert(field_name.clone(), value);
             }
 
             records.push(record);
@@ -177,6 +311,38 @@ impl ArrayHeadersStream {
     }
 }
 
+/// Column types declared by the type row `row`
+fn parse_type_row(
+    row: Option<&Value>,
+    header_field_count: usize,
+) -> Result<Vec<ColumnType>, WrapperError> {
+    let invalid = |reason: String| -> WrapperError {
+        BuiltinWrapperError::InvalidHeaderRow { reason }.into()
+    };
+    let row = row
+        .ok_or_else(|| invalid("Type row expected after the header row".to_string()))?
+        .as_array()
+        .ok_or_else(|| invalid("Type row (second element) is not an array".to_string()))?;
+    if row.len() != header_field_count {
+        return Err(invalid(format!(
+            "Type row has {} elements, expected {} (from header)",
+            row.len(),
+            header_field_count
+        )));
+    }
+    row.iter()
+        .enumerate()
+        .map(|(i, v)| {
+            v.as_str().and_then(ColumnType::parse).ok_or_else(|| {
+                invalid(format!(
+                    "Type row element {} is not one of \"int\", \"decimal\", \"bool\", \"string\": {}",
+                    i, v
+                ))
+            })
+        })
+        .collect()
+}
+
 impl Iterator for ArrayHeadersStream {
     type Item = Result<Map<String, Value>, WrapperError>;
 
@@ -261,7 +427,10 @@ mod tests {
         let input = "[]";
         let reader = Box::new(Cursor::new(input));
         let result = ArrayHeadersStream::new(reader, test_limits());
-        assert!(matches!(result, Err(WrapperError::InvalidHeaderRow { .. })));
+        assert!(matches!(
+            result.as_ref().map_err(BuiltinWrapperError::of),
+            Err(Some(BuiltinWrapperError::InvalidHeaderRow { .. }))
+        ));
     }
 
     #[test]
@@ -272,7 +441,10 @@ mod tests {
         ]"#;
         let reader = Box::new(Cursor::new(input));
         let result = ArrayHeadersStream::new(reader, test_limits());
-        assert!(matches!(result, Err(WrapperError::InvalidHeaderRow { .. })));
+        assert!(matches!(
+            result.as_ref().map_err(BuiltinWrapperError::of),
+            Err(Some(BuiltinWrapperError::InvalidHeaderRow { .. }))
+        ));
     }
 
     #[test]
@@ -283,7 +455,10 @@ mod tests {
         ]"#;
         let reader = Box::new(Cursor::new(input));
         let result = ArrayHeadersStream::new(reader, test_limits());
-        assert!(matches!(result, Err(WrapperError::InvalidHeaderRow { .. })));
+        assert!(matches!(
+            result.as_ref().map_err(BuiltinWrapperError::of),
+            Err(Some(BuiltinWrapperError::InvalidHeaderRow { .. }))
+        ));
     }
 
     #[test]
@@ -296,8 +471,8 @@ mod tests {
         let reader = Box::new(Cursor::new(input));
         let result = ArrayHeadersStream::new(reader, test_limits());
         assert!(matches!(
-            result,
-            Err(WrapperError::ArrayRowLengthMismatch { .. })
+            result.as_ref().map_err(BuiltinWrapperError::of),
+            Err(Some(BuiltinWrapperError::ArrayRowLengthMismatch { .. }))
         ));
     }
 
@@ -364,6 +539,105 @@ mod tests {
         ]"#;
         let reader = Box::new(Cursor::new(input));
         let result = ArrayHeadersStream::new(reader, test_limits());
-        assert!(matches!(result, Err(WrapperError::InvalidHeaderRow { .. })));
+        assert!(matches!(
+            result.as_ref().map_err(BuiltinWrapperError::of),
+            Err(Some(BuiltinWrapperError::InvalidHeaderRow { .. }))
+        ));
+    }
+
+    fn typed_stream(input: &'static str) -> Result<ArrayHeadersStream, WrapperError> {
+        let options = ArrayHeadersOptions {
+            type_row: true,
+            null_markers: vec!["".to_string(), "NULL".to_string(), "N/A".to_string()],
+        };
+        ArrayHeadersStream::with_options(Box::new(Cursor::new(input)), test_limits(), options)
+    }
+
+    #[test]
+    fn test_type_row_and_null_markers() {
+        let input = r#"[
+            ["id", "price", "active", "code"],
+            ["int", "decimal", "bool", "string"],
+            ["1", "9.50", "TRUE", 7],
+            [2, 3, false, "x"],
+            ["NULL", "", "N/A", null]
+        ]"#;
+
+        let stream = typed_stream(input).unwrap();
+        let records: Vec<Value> = stream.map(|r| Value::Object(r.unwrap())).collect();
+        assert_eq!(
+            records,
+            [
+                serde_json::json!({"id": 1, "price": 9.5, "active": true, "code": "7"}),
+                serde_json::json!({"id": 2, "price": 3, "active": false, "code": "x"}),
+                serde_json::json!({"id": null, "price": null, "active": null, "code": null}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decimal_cells_must_convert_exactly() {
+        let stream = typed_stream(r#"[["v"], ["decimal"], ["0.10"], ["-2.5e3"], ["42"]]"#);
+        let records: Vec<Value> = stream
+            .unwrap()
+            .map(|r| r.unwrap().remove("v").unwrap())
+            .collect();
+        assert_eq!(
+            records,
+            [
+                serde_json::json!(0.1),
+                serde_json::json!(-2500.0),
+                serde_json::json!(42)
+            ]
+        );
+
+        let lossy = typed_stream(r#"[["v"], ["decimal"], ["1.2345678901234567890123"]]"#);
+        match lossy.as_ref().map_err(BuiltinWrapperError::of) {
+            Err(Some(BuiltinWrapperError::CellTypeMismatch { expected, .. })) => {
+                assert_eq!(expected, "decimal");
+            }
+            other => panic!("unexpected result: {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_type_row_errors() {
+        let mismatch = typed_stream(r#"[["id"], ["int"], ["1"], ["1.5"]]"#);
+        match mismatch.as_ref().map_err(BuiltinWrapperError::of) {
+            Err(Some(BuiltinWrapperError::CellTypeMismatch {
+                row_index, field, ..
+            })) => {
+                assert_eq!(*row_index, 3);
+                assert_eq!(field, "id");
+            }
+            other => panic!("unexpected result: {:?}", other.err()),
+        }
+
+        for input in [
+            r#"[["id"]]"#,
+            r#"[["id"], ["integer"]]"#,
+            r#"[["id", "name"], ["int"]]"#,
+        ] {
+            assert!(matches!(
+                typed_stream(input)
+                    .as_ref()
+                    .map_err(BuiltinWrapperError::of),
+                Err(Some(BuiltinWrapperError::InvalidHeaderRow { .. }))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_null_markers_without_type_row() {
+        let options = ArrayHeadersOptions {
+            type_row: false,
+            null_markers: vec!["N/A".to_string()],
+        };
+        let input = r#"[["a", "b"], ["N/A", "n/a"]]"#;
+        let stream =
+            ArrayHeadersStream::with_options(Box::new(Cursor::new(input)), test_limits(), options)
+                .unwrap();
+        let records: Vec<Value> = stream.map(|r| Value::Object(r.unwrap())).collect();
+        assert_eq!(records, [serde_json::json!({"a": null, "b": "n/a"})]);
     }
 }
@@ -0,0 +1,190 @@
+//! Errors specific to the built-in wrappers
+//!
+//! Plugins never see these: a built-in wrapper reports them through
+//! [`WrapperError::Other`], so the plugin API stays free of the built-in
+//! wrappers' options.
+
+use super::error::WrapperError;
+use thiserror::Error;
+
+/// Errors raised by the sections, keyed-map and array-with-headers wrappers
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BuiltinWrapperError {
+    /// Section not found in input document
+    #[error(
+        "Section not found: '{section}' at pointer '{pointer}' does not exist.\n\
+         \n\
+         Available keys at this level: {available_keys}\n\
+         \n\
+         Suggested fixes:\n\
+         1. Check section name spelling\n\
+         2. Verify input structure matches expected format\n\
+         3. Use --wrapper-sections-missing-skip to allow missing sections"
+    )]
+    SectionNotFound {
+        /// Name of the section that was not found
+        section: String,
+        /// JSON Pointer path that was used
+        pointer: String,
+        /// Comma-separated list of keys available at the target location
+        available_keys: String,
+    },
+
+    /// Section label field collision
+    #[error(
+        "Section label collision: field '{field}' already exists in record from section '{section}'.\n\
+         \n\
+         The wrapper tried to inject section label into field '{field}', but this field\n\
+         already exists in the source record.\n\
+         \n\
+         Suggested fixes:\n\
+         1. Choose a different label field: --wrapper-section-label-field <field>\n\
+         2. Disable label injection: --wrapper-section-no-label"
+    )]
+    SectionLabelCollision {
+        /// Field name that collided
+        field: String,
+        /// Section that caused the collision
+        section: String,
+    },
+
+    /// Map key too long
+    #[error(
+        "Map key too long: key '{key}' has length {length} bytes (max: {max_length} bytes).\n\
+         \n\
+         This limit prevents malicious inputs and aligns with JAC's max_string_len_per_value.\n\
+         If you have a legitimate use case, consider restructuring your data or preprocessing externally."
+    )]
+    MapKeyTooLong {
+        /// The key that exceeded the limit
+        key: String,
+        /// Actual length of the key (bytes)
+        length: usize,
+        /// Maximum key length allowed (bytes)
+        max_length: usize,
+    },
+
+    /// Key field collision in map mode
+    #[error(
+        "Key field collision: field '{field}' already exists in record with map key '{map_key}'.\n\
+         \n\
+         The wrapper tried to inject the map key into field '{field}', but this field\n\
+         already exists in the source record.\n\
+         \n\
+         Suggested fixes:\n\
+         1. Choose a different key field: --wrapper-map-key-field <field>\n\
+         2. Enable overwrite mode: --wrapper-map-overwrite-key"
+    )]
+    KeyFieldCollision {
+        /// Field name that collided
+        field: String,
+        /// Map key that caused the collision
+        map_key: String,
+    },
+
+    /// Map value is not an object
+    #[error(
+        "Map value not an object: key '{key}' points to {found_type}, expected object.\n\
+         \n\
+         JAC map wrappers require all values to be objects (records).\n\
+         Scalar, null, and array values cannot be processed."
+    )]
+    MapValueNotObject {
+        /// The map key with wrong value type
+        key: String,
+        /// Actual type found
+        found_type: String,
+    },
+
+    /// Nested key path with an empty segment
+    #[error(
+        "Invalid key path '{path}': nested key fields are dotted paths such as 'meta.id' \
+         and cannot have empty segments"
+    )]
+    InvalidKeyPath {
+        /// The offending key path
+        path: String,
+    },
+
+    /// Array header row error
+    #[error(
+        "Invalid header row: {reason}\n\
+         \n\
+         Array-with-headers wrapper requires the first element to be an array of strings.\n\
+         Example: [[\"id\", \"name\"], [1, \"Alice\"], [2, \"Bob\"]]"
+    )]
+    InvalidHeaderRow {
+        /// Reason why the header is invalid
+        reason: String,
+    },
+
+    /// Array row length mismatch
+    #[error(
+        "Row length mismatch: row {row_index} has {actual} elements, expected {expected} (from header).\n\
+         \n\
+         All rows must have the same number of elements as the header row."
+    )]
+    ArrayRowLengthMismatch {
+        /// Index of the row with wrong length
+        row_index: usize,
+        /// Actual number of elements
+        actual: usize,
+        /// Expected number of elements
+        expected: usize,
+    },
+
+    /// Cell value does not match the type declared for its column
+    #[error(
+        "Type mismatch: row {row_index}, field '{field}' is declared {expected} but holds {value}.\n\
+         \n\
+         Fix the value, declare the column \"string\", or list the value as a null marker."
+    )]
+    CellTypeMismatch {
+        /// Index of the data row (counting the header as row 0)
+        row_index: usize,
+        /// Field name from the header row
+        field: String,
+        /// Declared column type
+        expected: String,
+        /// The offending value
+        value: String,
+    },
+}
+
+impl BuiltinWrapperError {
+    /// The built-in error carried by `err`, if any
+    pub fn of(err: &WrapperError) -> Option<&Self> {
+        match err {
+            WrapperError::Other(inner) => inner.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl From<BuiltinWrapperError> for WrapperError {
+    fn from(err: BuiltinWrapperError) -> Self {
+        WrapperError::Other(Box::new(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_errors_travel_in_other_with_their_message() {
+        let builtin = BuiltinWrapperError::InvalidKeyPath {
+            path: "meta..id".to_string(),
+        };
+        let message = builtin.to_string();
+        let err = WrapperError::from(builtin);
+
+        assert_eq!(err.to_string(), message);
+        assert!(matches!(
+            BuiltinWrapperError::of(&err),
+            Some(BuiltinWrapperError::InvalidKeyPath { path }) if path == "meta..id"
+        ));
+        assert!(BuiltinWrapperError::of(&WrapperError::other("plugin failure")).is_none());
+    }
+}
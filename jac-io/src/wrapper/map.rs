@@ -3,7 +3,11 @@
 //! This module implements the KeyedMap wrapper mode, which allows flattening
 //! object-of-objects structures into a record stream. Each object key is injected
 //! as a field in the corresponding record, preserving the original key-value mapping.
+//! Two-level maps (object of objects of objects) yield one record per inner
+//! entry carrying both keys, and key fields may name nested paths such as
+//! `meta.id`.
 
+use super::builtin_error::BuiltinWrapperError;
 use super::error::WrapperError;
 use super::utils::{navigate_pointer, parse_pointer};
 use crate::{KeyCollisionMode, WrapperLimits};
@@ -25,6 +29,46 @@ pub struct KeyedMapStreamMetrics {
     pub processing_duration: Duration,
 }
 
+/// Record fields receiving the keys of a keyed map
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapKeyFields {
+    /// Field for the outer (or only) map key
+    pub key_field: String,
+    /// Field for the inner map key; set for two-level maps, whose entries
+    /// are maps of records
+    pub inner_key_field: Option<String>,
+    /// Treat key fields as dotted paths (`meta.id`) into nested objects,
+    /// created as needed
+    pub nested: bool,
+}
+
+impl MapKeyFields {
+    /// Keys of a one-level map injected as the top-level field `key_field`
+    pub fn single(key_field: impl Into<String>) -> Self {
+        Self {
+            key_field: key_field.into(),
+            inner_key_field: None,
+            nested: false,
+        }
+    }
+
+    fn validate(&self) -> Result<(), WrapperError> {
+        if !self.nested {
+            return Ok(());
+        }
+        let fields = std::iter::once(&self.key_field).chain(self.inner_key_field.as_ref());
+        for field in fields {
+            if field.split('.').any(str::is_empty) {
+                return Err(BuiltinWrapperError::InvalidKeyPath {
+                    path: field.clone(),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Iterator that streams records from a keyed map object
 pub struct KeyedMapStream {
     /// The extracted records with injected keys
@@ -49,11 +93,33 @@ impl KeyedMapStream {
         key_field: String,
         limits: WrapperLimits,
         collision_mode: KeyCollisionMode,
+    ) -> Result<Self, WrapperError> {
+        Self::with_key_fields(
+            reader,
+            pointer,
+            MapKeyFields::single(key_field),
+            limits,
+            collision_mode,
+        )
+    }
+
+    /// Create a KeyedMapStream injecting keys as described by `keys`
+    ///
+    /// With `keys.inner_key_field` set, every map value must itself be a map
+    /// of records; each inner record gets the outer key in `keys.key_field`
+    /// and its own key in the inner key field.
+    pub fn with_key_fields<R: Read>(
+        reader: R,
+        pointer: String,
+        keys: MapKeyFields,
+        limits: WrapperLimits,
+        collision_mode: KeyCollisionMode,
     ) -> Result<Self, WrapperError> {
         let start_time = Instant::now();
 
-        // Validate limits
+        // Validate limits and key paths
         limits.validate()?;
+        keys.validate()?;
 
         // Read entire input into buffer (with size limit enforcement)
         let mut buffer = Vec::new();
@@ -112,43 +178,45 @@ impl KeyedMapStream {
         let max_key_length = Limits::default().max_string_len_per_value;
 
         for (key, value) in map_object {
-            // Validate key length
-            if key.len() > max_key_length {
-                return Err(WrapperError::MapKeyTooLong {
-                    key: key.clone(),
-                    length: key.len(),
-                    max_length: max_key_length,
-                });
-            }
-
-            // Ensure value is an object
-            let mut record = value
-                .as_object()
-                .ok_or_else(|| WrapperError::MapValueNotObject {
-                    key: key.clone(),
-                    found_type: type_name(value).to_string(),
-                })?
-                .clone();
-
-            // Inject key field
-            if record.contains_key(&key_field) {
-                match collision_mode {
-                    KeyCollisionMode::Error => {
-                        return Err(WrapperError::KeyFieldCollision {
-                            field: key_field.clone(),
-                            map_key: key.clone(),
-                        });
-                    }
-                    KeyCollisionMode::Overwrite => {
-                        // Overwrite existing field
-                        record.insert(key_field.clone(), Value::String(key.clone()));
-                    }
-                }
-            } else {
-                record.insert(key_field.clone(), Value::String(key.clone()));
+            check_key_length(key, max_key_length)?;
+            let entry = as_record(value, key)?;
+
+            let Some(inner_key_field) = &keys.inner_key_field else {
+                let mut record = entry.clone();
+                inject_key(
+                    &mut record,
+                    &keys.key_field,
+                    keys.nested,
+                    key,
+                    key,
+                    collision_mode,
+                )?;
+                records.push(record);
+                continue;
+            };
+
+            for (inner_key, inner_value) in entry {
+                check_key_length(inner_key, max_key_length)?;
+                let map_key = format!("{}/{}", key, inner_key);
+                let mut record = as_record(inner_value, &map_key)?.clone();
+                inject_key(
+                    &mut record,
+                    &keys.key_field,
+                    keys.nested,
+                    key,
+                    &map_key,
+                    collision_mode,
+                )?;
+                inject_key(
+                    &mut record,
+                    inner_key_field,
+                    keys.nested,
+                    inner_key,
+                    &map_key,
+                    collision_mode,
+                )?;
+                records.push(record);
             }
-
-            records.push(record);
         }
 
         let entry_count = records.len();
@@ -194,6 +262,78 @@ impl Iterator for KeyedMapStream {
     }
 }
 
+/// Reject map keys too long to be stored as string values
+fn check_key_length(key: &str, max_length: usize) -> Result<(), WrapperError> {
+    if key.len() > max_length {
+        return Err(BuiltinWrapperError::MapKeyTooLong {
+            key: key.to_string(),
+            length: key.len(),
+            max_length,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// The record held by map entry `map_key`
+fn as_record<'a>(value: &'a Value, map_key: &str) -> Result<&'a Map<String, Value>, WrapperError> {
+    value.as_object().ok_or_else(|| {
+        BuiltinWrapperError::MapValueNotObject {
+            key: map_key.to_string(),
+            found_type: type_name(value).to_string(),
+        }
+        .into()
+    })
+}
+
+/// Store `key` in `field` of `record`; with `nested`, `field` is a dotted
+/// path whose missing parents are created. A value already at the path, or
+/// a non-object parent along it, is a collision.
+fn inject_key(
+    record: &mut Map<String, Value>,
+    field: &str,
+    nested: bool,
+    key: &str,
+    map_key: &str,
+    collision_mode: KeyCollisionMode,
+) -> Result<(), WrapperError> {
+    let collision = || -> WrapperError {
+        BuiltinWrapperError::KeyFieldCollision {
+            field: field.to_string(),
+            map_key: map_key.to_string(),
+        }
+        .into()
+    };
+
+    let mut target = record;
+    let mut segments: Vec<&str> = if nested {
+        field.split('.').collect()
+    } else {
+        vec![field]
+    };
+    let leaf = segments.pop().unwrap_or(field);
+    for segment in segments {
+        let parent = target
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !parent.is_object() {
+            match collision_mode {
+                KeyCollisionMode::Error => return Err(collision()),
+                KeyCollisionMode::Overwrite => *parent = Value::Object(Map::new()),
+            }
+        }
+        target = parent
+            .as_object_mut()
+            .expect("parent was just made an object");
+    }
+
+    if target.contains_key(leaf) && collision_mode == KeyCollisionMode::Error {
+        return Err(collision());
+    }
+    target.insert(leaf.to_string(), Value::String(key.to_string()));
+    Ok(())
+}
+
 /// Get a human-readable type name
 fn type_name(value: &Value) -> &'static str {
     match value {
@@ -319,8 +459,8 @@ mod tests {
         );
 
         assert!(matches!(
-            result,
-            Err(WrapperError::KeyFieldCollision { .. })
+            result.as_ref().map_err(BuiltinWrapperError::of),
+            Err(Some(BuiltinWrapperError::KeyFieldCollision { .. }))
         ));
     }
 
@@ -362,8 +502,8 @@ mod tests {
         );
 
         assert!(matches!(
-            result,
-            Err(WrapperError::MapValueNotObject { .. })
+            result.as_ref().map_err(BuiltinWrapperError::of),
+            Err(Some(BuiltinWrapperError::MapValueNotObject { .. }))
         ));
     }
 
@@ -456,7 +596,10 @@ mod tests {
             KeyCollisionMode::Error,
         );
 
-        assert!(matches!(result, Err(WrapperError::MapKeyTooLong { .. })));
+        assert!(matches!(
+            result.as_ref().map_err(BuiltinWrapperError::of),
+            Err(Some(BuiltinWrapperError::MapKeyTooLong { .. }))
+        ));
     }
 
     #[test]
@@ -484,4 +627,96 @@ mod tests {
         let _records: Vec<_> = stream.collect::<Result<Vec<_>, _>>().unwrap();
         // Note: We can't check final metrics here as stream is consumed
     }
+
+    fn collect_with_keys(input: Value, keys: MapKeyFields) -> Result<Vec<Value>, WrapperError> {
+        let reader = Cursor::new(serde_json::to_vec(&input).unwrap());
+        let stream = KeyedMapStream::with_key_fields(
+            reader,
+            "".to_string(),
+            keys,
+            default_limits(),
+            KeyCollisionMode::Error,
+        )?;
+        Ok(stream
+            .map(|record| Value::Object(record.unwrap()))
+            .collect())
+    }
+
+    #[test]
+    fn nested_key_path_injection() {
+        let input = json!({
+            "alice": {"age": 30, "meta": {"team": "ops"}},
+            "bob": {"age": 25}
+        });
+        let keys = MapKeyFields {
+            nested: true,
+            ..MapKeyFields::single("meta.id")
+        };
+
+        let records = collect_with_keys(input.clone(), keys.clone()).unwrap();
+        assert_eq!(
+            records,
+            [
+                json!({"age": 30, "meta": {"team": "ops", "id": "alice"}}),
+                json!({"age": 25, "meta": {"id": "bob"}}),
+            ]
+        );
+
+        let clash = json!({"carol": {"meta": "scalar"}});
+        assert!(matches!(
+            collect_with_keys(clash, keys)
+                .as_ref()
+                .map_err(BuiltinWrapperError::of),
+            Err(Some(BuiltinWrapperError::KeyFieldCollision { .. }))
+        ));
+
+        // Without `nested` the dotted name is an ordinary field
+        let flat = collect_with_keys(input, MapKeyFields::single("meta.id")).unwrap();
+        assert_eq!(flat[1], json!({"age": 25, "meta.id": "bob"}));
+
+        let empty_segment = MapKeyFields {
+            nested: true,
+            ..MapKeyFields::single("meta..id")
+        };
+        assert!(matches!(
+            collect_with_keys(json!({}), empty_segment)
+                .as_ref()
+                .map_err(BuiltinWrapperError::of),
+            Err(Some(BuiltinWrapperError::InvalidKeyPath { .. }))
+        ));
+    }
+
+    #[test]
+    fn two_level_map_composite_keys() {
+        let input = json!({
+            "prod": {
+                "db": {"port": 5432},
+                "cache": {"port": 6379}
+            },
+            "dev": {
+                "db": {"port": 15432}
+            }
+        });
+        let keys = MapKeyFields {
+            key_field: "env".to_string(),
+            inner_key_field: Some("service".to_string()),
+            nested: false,
+        };
+
+        let records = collect_with_keys(input, keys.clone()).unwrap();
+        assert_eq!(
+            records,
+            [
+                json!({"port": 5432, "env": "prod", "service": "db"}),
+                json!({"port": 6379, "env": "prod", "service": "cache"}),
+                json!({"port": 15432, "env": "dev", "service": "db"}),
+            ]
+        );
+
+        let err = collect_with_keys(json!({"prod": {"db": 5432}}), keys).unwrap_err();
+        match BuiltinWrapperError::of(&err) {
+            Some(BuiltinWrapperError::MapValueNotObject { key, .. }) => assert_eq!(key, "prod/db"),
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
 }
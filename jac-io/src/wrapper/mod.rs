@@ -10,7 +10,8 @@
 //! - **Sections**: Concatenate multiple named arrays from a single object
 //! - **KeyedMap**: Flatten object-of-objects into records with key injection
 //! - **ArrayWithHeaders**: Convert CSV-like arrays (first row = headers) to records
-//! - **Plugin**: Custom wrapper implementations via the plugin system
+//! - **Plugin**: Custom wrapper implementations via the plugin system, whose
+//!   traits live in the separately versioned `jac-plugin-api` crate
 //!
 //! # Security & Limits
 //!
@@ -26,18 +27,22 @@
 //! If you need to preserve the original structure, archive the source file separately.
 
 pub mod array_headers;
-pub mod error;
+pub mod builtin_error;
 pub mod map;
-pub mod plugin;
 pub mod pointer;
 pub mod sections;
 pub mod utils;
 
-pub use array_headers::ArrayHeadersStream;
+// The plugin API lives in `jac-plugin-api` so plugins do not depend on jac-io
+pub use jac_plugin_api::{error, plugin, WrapperLimits};
+
+pub use array_headers::{ArrayHeadersOptions, ArrayHeadersStream};
+pub use builtin_error::BuiltinWrapperError;
 pub use error::WrapperError;
-pub use map::KeyedMapStream;
+pub use map::{KeyedMapStream, MapKeyFields};
 pub use plugin::{
-    FieldHint, FieldType, SchemaHints, WrapperPlugin, WrapperPluginMetadata, WrapperPluginRegistry,
+    FieldHint, FieldType, PluginRecords, SchemaHints, WrapperPlugin, WrapperPluginMetadata,
+    WrapperPluginRegistry,
 };
 pub use pointer::PointerArrayStream;
 pub use sections::SectionsStream;
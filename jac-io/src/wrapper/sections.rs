@@ -4,6 +4,7 @@
 //! multiple named arrays from a single top-level JSON object into a unified
 //! record stream. Each section can optionally be labeled to preserve provenance.
 
+use super::builtin_error::BuiltinWrapperError;
 use super::error::WrapperError;
 use super::utils::{navigate_pointer, parse_pointer};
 use crate::{MissingSectionBehavior, SectionSpec, WrapperLimits};
@@ -20,6 +21,8 @@ pub struct SectionsStreamMetrics {
     pub records_emitted: usize,
     /// Per-section record counts (section name, count)
     pub section_counts: Vec<(String, usize)>,
+    /// Sections cut short by their `max_records` (section name, elements skipped)
+    pub sections_truncated: Vec<(String, usize)>,
     /// Time spent processing
     pub processing_duration: Duration,
 }
@@ -97,6 +100,7 @@ impl SectionsStream {
         // Extract sections
         let mut extracted_sections = Vec::new();
         let mut section_counts = Vec::new();
+        let mut sections_truncated = Vec::new();
 
         for section_spec in sections {
             // Parse pointer
@@ -121,11 +125,12 @@ impl SectionsStream {
                             continue;
                         }
                         MissingSectionBehavior::Error => {
-                            return Err(WrapperError::SectionNotFound {
+                            return Err(BuiltinWrapperError::SectionNotFound {
                                 section: section_spec.name,
                                 pointer,
                                 available_keys,
-                            });
+                            }
+                            .into());
                         }
                     }
                 }
@@ -155,16 +160,24 @@ impl SectionsStream {
                 None
             };
 
-            for element in section_array {
+            let take = section_spec.max_records.unwrap_or(usize::MAX);
+            for element in section_array.iter().take(take) {
                 // Each element must be an object
-                let mut record = element
-                    .as_object()
-                    .ok_or_else(|| WrapperError::PointerTargetWrongType {
-                        pointer: format!("{}[element]", section_spec.pointer),
-                        expected_type: "object".to_string(),
-                        found_type: type_name(element).to_string(),
-                    })?
-                    .clone();
+                let object =
+                    element
+                        .as_object()
+                        .ok_or_else(|| WrapperError::PointerTargetWrongType {
+                            pointer: format!("{}[element]", section_spec.pointer),
+                            expected_type: "object".to_string(),
+                            found_type: type_name(element).to_string(),
+                        })?;
+                let mut record = match &section_spec.field_prefix {
+                    Some(prefix) => object
+                        .iter()
+                        .map(|(key, value)| (format!("{}{}", prefix, key), value.clone()))
+                        .collect(),
+                    None => object.clone(),
+                };
 
                 // Inject label if requested
                 if let Some(ref label) = label_to_inject {
@@ -173,10 +186,11 @@ impl SectionsStream {
 
                     // Check for collision
                     if record.contains_key(field) {
-                        return Err(WrapperError::SectionLabelCollision {
+                        return Err(BuiltinWrapperError::SectionLabelCollision {
                             field: field.clone(),
                             section: section_spec.name.clone(),
-                        });
+                        }
+                        .into());
                     }
 
                     record.insert(field.clone(), Value::String(label.clone()));
@@ -186,6 +200,9 @@ impl SectionsStream {
             }
 
             let count = section_records.len();
+            if section_array.len() > count {
+                sections_truncated.push((section_spec.name.clone(), section_array.len() - count));
+            }
             section_counts.push((section_spec.name.clone(), count));
             extracted_sections.push((section_spec.name.clone(), section_records));
         }
@@ -200,6 +217,7 @@ impl SectionsStream {
                 peak_buffer_bytes,
                 records_emitted: 0,
                 section_counts,
+                sections_truncated,
                 processing_duration,
             },
         })
@@ -270,11 +288,15 @@ mod tests {
                 name: "users".to_string(),
                 pointer: "/users".to_string(),
                 label: None,
+                field_prefix: None,
+                max_records: None,
             },
             SectionSpec {
                 name: "guests".to_string(),
                 pointer: "/guests".to_string(),
                 label: None,
+                field_prefix: None,
+                max_records: None,
             },
         ];
 
@@ -309,11 +331,15 @@ mod tests {
                 name: "users".to_string(),
                 pointer: "/users".to_string(),
                 label: Some("user".to_string()),
+                field_prefix: None,
+                max_records: None,
             },
             SectionSpec {
                 name: "admins".to_string(),
                 pointer: "/admins".to_string(),
                 label: Some("admin".to_string()),
+                field_prefix: None,
+                max_records: None,
             },
         ];
 
@@ -346,11 +372,15 @@ mod tests {
                 name: "users".to_string(),
                 pointer: "/users".to_string(),
                 label: None,
+                field_prefix: None,
+                max_records: None,
             },
             SectionSpec {
                 name: "admins".to_string(),
                 pointer: "/admins".to_string(),
                 label: None,
+                field_prefix: None,
+                max_records: None,
             },
         ];
 
@@ -387,6 +417,8 @@ mod tests {
             name: "admins".to_string(),
             pointer: "/admins".to_string(),
             label: None,
+            field_prefix: None,
+            max_records: None,
         }];
 
         let result = SectionsStream::new(
@@ -398,7 +430,10 @@ mod tests {
             MissingSectionBehavior::Error,
         );
 
-        assert!(matches!(result, Err(WrapperError::SectionNotFound { .. })));
+        assert!(matches!(
+            result.as_ref().map_err(BuiltinWrapperError::of),
+            Err(Some(BuiltinWrapperError::SectionNotFound { .. }))
+        ));
     }
 
     #[test]
@@ -412,6 +447,8 @@ mod tests {
             name: "users".to_string(),
             pointer: "/users".to_string(),
             label: None,
+            field_prefix: None,
+            max_records: None,
         }];
 
         let result = SectionsStream::new(
@@ -424,8 +461,8 @@ mod tests {
         );
 
         assert!(matches!(
-            result,
-            Err(WrapperError::SectionLabelCollision { .. })
+            result.as_ref().map_err(BuiltinWrapperError::of),
+            Err(Some(BuiltinWrapperError::SectionLabelCollision { .. }))
         ));
     }
 
@@ -440,6 +477,8 @@ mod tests {
             name: "users".to_string(),
             pointer: "/users".to_string(),
             label: None,
+            field_prefix: None,
+            max_records: None,
         }];
 
         let mut small_limits = WrapperLimits::default();
@@ -473,11 +512,15 @@ mod tests {
                 name: "users".to_string(),
                 pointer: "/users".to_string(),
                 label: None,
+                field_prefix: None,
+                max_records: None,
             },
             SectionSpec {
                 name: "admins".to_string(),
                 pointer: "/admins".to_string(),
                 label: None,
+                field_prefix: None,
+                max_records: None,
             },
         ];
 
@@ -505,6 +548,8 @@ mod tests {
             name: "users".to_string(),
             pointer: "/users".to_string(),
             label: None,
+            field_prefix: None,
+            max_records: None,
         }];
 
         let result = SectionsStream::new(
@@ -533,6 +578,8 @@ mod tests {
             name: "users".to_string(),
             pointer: "/users".to_string(),
             label: None,
+            field_prefix: None,
+            max_records: None,
         }];
 
         let result = SectionsStream::new(
@@ -549,4 +596,58 @@ mod tests {
             Err(WrapperError::PointerTargetWrongType { .. })
         ));
     }
+
+    #[test]
+    fn sections_stream_applies_field_prefixes_and_record_limits() {
+        let input = json!({
+            "users": [{"id": 1, "name": "alice"}, {"id": 2, "name": "bob"}],
+            "orders": [{"id": 10}, {"id": 11}, {"id": 12}]
+        });
+
+        let input_bytes = serde_json::to_vec(&input).unwrap();
+        let sections = vec![
+            SectionSpec {
+                name: "users".to_string(),
+                pointer: "/users".to_string(),
+                label: None,
+                field_prefix: Some("user_".to_string()),
+                max_records: None,
+            },
+            SectionSpec {
+                name: "orders".to_string(),
+                pointer: "/orders".to_string(),
+                label: None,
+                field_prefix: Some("order_".to_string()),
+                max_records: Some(2),
+            },
+        ];
+
+        let stream = SectionsStream::new(
+            input_bytes.as_slice(),
+            sections,
+            WrapperLimits::default(),
+            None,
+            true,
+            MissingSectionBehavior::Skip,
+        )
+        .unwrap();
+
+        let section_counts = stream.metrics().section_counts.clone();
+        let truncated = stream.metrics().sections_truncated.clone();
+        let records: Vec<Value> = stream.map(|r| Value::Object(r.unwrap())).collect();
+        assert_eq!(
+            records,
+            [
+                json!({"user_id": 1, "user_name": "alice", "_section": "users"}),
+                json!({"user_id": 2, "user_name": "bob", "_section": "users"}),
+                json!({"order_id": 10, "_section": "orders"}),
+                json!({"order_id": 11, "_section": "orders"}),
+            ]
+        );
+        assert_eq!(
+            section_counts,
+            [("users".to_string(), 2), ("orders".to_string(), 2)]
+        );
+        assert_eq!(truncated, [("orders".to_string(), 1)]);
+    }
 }
@@ -1,10 +1,20 @@
 //! Streaming writer for JAC files
 
-use jac_codec::{BlockBuilder, BlockData, BlockFinish, CompressOpts, TryAddRecordOutcome};
-use jac_format::{BlockIndexEntry, FileHeader, IndexFooter, JacError, Result};
-use std::collections::HashMap;
+use jac_codec::{
+    BlockBuilder, BlockData, BlockDecoder, BlockFinish, CompressOpts, DecompressOpts,
+    TryAddRecordOutcome,
+};
+use jac_format::checksum::compute_crc32c;
+use jac_format::constants::BLOCK_ALIGNMENT;
+use jac_format::{
+    BlockHeaderFormat, BlockIndexEntry, FileHeader, IndexFooter, JacError, Result, TypeTag,
+};
+use std::collections::{BTreeMap, HashMap};
 use std::io::Write;
 
+/// Zero bytes written in front of aligned blocks
+static ZERO_PADDING: [u8; BLOCK_ALIGNMENT as usize] = [0; BLOCK_ALIGNMENT as usize];
+
 /// JAC writer for streaming compression
 pub struct JacWriter<W: Write> {
     writer: Option<W>,
@@ -14,6 +24,16 @@ pub struct JacWriter<W: Write> {
     current_offset: u64,
     finished: bool,
     metrics: WriterMetrics,
+    /// Block header format of the file, with the field names defined so far
+    block_format: BlockHeaderFormat,
+    /// Pad block starts to `BLOCK_ALIGNMENT` (`FLAG_ALIGNED_BLOCKS`)
+    align_blocks: bool,
+    /// Largest size the current file may reach, index included
+    size_cap: Option<u64>,
+    /// Encoded length of the index entries of the current file
+    index_entries_len: u64,
+    /// Blocks held back because they would push the file past `size_cap`
+    deferred: Vec<BlockFinish>,
 }
 
 impl<W: Write> JacWriter<W> {
@@ -36,9 +56,66 @@ impl<W: Write> JacWriter<W> {
             current_offset: header_bytes.len() as u64,
             finished: false,
             metrics,
+            block_format: BlockHeaderFormat::for_file(&header),
+            align_blocks: header.aligned_blocks(),
+            size_cap: None,
+            index_entries_len: 0,
+            deferred: Vec::new(),
         })
     }
 
+    /// Cap the size of each file, index and pointer included.
+    ///
+    /// A block that would push the file past `cap` is held back rather than
+    /// written, and [`at_size_cap`](Self::at_size_cap) turns true until
+    /// [`continue_into`](Self::continue_into) moves it to a new file. The first
+    /// block of a file is always written, so a block larger than `cap` gets a
+    /// file of its own.
+    pub fn set_size_cap(&mut self, cap: Option<u64>) {
+        self.size_cap = cap;
+    }
+
+    /// Whether blocks are waiting for [`continue_into`](Self::continue_into).
+    pub fn at_size_cap(&self) -> bool {
+        !self.deferred.is_empty()
+    }
+
+    /// Finish the current file and continue writing into `next`.
+    ///
+    /// The current file gets its index (when `with_index` is set) but keeps
+    /// the records still being buffered; `next` starts with `header`, followed
+    /// by the blocks held back by the size cap. Metrics keep accumulating
+    /// across files. Returns the writer of the finished file.
+    pub fn continue_into(
+        &mut self,
+        mut next: W,
+        header: FileHeader,
+        with_index: bool,
+    ) -> Result<W> {
+        if with_index {
+            self.write_index()?;
+        }
+
+        let header_bytes = header.encode()?;
+        next.write_all(&header_bytes)?;
+        let finished = self
+            .writer
+            .replace(next)
+            .ok_or_else(|| JacError::Internal("JacWriter internal writer missing".to_string()))?;
+
+        self.block_index.clear();
+        self.index_entries_len = 0;
+        self.current_offset = header_bytes.len() as u64;
+        self.metrics.bytes_written += header_bytes.len() as u64;
+        self.block_format = BlockHeaderFormat::for_file(&header);
+        self.align_blocks = header.aligned_blocks();
+
+        for block_finish in std::mem::take(&mut self.deferred) {
+            self.write_compressed_block(block_finish)?;
+        }
+        Ok(finished)
+    }
+
     /// Write record to current block
     pub fn write_record(&mut self, rec: &serde_json::Map<String, serde_json::Value>) -> Result<()> {
         let mut pending = rec.clone();
@@ -81,12 +158,47 @@ impl<W: Write> JacWriter<W> {
         if block_finish.data.header.record_count == 0 {
             return Ok(());
         }
+        if !self.deferred.is_empty() {
+            self.deferred.push(block_finish);
+            return Ok(());
+        }
 
+        let format_before = self.size_cap.map(|_| self.block_format.clone());
         let block_bytes = self.encode_block(&block_finish.data)?;
+        let padding = if self.align_blocks {
+            self.current_offset.next_multiple_of(BLOCK_ALIGNMENT) - self.current_offset
+        } else {
+            0
+        };
+        let block_offset = self.current_offset + padding;
+        let block_size = block_bytes.len();
+        let record_count = block_finish.data.header.record_count;
+        let entry_len = uleb128_len(block_offset)
+            + uleb128_len(block_size as u64)
+            + uleb128_len(record_count as u64);
+
+        if let (Some(cap), Some(format_before)) = (self.size_cap, format_before) {
+            let projected = block_offset
+                + block_size as u64
+                + index_bound(
+                    self.block_index.len() + 1,
+                    self.index_entries_len + entry_len,
+                );
+            if !self.block_index.is_empty() && projected > cap {
+                // Encoding defined this block's field names in the file's
+                // table; the block will be encoded again for the next file.
+                self.block_format = format_before;
+                self.deferred.push(block_finish);
+                return Ok(());
+            }
+        }
 
         self.metrics.segment_limit_flushes += block_finish.segment_limit_flushes as u64;
         self.metrics.segment_limit_record_rejections +=
             block_finish.segment_limit_record_rejections as u64;
+        self.metrics.dictionary_hits += block_finish.dictionary_hits;
+        self.metrics.dictionary_misses += block_finish.dictionary_misses;
+        self.metrics.dictionary_spills += block_finish.dictionary_spills.len() as u64;
 
         for (field_name, flush_count) in &block_finish.per_field_flush_count {
             self.metrics
@@ -95,6 +207,13 @@ impl<W: Write> JacWriter<W> {
                 .or_insert_with(FieldMetrics::default)
                 .flush_count += flush_count;
         }
+        for field_name in &block_finish.dictionary_spills {
+            self.metrics
+                .per_field_metrics
+                .entry(field_name.clone())
+                .or_default()
+                .dictionary_spills += 1;
+        }
         for (field_name, rejection_count) in &block_finish.per_field_rejection_count {
             self.metrics
                 .per_field_metrics
@@ -102,6 +221,19 @@ impl<W: Write> JacWriter<W> {
                 .or_insert_with(FieldMetrics::default)
                 .rejection_count += rejection_count;
         }
+        for (field_name, counts) in &block_finish.per_field_type_counts {
+            let drift = self
+                .metrics
+                .type_drift
+                .entry(field_name.clone())
+                .or_default();
+            for (tag, count) in counts {
+                *drift.counts.entry(*tag).or_insert(0) += count;
+            }
+            if counts.keys().filter(|tag| **tag != TypeTag::Null).count() > 1 {
+                drift.mixed_blocks += 1;
+            }
+        }
         for (field_name, max_segment) in &block_finish.per_field_max_segment {
             let entry = self
                 .metrics
@@ -113,18 +245,16 @@ impl<W: Write> JacWriter<W> {
             }
         }
 
-        let block_offset = self.current_offset;
-        let block_size = block_bytes.len();
-        let record_count = block_finish.data.header.record_count;
-
         self.block_index.push(BlockIndexEntry {
             block_offset,
             block_size,
             record_count,
         });
+        self.index_entries_len += entry_len;
         self.metrics.blocks_written += 1;
 
         if let Some(writer) = self.writer.as_mut() {
+            writer.write_all(&ZERO_PADDING[..padding as usize])?;
             writer.write_all(&block_bytes)?;
         } else {
             return Err(JacError::Internal(
@@ -132,24 +262,56 @@ impl<W: Write> JacWriter<W> {
             ));
         }
 
-        self.current_offset += block_size as u64;
-        self.metrics.bytes_written += block_size as u64;
+        self.current_offset = block_offset + block_size as u64;
+        self.metrics.bytes_written += padding + block_size as u64;
 
         Ok(())
     }
 
+    /// Copy an already-encoded block (e.g. from [`crate::JacReader::read_raw_block`]) verbatim.
+    ///
+    /// Records buffered by `write_record` are flushed first so file order is
+    /// preserved. The block CRC, segment layout, and this writer's limits are
+    /// verified before anything is written.
+    pub fn write_raw_block(&mut self, block: BlockData) -> Result<()> {
+        if block.segments.len() != block.header.fields.len() {
+            return Err(JacError::CorruptBlock);
+        }
+
+        let encoded = encode_block_bytes(&block, &mut BlockHeaderFormat::default())?;
+        let verify = DecompressOpts {
+            limits: self.opts.limits.clone(),
+            verify_checksums: true,
+            ..DecompressOpts::default()
+        };
+        BlockDecoder::new(&encoded, &verify)?;
+
+        self.flush_block()?;
+        let record_count = block.header.record_count as u64;
+        self.write_compressed_block(BlockFinish {
+            data: block,
+            segment_limit_flushes: 0,
+            segment_limit_record_rejections: 0,
+            per_field_flush_count: HashMap::new(),
+            per_field_rejection_count: HashMap::new(),
+            per_field_max_segment: HashMap::new(),
+            per_field_type_counts: HashMap::new(),
+            dictionary_hits: 0,
+            dictionary_misses: 0,
+            dictionary_spills: Vec::new(),
+        })?;
+        self.metrics.records_written += record_count;
+        Ok(())
+    }
+
     /// Flush current block to output
     fn flush_block(&mut self) -> Result<()> {
         if self.block_builder.record_count() == 0 {
             return Ok(());
         }
 
-        // Finalize the block (this consumes the block builder)
-        let block_finish = std::mem::replace(
-            &mut self.block_builder,
-            BlockBuilder::new(self.opts.clone()),
-        )
-        .finalize()?;
+        // Finalize the block, keeping the column builders for the next one
+        let block_finish = self.block_builder.finalize_and_reset()?;
         self.write_compressed_block(block_finish)
     }
 
@@ -159,22 +321,8 @@ impl<W: Write> JacWriter<W> {
     }
 
     /// Encode block data to bytes
-    fn encode_block(&self, block_data: &BlockData) -> Result<Vec<u8>> {
-        let mut result = Vec::new();
-
-        // Encode block header
-        let header_bytes = block_data.header.encode()?;
-        result.extend_from_slice(&header_bytes);
-
-        // Write all compressed segments
-        for segment in &block_data.segments {
-            result.extend_from_slice(segment);
-        }
-
-        // Write CRC32C
-        result.extend_from_slice(&block_data.crc32c.to_le_bytes());
-
-        Ok(result)
+    fn encode_block(&mut self, block_data: &BlockData) -> Result<Vec<u8>> {
+        encode_block_bytes(block_data, &mut self.block_format)
     }
 
     /// Get current file offset
@@ -185,36 +333,14 @@ impl<W: Write> JacWriter<W> {
     fn finalize(mut self, with_index: bool) -> Result<WriterFinish<W>> {
         // Flush final block
         self.flush_block()?;
+        if self.at_size_cap() {
+            return Err(JacError::Internal(
+                "JacWriter finished with blocks held back by its size cap".to_string(),
+            ));
+        }
 
-        if with_index && !self.block_index.is_empty() {
-            // Write index footer
-            let index = IndexFooter {
-                blocks: self.block_index.clone(),
-            };
-            let index_bytes = index.encode()?;
-            let index_offset = self.get_current_offset();
-
-            if let Some(writer) = self.writer.as_mut() {
-                writer.write_all(&index_bytes)?;
-            } else {
-                return Err(JacError::Internal(
-                    "JacWriter internal writer missing".to_string(),
-                ));
-            }
-            self.current_offset += index_bytes.len() as u64;
-            self.metrics.bytes_written += index_bytes.len() as u64;
-
-            // Write 8-byte pointer to index (little-endian u64)
-            if let Some(writer) = self.writer.as_mut() {
-                writer.write_all(&index_offset.to_le_bytes())?;
-            } else {
-                return Err(JacError::Internal(
-                    "JacWriter internal writer missing".to_string(),
-                ));
-            }
-            let pointer_len = std::mem::size_of::<u64>() as u64;
-            self.current_offset += pointer_len;
-            self.metrics.bytes_written += pointer_len;
+        if with_index {
+            self.write_index()?;
         }
 
         self.finished = true;
@@ -229,6 +355,41 @@ impl<W: Write> JacWriter<W> {
         })
     }
 
+    /// Write the index footer and the pointer to it.
+    fn write_index(&mut self) -> Result<()> {
+        // An empty footer for zero-block files lets readers tell a finished
+        // empty file from a truncated one
+        let index = IndexFooter {
+            blocks: self.block_index.clone(),
+            field_names: self.block_format.field_names.clone(),
+        };
+        let index_bytes = index.encode()?;
+        let index_offset = self.get_current_offset();
+
+        if let Some(writer) = self.writer.as_mut() {
+            writer.write_all(&index_bytes)?;
+        } else {
+            return Err(JacError::Internal(
+                "JacWriter internal writer missing".to_string(),
+            ));
+        }
+        self.current_offset += index_bytes.len() as u64;
+        self.metrics.bytes_written += index_bytes.len() as u64;
+
+        // Write 8-byte pointer to index (little-endian u64)
+        if let Some(writer) = self.writer.as_mut() {
+            writer.write_all(&index_offset.to_le_bytes())?;
+        } else {
+            return Err(JacError::Internal(
+                "JacWriter internal writer missing".to_string(),
+            ));
+        }
+        let pointer_len = std::mem::size_of::<u64>() as u64;
+        self.current_offset += pointer_len;
+        self.metrics.bytes_written += pointer_len;
+        Ok(())
+    }
+
     /// Finish writing and optionally write index (legacy API).
     pub fn finish(self, with_index: bool) -> Result<W> {
         let finish = self.finalize(with_index)?;
@@ -249,6 +410,49 @@ impl<W: Write> JacWriter<W> {
     pub fn metrics(&self) -> WriterMetrics {
         self.metrics.clone()
     }
+
+    /// Records accepted so far, including those in the pending block.
+    pub fn records_written(&self) -> u64 {
+        self.metrics.records_written
+    }
+
+    /// Bytes flushed to the underlying writer so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.metrics.bytes_written
+    }
+}
+
+/// Bytes of a ULEB128 encoding of `value`.
+fn uleb128_len(value: u64) -> u64 {
+    u64::from((64 - value.leading_zeros()).max(1).div_ceil(7))
+}
+
+/// Upper bound on the index footer and pointer of a file with `blocks`
+/// blocks whose entries encode to `entries_len` bytes.
+fn index_bound(blocks: usize, entries_len: u64) -> u64 {
+    // magic, index_len, block count, entries, CRC32C, pointer
+    4 + 10 + uleb128_len(blocks as u64) + entries_len + 4 + 8
+}
+
+/// Encode a block with its header in `format`.
+fn encode_block_bytes(block_data: &BlockData, format: &mut BlockHeaderFormat) -> Result<Vec<u8>> {
+    // Encode block header
+    let mut result = block_data.header.encode_with_format(format)?;
+
+    // Write all compressed segments in payload (offset) order
+    for segment in block_data.payload_segments() {
+        result.extend_from_slice(segment);
+    }
+
+    // Write CRC32C (the codec's covers the plain header encoding)
+    let crc32c = if !format.is_plain() {
+        compute_crc32c(&result)
+    } else {
+        block_data.crc32c
+    };
+    result.extend_from_slice(&crc32c.to_le_bytes());
+
+    Ok(result)
 }
 
 impl<W: Write> Drop for JacWriter<W> {
@@ -278,6 +482,9 @@ pub struct FieldMetrics {
     pub rejection_count: u64,
     /// Maximum segment size observed for this field (uncompressed bytes).
     pub max_segment_size_seen: usize,
+    /// Number of blocks in which this field outgrew its dictionary limit and
+    /// its strings were stored raw.
+    pub dictionary_spills: u64,
 }
 
 /// Metrics emitted by `JacWriter` to aid progress reporting.
@@ -293,7 +500,52 @@ pub struct WriterMetrics {
     pub segment_limit_flushes: u64,
     /// Number of records rejected because a single field exceeded segment limits.
     pub segment_limit_record_rejections: u64,
+    /// String values that matched an earlier distinct string in their block column.
+    pub dictionary_hits: u64,
+    /// String values that added a new distinct string to their block column.
+    pub dictionary_misses: u64,
+    /// Field segments stored as raw strings because the field outgrew its
+    /// dictionary limit mid-block (one per field per block).
+    pub dictionary_spills: u64,
     /// Per-field breakdown of flush/rejection events and max segment sizes.
     /// Only populated when per-field tracking is enabled in the encoder.
     pub per_field_metrics: HashMap<String, FieldMetrics>,
+    /// Value types observed per field across the blocks this writer encoded.
+    /// Blocks copied with `write_raw_block` are not counted.
+    pub type_drift: BTreeMap<String, FieldTypeDrift>,
+}
+
+impl WriterMetrics {
+    /// Fields that carried more than one non-null value type.
+    ///
+    /// Such columns cannot use a single dictionary or delta encoding, which is
+    /// usually why a field expected to compress well did not.
+    pub fn drifting_fields(&self) -> impl Iterator<Item = (&String, &FieldTypeDrift)> {
+        self.type_drift.iter().filter(|(_, drift)| drift.is_mixed())
+    }
+}
+
+/// Value types observed for one field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldTypeDrift {
+    /// Present values per type tag, summed over all blocks.
+    pub counts: BTreeMap<TypeTag, u64>,
+    /// Blocks in which the field held more than one non-null type.
+    pub mixed_blocks: u64,
+}
+
+impl FieldTypeDrift {
+    /// Type tags observed, in tag order.
+    pub fn types(&self) -> Vec<TypeTag> {
+        self.counts.keys().copied().collect()
+    }
+
+    /// Whether the field carried more than one non-null type.
+    pub fn is_mixed(&self) -> bool {
+        self.counts
+            .keys()
+            .filter(|tag| **tag != TypeTag::Null)
+            .count()
+            > 1
+    }
 }
@@ -1,11 +1,13 @@
 use jac_format::Limits;
 use jac_io::{
     execute_compress, execute_decompress, execute_project, parallel::ParallelConfig,
-    CompressOptions, CompressRequest, ContainerFormat, DecompressFormat, DecompressOptions,
-    DecompressRequest, InputSource, JacInput, OutputSink, ProjectFormat, ProjectRequest,
-    WrapperConfig,
+    CoercionPolicy, CompressOptions, CompressRequest, ContainerFormat, DeclaredLimitsPolicy,
+    DecompressFormat, DecompressOptions, DecompressRequest, DictionaryPolicy, DuplicateKeyPolicy,
+    InjectFields, InputSource, JacInput, KeyNormalization, NumberCanonicalization, OutputSink,
+    OversizedRecordPolicy, ProjectFormat, ProjectRequest, Utf8Policy,
 };
 use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -155,21 +157,43 @@ impl ConcurrencyStressTest {
                     block_target_records: config.block_size,
                     default_codec: jac_codec::Codec::Zstd(config.compression_level),
                     canonicalize_keys: true,
+                    preserve_key_order: false,
+                    hot_fields: Vec::new(),
                     canonicalize_numbers: true,
+                    number_canonicalization: NumberCanonicalization::default(),
                     nested_opaque: true,
                     max_dict_entries: 4096,
+                    dictionary_policy: DictionaryPolicy::default(),
+                    field_hints: BTreeMap::new(),
+                    field_stats: false,
+                    quantile_sketches: false,
                     limits: Limits::default(),
                     parallel_config: ParallelConfig::default(),
+                    duplicate_key_policy: DuplicateKeyPolicy::default(),
+                    utf8_policy: Utf8Policy::default(),
+                    max_record_bytes: None,
+                    oversized_record_policy: OversizedRecordPolicy::default(),
+                    inject_fields: InjectFields::default(),
+                    dedupe: None,
+                    key_normalization: KeyNormalization::default(),
+                    type_coercion: CoercionPolicy::default(),
+                    zstd_auto_level: None,
+                    allow_empty: true,
+                    intern_field_names: false,
+                    block_header_crc: false,
+                    align_blocks: false,
+                    relaxed_json: None,
+                    max_output_bytes: None,
+                    temp_dir: None,
                 };
 
-                let request = CompressRequest {
-                    input: InputSource::Iterator(Box::new(records.into_iter())),
-                    output: OutputSink::Path(output_path.clone()),
-                    options,
-                    container_hint: Some(ContainerFormat::Ndjson),
-                    emit_index: true,
-                    wrapper_config: WrapperConfig::None,
-                };
+                let request = CompressRequest::builder()
+                    .input(InputSource::Iterator(Box::new(records.into_iter())))
+                    .output(OutputSink::Path(output_path.clone()))
+                    .options(options)
+                    .container_hint(ContainerFormat::Ndjson)
+                    .build()
+                    .unwrap();
 
                 let start = std::time::Instant::now();
                 let result = execute_compress(request);
@@ -234,14 +258,22 @@ impl ConcurrencyStressTest {
                 let options = DecompressOptions {
                     limits: Limits::default(),
                     verify_checksums: true,
+                    strict: true,
+                    declared_limits: DeclaredLimitsPolicy::default(),
+                    schema_mapping: None,
+                    raw_json_passthrough: true,
+                    canonical_json: false,
+                    validate_nested_json: true,
+                    snapshot: false,
                 };
 
-                let request = DecompressRequest {
-                    input: JacInput::Path(input_file),
-                    output: OutputSink::Writer(Box::new(Vec::new())),
-                    format: DecompressFormat::JsonArray,
-                    options,
-                };
+                let request = DecompressRequest::builder()
+                    .input(JacInput::Path(input_file))
+                    .output(OutputSink::Writer(Box::new(Vec::new())))
+                    .format(DecompressFormat::JsonArray)
+                    .options(options)
+                    .build()
+                    .unwrap();
 
                 let start = std::time::Instant::now();
                 let result = execute_decompress(request);
@@ -320,15 +352,23 @@ impl ConcurrencyStressTest {
                     let options = DecompressOptions {
                         limits: Limits::default(),
                         verify_checksums: true,
+                        strict: true,
+                        declared_limits: DeclaredLimitsPolicy::default(),
+                        schema_mapping: None,
+                        raw_json_passthrough: true,
+                        canonical_json: false,
+                        validate_nested_json: true,
+                        snapshot: false,
                     };
 
-                    let request = ProjectRequest {
-                        input: JacInput::Path(input_file.clone()),
-                        output: OutputSink::Writer(Box::new(Vec::new())),
-                        fields: vec![field.clone()],
-                        format: ProjectFormat::JsonArray,
-                        options,
-                    };
+                    let request = ProjectRequest::builder()
+                        .input(JacInput::Path(input_file.clone()))
+                        .output(OutputSink::Writer(Box::new(Vec::new())))
+                        .fields(vec![field.clone()])
+                        .format(ProjectFormat::JsonArray)
+                        .options(options)
+                        .build()
+                        .unwrap();
 
                     let result = execute_project(request);
                     if let Err(e) = result {
@@ -340,15 +380,23 @@ impl ConcurrencyStressTest {
                 let options = DecompressOptions {
                     limits: Limits::default(),
                     verify_checksums: true,
+                    strict: true,
+                    declared_limits: DeclaredLimitsPolicy::default(),
+                    schema_mapping: None,
+                    raw_json_passthrough: true,
+                    canonical_json: false,
+                    validate_nested_json: true,
+                    snapshot: false,
                 };
 
-                let request = ProjectRequest {
-                    input: JacInput::Path(input_file.clone()),
-                    output: OutputSink::Writer(Box::new(Vec::new())),
-                    fields: fields.clone(),
-                    format: ProjectFormat::JsonArray,
-                    options,
-                };
+                let request = ProjectRequest::builder()
+                    .input(JacInput::Path(input_file.clone()))
+                    .output(OutputSink::Writer(Box::new(Vec::new())))
+                    .fields(fields.clone())
+                    .format(ProjectFormat::JsonArray)
+                    .options(options)
+                    .build()
+                    .unwrap();
 
                 let result = execute_project(request);
                 if let Err(e) = result {
@@ -446,21 +494,43 @@ mod tests {
             block_target_records: 1000,
             default_codec: jac_codec::Codec::Zstd(1),
             canonicalize_keys: true,
+            preserve_key_order: false,
+            hot_fields: Vec::new(),
             canonicalize_numbers: true,
+            number_canonicalization: NumberCanonicalization::default(),
             nested_opaque: true,
             max_dict_entries: 4096,
+            dictionary_policy: DictionaryPolicy::default(),
+            field_hints: BTreeMap::new(),
+            field_stats: false,
+            quantile_sketches: false,
             limits: Limits::default(),
             parallel_config: ParallelConfig::default(),
+            duplicate_key_policy: DuplicateKeyPolicy::default(),
+            utf8_policy: Utf8Policy::default(),
+            max_record_bytes: None,
+            oversized_record_policy: OversizedRecordPolicy::default(),
+            inject_fields: InjectFields::default(),
+            dedupe: None,
+            key_normalization: KeyNormalization::default(),
+            type_coercion: CoercionPolicy::default(),
+            zstd_auto_level: None,
+            allow_empty: true,
+            intern_field_names: false,
+            block_header_crc: false,
+            align_blocks: false,
+            relaxed_json: None,
+            max_output_bytes: None,
+            temp_dir: None,
         };
 
-        let request = CompressRequest {
-            input: InputSource::Iterator(Box::new(records.into_iter())),
-            output: OutputSink::Path(test_file.clone()),
-            options,
-            container_hint: Some(ContainerFormat::Ndjson),
-            emit_index: true,
-            wrapper_config: WrapperConfig::None,
-        };
+        let request = CompressRequest::builder()
+            .input(InputSource::Iterator(Box::new(records.into_iter())))
+            .output(OutputSink::Path(test_file.clone()))
+            .options(options)
+            .container_hint(ContainerFormat::Ndjson)
+            .build()
+            .unwrap();
 
         execute_compress(request).unwrap();
 
@@ -501,21 +571,43 @@ mod tests {
             block_target_records: 1000,
             default_codec: jac_codec::Codec::Zstd(1),
             canonicalize_keys: true,
+            preserve_key_order: false,
+            hot_fields: Vec::new(),
             canonicalize_numbers: true,
+            number_canonicalization: NumberCanonicalization::default(),
             nested_opaque: true,
             max_dict_entries: 4096,
+            dictionary_policy: DictionaryPolicy::default(),
+            field_hints: BTreeMap::new(),
+            field_stats: false,
+            quantile_sketches: false,
             limits: Limits::default(),
             parallel_config: ParallelConfig::default(),
+            duplicate_key_policy: DuplicateKeyPolicy::default(),
+            utf8_policy: Utf8Policy::default(),
+            max_record_bytes: None,
+            oversized_record_policy: OversizedRecordPolicy::default(),
+            inject_fields: InjectFields::default(),
+            dedupe: None,
+            key_normalization: KeyNormalization::default(),
+            type_coercion: CoercionPolicy::default(),
+            zstd_auto_level: None,
+            allow_empty: true,
+            intern_field_names: false,
+            block_header_crc: false,
+            align_blocks: false,
+            relaxed_json: None,
+            max_output_bytes: None,
+            temp_dir: None,
         };
 
-        let request = CompressRequest {
-            input: InputSource::Iterator(Box::new(records.into_iter())),
-            output: OutputSink::Path(test_file.clone()),
-            options,
-            container_hint: Some(ContainerFormat::Ndjson),
-            emit_index: true,
-            wrapper_config: WrapperConfig::None,
-        };
+        let request = CompressRequest::builder()
+            .input(InputSource::Iterator(Box::new(records.into_iter())))
+            .output(OutputSink::Path(test_file.clone()))
+            .options(options)
+            .container_hint(ContainerFormat::Ndjson)
+            .build()
+            .unwrap();
 
         execute_compress(request).unwrap();
 
@@ -9,9 +9,9 @@ use jac_format::{
     constants::FLAG_NESTED_OPAQUE, BlockHeader, FieldDirectoryEntry, FileHeader, JacError, Limits,
 };
 use jac_io::{
-    execute_compress, execute_decompress, execute_project, CompressOptions, CompressRequest,
-    ContainerFormat, DecompressFormat, DecompressOptions, DecompressRequest, InputSource, JacInput,
-    JacReader, OutputSink, ProjectFormat, ProjectRequest, WrapperConfig,
+    execute_compress, execute_decompress, execute_project, CompressRequest, ContainerFormat,
+    DecompressFormat, DecompressRequest, InputSource, JacInput, JacReader, OutputSink,
+    ProjectFormat, ProjectRequest,
 };
 use serde_json::{json, Map, Value};
 use std::io::Cursor;
@@ -81,6 +81,7 @@ fn default_decode_opts() -> DecompressOpts {
     DecompressOpts {
         limits: Limits::default(),
         verify_checksums: true,
+        ..DecompressOpts::default()
     }
 }
 
@@ -103,6 +104,36 @@ fn reader_reports_unsupported_compression() {
     }
 }
 
+#[test]
+fn permissive_reader_skips_fields_with_unknown_compressor() {
+    let (file_header, mut block_header, segments) =
+        build_block(&[json!({"id": 1, "name": "a"}), json!({"id": 2})]);
+
+    for entry in block_header.fields.iter_mut() {
+        if entry.field_name == "name" {
+            entry.compressor = 99;
+        }
+    }
+
+    let bytes = encode_file(&file_header, &block_header, &segments, false);
+    let mut reader = JacReader::with_strict_mode(Cursor::new(bytes), default_decode_opts(), false)
+        .expect("reader");
+    let records = reader
+        .record_stream()
+        .expect("record stream")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("permissive decode");
+
+    assert_eq!(
+        records,
+        vec![map_from(json!({"id": 1})), map_from(json!({"id": 2}))]
+    );
+    let warnings = reader.warnings();
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    assert!(warnings[0].contains("field 'name'"));
+    assert!(warnings[0].contains("unsupported compressor id 99"));
+}
+
 #[test]
 fn reader_reports_reserved_type_tag() {
     let (file_header, block_header, mut segments) =
@@ -164,6 +195,7 @@ fn reader_enforces_string_length_limit() {
     let opts = DecompressOpts {
         limits,
         verify_checksums: true,
+        ..DecompressOpts::default()
     };
 
     let mut reader = JacReader::new(Cursor::new(bytes), opts).expect("reader");
@@ -252,20 +284,21 @@ fn reader_reports_decompress_error() {
 }
 
 #[test]
-fn compress_invalid_ndjson_reports_json_error() {
-    let request = CompressRequest {
-        input: InputSource::NdjsonReader(Box::new(Cursor::new(b"{invalid}\n".to_vec()))),
-        output: OutputSink::Writer(Box::new(Cursor::new(Vec::new()))),
-        options: CompressOptions::default(),
-        container_hint: Some(ContainerFormat::Ndjson),
-        emit_index: false,
-        wrapper_config: WrapperConfig::None,
-    };
+fn compress_invalid_ndjson_reports_line_and_offset() {
+    let request = CompressRequest::builder()
+        .input(InputSource::NdjsonReader(Box::new(Cursor::new(
+            b"{\"a\":1}\n\n{invalid}\n".to_vec(),
+        ))))
+        .output(OutputSink::Writer(Box::new(Cursor::new(Vec::new()))))
+        .container_hint(ContainerFormat::Ndjson)
+        .emit_index(false)
+        .build()
+        .unwrap();
 
     match execute_compress(request) {
-        Err(JacError::Json(_)) => {}
-        Err(err) => panic!("expected Json error, got {err:?}"),
-        Ok(_) => panic!("expected Json error, got Ok"),
+        Err(JacError::RecordParse { line, offset, .. }) => assert_eq!((line, offset), (3, 9)),
+        Err(err) => panic!("expected RecordParse error, got {err:?}"),
+        Ok(_) => panic!("expected RecordParse error, got Ok"),
     }
 }
 
@@ -348,16 +381,14 @@ fn reader_rejects_unsupported_version() {
 
 #[test]
 fn compress_single_object_succeeds() {
-    let request = CompressRequest {
-        input: InputSource::JsonArrayReader(Box::new(Cursor::new(Vec::from(
-            b"{\"id\":1}\n" as &[u8],
-        )))),
-        output: OutputSink::Writer(Box::new(Cursor::new(Vec::new()))),
-        options: CompressOptions::default(),
-        container_hint: Some(ContainerFormat::JsonArray),
-        emit_index: true,
-        wrapper_config: WrapperConfig::None,
-    };
+    let request = CompressRequest::builder()
+        .input(InputSource::JsonArrayReader(Box::new(Cursor::new(
+            Vec::from(b"{\"id\":1}\n" as &[u8]),
+        ))))
+        .output(OutputSink::Writer(Box::new(Cursor::new(Vec::new()))))
+        .container_hint(ContainerFormat::JsonArray)
+        .build()
+        .unwrap();
 
     match execute_compress(request) {
         Ok(_) => {} // Single object is now supported
@@ -367,14 +398,14 @@ fn compress_single_object_succeeds() {
 
 #[test]
 fn compress_missing_file_reports_io_error() {
-    let request = CompressRequest {
-        input: InputSource::NdjsonPath(PathBuf::from("/definitely/missing.ndjson")),
-        output: OutputSink::Writer(Box::new(Cursor::new(Vec::new()))),
-        options: CompressOptions::default(),
-        container_hint: None,
-        emit_index: false,
-        wrapper_config: WrapperConfig::None,
-    };
+    let request = CompressRequest::builder()
+        .input(InputSource::NdjsonPath(PathBuf::from(
+            "/definitely/missing.ndjson",
+        )))
+        .output(OutputSink::Writer(Box::new(Cursor::new(Vec::new()))))
+        .emit_index(false)
+        .build()
+        .unwrap();
 
     match execute_compress(request) {
         Err(JacError::Io(err)) => {
@@ -390,13 +421,12 @@ fn project_empty_fields_reports_internal_error() {
     let (file_header, block_header, segments) = build_block(&[json!({"value": 42})]);
     let bytes = encode_file(&file_header, &block_header, &segments, false);
 
-    let request = ProjectRequest {
-        input: JacInput::Reader(Box::new(Cursor::new(bytes))),
-        output: OutputSink::Writer(Box::new(Cursor::new(Vec::new()))),
-        fields: Vec::new(),
-        format: ProjectFormat::Ndjson,
-        options: DecompressOptions::default(),
-    };
+    let request = ProjectRequest::builder()
+        .input(JacInput::Reader(Box::new(Cursor::new(bytes))))
+        .output(OutputSink::Writer(Box::new(Cursor::new(Vec::new()))))
+        .format(ProjectFormat::Ndjson)
+        .build()
+        .unwrap();
 
     match execute_project(request) {
         Err(JacError::Internal(message)) => {
@@ -424,12 +454,12 @@ fn decompress_reports_io_error_on_output_failure() {
     let (file_header, block_header, segments) = build_block(&[json!({"v": 1})]);
     let bytes = encode_file(&file_header, &block_header, &segments, false);
 
-    let request = DecompressRequest {
-        input: JacInput::Reader(Box::new(Cursor::new(bytes))),
-        output: OutputSink::Writer(Box::new(FailingWriter)),
-        format: DecompressFormat::Ndjson,
-        options: DecompressOptions::default(),
-    };
+    let request = DecompressRequest::builder()
+        .input(JacInput::Reader(Box::new(Cursor::new(bytes))))
+        .output(OutputSink::Writer(Box::new(FailingWriter)))
+        .format(DecompressFormat::Ndjson)
+        .build()
+        .unwrap();
 
     match execute_decompress(request) {
         Err(JacError::Io(err)) => {
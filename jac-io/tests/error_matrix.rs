@@ -9,9 +9,9 @@ use jac_format::{
     constants::FLAG_NESTED_OPAQUE, BlockHeader, FieldDirectoryEntry, FileHeader, JacError, Limits,
 };
 use jac_io::{
-    execute_compress, execute_decompress, execute_project, CompressOptions, CompressRequest,
-    ContainerFormat, DecompressFormat, DecompressOptions, DecompressRequest, InputSource, JacInput,
-    JacReader, OutputSink, ProjectFormat, ProjectRequest, WrapperConfig,
+    execute_compress, execute_decompress, execute_project, CompressRequest, ContainerFormat,
+    DecompressFormat, DecompressRequest, InputSource, JacInput, JacReader, OutputSink,
+    ProjectFormat, ProjectRequest,
 };
 use serde_json::{json, Map, Value};
 use std::io::Cursor;
@@ -163,6 +163,7 @@ impl ErrorTestMatrix {
         let opts = DecompressOpts {
             limits,
             verify_checksums: true,
+            ..DecompressOpts::default()
         };
 
         let mut reader = JacReader::new(Cursor::new(bytes), opts).expect("reader");
@@ -179,16 +180,14 @@ impl ErrorTestMatrix {
     /// Test TypeMismatch error
     /// Triggered when JSON type doesn't match expected format
     pub fn test_type_mismatch() {
-        let request = CompressRequest {
-            input: InputSource::JsonArrayReader(Box::new(Cursor::new(Vec::from(
-                b"invalid json content\n" as &[u8],
-            )))),
-            output: OutputSink::Writer(Box::new(Cursor::new(Vec::new()))),
-            options: CompressOptions::default(),
-            container_hint: Some(ContainerFormat::JsonArray),
-            emit_index: true,
-            wrapper_config: WrapperConfig::None,
-        };
+        let request = CompressRequest::builder()
+            .input(InputSource::JsonArrayReader(Box::new(Cursor::new(
+                Vec::from(b"invalid json content\n" as &[u8]),
+            ))))
+            .output(OutputSink::Writer(Box::new(Cursor::new(Vec::new()))))
+            .container_hint(ContainerFormat::JsonArray)
+            .build()
+            .unwrap();
 
         match execute_compress(request) {
             Err(JacError::TypeMismatch) => {}
@@ -293,14 +292,14 @@ impl ErrorTestMatrix {
 
     /// Test Io error for input failures
     pub fn test_io_input_error() {
-        let request = CompressRequest {
-            input: InputSource::NdjsonPath(PathBuf::from("/definitely/missing.ndjson")),
-            output: OutputSink::Writer(Box::new(Cursor::new(Vec::new()))),
-            options: CompressOptions::default(),
-            container_hint: None,
-            emit_index: false,
-            wrapper_config: WrapperConfig::None,
-        };
+        let request = CompressRequest::builder()
+            .input(InputSource::NdjsonPath(PathBuf::from(
+                "/definitely/missing.ndjson",
+            )))
+            .output(OutputSink::Writer(Box::new(Cursor::new(Vec::new()))))
+            .emit_index(false)
+            .build()
+            .unwrap();
 
         match execute_compress(request) {
             Err(JacError::Io(err)) => {
@@ -316,12 +315,12 @@ impl ErrorTestMatrix {
         let (file_header, block_header, segments) = build_test_block(&[json!({"v": 1})]);
         let bytes = encode_test_file(&file_header, &block_header, &segments, false);
 
-        let request = DecompressRequest {
-            input: JacInput::Reader(Box::new(Cursor::new(bytes))),
-            output: OutputSink::Writer(Box::new(FailingWriter)),
-            format: DecompressFormat::Ndjson,
-            options: DecompressOptions::default(),
-        };
+        let request = DecompressRequest::builder()
+            .input(JacInput::Reader(Box::new(Cursor::new(bytes))))
+            .output(OutputSink::Writer(Box::new(FailingWriter)))
+            .format(DecompressFormat::Ndjson)
+            .build()
+            .unwrap();
 
         match execute_decompress(request) {
             Err(JacError::Io(err)) => {
@@ -332,16 +331,17 @@ impl ErrorTestMatrix {
         }
     }
 
-    /// Test Json error for malformed JSON input
+    /// Test Json error for malformed JSON array input
     pub fn test_json_error() {
-        let request = CompressRequest {
-            input: InputSource::NdjsonReader(Box::new(Cursor::new(b"{invalid}\n".to_vec()))),
-            output: OutputSink::Writer(Box::new(Cursor::new(Vec::new()))),
-            options: CompressOptions::default(),
-            container_hint: Some(ContainerFormat::Ndjson),
-            emit_index: false,
-            wrapper_config: WrapperConfig::None,
-        };
+        let request = CompressRequest::builder()
+            .input(InputSource::JsonArrayReader(Box::new(Cursor::new(
+                b"[{invalid}]".to_vec(),
+            ))))
+            .output(OutputSink::Writer(Box::new(Cursor::new(Vec::new()))))
+            .container_hint(ContainerFormat::JsonArray)
+            .emit_index(false)
+            .build()
+            .unwrap();
 
         match execute_compress(request) {
             Err(JacError::Json(_)) => {}
@@ -350,18 +350,43 @@ impl ErrorTestMatrix {
         }
     }
 
+    /// Test RecordParse error for a malformed NDJSON line
+    /// Reports the 1-based line number and the byte offset where the line starts
+    pub fn test_record_parse_error() {
+        let request = CompressRequest::builder()
+            .input(InputSource::NdjsonReader(Box::new(Cursor::new(
+                b"{\"a\":1}\n{invalid}\n".to_vec(),
+            ))))
+            .output(OutputSink::Writer(Box::new(Cursor::new(Vec::new()))))
+            .container_hint(ContainerFormat::Ndjson)
+            .emit_index(false)
+            .build()
+            .unwrap();
+
+        match execute_compress(request) {
+            Err(err @ JacError::RecordParse { .. }) => {
+                assert!(
+                    err.to_string().contains("line 2 (byte offset 8)"),
+                    "unexpected message: {err}"
+                );
+            }
+            Err(err) => panic!("expected RecordParse error, got {err:?}"),
+            Ok(_) => panic!("expected RecordParse error, got Ok"),
+        }
+    }
+
     /// Test Internal error for invalid internal state
     pub fn test_internal_error() {
         let (file_header, block_header, segments) = build_test_block(&[json!({"value": 42})]);
         let bytes = encode_test_file(&file_header, &block_header, &segments, false);
 
-        let request = ProjectRequest {
-            input: JacInput::Reader(Box::new(Cursor::new(bytes))),
-            output: OutputSink::Writer(Box::new(Cursor::new(Vec::new()))),
-            fields: Vec::new(), // Empty fields should trigger internal error
-            format: ProjectFormat::Ndjson,
-            options: DecompressOptions::default(),
-        };
+        // Empty fields should trigger internal error
+        let request = ProjectRequest::builder()
+            .input(JacInput::Reader(Box::new(Cursor::new(bytes))))
+            .output(OutputSink::Writer(Box::new(Cursor::new(Vec::new()))))
+            .format(ProjectFormat::Ndjson)
+            .build()
+            .unwrap();
 
         match execute_project(request) {
             Err(JacError::Internal(message)) => {
@@ -523,6 +548,11 @@ fn test_json_error() {
     ErrorTestMatrix::test_json_error();
 }
 
+#[test]
+fn test_record_parse_error() {
+    ErrorTestMatrix::test_record_parse_error();
+}
+
 #[test]
 fn test_internal_error() {
     ErrorTestMatrix::test_internal_error();
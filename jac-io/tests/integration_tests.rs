@@ -3,13 +3,16 @@
 use jac_codec::{Codec, CompressOpts, DecompressOpts};
 use jac_format::{
     constants::{FILE_MAGIC, INDEX_MAGIC},
-    FileHeader, IndexFooter, JacError, Limits,
+    FileHeader, IndexFooter, JacError, Limits, TypeTag,
 };
 use jac_io::{
-    execute_project, DecompressOptions, JacInput, JacReader, JacWriter, OutputSink, ProjectFormat,
+    execute_aggregate, execute_decompress, execute_project, Aggregate, AggregateRequest,
+    DecompressFormat, DecompressOptions, DecompressRequest, FieldCatalogOptions, FieldQuery,
+    IndexStatus, JacInput, JacReader, JacWriter, MultiJacReader, OutputSink, ProjectFormat,
     ProjectRequest,
 };
 use serde_json::{json, Map, Value};
+use std::collections::HashMap;
 use std::fs;
 use std::io::Cursor;
 use std::path::PathBuf;
@@ -36,6 +39,7 @@ fn default_decompress_opts() -> DecompressOpts {
     DecompressOpts {
         limits: Limits::default(),
         verify_checksums: true,
+        ..DecompressOpts::default()
     }
 }
 
@@ -285,6 +289,609 @@ fn projection_handles_absent_and_null() {
     assert_eq!(values[2], Some(Value::Bool(true)));
 }
 
+#[test]
+fn field_catalog_merges_directories_across_blocks() {
+    let (header, opts) = default_compress_opts(2);
+    let buffer = Cursor::new(Vec::<u8>::new());
+    let mut writer = JacWriter::new(buffer, header, opts).unwrap();
+
+    let records = [
+        json!({"user": "alice", "score": 1}),
+        json!({"user": "bob"}),
+        json!({"user": 7, "score": 2.5}),
+        json!({"user": null}),
+        json!({"extra": [1]}),
+    ];
+    for record in &records {
+        writer.write_record(&map_from(record.clone())).unwrap();
+    }
+
+    let bytes = finish_writer(writer, false);
+    let mut reader = JacReader::new(Cursor::new(bytes), default_decompress_opts()).unwrap();
+    let catalog = reader.field_catalog().unwrap();
+
+    let names: Vec<_> = catalog.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, vec!["extra", "score", "user"]);
+
+    let user = &catalog[2];
+    assert_eq!(user.blocks, vec![0, 1]);
+    assert_eq!(user.value_count, 4);
+    assert_eq!(
+        user.types,
+        vec![TypeTag::Null, TypeTag::Int, TypeTag::String]
+    );
+    assert!(user.compressed_bytes > 0 && user.uncompressed_bytes > 0);
+
+    let score = &catalog[1];
+    assert_eq!(score.blocks, vec![0, 1]);
+    assert_eq!(score.types, vec![TypeTag::Int, TypeTag::Decimal]);
+    assert_eq!(catalog[0].blocks, vec![2]);
+
+    let headers_only = reader
+        .field_catalog_with(&FieldCatalogOptions {
+            include_types: false,
+        })
+        .unwrap();
+    assert!(headers_only.iter().all(|entry| entry.types.is_empty()));
+    assert_eq!(reader.blocks().count(), 3, "catalog rewinds the reader");
+
+    let find = |reader: &mut JacReader<_>, has: &[&str], field_type| {
+        let query = FieldQuery {
+            has_fields: has.iter().map(|name| name.to_string()).collect(),
+            field_type,
+        };
+        reader
+            .find_fields(&query)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        find(&mut reader, &["user", "extra"], None),
+        ["extra", "user"]
+    );
+    assert!(find(&mut reader, &["user", "missing"], None).is_empty());
+    assert_eq!(find(&mut reader, &[], Some(TypeTag::Decimal)), ["score"]);
+    assert_eq!(
+        find(&mut reader, &["user"], Some(TypeTag::String)),
+        ["user"]
+    );
+    assert!(find(&mut reader, &["user"], Some(TypeTag::Decimal)).is_empty());
+}
+
+#[test]
+fn read_raw_segment_returns_compressed_bytes() {
+    let (header, mut opts) = default_compress_opts(4);
+    opts.default_codec = Codec::Zstd(3);
+    let buffer = Cursor::new(Vec::<u8>::new());
+    let mut writer = JacWriter::new(buffer, header, opts).unwrap();
+    for user in ["alice", "bob", "alice"] {
+        writer
+            .write_record(&map_from(json!({ "user": user })))
+            .unwrap();
+    }
+
+    let bytes = finish_writer(writer, true);
+    let mut reader = JacReader::new(Cursor::new(bytes), default_decompress_opts()).unwrap();
+    let block = reader.blocks().next().unwrap().unwrap();
+
+    let raw = reader.read_raw_segment(&block, "user").unwrap();
+    let entry = block.field_entry("user").unwrap();
+    assert_eq!(raw.entry.segment_offset, entry.segment_offset);
+    assert_eq!(raw.entry.compressor, 1);
+    assert_eq!(raw.bytes.len(), raw.entry.segment_compressed_len);
+    let decompressed = zstd::decode_all(raw.bytes.as_slice()).unwrap();
+    assert_eq!(decompressed.len(), raw.entry.segment_uncompressed_len);
+
+    assert!(reader.read_raw_segment(&block, "missing").is_err());
+}
+
+#[test]
+fn raw_blocks_copy_between_files_and_are_verified() {
+    let source = sample_projection_file();
+    let mut reader = JacReader::new(Cursor::new(source), default_decompress_opts()).unwrap();
+    let block = reader.blocks().next().unwrap().unwrap();
+    let raw = reader.read_raw_block(&block).unwrap();
+
+    let (header, opts) = default_compress_opts(4);
+    let mut writer = JacWriter::new(Cursor::new(Vec::<u8>::new()), header, opts).unwrap();
+    writer
+        .write_record(&map_from(json!({"user": "zed"})))
+        .unwrap();
+    writer.write_raw_block(raw.clone()).unwrap();
+    assert_eq!(writer.metrics().blocks_written, 2);
+    assert_eq!(writer.metrics().records_written, 4);
+
+    let mut corrupt = raw;
+    corrupt.crc32c ^= 1;
+    assert!(matches!(
+        writer.write_raw_block(corrupt),
+        Err(JacError::ChecksumMismatch)
+    ));
+
+    let bytes = finish_writer(writer, true);
+    let mut copy = JacReader::new(Cursor::new(bytes), default_decompress_opts()).unwrap();
+    let users: Vec<_> = copy
+        .record_stream()
+        .unwrap()
+        .map(|record| record.unwrap()["user"].clone())
+        .collect();
+    assert_eq!(
+        users,
+        vec![json!("zed"), json!("alice"), json!("bob"), json!("carol")]
+    );
+}
+
+#[test]
+fn multi_projection_aligns_fields_across_blocks() {
+    let (header, opts) = default_compress_opts(2);
+    let buffer = Cursor::new(Vec::<u8>::new());
+    let mut writer = JacWriter::new(buffer, header, opts).unwrap();
+
+    let records = [
+        json!({"a": 1, "b": "x"}),
+        json!({"a": 2}),
+        json!({"c": true}),
+        json!({"b": null}),
+        json!({"a": 5, "b": "y"}),
+    ];
+    for record in &records {
+        writer.write_record(&map_from(record.clone())).unwrap();
+    }
+
+    let bytes = finish_writer(writer, true);
+    let mut reader = JacReader::new(Cursor::new(bytes), default_decompress_opts()).unwrap();
+    let stream = reader
+        .multi_projection_stream(&["b", "a", "missing"])
+        .unwrap();
+    assert_eq!(stream.fields(), ["b", "a", "missing"]);
+
+    let rows = stream.collect::<jac_format::Result<Vec<_>>>().unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            vec![Some(json!("x")), Some(json!(1)), None],
+            vec![None, Some(json!(2)), None],
+            vec![None, None, None],
+            vec![Some(Value::Null), None, None],
+            vec![Some(json!("y")), Some(json!(5)), None],
+        ]
+    );
+
+    let empty: [&str; 0] = [];
+    assert!(reader.multi_projection_stream(&empty).is_err());
+}
+
+#[test]
+fn value_counts_merge_dictionary_and_decoded_blocks() {
+    let (header, opts) = default_compress_opts(16);
+    let buffer = Cursor::new(Vec::<u8>::new());
+    let mut writer = JacWriter::new(buffer, header, opts).unwrap();
+
+    for idx in 0..16 {
+        let user = if idx % 4 == 0 { "bob" } else { "alice" };
+        writer
+            .write_record(&map_from(json!({ "user": user })))
+            .unwrap();
+    }
+    for record in [json!({"user": "bob"}), json!({"user": 7}), json!({"id": 1})] {
+        writer.write_record(&map_from(record)).unwrap();
+    }
+
+    let bytes = finish_writer(writer, true);
+    let mut reader = JacReader::new(Cursor::new(bytes), default_decompress_opts()).unwrap();
+    let counts = reader.value_counts("user").unwrap();
+    assert_eq!(
+        counts.values,
+        vec![(json!("alice"), 12), (json!("bob"), 5), (json!(7), 1)]
+    );
+    assert_eq!(counts.total, 18);
+    assert_eq!(counts.distinct(), 3);
+    assert_eq!(counts.top(1), &[(json!("alice"), 12)]);
+    assert_eq!((counts.dictionary_blocks, counts.decoded_blocks), (1, 1));
+
+    assert_eq!(reader.value_counts("missing").unwrap().total, 0);
+    assert_eq!(reader.blocks().count(), 2);
+}
+
+#[test]
+fn counts_come_from_headers_with_and_without_index() {
+    let records = [
+        json!({"user": "alice", "team": null}),
+        json!({"user": "bob"}),
+        json!({"team": "ops"}),
+        json!({"user": "cy"}),
+        json!({"id": 5}),
+    ];
+
+    for with_index in [true, false] {
+        let (header, opts) = default_compress_opts(2);
+        let buffer = Cursor::new(Vec::<u8>::new());
+        let mut writer = JacWriter::new(buffer, header, opts).unwrap();
+        for record in &records {
+            writer.write_record(&map_from(record.clone())).unwrap();
+        }
+        let bytes = finish_writer(writer, with_index);
+
+        let mut reader = JacReader::new(Cursor::new(bytes), default_decompress_opts()).unwrap();
+        assert_eq!(reader.is_finalized(), with_index);
+        assert_eq!(reader.count_records().unwrap(), 5);
+        assert_eq!(reader.block_count().unwrap(), 3);
+        assert_eq!(reader.count_present("user").unwrap(), 3);
+        assert_eq!(reader.count_present("team").unwrap(), 2);
+        assert_eq!(reader.count_present("missing").unwrap(), 0);
+        assert_eq!(reader.record_stream().unwrap().count(), 5);
+    }
+}
+
+#[test]
+fn streams_from_offset_skip_leading_blocks() {
+    for with_index in [true, false] {
+        let (header, opts) = default_compress_opts(2);
+        let buffer = Cursor::new(Vec::<u8>::new());
+        let mut writer = JacWriter::new(buffer, header, opts).unwrap();
+        for n in 0..7 {
+            writer.write_record(&map_from(json!({ "n": n }))).unwrap();
+        }
+        let bytes = finish_writer(writer, with_index);
+        let mut reader = JacReader::new(Cursor::new(bytes), default_decompress_opts()).unwrap();
+
+        let mut stream = reader.record_stream_from(3).unwrap();
+        let ns: Vec<Value> = stream.by_ref().map(|r| r.unwrap()["n"].clone()).collect();
+        assert_eq!(ns, vec![json!(3), json!(4), json!(5), json!(6)]);
+        assert_eq!(stream.blocks_processed(), 3);
+
+        let rows: Vec<_> = reader
+            .multi_projection_stream_from(&["n"], 6)
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect();
+        assert_eq!(rows, vec![vec![Some(json!(6))]]);
+        assert_eq!(reader.record_stream_from(7).unwrap().count(), 0);
+    }
+}
+
+#[cfg(feature = "polars")]
+#[test]
+fn polars_frames_match_eager_and_lazy_reads() {
+    use polars::prelude::{col, lit, DataType};
+
+    let (header, opts) = default_compress_opts(2);
+    let buffer = Cursor::new(Vec::<u8>::new());
+    let mut writer = JacWriter::new(buffer, header, opts).unwrap();
+    let records = [
+        json!({"id": 1, "score": 1.5, "ok": true, "user": "alice"}),
+        json!({"id": 2, "score": 2, "user": {"name": "bob"}}),
+        json!({"id": 3, "ok": false, "gone": null}),
+    ];
+    for record in &records {
+        writer.write_record(&map_from(record.clone())).unwrap();
+    }
+    let bytes = finish_writer(writer, true);
+
+    let mut reader = JacReader::new(Cursor::new(bytes.clone()), default_decompress_opts()).unwrap();
+    let df = reader
+        .to_polars_df(&["id", "score", "ok", "user", "gone"])
+        .unwrap();
+    assert_eq!(df.shape(), (3, 5));
+    let dtypes: Vec<DataType> = df.dtypes();
+    assert_eq!(
+        dtypes,
+        vec![
+            DataType::Int64,
+            DataType::Float64,
+            DataType::Boolean,
+            DataType::String,
+            DataType::Null,
+        ]
+    );
+    let users: Vec<Option<&str>> = df
+        .column("user")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(users, vec![Some("alice"), Some("{\"name\":\"bob\"}"), None]);
+
+    let path = temp_output_path("polars");
+    fs::write(&path, &bytes).unwrap();
+    let lazy = jac_io::scan_jac(&path, default_decompress_opts())
+        .unwrap()
+        .filter(col("id").gt(lit(1)))
+        .select([col("id"), col("score")])
+        .collect()
+        .unwrap();
+    let _ = fs::remove_file(&path);
+    let ids: Vec<Option<i64>> = lazy
+        .column("id")
+        .unwrap()
+        .i64()
+        .unwrap()
+        .into_iter()
+        .collect();
+    let scores: Vec<Option<f64>> = lazy
+        .column("score")
+        .unwrap()
+        .f64()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(ids, vec![Some(2), Some(3)]);
+    assert_eq!(scores, vec![Some(2.0), None]);
+}
+
+fn archive_bytes(records: &[Value]) -> Vec<u8> {
+    let (header, opts) = default_compress_opts(2);
+    let buffer = Cursor::new(Vec::<u8>::new());
+    let mut writer = JacWriter::new(buffer, header, opts).unwrap();
+    for record in records {
+        writer.write_record(&map_from(record.clone())).unwrap();
+    }
+    finish_writer(writer, true)
+}
+
+fn multi_reader(archives: &[Vec<u8>]) -> MultiJacReader<Cursor<Vec<u8>>> {
+    let readers = archives
+        .iter()
+        .map(|bytes| JacReader::new(Cursor::new(bytes.clone()), default_decompress_opts()).unwrap())
+        .collect();
+    MultiJacReader::from_readers(readers).unwrap()
+}
+
+#[test]
+fn reader_recovers_index_footer_behind_a_stale_pointer() {
+    let records: Vec<Value> = (0..5).map(|n| json!({ "n": n })).collect();
+    let bytes = archive_bytes(&records);
+    let reader = JacReader::new(Cursor::new(bytes.clone()), default_decompress_opts()).unwrap();
+    assert_eq!(reader.index_status(), IndexStatus::Valid);
+    assert!(reader.index_status().warning().is_none());
+
+    let pointer_pos = bytes.len() - 8;
+    let found_at = u64::from_le_bytes(bytes[pointer_pos..].try_into().unwrap());
+    let mut stale = bytes.clone();
+    stale[pointer_pos..].copy_from_slice(&3u64.to_le_bytes());
+
+    let mut reader = JacReader::new(Cursor::new(stale), default_decompress_opts()).unwrap();
+    assert_eq!(
+        reader.index_status(),
+        IndexStatus::Recovered {
+            pointer: 3,
+            found_at
+        }
+    );
+    assert!(reader.is_finalized());
+    assert!(reader
+        .index_status()
+        .warning()
+        .unwrap()
+        .contains("jac pack"));
+    assert_eq!(reader.block_count().unwrap(), 3);
+    assert_eq!(reader.record_stream().unwrap().count(), 5);
+
+    let unindexed = {
+        let (header, opts) = default_compress_opts(2);
+        let mut writer = JacWriter::new(Cursor::new(Vec::<u8>::new()), header, opts).unwrap();
+        writer.write_record(&map_from(json!({"n": 0}))).unwrap();
+        finish_writer(writer, false)
+    };
+    let reader = JacReader::new(Cursor::new(unindexed), default_decompress_opts()).unwrap();
+    assert_eq!(reader.index_status(), IndexStatus::Absent);
+}
+
+#[test]
+fn concatenated_archives_ignore_mismatched_footer_and_read_through() {
+    let first = archive_bytes(&[json!({"n": 0}), json!({"n": 1}), json!({"n": 2})]);
+    let second = archive_bytes(&[json!({"n": 3}), json!({"n": 4})]);
+    let mut bytes = first.clone();
+    bytes.extend_from_slice(&second);
+
+    let mut reader = JacReader::new(Cursor::new(bytes), default_decompress_opts()).unwrap();
+    let pointer = u64::from_le_bytes(second[second.len() - 8..].try_into().unwrap());
+    assert_eq!(reader.index_status(), IndexStatus::Invalid { pointer });
+    assert!(!reader.is_finalized());
+    assert!(reader.index_status().warning().is_some());
+
+    let ns: Vec<Value> = reader
+        .record_stream()
+        .unwrap()
+        .map(|record| record.unwrap()["n"].clone())
+        .collect();
+    assert_eq!(ns, (0..5).map(|n| json!(n)).collect::<Vec<_>>());
+    assert_eq!(reader.count_records().unwrap(), 5);
+}
+
+#[test]
+fn multi_reader_chains_archives_and_merges_catalogs() {
+    let archives = [
+        archive_bytes(&[
+            json!({"id": 1, "user": "alice"}),
+            json!({"id": 2}),
+            json!({"id": 3}),
+        ]),
+        archive_bytes(&[json!({"id": "4", "host": "web-1"})]),
+    ];
+    let mut reader = multi_reader(&archives);
+    assert_eq!(reader.archive_count(), 2);
+    assert_eq!(reader.count_records().unwrap(), 4);
+    assert_eq!(reader.block_count().unwrap(), 3);
+
+    let mut stream = reader.record_stream();
+    let ids: Vec<Value> = stream.by_ref().map(|r| r.unwrap()["id"].clone()).collect();
+    assert_eq!(ids, vec![json!(1), json!(2), json!(3), json!("4")]);
+    assert_eq!(stream.archive_index(), Some(1));
+
+    let rows: Vec<_> = reader
+        .multi_projection_stream(&["user", "host"])
+        .unwrap()
+        .map(|row| row.unwrap())
+        .collect();
+    assert_eq!(rows[0], vec![Some(json!("alice")), None]);
+    assert_eq!(rows[3], vec![None, Some(json!("web-1"))]);
+
+    let catalog = reader.field_catalog().unwrap();
+    let names: Vec<&str> = catalog.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, vec!["host", "id", "user"]);
+    let id = &catalog[1];
+    assert_eq!(id.types, vec![TypeTag::Int, TypeTag::String]);
+    assert_eq!(id.blocks, vec![0, 1, 2]);
+    assert_eq!(id.value_count, 4);
+    assert_eq!(catalog[0].blocks, vec![2]);
+
+    let empty: Vec<JacReader<Cursor<Vec<u8>>>> = Vec::new();
+    assert!(MultiJacReader::from_readers(empty).is_err());
+    let no_fields: [&str; 0] = [];
+    assert!(reader.multi_projection_stream(&no_fields).is_err());
+}
+
+#[cfg(feature = "query")]
+#[test]
+fn multi_reader_query_prunes_per_archive_and_limits_overall() {
+    use jac_io::Query;
+
+    let archives = [
+        archive_bytes(&[
+            json!({"level": "INFO", "n": 1}),
+            json!({"level": "INFO", "n": 2}),
+        ]),
+        archive_bytes(&[
+            json!({"level": "ERROR", "n": 3}),
+            json!({"level": "ERROR", "n": 4}),
+        ]),
+        archive_bytes(&[json!({"level": "ERROR", "n": 5})]),
+    ];
+    let mut reader = multi_reader(&archives);
+    let mut out = Vec::new();
+    let summary = reader
+        .query(
+            &Query::parse("select n where level = 'ERROR' limit 2").unwrap(),
+            &mut out,
+        )
+        .unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "{\"n\":3}\n{\"n\":4}\n");
+    assert_eq!(summary.rows_written, 2);
+    assert_eq!(summary.blocks_pruned, 1);
+    assert_eq!(summary.blocks_scanned, 1);
+}
+
+#[cfg(feature = "query")]
+#[test]
+fn query_filters_projects_and_prunes_blocks() {
+    use jac_io::{execute_query, Query, QueryRequest};
+
+    let (header, opts) = default_compress_opts(16);
+    let buffer = Cursor::new(Vec::<u8>::new());
+    let mut writer = JacWriter::new(buffer, header, opts).unwrap();
+
+    // Block 0: dictionary-encoded levels without ERROR; block 1: no `level` at all.
+    for idx in 0..16 {
+        let level = if idx % 2 == 0 { "INFO" } else { "WARN" };
+        writer
+            .write_record(&map_from(json!({"ts": idx, "level": level})))
+            .unwrap();
+    }
+    for idx in 16..32 {
+        writer
+            .write_record(&map_from(json!({"ts": idx, "msg": "no level"})))
+            .unwrap();
+    }
+    writer
+        .write_record(&map_from(
+            json!({"ts": 32, "level": "ERROR", "msg": "boom", "code": 500}),
+        ))
+        .unwrap();
+    writer
+        .write_record(&map_from(json!({"ts": 33, "level": "ERROR", "code": 404})))
+        .unwrap();
+    let bytes = finish_writer(writer, true);
+
+    let run = |text: &str| {
+        let path = temp_output_path("query");
+        let summary = execute_query(QueryRequest {
+            input: JacInput::Reader(Box::new(Cursor::new(bytes.clone()))),
+            output: OutputSink::Path(path.clone()),
+            query: Query::parse(text).unwrap(),
+            options: DecompressOptions::default(),
+        })
+        .unwrap();
+        let content = fs::read_to_string(&path).expect("read query output");
+        let _ = fs::remove_file(&path);
+        let rows: Vec<Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        (summary, rows)
+    };
+
+    let (summary, rows) = run("select ts, msg where level = 'ERROR' limit 100");
+    assert_eq!(
+        rows,
+        vec![json!({"ts": 32, "msg": "boom"}), json!({"ts": 33})]
+    );
+    assert_eq!(summary.rows_written, 2);
+    assert_eq!((summary.blocks_scanned, summary.blocks_pruned), (1, 2));
+
+    let (summary, rows) = run("SELECT * WHERE code > 450 OR ts >= 30 LIMIT 2");
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0], json!({"ts": 30, "msg": "no level"}));
+    assert_eq!(summary.blocks_scanned, 2);
+}
+
+#[test]
+fn aggregate_merges_group_states_across_blocks() {
+    let (header, opts) = default_compress_opts(2);
+    let buffer = Cursor::new(Vec::<u8>::new());
+    let mut writer = JacWriter::new(buffer, header, opts).unwrap();
+
+    let records = [
+        json!({"level": "info", "bytes": 10, "user": "a"}),
+        json!({"level": "warn", "bytes": 1.5, "user": "b"}),
+        json!({"level": "info", "bytes": 5, "user": "b"}),
+        json!({"bytes": 7}),
+        json!({"level": "info", "bytes": "n/a", "user": "a"}),
+    ];
+    for record in &records {
+        writer.write_record(&map_from(record.clone())).unwrap();
+    }
+    let bytes = finish_writer(writer, true);
+
+    let aggregate = |group_by: Vec<String>, aggregates: Vec<Aggregate>| {
+        execute_aggregate(AggregateRequest {
+            input: JacInput::Reader(Box::new(Cursor::new(bytes.clone()))),
+            group_by,
+            aggregates,
+            options: DecompressOptions::default(),
+        })
+    };
+
+    let result = aggregate(
+        vec!["level".to_string()],
+        vec![
+            Aggregate::Count,
+            Aggregate::Sum("bytes".to_string()),
+            Aggregate::Max("bytes".to_string()),
+            Aggregate::DistinctApprox("user".to_string()),
+        ],
+    )
+    .unwrap();
+    let rows: Vec<Value> = result.rows().into_iter().map(Value::Object).collect();
+    assert_eq!(
+        rows,
+        vec![
+            json!({"level": "info", "count": 3, "sum(bytes)": 15, "max(bytes)": "n/a", "distinct(user)": 2}),
+            json!({"level": "warn", "count": 1, "sum(bytes)": 1.5, "max(bytes)": 1.5, "distinct(user)": 1}),
+            json!({"level": null, "count": 1, "sum(bytes)": 7, "max(bytes)": 7, "distinct(user)": 0}),
+        ]
+    );
+
+    let global = aggregate(Vec::new(), vec![Aggregate::Min("bytes".to_string())]).unwrap();
+    assert_eq!(global.groups.len(), 1);
+    assert_eq!(global.groups[0].values, vec![json!(1.5)]);
+
+    assert!(aggregate(Vec::new(), Vec::new()).is_err());
+}
+
 #[test]
 fn resync_skips_corrupt_block_when_not_strict() {
     let (header, opts) = default_compress_opts(1);
@@ -319,6 +926,47 @@ fn resync_skips_corrupt_block_when_not_strict() {
     assert_eq!(blocks[0].header.fields.len(), 1);
 }
 
+#[test]
+fn decompress_strict_fails_while_permissive_skips_and_warns() {
+    let (header, opts) = default_compress_opts(1);
+    let mut writer = JacWriter::new(Cursor::new(Vec::<u8>::new()), header, opts).unwrap();
+    writer.write_record(&map_from(json!({"id": 1}))).unwrap();
+    writer.flush().unwrap();
+    writer.write_record(&map_from(json!({"id": 2}))).unwrap();
+    let mut bytes = finish_writer(writer, false);
+    let header_len = FileHeader::decode(&bytes).unwrap().1;
+    bytes[header_len..header_len + 4].copy_from_slice(&[0u8; 4]);
+
+    let run = |strict: bool| {
+        let path = temp_output_path("permissive");
+        let result = execute_decompress(
+            DecompressRequest::builder()
+                .input(JacInput::Reader(Box::new(Cursor::new(bytes.clone()))))
+                .output(OutputSink::Path(path.clone()))
+                .format(DecompressFormat::Ndjson)
+                .options(DecompressOptions {
+                    strict,
+                    ..DecompressOptions::default()
+                })
+                .build()
+                .unwrap(),
+        );
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        let _ = fs::remove_file(&path);
+        (result, content)
+    };
+
+    let (strict, _) = run(true);
+    assert!(matches!(strict, Err(JacError::CorruptBlock)));
+
+    let (permissive, content) = run(false);
+    let summary = permissive.expect("permissive decompress");
+    assert_eq!(summary.records_written, 1);
+    assert_eq!(content, "{\"id\":2}\n");
+    assert_eq!(summary.warnings.len(), 1);
+    assert!(summary.warnings[0].contains("skipped unreadable block"));
+}
+
 #[test]
 fn decode_block_detects_crc_mismatch() {
     let (header, opts) = default_compress_opts(2);
@@ -371,15 +1019,16 @@ fn run_projection(bytes: &[u8], format: ProjectFormat, fields: &[&str]) -> Strin
         ProjectFormat::Ndjson => "ndjson",
         ProjectFormat::JsonArray => "json",
         ProjectFormat::Csv { .. } => "csv",
+        _ => "out",
     });
 
-    let request = ProjectRequest {
-        input: JacInput::Reader(Box::new(Cursor::new(bytes.to_vec()))),
-        output: OutputSink::Path(path.clone()),
-        fields: fields.iter().map(|field| field.to_string()).collect(),
-        format,
-        options: DecompressOptions::default(),
-    };
+    let request = ProjectRequest::builder()
+        .input(JacInput::Reader(Box::new(Cursor::new(bytes.to_vec()))))
+        .output(OutputSink::Path(path.clone()))
+        .fields(fields.iter().map(|field| field.to_string()))
+        .format(format)
+        .build()
+        .unwrap();
 
     execute_project(request).expect("projection");
 
@@ -396,3 +1045,213 @@ fn temp_output_path(label: &str) -> PathBuf {
         .as_nanos();
     base.join(format!("jac_integration_{}_{}.out", label, unique))
 }
+
+#[test]
+fn snapshot_reader_stops_at_last_complete_block() {
+    let (header, opts) = default_compress_opts(2);
+    let buffer = Cursor::new(Vec::<u8>::new());
+    let mut writer = JacWriter::new(buffer, header, opts).unwrap();
+    for id in 0..6 {
+        writer.write_record(&map_from(json!({"id": id}))).unwrap();
+    }
+    let bytes = finish_writer(writer, true);
+
+    let mut full =
+        JacReader::snapshot(Cursor::new(bytes.clone()), default_decompress_opts()).expect("reader");
+    assert!(full.is_finalized());
+    let blocks = full
+        .blocks()
+        .collect::<jac_format::Result<Vec<_>>>()
+        .expect("blocks");
+    assert_eq!(blocks.len(), 3);
+    let data_end = blocks[2].offset as usize + blocks[2].size;
+
+    let ids = |bytes: Vec<u8>| -> Vec<Value> {
+        let mut reader =
+            JacReader::snapshot(Cursor::new(bytes), default_decompress_opts()).expect("reader");
+        assert!(!reader.is_finalized());
+        reader
+            .record_stream()
+            .unwrap()
+            .map(|rec| rec.expect("snapshot read should not fail")["id"].clone())
+            .collect()
+    };
+
+    // Third block half written: only the first two blocks are visible.
+    let cut = blocks[2].offset as usize + blocks[2].size / 2;
+    assert_eq!(
+        ids(bytes[..cut].to_vec()),
+        vec![json!(0), json!(1), json!(2), json!(3)]
+    );
+
+    // Index footer half written: every block is visible.
+    let cut = data_end + (bytes.len() - data_end) / 2;
+    assert_eq!(ids(bytes[..cut].to_vec()).len(), 6);
+
+    // Strict readers keep reporting the truncation.
+    let cut = blocks[2].offset as usize + blocks[2].size / 2;
+    let mut strict = JacReader::new(
+        Cursor::new(bytes[..cut].to_vec()),
+        default_decompress_opts(),
+    )
+    .expect("reader");
+    let result: jac_format::Result<Vec<_>> = strict.record_stream().unwrap().collect();
+    assert!(matches!(result, Err(JacError::UnexpectedEof)));
+}
+
+#[test]
+fn plugin_written_against_plugin_api_runs_in_compress() {
+    use jac_io::{
+        execute_compress, CompressRequest, InputSource, WrapperConfig, WrapperPluginRegistry,
+    };
+    use jac_plugin_api::{PluginRecords, WrapperError, WrapperLimits, WrapperPlugin};
+    use std::io::Read;
+    use std::sync::Arc;
+
+    /// One record per non-empty input line
+    struct LinesPlugin;
+
+    impl WrapperPlugin for LinesPlugin {
+        fn name(&self) -> &str {
+            "integration-lines"
+        }
+
+        fn process(
+            &self,
+            mut input: Box<dyn Read + Send>,
+            config: &Value,
+            _limits: &WrapperLimits,
+        ) -> Result<PluginRecords, WrapperError> {
+            let field = config["field"].as_str().unwrap_or("line").to_string();
+            let mut text = String::new();
+            input.read_to_string(&mut text)?;
+            let records: Vec<_> = text
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(|line| Ok(map_from(json!({ field.as_str(): line }))))
+                .collect();
+            Ok(Box::new(records.into_iter()))
+        }
+    }
+
+    WrapperPluginRegistry::global()
+        .register(Arc::new(LinesPlugin))
+        .unwrap();
+    let request = CompressRequest::builder()
+        .input(InputSource::NdjsonReader(Box::new(Cursor::new(
+            b"first\n\nsecond\n".to_vec(),
+        ))))
+        .output(OutputSink::Writer(Box::new(Cursor::new(Vec::new()))))
+        .wrapper_config(WrapperConfig::Plugin {
+            plugin_name: "integration-lines".to_string(),
+            config: json!({ "field": "text" }),
+            limits: jac_io::WrapperLimits::default(),
+        })
+        .build()
+        .unwrap();
+    let summary = execute_compress(request).unwrap();
+    assert_eq!(summary.metrics.records_written, 2);
+}
+
+#[test]
+fn plugin_schema_hints_steer_column_encoding() {
+    use jac_io::{
+        execute_compress, CompressOptions, CompressRequest, FieldEncodingHint, InputSource,
+        WrapperConfig, WrapperPluginRegistry,
+    };
+    use jac_plugin_api::{
+        FieldHint, FieldType, PluginRecords, SchemaHints, WrapperError, WrapperLimits,
+        WrapperPlugin,
+    };
+    use std::io::Read;
+    use std::sync::Arc;
+
+    /// Events with an irregular timestamp and two unbounded string fields
+    struct EventsPlugin;
+
+    impl WrapperPlugin for EventsPlugin {
+        fn name(&self) -> &str {
+            "integration-events"
+        }
+
+        fn process(
+            &self,
+            mut input: Box<dyn Read + Send>,
+            _config: &Value,
+            _limits: &WrapperLimits,
+        ) -> Result<PluginRecords, WrapperError> {
+            input.read_to_end(&mut Vec::new())?;
+            let records: Vec<_> = (0..32u64)
+                .map(|i| {
+                    // One long gap fails the delta-uniformity check.
+                    let ts = 1_700_000_000 + i + if i < 16 { 0 } else { 3_600 };
+                    let id = format!("id-{}", i % 2);
+                    Ok(map_from(json!({ "ts": ts, "id": id, "host": id })))
+                })
+                .collect();
+            Ok(Box::new(records.into_iter()))
+        }
+
+        fn schema_hints(&self, _config: &Value) -> Option<SchemaHints> {
+            let field = |name: &str, expected_type, estimated_cardinality| FieldHint {
+                name: name.to_string(),
+                expected_type: Some(expected_type),
+                estimated_cardinality,
+                always_present: true,
+            };
+            Some(SchemaHints {
+                fields: vec![
+                    field("ts", FieldType::Int, None),
+                    field("id", FieldType::String, Some(1_000_000)),
+                    field("host", FieldType::String, Some(1_000_000)),
+                ],
+                estimated_record_count: Some(32),
+                uniform_schema: true,
+            })
+        }
+    }
+
+    WrapperPluginRegistry::global()
+        .register(Arc::new(EventsPlugin))
+        .unwrap();
+    let path = temp_output_path("schema_hints");
+    let mut options = CompressOptions::default();
+    // Explicit entries win over the plugin's hints.
+    options
+        .field_hints
+        .insert("host".to_string(), FieldEncodingHint::default());
+    let request = CompressRequest::builder()
+        .input(InputSource::NdjsonReader(Box::new(Cursor::new(Vec::new()))))
+        .output(OutputSink::Path(path.clone()))
+        .options(options)
+        .wrapper_config(WrapperConfig::Plugin {
+            plugin_name: "integration-events".to_string(),
+            config: Value::Null,
+            limits: jac_io::WrapperLimits::default(),
+        })
+        .build()
+        .unwrap();
+    execute_compress(request).unwrap();
+
+    let bytes = fs::read(&path).unwrap();
+    let _ = fs::remove_file(&path);
+    let mut reader = JacReader::new(Cursor::new(bytes), default_decompress_opts()).unwrap();
+    let mut flags = HashMap::new();
+    for block in reader.blocks() {
+        for entry in block.unwrap().header.fields {
+            *flags.entry(entry.field_name).or_insert(0u64) |= entry.encoding_flags;
+        }
+    }
+    // Bit 0 marks dictionary encoding, bit 1 delta encoding.
+    assert_eq!(flags["ts"], 0b10);
+    assert_eq!(flags["id"], 0);
+    assert_eq!(flags["host"], 0b01);
+
+    let records: Vec<_> = reader
+        .record_stream()
+        .unwrap()
+        .map(|record| record.unwrap())
+        .collect();
+    assert_eq!(records.len(), 32);
+    assert_eq!(records[20]["ts"], json!(1_700_003_620));
+}
@@ -1,8 +1,8 @@
 use jac_format::Result;
 use jac_io::{
     execute_compress, execute_decompress, parallel::ParallelConfig, CompressOptions,
-    CompressRequest, CompressSummary, ContainerFormat, DecompressFormat, DecompressOptions,
-    DecompressRequest, InputSource, JacInput, OutputSink, WrapperConfig,
+    CompressRequest, CompressSummary, ContainerFormat, DecompressFormat, DecompressRequest,
+    InputSource, JacInput, OutputSink,
 };
 use serde_json::{Map, Value};
 use std::fs;
@@ -57,14 +57,15 @@ fn compress_with_threads(
     options.limits.max_block_uncompressed_total = 32 * 1024 * 1024;
 
     let iterator_records = records.to_vec();
-    let request = CompressRequest {
-        input: InputSource::Iterator(Box::new(iterator_records.into_iter())),
-        output: OutputSink::Path(output_path.clone()),
-        options,
-        container_hint: Some(ContainerFormat::Ndjson),
-        emit_index: false,
-        wrapper_config: WrapperConfig::None,
-    };
+    let request = CompressRequest::builder()
+        .input(InputSource::Iterator(Box::new(
+            iterator_records.into_iter(),
+        )))
+        .output(OutputSink::Path(output_path.clone()))
+        .options(options)
+        .container_hint(ContainerFormat::Ndjson)
+        .emit_index(false)
+        .build()?;
 
     let summary = execute_compress(request)?;
     let bytes = fs::read(&output_path)?;
@@ -78,12 +79,11 @@ fn decompress_bytes(bytes: &[u8]) -> Result<Vec<Value>> {
 
     fs::write(&input_path, bytes)?;
 
-    let request = DecompressRequest {
-        input: JacInput::Path(input_path),
-        output: OutputSink::Path(output_path.clone()),
-        format: DecompressFormat::Ndjson,
-        options: DecompressOptions::default(),
-    };
+    let request = DecompressRequest::builder()
+        .input(JacInput::Path(input_path))
+        .output(OutputSink::Path(output_path.clone()))
+        .format(DecompressFormat::Ndjson)
+        .build()?;
 
     execute_decompress(request)?;
     let contents = fs::read_to_string(&output_path)?;
@@ -0,0 +1,58 @@
+//! Property-based round-trip tests across codec, limit and canonicalization
+//! combinations
+
+use jac_io::{Codec, Limits};
+use jac_test_utils::roundtrip::{arb_records, assert_roundtrip, RecordShape, RoundtripOpts};
+use proptest::prelude::*;
+
+fn codecs() -> impl Strategy<Value = Codec> {
+    prop::sample::select(vec![Codec::None, Codec::Zstd(1), Codec::Zstd(9)])
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(48))]
+
+    #[test]
+    fn records_roundtrip_across_codecs_and_block_sizes(
+        records in arb_records(RecordShape::default(), 40),
+        codec in codecs(),
+        block_target_records in 1usize..16,
+        with_index in any::<bool>(),
+    ) {
+        let mut opts = RoundtripOpts::with_codec(codec, block_target_records);
+        opts.with_index = with_index;
+        assert_roundtrip(&records, &opts);
+    }
+
+    #[test]
+    fn records_roundtrip_with_canonicalization(
+        records in arb_records(RecordShape::default(), 24),
+        canonicalize_keys in any::<bool>(),
+        canonicalize_numbers in any::<bool>(),
+        preserve_key_order in any::<bool>(),
+    ) {
+        let mut opts = RoundtripOpts::with_codec(Codec::Zstd(1), 8)
+            .canonicalize(canonicalize_keys, canonicalize_numbers);
+        opts.compress.preserve_key_order = preserve_key_order;
+        assert_roundtrip(&records, &opts);
+    }
+
+    #[test]
+    fn records_roundtrip_under_tight_limits(
+        records in arb_records(RecordShape { max_depth: 1, ..RecordShape::default() }, 32),
+        max_records_per_block in 1usize..8,
+        max_block_uncompressed_total in 8192usize..32768,
+        max_dict_entries in 1usize..6,
+    ) {
+        let limits = Limits {
+            max_records_per_block,
+            max_block_uncompressed_total,
+            max_dict_entries_per_field: max_dict_entries,
+            ..Limits::default()
+        };
+        let mut opts =
+            RoundtripOpts::with_codec(Codec::None, max_records_per_block).limits(limits);
+        opts.compress.max_dict_entries = max_dict_entries;
+        assert_roundtrip(&records, &opts);
+    }
+}
@@ -4,6 +4,7 @@ use thiserror::Error;
 
 /// Errors that can occur during wrapper preprocessing
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum WrapperError {
     /// Buffer limit exceeded while traversing to target
     #[error(
@@ -133,92 +134,6 @@ pub enum WrapperError {
         max_ptr_len: usize,
     },
 
-    /// Section not found in input document
-    #[error(
-        "Section not found: '{section}' at pointer '{pointer}' does not exist.\n\
-         \n\
-         Available keys at this level: {available_keys}\n\
-         \n\
-         Suggested fixes:\n\
-         1. Check section name spelling\n\
-         2. Verify input structure matches expected format\n\
-         3. Use --wrapper-sections-missing-skip to allow missing sections"
-    )]
-    SectionNotFound {
-        /// Name of the section that was not found
-        section: String,
-        /// JSON Pointer path that was used
-        pointer: String,
-        /// Comma-separated list of keys available at the target location
-        available_keys: String,
-    },
-
-    /// Section label field collision
-    #[error(
-        "Section label collision: field '{field}' already exists in record from section '{section}'.\n\
-         \n\
-         The wrapper tried to inject section label into field '{field}', but this field\n\
-         already exists in the source record.\n\
-         \n\
-         Suggested fixes:\n\
-         1. Choose a different label field: --wrapper-section-label-field <field>\n\
-         2. Disable label injection: --wrapper-section-no-label"
-    )]
-    SectionLabelCollision {
-        /// Field name that collided
-        field: String,
-        /// Section that caused the collision
-        section: String,
-    },
-
-    /// Map key too long
-    #[error(
-        "Map key too long: key '{key}' has length {length} bytes (max: {max_length} bytes).\n\
-         \n\
-         This limit prevents malicious inputs and aligns with JAC's max_string_len_per_value.\n\
-         If you have a legitimate use case, consider restructuring your data or preprocessing externally."
-    )]
-    MapKeyTooLong {
-        /// The key that exceeded the limit
-        key: String,
-        /// Actual length of the key (bytes)
-        length: usize,
-        /// Maximum key length allowed (bytes)
-        max_length: usize,
-    },
-
-    /// Key field collision in map mode
-    #[error(
-        "Key field collision: field '{field}' already exists in record with map key '{map_key}'.\n\
-         \n\
-         The wrapper tried to inject the map key into field '{field}', but this field\n\
-         already exists in the source record.\n\
-         \n\
-         Suggested fixes:\n\
-         1. Choose a different key field: --wrapper-map-key-field <field>\n\
-         2. Enable overwrite mode: --wrapper-map-overwrite-key"
-    )]
-    KeyFieldCollision {
-        /// Field name that collided
-        field: String,
-        /// Map key that caused the collision
-        map_key: String,
-    },
-
-    /// Map value is not an object
-    #[error(
-        "Map value not an object: key '{key}' points to {found_type}, expected object.\n\
-         \n\
-         JAC map wrappers require all values to be objects (records).\n\
-         Scalar, null, and array values cannot be processed."
-    )]
-    MapValueNotObject {
-        /// The map key with wrong value type
-        key: String,
-        /// Actual type found
-        found_type: String,
-    },
-
     /// JSON parsing error during wrapper traversal
     #[error("JSON parse error while processing wrapper: {context} - {source}")]
     JsonParse {
@@ -232,10 +147,6 @@ pub enum WrapperError {
     #[error("I/O error during wrapper processing: {0}")]
     Io(#[from] std::io::Error),
 
-    /// Underlying JAC error
-    #[error("JAC error: {0}")]
-    Jac(#[from] jac_format::JacError),
-
     /// Plugin already registered with same name
     #[error(
         "Plugin already registered: a plugin named '{name}' is already registered.\n\
@@ -278,35 +189,18 @@ pub enum WrapperError {
         reason: String,
     },
 
-    /// Array header row error
-    #[error(
-        "Invalid header row: {reason}\n\
-         \n\
-         Array-with-headers wrapper requires the first element to be an array of strings.\n\
-         Example: [[\"id\", \"name\"], [1, \"Alice\"], [2, \"Bob\"]]"
-    )]
-    InvalidHeaderRow {
-        /// Reason why the header is invalid
-        reason: String,
-    },
-
-    /// Array row length mismatch
-    #[error(
-        "Row length mismatch: row {row_index} has {actual} elements, expected {expected} (from header).\n\
-         \n\
-         All rows must have the same number of elements as the header row."
-    )]
-    ArrayRowLengthMismatch {
-        /// Index of the row with wrong length
-        row_index: usize,
-        /// Actual number of elements
-        actual: usize,
-        /// Expected number of elements
-        expected: usize,
-    },
+    /// Error from outside the wrapper traversal, such as a plugin's own
+    /// parsing or a built-in wrapper's checks; see [`WrapperError::other`]
+    #[error("{0}")]
+    Other(Box<dyn std::error::Error + Send + Sync>),
 }
 
 impl WrapperError {
+    /// Wrap an error of the plugin's own, or a message
+    pub fn other(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self::Other(err.into())
+    }
+
     /// Suggest a buffer size with safety margin
     pub fn suggest_buffer_size(current_bytes: usize) -> String {
         let suggested = (current_bytes as f64 * 1.5).ceil() as usize;
@@ -0,0 +1,32 @@
+//! Stable API for JAC wrapper plugins
+//!
+//! This crate holds everything a third-party wrapper plugin needs: the
+//! [`WrapperPlugin`] trait, its [`WrapperPluginRegistry`], the schema hint
+//! types, [`WrapperLimits`] and [`WrapperError`]. `jac-io` re-exports all of
+//! them under their old paths, so plugins may depend on either crate; depending
+//! on `jac-plugin-api` alone keeps a plugin building across `jac-io` releases.
+//!
+//! # Compatibility
+//!
+//! `jac-plugin-api` is versioned independently of the other workspace crates
+//! and follows semantic versioning (while 0.x, the minor version is the
+//! breaking one). Every `jac-io` release states the `jac-plugin-api` version it
+//! builds on, and changes to `jac-io` internals never change this crate.
+//!
+//! - [`WrapperError`] and [`FieldType`] are `#[non_exhaustive]`: new variants
+//!   arrive in compatible releases, so matches need a wildcard arm.
+//! - The crate depends on no other JAC crate. Errors from elsewhere, a
+//!   plugin's own included, travel in [`WrapperError::Other`].
+//! - New [`WrapperPlugin`] methods always come with a default implementation.
+//! - Adding fields to the public structs is a breaking change.
+
+pub mod error;
+pub mod limits;
+pub mod plugin;
+
+pub use error::WrapperError;
+pub use limits::WrapperLimits;
+pub use plugin::{
+    FieldHint, FieldType, PluginRecords, SchemaHints, WrapperPlugin, WrapperPluginMetadata,
+    WrapperPluginRegistry,
+};
@@ -0,0 +1,77 @@
+//! Limits enforced by wrappers while preprocessing input
+
+use crate::WrapperError;
+use serde::{Deserialize, Serialize};
+
+/// Wrapper-specific limits enforced during input preprocessing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WrapperLimits {
+    /// Maximum JSON pointer depth (default: 3, hard max: 10).
+    pub max_depth: usize,
+    /// Maximum bytes buffered before reaching target (default: 16 MiB, hard max: 128 MiB).
+    pub max_buffer_bytes: usize,
+    /// Maximum pointer string length (default: 256, hard max: 2048).
+    pub max_pointer_length: usize,
+}
+
+impl Default for WrapperLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            max_buffer_bytes: 16 * 1024 * 1024,
+            max_pointer_length: 256,
+        }
+    }
+}
+
+impl WrapperLimits {
+    /// Hard maximum limits that cannot be exceeded
+    pub fn hard_maximums() -> Self {
+        Self {
+            max_depth: 10,
+            max_buffer_bytes: 128 * 1024 * 1024,
+            max_pointer_length: 2048,
+        }
+    }
+
+    /// Validate limits against hard maximums
+    pub fn validate(&self) -> Result<(), WrapperError> {
+        let hard = Self::hard_maximums();
+
+        if self.max_depth > hard.max_depth {
+            return Err(WrapperError::ConfigurationExceedsHardLimits {
+                reason: format!("max_depth {} exceeds {}", self.max_depth, hard.max_depth),
+                max_depth: hard.max_depth,
+                max_buffer: hard.max_buffer_bytes,
+                max_ptr_len: hard.max_pointer_length,
+            });
+        }
+
+        if self.max_buffer_bytes > hard.max_buffer_bytes {
+            return Err(WrapperError::ConfigurationExceedsHardLimits {
+                reason: format!(
+                    "max_buffer_bytes {} exceeds {}",
+                    self.max_buffer_bytes, hard.max_buffer_bytes
+                ),
+                max_depth: hard.max_depth,
+                max_buffer: hard.max_buffer_bytes,
+                max_ptr_len: hard.max_pointer_length,
+            });
+        }
+
+        if self.max_pointer_length > hard.max_pointer_length {
+            return Err(WrapperError::ConfigurationExceedsHardLimits {
+                reason: format!(
+                    "max_pointer_length {} exceeds {}",
+                    self.max_pointer_length, hard.max_pointer_length
+                ),
+                max_depth: hard.max_depth,
+                max_buffer: hard.max_buffer_bytes,
+                max_ptr_len: hard.max_pointer_length,
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -6,7 +6,8 @@
 //! # Example
 //!
 //! ```rust,ignore
-//! use jac_io::wrapper::plugin::{WrapperPlugin, WrapperPluginMetadata};
+//! use jac_plugin_api::{PluginRecords, WrapperError, WrapperLimits, WrapperPlugin};
+//! use std::io::Read;
 //! use serde_json::{Map, Value};
 //!
 //! struct MyCustomWrapper;
@@ -21,15 +22,14 @@
 //!         input: Box<dyn Read + Send>,
 //!         config: &Value,
 //!         limits: &WrapperLimits,
-//!     ) -> Result<Box<dyn Iterator<Item = Map<String, Value>> + Send>, WrapperError> {
+//!     ) -> Result<PluginRecords, WrapperError> {
 //!         // Custom processing logic
 //!         todo!()
 //!     }
 //! }
 //! ```
 
-use super::WrapperError;
-use crate::WrapperLimits;
+use crate::{WrapperError, WrapperLimits};
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 use std::io::Read;
@@ -48,6 +48,9 @@ pub struct WrapperPluginMetadata {
     pub author: Option<String>,
 }
 
+/// Records produced by [`WrapperPlugin::process`]
+pub type PluginRecords = Box<dyn Iterator<Item = Result<Map<String, Value>, WrapperError>> + Send>;
+
 /// Trait for implementing custom wrapper plugins
 ///
 /// Plugins provide custom JSON preprocessing logic that can be registered
@@ -82,7 +85,7 @@ pub trait WrapperPlugin: Send + Sync {
         input: Box<dyn Read + Send>,
         config: &Value,
         limits: &WrapperLimits,
-    ) -> Result<Box<dyn Iterator<Item = Result<Map<String, Value>, WrapperError>> + Send>, WrapperError>;
+    ) -> Result<PluginRecords, WrapperError>;
 
     /// Optionally provide schema information for the output records
     ///
@@ -126,6 +129,7 @@ pub struct FieldHint {
 
 /// Expected field type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum FieldType {
     /// Null values only
     Null,
@@ -188,16 +192,13 @@ impl WrapperPluginRegistry {
     /// List all registered plugins
     pub fn list(&self) -> Vec<WrapperPluginMetadata> {
         let plugins = self.plugins.read().unwrap();
-        plugins
-            .values()
-            .map(|p| p.metadata())
-            .collect()
+        plugins.values().map(|p| p.metadata()).collect()
     }
 
     /// Get the global singleton instance
     pub fn global() -> &'static Self {
         static INSTANCE: std::sync::OnceLock<WrapperPluginRegistry> = std::sync::OnceLock::new();
-        INSTANCE.get_or_init(|| WrapperPluginRegistry::new())
+        INSTANCE.get_or_init(WrapperPluginRegistry::new)
     }
 }
 
@@ -225,7 +226,7 @@ mod tests {
             _input: Box<dyn Read + Send>,
             _config: &Value,
             _limits: &WrapperLimits,
-        ) -> Result<Box<dyn Iterator<Item = Result<Map<String, Value>, WrapperError>> + Send>, WrapperError> {
+        ) -> Result<PluginRecords, WrapperError> {
             Ok(Box::new(std::iter::empty()))
         }
     }
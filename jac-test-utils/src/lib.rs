@@ -7,10 +7,14 @@ use std::collections::HashMap;
 
 pub mod debug_tools;
 pub mod profiler;
+pub mod roundtrip;
 pub mod test_categories;
 pub mod test_config;
 pub mod test_debugger;
 pub mod visualization;
+pub mod workloads;
+
+pub use workloads::{Workload, WorkloadProfile};
 
 /// Builder for creating test records with common patterns
 pub struct RecordBuilder {
@@ -186,6 +190,19 @@ impl TestDataGenerator {
         ]
     }
 
+    /// Stream records for a workload profile, lazily and deterministically
+    ///
+    /// The same `profile` and `seed` always yield the same records; bound the
+    /// stream with `take(n)`.
+    pub fn workload(profile: WorkloadProfile, seed: u64) -> Workload {
+        Workload::new(profile, seed)
+    }
+
+    /// Collect `count` records of a workload profile
+    pub fn workload_records(profile: WorkloadProfile, seed: u64, count: usize) -> Vec<Value> {
+        Workload::new(profile, seed).take(count).collect()
+    }
+
     /// Generate a large number of records for stress testing
     pub fn large_record_set(count: usize) -> Vec<Value> {
         let mut records = Vec::with_capacity(count);
@@ -0,0 +1,338 @@
+//! Property-based round-trip harness
+//!
+//! This module provides proptest strategies that generate arbitrary JSON
+//! records (bounded depth and size, every scalar type, unicode text and
+//! decimals) and an [`assert_roundtrip`] helper that writes records through
+//! [`JacWriter`], reads them back with [`JacReader`] and compares the result
+//! semantically under the options that were used.
+
+use jac_io::{
+    Codec, CompressOpts, DecompressOpts, FileHeader, JacReader, JacWriter, Limits,
+    NumberCanonicalization,
+};
+use proptest::prelude::*;
+use serde_json::{Map, Number, Value};
+use std::io::Cursor;
+
+/// Bounds for generated records
+#[derive(Debug, Clone, Copy)]
+pub struct RecordShape {
+    /// Maximum nesting depth of objects/arrays inside a field value
+    pub max_depth: u32,
+    /// Maximum number of top-level fields per record
+    pub max_fields: usize,
+    /// Maximum number of elements per nested object/array
+    pub max_width: usize,
+    /// Maximum string length in characters
+    pub max_string_len: usize,
+}
+
+impl Default for RecordShape {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            max_fields: 8,
+            max_width: 4,
+            max_string_len: 24,
+        }
+    }
+}
+
+/// Field names shared across generated records so columns see repeated keys,
+/// schema drift and absent values.
+const FIELD_NAMES: &[&str] = &[
+    "id", "ts", "level", "user", "msg", "score", "tags", "meta", "ok", "név", "名前", "_",
+];
+
+/// Arbitrary unicode string of at most `max_len` characters
+pub fn arb_string(max_len: usize) -> impl Strategy<Value = String> {
+    prop_oneof![
+        prop::collection::vec(any::<char>(), 0..=max_len)
+            .prop_map(|chars| chars.into_iter().collect::<String>()),
+        // Low-cardinality values exercise dictionary encoding.
+        prop::sample::select(vec!["", "INFO", "WARN", "ERROR", "héllo", "世界", "🚀"])
+            .prop_map(str::to_string),
+    ]
+}
+
+/// Arbitrary finite decimal number
+pub fn arb_decimal() -> impl Strategy<Value = Number> {
+    prop_oneof![
+        // Short fixed-point values such as prices and ratios.
+        (any::<i32>(), 1u32..10)
+            .prop_map(|(mantissa, scale)| mantissa as f64 / 10f64.powi(scale as i32)),
+        prop::num::f64::NORMAL | prop::num::f64::ZERO,
+    ]
+    .prop_filter_map("finite decimal", Number::from_f64)
+}
+
+/// Arbitrary JSON number: small and full-range integers, integers beyond
+/// `i64`, and decimals
+pub fn arb_number() -> impl Strategy<Value = Number> {
+    prop_oneof![
+        (-1000i64..1000).prop_map(Number::from),
+        any::<i64>().prop_map(Number::from),
+        ((i64::MAX as u64 + 1)..=u64::MAX).prop_map(Number::from),
+        arb_decimal(),
+    ]
+}
+
+/// Arbitrary JSON scalar (null, bool, number or string)
+pub fn arb_scalar(max_string_len: usize) -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        arb_number().prop_map(Value::Number),
+        arb_string(max_string_len).prop_map(Value::String),
+    ]
+}
+
+/// Arbitrary JSON value of bounded depth and width
+pub fn arb_json_value(shape: RecordShape) -> BoxedStrategy<Value> {
+    let width = shape.max_width;
+    arb_scalar(shape.max_string_len)
+        .prop_recursive(shape.max_depth, 64, width.max(1) as u32, move |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..=width).prop_map(Value::Array),
+                prop::collection::vec((arb_string(8), inner), 0..=width)
+                    .prop_map(|entries| Value::Object(entries.into_iter().collect())),
+            ]
+        })
+        .boxed()
+}
+
+/// Arbitrary top-level record
+pub fn arb_record(shape: RecordShape) -> impl Strategy<Value = Map<String, Value>> {
+    let names = prop_oneof![
+        4 => prop::sample::select(FIELD_NAMES).prop_map(str::to_string),
+        1 => arb_string(12),
+    ];
+    prop::collection::vec((names, arb_json_value(shape)), 0..=shape.max_fields)
+        .prop_map(|entries| entries.into_iter().collect())
+}
+
+/// Arbitrary batch of up to `max_records` records
+pub fn arb_records(
+    shape: RecordShape,
+    max_records: usize,
+) -> impl Strategy<Value = Vec<Map<String, Value>>> {
+    prop::collection::vec(arb_record(shape), 0..=max_records)
+}
+
+/// Options for a single round trip
+#[derive(Debug, Clone)]
+pub struct RoundtripOpts {
+    /// Encoder options, including codec, limits and canonicalization
+    pub compress: CompressOpts,
+    /// Decoder options
+    pub decompress: DecompressOpts,
+    /// Write the index footer
+    pub with_index: bool,
+}
+
+impl Default for RoundtripOpts {
+    fn default() -> Self {
+        Self {
+            compress: CompressOpts {
+                default_codec: Codec::None,
+                ..CompressOpts::default()
+            },
+            decompress: DecompressOpts::default(),
+            with_index: true,
+        }
+    }
+}
+
+impl RoundtripOpts {
+    /// Options using `codec` with blocks of `block_target_records`
+    pub fn with_codec(codec: Codec, block_target_records: usize) -> Self {
+        let mut opts = Self::default();
+        opts.compress.default_codec = codec;
+        opts.compress.block_target_records = block_target_records;
+        opts
+    }
+
+    /// Apply the same limits to encoder and decoder
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.compress.limits = limits.clone();
+        self.decompress.limits = limits;
+        self
+    }
+
+    /// Enable key and/or number canonicalization.
+    ///
+    /// Numbers use a value-preserving policy that only trims trailing zeros;
+    /// the default policy's scientific collapsing (and `max_scale` rounding)
+    /// is lossy by design and would not round-trip.
+    pub fn canonicalize(mut self, keys: bool, numbers: bool) -> Self {
+        self.compress.canonicalize_keys = keys;
+        self.compress.canonicalize_numbers = numbers;
+        self.compress.number_canonicalization = NumberCanonicalization {
+            preserve_trailing_zeros: false,
+            scientific_exponent_threshold: None,
+            max_scale: None,
+        };
+        self
+    }
+
+    fn header(&self) -> FileHeader {
+        let opts = &self.compress;
+        let mut flags = 0;
+        if opts.canonicalize_keys {
+            flags |= jac_format::constants::FLAG_CANONICALIZE_KEYS;
+        }
+        if opts.canonicalize_numbers {
+            flags |= jac_format::constants::FLAG_CANONICALIZE_NUMBERS;
+        }
+        if opts.nested_opaque {
+            flags |= jac_format::constants::FLAG_NESTED_OPAQUE;
+        }
+        FileHeader {
+            flags,
+            default_compressor: opts.default_codec.compressor_id(),
+            default_compression_level: opts.default_codec.level(),
+            block_size_hint_records: opts.block_target_records,
+            user_metadata: Vec::new(),
+        }
+    }
+}
+
+/// Compress `records`, decode them again and return the decoded records
+pub fn roundtrip(
+    records: &[Map<String, Value>],
+    opts: &RoundtripOpts,
+) -> jac_io::Result<Vec<Map<String, Value>>> {
+    let mut writer = JacWriter::new(
+        Cursor::new(Vec::new()),
+        opts.header(),
+        opts.compress.clone(),
+    )?;
+    writer.write_records(records.iter().cloned())?;
+    let bytes = writer.finish(opts.with_index)?.into_inner();
+
+    let mut reader = JacReader::new(Cursor::new(bytes), opts.decompress.clone())?;
+    let decoded = reader
+        .record_stream()?
+        .collect::<jac_io::Result<Vec<_>>>()?;
+    Ok(decoded)
+}
+
+/// Round-trip `records` and panic with the first difference found.
+///
+/// Records must come back one-for-one. Values are compared semantically:
+/// object key order is ignored unless `preserve_key_order` is set (and keys
+/// must come back sorted when `canonicalize_keys` is set), and numbers compare
+/// by value, so `1.0` and a canonicalized `1` are equal.
+pub fn assert_roundtrip(records: &[Map<String, Value>], opts: &RoundtripOpts) {
+    let decoded = roundtrip(records, opts)
+        .unwrap_or_else(|err| panic!("round trip failed for {} records: {}", records.len(), err));
+    assert_eq!(decoded.len(), records.len(), "decoded record count differs");
+
+    for (idx, (original, actual)) in records.iter().zip(&decoded).enumerate() {
+        if let Some(path) = first_difference(
+            &Value::Object(original.clone()),
+            &Value::Object(actual.clone()),
+            "$",
+        ) {
+            panic!(
+                "record {} differs at {}:\nExpected: {}\nActual: {}",
+                idx,
+                path,
+                Value::Object(original.clone()),
+                Value::Object(actual.clone())
+            );
+        }
+
+        let keys: Vec<&String> = actual.keys().collect();
+        if opts.compress.canonicalize_keys {
+            let mut sorted = keys.clone();
+            sorted.sort();
+            assert_eq!(keys, sorted, "record {} keys are not canonical", idx);
+        } else if opts.compress.preserve_key_order {
+            let expected: Vec<&String> = original.keys().collect();
+            assert_eq!(keys, expected, "record {} key order not preserved", idx);
+        }
+    }
+}
+
+/// Path of the first semantic difference between two values, if any
+fn first_difference(expected: &Value, actual: &Value, path: &str) -> Option<String> {
+    match (expected, actual) {
+        (Value::Number(a), Value::Number(b)) => (!numbers_equal(a, b)).then(|| path.to_string()),
+        (Value::Array(a), Value::Array(b)) => {
+            if a.len() != b.len() {
+                return Some(path.to_string());
+            }
+            a.iter()
+                .zip(b)
+                .enumerate()
+                .find_map(|(idx, (a, b))| first_difference(a, b, &format!("{}[{}]", path, idx)))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            if a.len() != b.len() {
+                return Some(path.to_string());
+            }
+            a.iter().find_map(|(key, a)| {
+                let child = format!("{}.{}", path, key);
+                match b.get(key) {
+                    Some(b) => first_difference(a, b, &child),
+                    None => Some(child),
+                }
+            })
+        }
+        (a, b) => (a != b).then(|| path.to_string()),
+    }
+}
+
+fn numbers_equal(a: &Number, b: &Number) -> bool {
+    if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+        return a == b;
+    }
+    if let (Some(a), Some(b)) = (a.as_u64(), b.as_u64()) {
+        return a == b;
+    }
+    match (a.as_f64(), b.as_f64()) {
+        // Decimals are reparsed from text, so allow for the last-ulp slack of
+        // serde_json's float parser.
+        (Some(a), Some(b)) => a == b || (a - b).abs() <= a.abs().max(b.abs()) * 4.0 * f64::EPSILON,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_numbers_equal_after_canonicalization() {
+        let float: Value = serde_json::from_str("1.0").unwrap();
+        let int: Value = serde_json::from_str("1").unwrap();
+        assert!(first_difference(&float, &int, "$").is_none());
+        assert_eq!(
+            first_difference(&json!({"a": [1, 2]}), &json!({"a": [1, 3]}), "$").as_deref(),
+            Some("$.a[1]")
+        );
+    }
+
+    #[test]
+    fn test_assert_roundtrip_handcrafted_records() {
+        let records = sample_records();
+        assert_roundtrip(&records, &RoundtripOpts::default());
+        assert_roundtrip(
+            &records,
+            &RoundtripOpts::with_codec(Codec::Zstd(1), 2).canonicalize(true, true),
+        );
+    }
+
+    fn sample_records() -> Vec<Map<String, Value>> {
+        [
+            json!({"id": 1, "price": 1.50, "name": "héllo", "nested": {"b": 1, "a": [true]}}),
+            json!({"id": 18446744073709551615u64, "price": -0.001, "name": null}),
+            json!({}),
+        ]
+        .into_iter()
+        .map(|value| value.as_object().unwrap().clone())
+        .collect()
+    }
+}
@@ -0,0 +1,365 @@
+//! Deterministic synthetic workloads
+//!
+//! This module generates realistic record streams for benchmarks and soak
+//! tests. Each [`WorkloadProfile`] describes a data shape (web logs, IoT
+//! metrics, nested API responses, sparse wide schemas); a [`Workload`] yields
+//! records for a profile lazily, so millions of records can be streamed
+//! without materializing them. The same profile and seed always produce the
+//! same records, on every platform.
+
+use serde_json::{json, Map, Value};
+
+/// First timestamp emitted by every workload (2021-01-01T00:00:00Z)
+const BASE_TIMESTAMP: i64 = 1_609_459_200;
+
+/// Shape of the records a [`Workload`] produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkloadProfile {
+    /// HTTP access logs: low-cardinality methods/statuses, skewed users
+    WebLogs {
+        /// Number of distinct users
+        users: usize,
+    },
+    /// Periodic sensor readings cycling through a fleet of devices
+    IotMetrics {
+        /// Number of distinct devices
+        devices: usize,
+    },
+    /// API responses with nested objects and variable-length item arrays
+    NestedApi {
+        /// Maximum items per response
+        max_items: usize,
+    },
+    /// Many optional columns, each present in a fraction of records
+    SparseWide {
+        /// Number of optional columns
+        fields: usize,
+        /// Percentage of records (0-100) in which each column is present
+        fill_percent: u8,
+    },
+}
+
+impl WorkloadProfile {
+    /// Web logs for 1,000 users
+    pub fn web_logs() -> Self {
+        Self::WebLogs { users: 1_000 }
+    }
+
+    /// IoT metrics for 500 devices
+    pub fn iot_metrics() -> Self {
+        Self::IotMetrics { devices: 500 }
+    }
+
+    /// Nested API responses with up to 5 items
+    pub fn nested_api() -> Self {
+        Self::NestedApi { max_items: 5 }
+    }
+
+    /// 200 optional columns, each filled in 5% of records
+    pub fn sparse_wide() -> Self {
+        Self::SparseWide {
+            fields: 200,
+            fill_percent: 5,
+        }
+    }
+
+    /// Short name for reports and benchmark ids
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::WebLogs { .. } => "web_logs",
+            Self::IotMetrics { .. } => "iot_metrics",
+            Self::NestedApi { .. } => "nested_api",
+            Self::SparseWide { .. } => "sparse_wide",
+        }
+    }
+}
+
+/// Endless, seeded record iterator for a [`WorkloadProfile`]
+///
+/// Use `take(n)` to bound the stream.
+#[derive(Debug, Clone)]
+pub struct Workload {
+    profile: WorkloadProfile,
+    rng: SplitMix64,
+    index: u64,
+}
+
+impl Workload {
+    /// Create a workload; equal profiles and seeds yield equal records
+    pub fn new(profile: WorkloadProfile, seed: u64) -> Self {
+        Self {
+            profile,
+            rng: SplitMix64::new(seed),
+            index: 0,
+        }
+    }
+
+    /// Profile this workload generates
+    pub fn profile(&self) -> WorkloadProfile {
+        self.profile
+    }
+
+    fn web_log(&mut self, users: usize) -> Value {
+        const METHODS: &[&str] = &[
+            "GET", "GET", "GET", "GET", "GET", "GET", "POST", "POST", "PUT", "DELETE",
+        ];
+        const RESOURCES: &[&str] = &["users", "orders", "products", "carts", "search"];
+        const AGENTS: &[&str] = &[
+            "Mozilla/5.0 (X11; Linux x86_64)",
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_0)",
+            "curl/8.4.0",
+            "okhttp/4.12.0",
+        ];
+
+        let rng = &mut self.rng;
+        let status = match rng.below(100) {
+            0..=84 => 200,
+            85..=91 => 304,
+            92..=96 => 404,
+            _ => 500,
+        };
+        let resource = rng.pick(RESOURCES);
+        json!({
+            "ts": BASE_TIMESTAMP + self.index as i64 / 4,
+            "method": rng.pick(METHODS),
+            "path": format!("/api/v1/{}/{}", resource, rng.below(10_000)),
+            "status": status,
+            "latency_ms": cents(5.0 + rng.unit() * rng.unit() * 900.0),
+            "bytes": if status == 304 { 0 } else { 200 + rng.below(50_000) },
+            "user": format!("user_{}", rng.skewed(users.max(1) as u64)),
+            "ip": format!("10.{}.{}.{}", rng.below(4), rng.below(256), rng.below(256)),
+            "user_agent": rng.pick(AGENTS),
+        })
+    }
+
+    fn iot_metric(&mut self, devices: usize) -> Value {
+        let devices = devices.max(1) as u64;
+        let device = self.index % devices;
+        let round = self.index / devices;
+        let rng = &mut self.rng;
+        let battery = 100 - ((round + device) % 100) as i64;
+
+        let mut record = Map::new();
+        record.insert("device_id".into(), json!(format!("dev-{:05}", device)));
+        record.insert("ts".into(), json!(BASE_TIMESTAMP + (round * 10) as i64));
+        record.insert(
+            "temperature".into(),
+            json!(cents(18.0 + (device % 10) as f64 + rng.unit() * 2.0)),
+        );
+        record.insert("humidity".into(), json!(cents(30.0 + rng.unit() * 40.0)));
+        record.insert("battery".into(), json!(battery));
+        record.insert(
+            "status".into(),
+            json!(if battery < 15 { "low_battery" } else { "ok" }),
+        );
+        if rng.chance(1) {
+            record.insert(
+                "error_code".into(),
+                json!(rng.pick(&["E_TIMEOUT", "E_SENSOR"])),
+            );
+        }
+        Value::Object(record)
+    }
+
+    fn nested_api(&mut self, max_items: usize) -> Value {
+        const ENDPOINTS: &[&str] = &["/v2/catalog", "/v2/recommendations", "/v2/cart"];
+        const TAGS: &[&str] = &["new", "sale", "popular", "limited", "eco"];
+
+        let rng = &mut self.rng;
+        let item_count = rng.below(max_items as u64 + 1);
+        let items: Vec<Value> = (0..item_count)
+            .map(|_| {
+                let tag_count = rng.below(3);
+                json!({
+                    "id": rng.below(1_000_000),
+                    "name": format!("item-{}", rng.below(5_000)),
+                    "price": cents(1.0 + rng.unit() * 250.0),
+                    "in_stock": rng.chance(80),
+                    "tags": (0..tag_count).map(|_| rng.pick(TAGS)).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        let cursor = if rng.chance(30) {
+            Value::Null
+        } else {
+            json!(format!("{:016x}", rng.next_u64()))
+        };
+
+        json!({
+            "request_id": format!("{:016x}", rng.next_u64()),
+            "endpoint": rng.pick(ENDPOINTS),
+            "ts": BASE_TIMESTAMP + self.index as i64,
+            "response": {
+                "status": if rng.chance(97) { "ok" } else { "error" },
+                "data": {
+                    "items": items,
+                    "page": {"size": max_items, "count": item_count, "next": cursor},
+                },
+            },
+            "meta": {
+                "region": rng.pick(&["us-east", "eu-west", "ap-south"]),
+                "cached": rng.chance(40),
+            },
+        })
+    }
+
+    fn sparse_wide(&mut self, fields: usize, fill_percent: u8) -> Value {
+        let rng = &mut self.rng;
+        let mut record = Map::new();
+        record.insert("id".into(), json!(self.index));
+        for column in 0..fields {
+            if !rng.chance(fill_percent as u64) {
+                continue;
+            }
+            // Column types are fixed by position so each column keeps one type.
+            let value = match column % 4 {
+                0 => json!(rng.below(1_000) as i64 - 500),
+                1 => json!(format!("v{}", rng.below(50))),
+                2 => json!(rng.chance(50)),
+                _ => json!(cents(rng.unit() * 100.0)),
+            };
+            record.insert(format!("f{:04}", column), value);
+        }
+        Value::Object(record)
+    }
+}
+
+impl Iterator for Workload {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        let record = match self.profile {
+            WorkloadProfile::WebLogs { users } => self.web_log(users),
+            WorkloadProfile::IotMetrics { devices } => self.iot_metric(devices),
+            WorkloadProfile::NestedApi { max_items } => self.nested_api(max_items),
+            WorkloadProfile::SparseWide {
+                fields,
+                fill_percent,
+            } => self.sparse_wide(fields, fill_percent),
+        };
+        self.index += 1;
+        Some(record)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+/// Round to two fractional digits so decimals look like measured values
+fn cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// SplitMix64: tiny, fast and stable across platforms and crate versions
+#[derive(Debug, Clone)]
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..bound` (`bound` must be non-zero)
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// Uniform value in `[0, 1)`
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// True with probability `percent`/100
+    fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    /// Value in `0..bound` skewed toward small numbers
+    fn skewed(&mut self, bound: u64) -> u64 {
+        self.below(bound).min(self.below(bound))
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len() as u64) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILES: [WorkloadProfile; 4] = [
+        WorkloadProfile::WebLogs { users: 50 },
+        WorkloadProfile::IotMetrics { devices: 20 },
+        WorkloadProfile::NestedApi { max_items: 4 },
+        WorkloadProfile::SparseWide {
+            fields: 40,
+            fill_percent: 10,
+        },
+    ];
+
+    #[test]
+    fn test_workloads_are_deterministic_per_seed() {
+        for profile in PROFILES {
+            let first: Vec<Value> = Workload::new(profile, 7).take(200).collect();
+            let second: Vec<Value> = Workload::new(profile, 7).take(200).collect();
+            let other: Vec<Value> = Workload::new(profile, 8).take(200).collect();
+            assert_eq!(first, second, "{} not deterministic", profile.name());
+            assert_ne!(first, other, "{} ignores the seed", profile.name());
+            assert!(first.iter().all(Value::is_object));
+        }
+    }
+
+    #[test]
+    fn test_workload_profiles_respect_parameters() {
+        let users: std::collections::HashSet<String> =
+            Workload::new(WorkloadProfile::WebLogs { users: 10 }, 1)
+                .take(1_000)
+                .map(|r| r["user"].as_str().unwrap().to_string())
+                .collect();
+        assert!(users.len() <= 10);
+
+        let devices: std::collections::HashSet<String> =
+            Workload::new(WorkloadProfile::IotMetrics { devices: 3 }, 1)
+                .take(30)
+                .map(|r| r["device_id"].as_str().unwrap().to_string())
+                .collect();
+        assert_eq!(devices.len(), 3);
+
+        for record in Workload::new(WorkloadProfile::NestedApi { max_items: 2 }, 1).take(100) {
+            assert!(
+                record["response"]["data"]["items"]
+                    .as_array()
+                    .unwrap()
+                    .len()
+                    <= 2
+            );
+        }
+
+        let sparse = WorkloadProfile::SparseWide {
+            fields: 100,
+            fill_percent: 0,
+        };
+        for record in Workload::new(sparse, 1).take(10) {
+            assert_eq!(record.as_object().unwrap().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_workload_streams_lazily() {
+        let mut workload = Workload::new(WorkloadProfile::web_logs(), 42);
+        let record = workload.nth(100_000).unwrap();
+        assert!(record["ts"].as_i64().unwrap() > BASE_TIMESTAMP);
+    }
+}